@@ -0,0 +1,71 @@
+//! Cooperative cancellation of a compile.
+//!
+//! An editor that starts analysing a file and then sees a newer edit wants
+//! the old analysis to stop.  It hands a [`CancellationToken`] to the
+//! pipeline and keeps a clone; calling [`CancellationToken::cancel`] on the
+//! clone, from any thread, makes the pipeline give up at its next safe
+//! point — between tokens in the lexer, between passes in semantic
+//! analysis and codegen — and return [`Cancelled`].
+//!
+//! Work is never interrupted half way through a pass, so a cancelled
+//! compile leaves nothing behind that a later compile could trip over.
+
+use std::fmt;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// A shared flag asking a compile to stop.  Clones share the flag.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    flag: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        CancellationToken::default()
+    }
+
+    /// Ask every holder of this token to stop.
+    pub fn cancel(&self) {
+        self.flag.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.flag.load(Ordering::Relaxed)
+    }
+
+    /// `Err(Cancelled)` once [`cancel`](CancellationToken::cancel) has been
+    /// called; use with `?` at a safe point.
+    pub fn check(&self) -> Result<(), Cancelled> {
+        if self.is_cancelled() { Err(Cancelled) } else { Ok(()) }
+    }
+}
+
+/// A compile stopped because its [`CancellationToken`] was cancelled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cancelled;
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "analysis cancelled")
+    }
+}
+
+impl std::error::Error for Cancelled {}
+
+// ─── Tests ───────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clones_share_the_flag_across_threads() {
+        let token = CancellationToken::new();
+        assert_eq!(token.check(), Ok(()));
+        let other = token.clone();
+        std::thread::spawn(move || other.cancel()).join().unwrap();
+        assert!(token.is_cancelled());
+        assert_eq!(token.check(), Err(Cancelled));
+    }
+}
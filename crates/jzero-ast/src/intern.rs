@@ -0,0 +1,162 @@
+//! Hash-consing: structurally identical subtrees stored once.
+//!
+//! A [`Tree`] owns its children, so a generated input that repeats the
+//! same expression a thousand times holds a thousand copies of it.  An
+//! [`Interner`] is the optional shared form: each distinct subtree is
+//! stored once as a [`SharedNode`] whose children are [`NodeId`]s, and
+//! interning a tree returns the id of its root.  The parser's
+//! hash-consing mode, `jzero_parser::parse_tree_interned`, builds trees
+//! this way.
+//!
+//! Structure is the rule, the leaf token and the children — line numbers
+//! and semantic attributes are left out, so `1 + 2` on line 3 and on line
+//! 40 share storage.  Within one interner two subtrees are structurally
+//! equal exactly when their ids are, which makes the id a ready-made
+//! structural hash and lets a diff skip identical subtrees in O(1).
+
+use std::collections::HashMap;
+
+use crate::tree::Tree;
+
+/// A subtree in an [`Interner`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NodeId(u32);
+
+impl NodeId {
+    /// Position in [`Interner::nodes`].
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// One distinct subtree: a [`Tree`] node without line numbers or
+/// attributes, with its children interned.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SharedNode {
+    pub sym: String,
+    pub rule: i32,
+    /// `(category, text, sval)` for a leaf.
+    pub leaf: Option<(String, String, Option<String>)>,
+    pub kids: Vec<NodeId>,
+}
+
+/// The table of distinct subtrees.
+#[derive(Debug, Clone, Default)]
+pub struct Interner {
+    nodes: Vec<SharedNode>,
+    index: HashMap<SharedNode, NodeId>,
+    /// Nodes passed to [`Interner::intern`], shared or not.
+    seen: usize,
+}
+
+impl Interner {
+    pub fn new() -> Self {
+        Interner::default()
+    }
+
+    /// Intern `tree` and all its subtrees, returning the id of its root.
+    pub fn intern(&mut self, tree: &Tree) -> NodeId {
+        let kids = tree.kids.iter().map(|k| self.intern(k)).collect();
+        let node = SharedNode {
+            sym: tree.sym.clone(),
+            rule: tree.rule,
+            leaf: tree.tok.as_ref().map(|t| (t.category.clone(), t.text.clone(), t.sval.clone())),
+            kids,
+        };
+        self.seen += 1;
+        if let Some(&id) = self.index.get(&node) {
+            return id;
+        }
+        let id = NodeId(self.nodes.len() as u32);
+        self.nodes.push(node.clone());
+        self.index.insert(node, id);
+        id
+    }
+
+    /// The subtree `id`.
+    pub fn node(&self, id: NodeId) -> &SharedNode {
+        &self.nodes[id.index()]
+    }
+
+    /// Every distinct subtree, children before their parents.
+    pub fn nodes(&self) -> &[SharedNode] {
+        &self.nodes
+    }
+
+    /// Number of distinct subtrees stored.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Number of tree nodes interned so far, counting repeats; compare
+    /// with [`Interner::len`] for how much sharing saved.
+    pub fn interned(&self) -> usize {
+        self.seen
+    }
+
+    /// Rebuild subtree `id` as an owned [`Tree`].  Line numbers are 0 and
+    /// node ids are unnumbered, as they were not kept.
+    pub fn to_tree(&self, id: NodeId) -> Tree {
+        let node = self.node(id);
+        match &node.leaf {
+            Some((category, text, sval)) => {
+                let mut leaf = Tree::leaf(category, text, 0);
+                leaf.sym = node.sym.clone();
+                match sval {
+                    Some(s) => leaf.with_sval(s.clone()),
+                    None => leaf,
+                }
+            }
+            None => {
+                let kids = node.kids.iter().map(|&k| self.to_tree(k)).collect();
+                Tree::new(&node.sym, node.rule, kids)
+            }
+        }
+    }
+}
+
+// ─── Tests ───────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sum(a: &str, b: &str, line: usize) -> Tree {
+        Tree::new("AddExpr", 0, vec![
+            Tree::leaf("INTLIT", a, line),
+            Tree::leaf("PLUS", "+", line),
+            Tree::leaf("INTLIT", b, line),
+        ])
+    }
+
+    #[test]
+    fn identical_subtrees_share_an_id() {
+        let mut interner = Interner::new();
+        let a = interner.intern(&sum("1", "2", 3));
+        let b = interner.intern(&sum("1", "2", 40));
+        let c = interner.intern(&sum("2", "1", 3));
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        // 1, +, 2, 1+2, 2+1
+        assert_eq!((interner.len(), interner.interned()), (5, 12));
+        assert_eq!(interner.node(c).kids[0], interner.node(a).kids[2]);
+    }
+
+    #[test]
+    fn trees_rebuild_without_line_numbers() {
+        let mut interner = Interner::new();
+        let tree = Tree::new("Block", 0, vec![
+            sum("1", "2", 3),
+            Tree::leaf("STRINGLIT", "\"a\"", 4).with_sval("a".into()),
+        ]);
+        let id = interner.intern(&tree);
+        let rebuilt = interner.to_tree(id);
+        assert_eq!(rebuilt.node_count(), tree.node_count());
+        assert_eq!(rebuilt.kids[1].tok.as_ref().unwrap().sval.as_deref(), Some("a"));
+        assert_eq!(interner.intern(&rebuilt), id);
+    }
+}
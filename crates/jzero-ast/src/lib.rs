@@ -1,3 +1,9 @@
+pub mod cancel;
+pub mod intern;
+pub mod production;
 pub mod tree;
 
-pub use tree::Tree;
\ No newline at end of file
+pub use cancel::{CancellationToken, Cancelled};
+pub use intern::{Interner, NodeId, SharedNode};
+pub use production::ProductionInfo;
+pub use tree::{InvalidTree, Tree};
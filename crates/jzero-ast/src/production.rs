@@ -0,0 +1,249 @@
+//! What each production alternative is — the meaning of [`Tree::rule`].
+//!
+//! The parser tells the alternatives of a production apart by number:
+//! `MethodCall#2` is `base.method(args)`, `FieldDecl#2` a `private` field.
+//! [`PRODUCTIONS`] names every alternative the grammar builds and the role
+//! of each of its children, so tools can say "dotted call" and "receiver"
+//! instead.  The parser's tests check the table against `jzero.lalrpop`:
+//! an alternative missing here, or listed here but never built, fails them.
+//!
+//! ```
+//! use jzero_ast::Tree;
+//!
+//! let call = Tree::new("MethodCall", 2, vec![
+//!     Tree::leaf("IDENTIFIER", "out", 1),
+//!     Tree::leaf("IDENTIFIER", "println", 1),
+//!     Tree::leaf("INTLIT", "1", 1),
+//! ]);
+//! let info = call.production_info().unwrap();
+//! assert_eq!(info.to_string(), "MethodCall (dotted call)");
+//! assert_eq!(info.role(0), Some("receiver"));
+//! assert_eq!(info.role(2), Some("argument"));
+//! ```
+
+use std::fmt;
+
+use crate::tree::Tree;
+
+/// One alternative of a production.
+///
+/// A role is written `role`, `role...` for one that repeats, or either
+/// followed by `:Kind|Kind` — the syms a child in that role may have.
+/// Without kinds any child will do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProductionInfo {
+    pub sym: &'static str,
+    pub rule: i32,
+    /// What the alternative is, e.g. `dotted call`.
+    pub name: &'static str,
+    /// The role of each child, in order.  A last role ending in `...`
+    /// covers any number of children, none included.
+    pub roles: &'static [&'static str],
+}
+
+impl ProductionInfo {
+    /// The role of child `kid` as written in [`ProductionInfo::roles`].
+    fn role_spec(&self, kid: usize) -> Option<&'static str> {
+        match self.roles.get(kid) {
+            Some(role) => Some(role),
+            None => self.roles.last().copied().filter(|last| is_repeated(last)),
+        }
+    }
+
+    /// The role of child `kid`; `None` past the last child the
+    /// alternative has.
+    pub fn role(&self, kid: usize) -> Option<&'static str> {
+        self.role_spec(kid).map(|r| role_name(r).trim_end_matches("..."))
+    }
+
+    /// The syms child `kid` may have; `None` if any will do.
+    pub fn kinds(&self, kid: usize) -> Option<Vec<&'static str>> {
+        let (_, kinds) = self.role_spec(kid)?.split_once(':')?;
+        Some(kinds.split('|').collect())
+    }
+
+    /// Whether a child `sym` may be child `kid`.
+    pub fn accepts(&self, kid: usize, sym: &str) -> bool {
+        self.role_spec(kid).is_some() && self.kinds(kid).is_none_or(|k| k.contains(&sym))
+    }
+
+    /// Whether the last role repeats.
+    pub fn is_variadic(&self) -> bool {
+        self.roles.last().is_some_and(|r| is_repeated(r))
+    }
+
+    /// How many children the alternative has at least; exactly, unless
+    /// it [`is_variadic`](ProductionInfo::is_variadic).
+    pub fn min_kids(&self) -> usize {
+        self.roles.len() - usize::from(self.is_variadic())
+    }
+}
+
+fn role_name(spec: &str) -> &str {
+    spec.split_once(':').map_or(spec, |(name, _)| name)
+}
+
+fn is_repeated(spec: &str) -> bool {
+    role_name(spec).ends_with("...")
+}
+
+impl fmt::Display for ProductionInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.sym, self.name)
+    }
+}
+
+const fn p(sym: &'static str, rule: i32, name: &'static str, roles: &'static [&'static str]) -> ProductionInfo {
+    ProductionInfo { sym, rule, name, roles }
+}
+
+/// Every alternative the grammar builds, by production.
+pub const PRODUCTIONS: &[ProductionInfo] = &[
+    // The imports, then the class or interface of the file.
+    p("CompilationUnit", 0, "file with imports", &["import:ImportDecl", "declaration...:ImportDecl|ClassDecl|InterfaceDecl"]),
+    p("ImportDecl", 0, "import", &["name:IDENTIFIER", "name...:IDENTIFIER"]),
+    p("ImportDecl", 1, "import on demand", &["name:IDENTIFIER", "name...:IDENTIFIER"]),
+    p("ClassDecl", 0, "class", &["name:IDENTIFIER", "member...:FieldDecl|MethodDecl|ConstructorDecl"]),
+    p("ClassDecl", 1, "subclass", &["name:IDENTIFIER", "superclass:IDENTIFIER", "member...:FieldDecl|MethodDecl|ConstructorDecl"]),
+    p("ClassDecl", 2, "implementing class", &["name:IDENTIFIER", "interfaces:InterfaceList", "member...:FieldDecl|MethodDecl|ConstructorDecl"]),
+    p("ClassDecl", 3, "implementing subclass", &["name:IDENTIFIER", "superclass:IDENTIFIER", "interfaces:InterfaceList", "member...:FieldDecl|MethodDecl|ConstructorDecl"]),
+    p("InterfaceList", 0, "implements", &["interface...:IDENTIFIER"]),
+    p("InterfaceDecl", 0, "interface", &["name:IDENTIFIER", "method...:AbstractMethodDecl"]),
+    p("AbstractMethodDecl", 0, "abstract method", &["header:MethodHeader"]),
+    // The rule of FieldDecl and MethodHeader is the access modifier.
+    p("FieldDecl", 0, "public field", &["type", "declarator:VarDeclarator", "declarator...:VarDeclarator"]),
+    p("FieldDecl", 1, "protected field", &["type", "declarator:VarDeclarator", "declarator...:VarDeclarator"]),
+    p("FieldDecl", 2, "private field", &["type", "declarator:VarDeclarator", "declarator...:VarDeclarator"]),
+    // 3 more for a `static final` one.
+    p("FieldDecl", 3, "public constant", &["type", "declarator:VarDeclarator", "declarator...:VarDeclarator"]),
+    p("FieldDecl", 4, "protected constant", &["type", "declarator:VarDeclarator", "declarator...:VarDeclarator"]),
+    p("FieldDecl", 5, "private constant", &["type", "declarator:VarDeclarator", "declarator...:VarDeclarator"]),
+    // 6 more for a `static` one.
+    p("FieldDecl", 6, "public static field", &["type", "declarator:VarDeclarator", "declarator...:VarDeclarator"]),
+    p("FieldDecl", 7, "protected static field", &["type", "declarator:VarDeclarator", "declarator...:VarDeclarator"]),
+    p("FieldDecl", 8, "private static field", &["type", "declarator:VarDeclarator", "declarator...:VarDeclarator"]),
+    // 9 more for a `final` one.
+    p("FieldDecl", 9, "public final field", &["type", "declarator:VarDeclarator", "declarator...:VarDeclarator"]),
+    p("FieldDecl", 10, "protected final field", &["type", "declarator:VarDeclarator", "declarator...:VarDeclarator"]),
+    p("FieldDecl", 11, "private final field", &["type", "declarator:VarDeclarator", "declarator...:VarDeclarator"]),
+    p("VarDeclarator", 0, "name", &["name:IDENTIFIER"]),
+    p("VarDeclarator", 1, "array declarator", &["declarator:VarDeclarator"]),
+    p("VarDeclarator", 2, "initialized declarator", &["declarator:VarDeclarator", "initializer"]),
+    p("MethodDecl", 0, "method", &["header:MethodHeader", "body:Block"]),
+    p("MethodHeader", 0, "public method header", &["return type", "declarator:MethodDeclarator"]),
+    p("MethodHeader", 1, "protected method header", &["return type", "declarator:MethodDeclarator"]),
+    p("MethodHeader", 2, "private method header", &["return type", "declarator:MethodDeclarator"]),
+    // 3 more for an instance method, without `static`.
+    p("MethodHeader", 3, "public instance method header", &["return type", "declarator:MethodDeclarator"]),
+    p("MethodHeader", 4, "protected instance method header", &["return type", "declarator:MethodDeclarator"]),
+    p("MethodHeader", 5, "private instance method header", &["return type", "declarator:MethodDeclarator"]),
+    p("MethodDeclarator", 0, "method declarator", &["name:IDENTIFIER", "parameter...:FormalParm"]),
+    p("FormalParm", 0, "parameter", &["type", "declarator:VarDeclarator"]),
+    p("ConstructorDecl", 0, "constructor", &["declarator:ConstructorDeclarator", "body:Block"]),
+    p("ConstructorDeclarator", 0, "constructor declarator", &["name:IDENTIFIER", "parameter...:FormalParm"]),
+    p("Block", 0, "block", &["statement..."]),
+    p("LocalVarDecl", 0, "local variable", &["type", "declarator:VarDeclarator", "declarator...:VarDeclarator"]),
+    p("LocalVarDecl", 1, "local array", &["type:ArrayType", "declarator:VarDeclarator", "declarator...:VarDeclarator"]),
+    p("LocalVarDecl", 2, "inferred local", &["type:VAR", "declarator:VarDeclarator"]),
+    p("LocalVarDecl", 3, "final local", &["type", "declarator:VarDeclarator", "declarator...:VarDeclarator"]),
+    p("ArrayType", 0, "array type", &["element type"]),
+    p("EmptyStmt", 0, "empty statement", &[]),
+    p("IfThenStmt", 0, "if", &["condition", "then"]),
+    p("IfThenElseStmt", 0, "if-else", &["condition", "then", "else"]),
+    p("WhileStmt", 0, "while", &["condition", "body"]),
+    p("ForStmt", 0, "for", &["init", "condition", "update", "body:Block"]),
+    p("StmtExprList", 0, "expression list", &["list", "expression"]),
+    p("BreakStmt", 0, "break", &["label:EmptyLabel"]),
+    p("BreakStmt", 1, "labeled break", &["label:IDENTIFIER"]),
+    p("ReturnStmt", 0, "return value", &["value"]),
+    p("ReturnStmt", 1, "return", &["value:EmptyExpr"]),
+    p("TryStmt", 0, "try-catch", &["body:Block", "catch:CatchClause", "catch...:CatchClause"]),
+    // The finally block is kid 1, ahead of the catch clauses.
+    p("TryStmt", 1, "try-finally", &["body:Block", "finally:Block", "catch...:CatchClause"]),
+    p("CatchClause", 0, "catch", &["parameter:FormalParm", "body:Block"]),
+    p("ThrowStmt", 0, "throw", &["value"]),
+    // The body is kid 0, ahead of the labels; `default` is a DefaultLabel leaf.
+    p("SwitchStmt", 0, "switch", &["selector", "case...:CaseClause"]),
+    p("CaseClause", 0, "case group", &["body:Block", "label", "label..."]),
+    p("ArrayCreation", 0, "new array", &["element type", "size"]),
+    p("ArrayInit", 0, "array initializer", &["element..."]),
+    p("InstanceCreation", 0, "new object", &["class:IDENTIFIER", "argument..."]),
+    p("MethodCall", 0, "call", &["method", "argument..."]),
+    p("MethodCall", 1, "brace call", &["method", "argument..."]),
+    p("MethodCall", 2, "dotted call", &["receiver", "method:IDENTIFIER", "argument..."]),
+    p("MethodCall", 3, "dotted brace call", &["receiver", "method:IDENTIFIER", "argument..."]),
+    p("FieldAccess", 0, "field access", &["object", "field:IDENTIFIER"]),
+    p("ArrayAccess", 0, "array element", &["array", "index"]),
+    p("Assignment", 0, "assignment", &["target", "operator", "value"]),
+    p("UnaryMinus", 0, "negation", &["operand"]),
+    p("UnaryNot", 0, "not", &["operand"]),
+    p("CastExpr", 0, "cast", &["type", "operand"]),
+    p("MulExpr", 0, "multiplication", &["left", "operator:STAR", "right"]),
+    p("MulExpr", 1, "division", &["left", "operator:SLASH", "right"]),
+    p("MulExpr", 2, "remainder", &["left", "operator:PERCENT", "right"]),
+    p("AddExpr", 0, "addition", &["left", "operator:PLUS", "right"]),
+    p("AddExpr", 1, "subtraction", &["left", "operator:MINUS", "right"]),
+    p("RelExpr", 0, "comparison", &["left", "operator:LESS|GREATER|LESSEQUAL|GREATEREQUAL", "right"]),
+    p("InstanceOfExpr", 0, "instanceof", &["operand", "class:IDENTIFIER"]),
+    p("EqExpr", 0, "equality", &["left", "operator:EQUALEQUAL", "right"]),
+    p("EqExpr", 1, "inequality", &["left", "operator:NOTEQUAL", "right"]),
+    p("CondAndExpr", 0, "and", &["left", "operator:LOGICALAND", "right"]),
+    p("CondOrExpr", 0, "or", &["left", "operator:LOGICALOR", "right"]),
+];
+
+/// The alternative `rule` of production `sym`.
+pub fn production_info(sym: &str, rule: i32) -> Option<&'static ProductionInfo> {
+    PRODUCTIONS.iter().find(|p| p.sym == sym && p.rule == rule)
+}
+
+impl Tree {
+    /// What alternative built this node; `None` for a leaf, or a node no
+    /// grammar alternative builds.
+    pub fn production_info(&self) -> Option<&'static ProductionInfo> {
+        if self.is_leaf() { return None; }
+        production_info(&self.sym, self.rule)
+    }
+
+    /// The role of child `kid` in this node, e.g. `condition`.
+    pub fn kid_role(&self, kid: usize) -> Option<&'static str> {
+        self.production_info()?.role(kid)
+    }
+}
+
+// ─── Tests ───────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn each_alternative_is_listed_once() {
+        for (i, a) in PRODUCTIONS.iter().enumerate() {
+            assert!(PRODUCTIONS[i + 1..].iter().all(|b| (a.sym, a.rule) != (b.sym, b.rule)), "{}#{}", a.sym, a.rule);
+            let repeats = a.roles.iter().filter(|r| is_repeated(r)).count();
+            assert!(repeats == 0 || (repeats == 1 && a.is_variadic()), "{}#{}", a.sym, a.rule);
+        }
+    }
+
+    #[test]
+    fn roles_of_fixed_and_variadic_alternatives() {
+        let if_else = production_info("IfThenElseStmt", 0).unwrap();
+        assert_eq!((0..4).map(|i| if_else.role(i)).collect::<Vec<_>>(),
+            [Some("condition"), Some("then"), Some("else"), None]);
+        let block = production_info("Block", 0).unwrap();
+        assert_eq!(block.role(7), Some("statement"));
+        assert_eq!(production_info("FieldDecl", 2).unwrap().name, "private field");
+        assert!(Tree::leaf("INTLIT", "1", 1).production_info().is_none());
+        assert!(production_info("MethodCall", 4).is_none());
+    }
+
+    #[test]
+    fn kinds_limit_what_a_child_may_be() {
+        let field = production_info("FieldDecl", 0).unwrap();
+        assert_eq!((field.min_kids(), field.role(3)), (2, Some("declarator")));
+        assert!(field.accepts(0, "ArrayType") && field.accepts(5, "VarDeclarator"));
+        assert!(!field.accepts(1, "IDENTIFIER"));
+        assert_eq!(production_info("RelExpr", 0).unwrap().kinds(1).unwrap().len(), 4);
+        assert!(!production_info("ReturnStmt", 1).unwrap().accepts(1, "EmptyExpr"));
+    }
+}
@@ -1,22 +1,9 @@
 use std::cell::RefCell;
 use std::fmt;
 use std::rc::Rc;
-use std::sync::atomic::{AtomicU32, Ordering};
 
 use jzero_symtab::{SymTab, TypeInfo};
 
-/// Global counter for unique node IDs (used in DOT output).
-static NEXT_ID: AtomicU32 = AtomicU32::new(1);
-
-/// Reset the ID counter (useful for deterministic test output).
-pub fn reset_ids() {
-    NEXT_ID.store(1, Ordering::SeqCst);
-}
-
-fn next_id() -> u32 {
-    NEXT_ID.fetch_add(1, Ordering::SeqCst)
-}
-
 // ─── Leaf token info ─────────────────────────────────────
 
 /// Token information stored in leaf nodes.
@@ -28,8 +15,30 @@ pub struct LeafToken {
     pub text: String,
     /// Source line number (1-based)
     pub lineno: usize,
+    /// The value of a string literal, escapes decoded — the book's `sval`.
+    pub sval: Option<String>,
+}
+
+// ─── Invalid tree ────────────────────────────────────────
+
+/// A node that breaks an invariant [`Tree::validate`] checks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidTree {
+    pub node: u32,
+    pub sym: String,
+    /// Line of the node's first token; 0 if it has none.
+    pub lineno: usize,
+    pub problem: String,
 }
 
+impl fmt::Display for InvalidTree {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "node {} ({}): {}", self.node, self.sym, self.problem)
+    }
+}
+
+impl std::error::Error for InvalidTree {}
+
 // ─── Tree node ───────────────────────────────────────────
 
 /// A syntax tree node.
@@ -52,11 +61,13 @@ pub struct LeafToken {
 ///   Propagated top-down by `jzero-semantic`; `None` until semantic analysis runs.
 #[derive(Debug, Clone)]
 pub struct Tree {
-    /// Unique node ID for DOT output.
+    /// Node ID, unique within its tree once [`Tree::number_nodes`] has
+    /// run; 0 before that.  Used for DOT output and by codegen.
     pub id: u32,
     /// Production rule name (internal) or token category (leaf).
     pub sym: String,
-    /// Which alternative of the rule (0-based). -1 for leaves.
+    /// Which alternative of the rule (0-based). -1 for leaves.  See
+    /// [`Tree::production_info`] for what each alternative is.
     pub rule: i32,
     /// Number of children.
     pub nkids: usize,
@@ -83,7 +94,7 @@ impl Tree {
     /// Create a leaf node from a terminal symbol.
     pub fn leaf(category: &str, text: &str, lineno: usize) -> Self {
         Tree {
-            id: next_id(),
+            id: 0,
             sym: category.to_string(),
             rule: -1,
             nkids: 0,
@@ -91,6 +102,7 @@ impl Tree {
                 category: category.to_string(),
                 text: text.to_string(),
                 lineno,
+                sval: None,
             }),
             kids: Vec::new(),
             is_const: None,
@@ -99,6 +111,28 @@ impl Tree {
         }
     }
 
+    /// Create a placeholder for a construct the source leaves out, such as
+    /// the condition of `for (;;)` or the value of `return;`.
+    ///
+    /// It is a leaf with no text, on the line where the construct would
+    /// have been, and stands in the construct's place among its parent's
+    /// kids, so the parent's other kids keep their positions.  `sym` names
+    /// what is missing, e.g. `EmptyExpr`.
+    pub fn empty(sym: &str, lineno: usize) -> Self {
+        Tree::leaf(sym, "", lineno)
+    }
+
+    /// Returns true if this is a placeholder made by [`Tree::empty`].
+    pub fn is_empty_placeholder(&self) -> bool {
+        self.tok.as_ref().is_some_and(|t| t.text.is_empty())
+    }
+
+    /// Set the decoded value of a literal leaf.
+    pub fn with_sval(mut self, sval: String) -> Self {
+        if let Some(tok) = &mut self.tok { tok.sval = Some(sval); }
+        self
+    }
+
     /// Create an internal node from a production rule.
     ///
     /// `sym` is the production rule name (e.g. "ClassDecl", "MethodCall").
@@ -107,7 +141,7 @@ impl Tree {
     pub fn new(sym: &str, rule: i32, kids: Vec<Tree>) -> Self {
         let nkids = kids.len();
         Tree {
-            id: next_id(),
+            id: 0,
             sym: sym.to_string(),
             rule,
             nkids,
@@ -119,11 +153,150 @@ impl Tree {
         }
     }
 
+    /// Number the nodes 1, 2, 3, ... in post-order — children before their
+    /// parent, in the order the parser builds them.  The parser numbers
+    /// every tree it returns; passes that add nodes renumber afterwards.
+    pub fn number_nodes(&mut self) {
+        let mut next = 1;
+        self.number_from(&mut next);
+    }
+
+    fn number_from(&mut self, next: &mut u32) {
+        for kid in &mut self.kids {
+            kid.number_from(next);
+        }
+        self.id = *next;
+        *next += 1;
+    }
+
+    /// Number of nodes in the tree, this one included.
+    pub fn node_count(&self) -> usize {
+        1 + self.kids.iter().map(Tree::node_count).sum::<usize>()
+    }
+
     /// Returns true if this is a leaf node.
     pub fn is_leaf(&self) -> bool {
         self.tok.is_some()
     }
 
+    // ─── Checked mutation ────────────────────────────────
+
+    /// Append `kid` as the last child.
+    ///
+    /// Like the other checked mutations it keeps `nkids` in step, gives
+    /// the new subtree this node's scope wherever it has none of its own,
+    /// and clears this node's `is_const` and `typ`, which were computed
+    /// from the old children.
+    ///
+    /// # Panics
+    /// If this node is a leaf.
+    pub fn push_kid(&mut self, mut kid: Tree) {
+        self.adopt(&mut kid);
+        self.kids.push(kid);
+        self.kids_changed();
+    }
+
+    /// Replace child `i` with `kid` and return the old child.
+    ///
+    /// # Panics
+    /// If this node is a leaf or `i` is out of bounds.
+    pub fn replace_kid(&mut self, i: usize, mut kid: Tree) -> Tree {
+        self.adopt(&mut kid);
+        let old = std::mem::replace(&mut self.kids[i], kid);
+        self.kids_changed();
+        old
+    }
+
+    /// Remove child `i` and return it.
+    ///
+    /// # Panics
+    /// If `i` is out of bounds.
+    pub fn remove_kid(&mut self, i: usize) -> Tree {
+        let old = self.kids.remove(i);
+        self.kids_changed();
+        old
+    }
+
+    fn adopt(&self, kid: &mut Tree) {
+        assert!(!self.is_leaf(), "cannot add a kid to leaf {}", self.sym);
+        if let Some(st) = &self.stab {
+            kid.inherit_stab(st);
+        }
+    }
+
+    fn inherit_stab(&mut self, st: &Rc<RefCell<SymTab>>) {
+        let st = self.stab.get_or_insert_with(|| Rc::clone(st)).clone();
+        for kid in &mut self.kids {
+            kid.inherit_stab(&st);
+        }
+    }
+
+    fn kids_changed(&mut self) {
+        self.nkids = self.kids.len();
+        self.is_const = None;
+        self.typ = None;
+    }
+
+    /// Check the invariants of every node: `nkids` counts `kids`; a leaf
+    /// has a token, rule -1 and no kids; an internal node has no token and
+    /// the number and kinds of kids its alternative allows (see
+    /// [`Tree::production_info`]).
+    ///
+    /// # Errors
+    /// Returns the first node, in pre-order, that breaks one.
+    pub fn validate(&self) -> Result<(), InvalidTree> {
+        self.validate_node()?;
+        self.kids.iter().try_for_each(Tree::validate)
+    }
+
+    /// [`Tree::validate`] for this node alone, not its descendants.
+    ///
+    /// # Errors
+    /// Returns what is wrong with the node.
+    pub fn validate_node(&self) -> Result<(), InvalidTree> {
+        let invalid = |problem: String| Err(InvalidTree {
+            node: self.id,
+            sym: self.sym.clone(),
+            lineno: self.first_lineno(),
+            problem,
+        });
+        if self.nkids != self.kids.len() {
+            return invalid(format!("nkids is {} but it has {} kids", self.nkids, self.kids.len()));
+        }
+        if self.is_leaf() {
+            if !self.kids.is_empty() { return invalid("leaf has kids".to_string()); }
+            if self.rule != -1 { return invalid(format!("leaf has rule {}", self.rule)); }
+            return Ok(());
+        }
+        if self.rule < 0 {
+            return invalid(format!("internal node has rule {}", self.rule));
+        }
+        if let Some(info) = self.production_info() {
+            let fixed = info.min_kids();
+            let fits = if info.is_variadic() { self.kids.len() >= fixed } else { self.kids.len() == fixed };
+            if !fits {
+                let at_least = if info.is_variadic() { "at least " } else { "" };
+                return invalid(format!("{} needs {}{} kids, has {}", info.name, at_least, fixed, self.kids.len()));
+            }
+            for (i, kid) in self.kids.iter().enumerate() {
+                if !info.accepts(i, &kid.sym) {
+                    let kinds = info.kinds(i).unwrap_or_default().join(" or ");
+                    return invalid(format!("{} of {} is {}, not {}",
+                        info.role(i).unwrap_or_default(), info.name, kid.sym, kinds));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Line of the first token under this node; 0 if there is none.
+    pub fn first_lineno(&self) -> usize {
+        match &self.tok {
+            Some(t) => t.lineno,
+            None => self.kids.iter().map(Tree::first_lineno).find(|&l| l > 0).unwrap_or(0),
+        }
+    }
+
     // ─── Semantic attribute helpers ───────────────────────
 
     /// Attach a symbol table to this node (sets the `stab` attribute).
@@ -231,6 +404,51 @@ impl Tree {
         }
         buf
     }
+
+    // ─── JSON output ─────────────────────────────────────
+
+    /// The tree as JSON: every node has `id`, `sym` and `rule`; leaves add
+    /// `category`, `text` and `lineno`; internal nodes add `kids`.
+    pub fn to_json(&self) -> String {
+        let mut buf = String::new();
+        self.json_into(&mut buf);
+        buf.push('\n');
+        buf
+    }
+
+    fn json_into(&self, buf: &mut String) {
+        buf.push_str(&format!("{{\"id\":{},\"sym\":{},\"rule\":{}", self.id, json_str(&self.sym), self.rule));
+        if let Some(ref tok) = self.tok {
+            buf.push_str(&format!(
+                ",\"category\":{},\"text\":{},\"lineno\":{}",
+                json_str(&tok.category), json_str(&tok.text), tok.lineno
+            ));
+        } else {
+            buf.push_str(",\"kids\":[");
+            for (i, kid) in self.kids.iter().enumerate() {
+                if i > 0 { buf.push(','); }
+                kid.json_into(buf);
+            }
+            buf.push(']');
+        }
+        buf.push('}');
+    }
+}
+
+/// A JSON string literal for `s`.
+fn json_str(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"'  => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
 }
 
 impl fmt::Display for Tree {
@@ -245,7 +463,6 @@ mod tests {
 
     #[test]
     fn test_leaf_node() {
-        reset_ids();
         let leaf = Tree::leaf("IDENTIFIER", "hello", 1);
         assert!(leaf.is_leaf());
         assert_eq!(leaf.nkids, 0);
@@ -257,7 +474,6 @@ mod tests {
 
     #[test]
     fn test_internal_node() {
-        reset_ids();
         let left  = Tree::leaf("IDENTIFIER", "x", 1);
         let op    = Tree::leaf("ASSIGN", "=", 1);
         let right = Tree::leaf("INTLIT", "42", 1);
@@ -268,9 +484,55 @@ mod tests {
         assert!(assign.stab.is_none());
     }
 
+    #[test]
+    fn checked_mutation_keeps_nkids_and_scope() {
+        let mut block = Tree::new("Block", 0, vec![]);
+        block.set_stab(SymTab::new("method", None).into_rc());
+        block.set_typ(TypeInfo::void());
+        block.push_kid(Tree::new("EmptyStmt", 0, vec![]));
+        block.push_kid(Tree::new("ReturnStmt", 0, vec![Tree::leaf("INTLIT", "1", 2)]));
+        assert_eq!(block.nkids, 2);
+        assert!(block.typ.is_none());
+        assert!(block.kids[1].kids[0].stab.as_ref().is_some_and(|st| st.borrow().scope == "method"));
+
+        let old = block.replace_kid(0, Tree::new("BreakStmt", 0, vec![Tree::empty("EmptyLabel", 3)]));
+        assert_eq!(old.sym, "EmptyStmt");
+        assert_eq!(block.remove_kid(1).sym, "ReturnStmt");
+        assert_eq!((block.nkids, block.kids[0].sym.as_str()), (1, "BreakStmt"));
+        assert!(block.validate().is_ok());
+    }
+
+    #[test]
+    fn validate_finds_broken_invariants() {
+        let mut assign = Tree::new("Assignment", 0, vec![
+            Tree::leaf("IDENTIFIER", "x", 1), Tree::leaf("ASSIGN", "=", 1), Tree::leaf("INTLIT", "1", 1),
+        ]);
+        assert!(assign.validate().is_ok());
+        assign.kids.pop();
+        assert_eq!(assign.validate().unwrap_err().problem, "nkids is 3 but it has 2 kids");
+        assign.nkids = 2;
+        assert_eq!(assign.validate().unwrap_err().to_string(), "node 0 (Assignment): assignment needs 3 kids, has 2");
+        let add = Tree::new("AddExpr", 0, vec![
+            Tree::leaf("IDENTIFIER", "x", 7), Tree::leaf("MINUS", "-", 7), Tree::leaf("INTLIT", "1", 7),
+        ]);
+        let err = add.validate().unwrap_err();
+        assert_eq!((err.lineno, err.problem.as_str()), (7, "operator of addition is MINUS, not PLUS"));
+        let mut leaf = Tree::leaf("INTLIT", "1", 1);
+        leaf.rule = 0;
+        assert_eq!(leaf.validate().unwrap_err().problem, "leaf has rule 0");
+    }
+
+    #[test]
+    fn placeholders_are_positioned_leaves() {
+        let empty = Tree::empty("EmptyExpr", 4);
+        assert!(empty.is_leaf() && empty.is_empty_placeholder());
+        assert_eq!((empty.sym.as_str(), empty.tok.as_ref().unwrap().lineno), ("EmptyExpr", 4));
+        assert!(!Tree::leaf("INTLIT", "0", 4).is_empty_placeholder());
+        assert!(Tree::new("ReturnStmt", 1, vec![empty]).validate().is_ok());
+    }
+
     #[test]
     fn test_single_child_passthrough() {
-        reset_ids();
         let leaf = Tree::leaf("INTLIT", "42", 1);
         assert!(leaf.is_leaf());
         assert_eq!(leaf.sym, "INTLIT");
@@ -278,7 +540,6 @@ mod tests {
 
     #[test]
     fn test_set_typ() {
-        reset_ids();
         let mut lit = Tree::leaf("INTLIT", "42", 1);
         lit.set_typ(TypeInfo::int());
         assert_eq!(lit.typ.as_ref().unwrap().basetype(), "int");
@@ -290,7 +551,6 @@ mod tests {
 
     #[test]
     fn test_set_const() {
-        reset_ids();
         let mut lit = Tree::leaf("INTLIT", "42", 1);
         lit.set_const(true);
         assert_eq!(lit.is_const, Some(true));
@@ -298,7 +558,6 @@ mod tests {
 
     #[test]
     fn test_set_stab() {
-        reset_ids();
         let st = SymTab::new("global", None).into_rc();
         let mut node = Tree::new("ClassDecl", 0, vec![]);
         node.set_stab(Rc::clone(&st));
@@ -308,10 +567,10 @@ mod tests {
 
     #[test]
     fn test_dot_output() {
-        reset_ids();
         let name = Tree::leaf("IDENTIFIER", "hello", 1);
         let body = Tree::new("ClassBody", 1, vec![]);
-        let class = Tree::new("ClassDecl", 0, vec![name, body]);
+        let mut class = Tree::new("ClassDecl", 0, vec![name, body]);
+        class.number_nodes();
 
         let dot = class.to_dot();
         assert!(dot.contains("digraph {"));
@@ -323,7 +582,6 @@ mod tests {
 
     #[test]
     fn test_text_output() {
-        reset_ids();
         let name = Tree::leaf("IDENTIFIER", "x", 1);
         let val  = Tree::leaf("INTLIT", "42", 1);
         let op   = Tree::leaf("ASSIGN", "=", 1);
@@ -337,10 +595,23 @@ mod tests {
 
     #[test]
     fn test_const_label_in_text_output() {
-        reset_ids();
         let mut node = Tree::new("AddExpr", 0, vec![]);
         node.set_const(true);
         let text = node.to_text(0);
         assert!(text.contains("[const]"));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_json_output() {
+        let name = Tree::leaf("IDENTIFIER", "x", 1);
+        let val  = Tree::leaf("STRINGLIT", "\"a\"", 2);
+        let mut assign = Tree::new("Assignment", 0, vec![name, val]);
+        assign.number_nodes();
+        assert_eq!(
+            assign.to_json(),
+            "{\"id\":3,\"sym\":\"Assignment\",\"rule\":0,\"kids\":[\
+             {\"id\":1,\"sym\":\"IDENTIFIER\",\"rule\":-1,\"category\":\"IDENTIFIER\",\"text\":\"x\",\"lineno\":1},\
+             {\"id\":2,\"sym\":\"STRINGLIT\",\"rule\":-1,\"category\":\"STRINGLIT\",\"text\":\"\\\"a\\\"\",\"lineno\":2}]}\n"
+        );
+    }
+}
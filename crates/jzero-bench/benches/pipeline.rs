@@ -0,0 +1,92 @@
+//! Each phase of the compiler on generated programs of growing size.
+//!
+//! Run with `cargo bench -p jzero-bench`; criterion reports bytes per
+//! second of source for lexing, parsing, semantic analysis and IR
+//! generation, one benchmark group per phase and one benchmark per size.
+//! Each phase is measured alone: its input is prepared outside the timed
+//! loop.
+
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use jzero_bench::{ProgramShape, generate};
+
+/// Classes × methods × statements, from a small exercise to a large
+/// project.
+const SHAPES: [ProgramShape; 3] = [
+    ProgramShape { classes: 1, methods: 10, statements: 10 },
+    ProgramShape { classes: 10, methods: 20, statements: 20 },
+    ProgramShape { classes: 40, methods: 25, statements: 40 },
+];
+
+struct Program {
+    name: String,
+    bytes: u64,
+    sources: Vec<String>,
+}
+
+fn programs() -> Vec<Program> {
+    SHAPES.iter().map(|shape| {
+        let sources: Vec<String> = generate(shape).into_iter().map(|(_, s)| s).collect();
+        Program {
+            name: format!("{}x{}x{}", shape.classes, shape.methods, shape.statements),
+            bytes: sources.iter().map(|s| s.len() as u64).sum(),
+            sources,
+        }
+    }).collect()
+}
+
+fn bench_pipeline(c: &mut Criterion) {
+    let programs = programs();
+
+    let mut group = c.benchmark_group("lex");
+    for p in &programs {
+        group.throughput(Throughput::Bytes(p.bytes));
+        group.bench_with_input(BenchmarkId::from_parameter(&p.name), &p.sources, |b, sources| {
+            b.iter(|| sources.iter().map(|s| jzero_lexer::lex(s).tokens.len()).sum::<usize>())
+        });
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("parse");
+    group.sample_size(20);
+    for p in &programs {
+        group.throughput(Throughput::Bytes(p.bytes));
+        group.bench_with_input(BenchmarkId::from_parameter(&p.name), &p.sources, |b, sources| {
+            b.iter(|| sources.iter().map(|s| jzero_parser::parse_tree(s).unwrap()).collect::<Vec<_>>())
+        });
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("semantic");
+    group.sample_size(20);
+    for p in &programs {
+        let trees: Vec<_> = p.sources.iter().map(|s| jzero_parser::parse_tree(s).unwrap()).collect();
+        group.throughput(Throughput::Bytes(p.bytes));
+        group.bench_with_input(BenchmarkId::from_parameter(&p.name), &trees, |b, trees| {
+            b.iter_batched(
+                || trees.clone(),
+                |mut trees| trees.iter_mut().map(|t| jzero_semantic::analyze(t).errors.len()).sum::<usize>(),
+                criterion::BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("ir");
+    group.sample_size(20);
+    for p in &programs {
+        let analysed: Vec<_> = p.sources.iter().map(|s| {
+            let mut tree = jzero_parser::parse_tree(s).unwrap();
+            let sem = jzero_semantic::analyze(&mut tree);
+            assert!(sem.errors.is_empty(), "{:?}", sem.errors);
+            (tree, sem)
+        }).collect();
+        group.throughput(Throughput::Bytes(p.bytes));
+        group.bench_with_input(BenchmarkId::from_parameter(&p.name), &analysed, |b, analysed| {
+            b.iter(|| analysed.iter().map(|(tree, sem)| jzero_codegen::generate(tree, sem)).collect::<Vec<_>>())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_pipeline);
+criterion_main!(benches);
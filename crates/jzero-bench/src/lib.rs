@@ -0,0 +1,116 @@
+//! # jzero-bench
+//!
+//! Synthetic Jzero programs of any size, for the macro-benchmarks in
+//! `benches/pipeline.rs`:
+//!
+//! ```bash
+//! cargo bench -p jzero-bench
+//! ```
+//!
+//! [`generate`] writes one file per class.  Each method mixes the
+//! statements real programs are made of — arithmetic, `if`/`else`, `while`
+//! loops, calls and `println` — so every phase of the pipeline has work to
+//! do, and each program passes semantic analysis and compiles.
+//!
+//! ```
+//! use jzero_bench::{ProgramShape, generate};
+//!
+//! let files = generate(&ProgramShape { classes: 2, methods: 3, statements: 8 });
+//! assert_eq!(files.len(), 2);
+//! assert_eq!(files[0].0, "C0.java");
+//! assert!(files[1].1.starts_with("public class C1 {"));
+//! ```
+
+use std::fmt::Write;
+
+/// The size of a generated program.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProgramShape {
+    pub classes: usize,
+    /// Methods per class, `main` left out.
+    pub methods: usize,
+    /// Statements per method, its declarations and `return` left out.
+    pub statements: usize,
+}
+
+impl Default for ProgramShape {
+    fn default() -> Self {
+        ProgramShape { classes: 1, methods: 10, statements: 20 }
+    }
+}
+
+/// The files of a program of `shape`, as `(file name, source)` pairs.  The
+/// same shape always gives the same program.
+pub fn generate(shape: &ProgramShape) -> Vec<(String, String)> {
+    (0..shape.classes).map(|c| (format!("C{}.java", c), class(c, shape))).collect()
+}
+
+fn class(c: usize, shape: &ProgramShape) -> String {
+    let mut src = String::new();
+    let _ = writeln!(src, "public class C{} {{", c);
+    for m in 0..shape.methods {
+        method(&mut src, c, m, shape.statements);
+    }
+    let _ = writeln!(src, "    public static void main(String argv[]) {{");
+    if shape.methods > 0 {
+        let _ = writeln!(src, "        System.out.println(m{}({}));", shape.methods - 1, c + 1);
+    }
+    let _ = writeln!(src, "    }}");
+    src.push_str("}\n");
+    src
+}
+
+/// Method `m` of class `c`.  Only its first call statement calls the
+/// method before it, so running `main` costs time linear in the size.
+fn method(src: &mut String, c: usize, m: usize, statements: usize) {
+    let _ = writeln!(src, "    // Method {} of class {}.", m, c);
+    let _ = writeln!(src, "    public static int m{}(int n) {{", m);
+    src.push_str("        int acc;\n        int i;\n        acc = n;\n        i = 0;\n");
+    for s in 0..statements {
+        let _ = match s % 5 {
+            0 => writeln!(src, "        acc = acc + {} * n - (acc / {});", s + 1, s % 7 + 2),
+            1 => writeln!(
+                src,
+                "        if (acc > {}) {{\n            acc = acc - {};\n        }} else {{\n            acc = acc + 1;\n        }}",
+                s * 10, s,
+            ),
+            2 => writeln!(
+                src,
+                "        while (i < {}) {{\n            acc = acc + i;\n            i = i + 1;\n        }}\n        i = 0;",
+                s % 4 + 1,
+            ),
+            3 if s == 3 && m > 0 => writeln!(src, "        acc = m{}(acc % 100);", m - 1),
+            3 => writeln!(src, "        acc = acc % 1000;"),
+            _ => writeln!(src, "        System.out.println(acc);"),
+        };
+    }
+    src.push_str("        return acc;\n    }\n");
+}
+
+// ─── Tests ───────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generated_programs_compile() {
+        let shape = ProgramShape { classes: 3, methods: 4, statements: 12 };
+        let files = generate(&shape);
+        assert_eq!(files, generate(&shape));
+        for (name, source) in &files {
+            let tac = jzero::Compiler::new().source(source).tac()
+                .unwrap_or_else(|e| panic!("{}: {}\n{}", name, e, source));
+            // The methods and main.
+            assert_eq!(tac.matches("proc ").count(), shape.methods + 1, "{}", tac);
+        }
+    }
+
+    #[test]
+    fn the_size_grows_with_the_shape() {
+        let small = generate(&ProgramShape { classes: 1, methods: 2, statements: 5 });
+        let large = generate(&ProgramShape { classes: 1, methods: 20, statements: 50 });
+        assert!(large[0].1.len() > 50 * small[0].1.len(), "{} vs {}", large[0].1.len(), small[0].1.len());
+        assert!(generate(&ProgramShape { classes: 0, ..ProgramShape::default() }).is_empty());
+    }
+}
@@ -0,0 +1,127 @@
+//! `j0 examples` — write out the book's example programs.
+//!
+//! The programs in `tests/examples` are compiled into `j0`, each tagged
+//! with the chapter that uses it and the pipeline stage that chapter adds.
+//! `j0 examples` writes them to `<out-dir>/chNN/<name>.java`; with `--run`
+//! it also runs that stage on each one and prints the result, so a reader
+//! can follow a chapter without copying code by hand.
+
+use std::fs;
+use std::path::Path;
+use std::process;
+
+use jzero::emit::EmitKind;
+use jzero::Compiler;
+
+/// Where `j0 examples` writes when no `--out-dir` is given.
+const DEFAULT_DIR: &str = "j0-examples";
+
+/// What a chapter does with its example.
+enum Stage {
+    /// Write one artifact, e.g. the tokens for the lexer chapter.
+    Emit(EmitKind),
+    /// Compile and run in the VM with these arguments.
+    Run(&'static [&'static str]),
+}
+
+struct Example {
+    chapter: u32,
+    name: &'static str,
+    source: &'static str,
+    stage: Stage,
+}
+
+macro_rules! example {
+    ($chapter:expr, $name:literal, $stage:expr) => {
+        Example {
+            chapter: $chapter,
+            name: $name,
+            source: include_str!(concat!("../../../tests/examples/", $name, ".java")),
+            stage: $stage,
+        }
+    };
+}
+
+const EXAMPLES: &[Example] = &[
+    example!(3, "hello", Stage::Emit(EmitKind::Tokens)),
+    example!(4, "hello_loop", Stage::Emit(EmitKind::Ast)),
+    example!(5, "hello_loop", Stage::Emit(EmitKind::Ast)),
+    example!(6, "countdown", Stage::Emit(EmitKind::Symtab)),
+    example!(7, "fibonacci", Stage::Emit(EmitKind::Symtab)),
+    example!(8, "hello_loop", Stage::Emit(EmitKind::Symtab)),
+    example!(9, "hello_loop", Stage::Emit(EmitKind::Ir)),
+    example!(12, "hello", Stage::Run(&[])),
+    example!(13, "hello_loop", Stage::Emit(EmitKind::Asm)),
+    example!(15, "concat", Stage::Run(&[])),
+    example!(15, "greet", Stage::Run(&["a", "b", "c"])),
+    example!(15, "fizzbuzz", Stage::Run(&["a"; 15])),
+];
+
+/// `j0 examples [--chapter N] [--out-dir=<dir>] [--run]`.
+pub fn examples_command(args: &[String]) {
+    let mut chapter = None;
+    let mut out_dir = DEFAULT_DIR.to_string();
+    let mut run = false;
+    let mut it = args.iter();
+    while let Some(a) = it.next() {
+        if a == "--chapter" {
+            chapter = Some(it.next().and_then(|v| v.parse::<u32>().ok()).unwrap_or_else(|| usage()));
+        } else if let Some(n) = a.strip_prefix("--chapter=") {
+            chapter = Some(n.parse().unwrap_or_else(|_| usage()));
+        } else if let Some(d) = a.strip_prefix("--out-dir=") {
+            out_dir = d.to_string();
+        } else if a == "--run" {
+            run = true;
+        } else {
+            usage();
+        }
+    }
+
+    let selected: Vec<&Example> = EXAMPLES.iter()
+        .filter(|e| chapter.is_none_or(|n| e.chapter == n))
+        .collect();
+    if selected.is_empty() {
+        let mut chapters: Vec<String> = EXAMPLES.iter().map(|e| e.chapter.to_string()).collect();
+        chapters.dedup();
+        eprintln!("No examples for chapter {}; try one of: {}", chapter.unwrap_or(0), chapters.join(", "));
+        process::exit(1);
+    }
+
+    let mut failed = false;
+    for example in selected {
+        let dir = Path::new(&out_dir).join(format!("ch{:02}", example.chapter));
+        let path = dir.join(format!("{}.java", example.name));
+        if let Err(e) = fs::create_dir_all(&dir).and_then(|_| fs::write(&path, example.source)) {
+            eprintln!("Error writing '{}': {}", path.display(), e);
+            process::exit(1);
+        }
+        eprintln!("{}", path.display());
+        if run {
+            match run_stage(example) {
+                Ok(output) => print!("{}", output),
+                Err(e) => {
+                    eprintln!("{}: {}", path.display(), e);
+                    failed = true;
+                }
+            }
+        }
+    }
+    if failed { process::exit(1); }
+}
+
+/// The output of `example`'s stage.
+fn run_stage(example: &Example) -> Result<String, jzero::JzeroError> {
+    let compiler = Compiler::new().source(example.source);
+    match &example.stage {
+        Stage::Emit(kind) => {
+            let artifact = compiler.emit(&[*kind])?.remove(0);
+            Ok(String::from_utf8_lossy(&artifact.contents).into_owned())
+        }
+        Stage::Run(args) => Ok(compiler.run(args)?.stdout),
+    }
+}
+
+fn usage() -> ! {
+    eprintln!("Usage: j0 examples [--chapter <n>] [--out-dir=<dir>] [--run]");
+    process::exit(1);
+}
@@ -1,57 +1,567 @@
 use std::env;
 use std::fs;
+use std::path::Path;
 use std::process::{self, Command};
 
-use jzero_ast::tree::reset_ids;
-use jzero_parser::parse_tree;
+mod examples;
+mod teach;
 
 fn main() {
     let args: Vec<String> = env::args().collect();
 
     if args.len() < 2 {
-        eprintln!("Usage: j0 <source.java> [--png] [--codegen] [--bytecode] [--run]");
+        eprintln!("Usage: j0 <source.java> [--emit=<kinds>] [--out-dir=<dir>] [--png] [--book-compat] [--codegen] [--bytecode] [--run] [--trace-exec[=<n>]] [--seed=<n>] [--clock=<ms>] [--level=<level>] [--define NAME=VALUE] [--max-file-size=<bytes>] [--lossy-utf8] [--stats] [-v]");
+        eprintln!("       j0 <source.tac> --bytecode|--run");
+        eprintln!("       j0 teach <source.java>");
+        eprintln!("       j0 test --unit <source.java>...");
+        eprintln!("       j0 examples [--chapter <n>] [--out-dir=<dir>] [--run]");
+        eprintln!("       j0 compare --ref <expected_dir> [--book-compat] <src_dir>");
+        eprintln!("       j0 fmt [--check|--annotate] <source.java>...");
+        eprintln!("       j0 stub [--spec <spec.java>] [--check] <source.java>...");
+        eprintln!("       j0 semdiff <old.java> <new.java>");
+        eprintln!("       j0 grammar --precedence");
+        eprintln!("       j0 find-symbol <name> <source.java>...");
+        eprintln!("       j0 search <query> <source.java|dir>...");
+        eprintln!("       j0 deps [--emit=depgraph.dot] [--changed=<file>] <source.java>...");
+        eprintln!("       j0 check [--baseline <baseline.json>] [--deny warnings] [--warn|--allow <check>] <source.java|submission.zip>...");
         eprintln!();
         eprintln!("Options:");
-        eprintln!("  --png       Render the DOT file to PNG using Graphviz");
+        eprintln!("  --emit      Write artifacts: tokens,ast,ast-json,dot,symtab,ir,asm,bytecode");
+        eprintln!("  --out-dir   Directory for --emit and --png output, created if missing (default: next to the source)");
+        eprintln!("  --png       Write the syntax tree as DOT and render it to PNG using Graphviz");
+        eprintln!("  --book-compat  Print trees exactly as the book's reference implementation does");
         eprintln!("  --codegen   Run semantic analysis + codegen, print TAC IR");
         eprintln!("  --bytecode  Compile to bytecode, print assembler listing");
         eprintln!("  --run       Compile to bytecode and execute it in the VM");
+        eprintln!("  --trace-exec  With --run, print each instruction executed to stderr (the first <n>, default 10000)");
+        eprintln!("  --seed      With --run, seed Math.random() so every run draws the same numbers");
+        eprintln!("  --clock     With --run, freeze System.currentTimeMillis() at <ms>");
+        eprintln!("  -v          With --codegen, --bytecode or --run, report what the optimizer removed");
+        eprintln!("  --level     Language level: jzero (default) or extended, which adds final, switch, ...");
+        eprintln!("  --define    Give the static final constant NAME the value VALUE, e.g. --define DEBUG=true");
+        eprintln!("  --max-file-size  Refuse source files over <bytes> (default 8 MiB; [input] max-file-size in jzero.toml)");
+        eprintln!("  --stats          Report tree node, symbol table and string counts after analysis");
+        eprintln!("  --lossy-utf8     Read invalid UTF-8 in a source file as U+FFFD, with a warning, rather than refuse it");
+        eprintln!();
+        eprintln!("Commands:");
+        eprintln!("  teach       Step through lexing, parsing, scopes and codegen page by page");
+        eprintln!("  test        Run each `public static void testXxx()` method as a unit test");
+        eprintln!("  examples    Write the book's example programs (default: j0-examples/chNN/);");
+        eprintln!("              with --run, run each chapter's pipeline stage on them");
+        eprintln!("  compare     Compare trees, symbol tables, TAC and output with reference files");
+        eprintln!("  fmt         Rewrite files in canonical layout (--check: only report;");
+        eprintln!("              --annotate: print them with parameter names, constant values");
+        eprintln!("              and array types written in as comments)");
+        eprintln!("  stub        Add stubs for the methods of the --spec class, or of the interfaces");
+        eprintln!("              it implements among the files, a file lacks (--check: only list them)");
+        eprintln!("  semdiff     List the classes, fields and methods added, removed, renamed or");
+        eprintln!("              changed in signature between two versions of a file");
+        eprintln!("  grammar     With --precedence, print the operators by precedence and associativity");
+        eprintln!("  find-symbol List the symbols named <name>: a prefix, a glob with * and ?,");
+        eprintln!("              or with dots a qualified name such as hello.main.argv");
+        eprintln!("  search      List the syntax matching <query>, e.g. 'WhileStmt MethodCall[callee=\"println\"]',");
+        eprintln!("              in each file, and each .java file below each directory");
+        eprintln!("  deps        Write j0-compile-commands.json and the file dependency graph");
+        eprintln!("  check       Run the jzero.toml checks; with --baseline, report only new findings.");
+        eprintln!("              A .zip is checked as one project, without extracting it to disk.");
+        eprintln!("              --deny/--warn/--allow override levels, as [diagnostics] in jzero.toml");
         process::exit(1);
     }
 
+    // ── Formatter (j0 fmt [--check|--annotate] files...) ──────────────────────
+    if args[1] == "fmt" {
+        let check = args.iter().any(|a| a == "--check");
+        let annotate = args.iter().any(|a| a == "--annotate");
+        let files: Vec<&String> = args[2..].iter().filter(|a| !a.starts_with("--")).collect();
+        if files.is_empty() {
+            eprintln!("Usage: j0 fmt [--check|--annotate] <source.java>...");
+            process::exit(1);
+        }
+        let mut unformatted = false;
+        for path in files {
+            let source = match read_source(&args, path) {
+                Ok(s) => s,
+                Err(e) => {
+                    eprintln!("{}", e);
+                    process::exit(1);
+                }
+            };
+            // Lines that do not lex or hold an unmatched bracket are left
+            // as written; say which.
+            let jzero::fmt::Formatted { text: formatted, kept, .. } = jzero::fmt::format_recovering(&source);
+            let mut runs: Vec<(usize, usize, &str)> = Vec::new();
+            for (line, why) in &kept {
+                match runs.last_mut() {
+                    Some((_, last, w)) if *last + 1 == *line && *w == why.as_str() => *last = *line,
+                    _ => runs.push((*line, *line, why)),
+                }
+            }
+            for (first, last, why) in runs {
+                let lines = if first == last { format!("line {}", first) } else { format!("lines {}-{}", first, last) };
+                eprintln!("{}: {} left as written: {}", path, lines, why);
+            }
+            if annotate {
+                match jzero::hints::annotate(&formatted) {
+                    Ok(annotated) => print!("{}", annotated),
+                    Err(e) => {
+                        eprintln!("{}: {}", path, e);
+                        process::exit(1);
+                    }
+                }
+                continue;
+            }
+            if formatted == source { continue; }
+            if check {
+                println!("would reformat: {}", path);
+                unformatted = true;
+            } else if let Err(e) = fs::write(path, &formatted) {
+                eprintln!("Error writing '{}': {}", path, e);
+                process::exit(1);
+            }
+        }
+        if unformatted { process::exit(1); }
+        return;
+    }
+
+    // ── Method stubs (j0 stub [--spec spec.java] [--check] files...) ──────────
+    if args[1] == "stub" {
+        let check = args.iter().any(|a| a == "--check");
+        let spec_path = args.iter().position(|a| a == "--spec").and_then(|i| args.get(i + 1));
+        let files: Vec<&String> = args[2..].iter()
+            .filter(|a| !a.starts_with("--") && Some(*a) != spec_path)
+            .collect();
+        if files.is_empty() {
+            eprintln!("Usage: j0 stub [--spec <spec.java>] [--check] <source.java>...");
+            process::exit(1);
+        }
+        let read = |path: &str| read_source(&args, path).unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            process::exit(1);
+        });
+        let fail = |path: &str, e: jzero::JzeroError| -> ! {
+            eprintln!("{}: {}", path, e);
+            process::exit(1);
+        };
+        let sources: Vec<(&String, String)> = files.iter().map(|p| (*p, read(p))).collect();
+        let spec = spec_path.map(|p| read(p));
+        // Without --spec, a class is held to the interfaces it implements,
+        // which the other files declare.
+        let declared: Vec<(String, &str)> = sources.iter()
+            .filter_map(|(_, s)| Some((jzero::stub::class_methods(s).ok()?.0, s.as_str())))
+            .collect();
+        let mut incomplete = false;
+        for (path, source) in &sources {
+            let specs: Vec<&str> = match &spec {
+                Some(spec) => vec![spec.as_str()],
+                None => {
+                    let names = jzero::stub::implemented_interfaces(source).unwrap_or_else(|e| fail(path, e));
+                    names.iter().filter_map(|name| {
+                        let found = declared.iter().find(|(n, _)| n == name).map(|(_, s)| *s);
+                        if found.is_none() {
+                            eprintln!("{}: interface '{}' is not declared by any of the files", path, name);
+                            incomplete = true;
+                        }
+                        found
+                    }).collect()
+                }
+            };
+            let mut stubbed = source.clone();
+            let mut added = Vec::new();
+            for spec in specs {
+                let missing = jzero::stub::missing_methods(spec, &stubbed).unwrap_or_else(|e| fail(path, e));
+                let mismatched = jzero::stub::mismatched_methods(spec, &stubbed).unwrap_or_else(|e| fail(path, e));
+                for (have, want) in &mismatched {
+                    eprintln!("{}: '{}' does not match the spec's '{}'", path, have, want);
+                    incomplete = true;
+                }
+                if missing.is_empty() { continue; }
+                if check {
+                    for sig in &missing {
+                        println!("{}: missing {}", path, sig);
+                    }
+                    incomplete = true;
+                    continue;
+                }
+                stubbed = jzero::stub::add_stubs(spec, &stubbed).unwrap_or(stubbed);
+                added.extend(missing);
+            }
+            if added.is_empty() { continue; }
+            if let Err(e) = fs::write(path, stubbed) {
+                eprintln!("Error writing '{}': {}", path, e);
+                process::exit(1);
+            }
+            for sig in &added {
+                println!("{}: added {}", path, sig);
+            }
+        }
+        if incomplete { process::exit(1); }
+        return;
+    }
+
+    // ── Semantic diff (j0 semdiff old.java new.java) ─────────────────────────
+    if args[1] == "semdiff" {
+        let [_, _, old_path, new_path] = &args[..] else {
+            eprintln!("Usage: j0 semdiff <old.java> <new.java>");
+            process::exit(1);
+        };
+        let read = |path: &str| read_source(&args, path).unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            process::exit(2);
+        });
+        let (old, new) = (read(old_path), read(new_path));
+        let changes = match jzero::semdiff::diff(&old, &new) {
+            Ok(c) => c,
+            Err(e) => {
+                eprintln!("{}", e);
+                process::exit(2);
+            }
+        };
+        for change in &changes {
+            println!("{}", change);
+        }
+        // Like diff(1): 1 when the versions differ, 2 on trouble.
+        if !changes.is_empty() { process::exit(1); }
+        return;
+    }
+
+    // ── Grammar tables (j0 grammar --precedence) ─────────────────────────────
+    if args[1] == "grammar" {
+        if args.len() != 3 || args[2] != "--precedence" {
+            eprintln!("Usage: j0 grammar --precedence");
+            process::exit(1);
+        }
+        print!("{}", jzero_parser::precedence::chart());
+        return;
+    }
+
+    // ── Symbol search (j0 find-symbol NAME files...) ─────────────────────────
+    if args[1] == "find-symbol" {
+        let (Some(pattern), false) = (args.get(2), args.len() < 4) else {
+            eprintln!("Usage: j0 find-symbol <name> <source.java>...");
+            process::exit(1);
+        };
+        let graph = match jzero::symbols::ScopeGraph::load(&args[3..]) {
+            Ok(g) => g,
+            Err(e) => {
+                eprintln!("{}", e);
+                process::exit(1);
+            }
+        };
+        let found = graph.find_symbols(pattern);
+        if found.is_empty() {
+            eprintln!("no symbol matches '{}'", pattern);
+            process::exit(1);
+        }
+        for symbol in &found {
+            println!("{}", symbol);
+        }
+        return;
+    }
+
+    // ── Code search (j0 search QUERY files-or-dirs...) ────────────────────────
+    if args[1] == "search" {
+        let (Some(query), false) = (args.get(2), args.len() < 4) else {
+            eprintln!("Usage: j0 search <query> <source.java|dir>...");
+            process::exit(1);
+        };
+        let query = match jzero::search::Query::parse(query) {
+            Ok(q) => q,
+            Err(e) => {
+                eprintln!("{}", e);
+                process::exit(1);
+            }
+        };
+        let mut files = Vec::new();
+        for path in args[3..].iter().filter(|a| !a.starts_with("--")) {
+            if !Path::new(path).is_dir() {
+                files.push(path.clone());
+                continue;
+            }
+            match jzero::FileProvider::files(&jzero::RealFs, path) {
+                Ok(found) => files.extend(found.into_iter().filter(|f| f.ends_with(".java"))),
+                Err(e) => {
+                    eprintln!("{}", e);
+                    process::exit(1);
+                }
+            }
+        }
+        // One file that does not parse does not stop the search of the rest.
+        let mut matched = false;
+        for path in &files {
+            let found = read_source(&args, path)
+                .and_then(|source| query.search(path, &source))
+                .map_err(|e| e.to_string());
+            match found {
+                Ok(found) => {
+                    matched |= !found.is_empty();
+                    for m in &found {
+                        println!("{}", m);
+                    }
+                }
+                Err(e) => eprintln!("{}", e),
+            }
+        }
+        if !matched { process::exit(1); }
+        return;
+    }
+
+    // ── Project database (j0 deps files...) ──────────────────────────────────
+    if args[1] == "deps" {
+        let files: Vec<String> = args[2..].iter().filter(|a| !a.starts_with("--")).cloned().collect();
+        if files.is_empty() {
+            eprintln!("Usage: j0 deps [--emit=depgraph.dot] [--changed=<file>] <source.java>...");
+            process::exit(1);
+        }
+        let project = match jzero::Project::load(&files) {
+            Ok(p) => p,
+            Err(e) => {
+                eprintln!("{}", e);
+                process::exit(1);
+            }
+        };
+
+        let duplicates = project.duplicate_classes();
+        for dup in &duplicates {
+            eprintln!("error: {}", dup);
+        }
+        if !duplicates.is_empty() { process::exit(1); }
+
+        let dir = env::current_dir().map(|d| d.display().to_string()).unwrap_or_default();
+        let db_path = "j0-compile-commands.json";
+        if let Err(e) = fs::write(db_path, project.compile_commands(&dir)) {
+            eprintln!("Error writing '{}': {}", db_path, e);
+            process::exit(1);
+        }
+        eprintln!("Compilation database written to: {}", db_path);
+
+        if let Some(dot_path) = args.iter().find_map(|a| a.strip_prefix("--emit=")) {
+            if let Err(e) = fs::write(dot_path, project.to_dot()) {
+                eprintln!("Error writing '{}': {}", dot_path, e);
+                process::exit(1);
+            }
+            eprintln!("Dependency graph written to: {}", dot_path);
+        }
+
+        if let Some(changed) = args.iter().find_map(|a| a.strip_prefix("--changed=")) {
+            for path in project.dependents(changed) {
+                println!("{}", path);
+            }
+        }
+        return;
+    }
+
+    // ── Convention checks (j0 check [--baseline file] files...) ───────────────
+    if args[1] == "check" {
+        check_command(&args[2..]);
+        return;
+    }
+
+    // ── Book examples (j0 examples [--chapter N] [--run]) ─────────────────────
+    if args[1] == "examples" {
+        examples::examples_command(&args[2..]);
+        return;
+    }
+
+    // ── Reference comparison (j0 compare --ref dir src_dir) ───────────────────
+    if args[1] == "compare" {
+        compare_command(&args[2..]);
+        return;
+    }
+
+    // ── Teaching mode (j0 teach file.java) ────────────────────────────────────
+    if args[1] == "test" {
+        test_command(&args[2..]);
+        return;
+    }
+
+    if args[1] == "teach" {
+        let Some(path) = args.get(2) else {
+            eprintln!("Usage: j0 teach <source.java>");
+            process::exit(1);
+        };
+        match read_source(&args, path) {
+            Ok(source) => teach::teach(&source),
+            Err(e) => {
+                eprintln!("{}", e);
+                process::exit(1);
+            }
+        }
+        return;
+    }
+
     let source_path = &args[1];
     let render_png    = args.iter().any(|a| a == "--png");
     let do_codegen    = args.iter().any(|a| a == "--codegen");
     let do_bytecode   = args.iter().any(|a| a == "--bytecode");
     let do_run        = args.iter().any(|a| a == "--run");
+    let book_compat   = args.iter().any(|a| a == "--book-compat");
+    let verbose       = args.iter().any(|a| a == "-v" || a == "--verbose");
+    let trace_limit   = args.iter().find_map(|a| match a.strip_prefix("--trace-exec") {
+        Some("") => Some(jzero_vm::trace::DEFAULT_TRACE_LIMIT),
+        Some(n) => Some(n.strip_prefix('=').and_then(|n| n.parse().ok()).unwrap_or_else(|| {
+            eprintln!("--trace-exec takes a number of instructions, e.g. --trace-exec=500");
+            process::exit(2);
+        })),
+        None => None,
+    });
+    let seed  = number_option(&args, "--seed=", "a seed, e.g. --seed=42");
+    let clock = number_option(&args, "--clock=", "a time in milliseconds, e.g. --clock=0");
 
     // Read source file
-    let source = match fs::read_to_string(source_path) {
+    let source = match read_source(&args, source_path) {
         Ok(s) => s,
         Err(e) => {
-            eprintln!("Error reading '{}': {}", source_path, e);
+            eprintln!("{}", e);
             process::exit(1);
         }
     };
 
-    reset_ids();
+    // Collect program arguments (everything after the source file and flags).
+    let prog_args: Vec<String> = args[2..].iter().enumerate()
+        .filter(|(i, a)| !a.starts_with("--") && *a != "-v" && args[i + 1] != "--define")
+        .map(|(_, a)| a.clone())
+        .collect();
+    let defines = defines(&args);
+    let level = match args.iter().find_map(|a| a.strip_prefix("--level=")) {
+        Some(name) => jzero_lexer::Level::parse(name).unwrap_or_else(|| {
+            eprintln!("--level takes a language level: jzero or extended");
+            process::exit(2);
+        }),
+        None => jzero_lexer::Level::default(),
+    };
+    let argc = prog_args.len() as i64;
+
+    // Write the assembler listing and .j0 file, and/or run the image.
+    let finish = |output: jzero_codegen::pipeline::BytecodeOutput| {
+        if do_bytecode {
+            print!("{}", output.text);
+            let j0_path = j0_path(source_path);
+            if let Err(e) = fs::write(&j0_path, &output.binary) {
+                eprintln!("Error writing '{}': {}", j0_path, e);
+                process::exit(1);
+            }
+            eprintln!(".j0 written to: {}", j0_path);
+        }
+
+        if do_run {
+            // StdHost writes each line as it is printed, so the returned
+            // transcript is not echoed again here.
+            let mut host = jzero_vm::StdHost::new();
+            if let Some(seed) = seed { host = host.with_seed(seed as u64); }
+            if let Some(ms) = clock { host = host.with_clock(ms, 0); }
+            let result = jzero_vm::J0Machine::load_with_host(&output.binary, argc, Box::new(host))
+                .and_then(|mut m| {
+                    m.set_line_table(output.lines);
+                    if let Some(limit) = trace_limit {
+                        m.set_trace(jzero_vm::ExecTrace::new(Box::new(std::io::stderr()), limit));
+                    }
+                    m.interp()
+                });
+            match result {
+                Ok(_) => {
+                    println!("no errors");
+                }
+                Err(e) => {
+                    eprintln!("VM error: {}", e);
+                    process::exit(1);
+                }
+            }
+        }
+    };
+
+    // ── Hand-written or edited IR (j0 file.tac --bytecode/--run) ──────────────
+    if source_path.ends_with(".tac") {
+        if !(do_bytecode || do_run) {
+            eprintln!("{}: IR can only be compiled, with --bytecode or --run", source_path);
+            process::exit(2);
+        }
+        match jzero_codegen::parse_ir(&source) {
+            Ok(program) => finish(jzero_codegen::pipeline::compile_ir(&program, argc)),
+            Err(e) => {
+                eprintln!("{}: {}", source_path, e);
+                process::exit(1);
+            }
+        }
+        return;
+    }
+
+    convention_checks(source_path, &source);
+
+    if args.iter().any(|a| a == "--stats") {
+        let mut session = jzero::CompileSession::default();
+        let id = session.add_file(source_path, &source);
+        session.check(id);
+        eprint!("{}", session.memory_report());
+    }
+
+    let stem = Path::new(source_path).file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+    let out_dir = match args.iter().find_map(|a| a.strip_prefix("--out-dir=")) {
+        Some(d) => Path::new(d).to_path_buf(),
+        None => Path::new(source_path).parent().unwrap_or(Path::new("")).to_path_buf(),
+    };
+
+    // ── Selected artifacts (--emit=tokens,ast,...) ────────────────────────────
+    if let Some(list) = args.iter().find_map(|a| a.strip_prefix("--emit=")) {
+        let written = jzero::emit::EmitKind::parse_list(list)
+            .and_then(|kinds| {
+                let compiler = defines.iter().fold(jzero::Compiler::new().level(level), |c, (name, value)| c.define(name, value));
+                compiler.source(&source).book_compat(book_compat).emit(&kinds)
+            });
+        let artifacts = match written {
+            Ok(a) => a,
+            Err(e) => {
+                eprintln!("{}: {}", source_path, e);
+                process::exit(1);
+            }
+        };
+        create_out_dir(&out_dir);
+        for artifact in &artifacts {
+            let path = out_dir.join(artifact.file_name(&stem));
+            if let Err(e) = artifact.write_to(&path) {
+                eprintln!("{}", e);
+                process::exit(1);
+            }
+            eprintln!("{} written to: {}", artifact.kind, path.display());
+        }
+        if !(do_codegen || do_bytecode || do_run || render_png) { return; }
+    }
 
-    let mut tree = match parse_tree(&source) {
+    let mut tree = match jzero_parser::parse_tree_with(&source, jzero_lexer::LexOptions::at_level(level)) {
         Ok(t) => t,
         Err(e) => {
-            eprintln!("{}: {}", source_path, e);
+            // Printing the tree, show what the tokens around lexical errors
+            // still make of the file.
+            let printing = !(do_codegen || do_bytecode || do_run);
+            match jzero_parser::parse_tree_recovering(&source, jzero_lexer::LexOptions::at_level(level)) {
+                (Some(tree), found) if printing => {
+                    for d in &found {
+                        eprintln!("{}: {}", source_path, d.render(&source));
+                    }
+                    eprintln!("{}: tree built without the text in error:", source_path);
+                    print_tree(&tree, book_compat);
+                }
+                _ => eprintln!("{}: {}", source_path, e),
+            }
             process::exit(1);
         }
     };
+    for (name, value) in &defines {
+        if let Err(e) = jzero_semantic::define(&mut tree, name, value) {
+            eprintln!("{}: --define {}={}: {}", source_path, name, value, e);
+            process::exit(2);
+        }
+    }
 
     // ── TAC IR path (--codegen) ───────────────────────────────────────────────
     if do_codegen {
         let sem = jzero_semantic::analyze(&mut tree);
         for err in &sem.errors { eprintln!("{}", err); }
         let ctx = jzero_codegen::generate(&tree, &sem);
+        for err in &ctx.errors { eprintln!("{}", err); }
+        if verbose { report_optimizations(&ctx); }
         let asm = jzero_codegen::emit::emit(&tree, &ctx);
         print!("{}", asm);
-        if sem.errors.is_empty() { println!("no errors"); }
+        if sem.errors.is_empty() && ctx.errors.is_empty() { println!("no errors"); }
         return;
     }
 
@@ -61,67 +571,375 @@ fn main() {
         for err in &sem.errors { eprintln!("{}", err); }
         if !sem.errors.is_empty() { process::exit(1); }
 
-        // Collect program arguments (everything after the source file and flags).
-        let prog_args: Vec<String> = args[2..].iter()
-            .filter(|a| !a.starts_with("--"))
-            .cloned()
-            .collect();
-        let argc = prog_args.len() as i64;
-
         let ctx    = jzero_codegen::generate(&tree, &sem);
+        for err in &ctx.errors { eprintln!("{}", err); }
+        if !ctx.errors.is_empty() { process::exit(1); }
+        if verbose { report_optimizations(&ctx); }
         let output = jzero_codegen::pipeline::compile_bytecode(&tree, &ctx, argc);
 
-        if do_bytecode {
-            print!("{}", output.text);
-            let j0_path = j0_path(source_path);
-            if let Err(e) = fs::write(&j0_path, &output.binary) {
-                eprintln!("Error writing '{}': {}", j0_path, e);
-                process::exit(1);
-            }
-            eprintln!(".j0 written to: {}", j0_path);
+        finish(output);
+        return;
+    }
+
+    // ── Default path: print the tree (--png: also render it) ──────────────────
+    print_tree(&tree, book_compat);
+    if !render_png { return; }
+
+    create_out_dir(&out_dir);
+    let dot_path = out_dir.join(format!("{}.dot", stem));
+    if let Err(e) = fs::write(&dot_path, tree.to_dot()) {
+        eprintln!("Error writing '{}': {}", dot_path.display(), e);
+        process::exit(1);
+    }
+    eprintln!("DOT written to: {}", dot_path.display());
+
+    let png_path = out_dir.join(format!("{}.png", stem));
+    match Command::new("dot")
+        .arg("-Tpng")
+        .arg(&dot_path)
+        .arg("-o")
+        .arg(&png_path)
+        .status()
+    {
+        Ok(s) if s.success() => eprintln!("PNG written to: {}", png_path.display()),
+        Ok(s) => { eprintln!("dot exited with: {}", s); process::exit(1); }
+        Err(e) => {
+            eprintln!("Failed to run 'dot': {}", e);
+            eprintln!("Install Graphviz: sudo apt install graphviz");
+            process::exit(1);
         }
+    }
+}
 
-        if do_run {
-            match jzero_vm::run(&output.binary, &prog_args) {
-                Ok(out) => {
-                    print!("{}", out);
-                    println!("no errors");
+/// `j0 test --unit`: run the unit tests of each file, printing one line per
+/// test and the failures' messages and output, and exit with status 1 if
+/// any failed.
+fn test_command(args: &[String]) {
+    let files: Vec<&String> = args.iter().filter(|a| !a.starts_with("--")).collect();
+    if !args.iter().any(|a| a == "--unit") || files.is_empty() {
+        eprintln!("Usage: j0 test --unit <source.java>...");
+        process::exit(2);
+    }
+    let (mut passed, mut failed) = (0, 0);
+    for path in files {
+        let source = match read_source(args, path) {
+            Ok(s) => s,
+            Err(e) => {
+                eprintln!("{}", e);
+                process::exit(1);
+            }
+        };
+        let tests = match jzero::Compiler::new().source(&source).unit_tests() {
+            Ok(tests) => tests,
+            Err(e) => {
+                eprintln!("{}: {}", path, e);
+                process::exit(1);
+            }
+        };
+        for test in tests {
+            match &test.outcome {
+                Ok(()) => {
+                    println!("test {}::{} ... ok", path, test.name);
+                    passed += 1;
                 }
                 Err(e) => {
-                    eprintln!("VM error: {}", e);
-                    process::exit(1);
+                    println!("test {}::{} ... FAILED", path, test.name);
+                    println!("    line {}: {}", test.lineno, e);
+                    for line in test.stdout.lines() {
+                        println!("    | {}", line);
+                    }
+                    failed += 1;
                 }
             }
         }
-        return;
     }
+    println!();
+    println!("test result: {}. {} passed; {} failed", if failed == 0 { "ok" } else { "FAILED" }, passed, failed);
+    if failed > 0 { process::exit(1); }
+}
+
+/// The text of the source file `path`, read as the `[input]` settings of
+/// the nearest `jzero.toml` say, or `--max-file-size=<bytes>` and
+/// `--lossy-utf8` among `args`.  What a lossy read replaced is reported on
+/// stderr.
+fn read_source(args: &[String], path: &str) -> Result<String, jzero::JzeroError> {
+    let dir = Path::new(path).parent().unwrap_or(Path::new("."));
+    let mut options = jzero::ReadOptions::from_config(&jzero::Config::find(dir)?);
+    if let Some(max) = number_option(args, "--max-file-size=", "a size in bytes, e.g. --max-file-size=1048576") {
+        options.max_size = u64::try_from(max).unwrap_or(0);
+    }
+    options.lossy |= args.iter().any(|a| a == "--lossy-utf8");
+    let source = jzero::RealFs.read_with(path, options)?;
+    if let Some(warning) = &source.warning {
+        eprintln!("warning: {}", warning);
+    }
+    Ok(source.text)
+}
+
+/// The `NAME=VALUE` of each `--define NAME=VALUE` or `--define=NAME=VALUE`
+/// among `args`, exiting with a usage message if one lacks its `=`.
+fn defines(args: &[String]) -> Vec<(String, String)> {
+    let mut found = Vec::new();
+    let mut it = args.iter();
+    while let Some(a) = it.next() {
+        let definition = match a.strip_prefix("--define") {
+            Some("") => it.next().map(String::as_str),
+            Some(d) if d.starts_with('=') => Some(&d[1..]),
+            _ => continue,
+        };
+        match definition.and_then(|d| d.split_once('=')) {
+            Some((name, value)) => found.push((name.to_string(), value.to_string())),
+            None => {
+                eprintln!("--define takes a constant and its value, e.g. --define DEBUG=true");
+                process::exit(2);
+            }
+        }
+    }
+    found
+}
+
+/// What the optimizer removed, for `-v`.
+fn report_optimizations(ctx: &jzero_codegen::CodegenContext) {
+    eprintln!("{}", ctx.bounds_checks);
+    eprintln!("constant conditions: {} folded, their dead branches dropped", ctx.folded_conditions);
+}
+
+/// The value of the option `--name=<n>`, exiting with a usage message
+/// naming `what` if it is not a number.
+fn number_option(args: &[String], prefix: &str, what: &str) -> Option<i64> {
+    let value = args.iter().find_map(|a| a.strip_prefix(prefix))?;
+    Some(value.parse().unwrap_or_else(|_| {
+        eprintln!("{} takes {}", prefix.trim_end_matches('='), what);
+        process::exit(2);
+    }))
+}
+
+/// Create `dir` and its parents, if missing, before output is written
+/// there; exits if that fails.
+fn create_out_dir(dir: &Path) {
+    if let Err(e) = fs::create_dir_all(dir) {
+        eprintln!("Error creating '{}': {}", dir.display(), e);
+        process::exit(1);
+    }
+}
 
-    // ── Default path: tree + DOT ──────────────────────────────────────────────
-    print!("{}", tree);
+/// Print `tree`, in the book's format if `book_compat` is set.
+fn print_tree(tree: &jzero_ast::tree::Tree, book_compat: bool) {
+    if book_compat {
+        print!("{}", jzero::compat::tree_text(tree));
+    } else {
+        print!("{}", tree);
+    }
+}
+
+/// Run the optional `[checks]` from the nearest `jzero.toml` on one file.
+/// Findings are printed; an `error`-level finding stops the compile.
+fn convention_checks(path: &str, source: &str) {
+    let path = Path::new(path);
+    let dir = path.parent().unwrap_or(Path::new("."));
+    let config = match jzero::Config::find(dir) {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(1);
+        }
+    };
+    let findings = jzero::checks::run_checks(path, source, &config);
+    for f in &findings {
+        eprintln!("{}: {}", path.display(), f);
+    }
+    if findings.iter().any(|f| f.level == jzero::config::Level::Error) {
+        process::exit(1);
+    }
+}
 
-    let dot_path = format!("{}.dot", source_path);
-    let dot = tree.to_dot();
-    if let Err(e) = fs::write(&dot_path, &dot) {
-        eprintln!("Error writing '{}': {}", dot_path, e);
+/// `j0 check`: run the convention checks on every file.  A `.zip`
+/// argument stands for the `.java` files inside it; see [`check_archive`].
+///
+/// With `--baseline <file>`, a missing baseline is created from the current
+/// findings; an existing one hides the findings it records.
+///
+/// `--deny`, `--warn` and `--allow` set the level of a check, or with
+/// `warnings` of every warning, after those in `jzero.toml`.  Exits 1 if an
+/// `error`-level finding is reported.
+fn check_command(args: &[String]) {
+    let mut baseline_path = None;
+    let mut files = Vec::new();
+    let mut flags: Vec<(String, String)> = Vec::new();
+    let usage = || -> ! {
+        eprintln!("Usage: j0 check [--baseline <baseline.json>] [--deny warnings] [--warn|--allow <check>] <source.java|submission.zip>...");
         process::exit(1);
+    };
+    let mut it = args.iter();
+    while let Some(a) = it.next() {
+        let (flag, inline) = match a.split_once('=') {
+            Some((f, v)) => (f, Some(v.to_string())),
+            None => (a.as_str(), None),
+        };
+        match flag {
+            "--baseline" => baseline_path = inline.or_else(|| it.next().cloned()),
+            "--deny" | "--warn" | "--allow" => {
+                let code = inline.or_else(|| it.next().cloned()).unwrap_or_else(|| usage());
+                flags.push((flag.to_string(), code));
+            }
+            // Read by read_source.
+            "--max-file-size" | "--lossy-utf8" => {}
+            _ => files.push(a.clone()),
+        }
     }
-    eprintln!("DOT written to: {}", dot_path);
+    if files.is_empty() { usage(); }
 
-    if render_png {
-        let png_path = format!("{}.png", source_path);
-        match Command::new("dot")
-            .args(["-Tpng", &dot_path, "-o", &png_path])
-            .status()
-        {
-            Ok(s) if s.success() => eprintln!("PNG written to: {}", png_path),
-            Ok(s) => { eprintln!("dot exited with: {}", s); process::exit(1); }
+    let mut all: Vec<(String, Vec<jzero::checks::Finding>)> = Vec::new();
+    let mut failed = false;
+    for path in &files {
+        let dir = Path::new(path).parent().unwrap_or(Path::new("."));
+        let config = match jzero::Config::find(dir) {
+            Ok(c) => c,
             Err(e) => {
-                eprintln!("Failed to run 'dot': {}", e);
-                eprintln!("Install Graphviz: sudo apt install graphviz");
+                eprintln!("{}", e);
+                process::exit(1);
+            }
+        };
+        let mut overrides = jzero::SeverityOverrides::from_config(&config);
+        for (flag, code) in &flags {
+            overrides.apply_flag(flag, code);
+        }
+        let checked = all.len();
+        if path.ends_with(".zip") {
+            failed |= check_archive(path, &config, &mut all);
+        } else {
+            let source = match read_source(args, path) {
+                Ok(s) => s,
+                Err(e) => {
+                    eprintln!("{}", e);
+                    process::exit(1);
+                }
+            };
+            all.push((path.clone(), jzero::checks::run_checks(Path::new(path), &source, &config)));
+        }
+        for (_, findings) in &mut all[checked..] {
+            *findings = std::mem::take(findings).into_iter().filter_map(|f| overrides.finding(f)).collect();
+        }
+    }
+    let findings = all.iter().flat_map(|(p, fs)| fs.iter().map(move |f| (p.as_str(), f)));
+
+    let report = match &baseline_path {
+        Some(bp) if Path::new(bp).exists() => {
+            let baseline = fs::read_to_string(bp)
+                .map_err(|e| format!("Error reading '{}': {}", bp, e))
+                .and_then(|t| jzero::baseline::Baseline::parse(&t).map_err(|e| format!("{}: {}", bp, e)));
+            match baseline {
+                Ok(b) => b.new_findings(findings),
+                Err(e) => {
+                    eprintln!("{}", e);
+                    process::exit(1);
+                }
+            }
+        }
+        Some(bp) => {
+            let baseline = jzero::baseline::Baseline::from_findings(findings);
+            if let Err(e) = fs::write(bp, baseline.to_json()) {
+                eprintln!("Error writing '{}': {}", bp, e);
                 process::exit(1);
             }
+            eprintln!("Baseline of {} finding(s) written to: {}", baseline.len(), bp);
+            return;
+        }
+        None => findings.collect(),
+    };
+
+    for (path, f) in &report {
+        eprintln!("{}: {}", path, f);
+    }
+    if failed || report.iter().any(|(_, f)| f.level == jzero::config::Level::Error) {
+        process::exit(1);
+    }
+}
+
+/// Check the `.java` files of the zip archive at `path` as one project,
+/// reading them in memory.  Files are reported as `<path>/<entry>`.
+/// Returns true if the archive cannot be read, a file does not parse or
+/// two files declare the same class.
+fn check_archive(
+    path: &str,
+    config: &jzero::Config,
+    all: &mut Vec<(String, Vec<jzero::checks::Finding>)>,
+) -> bool {
+    use jzero::FileProvider;
+
+    let archive = match jzero::ZipFs::open(path) {
+        Ok(a) => a,
+        Err(e) => {
+            eprintln!("{}", e);
+            return true;
+        }
+    };
+    let mut failed = false;
+    for entry in archive.files("").unwrap_or_default() {
+        if !entry.ends_with(".java") { continue; }
+        match archive.read(&entry) {
+            Ok(source) => {
+                let findings = jzero::checks::run_checks(Path::new(&entry), &source, config);
+                all.push((format!("{}/{}", path, entry), findings));
+            }
+            Err(e) => {
+                eprintln!("{}: {}", path, e);
+                failed = true;
+            }
         }
     }
+    match jzero::Project::load_dir(&archive, "") {
+        Ok(project) => {
+            for dup in project.duplicate_classes() {
+                eprintln!("{}: error: {}", path, dup);
+                failed = true;
+            }
+        }
+        Err(e) => {
+            eprintln!("{}/{}", path, e);
+            failed = true;
+        }
+    }
+    failed
+}
+
+/// `j0 compare`: check the output for every source below `src_dir`
+/// against the reference files in the `--ref` directory, printing the
+/// first differing line of each mismatch.  Exits 1 if any differs.
+fn compare_command(args: &[String]) {
+    let mut ref_dir = None;
+    let mut src_dir = None;
+    let mut book_compat = false;
+    let mut it = args.iter();
+    while let Some(a) = it.next() {
+        if a == "--ref" {
+            ref_dir = it.next().cloned();
+        } else if let Some(d) = a.strip_prefix("--ref=") {
+            ref_dir = Some(d.to_string());
+        } else if a == "--book-compat" {
+            book_compat = true;
+        } else {
+            src_dir = Some(a.clone());
+        }
+    }
+    let (Some(ref_dir), Some(src_dir)) = (ref_dir, src_dir) else {
+        eprintln!("Usage: j0 compare --ref <expected_dir> [--book-compat] <src_dir>");
+        process::exit(1);
+    };
+
+    let found = match jzero::compare::compare(&jzero::RealFs, &src_dir, &ref_dir, book_compat) {
+        Ok(f) => f,
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(1);
+        }
+    };
+    for c in &found {
+        println!("{}", c);
+    }
+    let differing = found.iter().filter(|c| !c.is_same()).count();
+    println!("{} compared, {} differ", found.len(), differing);
+    if differing > 0 { process::exit(1); }
 }
 
 /// Derive the `.j0` output path from the source path.
@@ -0,0 +1,174 @@
+//! `j0 teach <file>` — step through the compiler one event at a time.
+//!
+//! Records every [`CompilerEvents`] callback while the source is compiled to
+//! TAC, then replays them as pages.  Each page shows the source with the
+//! current line marked, the tokens lexed so far, the node just reduced (with
+//! its subtree), and the scopes created so far.
+//!
+//! On a terminal, Enter advances one page and `q` quits.  When stdin is not
+//! a terminal every page is printed without pausing.
+
+use std::cell::RefCell;
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::rc::Rc;
+
+use jzero::{Compiler, CompilerEvents};
+use jzero_ast::tree::Tree;
+
+/// Number of recent tokens shown on each page.
+const TOKEN_WINDOW: usize = 8;
+/// Source lines shown above and below the current line.
+const SOURCE_CONTEXT: usize = 3;
+
+enum Step {
+    Token   { text: String, kind: String, line: usize },
+    Node    { sym: String, rule: i32, line: usize, subtree: String },
+    Scope   { scope: String, symbols: Vec<String> },
+    Diag    { msg: String },
+    Ir      { instr: String },
+}
+
+impl Step {
+    fn phase(&self) -> &'static str {
+        match self {
+            Step::Token { .. } => "lex",
+            Step::Node  { .. } => "parse",
+            Step::Scope { .. } => "symbol table",
+            Step::Diag  { .. } => "diagnostic",
+            Step::Ir    { .. } => "codegen",
+        }
+    }
+}
+
+#[derive(Default)]
+struct Recorder {
+    steps: RefCell<Vec<Step>>,
+}
+
+impl CompilerEvents for Recorder {
+    fn on_token(&self, tok: &jzero_lexer::SpannedToken) {
+        self.steps.borrow_mut().push(Step::Token {
+            text: tok.text.clone(),
+            kind: format!("{:?}", tok.token),
+            line: tok.line,
+        });
+    }
+
+    fn on_node_built(&self, node: &Tree) {
+        self.steps.borrow_mut().push(Step::Node {
+            sym:     node.sym.clone(),
+            rule:    node.rule,
+            line:    node.first_lineno(),
+            subtree: node.to_text(0),
+        });
+    }
+
+    fn on_scope_created(&self, scope: &jzero_symtab::SymTab) {
+        self.steps.borrow_mut().push(Step::Scope {
+            scope:   scope.scope.clone(),
+            symbols: scope.iter().map(|(name, _)| name.clone()).collect(),
+        });
+    }
+
+    fn on_diagnostic(&self, msg: &str) {
+        self.steps.borrow_mut().push(Step::Diag { msg: msg.to_string() });
+    }
+
+    fn on_ir_emitted(&self, instr: &jzero_codegen::Tac) {
+        self.steps.borrow_mut().push(Step::Ir { instr: instr.to_string() });
+    }
+}
+
+/// Compile `source` and page through every recorded event.
+pub fn teach(source: &str) {
+    let rec = Rc::new(Recorder::default());
+    let result = Compiler::new().source(source).events(rec.clone()).tac();
+
+    let steps = rec.steps.borrow();
+    let lines: Vec<&str> = source.lines().collect();
+    let interactive = io::stdin().is_terminal();
+    let mut stdin = io::stdin().lock();
+
+    let mut current_line = 1;
+    for (i, step) in steps.iter().enumerate() {
+        match step {
+            Step::Token { line, .. } | Step::Node { line, .. } if *line > 0 => current_line = *line,
+            _ => {}
+        }
+
+        let mut page = String::new();
+        page.push_str(&format!("── step {}/{} · {} ", i + 1, steps.len(), step.phase()));
+        page.push_str(&"─".repeat(40));
+        page.push('\n');
+        render_source(&mut page, &lines, current_line);
+        render_tokens(&mut page, &steps[..=i]);
+        render_step(&mut page, step);
+        render_scopes(&mut page, &steps[..=i]);
+
+        print!("{}", page);
+        if interactive {
+            print!("[Enter: next, q: quit] ");
+            let _ = io::stdout().flush();
+            let mut buf = String::new();
+            if stdin.read_line(&mut buf).is_err() || buf.trim() == "q" {
+                return;
+            }
+        }
+    }
+
+    match result {
+        Ok(_)  => println!("no errors"),
+        Err(_) => println!("compilation stopped"),
+    }
+}
+
+fn render_source(page: &mut String, lines: &[&str], current: usize) {
+    let lo = current.saturating_sub(SOURCE_CONTEXT).max(1);
+    let hi = (current + SOURCE_CONTEXT).min(lines.len());
+    page.push_str(" source:\n");
+    for n in lo..=hi {
+        let mark = if n == current { '>' } else { ' ' };
+        page.push_str(&format!(" {} {:4} | {}\n", mark, n, lines[n - 1]));
+    }
+}
+
+fn render_tokens(page: &mut String, so_far: &[Step]) {
+    let toks: Vec<&str> = so_far.iter()
+        .filter_map(|s| match s { Step::Token { text, .. } => Some(text.as_str()), _ => None })
+        .collect();
+    if toks.is_empty() { return; }
+    let start = toks.len().saturating_sub(TOKEN_WINDOW);
+    let ellipsis = if start > 0 { "… " } else { "" };
+    page.push_str(&format!(" tokens ({}): {}{}\n", toks.len(), ellipsis, toks[start..].join(" ")));
+}
+
+fn render_step(page: &mut String, step: &Step) {
+    match step {
+        Step::Token { text, kind, line } => {
+            page.push_str(&format!(" lexed {} {:?} on line {}\n", kind, text, line));
+        }
+        Step::Node { sym, rule, subtree, .. } => {
+            match jzero_ast::production::production_info(sym, *rule) {
+                Some(info) => page.push_str(&format!(" reduced {} (rule {}: {})\n", sym, rule, info.name)),
+                None => page.push_str(&format!(" reduced {} (rule {})\n", sym, rule)),
+            }
+            for l in subtree.lines() {
+                page.push_str(&format!("   {}\n", l));
+            }
+        }
+        Step::Scope { scope, symbols } => {
+            page.push_str(&format!(" scope {}: {}\n", scope, symbols.join(", ")));
+        }
+        Step::Diag { msg } => page.push_str(&format!(" error: {}\n", msg)),
+        Step::Ir { instr } => page.push_str(&format!(" emitted {}\n", instr.trim())),
+    }
+}
+
+fn render_scopes(page: &mut String, so_far: &[Step]) {
+    let scopes: Vec<&str> = so_far.iter()
+        .filter_map(|s| match s { Step::Scope { scope, .. } => Some(scope.as_str()), _ => None })
+        .collect();
+    if !scopes.is_empty() {
+        page.push_str(&format!(" scopes: {}\n", scopes.join(" → ")));
+    }
+}
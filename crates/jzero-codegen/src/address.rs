@@ -1,5 +1,5 @@
 /// A memory region in the generated program.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Region {
     /// Local stack slot, offset relative to frame pointer.
     Loc,
@@ -35,7 +35,7 @@ impl std::fmt::Display for Region {
 ///
 /// Either a region+offset pair (the common case), or a bare symbolic name
 /// used for emitting mangled method names like `PrintStream__println`.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Address {
     /// Region + integer offset.
     Regional { region: Region, offset: i64 },
@@ -0,0 +1,392 @@
+//! Phase 6 — Bounds-check elimination.
+//!
+//! Every array element access is generated as `BCHK a,i` followed by the
+//! `LOAD` or `STORE`, and the VM traps on a check that fails.  This pass
+//! removes the checks that cannot fail:
+//!
+//! - **Loop checks** — in the canonical loop
+//!   `for (i = 0; i < a.length; i += 1)`, a check of `a[i]` in the body is
+//!   redundant: the header just tested `i < a.length`, `i` starts at a
+//!   non-negative constant and only grows, at the end of the body, and
+//!   nothing in the loop assigns `a`.
+//! - **Constant checks** — `a[3]` where `a` surely holds an array made by
+//!   `new int[10]`: the sizes known from [`SemanticResult::array_sizes`]
+//!   are followed along the control-flow graph of the method, and a check
+//!   of a constant index below the size is dropped.
+//!
+//! The counts are kept in [`CodegenContext::bounds_checks`], which
+//! `j0 --codegen -v` reports.
+
+use std::collections::HashMap;
+
+use jzero_ast::tree::Tree;
+use jzero_semantic::SemanticResult;
+
+use crate::address::{Address, Region};
+use crate::context::CodegenContext;
+use crate::tac::{Op, Tac};
+
+/// What bounds-check elimination did to a program.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct BoundsCheckStats {
+    /// Checks generated, one per array element access.
+    pub checks: usize,
+    /// Checks removed from canonical loops.
+    pub removed_in_loops: usize,
+    /// Checks of a constant index into an array of known size removed.
+    pub removed_constant: usize,
+}
+
+impl BoundsCheckStats {
+    /// Checks left in the code.
+    pub fn kept(&self) -> usize {
+        self.checks - self.removed_in_loops - self.removed_constant
+    }
+}
+
+/// `bounds checks: 3 generated, 1 removed in loops, 1 removed with a constant index, 1 kept`
+impl std::fmt::Display for BoundsCheckStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "bounds checks: {} generated, {} removed in loops, {} removed with a constant index, {} kept",
+            self.checks, self.removed_in_loops, self.removed_constant, self.kept())
+    }
+}
+
+/// Remove the redundant bounds checks of every method of `tree`.
+pub fn eliminate(tree: &Tree, sem: &SemanticResult, ctx: &mut CodegenContext) {
+    let mut stats = BoundsCheckStats::default();
+    eliminate_in(tree, sem, ctx, &mut stats);
+    ctx.bounds_checks = stats;
+}
+
+fn eliminate_in(tree: &Tree, sem: &SemanticResult, ctx: &mut CodegenContext, stats: &mut BoundsCheckStats) {
+    if tree.sym == "MethodDecl" {
+        // The temporary each `new T[n]` of constant size leaves its array
+        // in; temporaries are only unique within a method.
+        let mut sizes = Sizes::new();
+        new_array_sizes(tree, sem, ctx, &mut sizes);
+        if let Some(block) = tree.kids.get(1) {
+            let info = ctx.node_mut(block.id);
+            info.icode = eliminate_checks(std::mem::take(&mut info.icode), &sizes, stats);
+        }
+        return;
+    }
+    for kid in &tree.kids {
+        eliminate_in(kid, sem, ctx, stats);
+    }
+}
+
+fn new_array_sizes(tree: &Tree, sem: &SemanticResult, ctx: &CodegenContext, sizes: &mut Sizes) {
+    if let Some(n) = sem.array_sizes.get(&tree.id)
+        && let Some(addr) = ctx.node(tree.id).and_then(|info| info.addr.clone())
+    {
+        sizes.insert(addr, *n);
+    }
+    for kid in &tree.kids {
+        new_array_sizes(kid, sem, ctx, sizes);
+    }
+}
+
+/// The code of one method without its redundant checks.  `sizes` gives
+/// the length of the array in each address that `NEWARRAY` leaves one of
+/// known size in.
+pub fn eliminate_checks(icode: Vec<Tac>, sizes: &Sizes, stats: &mut BoundsCheckStats) -> Vec<Tac> {
+    let mut redundant = vec![false; icode.len()];
+    stats.checks += icode.iter().filter(|t| t.op == Op::Bchk).count();
+    for at in loop_checks(&icode) {
+        redundant[at] = true;
+        stats.removed_in_loops += 1;
+    }
+    for at in constant_checks(&icode, sizes) {
+        if !redundant[at] {
+            redundant[at] = true;
+            stats.removed_constant += 1;
+        }
+    }
+    icode.into_iter().zip(redundant).filter(|(_, r)| !r).map(|(t, _)| t).collect()
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// Canonical loops
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// The checks of `a[i]` in the body of each canonical loop over `a`.
+///
+/// The loop is the code from a label `L` to the last `GOTO L` after it,
+/// and must look like
+///
+/// ```text
+///     ASN i,imm:n            (n >= 0, in the block before the loop)
+/// L:  ASIZE t,a
+///     BLT Lb,i,t
+///     GOTO exit              (exit after the loop)
+/// Lb: ...                    (no assignment to i or a)
+///     ADD t2,i,imm:k         (k >= 0)
+///     ASN i,t2
+///     GOTO L
+/// ```
+fn loop_checks(icode: &[Tac]) -> Vec<usize> {
+    let mut found = Vec::new();
+    for (head, tac) in icode.iter().enumerate() {
+        if tac.op != Op::Lab { continue; }
+        let label = tac.op1.as_ref();
+        let Some(back) = icode.iter().rposition(|t| t.op == Op::Goto && t.op1.as_ref() == label) else { continue };
+        if back <= head { continue; }
+        let Some((array, index)) = canonical_loop(icode, head, back) else { continue };
+        found.extend((head..back).filter(|&at| {
+            let t = &icode[at];
+            t.op == Op::Bchk && t.op1.as_ref() == Some(array) && t.op2.as_ref() == Some(index)
+        }));
+    }
+    found
+}
+
+/// The array and the induction variable of the loop from the label at
+/// `head` to the `GOTO` at `back`, if it is a canonical one.
+fn canonical_loop(icode: &[Tac], head: usize, back: usize) -> Option<(&Address, &Address)> {
+    let [asize, blt, exit, body] = icode.get(head + 1..head + 5)? else { return None };
+    let size = asize.op1.as_ref()?;
+    let array = asize.op2.as_ref()?;
+    let index = blt.op2.as_ref()?;
+    if asize.op != Op::Asize || blt.op != Op::Blt || blt.op3.as_ref() != Some(size)
+        || exit.op != Op::Goto || body.op != Op::Lab || blt.op1 != body.op1
+    {
+        return None;
+    }
+    if !label_after(icode, exit.op1.as_ref()?, back) { return None; }
+
+    // The update ends the body: `ADD t2,i,imm:k; ASN i,t2`, then the
+    // jump back, perhaps with the label a `continue` jumps to between.
+    let mut end = back;
+    while end > head && icode[end - 1].op == Op::Lab { end -= 1; }
+    let (add, asn) = (icode.get(end.checked_sub(2)?)?, &icode[end - 1]);
+    if add.op != Op::Add || add.op2.as_ref() != Some(index) || imm(add.op3.as_ref())? < 0
+        || asn.op != Op::Asn || asn.op1.as_ref() != Some(index) || asn.op2 != add.op1
+    {
+        return None;
+    }
+    let body = &icode[head..end - 2];
+    if body.iter().any(|t| writes(t) == Some(index) || writes(t) == Some(array)) {
+        return None;
+    }
+    // A call may assign a global.
+    let local = |a: &Address| matches!(a, Address::Regional { region: Region::Loc, .. });
+    if body.iter().any(|t| t.op == Op::Call) && !(local(array) && local(index)) {
+        return None;
+    }
+    // Nothing outside the loop may jump into it past the header.
+    let inside: Vec<&Address> = body[1..].iter().filter(|t| t.op == Op::Lab).filter_map(|t| t.op1.as_ref()).collect();
+    let outside = icode[..head].iter().chain(&icode[back + 1..]);
+    if outside.filter(|t| is_jump(t)).any(|t| t.op1.as_ref().is_some_and(|l| inside.contains(&l))) {
+        return None;
+    }
+
+    // `i` starts non-negative: its last assignment in the block before
+    // the loop is a constant.
+    let mut before = icode[..head].iter().rev().take_while(|t| t.op != Op::Lab && !is_jump(t));
+    let init = before.find(|t| writes(t) == Some(index))?;
+    (init.op == Op::Asn && imm(init.op2.as_ref())? >= 0).then_some((array, index))
+}
+
+/// Whether `label` is defined after position `at`.
+fn label_after(icode: &[Tac], label: &Address, at: usize) -> bool {
+    icode[at + 1..].iter().any(|t| t.op == Op::Lab && t.op1.as_ref() == Some(label))
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// Constant indices
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Lengths arrays are known to have at least, by the address holding
+/// the array.
+pub type Sizes = HashMap<Address, i64>;
+
+/// A basic block: the instructions `start..end` and the blocks control
+/// may go to next.
+struct Block {
+    start: usize,
+    end: usize,
+    succs: Vec<usize>,
+}
+
+/// Split `icode` into basic blocks: one starts at each label and after
+/// each jump.
+fn basic_blocks(icode: &[Tac]) -> Vec<Block> {
+    let mut starts = vec![0];
+    for (at, t) in icode.iter().enumerate() {
+        if t.op == Op::Lab && at > 0 { starts.push(at); }
+        if is_jump(t) || t.op == Op::Ret { starts.push(at + 1); }
+    }
+    starts.retain(|&s| s < icode.len());
+    starts.dedup();
+
+    let block_of = |label: &Address| {
+        let at = icode.iter().position(|t| t.op == Op::Lab && t.op1.as_ref() == Some(label))?;
+        starts.iter().position(|&s| s == at)
+    };
+    let mut blocks = Vec::new();
+    for (b, &start) in starts.iter().enumerate() {
+        let end = starts.get(b + 1).copied().unwrap_or(icode.len());
+        let last = &icode[end - 1];
+        let mut succs = Vec::new();
+        if is_jump(last) {
+            succs.extend(last.op1.as_ref().and_then(block_of));
+        }
+        if last.op != Op::Goto && last.op != Op::Ret && end < icode.len() {
+            succs.push(b + 1);
+        }
+        blocks.push(Block { start, end, succs });
+    }
+    blocks
+}
+
+/// The checks of a constant index inside the known size of the array.
+///
+/// On entry to a block an array is known to have the fewest elements any
+/// block leading to it leaves it with; the sizes are found by iterating
+/// to a fixed point.
+fn constant_checks(icode: &[Tac], new_sizes: &Sizes) -> Vec<usize> {
+    if icode.is_empty() { return Vec::new(); }
+    let blocks = basic_blocks(icode);
+    // `None`: not reached yet.
+    let mut entry: Vec<Option<Sizes>> = vec![None; blocks.len()];
+    entry[0] = Some(Sizes::new());
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for b in 0..blocks.len() {
+            let Some(mut known) = entry[b].clone() else { continue };
+            for t in &icode[blocks[b].start..blocks[b].end] {
+                transfer(t, &mut known, new_sizes);
+            }
+            for &s in &blocks[b].succs {
+                let merged = match &entry[s] {
+                    None => known.clone(),
+                    Some(old) => old.iter()
+                        .filter_map(|(a, n)| Some((a.clone(), *n.min(known.get(a)?))))
+                        .collect(),
+                };
+                if entry[s].as_ref() != Some(&merged) {
+                    entry[s] = Some(merged);
+                    changed = true;
+                }
+            }
+        }
+    }
+
+    let mut found = Vec::new();
+    for (b, block) in blocks.iter().enumerate() {
+        let Some(mut known) = entry[b].clone() else { continue };
+        for (at, t) in icode.iter().enumerate().take(block.end).skip(block.start) {
+            if t.op == Op::Bchk
+                && let (Some(array), Some(k)) = (t.op1.as_ref(), imm(t.op2.as_ref()))
+                && known.get(array).is_some_and(|&n| (0..n).contains(&k))
+            {
+                found.push(at);
+            }
+            transfer(t, &mut known, new_sizes);
+        }
+    }
+    found
+}
+
+/// Update the sizes `known` past instruction `t`.
+fn transfer(t: &Tac, known: &mut Sizes, new_sizes: &Sizes) {
+    if t.op == Op::Call {
+        // The callee may assign any global.
+        known.retain(|a, _| matches!(a, Address::Regional { region: Region::Loc, .. }));
+    }
+    let Some(dst) = writes(t) else { return };
+    let size = match t.op {
+        Op::NewArray => new_sizes.get(dst).copied(),
+        Op::Asn => t.op2.as_ref().and_then(|src| known.get(src).copied()),
+        _ => None,
+    };
+    match size {
+        Some(n) => { known.insert(dst.clone(), n); }
+        None => { known.remove(dst); }
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// Helpers
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// The address `t` assigns, if any.
+fn writes(t: &Tac) -> Option<&Address> {
+    match t.op {
+        Op::Add | Op::Sub | Op::Mul | Op::Div | Op::Mod | Op::Neg | Op::Sadd | Op::Fmul | Op::Fdiv
+        | Op::Asn | Op::Addr | Op::Asize | Op::Load | Op::NewArray | Op::Itos | Op::Ftos => t.op1.as_ref(),
+        _ => None,
+    }
+}
+
+fn is_jump(t: &Tac) -> bool {
+    matches!(t.op, Op::Goto | Op::Blt | Op::Ble | Op::Bgt | Op::Bge | Op::Beq | Op::Bne)
+}
+
+fn imm(a: Option<&Address>) -> Option<i64> {
+    match a? {
+        Address::Regional { region: Region::Imm, offset } => Some(*offset),
+        _ => None,
+    }
+}
+
+// ─── Tests ───────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use jzero_parser::parse_tree;
+    use jzero_semantic::analyze;
+
+    use super::*;
+    use crate::{emit::emit, generate};
+
+    /// The emitted code of `body` as the body of `main`, and the stats.
+    fn compile(body: &str) -> (String, BoundsCheckStats) {
+        let src = format!("public class t {{ public static void main(String argv[]) {{ {} }} }}", body);
+        let mut tree = parse_tree(&src).expect("parse failed");
+        let sem = analyze(&mut tree);
+        assert!(sem.errors.is_empty(), "{:?}", sem.errors);
+        let ctx = generate(&tree, &sem);
+        (emit(&tree, &ctx), ctx.bounds_checks)
+    }
+
+    const DECLS: &str = "int a[]; int i; int s; a = new int[10]; s = 0;";
+
+    #[test]
+    fn canonical_loop_needs_no_check() {
+        let (out, stats) = compile(&format!("{} for (i = 0; i < a.length; i += 1) {{ s = s + a[i]; }}", DECLS));
+        assert!(!out.contains("BCHK"), "{}", out);
+        assert_eq!(stats, BoundsCheckStats { checks: 1, removed_in_loops: 1, removed_constant: 0 });
+    }
+
+    #[test]
+    fn loops_that_move_the_index_keep_their_checks() {
+        for header in [
+            "for (i = 0; i < a.length; i += 1) { i = i + 1; s = s + a[i]; }",
+            "for (i = 0; i < a.length; i -= 1) { s = s + a[i]; }",
+            "for (i = 0; i < a.length; i += 1) { s = s + a[i + 1]; }",
+            "for (i = 0; i < a.length; i += 1) { a = new int[1]; s = s + a[i]; }",
+            "i = 0 - 1; while (i < a.length) { s = s + a[i]; i += 1; }",
+        ] {
+            let (out, stats) = compile(&format!("{} {}", DECLS, header));
+            assert_eq!(stats.kept(), 1, "{}\n{}", header, out);
+        }
+    }
+
+    #[test]
+    fn constant_index_inside_a_known_size() {
+        let (out, stats) = compile(&format!("{} s = a[3]; s = a[12]; if (s < 1) {{ s = a[9]; }} s = a[0];", DECLS));
+        assert_eq!((stats.checks, stats.removed_constant), (4, 3));
+        assert_eq!(out.lines().filter(|l| l.starts_with("BCHK")).collect::<Vec<_>>(), ["BCHK loc:16,imm:12"]);
+    }
+
+    #[test]
+    fn sizes_not_known_on_every_path_are_forgotten() {
+        let (_, stats) = compile("int a[]; int s; s = 0; if (s < 1) { a = new int[10]; } else { a = new int[2]; } s = a[5];");
+        assert_eq!((stats.checks, stats.kept()), (1, 1));
+        let (_, stats) = compile("int a[]; int s; s = 0; if (s < 1) { a = new int[10]; } else { a = new int[8]; } s = a[5];");
+        assert_eq!(stats.removed_constant, 1);
+    }
+}
@@ -48,6 +48,25 @@ pub enum Op {
     Sadd   = 26,
 
     Itos   = 27,
+    // ── Arrays ──────────────────────────────────────────────────────────────
+    /// Pop a length, allocate that many zeroed words on the heap, push the
+    /// array.
+    Newarray = 28,
+    /// Pop an index and an array, push the element.
+    Aload  = 29,
+    /// Pop a value, an index and an array, store the value as the element.
+    Astore = 30,
+    /// Pop an array, push its length.
+    Asize  = 31,
+    /// Pop an index and an array, trap unless the index is in bounds.
+    Bchk   = 32,
+    // ── Doubles ─────────────────────────────────────────────────────────────
+    /// Pop two fixed-point doubles, push their product.
+    Fmul   = 33,
+    /// Pop two fixed-point doubles, push their quotient.
+    Fdiv   = 34,
+    /// Pop a fixed-point double, push the pool key of its decimal text.
+    Ftos   = 35,
 }
 
 impl Op {
@@ -80,6 +99,14 @@ impl Op {
             Op::Spop   => "spop",
             Op::Sadd   => "sadd",
             Op::Itos   => "itos",
+            Op::Newarray => "newarray",
+            Op::Aload  => "aload",
+            Op::Astore => "astore",
+            Op::Asize  => "asize",
+            Op::Bchk   => "bchk",
+            Op::Fmul   => "fmul",
+            Op::Fdiv   => "fdiv",
+            Op::Ftos   => "ftos",
         }
     }
 
@@ -112,11 +139,28 @@ impl Op {
             25 => Some(Op::Spop),
             26 => Some(Op::Sadd),
             27 => Some(Op::Itos),
+            28 => Some(Op::Newarray),
+            29 => Some(Op::Aload),
+            30 => Some(Op::Astore),
+            31 => Some(Op::Asize),
+            32 => Some(Op::Bchk),
+            33 => Some(Op::Fmul),
+            34 => Some(Op::Fdiv),
+            35 => Some(Op::Ftos),
             _  => None,
         }
     }
 }
 
+/// The fractional bits of a `double`, which the VM keeps in a word as a
+/// fixed-point number: a double `d` is the integer `d * FIXED_ONE`.  It
+/// keeps about 7 decimal digits after the point, and an immediate
+/// operand an integer part below 2^23.
+pub const FIXED_BITS: u32 = 24;
+
+/// The fixed-point `1.0`.
+pub const FIXED_ONE: i64 = 1 << FIXED_BITS;
+
 // ---------------------------------------------------------------------------
 // Operand regions (Chapter 12, R_* constants)
 // ---------------------------------------------------------------------------
@@ -305,7 +349,7 @@ mod tests {
 
     #[test]
     fn opcode_roundtrip() {
-        for v in 1u8..=26 {
+        for v in 1u8..=35 {
             let op = Op::from_u8(v).unwrap();
             assert_eq!(op as u8, v);
         }
@@ -13,12 +13,16 @@
 //! ## Pass 2 — patch
 //! Walk `rv` again.  Every `Byc` whose `needs_patch` flag is set has its `opnd`
 //! replaced by the byte offset looked up from `labeltable`.
+//!
+//! Each `Byc` keeps the source line of the TAC instruction it came from;
+//! [`translate_with_lines`] returns them alongside, for the line table.
 
 use std::collections::HashMap;
 
 use crate::{
     address::{Address, Region},
     byc::{Byc, Op},
+    gencode::native_symbol,
     tac::{Op as TacOp, Tac},
 };
 
@@ -26,23 +30,48 @@ use crate::{
 // Public entry point
 // ---------------------------------------------------------------------------
 
+/// Function addresses at or below this one are native methods: import
+/// number `i` is called at `NATIVE_BASE - i`.  The VM's own built-ins
+/// (`println` and friends) are at -1, -2, …
+pub const NATIVE_BASE: i64 = -100;
+
+/// The runtime built-ins by mangled name, and the function address `CALL`
+/// reaches each at.
+pub const BUILTINS: &[(&str, i64)] = &[
+    ("PrintStream__println", -1),
+    ("InputStream__readLine", -2),
+    ("System__currentTimeMillis", -3),
+    ("Math__randomInt", -4),
+    ("assertEquals", -5),
+    ("Math__random", -6),
+];
+
 /// Translate a slice of TAC instructions into bytecode.
 ///
 /// Returns:
 /// - `Vec<Byc>` — the bytecode instruction sequence (all labels resolved)
 /// - `HashMap<i64, usize>` — labeltable mapping label-id → byte offset
 pub fn translate(icode: &[Tac]) -> (Vec<Byc>, HashMap<i64, usize>) {
-    let (mut bycs, labeltable) = pass1(icode);
-    pass2(&mut bycs, &labeltable);
+    let (bycs, labeltable, _) = translate_with_lines(icode, &[]);
     (bycs, labeltable)
 }
 
+/// [`translate`], calling the native methods `natives` (by qualified name,
+/// in import order) at their addresses, and also returning the source
+/// line of each `Byc` (0 if unknown), index for index.
+pub fn translate_with_lines(icode: &[Tac], natives: &[String]) -> (Vec<Byc>, HashMap<i64, usize>, Vec<usize>) {
+    let (mut bycs, labeltable, lines) = pass1(icode, natives);
+    pass2(&mut bycs, &labeltable);
+    (bycs, labeltable, lines)
+}
+
 // ---------------------------------------------------------------------------
 // Pass 1 — emit
 // ---------------------------------------------------------------------------
 
-fn pass1(icode: &[Tac]) -> (Vec<Byc>, HashMap<i64, usize>) {
+fn pass1(icode: &[Tac], natives: &[String]) -> (Vec<Byc>, HashMap<i64, usize>, Vec<usize>) {
     let mut rv: Vec<Byc> = Vec::new();
+    let mut lines: Vec<usize> = Vec::new();
     let mut labeltable: HashMap<i64, usize> = HashMap::new();
     // Tracks whether the method address has been pushed ahead of the first
     // PARM in a call sequence (reset to false after each CALL).
@@ -57,6 +86,8 @@ fn pass1(icode: &[Tac]) -> (Vec<Byc>, HashMap<i64, usize>) {
             TacOp::Sub => emit_binary(&mut rv, Op::Sub,  instr),
             TacOp::Mul => emit_binary(&mut rv, Op::Mul,  instr),
             TacOp::Div => emit_binary(&mut rv, Op::Div,  instr),
+            TacOp::Fmul => emit_binary(&mut rv, Op::Fmul, instr),
+            TacOp::Fdiv => emit_binary(&mut rv, Op::Fdiv, instr),
             TacOp::Mod => emit_binary(&mut rv, Op::Mod,  instr),
 
             // ----------------------------------------------------------------
@@ -125,7 +156,7 @@ fn pass1(icode: &[Tac]) -> (Vec<Byc>, HashMap<i64, usize>) {
                     continue;
                 }
                 if !method_addr_pushed {
-                    if let Some(call_addr) = find_call_addr(icode, i) {
+                    if let Some(call_addr) = find_call_addr(icode, i, natives) {
                         rv.push(call_addr);
                     }
                     method_addr_pushed = true;
@@ -134,8 +165,17 @@ fn pass1(icode: &[Tac]) -> (Vec<Byc>, HashMap<i64, usize>) {
             }
 
             TacOp::Call => {
+                // A call with no PARM to push it ahead gets its method
+                // address here.
+                if !method_addr_pushed {
+                    rv.push(call_addr(instr, natives));
+                }
                 // op2 holds the arg count (as an Imm address).
                 rv.push(Byc::new(Op::Call, instr.op2.as_ref()));
+                // A native method leaves its result, if any, on the stack.
+                if instr.op3.is_some() {
+                    rv.push(Byc::new(Op::Pop, instr.op3.as_ref()));
+                }
                 method_addr_pushed = false;
             }
 
@@ -169,30 +209,35 @@ fn pass1(icode: &[Tac]) -> (Vec<Byc>, HashMap<i64, usize>) {
                 rv.push(Byc::new(Op::Load, instr.op1.as_ref()));
             }
 
-            // ASIZE: get array length. In our VM the argv slot holds the
-            // length directly (not a pointer), so treat as a direct copy.
-            TacOp::Asize => {
-                rv.push(Byc::new(Op::Push, instr.op2.as_ref()));
-                rv.push(Byc::new(Op::Pop,  instr.op1.as_ref()));
-            }
+            // ASIZE: PUSH op2, ASIZE, POP op1 — the VM keeps each array's
+            // length on the heap, argv's included.
+            TacOp::Asize => emit_unary(&mut rv, Op::Asize, instr),
 
-            // NewArray: treated as ASN for now (address already computed
-            // by the runtime; a real heap allocator would go here).
-            TacOp::NewArray => {
-                rv.push(Byc::new(Op::Push, instr.op2.as_ref()));
-                rv.push(Byc::new(Op::Pop,  instr.op1.as_ref()));
+            // NEWARRAY: PUSH op2, NEWARRAY, POP op1.
+            TacOp::NewArray => emit_unary(&mut rv, Op::Newarray, instr),
+
+            // THROW: the VM cannot unwind to a handler, so the exception
+            // ends the program.
+            TacOp::Throw => {
+                rv.push(Byc::no_operand(Op::Halt));
             }
 
-            // Load through pointer (LCON equivalent).
-            TacOp::Load => {
-                rv.push(Byc::new(Op::Load, instr.op2.as_ref()));
-                rv.push(Byc::new(Op::Pop,  instr.op1.as_ref()));
+            // Element load: PUSH op2, PUSH op3, ALOAD, POP op1.
+            TacOp::Load => emit_binary(&mut rv, Op::Aload, instr),
+
+            // Bounds check: PUSH op1, PUSH op2, BCHK.
+            TacOp::Bchk => {
+                rv.push(Byc::new(Op::Push, instr.op1.as_ref()));
+                rv.push(Byc::new(Op::Push, instr.op2.as_ref()));
+                rv.push(Byc::no_operand(Op::Bchk));
             }
 
-            // Store through pointer (SCON equivalent).
+            // Element store: PUSH op1, PUSH op2, PUSH op3, ASTORE.
             TacOp::Store => {
-                rv.push(Byc::new(Op::Store, instr.op2.as_ref()));
-                rv.push(Byc::new(Op::Pop,   instr.op1.as_ref()));
+                rv.push(Byc::new(Op::Push, instr.op1.as_ref()));
+                rv.push(Byc::new(Op::Push, instr.op2.as_ref()));
+                rv.push(Byc::new(Op::Push, instr.op3.as_ref()));
+                rv.push(Byc::no_operand(Op::Astore));
             }
 
             // ----------------------------------------------------------------
@@ -206,10 +251,18 @@ fn pass1(icode: &[Tac]) -> (Vec<Byc>, HashMap<i64, usize>) {
                 rv.push(Byc::no_operand(Op::Itos));
                 rv.push(Byc::new(Op::Pop,  instr.op1.as_ref()));
             }
+
+            TacOp::Ftos => {
+                // PUSH the double, FTOS converts TOS to a string pool key.
+                rv.push(Byc::new(Op::Push, instr.op2.as_ref()));
+                rv.push(Byc::no_operand(Op::Ftos));
+                rv.push(Byc::new(Op::Pop,  instr.op1.as_ref()));
+            }
         }
+        lines.resize(rv.len(), instr.line);
     }
 
-    (rv, labeltable)
+    (rv, labeltable, lines)
 }
 
 // ---------------------------------------------------------------------------
@@ -262,20 +315,30 @@ fn emit_branch(rv: &mut Vec<Byc>, cmp: Op, instr: &Tac) {
 
 /// Scan forward from `start` to find the nearest CALL instruction and return
 /// a `Byc` that pushes the method address.
+fn find_call_addr(icode: &[Tac], start: usize, natives: &[String]) -> Option<Byc> {
+    icode[start + 1..].iter()
+        .find(|instr| instr.op == TacOp::Call)
+        .map(|call| call_addr(call, natives))
+}
+
+/// A `Byc` pushing the address of the method `call` calls.
 ///
-/// - Named symbols (e.g. `PrintStream__println`) → `PUSH imm:-1`
+/// - Native methods → `PUSH imm:<NATIVE_BASE - import number>`
+/// - Runtime built-ins → `PUSH imm:<its address in BUILTINS>`
+/// - Other named symbols → `PUSH imm:-1`
 /// - All other addresses → `PUSH <addr>`
-fn find_call_addr(icode: &[Tac], start: usize) -> Option<Byc> {
-    for instr in &icode[start + 1..] {
-        if instr.op == TacOp::Call {
-            // op1 is the method name/address in our TAC (see gencode.rs).
-            return Some(match &instr.op1 {
-                Some(Address::Symbol(_)) => Byc::imm(Op::Push, -1),
-                other => Byc::new(Op::Push, other.as_ref()),
-            });
+fn call_addr(call: &Tac, natives: &[String]) -> Byc {
+    // op1 is the method name/address in our TAC (see gencode.rs).
+    match &call.op1 {
+        Some(Address::Symbol(name)) => {
+            if let Some(i) = natives.iter().position(|q| native_symbol(q) == *name) {
+                return Byc::imm(Op::Push, NATIVE_BASE - i as i64);
+            }
+            let builtin = BUILTINS.iter().find(|(b, _)| b == name);
+            Byc::imm(Op::Push, builtin.map_or(-1, |(_, addr)| *addr))
         }
+        other => Byc::new(Op::Push, other.as_ref()),
     }
-    None
 }
 
 /// Extract the integer value from an `Imm` address, defaulting to 0.
@@ -305,7 +368,7 @@ mod tests {
 
     // Helper: build a minimal TAC instruction.
     fn tac(op: TacOp, op1: Option<Address>, op2: Option<Address>, op3: Option<Address>) -> Tac {
-        Tac { op, op1, op2, op3 }
+        Tac { op, op1, op2, op3, line: 0 }
     }
 
     #[test]
@@ -332,6 +395,16 @@ mod tests {
         assert_eq!(bycs[1].op, Op::Pop);
     }
 
+    #[test]
+    fn element_store_pushes_array_index_and_value() {
+        let icode = vec![
+            tac(TacOp::Store, Some(loc(16)), Some(imm(1)), Some(imm(5))),
+        ];
+        let (bycs, _) = translate(&icode);
+        let ops: Vec<Op> = bycs.iter().map(|b| b.op).collect();
+        assert_eq!(ops, [Op::Push, Op::Push, Op::Push, Op::Astore]);
+    }
+
     #[test]
     fn label_recorded_in_labeltable() {
         let icode = vec![
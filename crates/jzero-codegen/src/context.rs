@@ -1,6 +1,8 @@
 use std::collections::HashMap;
 use crate::address::{Address, Region};
+use crate::bounds::BoundsCheckStats;
 use crate::tac::Tac;
+use jzero_semantic::ConstValue;
 
 /// Per-node codegen state, stored parallel to the AST.
 /// Keyed by `Tree::id`.
@@ -49,6 +51,26 @@ pub struct CodegenContext {
     pub globals: Vec<(String, Address)>,
     /// Next offset in the global region.
     global_offset: i64,
+    /// What bounds-check elimination removed.
+    pub bounds_checks: BoundsCheckStats,
+    /// The native methods called, by qualified name (`Robot.move`); each
+    /// one's index is its import number in the bytecode.
+    pub natives: Vec<String>,
+    /// The value of each use of a named constant, from
+    /// [`SemanticResult::constants`](jzero_semantic::SemanticResult::constants).
+    pub constants: HashMap<u32, ConstValue>,
+    /// The `if` and `while` statements whose condition folded to a
+    /// constant, and whose dead branch was dropped.
+    pub folded_conditions: usize,
+    /// The code of the initializers of the static fields of every class,
+    /// run once at startup before the entry point.
+    pub class_init: Vec<Tac>,
+    /// The code of the initializers of each class's other fields, by class
+    /// name, run by each `new` of the class.
+    pub instance_init: HashMap<String, Vec<Tac>>,
+    /// The constructs the VM cannot run, as `line N: ...` messages.  The
+    /// code is incomplete unless this is empty.
+    pub errors: Vec<String>,
 }
 
 impl CodegenContext {
@@ -62,6 +84,13 @@ impl CodegenContext {
             strings_offset: 0,
             globals:        Vec::new(),
             global_offset:  0,
+            bounds_checks:  BoundsCheckStats::default(),
+            natives:        Vec::new(),
+            constants:      HashMap::new(),
+            folded_conditions: 0,
+            class_init:     Vec::new(),
+            instance_init:  HashMap::new(),
+            errors:         Vec::new(),
         }
     }
 
@@ -124,6 +153,19 @@ impl CodegenContext {
         Address::new(Region::Strings, offset)
     }
 
+    // ── Native methods ───────────────────────────────────────────────────────
+
+    /// The import number of native method `qualified`, added if new.
+    pub fn native_index(&mut self, qualified: &str) -> usize {
+        match self.natives.iter().position(|n| n == qualified) {
+            Some(i) => i,
+            None => {
+                self.natives.push(qualified.to_string());
+                self.natives.len() - 1
+            }
+        }
+    }
+
     // ── Variable address lookup ──────────────────────────────────────────────
 
     /// Look up the address assigned to a variable.
@@ -1,9 +1,13 @@
 //! Phase 4 — Intermediate code emission.
 
 use jzero_ast::tree::Tree;
-use jzero_symtab::SymTab;
+use jzero_lexer::escape::char_value;
+use jzero_semantic::{case_value, eval_const, eval_const_with, ConstValue};
+use jzero_symtab::entry::SymbolKind;
+use jzero_symtab::{SymTab, SymbolOrigin};
 
-use crate::address::Address;
+use crate::address::{Address, Region};
+use crate::byc::FIXED_ONE;
 use crate::context::CodegenContext;
 use crate::layout::var_key;
 use crate::tac::{Op, Tac};
@@ -13,6 +17,12 @@ use crate::tac::{Op, Tac};
 // ═══════════════════════════════════════════════════════════════════════════════
 
 pub fn gencode(tree: &Tree, ctx: &mut CodegenContext) {
+    // A field's initializer was generated ahead of the methods, by
+    // gen_field_inits.
+    if tree.sym == "FieldDecl" {
+        return;
+    }
+
     // Special case: MethodCall where kids[0] is a FieldAccess chain.
     // We must NOT recurse into kids[0] — it's the method name, not a value
     // to load. Instead we handle recursion manually inside gen_method_call_field.
@@ -22,6 +32,7 @@ pub fn gencode(tree: &Tree, ctx: &mut CodegenContext) {
             && tree.kids[0].sym == "FieldAccess";
         if name_is_field_access {
             gen_method_call_field(tree, ctx);
+            stamp_lines(tree, ctx);
             return;
         }
     }
@@ -37,6 +48,8 @@ pub fn gencode(tree: &Tree, ctx: &mut CodegenContext) {
         "MulExpr"              => gen_mul_expr(tree, ctx),
         "UnaryMinus"           => gen_unary_minus(tree, ctx),
         "UnaryNot"             => gen_unary_not(tree, ctx),
+        "CastExpr"             => gen_cast(tree, ctx),
+        "InstanceOfExpr"       => gen_instanceof(tree, ctx),
         "RelExpr"              => gen_rel_expr(tree, ctx),
         "EqExpr"               => gen_eq_expr(tree, ctx),
         "CondAndExpr"          => gen_cond_and(tree, ctx),
@@ -44,6 +57,8 @@ pub fn gencode(tree: &Tree, ctx: &mut CodegenContext) {
         "Assignment"           => gen_assignment(tree, ctx),
         "ArrayAccess"          => gen_array_access(tree, ctx),
         "ArrayCreation"        => gen_array_creation(tree, ctx),
+        "ArrayInit"            => gen_array_init(tree, ctx),
+        "VarDeclarator" if tree.rule == 2 => gen_var_init(tree, ctx),
         "InstanceCreation"     => gen_instance_creation(tree, ctx),
         "MethodCall"           => gen_method_call(tree, ctx),
         "FieldAccess"          => gen_field_access(tree, ctx),
@@ -53,8 +68,22 @@ pub fn gencode(tree: &Tree, ctx: &mut CodegenContext) {
         "WhileStmt"            => gen_while(tree, ctx),
         "ForStmt"              => gen_for(tree, ctx),
         "BreakStmt"            => gen_break(tree, ctx),
+        "SwitchStmt"           => gen_switch(tree, ctx),
+        "TryStmt"              => gen_try(tree, ctx),
+        "ThrowStmt"            => gen_throw(tree, ctx),
         _                      => default_concat(tree, ctx),
     }
+    stamp_lines(tree, ctx);
+}
+
+/// Give the instructions `tree` generated itself — those its children did
+/// not — the line `tree` starts on.
+fn stamp_lines(tree: &Tree, ctx: &mut CodegenContext) {
+    let line = tree.first_lineno();
+    if line == 0 || ctx.node(tree.id).is_none() { return; }
+    for instr in ctx.node_mut(tree.id).icode.iter_mut().filter(|t| t.line == 0) {
+        instr.line = line;
+    }
 }
 
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -65,9 +94,14 @@ fn gen_leaf(tree: &Tree, ctx: &mut CodegenContext) {
     let tok = match &tree.tok { Some(t) => t, None => return };
     let addr = match tok.category.as_str() {
         "INTLIT"     => { let v: i64 = tok.text.parse().unwrap_or(0); Some(Address::imm(v)) }
-        "DOUBLELIT"  => Some(ctx.intern_string(&tok.text)),
+        "LONGLIT"    => { let v: i64 = tok.text.trim_end_matches(['l', 'L']).parse().unwrap_or(0); Some(Address::imm(v)) }
+        "DOUBLELIT"  => { let v: f64 = tok.text.trim_end_matches(['d', 'D']).parse().unwrap_or(0.0); Some(Address::imm(fixed_point(v))) }
         "BOOLLIT"    => Some(Address::imm(if tok.text == "true" { 1 } else { 0 })),
-        "STRINGLIT"  => { let raw = tok.text.trim_matches('"'); Some(ctx.intern_string(raw)) }
+        "STRINGLIT"  => {
+            let value = tok.sval.as_deref().unwrap_or_else(|| tok.text.trim_matches('"'));
+            Some(ctx.intern_string(value))
+        }
+        "CHARLIT"    => char_value(&tok.text).map(|c| Address::imm(c as i64)),
         "NULL"       => Some(Address::imm(0)),
         "IDENTIFIER" => lookup_var(tree, ctx),
         _            => None,
@@ -108,6 +142,11 @@ fn gen_add_expr(tree: &Tree, ctx: &mut CodegenContext) {
     };
 
     let mut icode = concat_kids_icode(tree, ctx);
+    let (lhs, rhs) = if !is_string && is_double(tree) {
+        (as_double(&tree.kids[0], &mut icode, ctx), as_double(&tree.kids[2], &mut icode, ctx))
+    } else {
+        (lhs, rhs)
+    };
     icode.push(Tac::new3(op, dst.clone(), lhs, rhs));
     let info = ctx.node_mut(tree.id);
     info.icode = icode; info.addr = Some(dst);
@@ -118,8 +157,13 @@ fn gen_mul_expr(tree: &Tree, ctx: &mut CodegenContext) {
     let dst = ctx.genlocal();
     let lhs = addr_of(&tree.kids[0], ctx);
     let rhs = addr_of(&tree.kids[2], ctx);
-    let op  = match tree.rule { 0 => Op::Mul, 1 => Op::Div, _ => Op::Mod };
     let mut icode = concat_kids_icode(tree, ctx);
+    let (op, lhs, rhs) = if is_double(tree) {
+        let op = match tree.rule { 0 => Op::Fmul, 1 => Op::Fdiv, _ => Op::Mod };
+        (op, as_double(&tree.kids[0], &mut icode, ctx), as_double(&tree.kids[2], &mut icode, ctx))
+    } else {
+        (match tree.rule { 0 => Op::Mul, 1 => Op::Div, _ => Op::Mod }, lhs, rhs)
+    };
     icode.push(Tac::new3(op, dst.clone(), lhs, rhs));
     let info = ctx.node_mut(tree.id);
     info.icode = icode; info.addr = Some(dst);
@@ -145,6 +189,45 @@ fn gen_unary_not(tree: &Tree, ctx: &mut CodegenContext) {
     info.icode = icode; info.addr = Some(dst);
 }
 
+/// The VM keeps every number in one word, a double as a fixed-point
+/// one, so a cast between an int and a double scales the operand and any
+/// other changes only the type.  A cast that folds, such as `(int) 7.9`,
+/// is lowered to the folded value, the one a constant initialized by it
+/// gets.
+fn gen_cast(tree: &Tree, ctx: &mut CodegenContext) {
+    if tree.kids.len() < 2 { return default_concat(tree, ctx); }
+    let folded = match eval_const_with(tree, &ctx.constants) {
+        Some(ConstValue::Int(n)) => Some(n),
+        Some(ConstValue::Double(d)) => Some(fixed_point(d)),
+        _ => None,
+    };
+    if let Some(n) = folded {
+        let info = ctx.node_mut(tree.id);
+        info.icode = vec![]; info.addr = Some(Address::imm(n));
+        return;
+    }
+    let mut icode = concat_kids_icode(tree, ctx);
+    let operand = match (is_double(tree), is_double(&tree.kids[1])) {
+        (true, false) => as_double(&tree.kids[1], &mut icode, ctx),
+        (false, true) => {
+            let tmp = ctx.genlocal();
+            icode.push(Tac::new3(Op::Div, tmp.clone(), addr_of(&tree.kids[1], ctx), Address::imm(FIXED_ONE)));
+            tmp
+        }
+        _ => addr_of(&tree.kids[1], ctx),
+    };
+    let info = ctx.node_mut(tree.id);
+    info.icode = icode; info.addr = Some(operand);
+}
+
+/// An object in the VM does not know its class, so `instanceof` cannot
+/// be compiled; it is an error rather than a test that is dropped.
+fn gen_instanceof(tree: &Tree, ctx: &mut CodegenContext) {
+    let line = tree.first_lineno();
+    ctx.errors.push(format!("line {line}: instanceof cannot be compiled: an object does not know its class at run time"));
+    default_concat(tree, ctx);
+}
+
 // ═══════════════════════════════════════════════════════════════════════════════
 // Relational / equality
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -169,11 +252,14 @@ fn gen_eq_expr(tree: &Tree, ctx: &mut CodegenContext) {
 }
 
 fn emit_condition(tree: &Tree, branch_op: Op, ctx: &mut CodegenContext) {
-    let lhs      = addr_of(&tree.kids[0], ctx);
-    let rhs      = addr_of(&tree.kids[2], ctx);
     let on_true  = ctx.node(tree.id).and_then(|n| n.on_true.clone());
     let on_false = ctx.node(tree.id).and_then(|n| n.on_false.clone());
     let mut icode = concat_kids_icode(tree, ctx);
+    let (lhs, rhs) = if is_double(&tree.kids[0]) || is_double(&tree.kids[2]) {
+        (as_double(&tree.kids[0], &mut icode, ctx), as_double(&tree.kids[2], &mut icode, ctx))
+    } else {
+        (addr_of(&tree.kids[0], ctx), addr_of(&tree.kids[2], ctx))
+    };
     if let Some(t) = on_true  { icode.push(Tac::new3(branch_op, t, lhs, rhs)); }
     if let Some(f) = on_false { icode.push(Tac::new1(Op::Goto, f)); }
     ctx.node_mut(tree.id).icode = icode;
@@ -200,9 +286,12 @@ fn gen_cond_or(tree: &Tree, ctx: &mut CodegenContext) {
 fn gen_assignment(tree: &Tree, ctx: &mut CodegenContext) {
     if tree.kids.len() < 3 { return default_concat(tree, ctx); }
     let op_cat   = tree.kids[1].tok.as_ref().map(|t| t.category.as_str()).unwrap_or("ASSIGN");
+    if tree.kids[0].sym == "ArrayAccess" && tree.kids[0].kids.len() >= 2 {
+        return gen_element_assignment(tree, op_cat, ctx);
+    }
     let lhs_addr = addr_of(&tree.kids[0], ctx);
-    let rhs_addr = addr_of(&tree.kids[2], ctx);
     let mut icode = concat_kids_icode(tree, ctx);
+    let rhs_addr = assigned(&tree.kids[0], &tree.kids[2], &mut icode, ctx);
     match op_cat {
         "ASSIGN" => {
             icode.push(Tac::new2(Op::Asn, lhs_addr.clone(), rhs_addr));
@@ -223,6 +312,36 @@ fn gen_assignment(tree: &Tree, ctx: &mut CodegenContext) {
     info.icode = icode; info.addr = Some(lhs_addr);
 }
 
+/// `a[i] = e`: the value is stored into the element.  A plain `=` does
+/// not load the element first; `a[i] += e` does.
+fn gen_element_assignment(tree: &Tree, op_cat: &str, ctx: &mut CodegenContext) {
+    let element = &tree.kids[0];
+    let base    = addr_of(&element.kids[0], ctx);
+    let index   = addr_of(&element.kids[1], ctx);
+    let mut icode = match op_cat {
+        "ASSIGN" => concat_kids_icode(element, ctx),
+        _        => take_icode(element, ctx),
+    };
+    icode.extend(take_icode(&tree.kids[2], ctx));
+    let rhs = assigned(element, &tree.kids[2], &mut icode, ctx);
+    let value = match op_cat {
+        "PLUSASSIGN" | "MINUSASSIGN" => {
+            let op  = if op_cat == "PLUSASSIGN" { Op::Add } else { Op::Sub };
+            let tmp = ctx.genlocal();
+            icode.push(Tac::new3(op, tmp.clone(), addr_of(element, ctx), rhs));
+            tmp
+        }
+        _ => {
+            // `=` skipped the element's load, and with it its check.
+            icode.push(Tac::new2(Op::Bchk, base.clone(), index.clone()));
+            rhs
+        }
+    };
+    icode.push(Tac::new3(Op::Store, base, index, value.clone()));
+    let info = ctx.node_mut(tree.id);
+    info.icode = icode; info.addr = Some(value);
+}
+
 // ═══════════════════════════════════════════════════════════════════════════════
 // Arrays
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -237,17 +356,75 @@ fn gen_array_creation(tree: &Tree, ctx: &mut CodegenContext) {
     info.icode = icode; info.addr = Some(dst);
 }
 
+/// `{a, b}`: a new array of two, `a` stored at 0 and `b` at 1.
+fn gen_array_init(tree: &Tree, ctx: &mut CodegenContext) {
+    let dst = ctx.genlocal();
+    let mut icode = concat_kids_icode(tree, ctx);
+    icode.push(Tac::new2(Op::NewArray, dst.clone(), Address::imm(tree.kids.len() as i64)));
+    for (i, kid) in tree.kids.iter().enumerate() {
+        icode.push(Tac::new3(Op::Store, dst.clone(), Address::imm(i as i64), addr_of(kid, ctx)));
+    }
+    let info = ctx.node_mut(tree.id);
+    info.icode = icode; info.addr = Some(dst);
+}
+
+/// `x = e` or `a[] = {..}`: the value of the initializer, assigned to the
+/// name.
+fn gen_var_init(tree: &Tree, ctx: &mut CodegenContext) {
+    let (Some(name), Some(init)) = (tree.kids.first().and_then(declared_leaf), tree.kids.get(1)) else {
+        return default_concat(tree, ctx);
+    };
+    let var = addr_of(name, ctx);
+    let mut icode = concat_kids_icode(tree, ctx);
+    let value = assigned(name, init, &mut icode, ctx);
+    icode.push(Tac::new2(Op::Asn, var, value));
+    ctx.node_mut(tree.id).icode = icode;
+}
+
 fn gen_array_access(tree: &Tree, ctx: &mut CodegenContext) {
     if tree.kids.len() < 2 { return default_concat(tree, ctx); }
     let dst   = ctx.genlocal();
     let base  = addr_of(&tree.kids[0], ctx);
     let index = addr_of(&tree.kids[1], ctx);
     let mut icode = concat_kids_icode(tree, ctx);
+    icode.push(Tac::new2(Op::Bchk, base.clone(), index.clone()));
     icode.push(Tac::new3(Op::Load, dst.clone(), base, index));
     let info = ctx.node_mut(tree.id);
     info.icode = icode; info.addr = Some(dst);
 }
 
+// ═══════════════════════════════════════════════════════════════════════════════
+// Classes
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Generate the initializers of the fields of every class, ahead of the
+/// methods so that a `new` can run its class's.  Those of static fields
+/// go to [`CodegenContext::class_init`], run at startup; the others to
+/// [`CodegenContext::instance_init`], run by each `new` of the class.
+pub fn gen_field_inits(tree: &Tree, ctx: &mut CodegenContext) {
+    if tree.sym != "ClassDecl" {
+        for kid in &tree.kids {
+            gen_field_inits(kid, ctx);
+        }
+        return;
+    }
+    let class = tree.kids.first().and_then(|n| n.tok.as_ref()).map(|t| t.text.clone()).unwrap_or_default();
+    for field in tree.kids.iter().filter(|k| k.sym == "FieldDecl") {
+        for kid in &field.kids {
+            gencode(kid, ctx);
+        }
+        default_concat(field, ctx);
+        stamp_lines(field, ctx);
+        let code = take_icode(field, ctx);
+        // The rule records `static` as 3 to 8; see the grammar's FieldDecl.
+        if (3..9).contains(&field.rule) {
+            ctx.class_init.extend(code);
+        } else {
+            ctx.instance_init.entry(class.clone()).or_default().extend(code);
+        }
+    }
+}
+
 // ═══════════════════════════════════════════════════════════════════════════════
 // Instance creation
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -258,6 +435,12 @@ fn gen_instance_creation(tree: &Tree, ctx: &mut CodegenContext) {
     let class_addr = addr_of(&tree.kids[0], ctx);
     let n_args     = (tree.kids.len() - 1) as i64;
     let mut icode  = concat_kids_icode(tree, ctx);
+    // The fields are initialized after the arguments are evaluated and
+    // before the constructor runs.
+    let class = tree.kids[0].tok.as_ref().map(|t| t.text.as_str()).unwrap_or_default();
+    if let Some(init) = ctx.instance_init.get(class) {
+        icode.extend(init.iter().cloned());
+    }
     for kid in tree.kids[1..].iter().rev() {
         if let Some(a) = ctx.node(kid.id).and_then(|n| n.addr.clone()) {
             icode.push(Tac::new1(Op::Parm, a));
@@ -294,7 +477,7 @@ fn gen_method_call_field(tree: &Tree, ctx: &mut CodegenContext) {
         let mut icode = tree.kids.get(1)
             .map(|k| take_icode(k, ctx))
             .unwrap_or_default();
-        icode.push(Tac::new2(Op::Itos, dst.clone(), arg_addr));
+        icode.push(Tac::new2(to_string_op(tree.kids.get(1)), dst.clone(), arg_addr));
         let info = ctx.node_mut(tree.id);
         info.icode = icode;
         info.addr  = Some(dst);
@@ -317,9 +500,19 @@ fn gen_method_call_field(tree: &Tree, ctx: &mut CodegenContext) {
     for kid in &tree.kids[args_start..] {
         icode.extend(take_icode(kid, ctx));
     }
+    // println prints a double as its text.
+    let mut args: Vec<Address> = tree.kids[args_start..].iter().map(|kid| addr_of(kid, ctx)).collect();
+    if mangled == "PrintStream__println" {
+        for (arg, kid) in args.iter_mut().zip(&tree.kids[args_start..]) {
+            if is_double(kid) {
+                let text = ctx.genlocal();
+                icode.push(Tac::new2(Op::Ftos, text.clone(), std::mem::replace(arg, text)));
+            }
+        }
+    }
     // Push args in reverse order.
-    for kid in tree.kids[args_start..].iter().rev() {
-        icode.push(Tac::new1(Op::Parm, addr_of(kid, ctx)));
+    for arg in args.into_iter().rev() {
+        icode.push(Tac::new1(Op::Parm, arg));
     }
     // Receiver: look up the base identifier's address directly from stab.
     // For System.out.println, base_chain = ["System", "out"],
@@ -331,8 +524,8 @@ fn gen_method_call_field(tree: &Tree, ctx: &mut CodegenContext) {
                 .and_then(|leaf| find_in_chain(leaf.stab.as_ref()?, base_name, ctx))
         })
         .unwrap_or_else(Address::self_ptr);
-    icode.push(Tac::new1(Op::Parm, receiver_addr));
-    icode.push(Tac::new2(Op::Call, Address::symbol(&mangled), Address::imm(n_args)));
+    let external = external_method(fa, &base_chain, &method_name);
+    icode.extend(call_code(tree, ctx, &mangled, external, receiver_addr, n_args, &dst));
 
     let info = ctx.node_mut(tree.id);
     info.icode = icode;
@@ -363,7 +556,7 @@ fn gen_method_call(tree: &Tree, ctx: &mut CodegenContext) {
             let mut icode = tree.kids.get(2)
                 .map(|k| take_icode(k, ctx))
                 .unwrap_or_default();
-            icode.push(Tac::new2(Op::Itos, dst.clone(), arg_addr));
+            icode.push(Tac::new2(to_string_op(tree.kids.get(2)), dst.clone(), arg_addr));
             let info = ctx.node_mut(tree.id);
             info.icode = icode;
             info.addr  = Some(dst);
@@ -383,8 +576,8 @@ fn gen_method_call(tree: &Tree, ctx: &mut CodegenContext) {
         let obj_addr = ctx.node(tree.kids[0].id)
             .and_then(|n| n.addr.clone())
             .unwrap_or_else(Address::self_ptr);
-        icode.push(Tac::new1(Op::Parm, obj_addr));
-        icode.push(Tac::new2(Op::Call, Address::symbol(&mangled), Address::imm(n_args)));
+        let external = external_method(&tree.kids[0], &base_chain, method_name);
+        icode.extend(call_code(tree, ctx, &mangled, external, obj_addr, n_args, &dst));
     } else {
         // Direct call: kids[0] = method name leaf, kids[1..] = args.
         let method_addr = addr_of(&tree.kids[0], ctx);
@@ -396,8 +589,12 @@ fn gen_method_call(tree: &Tree, ctx: &mut CodegenContext) {
         for kid in tree.kids[args_start..].iter().rev() {
             icode.push(Tac::new1(Op::Parm, addr_of(kid, ctx)));
         }
-        icode.push(Tac::new1(Op::Parm, Address::self_ptr()));
-        icode.push(Tac::new2(Op::Call, method_addr, Address::imm(n_args)));
+        if let Some(name) = library_function(&tree.kids[0]) {
+            icode.extend(call_code(tree, ctx, &name, Some(External::Library), Address::self_ptr(), n_args, &dst));
+        } else {
+            icode.push(Tac::new1(Op::Parm, Address::self_ptr()));
+            icode.push(Tac::new2(Op::Call, method_addr, Address::imm(n_args)));
+        }
     }
 
     let info = ctx.node_mut(tree.id);
@@ -410,20 +607,21 @@ fn gen_method_call(tree: &Tree, ctx: &mut CodegenContext) {
 
 fn gen_field_access(tree: &Tree, ctx: &mut CodegenContext) {
     if tree.kids.len() < 2 { return default_concat(tree, ctx); }
-    let dst       = ctx.genlocal();
-    let base_addr = addr_of(&tree.kids[0], ctx);
     if tree.kids[1].tok.as_ref().map(|t| t.text.as_str()) == Some("length") {
+        let dst       = ctx.genlocal();
+        let base_addr = addr_of(&tree.kids[0], ctx);
         let mut icode = concat_kids_icode(tree, ctx);
         icode.push(Tac::new2(Op::Asize, dst.clone(), base_addr));
         let info = ctx.node_mut(tree.id);
         info.icode = icode; info.addr = Some(dst);
         return;
     }
+    // The VM has no objects — `new` gives 0 — so a field has one slot, in
+    // the class region, whatever object it is reached through.
     let field_addr = addr_of(&tree.kids[1], ctx);
-    let mut icode  = concat_kids_icode(tree, ctx);
-    icode.push(Tac::new3(Op::Load, dst.clone(), base_addr, field_addr));
+    let icode = concat_kids_icode(tree, ctx);
     let info = ctx.node_mut(tree.id);
-    info.icode = icode; info.addr = Some(dst);
+    info.icode = icode; info.addr = Some(field_addr);
 }
 
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -441,16 +639,31 @@ fn gen_return(tree: &Tree, ctx: &mut CodegenContext) {
     ctx.node_mut(tree.id).icode = icode;
 }
 
+/// The value of a condition that names a `static final` constant and
+/// folds, such as `DEBUG` or `LEVEL > 2`.  Its statement keeps only the
+/// branch that runs; a condition of literals alone is compiled as written.
+fn constant_condition(cond: &Tree, ctx: &mut CodegenContext) -> Option<bool> {
+    if eval_const(cond).is_some() { return None; }
+    let ConstValue::Bool(value) = eval_const_with(cond, &ctx.constants)? else { return None };
+    ctx.folded_conditions += 1;
+    Some(value)
+}
+
 fn gen_if_then(tree: &Tree, ctx: &mut CodegenContext) {
     if tree.kids.len() < 2 { return default_concat(tree, ctx); }
     let cond_first = ctx.node(tree.kids[0].id).and_then(|n| n.first.clone());
     let on_true    = ctx.node(tree.kids[0].id).and_then(|n| n.on_true.clone());
     let follow     = ctx.node(tree.id).and_then(|n| n.follow.clone());
+    let constant   = constant_condition(&tree.kids[0], ctx);
+    let cond       = take_icode(&tree.kids[0], ctx);
+    let then       = take_icode(&tree.kids[1], ctx);
     let mut icode  = vec![];
     if let Some(f) = cond_first { icode.push(Tac::new1(Op::Lab, f)); }
-    icode.extend(take_icode(&tree.kids[0], ctx));
-    if let Some(t) = on_true    { icode.push(Tac::new1(Op::Lab, t)); }
-    icode.extend(take_icode(&tree.kids[1], ctx));
+    if constant.is_none()       { icode.extend(cond); }
+    if constant != Some(false) {
+        if let Some(t) = on_true { icode.push(Tac::new1(Op::Lab, t)); }
+        icode.extend(then);
+    }
     if let Some(f) = follow     { icode.push(Tac::new1(Op::Lab, f)); }
     ctx.node_mut(tree.id).icode = icode;
 }
@@ -461,14 +674,30 @@ fn gen_if_then_else(tree: &Tree, ctx: &mut CodegenContext) {
     let on_true    = ctx.node(tree.kids[0].id).and_then(|n| n.on_true.clone());
     let else_first = ctx.node(tree.kids[2].id).and_then(|n| n.first.clone());
     let follow     = ctx.node(tree.id).and_then(|n| n.follow.clone());
+    let constant   = constant_condition(&tree.kids[0], ctx);
+    let cond       = take_icode(&tree.kids[0], ctx);
+    let then       = take_icode(&tree.kids[1], ctx);
+    let els        = take_icode(&tree.kids[2], ctx);
     let mut icode  = vec![];
     if let Some(f) = cond_first     { icode.push(Tac::new1(Op::Lab, f)); }
-    icode.extend(take_icode(&tree.kids[0], ctx));
-    if let Some(t) = on_true        { icode.push(Tac::new1(Op::Lab, t)); }
-    icode.extend(take_icode(&tree.kids[1], ctx));
-    if let Some(f) = follow.clone() { icode.push(Tac::new1(Op::Goto, f)); }
-    if let Some(f) = else_first     { icode.push(Tac::new1(Op::Lab, f)); }
-    icode.extend(take_icode(&tree.kids[2], ctx));
+    match constant {
+        Some(true) => {
+            if let Some(t) = on_true { icode.push(Tac::new1(Op::Lab, t)); }
+            icode.extend(then);
+        }
+        Some(false) => {
+            if let Some(f) = else_first { icode.push(Tac::new1(Op::Lab, f)); }
+            icode.extend(els);
+        }
+        None => {
+            icode.extend(cond);
+            if let Some(t) = on_true        { icode.push(Tac::new1(Op::Lab, t)); }
+            icode.extend(then);
+            if let Some(f) = follow.clone() { icode.push(Tac::new1(Op::Goto, f)); }
+            if let Some(f) = else_first     { icode.push(Tac::new1(Op::Lab, f)); }
+            icode.extend(els);
+        }
+    }
     if let Some(f) = follow         { icode.push(Tac::new1(Op::Lab, f)); }
     ctx.node_mut(tree.id).icode = icode;
 }
@@ -485,9 +714,36 @@ fn gen_while(tree: &Tree, ctx: &mut CodegenContext) {
 
     let mut icode = vec![];
     if let Some(f) = cond_first.clone() { icode.push(Tac::new1(Op::Lab, f)); }
-    icode.extend(take_icode(&tree.kids[0], ctx));
-    if let Some(t) = on_true            { icode.push(Tac::new1(Op::Lab, t)); }
+    let cond = take_icode(&tree.kids[0], ctx);
+    match constant_condition(&tree.kids[0], ctx) {
+        // The loop never runs.
+        Some(false) => {
+            take_icode(&tree.kids[1], ctx);
+            icode.push(Tac::new1(Op::Lab, follow));
+            ctx.node_mut(tree.id).icode = icode;
+            return;
+        }
+        // The loop runs until a `break` or `return`.
+        Some(true) => {}
+        None => {
+            icode.extend(cond);
+            // A condition that is just a value, such as `true` or a
+            // variable, does not branch by itself: test it against false.
+            let branches = matches!(tree.kids[0].sym.as_str(), "RelExpr" | "EqExpr" | "CondAndExpr" | "CondOrExpr");
+            if !branches && let Some(t) = on_true.clone() {
+                icode.push(Tac::new3(Op::Bne, t, addr_of(&tree.kids[0], ctx), Address::imm(0)));
+                icode.push(Tac::new1(Op::Goto, follow.clone()));
+            }
+        }
+    }
+    // An empty body's on_true is the condition itself, already placed.
+    if let Some(t) = on_true.filter(|t| Some(t) != cond_first.as_ref()) {
+        icode.push(Tac::new1(Op::Lab, t));
+    }
     icode.extend(take_icode(&tree.kids[1], ctx));
+    if let Some(b) = ctx.node(tree.kids[1].id).and_then(|n| n.follow.clone()) {
+        icode.push(Tac::new1(Op::Lab, b));
+    }
     if let Some(f) = cond_first         { icode.push(Tac::new1(Op::Goto, f)); }
     icode.push(Tac::new1(Op::Lab, follow));
     ctx.node_mut(tree.id).icode = icode;
@@ -510,6 +766,49 @@ fn gen_for(tree: &Tree, ctx: &mut CodegenContext) {
     ctx.node_mut(tree.id).icode = icode;
 }
 
+/// Test the selector against each `case` value in turn and jump to the
+/// first group that matches, else to the `default` group or out of the
+/// switch.  The groups follow in source order, so one without a `break`
+/// falls through into the next.
+fn gen_switch(tree: &Tree, ctx: &mut CodegenContext) {
+    let Some((selector, clauses)) = tree.kids.split_first() else { return default_concat(tree, ctx) };
+    let first  = ctx.node(tree.id).and_then(|n| n.first.clone());
+    let follow = ctx.node(tree.id)
+        .and_then(|n| n.follow.clone())
+        .unwrap_or_else(|| ctx.genlabel());
+    let value = addr_of(selector, ctx);
+
+    let mut icode = vec![];
+    if let Some(f) = first { icode.push(Tac::new1(Op::Lab, f)); }
+    icode.extend(take_icode(selector, ctx));
+    let mut otherwise = follow.clone();
+    for clause in clauses {
+        let Some(entry) = ctx.node(clause.id).and_then(|n| n.first.clone()) else { continue };
+        for label in clause.kids.iter().skip(1) {
+            match case_value(label) {
+                Some(n) => icode.push(Tac::new3(Op::Beq, entry.clone(), value.clone(), Address::imm(n))),
+                None if label.sym == "DefaultLabel" => otherwise = entry.clone(),
+                // Semantic analysis has reported it.
+                None => {}
+            }
+        }
+    }
+    icode.push(Tac::new1(Op::Goto, otherwise));
+    for clause in clauses {
+        if let Some(entry) = ctx.node(clause.id).and_then(|n| n.first.clone()) {
+            icode.push(Tac::new1(Op::Lab, entry));
+        }
+        if let Some(body) = clause.kids.first() {
+            icode.extend(take_icode(body, ctx));
+        }
+        if let Some(end) = ctx.node(clause.id).and_then(|n| n.follow.clone()) {
+            icode.push(Tac::new1(Op::Lab, end));
+        }
+    }
+    icode.push(Tac::new1(Op::Lab, follow));
+    ctx.node_mut(tree.id).icode = icode;
+}
+
 fn gen_break(tree: &Tree, ctx: &mut CodegenContext) {
     let follow = ctx.node(tree.id).and_then(|n| n.follow.clone());
     let mut icode = vec![];
@@ -527,6 +826,41 @@ fn addr_of(tree: &Tree, ctx: &CodegenContext) -> Address {
         .unwrap_or_else(|| Address::imm(0))
 }
 
+/// Whether `tree` is a `double`, which the VM keeps as a fixed-point
+/// number of [`FIXED_ONE`] to 1.
+fn is_double(tree: &Tree) -> bool {
+    tree.typ.as_ref().is_some_and(|t| t.basetype() == "double")
+}
+
+/// `ITOS`, or `FTOS` for a double: the conversion of `String.valueOf`.
+fn to_string_op(arg: Option<&Tree>) -> Op {
+    if arg.is_some_and(is_double) { Op::Ftos } else { Op::Itos }
+}
+
+/// The fixed-point word of the double `d`.
+fn fixed_point(d: f64) -> i64 {
+    (d * FIXED_ONE as f64).round() as i64
+}
+
+/// The address of `tree` as a double: an integer is scaled to fixed
+/// point, by code appended to `icode` unless it is a constant.
+fn as_double(tree: &Tree, icode: &mut Vec<Tac>, ctx: &mut CodegenContext) -> Address {
+    let addr = addr_of(tree, ctx);
+    if is_double(tree) { return addr; }
+    if let Address::Regional { region: Region::Imm, offset } = addr {
+        return Address::imm(offset * FIXED_ONE);
+    }
+    let tmp = ctx.genlocal();
+    icode.push(Tac::new3(Op::Mul, tmp.clone(), addr, Address::imm(FIXED_ONE)));
+    tmp
+}
+
+/// The address of `value` as assigned to `target`: an integer assigned to
+/// a double widens to one.
+fn assigned(target: &Tree, value: &Tree, icode: &mut Vec<Tac>, ctx: &mut CodegenContext) -> Address {
+    if is_double(target) { as_double(value, icode, ctx) } else { addr_of(value, ctx) }
+}
+
 fn concat_kids_icode(tree: &Tree, ctx: &CodegenContext) -> Vec<Tac> {
     let mut out = vec![];
     for kid in &tree.kids {
@@ -541,6 +875,27 @@ fn take_icode(tree: &Tree, ctx: &CodegenContext) -> Vec<Tac> {
     ctx.node(tree.id).map(|n| n.icode.clone()).unwrap_or_default()
 }
 
+/// The try block, then the finally block.  Nothing throws at run time
+/// yet — `throw` ends the program — so no code is generated for the
+/// catch clauses.
+fn gen_try(tree: &Tree, ctx: &mut CodegenContext) {
+    let mut icode = tree.kids.first().map(|body| take_icode(body, ctx)).unwrap_or_default();
+    if tree.rule == 1
+        && let Some(fin) = tree.kids.get(1)
+    {
+        icode.extend(take_icode(fin, ctx));
+    }
+    ctx.node_mut(tree.id).icode = icode;
+}
+
+fn gen_throw(tree: &Tree, ctx: &mut CodegenContext) {
+    let mut icode = concat_kids_icode(tree, ctx);
+    if let Some(value) = tree.kids.first() {
+        icode.push(Tac::new1(Op::Throw, addr_of(value, ctx)));
+    }
+    ctx.node_mut(tree.id).icode = icode;
+}
+
 fn default_concat(tree: &Tree, ctx: &mut CodegenContext) {
     ctx.node_mut(tree.id).icode = concat_kids_icode(tree, ctx);
 }
@@ -568,6 +923,12 @@ fn reemit_condition(tree: &Tree, ctx: &mut CodegenContext) {
 // Helpers — method name mangling
 // ═══════════════════════════════════════════════════════════════════════════════
 
+/// The name leaf of a VarDeclarator, through any `[]`s.
+fn declared_leaf(tree: &Tree) -> Option<&Tree> {
+    if tree.is_leaf() { return Some(tree); }
+    declared_leaf(tree.kids.first()?)
+}
+
 /// Split a FieldAccess chain into (base_chain, method_name).
 /// FieldAccess(FieldAccess(System, out), println) → (["System", "out"], "println")
 fn split_field_chain(tree: &Tree) -> (Vec<String>, String) {
@@ -609,6 +970,72 @@ fn collect_field_chain(tree: &Tree) -> Vec<String> {
     vec![]
 }
 
+/// A static method implemented outside the program.
+enum External {
+    /// A native method of the embedder, `Class.method`.
+    Native(String),
+    /// A method of the runtime library, e.g. `Math.randomInt`.
+    Library,
+}
+
+/// The PARM of `receiver` and the CALL of `mangled`.  An external method is
+/// static, so it gets no receiver, and hands its result back on the stack
+/// for CALL to store in `dst`; a Jzero method's result is not returned
+/// that way.
+fn call_code(
+    tree: &Tree,
+    ctx: &mut CodegenContext,
+    mangled: &str,
+    external: Option<External>,
+    receiver: Address,
+    n_args: i64,
+    dst: &Address,
+) -> Vec<Tac> {
+    let Some(external) = external else {
+        return vec![
+            Tac::new1(Op::Parm, receiver),
+            Tac::new2(Op::Call, Address::symbol(mangled), Address::imm(n_args)),
+        ];
+    };
+    if let External::Native(qualified) = external {
+        ctx.native_index(&qualified);
+    }
+    let returns = tree.typ.as_ref().is_some_and(|t| t.basetype() != "void");
+    vec![if returns {
+        Tac::new3(Op::Call, Address::symbol(mangled), Address::imm(n_args), dst.clone())
+    } else {
+        Tac::new2(Op::Call, Address::symbol(mangled), Address::imm(n_args))
+    }]
+}
+
+/// What the call `class.method(...)`, whose receiver is `base`, calls if
+/// it is a native or library method.
+fn external_method(base: &Tree, chain: &[String], method: &str) -> Option<External> {
+    let [class] = chain else { return None };
+    let stab = find_base_leaf(base)?.stab.as_ref()?;
+    let entry = stab.borrow().lookup(class)?;
+    let origin = entry.st.as_ref()?.borrow().lookup_local(method)?.origin;
+    match origin {
+        SymbolOrigin::Native  => Some(External::Native(format!("{}.{}", class, method))),
+        SymbolOrigin::Library => Some(External::Library),
+        _ => None,
+    }
+}
+
+/// The name of the library method `leaf` names, for a call of it without
+/// a class, such as `assertEquals(..)`.
+fn library_function(leaf: &Tree) -> Option<String> {
+    let name = &leaf.tok.as_ref()?.text;
+    let entry = leaf.stab.as_ref()?.borrow().lookup(name)?;
+    (entry.kind == SymbolKind::Method && entry.origin == SymbolOrigin::Library).then(|| name.clone())
+}
+
+/// The symbol a call to native method `qualified` is made to: `Robot.move`
+/// is `Robot__move`, as [`mangle_method`] names any dotted call.
+pub fn native_symbol(qualified: &str) -> String {
+    qualified.replacen('.', "__", 1)
+}
+
 /// Mangle a dotted call into a C-style symbol name.
 fn mangle_method(chain: &[String], method: &str) -> String {
     if chain == ["System", "out"] && method == "println" {
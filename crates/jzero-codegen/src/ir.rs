@@ -0,0 +1,456 @@
+//! The textual intermediate code, and a parser that reads it back.
+//!
+//! [`emit::emit`] writes a program's three-address code in this form, and
+//! [`parse_ir`] reads it into an [`IrProgram`] that
+//! [`pipeline::compile_ir`] turns into bytecode, so that IR can be kept as
+//! golden files and edited by hand between the stages.  Printing what
+//! `parse_ir` read gives back the same text.
+//!
+//! # Grammar
+//!
+//! One item per line; blank lines are ignored.
+//!
+//! ```text
+//! program  = [".string" { label string }]
+//!            [".global" { global }]
+//!            [".native" { native }]
+//!            ".code" { proc }
+//! label    = "L" int ":"
+//! string   = "string" quoted              (escapes as in Jzero literals)
+//! global   = "global" address "," name
+//! native   = "native" Class "." method
+//! proc     = "proc" name "," int "," int  (name, frame size, parameters)
+//!            { label | instr } "RET" "end"
+//! instr    = OPCODE [operand { "," operand }]     (at most 3 operands)
+//! operand  = region ":" int | "L" int | "self" | name
+//! region   = "loc" | "global" | "strings" | "class" | "imm"
+//! ```
+//!
+//! Opcodes are the upper-case names of [`Op`], such as `ASN` and `BLT`.  A
+//! string's offset in the strings region is not written: each string
+//! follows the last, NUL-terminated and padded to 8 bytes.  The frame size
+//! of a `proc` is written as 0; the bare `RET` before `end` closes every
+//! method and is not part of [`IrProc::code`].
+//!
+//! [`emit::emit`]: crate::emit::emit
+//! [`pipeline::compile_ir`]: crate::pipeline::compile_ir
+
+use std::fmt;
+
+use jzero_ast::tree::Tree;
+use jzero_lexer::escape::{quote, string_value};
+
+use crate::address::{Address, Region};
+use crate::context::{CodegenContext, StringEntry};
+use crate::emit::{count_params, find_method_name, format_tac};
+use crate::tac::{Op, Tac};
+
+/// A whole program's intermediate code.
+#[derive(Debug, Clone, Default)]
+pub struct IrProgram {
+    pub strings: Vec<StringEntry>,
+    /// Global variables: name and address.
+    pub globals: Vec<(String, Address)>,
+    /// The native methods called, by qualified name, in import order.
+    pub natives: Vec<String>,
+    pub procs:   Vec<IrProc>,
+}
+
+/// One method's intermediate code.
+#[derive(Debug, Clone)]
+pub struct IrProc {
+    pub name:   String,
+    pub params: usize,
+    pub code:   Vec<Tac>,
+}
+
+/// A line `parse_ir` could not read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IrError {
+    /// 1-based line number.
+    pub line:    usize,
+    pub message: String,
+}
+
+impl fmt::Display for IrError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for IrError {}
+
+impl IrProgram {
+    /// The intermediate code generated for `tree`.
+    pub fn from_context(tree: &Tree, ctx: &CodegenContext) -> Self {
+        let mut procs = Vec::new();
+        collect_procs(tree, ctx, &mut procs);
+        // The static fields are initialized by the entry point, the first
+        // proc, as the image runs it.
+        if let Some(first) = procs.first_mut() {
+            first.code.splice(0..0, ctx.class_init.iter().cloned());
+        }
+        IrProgram {
+            strings: ctx.strings.clone(),
+            globals: ctx.globals.clone(),
+            natives: ctx.natives.clone(),
+            procs,
+        }
+    }
+}
+
+/// Walk the tree looking for MethodDecl nodes, in source order.
+fn collect_procs(tree: &Tree, ctx: &CodegenContext, procs: &mut Vec<IrProc>) {
+    if tree.sym == "MethodDecl" {
+        // The icode is on the Block child (kids[1] of MethodDecl).
+        let code = tree.kids.get(1)
+            .and_then(|block| ctx.node(block.id))
+            .map_or_else(Vec::new, |info| info.icode.clone());
+        procs.push(IrProc {
+            name:   find_method_name(tree).unwrap_or_else(|| "unknown".to_string()),
+            params: count_params(tree),
+            code,
+        });
+        return;
+    }
+    for kid in &tree.kids {
+        collect_procs(kid, ctx, procs);
+    }
+}
+
+impl fmt::Display for IrProgram {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.strings.is_empty() {
+            writeln!(f, ".string")?;
+            for entry in &self.strings {
+                writeln!(f, "{}:", entry.label)?;
+                writeln!(f, "string {}", quote(&entry.value))?;
+            }
+        }
+        if !self.globals.is_empty() {
+            writeln!(f, ".global")?;
+            for (name, addr) in &self.globals {
+                writeln!(f, "global {},{}", addr, name)?;
+            }
+        }
+        if !self.natives.is_empty() {
+            writeln!(f, ".native")?;
+            for native in &self.natives {
+                writeln!(f, "native {}", native)?;
+            }
+        }
+        writeln!(f, ".code")?;
+        for proc in &self.procs {
+            // We don't track per-method frame size yet; the VM computes it.
+            writeln!(f, "proc {},0,{}", proc.name, proc.params)?;
+            for tac in &proc.code {
+                writeln!(f, "{}", format_tac(tac))?;
+            }
+            writeln!(f, "RET")?;
+            writeln!(f, "end")?;
+        }
+        Ok(())
+    }
+}
+
+// ─── Parser ──────────────────────────────────────────────────────────────────
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Section {
+    None,
+    String,
+    Global,
+    Native,
+    Code,
+}
+
+/// Read intermediate code in the form [`IrProgram`] prints.
+///
+/// # Errors
+/// Returns an [`IrError`] for the first line that does not fit the grammar.
+pub fn parse_ir(text: &str) -> Result<IrProgram, IrError> {
+    let mut program = IrProgram::default();
+    let mut section = Section::None;
+    let mut pending_label: Option<Address> = None;
+    let mut strings_offset = 0;
+    let mut proc: Option<IrProc> = None;
+    let mut last_line = 0;
+
+    for (i, raw) in text.lines().enumerate() {
+        let line = i + 1;
+        last_line = line;
+        let err = |message: String| IrError { line, message };
+        let item = raw.trim();
+        if item.is_empty() {
+            continue;
+        }
+
+        if let Some(name) = item.strip_prefix('.') {
+            if proc.is_some() {
+                return Err(err(format!("section .{} inside a proc", name)));
+            }
+            if pending_label.is_some() {
+                return Err(err("label with no string".to_string()));
+            }
+            let next = match name {
+                "string" => Section::String,
+                "global" => Section::Global,
+                "native" => Section::Native,
+                "code"   => Section::Code,
+                _ => return Err(err(format!("unknown section .{}", name))),
+            };
+            if next as u8 <= section as u8 {
+                return Err(err(format!("section .{} out of order", name)));
+            }
+            section = next;
+            continue;
+        }
+
+        match section {
+            Section::None => return Err(err(format!("{:?} before the first section", item))),
+            Section::String => {
+                if let Some(label) = item.strip_suffix(':') {
+                    if pending_label.is_some() {
+                        return Err(err("label with no string".to_string()));
+                    }
+                    pending_label = Some(label_address(label).ok_or_else(|| err(format!("bad label {:?}", label)))?);
+                    continue;
+                }
+                let literal = item.strip_prefix("string ")
+                    .ok_or_else(|| err(format!("expected a string, found {:?}", item)))?;
+                let value = string_value(literal.trim())
+                    .ok_or_else(|| err(format!("bad string literal {}", literal.trim())))?;
+                let label = pending_label.take().ok_or_else(|| err("string with no label".to_string()))?;
+                let padded = (value.len() + 1 + 7) & !7;
+                program.strings.push(StringEntry { label, string_offset: strings_offset, value });
+                strings_offset += padded as i64;
+            }
+            Section::Global => {
+                let rest = item.strip_prefix("global ")
+                    .ok_or_else(|| err(format!("expected a global, found {:?}", item)))?;
+                let (addr, name) = rest.split_once(',')
+                    .ok_or_else(|| err("expected `global <address>,<name>`".to_string()))?;
+                let addr = parse_operand(addr.trim()).map_err(err)?;
+                program.globals.push((name.trim().to_string(), addr));
+            }
+            Section::Native => {
+                let name = item.strip_prefix("native ")
+                    .ok_or_else(|| err(format!("expected a native, found {:?}", item)))?;
+                if name.trim().split_once('.').is_none_or(|(class, method)| !is_name(class) || !is_name(method)) {
+                    return Err(err(format!("expected Class.method, found {:?}", name.trim())));
+                }
+                program.natives.push(name.trim().to_string());
+            }
+            Section::Code => {
+                if let Some(rest) = item.strip_prefix("proc ") {
+                    if proc.is_some() {
+                        return Err(err("proc inside a proc".to_string()));
+                    }
+                    proc = Some(parse_proc_header(rest).map_err(err)?);
+                    continue;
+                }
+                let Some(current) = proc.as_mut() else {
+                    return Err(err(format!("{:?} outside a proc", item)));
+                };
+                if item == "end" {
+                    match current.code.pop() {
+                        Some(Tac { op: Op::Ret, op1: None, .. }) => {}
+                        _ => return Err(err("a proc must end with a bare RET".to_string())),
+                    }
+                    program.procs.extend(proc.take());
+                    continue;
+                }
+                current.code.push(parse_instruction(item).map_err(err)?);
+            }
+        }
+    }
+
+    if pending_label.is_some() {
+        return Err(IrError { line: last_line, message: "label with no string".to_string() });
+    }
+    if proc.is_some() {
+        return Err(IrError { line: last_line, message: "proc with no end".to_string() });
+    }
+    if section != Section::Code {
+        return Err(IrError { line: last_line, message: "no .code section".to_string() });
+    }
+    Ok(program)
+}
+
+/// `name,frame,params` after `proc`.
+fn parse_proc_header(rest: &str) -> Result<IrProc, String> {
+    let fields: Vec<&str> = rest.split(',').map(str::trim).collect();
+    let [name, frame, params] = fields[..] else {
+        return Err("expected `proc <name>,<frame>,<params>`".to_string());
+    };
+    if !is_name(name) {
+        return Err(format!("bad proc name {:?}", name));
+    }
+    frame.parse::<i64>().map_err(|_| format!("bad frame size {:?}", frame))?;
+    let params = params.parse().map_err(|_| format!("bad parameter count {:?}", params))?;
+    Ok(IrProc { name: name.to_string(), params, code: Vec::new() })
+}
+
+/// A label definition `L7:` or an instruction `OP a,b,c`.
+fn parse_instruction(item: &str) -> Result<Tac, String> {
+    if let Some(label) = item.strip_suffix(':') {
+        let addr = label_address(label).ok_or_else(|| format!("bad label {:?}", label))?;
+        return Ok(Tac::new1(Op::Lab, addr));
+    }
+    let (name, operands) = item.split_once(' ').unwrap_or((item, ""));
+    let op = opcode(name).ok_or_else(|| format!("unknown opcode {:?}", name))?;
+    let mut addrs = Vec::new();
+    if !operands.trim().is_empty() {
+        for operand in operands.split(',') {
+            addrs.push(parse_operand(operand.trim())?);
+        }
+    }
+    if addrs.len() > 3 {
+        return Err(format!("{} has {} operands, at most 3 are allowed", name, addrs.len()));
+    }
+    let mut addrs = addrs.into_iter();
+    let mut tac = Tac::new0(op);
+    tac.op1 = addrs.next();
+    tac.op2 = addrs.next();
+    tac.op3 = addrs.next();
+    Ok(tac)
+}
+
+/// An operand in the form [`Address`] prints.
+fn parse_operand(text: &str) -> Result<Address, String> {
+    if text == "self" {
+        return Ok(Address::self_ptr());
+    }
+    if let Some(addr) = label_address(text) {
+        return Ok(addr);
+    }
+    if let Some((region, offset)) = text.split_once(':') {
+        let region = match region {
+            "loc"     => Region::Loc,
+            "global"  => Region::Global,
+            "strings" => Region::Strings,
+            "class"   => Region::Class,
+            "imm"     => Region::Imm,
+            _ => return Err(format!("unknown region {:?}", region)),
+        };
+        let offset = offset.parse().map_err(|_| format!("bad offset in {:?}", text))?;
+        return Ok(Address::new(region, offset));
+    }
+    if is_name(text) {
+        return Ok(Address::symbol(text));
+    }
+    Err(format!("bad operand {:?}", text))
+}
+
+/// The label address `L<id>`, if `text` is one.
+fn label_address(text: &str) -> Option<Address> {
+    let id = text.strip_prefix('L')?;
+    if id.is_empty() || !id.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    id.parse().ok().map(Address::lab)
+}
+
+fn is_name(text: &str) -> bool {
+    let mut chars = text.chars();
+    chars.next().is_some_and(|c| c.is_alphabetic() || c == '_')
+        && chars.all(|c| c.is_alphanumeric() || c == '_')
+}
+
+/// The opcode an instruction line names; the declaration
+/// pseudo-instructions have lines of their own and are not opcodes.
+fn opcode(name: &str) -> Option<Op> {
+    Some(match name {
+        "ADD"      => Op::Add,
+        "SUB"      => Op::Sub,
+        "MUL"      => Op::Mul,
+        "DIV"      => Op::Div,
+        "MOD"      => Op::Mod,
+        "NEG"      => Op::Neg,
+        "SADD"     => Op::Sadd,
+        "FMUL"     => Op::Fmul,
+        "FDIV"     => Op::Fdiv,
+        "ASN"      => Op::Asn,
+        "ADDR"     => Op::Addr,
+        "ASIZE"    => Op::Asize,
+        "LOAD"     => Op::Load,
+        "STORE"    => Op::Store,
+        "NEWARRAY" => Op::NewArray,
+        "BCHK"     => Op::Bchk,
+        "GOTO"     => Op::Goto,
+        "BLT"      => Op::Blt,
+        "BLE"      => Op::Ble,
+        "BGT"      => Op::Bgt,
+        "BGE"      => Op::Bge,
+        "BEQ"      => Op::Beq,
+        "BNE"      => Op::Bne,
+        "PARM"     => Op::Parm,
+        "CALL"     => Op::Call,
+        "RET"      => Op::Ret,
+        "THROW"    => Op::Throw,
+        "ITOS"     => Op::Itos,
+        "FTOS"     => Op::Ftos,
+        _ => return None,
+    })
+}
+
+// ─── Tests ───────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HAND_WRITTEN: &str = "\
+.string
+L0:
+string \"n = \\\"%d\\\"\\n\"
+L1:
+string \"done\"
+.global
+global global:0,System
+.native
+native Robot.move
+.code
+proc main,0,1
+ASN loc:16,imm:3
+L2:
+BLE L3,loc:16,imm:0
+SUB loc:24,loc:16,imm:1
+ASN loc:16,loc:24
+GOTO L2
+L3:
+PARM strings:16
+PARM global:0
+CALL PrintStream__println,imm:1
+PARM imm:2
+CALL Robot__move,imm:1,loc:32
+RET
+end
+";
+
+    #[test]
+    fn printing_what_was_read_gives_the_same_text() {
+        let program = parse_ir(HAND_WRITTEN).unwrap();
+        assert_eq!(program.to_string(), HAND_WRITTEN);
+        assert_eq!(program.strings[0].value, "n = \"%d\"\n");
+        // 10 bytes and a NUL, padded to 16.
+        assert_eq!(program.strings[1].string_offset, 16);
+        assert_eq!(program.natives, ["Robot.move"]);
+        let code = &program.procs[0].code;
+        assert_eq!(code.len(), 12, "the closing RET is not code");
+        assert_eq!(code[2].op, Op::Ble);
+        assert_eq!(code[11].op3, Some(Address::loc(32)));
+    }
+
+    #[test]
+    fn bad_lines_are_reported_with_their_number() {
+        let err = |text: &str| parse_ir(text).unwrap_err().to_string();
+        assert_eq!(err(".code\nproc main,0,0\nJUMP L1\nRET\nend\n"), "line 3: unknown opcode \"JUMP\"");
+        assert_eq!(err(".code\nproc main,0,0\nASN loc:8,heap:0\nRET\nend\n"), "line 3: unknown region \"heap\"");
+        assert_eq!(err(".code\nproc main,0,0\nASN loc:8,imm:1\nend\n"), "line 4: a proc must end with a bare RET");
+        assert_eq!(err(".code\nproc main,0,0\nRET\n"), "line 3: proc with no end");
+        assert_eq!(err(".code\n.global\n"), "line 2: section .global out of order");
+        assert_eq!(err(".string\nstring \"x\"\n.code\n"), "line 2: string with no label");
+        assert_eq!(err("ASN loc:8,imm:1\n"), "line 1: \"ASN loc:8,imm:1\" before the first section");
+    }
+}
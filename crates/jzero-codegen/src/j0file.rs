@@ -15,7 +15,9 @@
 //!
 //! ```text
 //! PUSH <addr-of-main>   (R_ABS, byte offset of the proc main)
-//! CALL imm:0            (0 parameters)
+//! PUSH imm:argc
+//! NEWARRAY              (argv, an array of argc elements)
+//! CALL imm:1            (1 parameter)
 //! HALT
 //! ```
 //!
@@ -26,6 +28,9 @@ use std::collections::HashMap;
 
 use crate::byc::{Byc, BycRegion, Op};
 
+/// Words in the startup sequence ahead of the first instruction.
+pub const STARTUP_WORDS: usize = 5;
+
 // ---------------------------------------------------------------------------
 // Public API
 // ---------------------------------------------------------------------------
@@ -50,12 +55,11 @@ pub fn assemble(
     let data_padded  = pad8(data);
     let data_words   = data_padded.len() / 8;
     let header_words = 3usize;
-    let startup_words = 4usize;
     let first_instr_word_off = header_words + data_words;
 
     // Byte offset of the first program instruction (after startup sequence),
     // relative to word 0 (the magic word).
-    let code_base_bytes = (first_instr_word_off + startup_words) * 8;
+    let code_base_bytes = (first_instr_word_off + STARTUP_WORDS) * 8;
 
     // -----------------------------------------------------------------------
     // Relocate label references.
@@ -82,10 +86,11 @@ pub fn assemble(
 
     // -----------------------------------------------------------------------
     // Build the startup sequence.
-    // Pushes: fn_addr(main), argc, then calls main with 1 arg.
+    // Pushes: fn_addr(main), an argv array of argc elements, then calls
+    // main with 1 arg.
     // Stack layout after CALL:
     //   stack[fn_slot+0] = main_addr  (bp points here, loc:0)
-    //   stack[fn_slot+1] = argc       (loc:8, argv param)
+    //   stack[fn_slot+1] = argv       (loc:8, argv param)
     //   stack[fn_slot+2] = saved_ip
     //   stack[fn_slot+3] = saved_bp
     //   stack[fn_slot+4] = saved_ret
@@ -93,6 +98,7 @@ pub fn assemble(
     let startup: Vec<Byc> = vec![
         Byc { op: Op::Push, region: BycRegion::Imm, opnd: main_addr, needs_patch: false },
         Byc::imm(Op::Push, argc),
+        Byc::no_operand(Op::Newarray),
         Byc::imm(Op::Call, 1),
         Byc::no_operand(Op::Halt),
     ];
@@ -131,7 +137,9 @@ pub fn disassemble_text(bycs: &[Byc], data: &[u8]) -> String {
     s.push_str(".startup\n");
     // (The actual startup bytes are baked by `assemble`; shown symbolically.)
     s.push_str("\tpush <main>\n");
-    s.push_str("\tcall 0\n");
+    s.push_str("\tpush <argc>\n");
+    s.push_str("\tnewarray\n");
+    s.push_str("\tcall 1\n");
     s.push_str("\thalt\n");
 
     // Instructions
@@ -187,8 +195,8 @@ mod tests {
         // Word 2: first-instr offset = 3 (header words, no data)
         let off = i64::from_le_bytes(out[16..24].try_into().unwrap());
         assert_eq!(off, 3);
-        // Total size = 3 header words + 4 startup words = 7 × 8 = 56 bytes
-        assert_eq!(out.len(), 56);
+        // Total size = 3 header words + 5 startup words = 8 × 8 = 64 bytes
+        assert_eq!(out.len(), 64);
     }
 
     #[test]
@@ -208,12 +216,12 @@ mod tests {
             Byc::no_operand(Op::Halt),
         ];
         let out = assemble(&bycs, &[], &HashMap::new(), None, 0);
-        // 3 header + 4 startup + 2 program = 9 words × 8 bytes = 72
-        assert_eq!(out.len(), 72);
+        // 3 header + 5 startup + 2 program = 10 words × 8 bytes = 80
+        assert_eq!(out.len(), 80);
     }
 
     #[test]
-    fn startup_sequence_is_halt_call_push() {
+    fn startup_sequence_calls_main_with_argv() {
         let out = assemble(&[], &[], &HashMap::new(), None, 0);
         // Startup begins at word 3 (byte 24).
         // Word 3: PUSH R_IMM <main_addr=0>
@@ -222,11 +230,13 @@ mod tests {
         // Word 4: PUSH imm:argc
         assert_eq!(out[32], Op::Push as u8);
         assert_eq!(out[33], BycRegion::Imm as u8);
-        // Word 5: CALL imm:1
-        assert_eq!(out[40], Op::Call as u8);
-        assert_eq!(out[41], BycRegion::Imm as u8);
-        // Word 6: HALT
-        assert_eq!(out[48], Op::Halt as u8);
+        // Word 5: NEWARRAY
+        assert_eq!(out[40], Op::Newarray as u8);
+        // Word 6: CALL imm:1
+        assert_eq!(out[48], Op::Call as u8);
+        assert_eq!(out[49], BycRegion::Imm as u8);
+        // Word 7: HALT
+        assert_eq!(out[56], Op::Halt as u8);
     }
 
     #[test]
@@ -60,12 +60,30 @@ pub fn genfirst(tree: &Tree, ctx: &mut CodegenContext) {
                 .unwrap_or_else(|| ctx.genlabel()))
         }
 
+        // ── While — first comes from the condition (kids[0]).  A condition
+        //    such as `true` emits no code of its own, but the loop jumps back
+        //    to it, so it is given a label too.
+        "WhileStmt" if !tree.kids.is_empty() => {
+            let first = first_from_kid(tree, ctx, 0).unwrap_or_else(|| ctx.genlabel());
+            ctx.node_mut(tree.kids[0].id).first = Some(first.clone());
+            Some(first)
+        }
+
         // ── Control flow — first comes from the condition (kids[0]).
         "WhileStmt" | "IfThenStmt" | "IfThenElseStmt" => {
             Some(first_from_kid(tree, ctx, 0)
                 .unwrap_or_else(|| ctx.genlabel()))
         }
 
+        // ── Switch — first comes from the selector (kids[0]).  Each case
+        //    group is jumped to from the tests ahead of it, so it gets a
+        //    label of its own.
+        "SwitchStmt" => {
+            Some(first_from_kid(tree, ctx, 0)
+                .unwrap_or_else(|| ctx.genlabel()))
+        }
+        "CaseClause" => Some(ctx.genlabel()),
+
         // ── For loop — first comes from init (kids[0]).
         "ForStmt" => {
             Some(first_from_kid(tree, ctx, 0)
@@ -84,8 +102,8 @@ pub fn genfirst(tree: &Tree, ctx: &mut CodegenContext) {
                 .unwrap_or_else(|| ctx.genlabel()))
         }
 
-        // ── Array/instance creation — always emits an instruction.
-        "ArrayCreation" | "InstanceCreation" | "ArrayAccess" => {
+        // ── Array/instance creation and break — always emit an instruction.
+        "ArrayCreation" | "ArrayInit" | "InstanceCreation" | "ArrayAccess" | "BreakStmt" => {
             Some(ctx.genlabel())
         }
 
@@ -115,9 +133,25 @@ pub fn genfirst(tree: &Tree, ctx: &mut CodegenContext) {
 /// all of that node's code has executed.  It is an inherited attribute:
 /// parents set it on children before recursing.
 ///
+/// A `break` follows on to the exit of the innermost loop or switch
+/// around it.
+///
 /// Call this after [`genfirst`] has run on the whole tree.
 pub fn genfollow(tree: &Tree, ctx: &mut CodegenContext) {
-    let my_follow = ctx.node(tree.id).and_then(|n| n.follow.clone());
+    follow_with_exits(tree, ctx, &mut Vec::new());
+}
+
+/// [`genfollow`], with the exit labels of the loops and switches around
+/// `tree`, innermost last.
+fn follow_with_exits(tree: &Tree, ctx: &mut CodegenContext, exits: &mut Vec<Address>) {
+    let mut my_follow = ctx.node(tree.id).and_then(|n| n.follow.clone());
+    let is_loop = matches!(tree.sym.as_str(), "WhileStmt" | "ForStmt" | "SwitchStmt");
+    if is_loop && my_follow.is_none() {
+        // A loop or switch ending a method still needs an exit for its breaks.
+        let exit = ctx.genlabel();
+        ctx.node_mut(tree.id).follow = Some(exit.clone());
+        my_follow = Some(exit);
+    }
 
     match tree.sym.as_str() {
         // ── Block: sequence of statements.
@@ -142,17 +176,16 @@ pub fn genfollow(tree: &Tree, ctx: &mut CodegenContext) {
 
         // ── WhileStmt: kids = [cond, body]
         //    cond.follow = our follow (if cond is false → exit)
-        //    body.follow = cond.first (loop back)
+        //    body.follow = a new label ending the body, where the loop
+        //                  jumps back to cond.  It is not cond.first itself:
+        //                  the statement ending the body places its follow.
         "WhileStmt" if tree.kids.len() == 2 => {
-            let cond_first = ctx.node(tree.kids[0].id).and_then(|n| n.first.clone());
             // cond's follow is the loop exit (our follow)
             if let Some(f) = my_follow.clone() {
                 ctx.node_mut(tree.kids[0].id).follow = Some(f);
             }
-            // body's follow loops back to cond
-            if let Some(f) = cond_first {
-                ctx.node_mut(tree.kids[1].id).follow = Some(f);
-            }
+            let back = ctx.genlabel();
+            ctx.node_mut(tree.kids[1].id).follow = Some(back);
         }
 
         // ── IfThenStmt: kids = [cond, body]
@@ -199,6 +232,56 @@ pub fn genfollow(tree: &Tree, ctx: &mut CodegenContext) {
             }
         }
 
+        // ── TryStmt: kids = [body, catch...] or [body, finally, catch...]
+        //    body.follow    = finally.first (or our follow)
+        //    catch.follow   = finally.first (or our follow)
+        //    finally.follow = our follow
+        "TryStmt" if tree.kids.len() >= 2 => {
+            let finally_first = (tree.rule == 1)
+                .then(|| ctx.node(tree.kids[1].id).and_then(|n| n.first.clone()))
+                .flatten();
+            if let Some(f) = finally_first.or(my_follow.clone()) {
+                ctx.node_mut(tree.kids[0].id).follow = Some(f.clone());
+                for kid in &tree.kids[tree.rule as usize + 1..] {
+                    ctx.node_mut(kid.id).follow = Some(f.clone());
+                }
+            }
+            if tree.rule == 1
+                && let Some(f) = my_follow.clone()
+            {
+                ctx.node_mut(tree.kids[1].id).follow = Some(f);
+            }
+        }
+
+        // ── SwitchStmt: kids = [selector, case...]
+        //    selector.follow = our follow
+        //    case.follow     = a new label ending the group, from which
+        //                      control falls through into the next one
+        "SwitchStmt" if !tree.kids.is_empty() => {
+            if let Some(f) = my_follow.clone() {
+                ctx.node_mut(tree.kids[0].id).follow = Some(f);
+            }
+            for clause in &tree.kids[1..] {
+                let end = ctx.genlabel();
+                ctx.node_mut(clause.id).follow = Some(end);
+            }
+        }
+
+        // ── CaseClause: kids = [body, label...]
+        //    body.follow = our follow
+        "CaseClause" if !tree.kids.is_empty() => {
+            if let Some(f) = my_follow.clone() {
+                ctx.node_mut(tree.kids[0].id).follow = Some(f);
+            }
+        }
+
+        // ── BreakStmt: leaves the innermost loop or switch.
+        "BreakStmt" => {
+            if let Some(exit) = exits.last() {
+                ctx.node_mut(tree.id).follow = Some(exit.clone());
+            }
+        }
+
         // ── AddExpr/MulExpr: kids = [lhs, op_leaf, rhs]
         //    both operands inherit our follow.
         "AddExpr" | "MulExpr" | "RelExpr" | "EqExpr"
@@ -224,8 +307,14 @@ pub fn genfollow(tree: &Tree, ctx: &mut CodegenContext) {
     }
 
     // Recurse pre-order (children already have their follow set above).
+    if is_loop {
+        exits.extend(my_follow);
+    }
     for kid in &tree.kids {
-        genfollow(kid, ctx);
+        follow_with_exits(kid, ctx, exits);
+    }
+    if is_loop {
+        exits.pop();
     }
 }
 
@@ -271,10 +360,12 @@ pub fn gentargets(tree: &Tree, ctx: &mut CodegenContext) {
         }
 
         // ── WhileStmt: kids = [cond, body]
-        //    cond.on_true  = body.first  (enter loop body)
+        //    cond.on_true  = body.first  (enter loop body), or cond.first
+        //                    for an empty body (test again)
         //    cond.on_false = our follow  (exit loop)
         "WhileStmt" if tree.kids.len() == 2 => {
-            let body_first = ctx.node(tree.kids[1].id).and_then(|n| n.first.clone());
+            let body_first = ctx.node(tree.kids[1].id).and_then(|n| n.first.clone())
+                .or_else(|| ctx.node(tree.kids[0].id).and_then(|n| n.first.clone()));
             if let Some(t) = body_first {
                 ctx.node_mut(tree.kids[0].id).on_true = Some(t);
             }
@@ -10,14 +10,17 @@
 //! 3. **genfollow**  — inherit `follow` exit-point labels (pre-order).
 //! 4. **gentargets** — inherit `on_true`/`on_false` for Boolean exprs (pre-order).
 //! 5. **gencode**    — emit `Vec<Tac>` for each node (post-order).
+//! 6. **bounds**     — remove the array bounds checks that cannot fail.
 
 pub mod address;
+pub mod bounds;
 pub mod byc;
 pub mod bytecode;
 pub mod j0file;
 pub mod context;
 pub mod emit;
 pub mod gencode;
+pub mod ir;
 pub mod labels;
 pub mod layout;
 pub mod pipeline;
@@ -25,16 +28,20 @@ pub mod tac;
 mod tests;
 
 use jzero_ast::tree::Tree;
+use jzero_ast::{CancellationToken, Cancelled};
 use jzero_semantic::SemanticResult;
 
 pub use address::{Address, Region};
+pub use bounds::BoundsCheckStats;
 pub use context::CodegenContext;
+pub use ir::{parse_ir, IrError, IrProgram};
 pub use tac::{Op, Tac};
 
 /// Run all codegen passes on an already-analysed syntax tree.
 ///
 /// Returns the populated [`CodegenContext`]. Call [`emit::emit`] on the
-/// result to produce human-readable assembler output.
+/// result to produce human-readable assembler output.  A construct the VM
+/// cannot run is listed in [`CodegenContext::errors`].
 ///
 /// # Example
 ///
@@ -48,22 +55,58 @@ pub use tac::{Op, Tac};
 /// println!("{}", asm);
 /// ```
 pub fn generate(tree: &Tree, sem: &SemanticResult) -> CodegenContext {
+    match run_passes(tree, sem, None) {
+        Ok(ctx) => ctx,
+        Err(Cancelled) => unreachable!("codegen without a token cannot be cancelled"),
+    }
+}
+
+/// Like [`generate`], but give up between passes once `cancel` is
+/// cancelled.
+///
+/// # Errors
+/// Returns [`Cancelled`] if the token was cancelled before the last pass.
+pub fn generate_cancellable(
+    tree: &Tree,
+    sem: &SemanticResult,
+    cancel: &CancellationToken,
+) -> Result<CodegenContext, Cancelled> {
+    run_passes(tree, sem, Some(cancel))
+}
+
+fn run_passes(
+    tree: &Tree,
+    sem: &SemanticResult,
+    cancel: Option<&CancellationToken>,
+) -> Result<CodegenContext, Cancelled> {
+    let check = || cancel.map_or(Ok(()), CancellationToken::check);
     let mut ctx = CodegenContext::new();
 
     // Pass 1 — assign addresses to all variables and parameters.
+    check()?;
     layout::assign_addresses(&sem.global, &mut ctx);
 
     // Pass 2 — synthesize `first` labels (post-order).
+    check()?;
     labels::genfirst(tree, &mut ctx);
 
     // Pass 3 — inherit `follow` labels (pre-order).
+    check()?;
     labels::genfollow(tree, &mut ctx);
 
     // Pass 4 — inherit `on_true`/`on_false` (pre-order).
+    check()?;
     labels::gentargets(tree, &mut ctx);
 
     // Pass 5 — emit intermediate code (post-order).
+    check()?;
+    ctx.constants = sem.constants.clone();
+    gencode::gen_field_inits(tree, &mut ctx);
     gencode::gencode(tree, &mut ctx);
 
-    ctx
-}
\ No newline at end of file
+    // Pass 6 — eliminate redundant bounds checks.
+    check()?;
+    bounds::eliminate(tree, sem, &mut ctx);
+
+    Ok(ctx)
+}
@@ -8,9 +8,10 @@
 use jzero_ast::tree::Tree;
 
 use crate::{
-    bytecode::translate,
-    context::CodegenContext,
-    j0file::{assemble, disassemble_text},
+    bytecode::translate_with_lines,
+    context::{CodegenContext, StringEntry},
+    ir::IrProgram,
+    j0file::{assemble, disassemble_text, STARTUP_WORDS},
 };
 
 /// Result of the bytecode compilation step.
@@ -18,19 +19,72 @@ pub struct BytecodeOutput {
     pub binary:      Vec<u8>,
     pub text:        String,
     pub main_offset: usize,
+    /// The source line of each instruction of the code section.
+    pub lines:       LineTable,
+    /// The native methods the code calls, by qualified name; the VM must
+    /// be given each before it runs the image.
+    pub natives:     Vec<String>,
+}
+
+/// Which source line each instruction of an image's code section was
+/// generated for, by absolute byte offset in the image.  The VM's
+/// execution counts and trace use it to speak in source lines.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LineTable {
+    /// Byte offset of the first code instruction.
+    base:  usize,
+    /// Line of each code instruction in turn; 0 if unknown.
+    lines: Vec<usize>,
+}
+
+impl LineTable {
+    pub fn new(base: usize, lines: Vec<usize>) -> Self {
+        LineTable { base, lines }
+    }
+
+    /// The line of the instruction at byte offset `offset`; `None` for
+    /// the startup sequence, or an instruction of no known line.
+    pub fn line_of(&self, offset: usize) -> Option<usize> {
+        let index = offset.checked_sub(self.base)? / 8;
+        self.lines.get(index).copied().filter(|&l| l > 0)
+    }
 }
 
 /// Compile a fully-generated `CodegenContext` into a `.j0` binary image.
 /// `argc` is the number of command-line arguments to pass to main().
 pub fn compile_bytecode(tree: &Tree, ctx: &CodegenContext, argc: i64) -> BytecodeOutput {
-    // ── 1. Collect flat TAC ──────────────────────────────────────────────────
-    let icode = collect_icode(tree, ctx);
+    compile_icode(collect_icode(tree, ctx), &ctx.strings, &ctx.natives, argc)
+}
+
+/// Compile intermediate code read with [`parse_ir`](crate::ir::parse_ir)
+/// into a `.j0` binary image, as [`compile_bytecode`] compiles the code it
+/// was printed from.
+pub fn compile_ir(program: &IrProgram, argc: i64) -> BytecodeOutput {
+    let icode = program.procs.iter().flat_map(|proc| method_code(&proc.code)).collect();
+    compile_icode(icode, &program.strings, &program.natives, argc)
+}
+
+/// [`compile_bytecode`] with the method `entry` run at startup instead of
+/// the first method, e.g. to run one unit test; `None` if the tree has no
+/// such method.  It is called as `main` is, with `argc` as its argument,
+/// and initializes the static fields first as `main` does.
+pub fn compile_bytecode_entry(tree: &Tree, ctx: &CodegenContext, argc: i64, entry: &str) -> Option<BytecodeOutput> {
+    let icode = entry_methods(tree, ctx, Some(entry))?.into_iter().flat_map(|(_, code)| method_code(&code)).collect();
+    Some(compile_icode(icode, &ctx.strings, &ctx.natives, argc))
+}
+
+fn compile_icode(
+    icode: Vec<crate::tac::Tac>,
+    strings: &[StringEntry],
+    natives: &[String],
+    argc: i64,
+) -> BytecodeOutput {
 
     // ── 2. Serialize string pool → data section bytes ────────────────────────
-    let data_bytes = build_data_section(ctx);
+    let data_bytes = build_data_section(strings);
 
     // ── 3. Translate TAC → bytecode ──────────────────────────────────────────
-    let (bycs, labeltable) = translate(&icode);
+    let (bycs, labeltable, lines) = translate_with_lines(&icode, natives);
 
     // DEBUG: dump icode and bytecode
     for (i, t) in icode.iter().enumerate() {
@@ -42,20 +96,25 @@ pub fn compile_bytecode(tree: &Tree, ctx: &CodegenContext, argc: i64) -> Bytecod
 
     // ── 4. Compute main's absolute byte offset ───────────────────────────────
     // The TAC for Jzero programs has no Lab before `proc main` — main is simply
-    // the first instruction in the code section.  Its absolute byte offset is:
-    //   (3 header words + data_words + 3 startup words) * 8
+    // the first method, at the first instruction in the code section.  Its absolute byte offset is:
+    //   (3 header words + data_words + STARTUP_WORDS) * 8
     // We compute data_words here so assemble() can use the same value.
     let data_padded_len = (data_bytes.len() + 7) & !7;
     let data_words   = data_padded_len / 8;
     let header_words = 3usize;
-    let startup_words = 4usize;
-    let main_offset  = (header_words + data_words + startup_words) * 8;
+    let main_offset  = (header_words + data_words + STARTUP_WORDS) * 8;
 
     // ── 5. Assemble ──────────────────────────────────────────────────────────
     let binary = assemble(&bycs, &data_bytes, &labeltable, Some(main_offset as i64), argc);
     let text   = disassemble_text(&bycs, &data_bytes);
 
-    BytecodeOutput { binary, text, main_offset }
+    BytecodeOutput {
+        binary,
+        text,
+        main_offset,
+        lines: LineTable::new(main_offset, lines),
+        natives: natives.to_vec(),
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -64,41 +123,73 @@ pub fn compile_bytecode(tree: &Tree, ctx: &CodegenContext, argc: i64) -> Bytecod
 
 /// Walk the tree and concatenate all icode vecs from MethodDecl blocks.
 /// Returns a single flat Vec<Tac> representing the whole program.
-fn collect_icode(tree: &Tree, ctx: &CodegenContext) -> Vec<crate::tac::Tac> {
-    let mut out = Vec::new();
-    collect_icode_rec(tree, ctx, &mut out);
-    out
+pub fn collect_icode(tree: &Tree, ctx: &CodegenContext) -> Vec<crate::tac::Tac> {
+    entry_methods(tree, ctx, None).unwrap_or_default().into_iter().flat_map(|(_, code)| method_code(&code)).collect()
+}
+
+/// The name and body code of each method of `tree`: `entry` first, or the
+/// first method if `None`, with the static fields initialized at its
+/// start, and then the others in source order.  `None` if there is no
+/// method `entry`.
+fn entry_methods(tree: &Tree, ctx: &CodegenContext, entry: Option<&str>) -> Option<Vec<(String, Vec<crate::tac::Tac>)>> {
+    let mut methods = Vec::new();
+    collect_methods(tree, ctx, &mut methods);
+    let at = match entry {
+        Some(entry) => methods.iter().position(|(name, _)| name == entry)?,
+        None if methods.is_empty() => return Some(methods),
+        None => 0,
+    };
+    let (name, code) = methods.remove(at);
+    methods.insert(0, (name, ctx.class_init.iter().cloned().chain(code).collect()));
+    Some(methods)
 }
 
-fn collect_icode_rec(tree: &Tree, ctx: &CodegenContext, out: &mut Vec<crate::tac::Tac>) {
+/// The name and body code of each method of `tree`, in source order.
+fn collect_methods(tree: &Tree, ctx: &CodegenContext, methods: &mut Vec<(String, Vec<crate::tac::Tac>)>) {
     if tree.sym == "MethodDecl" {
-        // Find the highest local offset used in this method's icode so we can
-        // emit LOCAL n to pre-allocate stack space and prevent overlap between
-        // locals and the expression stack.
-        if let Some(block) = tree.kids.get(1) {
-            if let Some(info) = ctx.node(block.id) {
-                let max_local = max_local_offset(&info.icode);
-                if max_local > 0 {
-                    // Emit LOCAL n where n = number of 8-byte slots needed.
-                    let n = max_local / 8;
-                    out.push(crate::tac::Tac::new1(
-                        crate::tac::Op::Proc,
-                        crate::address::Address::imm(n),
-                    ));
-                }
-                out.extend(info.icode.iter().cloned());
-            }
-        }
-        // Append explicit RET.
-        out.push(crate::tac::Tac::new1(
-            crate::tac::Op::Ret,
-            crate::address::Address::imm(0),
-        ));
+        methods.push((method_name(tree).unwrap_or_default(), collect_method(tree, ctx)));
         return;
     }
     for kid in &tree.kids {
-        collect_icode_rec(kid, ctx, out);
+        collect_methods(kid, ctx, methods);
+    }
+}
+
+/// The name a MethodDecl declares.
+fn method_name(tree: &Tree) -> Option<String> {
+    if tree.sym == "MethodDeclarator" {
+        return tree.kids.first()?.tok.as_ref().map(|t| t.text.clone());
+    }
+    tree.kids.iter().find_map(method_name)
+}
+
+/// The code of the body of the MethodDecl `tree`.
+fn collect_method(tree: &Tree, ctx: &CodegenContext) -> Vec<crate::tac::Tac> {
+    tree.kids.get(1).and_then(|block| ctx.node(block.id)).map_or_else(Vec::new, |info| info.icode.clone())
+}
+
+/// A method body's `icode`, framed for the bytecode translator.
+fn method_code(icode: &[crate::tac::Tac]) -> Vec<crate::tac::Tac> {
+    let mut out = Vec::new();
+    // Find the highest local offset used in this method's icode so we can
+    // emit LOCAL n to pre-allocate stack space and prevent overlap between
+    // locals and the expression stack.
+    let max_local = max_local_offset(icode);
+    if max_local > 0 {
+        // Emit LOCAL n where n = number of 8-byte slots needed.
+        let n = max_local / 8;
+        out.push(crate::tac::Tac::new1(
+            crate::tac::Op::Proc,
+            crate::address::Address::imm(n),
+        ));
     }
+    out.extend(icode.iter().cloned());
+    // Append explicit RET.
+    out.push(crate::tac::Tac::new1(
+        crate::tac::Op::Ret,
+        crate::address::Address::imm(0),
+    ));
+    out
 }
 
 // ---------------------------------------------------------------------------
@@ -109,20 +200,20 @@ fn collect_icode_rec(tree: &Tree, ctx: &CodegenContext, out: &mut Vec<crate::tac
 ///
 /// Each string is written at its `string_offset` as NUL-terminated UTF-8,
 /// padded so the next entry starts on an 8-byte boundary.
-fn build_data_section(ctx: &CodegenContext) -> Vec<u8> {
-    if ctx.strings.is_empty() {
+fn build_data_section(strings: &[StringEntry]) -> Vec<u8> {
+    if strings.is_empty() {
         return Vec::new();
     }
 
     // Total size: last entry's offset + its padded length.
-    let total = ctx.strings.iter().map(|e| {
+    let total = strings.iter().map(|e| {
         let raw = e.value.len() + 1; // +1 for NUL
         e.string_offset as usize + pad8_size(raw)
     }).max().unwrap_or(0);
 
     let mut data = vec![0u8; total];
 
-    for entry in &ctx.strings {
+    for entry in strings {
         let off   = entry.string_offset as usize;
         let bytes = entry.value.as_bytes();
         let end   = off + bytes.len();
@@ -162,13 +253,11 @@ fn max_local_offset(icode: &[crate::tac::Tac]) -> i64 {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use jzero_ast::tree::reset_ids;
     use jzero_parser::parse_tree;
     use jzero_semantic::analyze;
     use crate::generate;
 
     fn compile(src: &str) -> BytecodeOutput {
-        reset_ids();
         let mut tree = parse_tree(src).expect("parse failed");
         let sem = analyze(&mut tree);
         let ctx = generate(&tree, &sem);
@@ -220,4 +309,15 @@ mod tests {
         }"#);
         assert_eq!(out.binary.len() % 8, 0, "binary not word-aligned");
     }
+
+    #[test]
+    fn line_table_maps_code_to_source_lines() {
+        let out = compile("public class t {\n    public static void main(String argv[]) {\n        int x;\n        x = 1 + 2;\n        System.out.println(x);\n    }\n}\n");
+        assert_eq!(out.lines.line_of(0), None, "the header has no line");
+        let code_end = out.binary.len();
+        let lines: Vec<usize> = (out.main_offset..code_end).step_by(8)
+            .filter_map(|off| out.lines.line_of(off)).collect();
+        assert!(lines.contains(&4) && lines.contains(&5), "{:?}", lines);
+        assert!(lines.windows(2).all(|w| w[0] <= w[1]), "{:?}", lines);
+    }
 }
\ No newline at end of file
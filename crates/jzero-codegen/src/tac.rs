@@ -10,23 +10,25 @@ pub struct Tac {
     pub op1: Option<Address>,
     pub op2: Option<Address>,
     pub op3: Option<Address>,
+    /// Source line the instruction was generated for; 0 if unknown.
+    pub line: usize,
 }
 
 impl Tac {
     pub fn new0(op: Op) -> Self {
-        Self { op, op1: None, op2: None, op3: None }
+        Self { op, op1: None, op2: None, op3: None, line: 0 }
     }
 
     pub fn new1(op: Op, op1: Address) -> Self {
-        Self { op, op1: Some(op1), op2: None, op3: None }
+        Self { op, op1: Some(op1), op2: None, op3: None, line: 0 }
     }
 
     pub fn new2(op: Op, op1: Address, op2: Address) -> Self {
-        Self { op, op1: Some(op1), op2: Some(op2), op3: None }
+        Self { op, op1: Some(op1), op2: Some(op2), op3: None, line: 0 }
     }
 
     pub fn new3(op: Op, op1: Address, op2: Address, op3: Address) -> Self {
-        Self { op, op1: Some(op1), op2: Some(op2), op3: Some(op3) }
+        Self { op, op1: Some(op1), op2: Some(op2), op3: Some(op3), line: 0 }
     }
 }
 
@@ -60,6 +62,10 @@ pub enum Op {
     Neg,
     /// op1 = op2 ++ op3 (string concatenation)
     Sadd,
+    /// op1 = op2 * op3, of two fixed-point doubles
+    Fmul,
+    /// op1 = op2 / op3, of two fixed-point doubles
+    Fdiv,
 
     // ── Data movement ───────────────────────────────────────────────────────
     /// op1 = op2  (assignment / copy)
@@ -76,6 +82,8 @@ pub enum Op {
     Store,
     /// op1 = alloc(op2 * wordsize)  (heap array allocation)
     NewArray,
+    /// Trap unless 0 <= op2 < sizeof(array at op1)  (bounds check)
+    Bchk,
 
     // ── Control flow ────────────────────────────────────────────────────────
     /// Unconditional jump to op1 (a label address)
@@ -98,13 +106,19 @@ pub enum Op {
     // ── Method calls ────────────────────────────────────────────────────────
     /// Push parameter op1 onto the call stack
     Parm,
-    /// Call method at op1 with op2 parameters
+    /// Call method at op1 with op2 parameters; a native method's result,
+    /// if any, goes to op3
     Call,
     /// Return (from current method); op1 is optional return value
     Ret,
+    /// Throw the exception op1.  With no handlers at run time yet, this
+    /// ends the program.
+    Throw,
 
     /// op1 = String.valueOf(op2)  — convert integer to string pool key
     Itos,
+    /// op1 = String.valueOf(op2)  — convert a double to string pool key
+    Ftos,
 
     // ── Declarations (pseudo-instructions) ──────────────────────────────────
     /// Declare a global variable: name at address op1
@@ -127,12 +141,15 @@ impl std::fmt::Display for Op {
             Op::Mod        => "MOD",
             Op::Neg        => "NEG",
             Op::Sadd       => "SADD",
+            Op::Fmul       => "FMUL",
+            Op::Fdiv       => "FDIV",
             Op::Asn        => "ASN",
             Op::Addr       => "ADDR",
             Op::Asize      => "ASIZE",
             Op::Load       => "LOAD",
             Op::Store      => "STORE",
             Op::NewArray   => "NEWARRAY",
+            Op::Bchk       => "BCHK",
             Op::Goto       => "GOTO",
             Op::Lab        => "LAB",
             Op::Blt        => "BLT",
@@ -144,7 +161,9 @@ impl std::fmt::Display for Op {
             Op::Parm       => "PARM",
             Op::Call       => "CALL",
             Op::Ret        => "RET",
+            Op::Throw      => "THROW",
             Op::Itos       => "ITOS",
+            Op::Ftos       => "FTOS",
             Op::Global     => "global",
             Op::StringDecl => "string",
             Op::Proc       => "proc",
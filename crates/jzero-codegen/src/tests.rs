@@ -1,13 +1,11 @@
 #[cfg(test)]
 mod tests {
-    use jzero_ast::tree::reset_ids;
     use jzero_parser::parse_tree;
     use jzero_semantic::analyze;
 
     use crate::{emit::emit, generate};
 
     fn compile(src: &str) -> String {
-        reset_ids();
         let mut tree = parse_tree(src).expect("parse failed");
         let sem = analyze(&mut tree);
         let ctx = generate(&tree, &sem);
@@ -62,6 +60,18 @@ mod tests {
         assert!(string_section.contains("L"), "string label missing");
     }
 
+    #[test]
+    fn test_string_escapes_listed_escaped() {
+        let out = compile(
+            r#"public class t {
+                 public static void main(String argv[]) {
+                   System.out.println("a\tb\n");
+                 }
+               }"#,
+        );
+        assert!(out.contains(r#"string "a\tb\n""#), "{}", out);
+    }
+
     #[test]
     fn test_string_deduplication() {
         let out = compile(
@@ -173,6 +183,33 @@ mod tests {
         assert!(out.contains("imm:42"), "integer literal should appear as imm:42");
     }
 
+    #[test]
+    fn test_imm_operand_for_char_literal() {
+        let out = compile(
+            r#"public class t {
+                 public static void main(String argv[]) {
+                   int c;
+                   c = '\n';
+                 }
+               }"#,
+        );
+        assert!(out.contains("imm:10"), "'\\n' should appear as imm:10");
+    }
+
+    #[test]
+    fn test_constant_cast_is_folded() {
+        let out = compile(
+            r#"public class t {
+                 public static void main(String argv[]) {
+                   int n;
+                   n = (int) 7.9;
+                 }
+               }"#,
+        );
+        let asn = out.lines().find(|l| l.trim().starts_with("ASN")).expect("ASN missing");
+        assert!(asn.ends_with("imm:7"), "(int) 7.9 should be assigned as imm:7: {asn}");
+    }
+
     #[test]
     fn test_assignment_emits_asn() {
         let out = compile(
@@ -186,6 +223,43 @@ mod tests {
         assert!(has_op(&out, "ASN"), "ASN instruction missing");
     }
 
+    #[test]
+    fn test_try_runs_its_block_then_finally() {
+        let src = r#"public class t {
+                 public static void main(String argv[]) {
+                   int x;
+                   try { x = 1; } catch (Oops e) { x = 2; } finally { x = 3; }
+                   throw new Oops();
+                 }
+               }"#;
+        let mut tree = jzero_parser::parse_tree_with(src, jzero_lexer::LexOptions::at_level(jzero_lexer::Level::Extended))
+            .expect("parse failed");
+        let sem = analyze(&mut tree);
+        let out = emit(&tree, &generate(&tree, &sem));
+        let asn: Vec<&str> = out.lines().filter(|l| l.starts_with("ASN loc:16,imm:")).collect();
+        assert_eq!(asn, ["ASN loc:16,imm:1", "ASN loc:16,imm:3"], "{}", out);
+        assert_eq!(count_op(&out, "THROW"), 1, "{}", out);
+    }
+
+    #[test]
+    fn test_compound_assignments_emit_their_operator() {
+        let out = compile(
+            r#"public class t {
+                 public static void main(String argv[]) {
+                   int x;
+                   x = 5;
+                   x *= 3;
+                   x /= 2;
+                   x %= 4;
+                 }
+               }"#,
+        );
+        for op in ["MUL", "DIV", "MOD"] {
+            assert_eq!(count_op(&out, op), 1, "{} missing:\n{}", op, out);
+        }
+        assert_eq!(count_op(&out, "ASN"), 4);
+    }
+
     // ── Array access ──────────────────────────────────────────────────────────
 
     #[test]
@@ -201,6 +275,58 @@ mod tests {
         assert!(has_op(&out, "ASIZE"), "ASIZE instruction missing for .length");
     }
 
+    #[test]
+    fn test_array_initializer_stores_each_element() {
+        let out = compile(
+            r#"public class t {
+                 static int primes[] = {2, 3, 5};
+                 public static void main(String argv[]) {
+                   int grid[][] = {{1}, {2, 3}};
+                   int x;
+                   x = primes[1];
+                 }
+               }"#,
+        );
+        assert_eq!(count_op(&out, "NEWARRAY"), 4);
+        assert_eq!(count_op(&out, "STORE"), 3 + 2 + 1 + 2);
+        // The static field is set up in main, before its first statement.
+        let body: Vec<&str> = out.lines().map(str::trim).skip_while(|l| !l.starts_with("NEWARRAY")).collect();
+        assert!(body[1].starts_with("STORE") && body[1].ends_with("imm:0,imm:2"), "{}", out);
+        assert!(body[4].starts_with("ASN") && body[4].contains("class:"), "{}", out);
+    }
+
+    #[test]
+    fn test_instance_fields_are_initialized_by_new() {
+        let out = compile(
+            r#"public class t {
+                 int n = 7;
+                 public static void main(String argv[]) {
+                   t a;
+                   a = new t();
+                 }
+               }"#,
+        );
+        let body: Vec<&str> = out.lines().map(str::trim).skip_while(|l| !l.starts_with("proc main")).collect();
+        assert!(body[1].starts_with("ASN class:") && body[1].ends_with(",imm:7"), "{}", out);
+        assert!(body[2].starts_with("PARM self") && body[3].starts_with("CALL"), "{}", out);
+    }
+
+    #[test]
+    fn test_initializer_is_assigned_in_place() {
+        let out = compile(
+            r#"public class t {
+                 public static void main(String argv[]) {
+                   int x = 4, y = x * 2;
+                   System.out.println(y);
+                 }
+               }"#,
+        );
+        let body: Vec<&str> = out.lines().map(str::trim).skip_while(|l| !l.starts_with("proc main")).collect();
+        assert!(body[1].starts_with("ASN loc:") && body[1].ends_with(",imm:4"), "{}", out);
+        assert!(body[2].starts_with("MUL"), "{}", out);
+        assert!(body[3].starts_with("ASN loc:"), "{}", out);
+    }
+
     // ── Control flow — while ─────────────────────────────────────────────────
 
     #[test]
@@ -274,6 +400,42 @@ mod tests {
         assert!(count_op(&out, "GOTO") >= 1);
     }
 
+    #[test]
+    fn test_constant_conditions_drop_the_dead_branch() {
+        let src = r#"public class t {
+                 static final bool DEBUG = false;
+                 static final int LEVEL = 2;
+                 public static void main(String argv[]) {
+                   int x;
+                   x = 1;
+                   if (DEBUG) { x = 7; }
+                   if (LEVEL > 1) { x = 8; } else { x = 9; }
+                   while (DEBUG && x > 0) { x = 6; }
+                 }
+               }"#;
+        let codegen = |tree: &mut jzero_ast::tree::Tree| {
+            let sem = analyze(tree);
+            assert!(sem.errors.is_empty(), "{:?}", sem.errors);
+            let ctx = generate(tree, &sem);
+            (emit(tree, &ctx), ctx.folded_conditions)
+        };
+        let extended = jzero_lexer::LexOptions::at_level(jzero_lexer::Level::Extended);
+        let mut tree = jzero_parser::parse_tree_with(src, extended).expect("parse failed");
+        let (out, folded) = codegen(&mut tree);
+        assert_eq!(folded, 3);
+        for dead in ["imm:7", "imm:9", "imm:6"] {
+            assert!(!out.contains(dead), "{} left in:\n{}", dead, out);
+        }
+        assert!(out.contains("imm:8") && !has_op(&out, "BGT") && !has_op(&out, "BNE"), "{}", out);
+
+        let mut tree = jzero_parser::parse_tree_with(src, extended).expect("parse failed");
+        jzero_semantic::define(&mut tree, "DEBUG", "true").unwrap();
+        let (out, _) = codegen(&mut tree);
+        assert!(out.contains("imm:7") && !out.contains("imm:9"), "{}", out);
+        // `DEBUG && x > 0` still tests `x`.
+        assert!(out.contains("imm:6") && has_op(&out, "BGT"), "{}", out);
+    }
+
     // ── Method calls ──────────────────────────────────────────────────────────
 
     #[test]
@@ -369,4 +531,54 @@ mod tests {
         assert!(out.contains("imm:3"), "literal 3 as immediate");
         assert!(out.contains("imm:1"), "literal 1 as immediate");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_cancelled_codegen_stops() {
+        use jzero_ast::{CancellationToken, Cancelled};
+        let src = "public class T { public static void main(String argv[]) { } }";
+        let mut tree = parse_tree(src).expect("parse failed");
+        let sem = analyze(&mut tree);
+        let token = CancellationToken::new();
+        assert!(crate::generate_cancellable(&tree, &sem, &token).is_ok());
+        token.cancel();
+        assert!(matches!(crate::generate_cancellable(&tree, &sem, &token), Err(Cancelled)));
+    }
+
+    // ── Golden files ──────────────────────────────────────────────────────────
+
+    /// The IR of each example is kept in `tests/golden/<example>.tac`.  Run
+    /// with `JZERO_BLESS=1` to rewrite the files after a deliberate change
+    /// to lowering, and review the diff.
+    #[test]
+    fn test_examples_match_their_golden_ir() {
+        use crate::ir::parse_ir;
+        use crate::pipeline::{compile_bytecode, compile_ir};
+
+        let root = concat!(env!("CARGO_MANIFEST_DIR"), "/../../tests");
+        let bless = std::env::var_os("JZERO_BLESS").is_some();
+        let mut paths: Vec<_> = std::fs::read_dir(format!("{}/examples", root)).unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().is_some_and(|e| e == "java"))
+            .collect();
+        paths.sort();
+        assert!(!paths.is_empty());
+        for path in paths {
+            let mut tree = parse_tree(&std::fs::read_to_string(&path).unwrap()).expect("parse failed");
+            let sem = analyze(&mut tree);
+            let ctx = generate(&tree, &sem);
+            let ir = emit(&tree, &ctx);
+
+            let golden = format!("{}/golden/{}.tac", root, path.file_stem().unwrap().to_string_lossy());
+            if bless {
+                std::fs::write(&golden, &ir).unwrap();
+            }
+            let expected = std::fs::read_to_string(&golden).unwrap_or_default();
+            assert!(ir == expected, "{} differs from {}; rerun with JZERO_BLESS=1 if intended\n{}", path.display(), golden, ir);
+
+            // The golden text reads back into the same program.
+            let program = parse_ir(&expected).unwrap_or_else(|e| panic!("{}: {}", golden, e));
+            assert_eq!(program.to_string(), expected);
+            assert_eq!(compile_ir(&program, 0).binary, compile_bytecode(&tree, &ctx, 0).binary, "{}", golden);
+        }
+    }
+}
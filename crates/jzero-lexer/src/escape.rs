@@ -0,0 +1,141 @@
+//! Backslash escapes in character and string literals.
+//!
+//! Jzero accepts Java's single-character escapes: `\b`, `\t`, `\n`, `\f`,
+//! `\r`, `\"`, `\'`, `\\` and `\0`.  Unicode (`\u0041`) and multi-digit
+//! octal escapes are not supported.
+//!
+//! A text block, `"""` to `"""` over several lines, also takes `\` at the
+//! end of a line to join it to the next.
+
+/// The character written as `\c`, or `None` if `\c` is not an escape.
+pub fn unescape(c: char) -> Option<char> {
+    match c {
+        'b'  => Some('\u{8}'),
+        't'  => Some('\t'),
+        'n'  => Some('\n'),
+        'f'  => Some('\u{c}'),
+        'r'  => Some('\r'),
+        '"'  => Some('"'),
+        '\'' => Some('\''),
+        '\\' => Some('\\'),
+        '0'  => Some('\0'),
+        _ => None,
+    }
+}
+
+/// The value of a character literal including its quotes, e.g. `'a'` or
+/// `'\n'`; `None` if it is not exactly one character or valid escape.
+pub fn char_value(literal: &str) -> Option<char> {
+    let body = literal.strip_prefix('\'')?.strip_suffix('\'')?;
+    let mut chars = body.chars();
+    let c = match chars.next()? {
+        '\\' => unescape(chars.next()?)?,
+        '\'' | '\n' => return None,
+        c => c,
+    };
+    chars.next().is_none().then_some(c)
+}
+
+/// The value of a string literal including its quotes, e.g. `"a\tb"`
+/// or a text block; `None` if it has an invalid escape.
+pub fn string_value(literal: &str) -> Option<String> {
+    if let Some(block) = literal.strip_prefix(TEXT_BLOCK) {
+        return text_block_value(block.strip_suffix(TEXT_BLOCK)?);
+    }
+    let body = literal.strip_prefix('"')?.strip_suffix('"')?;
+    unescape_all(body, false)
+}
+
+const TEXT_BLOCK: &str = "\"\"\"";
+
+/// The value of a text block between its `"""`s.
+///
+/// The content starts on the line after the opening `"""`.  As in Java,
+/// the indentation shared by its non-blank lines and the line of the
+/// closing `"""` is stripped, as is the whitespace ending each line, and
+/// escapes are decoded last:
+///
+/// ```
+/// use jzero_lexer::escape::string_value;
+///
+/// let block = r#""""
+///     Dear \"X\",
+///       hello
+///     """"#;
+/// assert_eq!(string_value(block).unwrap(), "Dear \"X\",\n  hello\n");
+/// ```
+fn text_block_value(body: &str) -> Option<String> {
+    let (_, content) = body.split_once('\n')?;
+    let lines: Vec<&str> = content.split('\n').map(|l| l.strip_suffix('\r').unwrap_or(l)).collect();
+    let indent = |line: &str| line.len() - line.trim_start_matches(BLANK).len();
+    let is_blank = |line: &str| line.trim_matches(BLANK).is_empty();
+    let last = lines.len() - 1;
+    let strip = lines.iter().enumerate()
+        .filter(|&(i, line)| i == last || !is_blank(line))
+        .map(|(_, line)| indent(line))
+        .min()
+        .unwrap_or(0);
+    let stripped: Vec<&str> = lines.iter()
+        .map(|line| if is_blank(line) { "" } else { line[strip..].trim_end_matches(BLANK) })
+        .collect();
+    unescape_all(&stripped.join("\n"), true)
+}
+
+const BLANK: [char; 3] = [' ', '\t', '\u{c}'];
+
+/// `body` with its escapes decoded; with `join_lines`, `\` before a
+/// newline removes both.
+fn unescape_all(body: &str, join_lines: bool) -> Option<String> {
+    let mut value = String::with_capacity(body.len());
+    let mut chars = body.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            value.push(c);
+            continue;
+        }
+        match chars.next()? {
+            '\n' if join_lines => {}
+            e => value.push(unescape(e)?),
+        }
+    }
+    Some(value)
+}
+
+/// The byte offset and text of the first invalid escape in `literal`,
+/// e.g. `(3, "\\q")` for `"ab\qc"`.
+pub fn bad_escape(literal: &str) -> Option<(usize, &str)> {
+    let block = literal.starts_with(TEXT_BLOCK);
+    let mut chars = literal.char_indices();
+    while let Some((i, c)) = chars.next() {
+        if c != '\\' { continue; }
+        match chars.next() {
+            Some((_, e)) if unescape(e).is_some() || (block && e == '\n') => {}
+            next => {
+                let end = next.map_or(literal.len(), |(j, e)| j + e.len_utf8());
+                return Some((i, &literal[i..end]));
+            }
+        }
+    }
+    None
+}
+
+/// The text of a string literal that has `value`, quotes included:
+/// the inverse of [`string_value`].
+pub fn quote(value: &str) -> String {
+    let mut out = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '\u{8}' => out.push_str("\\b"),
+            '\t'    => out.push_str("\\t"),
+            '\n'    => out.push_str("\\n"),
+            '\u{c}' => out.push_str("\\f"),
+            '\r'    => out.push_str("\\r"),
+            '"'     => out.push_str("\\\""),
+            '\\'    => out.push_str("\\\\"),
+            '\0'    => out.push_str("\\0"),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
@@ -0,0 +1,179 @@
+//! Incremental relexing — patch a token list after an edit.
+//!
+//! An editor changes a few bytes per keystroke.  [`relex`] takes the
+//! tokens of the text before the edit and lexes only from just before the
+//! edited range until the new tokens line up with the old ones again; the
+//! tokens after that point are the old ones, moved by the size of the
+//! edit.  The result is what [`lex_with`](crate::lex_with) would give for
+//! the new text.
+//!
+//! ```
+//! use jzero_lexer::{LexOptions, SourceEdit, lex, relex};
+//!
+//! let old = lex("int x;\nint y;\n");
+//! // "x" → "count"
+//! let edit = SourceEdit { start: 4, old_end: 5, new_end: 9 };
+//! let new = relex(&old, "int count;\nint y;\n", &edit, &LexOptions::default());
+//! assert_eq!(new, lex("int count;\nint y;\n"));
+//! ```
+
+use crate::{LexError, LexOptions, Lexed, SpannedToken};
+
+/// An edit to a source: the bytes at `start..old_end` of the old text
+/// were replaced by the bytes at `start..new_end` of the new one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SourceEdit {
+    pub start: usize,
+    pub old_end: usize,
+    pub new_end: usize,
+}
+
+impl SourceEdit {
+    /// The edit that replaces `old_len` bytes at `start` with `new_text`.
+    pub fn replace(start: usize, old_len: usize, new_text: &str) -> Self {
+        SourceEdit { start, old_end: start + old_len, new_end: start + new_text.len() }
+    }
+
+    /// The old offset of `offset` in the new text, which must not be
+    /// before `new_end`.
+    fn old_offset(&self, offset: usize) -> usize {
+        offset - self.new_end + self.old_end
+    }
+
+    /// The new offset of `offset` in the old text, which must not be
+    /// before `old_end`.
+    fn new_offset(&self, offset: usize) -> usize {
+        offset - self.old_end + self.new_end
+    }
+}
+
+/// The tokens of `source`, given `old`, the tokens of the text `edit`
+/// turned into `source`, lexed with the same `options`.
+pub fn relex(old: &Lexed, source: &str, edit: &SourceEdit, options: &LexOptions) -> Lexed {
+    let tokens = &old.tokens;
+
+    // The first token the edit can change is the first one reaching the
+    // edit — touching it counts, as `x` + `y` is `xy` — and any run of
+    // tokens with no space between them before it: `1` `e` + `5` is
+    // `1e5`.  Lexing restarts after the last token before those.
+    let mut first = tokens.partition_point(|t| t.end < edit.start);
+    while first > 0 {
+        let next = tokens.get(first).map_or(edit.start, |t| t.start.min(edit.start));
+        if tokens[first - 1].end < next { break; }
+        first -= 1;
+    }
+    let (restart, line) = match first.checked_sub(1).map(|i| &tokens[i]) {
+        Some(t) => (t.end, t.line + t.text.matches('\n').count()),
+        None => (0, 1),
+    };
+
+    let mut lexed = Lexed {
+        tokens: tokens[..first].to_vec(),
+        errors: old.errors.iter().filter(|e| e.start < restart).cloned().collect(),
+    };
+
+    // Lex until a token past the edit is one the old list has at the
+    // same place; from there on the two texts lex alike.
+    let mut synced = None;
+    crate::lex_from(source, restart, line, options, |found| match found {
+        Ok(tok) => {
+            if tok.start >= edit.new_end {
+                let at = edit.old_offset(tok.start);
+                let j = tokens.partition_point(|t| t.start < at);
+                if let Some(o) = tokens.get(j)
+                    && o.start == at && o.token == tok.token && o.text == tok.text
+                {
+                    synced = Some((j, tok.line as isize - o.line as isize));
+                    return false;
+                }
+            }
+            lexed.tokens.push(tok);
+            true
+        }
+        Err(e) => {
+            lexed.errors.push(e);
+            true
+        }
+    });
+
+    if let Some((j, lines)) = synced {
+        let sync_at = tokens[j].start;
+        let moved = |line: usize| (line as isize + lines) as usize;
+        lexed.tokens.extend(tokens[j..].iter().map(|t| SpannedToken {
+            line: moved(t.line),
+            start: edit.new_offset(t.start),
+            end: edit.new_offset(t.end),
+            ..t.clone()
+        }));
+        lexed.errors.extend(old.errors.iter().filter(|e| e.start >= sync_at).map(|e| LexError {
+            line: moved(e.line),
+            start: edit.new_offset(e.start),
+            ..e.clone()
+        }));
+    }
+    lexed
+}
+
+// ─── Tests ───────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lex;
+
+    const SRC: &str = r#"public class t {
+    /* counts */
+    public static void main(String argv[]) {
+        int x;
+        x = 1e5 + 12;
+        System.out.println("x is " + x); @
+    }
+}
+"#;
+
+    /// Relex `SRC` with `start..end` replaced by `text` and check the
+    /// result against lexing the new text from scratch.
+    fn check(start: usize, end: usize, text: &str) {
+        let new = format!("{}{}{}", &SRC[..start], text, &SRC[end..]);
+        let edit = SourceEdit::replace(start, end - start, text);
+        let relexed = relex(&lex(SRC), &new, &edit, &LexOptions::default());
+        assert_eq!(relexed, lex(&new), "{:?} at {}..{}", text, start, end);
+    }
+
+    #[test]
+    fn matches_a_full_lex_for_every_small_edit() {
+        let inserts = ["", "a", " ", "\n", "\"", "/*", "*/", "1", ".", "+", "@", "//"];
+        for start in 0..SRC.len() {
+            for len in 0..3.min(SRC.len() - start) {
+                for text in inserts {
+                    check(start, start + len, text);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn keeps_the_old_tokens_away_from_the_edit() {
+        // Mark the first and last tokens: a relex that reuses them keeps
+        // the marks, one that lexed them again would not.
+        let mut old = lex(SRC);
+        old.tokens[0].text = "PUBLIC".to_string();
+        old.tokens.last_mut().unwrap().text = "END".to_string();
+        let at = SRC.find("x = 1e5").unwrap();
+        let new = SRC.replacen("x = 1e5", "count = 1e5", 1);
+        let relexed = relex(&old, &new, &SourceEdit::replace(at, 1, "count"), &LexOptions::default());
+        assert_eq!(relexed.tokens[0].text, "PUBLIC");
+        assert_eq!(relexed.tokens.last().unwrap().text, "END");
+        assert_eq!(relexed.tokens.len(), lex(&new).tokens.len());
+        assert_eq!(relexed.errors, lex(&new).errors);
+    }
+
+    #[test]
+    fn edits_that_open_a_comment_or_string_reach_the_end() {
+        let at = SRC.find("int x").unwrap();
+        check(at, at, "/*");
+        check(at, at, "\"");
+        let errors = lex(&format!("{}/*{}", &SRC[..at], &SRC[at..])).errors;
+        assert_eq!(errors.len(), 1);
+    }
+}
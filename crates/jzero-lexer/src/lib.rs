@@ -1,64 +1,169 @@
+pub mod escape;
+pub mod incremental;
+pub mod options;
 pub mod token;
+pub mod trivia;
+
+pub use incremental::{SourceEdit, relex};
+pub use options::{LexOptions, Level};
+pub use trivia::{lex_lossless, lex_lossless_recovering, LosslessFile, LosslessToken, Trivia, TriviaKind};
 
 use logos::Logos;
 use token::{LexerExtras, Token};
 
-/// A token paired with its source text and line number.
-#[derive(Debug, Clone)]
+/// A token paired with its source text, line number and byte range.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct SpannedToken {
     pub token: Token,
     pub text: String,
     pub line: usize,
+    /// Byte offset of the token's first character in the source.
+    pub start: usize,
+    /// Byte offset just past the token, so `&source[start..end] == text`.
+    pub end: usize,
+}
+
+/// The tokens of a source and the errors met lexing it.
+///
+/// An error does not stop lexing: the text it covers is skipped and
+/// lexing goes on after it, so `tokens` holds every token that lexed even
+/// when `errors` is not empty.  A stray `@` costs one error, not the file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Lexed {
+    pub tokens: Vec<SpannedToken>,
+    pub errors: Vec<LexError>,
+}
+
+impl Lexed {
+    pub fn is_ok(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// The tokens if there were no errors, else the errors.
+    pub fn into_result(self) -> Result<Vec<SpannedToken>, Vec<LexError>> {
+        if self.errors.is_empty() { Ok(self.tokens) } else { Err(self.errors) }
+    }
 }
 
-/// Lex the input source, returning all meaningful tokens with line numbers.
+/// Lex the input source, returning all meaningful tokens with line numbers
+/// along with any errors.
 ///
 /// Hidden tokens (newlines, comments) are consumed for line tracking
 /// but not included in the output.
-pub fn lex(source: &str) -> Result<Vec<SpannedToken>, Vec<LexError>> {
-    let mut lexer = Token::lexer_with_extras(source, LexerExtras { line: 1 });
-    let mut tokens = Vec::new();
-    let mut errors = Vec::new();
+pub fn lex(source: &str) -> Lexed {
+    lex_with(source, &LexOptions::default())
+}
+
+/// [`lex`] at the language level in `options`.
+pub fn lex_with(source: &str, options: &LexOptions) -> Lexed {
+    let mut lexed = Lexed::default();
+    lex_from(source, 0, 1, options, |found| {
+        match found {
+            Ok(tok) => lexed.tokens.push(tok),
+            Err(e) => lexed.errors.push(e),
+        }
+        true
+    });
+    lexed
+}
+
+/// Lex `source` from byte `start`, which is on line `line` and must not
+/// be inside a token or comment, handing each token and error to `found`
+/// until it returns false.
+fn lex_from(
+    source: &str,
+    start: usize,
+    line: usize,
+    options: &LexOptions,
+    mut found: impl FnMut(Result<SpannedToken, LexError>) -> bool,
+) {
+    let mut lexer = Token::lexer_with_extras(&source[start..], LexerExtras { line });
 
     while let Some(result) = lexer.next() {
-        let line = lexer.extras.line;
         let text = lexer.slice().to_string();
+        // The line count already includes the newlines inside the token.
+        let line = lexer.extras.line - text.matches('\n').count();
+        let span = lexer.span();
 
-        match result {
+        let item = match result {
             Ok(tok) if tok.is_hidden() => continue,
-            Ok(tok) => {
-                tokens.push(SpannedToken {
-                    token: tok,
-                    text,
-                    line,
-                });
-            }
-            Err(_) => {
-                errors.push(LexError {
-                    line,
-                    text,
-                });
-            }
-        }
+            Ok(tok) => Ok(SpannedToken {
+                token: tok.at_level(options.language_level),
+                text,
+                line,
+                start: start + span.start,
+                end: start + span.end,
+            }),
+            Err(_) => Err(LexError::new(line, start + span.start, &text)),
+        };
+        if !found(item) { break; }
     }
+}
 
-    if errors.is_empty() {
-        Ok(tokens)
-    } else {
-        Err(errors)
-    }
+/// What went wrong in a [`LexError`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LexErrorKind {
+    /// A character that starts no token, e.g. `@`.
+    UnrecognizedChar,
+    /// An invalid escape in a string or character literal, e.g. `\q`.
+    BadEscape,
+    /// A `"` with no closing quote on its line.
+    UnterminatedString,
+    /// A `/*` with no closing `*/`.
+    UnterminatedComment,
 }
 
 /// A lexical error with location info.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct LexError {
+    pub kind: LexErrorKind,
     pub line: usize,
+    /// Byte offset in the source of the text that failed to lex.
+    pub start: usize,
+    /// The offending text: the character, the escape, the string up to
+    /// the end of its line, or the `/*` or `"""` opening a comment or
+    /// text block.
     pub text: String,
 }
 
+impl LexError {
+    /// The error for the unmatched text `slice` at byte `start`.
+    fn new(line: usize, start: usize, slice: &str) -> Self {
+        let quoted = slice.starts_with(['"', '\'']);
+        match escape::bad_escape(slice) {
+            Some((_, esc)) if quoted => {
+                LexError { kind: LexErrorKind::BadEscape, line, start, text: esc.to_string() }
+            }
+            _ if slice.starts_with("/*") => {
+                LexError { kind: LexErrorKind::UnterminatedComment, line, start, text: "/*".to_string() }
+            }
+            _ if slice.starts_with("\"\"\"") => {
+                LexError { kind: LexErrorKind::UnterminatedString, line, start, text: "\"\"\"".to_string() }
+            }
+            _ if slice.starts_with('"') => {
+                LexError { kind: LexErrorKind::UnterminatedString, line, start, text: slice.to_string() }
+            }
+            _ => LexError { kind: LexErrorKind::UnrecognizedChar, line, start, text: slice.to_string() },
+        }
+    }
+}
+
 impl std::fmt::Display for LexError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "line {}: unrecognized character: {:?}", self.line, self.text)
+        match self.kind {
+            LexErrorKind::UnrecognizedChar => {
+                write!(f, "line {}: unrecognized character: {:?}", self.line, self.text)
+            }
+            LexErrorKind::BadEscape => {
+                write!(f, "line {}: invalid escape sequence: {:?}", self.line, self.text)
+            }
+            LexErrorKind::UnterminatedString => {
+                write!(f, "unterminated string literal starting at line {}", self.line)
+            }
+            LexErrorKind::UnterminatedComment => {
+                write!(f, "unterminated block comment beginning at line {}", self.line)
+            }
+        }
     }
 }
 
@@ -75,7 +180,7 @@ mod tests {
     }
 }"#;
 
-        let tokens = lex(source).expect("lexing should succeed");
+        let tokens = lex(source).into_result().expect("lexing should succeed");
 
         for token in &tokens {
             println!("{:?}", token);
@@ -109,7 +214,7 @@ mod tests {
     fn test_block_comment_line_tracking() {
         let source = "int /* comment\nspanning\nlines */ x";
 
-        let tokens = lex(source).expect("lexing should succeed");
+        let tokens = lex(source).into_result().expect("lexing should succeed");
 
         assert_eq!(tokens[0].token, Token::Int);
         assert_eq!(tokens[0].line, 1);
@@ -124,10 +229,193 @@ mod tests {
     fn test_unrecognized_character() {
         let source = "int @ x";
 
-        let errors = lex(source).unwrap_err();
+        let errors = lex(source).errors;
 
         assert_eq!(errors.len(), 1);
         assert_eq!(errors[0].text, "@");
         assert_eq!(errors[0].line, 1);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_tokens_survive_errors() {
+        let lexed = lex("int @ x;
+y = \"abc;\nz = 1;");
+        let texts: Vec<&str> = lexed.tokens.iter().map(|t| t.text.as_str()).collect();
+        assert_eq!(texts, ["int", "x", ";", "y", "=", "z", "=", "1", ";"]);
+        assert_eq!(lexed.errors.iter().map(|e| e.kind).collect::<Vec<_>>(),
+                   [LexErrorKind::UnrecognizedChar, LexErrorKind::UnterminatedString]);
+        assert!(lexed.into_result().is_err());
+    }
+
+    #[test]
+    fn test_every_spelling_lexes_to_its_token() {
+        for (i, tok) in Token::ALL.iter().enumerate() {
+            assert!(!Token::ALL[..i].contains(tok), "{:?} listed twice in Token::ALL", tok);
+            let Some(text) = tok.spelling() else { continue };
+            let tokens = lex_with(text, &LexOptions::at_level(Level::MAX)).into_result().expect("lexing should succeed");
+            assert_eq!(tokens.len(), 1, "{:?}", text);
+            assert_eq!(&tokens[0].token, tok, "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn test_every_token_has_one_class() {
+        for tok in Token::ALL {
+            let classes = [
+                tok.is_hidden(),
+                tok.is_keyword(),
+                tok.is_literal(),
+                tok.is_operator(),
+                tok.is_delimiter(),
+                *tok == Token::Identifier,
+            ];
+            assert_eq!(classes.iter().filter(|&&c| c).count(), 1, "{:?}", tok);
+        }
+        assert_eq!(Token::StringKw.category_name(), "STRING");
+        assert_eq!(Token::False.category_name(), "BOOLLIT");
+        assert_eq!(Token::PlusAssign.category_name(), "PLUSASSIGN");
+    }
+
+    #[test]
+    fn test_language_level_enables_keywords() {
+        let source = "char c; int x;";
+        let jzero = lex(source).into_result().unwrap();
+        assert_eq!(jzero[0].token, Token::Identifier);
+        assert_eq!(jzero[3].token, Token::Int);
+
+        let extended = lex_with(source, &LexOptions::at_level(Level::Extended)).into_result().unwrap();
+        assert_eq!(extended[0].token, Token::Char);
+    }
+
+    #[test]
+    fn test_keyword_lookup() {
+        assert_eq!(Token::keyword("extends"), Some(Token::Extends));
+        assert_eq!(Token::keyword("extends").unwrap().min_level(), Level::Extended);
+        assert_eq!(Token::keyword("boolean"), Some(Token::ReservedWord));
+        assert_eq!(Token::keyword("while"), Some(Token::While));
+        assert_eq!(Token::keyword("extender"), None);
+        assert_eq!(Token::keyword("true"), None);
+        assert_eq!(Level::parse(Level::Extended.name()), Some(Level::Extended));
+    }
+
+    #[test]
+    fn test_char_literals() {
+        let tokens = lex(r"'a' '\n' '\'' '\\'").into_result().unwrap();
+        assert!(tokens.iter().all(|t| t.token == Token::CharLit));
+        let values: Vec<_> = tokens.iter().map(|t| escape::char_value(&t.text)).collect();
+        assert_eq!(values, [Some('a'), Some('\n'), Some('\''), Some('\\')]);
+
+        let errors = lex(r"'\q'").errors;
+        assert_eq!((errors[0].kind, errors[0].text.as_str()), (LexErrorKind::BadEscape, r"\q"));
+        assert!(!lex("''").is_ok());
+        assert_eq!(escape::char_value("'ab'"), None);
+    }
+
+    #[test]
+    fn test_string_escapes() {
+        let tokens = lex(r#"s = "say \"hi\"\n";"#).into_result().unwrap();
+        assert_eq!(tokens[2].token, Token::StringLit);
+        assert_eq!(escape::string_value(&tokens[2].text).unwrap(), "say \"hi\"\n");
+        assert_eq!(escape::quote("say \"hi\"\n"), tokens[2].text);
+
+        let errors = lex(r#"s = "a\qb"; t = 1;"#).errors;
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].to_string(), r#"line 1: invalid escape sequence: "\\q""#);
+    }
+
+    #[test]
+    fn test_text_blocks() {
+        let source = "s = \"\"\"\n    <p>\n      \\\"hi\\\" \\\n      there\n    </p>\n    \"\"\";\nt = 1;\n";
+        let tokens = lex(source).into_result().unwrap();
+        assert_eq!((tokens[2].token.clone(), tokens[2].line), (Token::StringLit, 1));
+        assert_eq!(escape::string_value(&tokens[2].text).unwrap(), "<p>\n  \"hi\"   there\n</p>\n");
+        assert_eq!((tokens[4].text.as_str(), tokens[4].line), ("t", 7));
+
+        // The closing delimiter on the last text line keeps no newline,
+        // and blank lines do not count towards the indentation.
+        let tokens = lex("\"\"\"\n   a\n\n     b\"\"\"").into_result().unwrap();
+        assert_eq!(escape::string_value(&tokens[0].text).unwrap(), "a\n\n  b");
+        // Without a line break after it, `"""` is not a text block.
+        assert_eq!(lex("\"\"\"a\"\"\"").tokens.len(), 3);
+    }
+
+    #[test]
+    fn test_text_block_errors() {
+        let errors = lex("s = \"\"\"\n  oops \\q\n  \"\"\";\nt = @;").errors;
+        assert_eq!(errors.iter().map(|e| (e.kind, e.line)).collect::<Vec<_>>(),
+                   [(LexErrorKind::BadEscape, 1), (LexErrorKind::UnrecognizedChar, 4)]);
+        let errors = lex("s = \"\"\"\n  never closed\n").errors;
+        assert_eq!((errors[0].kind, errors[0].text.as_str(), errors.len()), (LexErrorKind::UnterminatedString, "\"\"\"", 1));
+    }
+
+    #[test]
+    fn test_unterminated_string() {
+        let errors = lex("x = \"abc;\ny = 1;\nz = \"ok\";").errors;
+        assert_eq!(errors.len(), 1);
+        assert_eq!((errors[0].kind, errors[0].text.as_str()), (LexErrorKind::UnterminatedString, "\"abc;"));
+        assert_eq!(errors[0].to_string(), "unterminated string literal starting at line 1");
+
+        // Lexing goes on at the next line.
+        let errors = lex("x = \"abc;\ny = @;").errors;
+        assert_eq!(errors.iter().map(|e| (e.kind, e.line)).collect::<Vec<_>>(),
+                   [(LexErrorKind::UnterminatedString, 1), (LexErrorKind::UnrecognizedChar, 2)]);
+    }
+
+    #[test]
+    fn test_unterminated_block_comment() {
+        let errors = lex("int x;\n/* no end\nint y = \"q;\n@").errors;
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].kind, LexErrorKind::UnterminatedComment);
+        assert_eq!(errors[0].to_string(), "unterminated block comment beginning at line 2");
+
+        // A closed comment ending in `**/` is still a comment.
+        assert_eq!(lex("/* a **/ x /**/").into_result().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_number_suffixes() {
+        let tokens = lex("12L 7l 1.5f 2e3F 3f 1.5d 4D .5 9").into_result().unwrap();
+        let kinds: Vec<&Token> = tokens.iter().map(|t| &t.token).collect();
+        assert_eq!(kinds, [
+            &Token::LongLit, &Token::LongLit,
+            &Token::FloatLit, &Token::FloatLit, &Token::FloatLit,
+            &Token::DoubleLit, &Token::DoubleLit, &Token::DoubleLit,
+            &Token::IntLit,
+        ]);
+        assert_eq!(tokens[0].text, "12L");
+        assert_eq!(Token::FloatLit.category_name(), "FLOATLIT");
+    }
+
+    #[test]
+    fn test_unicode_identifiers() {
+        let tokens = lex("int größe_2 = café + _x; class").into_result().unwrap();
+        let idents: Vec<&str> = tokens.iter()
+            .filter(|t| t.token == Token::Identifier)
+            .map(|t| t.text.as_str())
+            .collect();
+        assert_eq!(idents, ["größe_2", "café", "_x"]);
+        assert_eq!(tokens.last().unwrap().token, Token::Class);
+        // Digits and symbols still cannot start a name.
+        assert!(lex("int 2x;").into_result().unwrap().iter().all(|t| t.text != "2x"));
+        assert!(!lex("int →;").is_ok());
+    }
+
+    #[test]
+    fn test_all_is_in_declaration_order() {
+        // The parser indexes tables by `Token as usize`.
+        for (i, tok) in Token::ALL.iter().enumerate() {
+            assert_eq!(tok.clone() as usize, i, "{:?}", tok);
+        }
+    }
+
+    #[test]
+    fn test_byte_spans() {
+        let source = "int größe;\n/* c */ x = \"é\";";
+        let tokens = lex(source).into_result().unwrap();
+        for t in &tokens {
+            assert_eq!(&source[t.start..t.end], t.text);
+        }
+        assert_eq!((tokens[1].start, tokens[1].end), (4, 11));
+        assert_eq!(tokens[3].start, 21);
+    }
+}
@@ -0,0 +1,56 @@
+//! Lexer options — which words are keywords.
+//!
+//! The book grows Jzero chapter by chapter, and this workspace grows it
+//! further.  A word such as `switch` only becomes a keyword at the
+//! [`Level`] that introduces it; below that level it lexes as an ordinary
+//! identifier, so programs written for the smaller language keep working.
+
+/// A language level.  Levels are ordered: each one enables every keyword
+/// of the levels below it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Level {
+    /// The language of the book.
+    #[default]
+    Jzero,
+    /// Adds `char`, `long`, `this`, `final`, `switch`/`case`/`default`,
+    /// `try`/`catch`/`finally`/`throw`, `extends`, `interface`,
+    /// `implements`, `instanceof`, `import` and `var`.
+    Extended,
+}
+
+impl Level {
+    /// The highest level, with every keyword enabled.
+    pub const MAX: Level = Level::Extended;
+
+    /// Parse a level name as used in configuration, e.g. `extended`.
+    pub fn parse(s: &str) -> Option<Level> {
+        match s {
+            "jzero"    => Some(Level::Jzero),
+            "extended" => Some(Level::Extended),
+            _ => None,
+        }
+    }
+
+    /// The name [`Level::parse`] takes.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Level::Jzero    => "jzero",
+            Level::Extended => "extended",
+        }
+    }
+}
+
+/// Options for [`lex_with`](crate::lex_with) and the parser's lexer.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LexOptions {
+    pub language_level: Level,
+    /// Stop with an error after this many tokens.  Only the parser's lexer
+    /// enforces it; it bounds the size of the tree built from the input.
+    pub max_tokens: Option<usize>,
+}
+
+impl LexOptions {
+    pub fn at_level(language_level: Level) -> Self {
+        LexOptions { language_level, ..LexOptions::default() }
+    }
+}
@@ -1,5 +1,7 @@
 use logos::Logos;
 
+use crate::options::Level;
+
 #[derive(Default, Debug, Clone)]
 pub struct LexerExtras {
     pub line: usize,
@@ -13,6 +15,27 @@ fn block_comment_callback(lex: &mut logos::Lexer<Token>) {
     lex.extras.line += lex.slice().chars().filter(|&c| c == '\n').count();
 }
 
+/// A `/*` with no `*/` runs to the end of the input.  Matching it as one
+/// error, rather than lexing the rest as `/`, `*` and code, reports the
+/// comment once instead of a cascade of errors from its text.
+fn unterminated_comment(lex: &mut logos::Lexer<Token>) -> bool {
+    block_comment_callback(lex);
+    false
+}
+
+/// A text block spans lines; count them, valid or not, so the tokens after
+/// it are on the right line.
+fn text_block_callback(lex: &mut logos::Lexer<Token>) -> bool {
+    lex.extras.line += lex.slice().chars().filter(|&c| c == '\n').count();
+    crate::escape::string_value(lex.slice()).is_some()
+}
+
+/// A `"""` with no closing `"""` runs to the end of the input, like `/*`.
+fn unterminated_text_block(lex: &mut logos::Lexer<Token>) -> bool {
+    text_block_callback(lex);
+    false
+}
+
 #[derive(Logos, Debug, PartialEq, Eq, Hash, Clone)]
 #[logos(extras = LexerExtras)]
 #[logos(skip r"[ \t\r\f]+")]
@@ -24,7 +47,11 @@ pub enum Token {
     #[regex(r"//[^\n]*\n?", newline_callback, allow_greedy = true)]
     LineComment,
     #[regex(r"/\*([^*]|\*+[^*/])*\*+/", block_comment_callback)]
+    #[regex(r"/\*([^*]|\*+[^*/])*\**", unterminated_comment)]
     BlockComment,
+    /// `/** ... */`, a Javadoc-style comment; `/**/` is a block comment.
+    #[regex(r"/\*\*([^*]|\*+[^*/])*\*+/", block_comment_callback, priority = 10)]
+    DocComment,
 
     // ── Keywords ──────────────────────────────────────────────
     #[token("bool")]
@@ -47,6 +74,10 @@ pub enum Token {
     New,
     #[token("null")]
     Null,
+    #[token("private")]
+    Private,
+    #[token("protected")]
+    Protected,
     #[token("public")]
     Public,
     #[token("return")]
@@ -60,6 +91,65 @@ pub enum Token {
     #[token("while")]
     While,
 
+    // ── Keywords from Level::Extended ─────────────────────────
+    // Identifiers at lower levels; see `Token::min_level`.
+    #[token("case")]
+    Case,
+    #[token("catch")]
+    Catch,
+    #[token("char")]
+    Char,
+    #[token("default")]
+    Default,
+    #[token("extends")]
+    Extends,
+    #[token("final")]
+    Final,
+    #[token("finally")]
+    Finally,
+    #[token("implements")]
+    Implements,
+    #[token("import")]
+    Import,
+    #[token("instanceof")]
+    Instanceof,
+    #[token("interface")]
+    Interface,
+    #[token("long")]
+    Long,
+    #[token("switch")]
+    Switch,
+    #[token("this")]
+    This,
+    #[token("throw")]
+    Throw,
+    #[token("try")]
+    Try,
+    #[token("var")]
+    Var,
+
+    // ── Java keywords Jzero does not support ──────────────────
+    #[token("abstract")]
+    #[token("assert")]
+    #[token("boolean")]
+    #[token("byte")]
+    #[token("const")]
+    #[token("continue")]
+    #[token("do")]
+    #[token("enum")]
+    #[token("float")]
+    #[token("goto")]
+    #[token("native")]
+    #[token("package")]
+    #[token("short")]
+    #[token("strictfp")]
+    #[token("super")]
+    #[token("synchronized")]
+    #[token("throws")]
+    #[token("transient")]
+    #[token("volatile")]
+    ReservedWord,
+
     // ── Boolean literals ──────────────────────────────────────
     #[token("true")]
     True,
@@ -105,6 +195,12 @@ pub enum Token {
     PlusAssign,
     #[token("-=")]
     MinusAssign,
+    #[token("*=")]
+    StarAssign,
+    #[token("/=")]
+    SlashAssign,
+    #[token("%=")]
+    PercentAssign,
 
     // ── Operators (single-char) ───────────────────────────────
     #[token("=")]
@@ -127,21 +223,306 @@ pub enum Token {
     Greater,
 
     // ── Literals ──────────────────────────────────────────────
-    #[regex(r"[0-9]+\.[0-9]*([eE][+-]?[0-9]+)?|[0-9]*\.[0-9]+([eE][+-]?[0-9]+)?", priority = 3)]
-    #[regex(r"[0-9]+[eE][+-]?[0-9]+", priority = 3)]
+    // A `d` or `D` suffix spells the default: `1d` and `1.5D` are doubles.
+    #[regex(r"[0-9]+\.[0-9]*([eE][+-]?[0-9]+)?[dD]?|[0-9]*\.[0-9]+([eE][+-]?[0-9]+)?[dD]?", priority = 3)]
+    #[regex(r"[0-9]+[eE][+-]?[0-9]+[dD]?|[0-9]+[dD]", priority = 3)]
     DoubleLit,
+    /// `1.5f`, `2e3F`, `1f`.
+    #[regex(r"([0-9]+\.[0-9]*([eE][+-]?[0-9]+)?|[0-9]*\.[0-9]+([eE][+-]?[0-9]+)?|[0-9]+([eE][+-]?[0-9]+)?)[fF]", priority = 3)]
+    FloatLit,
     #[regex(r"[0-9]+", priority = 2)]
     IntLit,
-    #[regex(r#""[^"]*""#)]
+    /// `12L`, `12l`.
+    #[regex(r"[0-9]+[lL]", priority = 3)]
+    LongLit,
+    /// `"..."` on one line, or a text block: `"""`, the rest of its line
+    /// blank, then lines of text up to the closing `"""`.
+    #[regex(r#""([^"\\\n]|\\[^\n])*""#, |lex| crate::escape::string_value(lex.slice()).is_some())]
+    #[regex(r#""""[ \t\f]*\r?\n([^"\\]|\\(.|\n)|"([^"\\]|\\(.|\n))|""([^"\\]|\\(.|\n)))*""""#, text_block_callback)]
+    #[regex(r#""""[ \t\f]*\r?\n([^"\\]|\\(.|\n)|"([^"\\]|\\(.|\n))|""([^"\\]|\\(.|\n)))*"?"?"#, unterminated_text_block)]
     StringLit,
+    #[regex(r"'([^'\\\n]|\\[^\n])'", |lex| crate::escape::char_value(lex.slice()).is_some())]
+    CharLit,
 
     // ── Identifier ────────────────────────────────────────────
-    #[regex(r"[a-zA-Z_][a-zA-Z0-9_]*")]
+    #[regex(r"[\p{XID_Start}_]\p{XID_Continue}*")]
     Identifier,
 }
 
 impl Token {
+    /// Every variant, in declaration order.
+    pub const ALL: &'static [Token] = &[
+        Token::Newline, Token::LineComment, Token::BlockComment, Token::DocComment, Token::Bool,
+        Token::Break, Token::Class, Token::Double, Token::Else, Token::For,
+        Token::If, Token::Int, Token::New, Token::Null, Token::Private,
+        Token::Protected, Token::Public, Token::Return, Token::Static,
+        Token::StringKw, Token::Void, Token::While,
+        Token::Case, Token::Catch, Token::Char, Token::Default, Token::Extends,
+        Token::Final, Token::Finally, Token::Implements, Token::Import, Token::Instanceof, Token::Interface,
+        Token::Long, Token::Switch, Token::This, Token::Throw, Token::Try, Token::Var,
+        Token::ReservedWord, Token::True, Token::False,
+        Token::LParen, Token::RParen, Token::LBracket, Token::RBracket,
+        Token::LBrace, Token::RBrace, Token::Semicolon, Token::Colon, Token::Comma,
+        Token::Dot, Token::LessEqual, Token::GreaterEqual, Token::EqualEqual,
+        Token::NotEqual, Token::LogicalAnd, Token::LogicalOr, Token::PlusAssign,
+        Token::MinusAssign, Token::StarAssign, Token::SlashAssign, Token::PercentAssign, Token::Assign, Token::Bang, Token::Plus, Token::Minus,
+        Token::Star, Token::Slash, Token::Percent, Token::Less, Token::Greater,
+        Token::DoubleLit, Token::FloatLit, Token::IntLit, Token::LongLit,
+        Token::StringLit, Token::CharLit, Token::Identifier,
+    ];
+
+    /// The fixed source text of a keyword, delimiter or operator; `None` for
+    /// comments, newlines, literals, identifiers and reserved words.
+    ///
+    /// The match is exhaustive on purpose: a new variant does not compile
+    /// until it is listed here — add it to [`Token::ALL`] at the same time.
+    pub fn spelling(&self) -> Option<&'static str> {
+        match self {
+            Token::Bool         => Some("bool"),
+            Token::Break        => Some("break"),
+            Token::Class        => Some("class"),
+            Token::Double       => Some("double"),
+            Token::Else         => Some("else"),
+            Token::For          => Some("for"),
+            Token::If           => Some("if"),
+            Token::Int          => Some("int"),
+            Token::New          => Some("new"),
+            Token::Null         => Some("null"),
+            Token::Private      => Some("private"),
+            Token::Protected    => Some("protected"),
+            Token::Public       => Some("public"),
+            Token::Return       => Some("return"),
+            Token::Static       => Some("static"),
+            Token::StringKw     => Some("string"),
+            Token::Void         => Some("void"),
+            Token::While        => Some("while"),
+            Token::Case         => Some("case"),
+            Token::Catch        => Some("catch"),
+            Token::Char         => Some("char"),
+            Token::Default      => Some("default"),
+            Token::Extends      => Some("extends"),
+            Token::Final        => Some("final"),
+            Token::Finally      => Some("finally"),
+            Token::Implements   => Some("implements"),
+            Token::Import       => Some("import"),
+            Token::Instanceof   => Some("instanceof"),
+            Token::Interface    => Some("interface"),
+            Token::Long         => Some("long"),
+            Token::Switch       => Some("switch"),
+            Token::This         => Some("this"),
+            Token::Throw        => Some("throw"),
+            Token::Try          => Some("try"),
+            Token::Var          => Some("var"),
+            Token::True         => Some("true"),
+            Token::False        => Some("false"),
+            Token::LParen       => Some("("),
+            Token::RParen       => Some(")"),
+            Token::LBracket     => Some("["),
+            Token::RBracket     => Some("]"),
+            Token::LBrace       => Some("{"),
+            Token::RBrace       => Some("}"),
+            Token::Semicolon    => Some(";"),
+            Token::Colon        => Some(":"),
+            Token::Comma        => Some(","),
+            Token::Dot          => Some("."),
+            Token::LessEqual    => Some("<="),
+            Token::GreaterEqual => Some(">="),
+            Token::EqualEqual   => Some("=="),
+            Token::NotEqual     => Some("!="),
+            Token::LogicalAnd   => Some("&&"),
+            Token::LogicalOr    => Some("||"),
+            Token::PlusAssign   => Some("+="),
+            Token::MinusAssign  => Some("-="),
+            Token::StarAssign   => Some("*="),
+            Token::SlashAssign  => Some("/="),
+            Token::PercentAssign => Some("%="),
+            Token::Assign       => Some("="),
+            Token::Bang         => Some("!"),
+            Token::Plus         => Some("+"),
+            Token::Minus        => Some("-"),
+            Token::Star         => Some("*"),
+            Token::Slash        => Some("/"),
+            Token::Percent      => Some("%"),
+            Token::Less         => Some("<"),
+            Token::Greater      => Some(">"),
+            Token::Newline
+            | Token::LineComment
+            | Token::BlockComment
+            | Token::DocComment
+            | Token::DoubleLit
+            | Token::FloatLit
+            | Token::IntLit
+            | Token::LongLit
+            | Token::StringLit
+            | Token::CharLit
+            | Token::ReservedWord
+            | Token::Identifier => None,
+        }
+    }
+
     pub fn is_hidden(&self) -> bool {
-        matches!(self, Token::Newline | Token::LineComment | Token::BlockComment)
+        matches!(self, Token::Newline | Token::LineComment | Token::BlockComment | Token::DocComment)
+    }
+
+    /// The lowest language level at which this token is a keyword.
+    pub fn min_level(&self) -> Level {
+        match self {
+            Token::Case | Token::Catch | Token::Char | Token::Default | Token::Extends
+            | Token::Final | Token::Finally | Token::Implements | Token::Import | Token::Instanceof
+            | Token::Interface | Token::Long | Token::Switch | Token::This
+            | Token::Throw | Token::Try | Token::Var => Level::Extended,
+            _ => Level::Jzero,
+        }
+    }
+
+    /// The keyword `word` is at [`Level::MAX`], if it is one.  Below the
+    /// token's [`min_level`](Token::min_level) the lexer gives an
+    /// identifier for it instead.
+    pub fn keyword(word: &str) -> Option<Token> {
+        let mut lex = Token::lexer(word);
+        match lex.next() {
+            Some(Ok(tok)) if tok.is_keyword() && lex.slice() == word => Some(tok),
+            _ => None,
+        }
+    }
+
+    /// This token as seen at `level`: a keyword not yet enabled there is an
+    /// ordinary identifier.
+    pub fn at_level(self, level: Level) -> Token {
+        if self.min_level() > level { Token::Identifier } else { self }
+    }
+
+    /// Reserved words: type names, modifiers and statement keywords,
+    /// including the Java ones Jzero rejects.  `true`, `false` and `null`
+    /// are literals, as in Java.
+    pub fn is_keyword(&self) -> bool {
+        matches!(
+            self,
+            Token::Bool | Token::Break | Token::Class | Token::Double | Token::Else
+                | Token::For | Token::If | Token::Int | Token::New | Token::Private
+                | Token::Protected | Token::Public | Token::Return | Token::Static
+                | Token::StringKw | Token::Void | Token::While
+                | Token::Case | Token::Catch | Token::Char | Token::Default
+                | Token::Extends | Token::Final | Token::Finally | Token::Implements | Token::Import
+                | Token::Instanceof | Token::Interface | Token::Long | Token::Switch
+                | Token::This | Token::Throw | Token::Try | Token::Var | Token::ReservedWord
+        )
+    }
+
+    pub fn is_literal(&self) -> bool {
+        matches!(
+            self,
+            Token::True | Token::False | Token::Null
+                | Token::IntLit | Token::LongLit | Token::DoubleLit | Token::FloatLit
+                | Token::StringLit | Token::CharLit
+        )
+    }
+
+    /// Arithmetic, comparison, logical and assignment operators.
+    pub fn is_operator(&self) -> bool {
+        matches!(
+            self,
+            Token::LessEqual | Token::GreaterEqual | Token::EqualEqual | Token::NotEqual
+                | Token::LogicalAnd | Token::LogicalOr | Token::PlusAssign | Token::MinusAssign
+                | Token::StarAssign | Token::SlashAssign | Token::PercentAssign
+                | Token::Assign | Token::Bang | Token::Plus | Token::Minus | Token::Star
+                | Token::Slash | Token::Percent | Token::Less | Token::Greater
+        )
+    }
+
+    /// Brackets and separators.
+    pub fn is_delimiter(&self) -> bool {
+        matches!(
+            self,
+            Token::LParen | Token::RParen | Token::LBracket | Token::RBracket
+                | Token::LBrace | Token::RBrace | Token::Semicolon | Token::Colon
+                | Token::Comma | Token::Dot
+        )
+    }
+
+    /// The leaf category the parser gives this token in the syntax tree,
+    /// e.g. `IDENTIFIER`, `INTLIT`, `PLUSASSIGN`.  `true` and `false` share
+    /// `BOOLLIT`.
+    pub fn category_name(&self) -> &'static str {
+        match self {
+            Token::Newline      => "NEWLINE",
+            Token::LineComment  => "LINECOMMENT",
+            Token::BlockComment => "BLOCKCOMMENT",
+            Token::DocComment   => "DOCCOMMENT",
+            Token::Bool         => "BOOL",
+            Token::Break        => "BREAK",
+            Token::Class        => "CLASS",
+            Token::Double       => "DOUBLE",
+            Token::Else         => "ELSE",
+            Token::For          => "FOR",
+            Token::If           => "IF",
+            Token::Int          => "INT",
+            Token::New          => "NEW",
+            Token::Null         => "NULL",
+            Token::Private      => "PRIVATE",
+            Token::Protected    => "PROTECTED",
+            Token::Public       => "PUBLIC",
+            Token::Return       => "RETURN",
+            Token::Static       => "STATIC",
+            Token::StringKw     => "STRING",
+            Token::Void         => "VOID",
+            Token::While        => "WHILE",
+            Token::Case         => "CASE",
+            Token::Catch        => "CATCH",
+            Token::Char         => "CHAR",
+            Token::Default      => "DEFAULT",
+            Token::Extends      => "EXTENDS",
+            Token::Final        => "FINAL",
+            Token::Finally      => "FINALLY",
+            Token::Implements   => "IMPLEMENTS",
+            Token::Import       => "IMPORT",
+            Token::Instanceof   => "INSTANCEOF",
+            Token::Interface    => "INTERFACE",
+            Token::Long         => "LONG",
+            Token::Switch       => "SWITCH",
+            Token::This         => "THIS",
+            Token::Throw        => "THROW",
+            Token::Try          => "TRY",
+            Token::Var          => "VAR",
+            Token::ReservedWord => "RESERVEDWORD",
+            Token::True | Token::False => "BOOLLIT",
+            Token::LParen       => "LPAREN",
+            Token::RParen       => "RPAREN",
+            Token::LBracket     => "LBRACKET",
+            Token::RBracket     => "RBRACKET",
+            Token::LBrace       => "LBRACE",
+            Token::RBrace       => "RBRACE",
+            Token::Semicolon    => "SEMICOLON",
+            Token::Colon        => "COLON",
+            Token::Comma        => "COMMA",
+            Token::Dot          => "DOT",
+            Token::LessEqual    => "LESSEQUAL",
+            Token::GreaterEqual => "GREATEREQUAL",
+            Token::EqualEqual   => "EQUALEQUAL",
+            Token::NotEqual     => "NOTEQUAL",
+            Token::LogicalAnd   => "LOGICALAND",
+            Token::LogicalOr    => "LOGICALOR",
+            Token::PlusAssign   => "PLUSASSIGN",
+            Token::MinusAssign  => "MINUSASSIGN",
+            Token::StarAssign   => "STARASSIGN",
+            Token::SlashAssign  => "SLASHASSIGN",
+            Token::PercentAssign => "PERCENTASSIGN",
+            Token::Assign       => "ASSIGN",
+            Token::Bang         => "BANG",
+            Token::Plus         => "PLUS",
+            Token::Minus        => "MINUS",
+            Token::Star         => "STAR",
+            Token::Slash        => "SLASH",
+            Token::Percent      => "PERCENT",
+            Token::Less         => "LESS",
+            Token::Greater      => "GREATER",
+            Token::DoubleLit    => "DOUBLELIT",
+            Token::FloatLit     => "FLOATLIT",
+            Token::IntLit       => "INTLIT",
+            Token::LongLit      => "LONGLIT",
+            Token::StringLit    => "STRINGLIT",
+            Token::CharLit      => "CHARLIT",
+            Token::Identifier   => "IDENTIFIER",
+        }
     }
 }
\ No newline at end of file
@@ -0,0 +1,207 @@
+//! Lossless lexing — tokens with their surrounding whitespace and comments.
+//!
+//! [`lex`](crate::lex) throws away everything between tokens.  For tools
+//! that rewrite source (the formatter, refactorings) that is not enough:
+//! they need to reproduce the user's untouched code byte for byte.
+//!
+//! [`lex_lossless`] keeps every byte.  The whitespace, newlines and comments
+//! before a token are attached to it as `leading` trivia; anything after
+//! the last token is kept in [`LosslessFile::trailing`].  Concatenating the
+//! trivia and token text in order gives back the exact input:
+//!
+//! ```
+//! let src = "int x; // count\n";
+//! let file = jzero_lexer::lex_lossless(src).unwrap();
+//! assert_eq!(file.to_source(), src);
+//! ```
+//!
+//! [`lex_lossless_recovering`] goes on past text that does not lex, keeping
+//! it as [`TriviaKind::Unlexed`] trivia, so even a file with lexical errors
+//! round-trips.
+
+use logos::Logos;
+
+use crate::LexError;
+use crate::options::Level;
+use crate::token::{LexerExtras, Token};
+
+/// What kind of non-token text a [`Trivia`] holds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TriviaKind {
+    /// Spaces, tabs, carriage returns, form feeds.
+    Whitespace,
+    Newline,
+    /// `// ...` including its terminating newline, if any.
+    LineComment,
+    /// `/* ... */`
+    BlockComment,
+    /// `/** ... */`
+    DocComment,
+    /// Text that does not lex, such as a stray `#` or an unterminated
+    /// string; only [`lex_lossless_recovering`] keeps it.
+    Unlexed,
+}
+
+/// A run of source text that is not a token.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Trivia {
+    pub kind: TriviaKind,
+    pub text: String,
+}
+
+impl Trivia {
+    pub fn is_comment(&self) -> bool {
+        matches!(self.kind, TriviaKind::LineComment | TriviaKind::BlockComment | TriviaKind::DocComment)
+    }
+
+    /// Number of line breaks inside this trivia.
+    pub fn newlines(&self) -> usize {
+        self.text.matches('\n').count()
+    }
+}
+
+/// A token together with the trivia that precedes it.
+#[derive(Debug, Clone)]
+pub struct LosslessToken {
+    pub token: Token,
+    pub text: String,
+    pub line: usize,
+    pub leading: Vec<Trivia>,
+}
+
+/// The lossless token stream for a whole file.
+#[derive(Debug, Clone, Default)]
+pub struct LosslessFile {
+    pub tokens: Vec<LosslessToken>,
+    /// Trivia after the last token (end-of-file comments and whitespace).
+    pub trailing: Vec<Trivia>,
+}
+
+impl LosslessFile {
+    /// Reassemble the original source text.
+    pub fn to_source(&self) -> String {
+        let mut out = String::new();
+        for tok in &self.tokens {
+            for t in &tok.leading { out.push_str(&t.text); }
+            out.push_str(&tok.text);
+        }
+        for t in &self.trailing { out.push_str(&t.text); }
+        out
+    }
+}
+
+/// Lex `source`, keeping all whitespace and comments as trivia.
+pub fn lex_lossless(source: &str) -> Result<LosslessFile, Vec<LexError>> {
+    let (file, errors) = lex_lossless_recovering(source);
+    if errors.is_empty() { Ok(file) } else { Err(errors) }
+}
+
+/// Like [`lex_lossless`], with the text each error covers kept as
+/// [`TriviaKind::Unlexed`] trivia, one piece per error, and the errors.
+pub fn lex_lossless_recovering(source: &str) -> (LosslessFile, Vec<LexError>) {
+    let mut lexer = Token::lexer_with_extras(source, LexerExtras { line: 1 });
+    let mut file = LosslessFile::default();
+    let mut pending: Vec<Trivia> = Vec::new();
+    let mut errors = Vec::new();
+    let mut last_end = 0;
+
+    while let Some(result) = lexer.next() {
+        let span = lexer.span();
+        // Logos skips horizontal whitespace silently — recover it from the gap.
+        if span.start > last_end {
+            pending.push(Trivia {
+                kind: TriviaKind::Whitespace,
+                text: source[last_end..span.start].to_string(),
+            });
+        }
+        last_end = span.end;
+
+        let text = lexer.slice().to_string();
+        // The line count already includes the newlines inside the token.
+        let line = lexer.extras.line - text.matches('\n').count();
+        match result {
+            Ok(tok) => {
+                let kind = match tok {
+                    Token::Newline      => Some(TriviaKind::Newline),
+                    Token::LineComment  => Some(TriviaKind::LineComment),
+                    Token::BlockComment => Some(TriviaKind::BlockComment),
+                    Token::DocComment   => Some(TriviaKind::DocComment),
+                    _ => None,
+                };
+                match kind {
+                    Some(kind) => pending.push(Trivia { kind, text }),
+                    None => {
+                        file.tokens.push(LosslessToken {
+                            token: tok.at_level(Level::default()),
+                            text,
+                            line,
+                            leading: std::mem::take(&mut pending),
+                        });
+                    }
+                }
+            }
+            Err(_) => {
+                errors.push(LexError::new(line, span.start, &text));
+                pending.push(Trivia { kind: TriviaKind::Unlexed, text });
+            }
+        }
+    }
+    if source.len() > last_end {
+        pending.push(Trivia {
+            kind: TriviaKind::Whitespace,
+            text: source[last_end..].to_string(),
+        });
+    }
+    file.trailing = pending;
+    (file, errors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip_is_exact() {
+        let src = "/* head */\npublic class a {\n\t// note\n    int  x ;   \r\n}\n\n// tail";
+        let file = lex_lossless(src).unwrap();
+        assert_eq!(file.to_source(), src);
+    }
+
+    #[test]
+    fn comments_attach_to_next_token() {
+        let src = "int x;\n// about y\nint y;";
+        let file = lex_lossless(src).unwrap();
+        let int_y = &file.tokens[3];
+        assert_eq!(int_y.token, Token::Int);
+        assert_eq!(int_y.line, 3);
+        assert!(int_y.leading.iter().any(|t| t.kind == TriviaKind::LineComment
+            && t.text == "// about y\n"));
+    }
+
+    #[test]
+    fn trailing_trivia_is_kept() {
+        let file = lex_lossless("x /* end */ \n").unwrap();
+        assert_eq!(file.tokens.len(), 1);
+        let kinds: Vec<_> = file.trailing.iter().map(|t| t.kind).collect();
+        assert_eq!(kinds, vec![
+            TriviaKind::Whitespace, TriviaKind::BlockComment,
+            TriviaKind::Whitespace, TriviaKind::Newline,
+        ]);
+    }
+
+    #[test]
+    fn lexical_errors_are_reported() {
+        assert!(lex_lossless("int # x;").is_err());
+    }
+
+    #[test]
+    fn unlexed_text_is_kept_as_trivia() {
+        let src = "int # x = \"abc;\n}";
+        let (file, errors) = lex_lossless_recovering(src);
+        assert_eq!(file.to_source(), src);
+        assert_eq!(errors.len(), 2);
+        let x = &file.tokens[1];
+        assert_eq!((x.text.as_str(), x.leading[1].kind, x.leading[1].text.as_str()), ("x", TriviaKind::Unlexed, "#"));
+        assert_eq!(file.tokens[3].leading[1].text, "\"abc;");
+    }
+}
@@ -0,0 +1,57 @@
+//! Lexer and parser throughput on a large generated corpus.
+//!
+//! Run with `cargo bench -p jzero-parser`; criterion reports tokens per
+//! second for the bridge from Logos tokens to grammar terminals, and bytes
+//! per second for a full parse, which also builds every leaf's line number.
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use jzero_parser::lexer::Lexer;
+
+/// The body of `tests/examples/fizzbuzz.java`, as one method.
+const METHOD: &str = r#"
+    // Prints fizz, buzz, fizzbuzz, or the iteration number.
+    public static void run_N(String argv[]) {
+        int n;
+        int i;
+        n = argv.length;
+        i = 1;
+        while (i <= n) {
+            if (i % 15 < 1) {
+                System.out.println("fizzbuzz");
+            } else {
+                if (i % 3 < 1) { System.out.println("fizz"); }
+                else { System.out.println(String.valueOf(i * 2.5)); }
+            }
+            i = i + 1;
+        }
+    }
+"#;
+
+/// A class of `methods` copies of [`METHOD`], about 500 bytes each.
+fn corpus(methods: usize) -> String {
+    let mut src = String::from("public class corpus {\n");
+    for n in 0..methods {
+        src.push_str(&METHOD.replace("run_N", &format!("run_{}", n)));
+    }
+    src.push_str("    public static void main(String argv[]) { }\n}\n");
+    src
+}
+
+fn bench_lexer(c: &mut Criterion) {
+    let src = corpus(2000);
+    let tokens = Lexer::new(&src).count() as u64;
+
+    let mut group = c.benchmark_group("lexer");
+    group.throughput(Throughput::Elements(tokens));
+    group.bench_function("tokens", |b| b.iter(|| Lexer::new(&src).count()));
+    group.finish();
+
+    let mut group = c.benchmark_group("parser");
+    group.sample_size(20);
+    group.throughput(Throughput::Bytes(src.len() as u64));
+    group.bench_function("parse_tree", |b| b.iter(|| jzero_parser::parse_tree(&src).unwrap()));
+    group.finish();
+}
+
+criterion_group!(benches, bench_lexer);
+criterion_main!(benches);
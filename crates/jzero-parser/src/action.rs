@@ -18,4 +18,28 @@ impl<'a> TreeAction<'a> {
     pub fn apply(self, tree: Tree) -> Tree {
         (self.0)(tree)
     }
-}
\ No newline at end of file
+}
+/// What follows the first name declared with a class type, left-factored
+/// out of IdentifierStartedStmt and ForInit: the `[]`s after the name, its
+/// initializer, and the declarators after it.
+pub struct DeclRest {
+    pub dims: usize,
+    pub init: Option<Tree>,
+    pub rest: Vec<Tree>,
+}
+
+impl DeclRest {
+    /// The `LocalVarDecl` of type `type_id` whose first name is `name`.
+    pub fn local_var_decl(self, type_id: Tree, name: Tree) -> Tree {
+        let mut first = Tree::new("VarDeclarator", 0, vec![name]);
+        for _ in 0..self.dims {
+            first = Tree::new("VarDeclarator", 1, vec![first]);
+        }
+        if let Some(init) = self.init {
+            first = Tree::new("VarDeclarator", 2, vec![first, init]);
+        }
+        let mut kids = vec![type_id, first];
+        kids.extend(self.rest);
+        Tree::new("LocalVarDecl", 0, kids)
+    }
+}
@@ -0,0 +1,289 @@
+//! Consistency check between the lexer's [`Token`] set and the grammar's
+//! terminals.
+//!
+//! Tokens reach the parser in two steps: [`Lexer`](crate::lexer::Lexer)
+//! turns each `Token` into a [`Tok`], and the `extern` block of
+//! `jzero.lalrpop` names each `Tok` variant as a terminal.  Nothing stops the
+//! two from drifting apart — a new token can be mapped to the wrong `Tok`, or
+//! a terminal can be left that no token produces.  [`verify`] lexes a sample
+//! of every token through the real `Lexer`, reads the terminal table from
+//! the grammar source, and reports every mismatch.
+//!
+//! New variants are caught at compile time as far as possible:
+//! [`Token::spelling`] and [`tok_variant`] are exhaustive matches.
+
+use std::fmt;
+
+use jzero_lexer::token::Token;
+use jzero_lexer::{LexOptions, Level};
+
+use crate::lexer::{Lexer, Tok};
+
+const GRAMMAR: &str = include_str!("jzero.lalrpop");
+
+/// One `"name" => Tok::Variant` line of the grammar's `extern` block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Terminal {
+    /// The terminal as written in productions, e.g. `";"` or `identifier`.
+    pub name: String,
+    /// The `Tok` variant it stands for.
+    pub variant: String,
+    /// Whether the terminal carries the token's text or value.
+    pub has_payload: bool,
+}
+
+/// A mismatch found by [`verify`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BridgeError {
+    /// The token becomes a `Tok` that no terminal names.
+    Unmapped { token: String, variant: String },
+    /// More than one terminal names the token's `Tok` variant.
+    Ambiguous { token: String, terminals: Vec<String> },
+    /// A keyword or operator is delivered as a terminal with other spelling.
+    WrongTerminal { token: String, spelling: String, terminal: String },
+    /// A fixed-spelling terminal is produced by more than one token.
+    SharedTerminal { terminal: String, tokens: Vec<String> },
+    /// No token produces the terminal.
+    UnusedTerminal { terminal: String },
+}
+
+impl fmt::Display for BridgeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BridgeError::Unmapped { token, variant } => {
+                write!(f, "token {} becomes Tok::{}, which no grammar terminal names", token, variant)
+            }
+            BridgeError::Ambiguous { token, terminals } => {
+                write!(f, "token {} matches several terminals: {}", token, terminals.join(", "))
+            }
+            BridgeError::WrongTerminal { token, spelling, terminal } => {
+                write!(f, "token {} ('{}') is delivered as terminal \"{}\"", token, spelling, terminal)
+            }
+            BridgeError::SharedTerminal { terminal, tokens } => {
+                write!(f, "terminal \"{}\" is produced by several tokens: {}", terminal, tokens.join(", "))
+            }
+            BridgeError::UnusedTerminal { terminal } => {
+                write!(f, "terminal \"{}\" is not produced by any token", terminal)
+            }
+        }
+    }
+}
+
+/// The terminals declared in `jzero.lalrpop`, in source order.
+pub fn terminals() -> Vec<Terminal> {
+    let Some(start) = GRAMMAR.find("enum Tok<'input> {") else { return vec![] };
+    GRAMMAR[start..]
+        .lines()
+        .skip(1)
+        .map(|l| l.split("//").next().unwrap_or_default().trim())
+        .take_while(|l| *l != "}")
+        .filter_map(|l| {
+            let (name, rhs) = l.split_once("=>")?;
+            let name = name.trim().trim_matches('"').to_string();
+            let rhs = rhs.trim().trim_end_matches(',').strip_prefix("Tok::")?;
+            let variant = rhs.split('(').next().unwrap_or_default().trim().to_string();
+            Some(Terminal { name, variant, has_payload: rhs.contains('(') })
+        })
+        .collect()
+}
+
+/// The name of a `Tok` variant, as written in the grammar.
+pub fn tok_variant(tok: &Tok<'_>) -> &'static str {
+    match tok {
+        Tok::Bool => "Bool",
+        Tok::Break => "Break",
+        Tok::Class => "Class",
+        Tok::Double => "Double",
+        Tok::Else => "Else",
+        Tok::For => "For",
+        Tok::If => "If",
+        Tok::Int => "Int",
+        Tok::New => "New",
+        Tok::Null => "Null",
+        Tok::Private => "Private",
+        Tok::Protected => "Protected",
+        Tok::Public => "Public",
+        Tok::Return => "Return",
+        Tok::Static => "Static",
+        Tok::StringKw => "StringKw",
+        Tok::Void => "Void",
+        Tok::While => "While",
+        Tok::Case => "Case",
+        Tok::Catch => "Catch",
+        Tok::Char => "Char",
+        Tok::Default => "Default",
+        Tok::Extends => "Extends",
+        Tok::Final => "Final",
+        Tok::Finally => "Finally",
+        Tok::Implements => "Implements",
+        Tok::Import => "Import",
+        Tok::Instanceof => "Instanceof",
+        Tok::Interface => "Interface",
+        Tok::Long => "Long",
+        Tok::Switch => "Switch",
+        Tok::This => "This",
+        Tok::Throw => "Throw",
+        Tok::Try => "Try",
+        Tok::Var => "Var",
+        Tok::BoolLit(_) => "BoolLit",
+        Tok::IntLit(_) => "IntLit",
+        Tok::LongLit(_) => "LongLit",
+        Tok::DoubleLit(_) => "DoubleLit",
+        Tok::FloatLit(_) => "FloatLit",
+        Tok::StringLit(_) => "StringLit",
+        Tok::CharLit(_) => "CharLit",
+        Tok::Identifier(_) => "Identifier",
+        Tok::ReservedWord(_) => "ReservedWord",
+        Tok::LParen => "LParen",
+        Tok::RParen => "RParen",
+        Tok::LBracket => "LBracket",
+        Tok::RBracket => "RBracket",
+        Tok::LBrace => "LBrace",
+        Tok::RBrace => "RBrace",
+        Tok::Semicolon => "Semicolon",
+        Tok::Colon => "Colon",
+        Tok::Comma => "Comma",
+        Tok::Dot => "Dot",
+        Tok::Plus => "Plus",
+        Tok::Minus => "Minus",
+        Tok::Star => "Star",
+        Tok::Slash => "Slash",
+        Tok::Percent => "Percent",
+        Tok::Assign => "Assign",
+        Tok::Bang => "Bang",
+        Tok::Less => "Less",
+        Tok::Greater => "Greater",
+        Tok::LessEqual => "LessEqual",
+        Tok::GreaterEqual => "GreaterEqual",
+        Tok::EqualEqual => "EqualEqual",
+        Tok::NotEqual => "NotEqual",
+        Tok::LogicalAnd => "LogicalAnd",
+        Tok::LogicalOr => "LogicalOr",
+        Tok::PlusAssign => "PlusAssign",
+        Tok::MinusAssign => "MinusAssign",
+        Tok::StarAssign => "StarAssign",
+        Tok::SlashAssign => "SlashAssign",
+        Tok::PercentAssign => "PercentAssign",
+        Tok::Eof => "Eof",
+    }
+}
+
+/// Source text that lexes to `token`; `None` for hidden tokens.
+fn sample(token: &Token) -> Option<&'static str> {
+    if token.is_hidden() { return None; }
+    token.spelling().or(match token {
+        Token::IntLit     => Some("1"),
+        Token::LongLit    => Some("1L"),
+        Token::DoubleLit  => Some("1.5"),
+        Token::FloatLit   => Some("1.5f"),
+        Token::StringLit  => Some("\"s\""),
+        Token::CharLit    => Some("'c'"),
+        Token::Identifier => Some("x"),
+        Token::ReservedWord => Some("goto"),
+        _ => None,
+    })
+}
+
+/// Check that every visible token reaches exactly one terminal with the
+/// right spelling, and that every terminal is reachable.
+///
+/// # Errors
+/// Returns every mismatch found.
+pub fn verify() -> Result<(), Vec<BridgeError>> {
+    let terminals = terminals();
+    let mut errors = Vec::new();
+    // For each terminal, the tokens that reach it.
+    let mut producers: Vec<Vec<String>> = vec![Vec::new(); terminals.len()];
+
+    for token in Token::ALL {
+        let Some(text) = sample(token) else { continue };
+        let name = format!("{:?}", token);
+        let toks: Vec<_> = Lexer::with_options(text, LexOptions::at_level(Level::MAX)).collect();
+        let variant = match toks.as_slice() {
+            [Ok((_, tok, _)), Ok((_, Tok::Eof, _))] => tok_variant(tok),
+            _ => "<none>",
+        };
+
+        let matches: Vec<usize> = terminals.iter()
+            .enumerate()
+            .filter(|(_, t)| t.variant == variant)
+            .map(|(i, _)| i)
+            .collect();
+        let i = match matches.as_slice() {
+            [i] => *i,
+            [] => {
+                errors.push(BridgeError::Unmapped { token: name, variant: variant.to_string() });
+                continue;
+            }
+            _ => {
+                let names = matches.iter().map(|&i| terminals[i].name.clone()).collect();
+                errors.push(BridgeError::Ambiguous { token: name, terminals: names });
+                continue;
+            }
+        };
+
+        let terminal = &terminals[i];
+        if let Some(spelling) = token.spelling()
+            && !terminal.has_payload
+            && terminal.name != spelling
+        {
+            errors.push(BridgeError::WrongTerminal {
+                token: name.clone(),
+                spelling: spelling.to_string(),
+                terminal: terminal.name.clone(),
+            });
+        }
+        producers[i].push(name);
+    }
+
+    // The end of the input is the lexer's own token, delivered even for
+    // an empty one.
+    let end: Vec<_> = Lexer::new("").collect();
+    if let [Ok((0, tok, 0))] = end.as_slice()
+        && let Some(i) = terminals.iter().position(|t| t.variant == tok_variant(tok))
+    {
+        producers[i].push("end of input".to_string());
+    }
+
+    for (terminal, tokens) in terminals.iter().zip(producers) {
+        if tokens.is_empty() {
+            errors.push(BridgeError::UnusedTerminal { terminal: terminal.name.clone() });
+        } else if tokens.len() > 1 && !terminal.has_payload {
+            errors.push(BridgeError::SharedTerminal { terminal: terminal.name.clone(), tokens });
+        }
+    }
+
+    if errors.is_empty() { Ok(()) } else { Err(errors) }
+}
+
+// ─── Tests ───────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_terminal_table_is_read_from_grammar() {
+        let ts = terminals();
+        assert!(ts.contains(&Terminal { name: ";".into(), variant: "Semicolon".into(), has_payload: false }));
+        assert!(ts.contains(&Terminal { name: "boollit".into(), variant: "BoolLit".into(), has_payload: true }));
+        assert_eq!(ts.iter().filter(|t| t.variant == "New").count(), 1);
+    }
+
+    #[test]
+    fn test_token_order_matches_declaration() {
+        // A variant inserted into `Token` without updating `Token::ALL`
+        // shifts the discriminants after it.
+        for (i, tok) in Token::ALL.iter().enumerate() {
+            assert_eq!(tok.clone() as usize, i, "{:?} is out of place in Token::ALL", tok);
+        }
+    }
+
+    #[test]
+    fn test_bridge_is_consistent() {
+        if let Err(errors) = verify() {
+            let lines: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+            panic!("lexer and grammar disagree:\n{}", lines.join("\n"));
+        }
+    }
+}
@@ -1,8 +1,11 @@
+use jzero_ast::{CancellationToken, Cancelled};
+use jzero_lexer::{LexOptions, escape};
 use jzero_lexer::token::Token;
+use jzero_text::{Diagnostic, Span};
 use logos::SpannedIter;
 use std::fmt;
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum Tok<'input> {
     // Keywords
     Bool,
@@ -15,24 +18,49 @@ pub enum Tok<'input> {
     Int,
     New,        // ← NEW
     Null,
+    Private,
+    Protected,
     Public,
     Return,
     Static,
     StringKw,
     Void,
     While,
+    Case,
+    Catch,
+    Char,
+    Default,
+    Extends,
+    Final,
+    Finally,
+    Implements,
+    Import,
+    Instanceof,
+    Interface,
+    Long,
+    Switch,
+    This,
+    Throw,
+    Try,
+    Var,
 
     // Boolean literals
     BoolLit(bool),
 
     // Literals with text
     IntLit(&'input str),
+    LongLit(&'input str),
     DoubleLit(&'input str),
+    FloatLit(&'input str),
     StringLit(&'input str),
+    CharLit(&'input str),
 
     // Identifier with text
     Identifier(&'input str),
 
+    // A Java keyword Jzero does not support
+    ReservedWord(&'input str),
+
     // Delimiters
     LParen,
     RParen,
@@ -41,6 +69,7 @@ pub enum Tok<'input> {
     LBrace,
     RBrace,
     Semicolon,
+    Colon,
     Comma,
     Dot,
 
@@ -62,6 +91,13 @@ pub enum Tok<'input> {
     LogicalOr,
     PlusAssign,
     MinusAssign,
+    StarAssign,
+    SlashAssign,
+    PercentAssign,
+
+    /// The end of the input, delivered once after the last token at the
+    /// input's length, so errors met there have a position to point at.
+    Eof,
 }
 
 impl<'input> fmt::Display for Tok<'input> {
@@ -77,17 +113,40 @@ impl<'input> fmt::Display for Tok<'input> {
             Tok::Int => write!(f, "int"),
             Tok::New => write!(f, "new"),
             Tok::Null => write!(f, "null"),
+            Tok::Private => write!(f, "private"),
+            Tok::Protected => write!(f, "protected"),
             Tok::Public => write!(f, "public"),
             Tok::Return => write!(f, "return"),
             Tok::Static => write!(f, "static"),
             Tok::StringKw => write!(f, "string"),
             Tok::Void => write!(f, "void"),
             Tok::While => write!(f, "while"),
+            Tok::Case => write!(f, "case"),
+            Tok::Catch => write!(f, "catch"),
+            Tok::Char => write!(f, "char"),
+            Tok::Default => write!(f, "default"),
+            Tok::Extends => write!(f, "extends"),
+            Tok::Final => write!(f, "final"),
+            Tok::Finally => write!(f, "finally"),
+            Tok::Implements => write!(f, "implements"),
+            Tok::Import => write!(f, "import"),
+            Tok::Instanceof => write!(f, "instanceof"),
+            Tok::Interface => write!(f, "interface"),
+            Tok::Long => write!(f, "long"),
+            Tok::Switch => write!(f, "switch"),
+            Tok::This => write!(f, "this"),
+            Tok::Throw => write!(f, "throw"),
+            Tok::Try => write!(f, "try"),
+            Tok::Var => write!(f, "var"),
             Tok::BoolLit(b) => write!(f, "{}", b),
             Tok::IntLit(s) => write!(f, "{}", s),
+            Tok::LongLit(s) => write!(f, "{}", s),
             Tok::DoubleLit(s) => write!(f, "{}", s),
+            Tok::FloatLit(s) => write!(f, "{}", s),
             Tok::StringLit(s) => write!(f, "{}", s),
+            Tok::CharLit(s) => write!(f, "{}", s),
             Tok::Identifier(s) => write!(f, "{}", s),
+            Tok::ReservedWord(s) => write!(f, "{}", s),
             Tok::LParen => write!(f, "("),
             Tok::RParen => write!(f, ")"),
             Tok::LBracket => write!(f, "["),
@@ -95,6 +154,7 @@ impl<'input> fmt::Display for Tok<'input> {
             Tok::LBrace => write!(f, "{{"),
             Tok::RBrace => write!(f, "}}"),
             Tok::Semicolon => write!(f, ";"),
+            Tok::Colon => write!(f, ":"),
             Tok::Comma => write!(f, ","),
             Tok::Dot => write!(f, "."),
             Tok::Plus => write!(f, "+"),
@@ -114,88 +174,236 @@ impl<'input> fmt::Display for Tok<'input> {
             Tok::LogicalOr => write!(f, "||"),
             Tok::PlusAssign => write!(f, "+="),
             Tok::MinusAssign => write!(f, "-="),
+            Tok::StarAssign => write!(f, "*="),
+            Tok::SlashAssign => write!(f, "/="),
+            Tok::PercentAssign => write!(f, "%="),
+            Tok::Eof => write!(f, "end of file"),
         }
     }
 }
 
+/// What went wrong in a [`LexicalError`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LexicalErrorKind {
+    /// A character that starts no token, e.g. `@`.
+    UnknownChar,
+    /// A `"` with no closing quote on its line.
+    UnterminatedString,
+    /// A `/*` with no closing `*/`.
+    UnterminatedComment,
+    /// An unknown `\` escape inside a string or character literal.
+    BadEscape,
+    /// A number running straight into letters, e.g. `12abc`.
+    NumberFormat,
+    /// Lexing stopped because its [`CancellationToken`] was cancelled.
+    Cancelled,
+    /// The input has more tokens than [`LexOptions::max_tokens`] allows.
+    TooManyTokens,
+}
+
+impl LexicalErrorKind {
+    /// The diagnostic code, e.g. `unknown-char`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            LexicalErrorKind::UnknownChar         => "unknown-char",
+            LexicalErrorKind::UnterminatedString  => "unterminated-string",
+            LexicalErrorKind::UnterminatedComment => "unterminated-comment",
+            LexicalErrorKind::BadEscape           => "bad-escape",
+            LexicalErrorKind::NumberFormat        => "number-format",
+            LexicalErrorKind::Cancelled           => "cancelled",
+            LexicalErrorKind::TooManyTokens       => "too-many-tokens",
+        }
+    }
+}
+
+/// An error met lexing, placed both as a byte span and as the line and
+/// column it starts at.
 #[derive(Clone, Debug, PartialEq)]
 pub struct LexicalError {
-    pub pos: usize,
-    pub msg: String,
+    pub kind: LexicalErrorKind,
+    /// The offending text, as byte offsets into the input.
+    pub span: Span,
+    /// 1-based line of `span.start`.
+    pub line: usize,
+    /// 1-based column of `span.start`, in characters.
+    pub column: usize,
+    pub text: String,
+}
+
+impl LexicalError {
+    fn new(kind: LexicalErrorKind, input: &str, start: usize, end: usize) -> Self {
+        let (line, column) = jzero_text::line_col(input, start);
+        LexicalError { kind, span: Span::new(start, end), line, column, text: input[start..end].to_string() }
+    }
+
+    pub fn message(&self) -> String {
+        match self.kind {
+            LexicalErrorKind::UnknownChar         => format!("unrecognized character {:?}", self.text),
+            LexicalErrorKind::UnterminatedString  => "unterminated string literal".to_string(),
+            LexicalErrorKind::UnterminatedComment => "unterminated block comment".to_string(),
+            LexicalErrorKind::BadEscape           => format!("invalid escape sequence {:?}", self.text),
+            LexicalErrorKind::NumberFormat        => format!("malformed number {:?}", self.text),
+            LexicalErrorKind::Cancelled           => Cancelled.to_string(),
+            LexicalErrorKind::TooManyTokens       => "too many tokens".to_string(),
+        }
+    }
 }
 
 impl fmt::Display for LexicalError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "lexical error at byte {}: {}", self.pos, self.msg)
+        write!(f, "lexical error at line {} column {}: {}", self.line, self.column, self.message())
+    }
+}
+
+impl From<&LexicalError> for Diagnostic {
+    fn from(e: &LexicalError) -> Self {
+        Diagnostic::error(e.span, e.message())
+            .with_code(e.kind.code())
+            .with_arg("text", format!("{:?}", e.text))
+            .with_arg("line", e.line)
+            .with_arg("column", e.column)
+    }
+}
+
+/// The `Tok` for every token whose terminal carries no text, indexed by
+/// `Token as usize`, so the hot path of [`Lexer`] maps keywords and
+/// operators with one load instead of slicing the input.
+static FIXED: [Option<Tok<'static>>; Token::ALL.len()] = {
+    let mut table = [const { None }; Token::ALL.len()];
+    let mut i = 0;
+    while i < Token::ALL.len() {
+        table[i] = fixed(&Token::ALL[i]);
+        i += 1;
+    }
+    table
+};
+
+/// The `Tok` for `tok` if its terminal carries no text.  An exhaustive
+/// match, so a new token cannot be forgotten.
+const fn fixed(tok: &Token) -> Option<Tok<'static>> {
+    match tok {
+        Token::Bool => Some(Tok::Bool),
+        Token::Break => Some(Tok::Break),
+        Token::Class => Some(Tok::Class),
+        Token::Double => Some(Tok::Double),
+        Token::Else => Some(Tok::Else),
+        Token::For => Some(Tok::For),
+        Token::If => Some(Tok::If),
+        Token::Int => Some(Tok::Int),
+        Token::New => Some(Tok::New),
+        Token::Null => Some(Tok::Null),
+        Token::Private => Some(Tok::Private),
+        Token::Protected => Some(Tok::Protected),
+        Token::Public => Some(Tok::Public),
+        Token::Return => Some(Tok::Return),
+        Token::Static => Some(Tok::Static),
+        Token::StringKw => Some(Tok::StringKw),
+        Token::Void => Some(Tok::Void),
+        Token::While => Some(Tok::While),
+        Token::Case => Some(Tok::Case),
+        Token::Catch => Some(Tok::Catch),
+        Token::Char => Some(Tok::Char),
+        Token::Default => Some(Tok::Default),
+        Token::Extends => Some(Tok::Extends),
+        Token::Final => Some(Tok::Final),
+        Token::Finally => Some(Tok::Finally),
+        Token::Implements => Some(Tok::Implements),
+        Token::Import => Some(Tok::Import),
+        Token::Instanceof => Some(Tok::Instanceof),
+        Token::Interface => Some(Tok::Interface),
+        Token::Long => Some(Tok::Long),
+        Token::Switch => Some(Tok::Switch),
+        Token::This => Some(Tok::This),
+        Token::Throw => Some(Tok::Throw),
+        Token::Try => Some(Tok::Try),
+        Token::Var => Some(Tok::Var),
+        Token::True => Some(Tok::BoolLit(true)),
+        Token::False => Some(Tok::BoolLit(false)),
+        Token::LParen => Some(Tok::LParen),
+        Token::RParen => Some(Tok::RParen),
+        Token::LBracket => Some(Tok::LBracket),
+        Token::RBracket => Some(Tok::RBracket),
+        Token::LBrace => Some(Tok::LBrace),
+        Token::RBrace => Some(Tok::RBrace),
+        Token::Semicolon => Some(Tok::Semicolon),
+        Token::Colon => Some(Tok::Colon),
+        Token::Comma => Some(Tok::Comma),
+        Token::Dot => Some(Tok::Dot),
+        Token::Plus => Some(Tok::Plus),
+        Token::Minus => Some(Tok::Minus),
+        Token::Star => Some(Tok::Star),
+        Token::Slash => Some(Tok::Slash),
+        Token::Percent => Some(Tok::Percent),
+        Token::Assign => Some(Tok::Assign),
+        Token::Bang => Some(Tok::Bang),
+        Token::Less => Some(Tok::Less),
+        Token::Greater => Some(Tok::Greater),
+        Token::LessEqual => Some(Tok::LessEqual),
+        Token::GreaterEqual => Some(Tok::GreaterEqual),
+        Token::EqualEqual => Some(Tok::EqualEqual),
+        Token::NotEqual => Some(Tok::NotEqual),
+        Token::LogicalAnd => Some(Tok::LogicalAnd),
+        Token::LogicalOr => Some(Tok::LogicalOr),
+        Token::PlusAssign => Some(Tok::PlusAssign),
+        Token::MinusAssign => Some(Tok::MinusAssign),
+        Token::StarAssign => Some(Tok::StarAssign),
+        Token::SlashAssign => Some(Tok::SlashAssign),
+        Token::PercentAssign => Some(Tok::PercentAssign),
+        Token::IntLit | Token::LongLit | Token::DoubleLit | Token::FloatLit
+        | Token::StringLit | Token::CharLit
+        | Token::Identifier | Token::ReservedWord
+        | Token::Newline | Token::LineComment | Token::BlockComment | Token::DocComment => None,
     }
 }
 
 pub struct Lexer<'input> {
     input: &'input str,
     inner: SpannedIter<'input, Token>,
+    options: LexOptions,
+    /// Tokens starting before this offset were swallowed by an error.
+    resume: usize,
+    cancel: Option<CancellationToken>,
+    /// Tokens delivered so far, for [`LexOptions::max_tokens`].
+    count: usize,
+    /// Whether [`Tok::Eof`] has been delivered.
+    at_eof: bool,
 }
 
 impl<'input> Lexer<'input> {
     pub fn new(input: &'input str) -> Self {
+        Lexer::with_options(input, LexOptions::default())
+    }
+
+    pub fn with_options(input: &'input str, options: LexOptions) -> Self {
         use logos::Logos;
-        Lexer { input, inner: Token::lexer(input).spanned() }
+        Lexer { input, inner: Token::lexer(input).spanned(), options, resume: 0, cancel: None, count: 0, at_eof: false }
+    }
+
+    /// Stop with a [`LexicalErrorKind::Cancelled`] error at the next token
+    /// once `token` is cancelled.
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancel = Some(token);
+        self
     }
 
     fn map_token(&self, tok: Token, start: usize, end: usize) -> Tok<'input> {
+        if let Some(fixed) = FIXED[tok.clone() as usize] {
+            return fixed;
+        }
         let slice = &self.input[start..end];
         match tok {
-            Token::Bool => Tok::Bool,
-            Token::Break => Tok::Break,
-            Token::Class => Tok::Class,
-            Token::Double => Tok::Double,
-            Token::Else => Tok::Else,
-            Token::For => Tok::For,
-            Token::If => Tok::If,
-            Token::Int => Tok::Int,
-            Token::New => Tok::New,       // ← NEW
-            Token::Null => Tok::Null,
-            Token::Public => Tok::Public,
-            Token::Return => Tok::Return,
-            Token::Static => Tok::Static,
-            Token::StringKw => Tok::StringKw,
-            Token::Void => Tok::Void,
-            Token::While => Tok::While,
-            Token::True => Tok::BoolLit(true),
-            Token::False => Tok::BoolLit(false),
             Token::IntLit => Tok::IntLit(slice),
+            Token::LongLit => Tok::LongLit(slice),
             Token::DoubleLit => Tok::DoubleLit(slice),
+            Token::FloatLit => Tok::FloatLit(slice),
             Token::StringLit => Tok::StringLit(slice),
+            Token::CharLit => Tok::CharLit(slice),
             Token::Identifier => Tok::Identifier(slice),
-            Token::LParen => Tok::LParen,
-            Token::RParen => Tok::RParen,
-            Token::LBracket => Tok::LBracket,
-            Token::RBracket => Tok::RBracket,
-            Token::LBrace => Tok::LBrace,
-            Token::RBrace => Tok::RBrace,
-            Token::Semicolon => Tok::Semicolon,
-            Token::Comma => Tok::Comma,
-            Token::Dot => Tok::Dot,
-            Token::Plus => Tok::Plus,
-            Token::Minus => Tok::Minus,
-            Token::Star => Tok::Star,
-            Token::Slash => Tok::Slash,
-            Token::Percent => Tok::Percent,
-            Token::Assign => Tok::Assign,
-            Token::Bang => Tok::Bang,
-            Token::Less => Tok::Less,
-            Token::Greater => Tok::Greater,
-            Token::LessEqual => Tok::LessEqual,
-            Token::GreaterEqual => Tok::GreaterEqual,
-            Token::EqualEqual => Tok::EqualEqual,
-            Token::NotEqual => Tok::NotEqual,
-            Token::LogicalAnd => Tok::LogicalAnd,
-            Token::LogicalOr => Tok::LogicalOr,
-            Token::PlusAssign => Tok::PlusAssign,
-            Token::MinusAssign => Tok::MinusAssign,
-            Token::Colon => Tok::Semicolon,
-            Token::Newline | Token::LineComment | Token::BlockComment => {
+            Token::ReservedWord => Tok::ReservedWord(slice),
+            Token::Newline | Token::LineComment | Token::BlockComment | Token::DocComment => {
                 unreachable!("hidden tokens should be filtered")
             }
+            _ => unreachable!("{:?} is in the fixed table", tok),
         }
     }
 }
@@ -205,17 +413,76 @@ impl<'input> Iterator for Lexer<'input> {
 
     fn next(&mut self) -> Option<Self::Item> {
         loop {
-            match self.inner.next() {
-                None => return None,
-                Some((result, span)) => match result {
-                    Ok(tok) => {
-                        if tok.is_hidden() { continue; }
-                        let mapped = self.map_token(tok, span.start, span.end);
-                        return Some(Ok((span.start, mapped, span.end)));
+            let Some((result, span)) = self.inner.next() else {
+                if self.at_eof { return None; }
+                self.at_eof = true;
+                let end = self.input.len();
+                return Some(Ok((end, Tok::Eof, end)));
+            };
+            if span.start < self.resume { continue; }
+            if let Some(cancel) = &self.cancel
+                && cancel.is_cancelled()
+            {
+                self.resume = self.input.len();
+                let kind = LexicalErrorKind::Cancelled;
+                return Some(Err(LexicalError::new(kind, self.input, span.start, span.start)));
+            }
+            match result {
+                Ok(tok) if tok.is_hidden() => continue,
+                Ok(tok) => {
+                    if matches!(tok, Token::IntLit | Token::LongLit | Token::DoubleLit | Token::FloatLit)
+                        && let Some(err) = self.number_suffix(span.start, span.end)
+                    {
+                        return Some(Err(err));
+                    }
+                    self.count += 1;
+                    if self.options.max_tokens.is_some_and(|max| self.count > max) {
+                        self.resume = self.input.len();
+                        let kind = LexicalErrorKind::TooManyTokens;
+                        return Some(Err(LexicalError::new(kind, self.input, span.start, span.end)));
                     }
-                    Err(msg) => return Some(Err(LexicalError { pos: span.start, msg })),
-                },
+                    let tok = tok.at_level(self.options.language_level);
+                    let mapped = self.map_token(tok, span.start, span.end);
+                    return Some(Ok((span.start, mapped, span.end)));
+                }
+                Err(_) => return Some(Err(self.classify(span.start, span.end))),
             }
         }
     }
-}
\ No newline at end of file
+}
+
+impl<'input> Lexer<'input> {
+    /// A number literal followed directly by letters, as in `12abc`.
+    fn number_suffix(&mut self, start: usize, end: usize) -> Option<LexicalError> {
+        let rest = &self.input[end..];
+        let len = rest.find(|c: char| !(c.is_alphanumeric() || c == '_')).unwrap_or(rest.len());
+        if len == 0 { return None; }
+        self.resume = end + len;
+        Some(LexicalError::new(LexicalErrorKind::NumberFormat, self.input, start, end + len))
+    }
+
+    /// Turn a failed match at `start..end` into a categorised error.
+    fn classify(&mut self, start: usize, end: usize) -> LexicalError {
+        let slice = &self.input[start..end];
+        let closed = slice.len() > 1 && slice.starts_with(['"', '\'']) && slice.ends_with(&slice[..1]);
+        if closed && let Some((at, esc)) = escape::bad_escape(slice) {
+            // `"a\qb"`: report the escape itself.
+            self.resume = end;
+            let kind = LexicalErrorKind::BadEscape;
+            return LexicalError::new(kind, self.input, start + at, start + at + esc.len());
+        }
+        if slice.starts_with("/*") {
+            // The comment runs to the end of the input; point at its start.
+            return LexicalError::new(LexicalErrorKind::UnterminatedComment, self.input, start, start + 2);
+        }
+        if slice.starts_with("\"\"\"") {
+            // A text block runs to the end of the input; point at its start.
+            return LexicalError::new(LexicalErrorKind::UnterminatedString, self.input, start, start + 3);
+        }
+        if slice.starts_with('"') {
+            // A string cannot span lines, so the error ends with its line.
+            return LexicalError::new(LexicalErrorKind::UnterminatedString, self.input, start, end);
+        }
+        LexicalError::new(LexicalErrorKind::UnknownChar, self.input, start, end)
+    }
+}
@@ -1,6 +1,9 @@
 pub mod action;
+pub mod bridge;
 pub mod lexer;
 pub mod loc;
+pub mod precedence;
+pub mod treesitter;
 
 // LALRPOP generates the parser module from jzero.lalrpop at build time
 lalrpop_util::lalrpop_mod!(
@@ -14,9 +17,14 @@ lalrpop_util::lalrpop_mod!(
     jzero
 );
 
+use jzero_ast::{CancellationToken, Interner, NodeId};
 use jzero_ast::tree::Tree;
+use jzero_lexer::LexOptions;
+use jzero_lexer::token::Token;
 use lexer::{Lexer, LexicalError, Tok};
+use loc::LineIndex;
 use lalrpop_util::ParseError;
+use jzero_text::{Diagnostic, Span};
 
 /// Result of parsing: success flag plus any error messages.
 #[derive(Debug)]
@@ -30,7 +38,8 @@ pub struct ParseResult {
 /// This corresponds to Chapter 4 of the book: accept/reject with error recovery.
 pub fn parse(input: &str) -> ParseResult {
     let lexer = Lexer::new(input);
-    match jzero::ClassDeclParser::new().parse(input, lexer) {
+    let level = LexOptions::default().language_level;
+    match jzero::ClassDeclParser::new().parse(&LineIndex::new(input), lexer).map_err(|e| at_level(at_eof(e), level)) {
         Ok(_) => ParseResult {
             success: true,
             errors: vec![],
@@ -49,10 +58,182 @@ pub fn parse(input: &str) -> ParseResult {
 ///
 /// This corresponds to Chapter 5 of the book: building syntax trees.
 pub fn parse_tree(input: &str) -> Result<Tree, String> {
-    let lexer = Lexer::new(input);
+    parse_tree_with(input, LexOptions::default())
+}
+
+/// [`parse_tree`] with the keywords of `options.language_level`.
+pub fn parse_tree_with(input: &str, options: LexOptions) -> Result<Tree, String> {
+    parse_raw(input, options, None).map_err(|e| format_error(input, e))
+}
+
+/// [`parse_tree_with`], stopping at the next token once `cancel` is
+/// cancelled.  Check the token to tell cancellation from a syntax error.
+pub fn parse_tree_cancellable(
+    input: &str,
+    options: LexOptions,
+    cancel: &CancellationToken,
+) -> Result<Tree, String> {
+    parse_raw(input, options, Some(cancel)).map_err(|e| format_error(input, e))
+}
+
+/// [`parse_tree_with`] in hash-consing mode: the tree is stored in
+/// `interner`, where a subtree identical to one seen before, in this
+/// input or an earlier one, shares its storage.  Returns the id of the
+/// root; [`Interner::to_tree`] rebuilds it.
+pub fn parse_tree_interned(input: &str, options: LexOptions, interner: &mut Interner) -> Result<NodeId, String> {
+    parse_tree_with(input, options).map(|tree| interner.intern(&tree))
+}
+
+/// [`parse_tree_with`], reporting a failure as a [`Diagnostic`] placed at
+/// the offending token rather than as a string.
+///
+/// With a `cancel` token, parsing stops at the next token once it is
+/// cancelled and fails with a diagnostic whose code is `cancelled`.
+pub fn parse_tree_diagnostic(
+    input: &str,
+    options: LexOptions,
+    cancel: Option<&CancellationToken>,
+) -> Result<Tree, Diagnostic> {
+    parse_raw(input, options, cancel).map_err(|e| match e {
+        ParseError::User { error } => Diagnostic::from(&error),
+        e => syntax_diagnostic(input, e),
+    })
+}
+
+/// A syntax error as a [`Diagnostic`], with a code per kind of error and
+/// the `line`, `column`, `token`, `word` and `expected` its message shows
+/// as arguments.
+fn syntax_diagnostic(input: &str, e: ParseError<usize, Tok<'_>, LexicalError>) -> Diagnostic {
+    let offset = error_offset(&e);
+    let (line, column) = offset_to_line_col(input, offset);
+    let level = match &e {
+        ParseError::UnrecognizedToken { token: (_, Tok::Identifier(word), _), .. } => keyword_level(word),
+        _ => None,
+    };
+    let (code, token, expected) = match &e {
+        ParseError::InvalidToken { .. } => ("invalid-token", None, None),
+        ParseError::UnrecognizedEof { expected, .. } => ("unexpected-eof", None, Some(expected)),
+        ParseError::UnrecognizedToken { token: (_, Tok::ReservedWord(word), _), .. } =>
+            ("reserved-word", Some(("word", word.to_string())), None),
+        ParseError::UnrecognizedToken { token: (_, Tok::Identifier(word), _), .. } if level.is_some() =>
+            ("level-keyword", Some(("word", word.to_string())), None),
+        ParseError::UnrecognizedToken { token: (_, tok, _), expected } => {
+            let colon = *tok == Tok::Colon && expected.iter().any(|e| e == "\";\"");
+            let code = if colon { "colon-for-semicolon" } else { "unexpected-token" };
+            (code, Some(("token", tok.to_string())), Some(expected))
+        }
+        ParseError::ExtraToken { token: (_, tok, _) } => ("extra-token", Some(("token", tok.to_string())), None),
+        ParseError::User { error } => return Diagnostic::from(error),
+    };
+    let mut d = Diagnostic::error(Span::at(offset), format_error(input, e.clone()))
+        .with_code(code)
+        .with_arg("line", line)
+        .with_arg("column", column);
+    if let Some((name, token)) = token {
+        d = d.with_arg(name, token);
+    }
+    if let Some(expected) = expected {
+        d = d.with_arg("expected", expected_text(expected));
+    }
+    if let Some(level) = level {
+        d = d.with_arg("level", level.name());
+    }
+    d
+}
+
+/// The level `word` is a keyword from, if it is one the lexer gave as an
+/// identifier: `extends` out of place in a Jzero program is most likely
+/// meant as the keyword, and saying so beats "unexpected token".
+fn keyword_level(word: &str) -> Option<jzero_lexer::Level> {
+    Token::keyword(word).map(|t| t.min_level()).filter(|l| *l > jzero_lexer::Level::Jzero)
+}
+
+/// [`parse_tree_diagnostic`], going on past lexical errors: the text an
+/// error covers is left out and the tokens around it are parsed, so that
+/// one stray character does not cost the whole tree.  Returns the tree if
+/// the remaining tokens parse, and every error found, in source order.
+pub fn parse_tree_recovering(input: &str, options: LexOptions) -> (Option<Tree>, Vec<Diagnostic>) {
+    let mut found = Vec::new();
+    let mut tokens = Vec::new();
+    for item in Lexer::with_options(input, options) {
+        match item {
+            Ok(tok) => tokens.push(Ok(tok)),
+            Err(e) => found.push(Diagnostic::from(&e)),
+        }
+    }
+    let parsed = jzero::ClassDeclParser::new().parse(&LineIndex::new(input), tokens);
+    let tree = match parsed.map_err(|e| at_level(at_eof(e), options.language_level)) {
+        Ok(mut tree) => {
+            tree.number_nodes();
+            Some(tree)
+        }
+        Err(e) => {
+            found.push(syntax_diagnostic(input, e));
+            None
+        }
+    };
+    found.sort_by_key(|d| d.span.start);
+    (tree, found)
+}
+
+fn parse_raw<'input>(
+    input: &'input str,
+    options: LexOptions,
+    cancel: Option<&CancellationToken>,
+) -> Result<Tree, ParseError<usize, Tok<'input>, LexicalError>> {
+    let mut lexer = Lexer::with_options(input, options);
+    if let Some(token) = cancel {
+        lexer = lexer.with_cancellation(token.clone());
+    }
     jzero::ClassDeclParser::new()
-        .parse(input, lexer)
-        .map_err(|e| format_error(input, e))
+        .parse(&LineIndex::new(input), lexer)
+        .map(|mut tree| {
+            tree.number_nodes();
+            debug_assert!(tree.validate().is_ok(), "{:?}", tree.validate());
+            tree
+        })
+        .map_err(|e| at_level(at_eof(e), options.language_level))
+}
+
+/// Report the lexer's [`Tok::Eof`] met out of place as the end of file it
+/// stands for, at the position it carries.
+fn at_eof(err: ParseError<usize, Tok<'_>, LexicalError>) -> ParseError<usize, Tok<'_>, LexicalError> {
+    match err {
+        ParseError::UnrecognizedToken { token: (location, Tok::Eof, _), expected } =>
+            ParseError::UnrecognizedEof { location, expected },
+        err => err,
+    }
+}
+
+/// Leave the keywords above `level` out of the terminals `err` expected:
+/// the lexer gives them as identifiers there, so none of them could come.
+fn at_level(
+    err: ParseError<usize, Tok<'_>, LexicalError>,
+    level: jzero_lexer::Level,
+) -> ParseError<usize, Tok<'_>, LexicalError> {
+    let keep = |e: &String| Token::keyword(e.trim_matches('"')).is_none_or(|t| t.min_level() <= level);
+    match err {
+        ParseError::UnrecognizedEof { location, mut expected } => {
+            expected.retain(keep);
+            ParseError::UnrecognizedEof { location, expected }
+        }
+        ParseError::UnrecognizedToken { token, mut expected } => {
+            expected.retain(keep);
+            ParseError::UnrecognizedToken { token, expected }
+        }
+        err => err,
+    }
+}
+
+/// Byte offset at which a parse error is reported.
+fn error_offset(err: &ParseError<usize, Tok<'_>, LexicalError>) -> usize {
+    match err {
+        ParseError::InvalidToken { location }
+        | ParseError::UnrecognizedEof { location, .. } => *location,
+        ParseError::UnrecognizedToken { token: (start, _, _), .. }
+        | ParseError::ExtraToken { token: (start, _, _) } => *start,
+        ParseError::User { error } => error.span.start,
+    }
 }
 
 /// Format a LALRPOP ParseError into a human-readable string.
@@ -69,42 +250,58 @@ fn format_error(
             let (line, col) = offset_to_line_col(input, location);
             format!(
                 "Unexpected end of file at line {} column {}. Expected one of: {}",
-                line, col, expected.join(", ")
+                line, col, expected_text(&expected)
             )
         }
-        ParseError::UnrecognizedToken { token: (start, tok, _end), expected } => {
+        ParseError::UnrecognizedToken { token: (start, Tok::ReservedWord(word), _end), .. } => {
             let (line, col) = offset_to_line_col(input, start);
             format!(
-                "Unexpected token '{}' at line {} column {}. Expected one of: {}",
-                tok, line, col, expected.join(", ")
+                "Reserved word '{}' at line {} column {} is not supported in Jzero",
+                word, line, col
+            )
+        }
+        ParseError::UnrecognizedToken { token: (start, Tok::Identifier(word), _end), .. }
+            if keyword_level(word).is_some() =>
+        {
+            let (line, col) = offset_to_line_col(input, start);
+            format!(
+                "'{}' at line {} column {} is a keyword of language level '{}' and is not supported in Jzero",
+                word, line, col, keyword_level(word).unwrap().name()
             )
         }
+        ParseError::UnrecognizedToken { token: (start, tok, _end), expected } => {
+            let (line, col) = offset_to_line_col(input, start);
+            let mut msg = format!(
+                "Unexpected token '{}' at line {} column {}. Expected one of: {}",
+                tok, line, col, expected_text(&expected)
+            );
+            // `:` only ever ends a case label.
+            if tok == Tok::Colon && expected.iter().any(|e| e == "\";\"") {
+                msg.push_str(". ':' only ends a case label; did you mean ';'?");
+            }
+            msg
+        }
         ParseError::ExtraToken { token: (start, tok, _end) } => {
             let (line, col) = offset_to_line_col(input, start);
             format!("Extra token '{}' at line {} column {}", tok, line, col)
         }
-        ParseError::User { error } => {
-            format!("{}", error)
-        }
+        ParseError::User { error } => Diagnostic::from(&error).render(input),
     }
 }
 
-/// Convert a byte offset into (1-based line, 1-based column).
+/// The terminals a parser expected, as a message lists them; the `"EOF"`
+/// every input ends with is the end of file.
+fn expected_text(expected: &[String]) -> String {
+    let names: Vec<&str> = expected.iter()
+        .map(|e| if e == "\"EOF\"" { "end of file" } else { e.as_str() })
+        .collect();
+    names.join(", ")
+}
+
+/// Convert a byte offset into (1-based line, 1-based column), counting
+/// the column in characters so `é` or `→` before it counts once.
 fn offset_to_line_col(input: &str, offset: usize) -> (usize, usize) {
-    let mut line = 1;
-    let mut col = 1;
-    for (i, ch) in input.char_indices() {
-        if i >= offset {
-            break;
-        }
-        if ch == '\n' {
-            line += 1;
-            col = 1;
-        } else {
-            col += 1;
-        }
-    }
-    (line, col)
+    jzero_text::line_col(input, offset)
 }
 
 #[cfg(test)]
@@ -352,6 +549,56 @@ public class T {
         assert_eq!(var_decl.kids[1].sym, "VarDeclarator");
     }
 
+    #[test]
+    fn test_tree_array_initializers() {
+        let src = r#"
+public class T {
+    public static void main(String argv[]) {
+        int a[] = {1, 2 + 3,};
+        Point p[][] = {{}, {origin}}, q;
+        for (String s[] = {"x"}; ; ) { }
+    }
+}
+"#;
+        let tree = parse_tree(src).expect("parse failed");
+        let block = get_method_block(&tree);
+        let a = &block.kids[0].kids[1];
+        assert_eq!((a.sym.as_str(), a.rule, a.kids[0].rule), ("VarDeclarator", 2, 1));
+        assert_eq!(a.kids[1].sym, "ArrayInit");
+        assert_eq!(a.kids[1].kids.len(), 2);
+        assert_eq!(a.kids[1].kids[1].sym, "AddExpr");
+        // A class-typed declaration keeps the `[]`s of its first name.
+        let p = &block.kids[1];
+        assert_eq!(p.kids.len(), 3);
+        assert_eq!((p.kids[1].rule, p.kids[1].kids[0].rule, p.kids[1].kids[0].kids[0].rule), (2, 1, 1));
+        let init = &p.kids[1].kids[1];
+        assert_eq!(init.kids.iter().map(|k| k.kids.len()).collect::<Vec<_>>(), [0, 1]);
+        let s = &block.kids[2].kids[0].kids[1];
+        assert_eq!((s.rule, s.kids[1].kids.len()), (2, 1));
+    }
+
+    #[test]
+    fn test_tree_initialized_locals() {
+        let src = r#"
+public class T {
+    public static void main(String argv[]) {
+        int x = 5, y, z = x * 2;
+        String s = "hi";
+        for (int i = 0; i < 3; i += 1) { }
+    }
+}
+"#;
+        let tree = parse_tree(src).expect("parse failed");
+        let block = get_method_block(&tree);
+        let decl = &block.kids[0];
+        assert_eq!(decl.kids.iter().map(|k| k.rule).collect::<Vec<_>>(), [-1, 2, 0, 2]);
+        assert_eq!((decl.kids[1].kids[1].sym.as_str(), decl.kids[3].kids[1].sym.as_str()), ("INTLIT", "MulExpr"));
+        let s = &block.kids[1];
+        assert_eq!((s.kids[1].rule, s.kids[1].kids[1].sym.as_str()), (2, "STRINGLIT"));
+        let i = &block.kids[2].kids[0].kids[1];
+        assert_eq!((i.rule, i.kids[1].sym.as_str()), (2, "INTLIT"));
+    }
+
     #[test]
     fn test_tree_assignment() {
         let src = r#"
@@ -371,6 +618,26 @@ public class T {
         assert_eq!(assign.kids[2].tok.as_ref().unwrap().text, "42");
     }
 
+    #[test]
+    fn test_tree_compound_assignments() {
+        let src = r#"
+public class T {
+    public static void main(String argv[]) {
+        x += 1; x -= 2; x *= 3; x /= 4; x %= 5;
+    }
+}
+"#;
+        let tree = parse_tree(src).expect("parse failed");
+        let block = get_method_block(&tree);
+        let ops: Vec<(&str, &str)> = block.kids.iter().map(|assign| {
+            assert_eq!((assign.sym.as_str(), assign.nkids), ("Assignment", 3));
+            let op = assign.kids[1].tok.as_ref().unwrap();
+            (op.category.as_str(), op.text.as_str())
+        }).collect();
+        assert_eq!(ops, [("PLUSASSIGN", "+="), ("MINUSASSIGN", "-="), ("STARASSIGN", "*="),
+            ("SLASHASSIGN", "/="), ("PERCENTASSIGN", "%=")]);
+    }
+
     #[test]
     fn test_tree_arithmetic() {
         let src = r#"
@@ -465,6 +732,58 @@ public class T {
         assert_eq!(for_stmt.kids[3].sym, "Block");
     }
 
+    #[test]
+    fn test_tree_while_loop_bodies() {
+        let src = r#"
+public class T {
+    public static void main(String argv[]) {
+        while (i < 10) i = i + 1;
+        while (i > 0) System.out.println(i);
+        while (true) { break; }
+        while (x) ;
+    }
+}
+"#;
+        let tree = parse_tree(src).expect("parse failed");
+        let block = get_method_block(&tree);
+        let bodies: Vec<&str> = block.kids.iter().map(|w| {
+            assert_eq!((w.sym.as_str(), w.nkids), ("WhileStmt", 2));
+            w.kids[1].sym.as_str()
+        }).collect();
+        assert_eq!(bodies, ["Assignment", "MethodCall", "Block", "EmptyStmt"]);
+
+        let decl = "public class T { public static void main(String argv[]) { while (b) int i; } }";
+        assert!(parse_tree(decl).is_err(), "a declaration is not a loop body");
+    }
+
+    #[test]
+    fn test_tree_left_out_parts_are_placeholders() {
+        let src = r#"
+public class T {
+    public static void main(String argv[]) {
+        for (;
+             ;
+             ) {
+            break;
+        }
+        return;
+    }
+}
+"#;
+        let tree = parse_tree(src).expect("parse failed");
+        let block = get_method_block(&tree);
+        let for_stmt = &block.kids[0];
+        let parts: Vec<(&str, usize)> = for_stmt.kids[..3].iter()
+            .map(|k| (k.sym.as_str(), k.tok.as_ref().unwrap().lineno))
+            .collect();
+        assert_eq!(parts, [("EmptyForInit", 4), ("EmptyExpr", 5), ("EmptyForUpdate", 6)]);
+        assert!(for_stmt.kids[..3].iter().all(Tree::is_empty_placeholder));
+        let brk = &for_stmt.kids[3].kids[0];
+        assert_eq!((brk.rule, brk.kids[0].sym.as_str()), (0, "EmptyLabel"));
+        let ret = &block.kids[1];
+        assert_eq!((ret.rule, ret.nkids, ret.kids[0].tok.as_ref().unwrap().lineno), (1, 1, 9));
+    }
+
     #[test]
     fn test_tree_field_assignment() {
         let src = r#"
@@ -483,6 +802,497 @@ public class T {
         assert_eq!(assign.kids[0].kids[1].tok.as_ref().unwrap().text, "field");
     }
 
+    #[test]
+    fn test_tree_named_constants() {
+        let src = r#"
+public class T {
+    static final bool DEBUG = false;
+    private static final int A = 1, B;
+}
+"#;
+        let tree = parse_tree_with(src, LexOptions::at_level(jzero_lexer::Level::Extended)).expect("parse failed");
+        let (debug, ab) = (&tree.kids[1], &tree.kids[2]);
+        assert_eq!((debug.sym.as_str(), debug.rule, debug.kids[0].sym.as_str()), ("FieldDecl", 3, "BOOL"));
+        assert_eq!((ab.rule, ab.kids.len()), (5, 3));
+        // `final` is a keyword only at the extended level.
+        assert!(parse_tree(src).is_err());
+    }
+
+    #[test]
+    fn test_tree_subclass() {
+        let src = "public class Sub extends Base { int y; }";
+        let tree = parse_tree_with(src, LexOptions::at_level(jzero_lexer::Level::Extended)).expect("parse failed");
+        assert_eq!((tree.sym.as_str(), tree.rule, tree.kids.len()), ("ClassDecl", 1, 3));
+        assert_eq!(tree.kids[1].tok.as_ref().unwrap().text, "Base");
+        assert_eq!(tree.kids[2].sym, "FieldDecl");
+        assert!(parse_tree(src).is_err());
+    }
+
+    #[test]
+    fn test_tree_interfaces() {
+        let level = || LexOptions::at_level(jzero_lexer::Level::Extended);
+        let tree = parse_tree_with("public interface Shape { double area(double w); private void hide(); }", level())
+            .expect("parse failed");
+        assert_eq!((tree.sym.as_str(), tree.kids.len()), ("InterfaceDecl", 3));
+        let header = &tree.kids[1].kids[0];
+        assert_eq!((tree.kids[1].sym.as_str(), header.sym.as_str(), tree.kids[2].kids[0].rule), ("AbstractMethodDecl", "MethodHeader", 2));
+
+        let tree = parse_tree_with("public class Sq extends Rect implements Shape, Named { }", level()).expect("parse failed");
+        assert_eq!((tree.rule, tree.kids[2].sym.as_str(), tree.kids[2].kids.len()), (3, "InterfaceList", 2));
+        let tree = parse_tree_with("public class Sq implements Shape { int n; }", level()).expect("parse failed");
+        assert_eq!((tree.rule, tree.kids[1].sym.as_str(), tree.kids[2].sym.as_str()), (2, "InterfaceList", "FieldDecl"));
+    }
+
+    #[test]
+    fn test_tree_imports() {
+        let src = "import java.util.Scanner;\nimport java.util.*;\npublic class T { }";
+        let tree = parse_tree_with(src, LexOptions::at_level(jzero_lexer::Level::Extended)).expect("parse failed");
+        let syms: Vec<&str> = tree.kids.iter().map(|k| k.sym.as_str()).collect();
+        assert_eq!((tree.sym.as_str(), syms), ("CompilationUnit", vec!["ImportDecl", "ImportDecl", "ClassDecl"]));
+        assert_eq!((tree.kids[0].rule, tree.kids[0].kids.len()), (0, 3));
+        assert_eq!((tree.kids[1].rule, tree.kids[1].kids.len()), (1, 2));
+        // Without imports the class is the root, as before.
+        assert_eq!(parse_tree("public class T { }").expect("parse failed").sym, "ClassDecl");
+        assert!(parse_tree_with("import java.*.Scanner;\npublic class T { }", LexOptions::at_level(jzero_lexer::Level::Extended)).is_err());
+    }
+
+    #[test]
+    fn test_tree_instance_members() {
+        let src = "public class C { int n; private static int made; public int get() { return this.n; } public void set(int k) { this.n = k; } }";
+        let tree = parse_tree_with(src, LexOptions::at_level(jzero_lexer::Level::Extended)).expect("parse failed");
+        let rules: Vec<i32> = tree.kids[1..].iter().map(|k| if k.sym == "MethodDecl" { k.kids[0].rule } else { k.rule }).collect();
+        assert_eq!(rules, [0, 8, 3, 3]);
+        let ret = &tree.kids[3].kids[1].kids[0];
+        assert_eq!((ret.kids[0].sym.as_str(), ret.kids[0].kids[0].sym.as_str()), ("FieldAccess", "THIS"));
+        let assign = &tree.kids[4].kids[1].kids[0];
+        assert_eq!((assign.sym.as_str(), assign.kids[0].kids[0].sym.as_str()), ("Assignment", "THIS"));
+        // Below the extended level `this` is a name like any other.
+        let tree = parse_tree(src).expect("parse failed");
+        assert_eq!(tree.kids[3].kids[1].kids[0].kids[0].kids[0].sym, "IDENTIFIER");
+    }
+
+    #[test]
+    fn test_tree_cast() {
+        let src = "public class T { public static void f() { r = (int) x * y; r = (x) - y; r = -(double) x; } }";
+        let tree = parse_tree(src).expect("parse failed");
+        let block = get_method_block(&tree);
+        let mul = &block.kids[0].kids[2];
+        assert_eq!((mul.sym.as_str(), mul.kids[0].sym.as_str()), ("MulExpr", "CastExpr"));
+        assert_eq!((mul.kids[0].kids[0].sym.as_str(), mul.kids[0].kids[1].sym.as_str()), ("INT", "IDENTIFIER"));
+        // A parenthesized name is no cast.
+        let sub = &block.kids[1].kids[2];
+        assert_eq!((sub.sym.as_str(), sub.kids[0].sym.as_str()), ("AddExpr", "IDENTIFIER"));
+        let neg = &block.kids[2].kids[2];
+        assert_eq!((neg.sym.as_str(), neg.kids[0].kids[0].sym.as_str()), ("UnaryMinus", "DOUBLE"));
+    }
+
+    #[test]
+    fn test_tree_char_and_long() {
+        let src = "public class T { char c; public static long f(char k, long n) { long m; m = (long) k; c = 'x'; return n; } }";
+        let tree = parse_tree_with(src, LexOptions::at_level(jzero_lexer::Level::Extended)).expect("parse failed");
+        assert_eq!(tree.kids[1].kids[0].sym, "CHAR");
+        let header = &tree.kids[2].kids[0];
+        let parms: Vec<&str> = header.kids[1].kids[1..].iter().map(|p| p.kids[0].sym.as_str()).collect();
+        assert_eq!((header.kids[0].sym.as_str(), parms), ("LONG", vec!["CHAR", "LONG"]));
+        let block = &tree.kids[2].kids[1];
+        assert_eq!((block.kids[0].kids[0].sym.as_str(), block.kids[1].kids[2].kids[0].sym.as_str()), ("LONG", "LONG"));
+        // Below the extended level they are class names.
+        let tree = parse_tree("public class T { char c; }").expect("parse failed");
+        assert_eq!(tree.kids[1].kids[0].sym, "IDENTIFIER");
+    }
+
+    #[test]
+    fn test_tree_final() {
+        let src = "public class T { private final int size = 4; public static final int MAX = 10; public static void f() { final Point p = q; } }";
+        let tree = parse_tree_with(src, LexOptions::at_level(jzero_lexer::Level::Extended)).expect("parse failed");
+        assert_eq!((tree.kids[1].rule, tree.kids[2].rule), (11, 3));
+        let decl = &tree.kids[3].kids[1].kids[0];
+        assert_eq!((decl.sym.as_str(), decl.rule, decl.kids[0].sym.as_str()), ("LocalVarDecl", 3, "IDENTIFIER"));
+    }
+
+    #[test]
+    fn test_tree_var() {
+        let src = "public class T { public static void f() { var x = 1 + 2; for (var i = 0; i < x; i += 1) { } } }";
+        let tree = parse_tree_with(src, LexOptions::at_level(jzero_lexer::Level::Extended)).expect("parse failed");
+        let block = &tree.kids[1].kids[1];
+        let decl = &block.kids[0];
+        assert_eq!((decl.sym.as_str(), decl.rule, decl.kids[0].sym.as_str()), ("LocalVarDecl", 2, "VAR"));
+        assert_eq!((decl.kids[1].rule, decl.kids[1].kids[1].sym.as_str()), (2, "AddExpr"));
+        // Below the extended level `var` is a class name like any other.
+        let tree = parse_tree(src).expect("parse failed");
+        let decl = &tree.kids[1].kids[1].kids[0];
+        assert_eq!((decl.rule, decl.kids[0].sym.as_str()), (0, "IDENTIFIER"));
+    }
+
+    #[test]
+    fn test_tree_instance_creation() {
+        let src = r#"
+public class T {
+    public static void main(String argv[]) {
+        p = new Point(1, x + 2);
+        n = new Point().x;
+    }
+}
+"#;
+        let tree = parse_tree(src).expect("parse failed");
+        let block = get_method_block(&tree);
+        let create = &block.kids[0].kids[2];
+        assert_eq!(create.sym, "InstanceCreation");
+        let kids: Vec<&str> = create.kids.iter().map(|k| k.sym.as_str()).collect();
+        assert_eq!(kids, ["IDENTIFIER", "INTLIT", "AddExpr"]);
+        assert_eq!(create.kids[0].tok.as_ref().unwrap().text, "Point");
+        let access = &block.kids[1].kids[2];
+        assert_eq!((access.sym.as_str(), access.kids[0].sym.as_str()), ("FieldAccess", "InstanceCreation"));
+    }
+
+    #[test]
+    fn test_tree_access_modifiers() {
+        let src = r#"
+public class T {
+    int a;
+    public int b;
+    protected int c;
+    private int d;
+    private static void f() { }
+}
+"#;
+        let tree = parse_tree(src).expect("parse failed");
+        let rules: Vec<i32> = tree.kids[1..5].iter().map(|k| k.rule).collect();
+        assert_eq!(rules, vec![0, 0, 1, 2]);
+        let header = &tree.kids[5].kids[0];
+        assert_eq!(header.sym, "MethodHeader");
+        assert_eq!(header.rule, 2);
+    }
+
+    #[test]
+    fn test_stray_colon_is_diagnosed() {
+        let src = r#"
+public class T {
+    public static void main(String argv[]) {
+        int x:
+    }
+}
+"#;
+        let result = parse(src);
+        assert!(!result.success);
+        assert!(result.errors[0].starts_with("Unexpected token ':' at line 4"), "{:?}", result.errors);
+        assert!(result.errors[0].ends_with("did you mean ';'?"), "{:?}", result.errors);
+    }
+
+    #[test]
+    fn test_unicode_identifiers() {
+        let src = "public class Größe { public static void main(String argv[]) { int π; π = 3; } }";
+        let tree = parse_tree(src).unwrap();
+        assert_eq!(tree.kids[0].tok.as_ref().unwrap().text, "Größe");
+        let spans: Vec<_> = Lexer::new("π = ü;").filter_map(Result::ok).map(|(s, _, e)| (s, e)).collect();
+        assert_eq!(spans, [(0, 2), (3, 4), (5, 7), (7, 8), (8, 8)]);
+
+        // Columns count characters, not bytes.
+        let result = parse("public class Größe { int x int }");
+        assert!(result.errors[0].contains("line 1 column 28"), "{:?}", result.errors);
+    }
+
+    #[test]
+    fn test_lexical_error_kinds() {
+        use lexer::LexicalErrorKind;
+        let first_error = |src| Lexer::new(src).find_map(Result::err).unwrap();
+
+        let e = first_error("int @ x");
+        assert_eq!(e.kind, LexicalErrorKind::UnknownChar);
+        assert_eq!((e.span.start, e.span.end), (4, 5));
+        assert_eq!((e.line, e.column), (1, 5));
+        assert_eq!(e.to_string(), "lexical error at line 1 column 5: unrecognized character \"@\"");
+
+        let e = first_error("x = \"abc;\ny = 1;");
+        assert_eq!(e.kind, LexicalErrorKind::UnterminatedString);
+        assert_eq!(e.text, "\"abc;");
+        let rest: Vec<_> = Lexer::new("x = \"abc;\ny = 1;").filter_map(Result::ok).map(|(_, t, _)| t).collect();
+        assert_eq!(rest[2..], [Tok::Identifier("y"), Tok::Assign, Tok::IntLit("1"), Tok::Semicolon, Tok::Eof]);
+        // Columns count characters, not bytes.
+        let e = first_error("x = 1;\n  y = \"é\\q\";");
+        assert_eq!((e.line, e.column), (2, 9));
+
+        let e = first_error("x = 1; /* open\ny = @;");
+        assert_eq!(e.kind, LexicalErrorKind::UnterminatedComment);
+        assert_eq!((e.text.as_str(), e.span.start), ("/*", 7));
+        assert_eq!((e.line, e.column), (1, 8));
+        assert_eq!(Lexer::new("x = 1; /* open\ny = @;").filter(Result::is_err).count(), 1);
+
+        let e = first_error("x = 12abc;");
+        assert_eq!(e.kind, LexicalErrorKind::NumberFormat);
+        assert_eq!(e.text, "12abc");
+        let rest: Vec<_> = Lexer::new("x = 12abc;").filter_map(Result::ok).map(|(_, t, _)| t).collect();
+        assert_eq!(rest, vec![Tok::Identifier("x"), Tok::Assign, Tok::Semicolon, Tok::Eof]);
+
+        let e = first_error("x = 12Lx;");
+        assert_eq!((e.kind, e.text.as_str()), (LexicalErrorKind::NumberFormat, "12Lx"));
+
+        let e = first_error("s = \"a\\qb\"; t = 1;");
+        assert_eq!(e.kind, LexicalErrorKind::BadEscape);
+        assert_eq!((e.text.as_str(), e.span.start), ("\\q", 6));
+
+        let e = first_error("c = '\\q';");
+        assert_eq!(e.kind, LexicalErrorKind::BadEscape);
+        assert_eq!(e.text, "\\q");
+    }
+
+    #[test]
+    fn test_tree_char_literal() {
+        let src = r#"
+public class T {
+    public static void main(String argv[]) {
+        x = '\n';
+    }
+}
+"#;
+        let tree = parse_tree(src).expect("parse failed");
+        let assign = &get_method_block(&tree).kids[0];
+        let tok = assign.kids[2].tok.as_ref().unwrap();
+        assert_eq!((tok.category.as_str(), tok.text.as_str()), ("CHARLIT", "'\\n'"));
+    }
+
+    #[test]
+    fn test_tree_suffixed_literals() {
+        let src = r#"
+public class T {
+    public static void main(String argv[]) {
+        x = 12L;
+        y = 1.5f;
+        z = 2d;
+    }
+}
+"#;
+        let tree = parse_tree(src).expect("parse failed");
+        let leaves: Vec<(String, String)> = get_method_block(&tree).kids.iter()
+            .map(|a| a.kids[2].tok.as_ref().unwrap())
+            .map(|t| (t.category.clone(), t.text.clone()))
+            .collect();
+        assert_eq!(leaves, [
+            ("LONGLIT".to_string(), "12L".to_string()),
+            ("FLOATLIT".to_string(), "1.5f".to_string()),
+            ("DOUBLELIT".to_string(), "2d".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn test_tree_string_value() {
+        let src = r#"
+public class T {
+    public static void main(String argv[]) {
+        s = "say \"hi\"";
+    }
+}
+"#;
+        let tree = parse_tree(src).expect("parse failed");
+        let assign = &get_method_block(&tree).kids[0];
+        let tok = assign.kids[2].tok.as_ref().unwrap();
+        assert_eq!(tok.text, r#""say \"hi\"""#);
+        assert_eq!(tok.sval.as_deref(), Some("say \"hi\""));
+    }
+
+    #[test]
+    fn test_text_block_is_a_string_leaf() {
+        let src = "public class T {\n    public static void main(String argv[]) {\n        s = \"\"\"\n            one\n              two\n            \"\"\";\n        t = 1;\n    }\n}\n";
+        let tree = parse_tree(src).expect("parse failed");
+        let block = get_method_block(&tree);
+        let tok = block.kids[0].kids[2].tok.as_ref().unwrap();
+        assert_eq!((tok.category.as_str(), tok.lineno), ("STRINGLIT", 3));
+        assert_eq!(tok.sval.as_deref(), Some("one\n  two\n"));
+        assert_eq!(block.kids[1].kids[0].tok.as_ref().unwrap().lineno, 7);
+
+        let open = src.find("\"\"\"").unwrap();
+        let err = parse_tree_diagnostic(&src[..open + 20], LexOptions::default(), None).unwrap_err();
+        assert_eq!((err.code, err.span.start, err.span.end), (Some("unterminated-string"), open, open + 3));
+    }
+
+    #[test]
+    fn test_token_limit() {
+        use lexer::LexicalErrorKind;
+        let options = LexOptions { max_tokens: Some(3), ..LexOptions::default() };
+        let toks: Vec<_> = Lexer::with_options("int x; y", options).collect();
+        assert_eq!(toks.len(), 5);
+        assert_eq!(toks[4], Ok((8, Tok::Eof, 8)));
+        let err = toks[3].clone().unwrap_err();
+        assert_eq!((err.kind, err.text.as_str()), (LexicalErrorKind::TooManyTokens, "y"));
+    }
+
+    #[test]
+    fn test_lexical_error_renders_as_diagnostic() {
+        let src = "public class T {\n    int x @;\n}";
+        let err = parse_tree(src).unwrap_err();
+        assert_eq!(err, "error: line 2: unrecognized character \"@\" [unknown-char]");
+        let d = parse_tree_diagnostic(src, LexOptions::default(), None).unwrap_err();
+        assert_eq!(d.render(src), err);
+        assert!(d.args.iter().any(|(k, v)| *k == "column" && v == "11"), "{:?}", d.args);
+    }
+
+    #[test]
+    fn test_tree_is_built_around_lexical_errors() {
+        let src = "public class T {\n    int x @;\n    int y #;\n}";
+        let (tree, found) = parse_tree_recovering(src, LexOptions::default());
+        assert_eq!(tree.unwrap().kids.len(), 3);
+        let codes: Vec<_> = found.iter().map(|d| (d.code, d.span.start)).collect();
+        assert_eq!(codes, [(Some("unknown-char"), 27), (Some("unknown-char"), 40)]);
+
+        // A syntax error after a lexical one is reported too.
+        let (tree, found) = parse_tree_recovering("public class T { int @ }", LexOptions::default());
+        assert!(tree.is_none());
+        assert_eq!(found.iter().map(|d| d.code.unwrap()).collect::<Vec<_>>(), ["unknown-char", "unexpected-token"]);
+    }
+
+    #[test]
+    fn test_cancelled_parse_stops() {
+        let src = "public class T { }";
+        let token = CancellationToken::new();
+        assert!(parse_tree_diagnostic(src, LexOptions::default(), Some(&token)).is_ok());
+        token.cancel();
+        let err = parse_tree_diagnostic(src, LexOptions::default(), Some(&token)).unwrap_err();
+        assert_eq!(err.code, Some("cancelled"));
+        assert_eq!(err.span.start, 0);
+    }
+
+    #[test]
+    fn test_language_level_keywords() {
+        let src = r#"
+public class T {
+    public static void main(String argv[]) {
+        int this;
+        this = 1;
+    }
+}
+"#;
+        assert!(parse_tree(src).is_ok());
+        let err = parse_tree_with(src, LexOptions::at_level(jzero_lexer::Level::Extended)).unwrap_err();
+        assert!(err.starts_with("Unexpected token 'this' at line 4"), "{}", err);
+    }
+
+    #[test]
+    fn test_tree_try_catch_finally_and_throw() {
+        let src = r#"
+public class T {
+    public static void main(String argv[]) {
+        try { f(); } catch (Oops e) { g(); } catch (Other e) { }
+        try { f(); } finally { g(); }
+        throw new Oops();
+    }
+}
+"#;
+        let tree = parse_tree_with(src, LexOptions::at_level(jzero_lexer::Level::Extended)).expect("parse failed");
+        let block = get_method_block(&tree);
+        let kids = |t: &Tree| t.kids.iter().map(|k| k.sym.clone()).collect::<Vec<_>>();
+        assert_eq!((block.kids[0].sym.as_str(), block.kids[0].rule), ("TryStmt", 0));
+        assert_eq!(kids(&block.kids[0]), ["Block", "CatchClause", "CatchClause"]);
+        assert_eq!(kids(&block.kids[0].kids[1]), ["FormalParm", "Block"]);
+        assert_eq!((block.kids[1].rule, kids(&block.kids[1])), (1, vec!["Block".to_string(), "Block".to_string()]));
+        assert_eq!(kids(&block.kids[2]), ["InstanceCreation"]);
+        assert_eq!(block.kids[2].sym, "ThrowStmt");
+
+        // A try needs a catch or a finally; at level jzero there is no try.
+        let bare = "public class T {\n    public static void main(String argv[]) {\n        try { }\n    }\n}\n";
+        assert!(parse_tree_with(bare, LexOptions::at_level(jzero_lexer::Level::Extended)).is_err());
+        assert!(parse_tree(src).is_err());
+    }
+
+    #[test]
+    fn test_tree_switch_case_groups() {
+        let src = r#"
+public class T {
+    public static void main(String argv[]) {
+        switch (n) {
+            case 1: case 2: f(); break;
+            default: g();
+            case -3:
+        }
+        switch (n) { }
+    }
+}
+"#;
+        let tree = parse_tree_with(src, LexOptions::at_level(jzero_lexer::Level::Extended)).expect("parse failed");
+        let block = get_method_block(&tree);
+        let kids = |t: &Tree| t.kids.iter().map(|k| k.sym.clone()).collect::<Vec<_>>();
+        assert_eq!(kids(&block.kids[0]), ["IDENTIFIER", "CaseClause", "CaseClause", "CaseClause"]);
+        let [_, first, second, last] = &block.kids[0].kids[..] else { unreachable!() };
+        assert_eq!(kids(first), ["Block", "INTLIT", "INTLIT"]);
+        assert_eq!(kids(&first.kids[0]), ["MethodCall", "BreakStmt"]);
+        assert_eq!(kids(second), ["Block", "DefaultLabel"]);
+        assert_eq!(second.kids[1].tok.as_ref().unwrap().lineno, 6);
+        // Trailing labels get an empty body.
+        assert_eq!(kids(last), ["Block", "UnaryMinus"]);
+        assert!(last.kids[0].kids.is_empty());
+        assert_eq!(kids(&block.kids[1]), ["IDENTIFIER"]);
+
+        let bad = "public class T {\n    public static void main(String argv[]) {\n        switch (n) { f(); }\n    }\n}\n";
+        assert!(parse_tree_with(bad, LexOptions::at_level(jzero_lexer::Level::Extended)).is_err());
+        assert!(parse_tree(src).is_err());
+    }
+
+    #[test]
+    fn test_reserved_word_is_rejected() {
+        let src = r#"
+public class T {
+    public static void main(String argv[]) {
+        int x;
+        do x = 1;
+    }
+}
+"#;
+        let err = parse_tree(src).unwrap_err();
+        assert_eq!(err, "Reserved word 'do' at line 5 column 9 is not supported in Jzero");
+    }
+
+    #[test]
+    fn test_keyword_of_a_higher_level_is_named() {
+        let src = "public class T extends Base {\n}\n";
+        let err = parse_tree(src).unwrap_err();
+        assert_eq!(err, "'extends' at line 1 column 16 is a keyword of language level 'extended' and is not supported in Jzero");
+        let d = parse_tree_diagnostic(src, LexOptions::default(), None).unwrap_err();
+        assert_eq!(d.code, Some("level-keyword"));
+        assert!(d.args.iter().any(|(k, v)| *k == "level" && v == "extended"), "{:?}", d.args);
+
+        // As a name it is still an identifier.
+        assert!(parse_tree("public class T {\n    public static void main(String argv[]) {\n        int import;\n    }\n}\n").is_ok());
+        let err = parse_tree("public class T {\n    boolean b;\n}\n").unwrap_err();
+        assert_eq!(err, "Reserved word 'boolean' at line 2 column 5 is not supported in Jzero");
+    }
+
+    #[test]
+    fn test_interned_trees_share_identical_subtrees() {
+        let src = "public class T {\n    public static void main(String argv[]) {\n        int x;\n        x = 1 + 2;\n        x = 1 + 2;\n    }\n}\n";
+        let mut interner = Interner::new();
+        let root = parse_tree_interned(src, LexOptions::default(), &mut interner).expect("parse failed");
+        // Both `x = 1 + 2;` statements, on different lines, are one node.
+        assert_eq!(interner.nodes().iter().filter(|n| n.sym == "Assignment").count(), 1);
+        assert!(interner.len() < interner.interned());
+
+        // A second input adds only what it does not share.
+        let before = interner.len();
+        assert_eq!(parse_tree_interned(src, LexOptions::default(), &mut interner), Ok(root));
+        assert_eq!(interner.len(), before);
+        assert_eq!(interner.to_tree(root).node_count(), parse_tree(src).unwrap().node_count());
+    }
+
+    #[test]
+    fn test_unexpected_end_of_file_points_at_the_end() {
+        let src = "public class T {\n    public static void main(String argv[]) {\n        int x;\n";
+        let err = parse_tree(src).unwrap_err();
+        assert!(err.starts_with("Unexpected end of file at line 4 column 1. Expected one of: "), "{}", err);
+        assert!(!err.contains("EOF"), "{}", err);
+        // Only the keywords of the level parsed at are expected.
+        assert!(!err.contains("\"char\"") && !err.contains("\"var\""), "{}", err);
+        let err = parse_tree_with(src, LexOptions::at_level(jzero_lexer::Level::Extended)).unwrap_err();
+        assert!(err.contains("\"char\"") && err.contains("\"var\""), "{}", err);
+        let d = parse_tree_diagnostic(src, LexOptions::default(), None).unwrap_err();
+        assert_eq!((d.code, d.span.start), (Some("unexpected-eof"), src.len()));
+
+        // Past the end of the class, only the end of file is expected.
+        let err = parse_tree("public class T { }\n}").unwrap_err();
+        assert_eq!(err, "Unexpected token '}' at line 2 column 1. Expected one of: end of file");
+
+        let toks: Vec<_> = Lexer::new("x ").collect();
+        assert_eq!(toks.last(), Some(&Ok((2, Tok::Eof, 2))));
+        assert_eq!(Lexer::new("").count(), 1);
+    }
+
     #[test]
     fn test_tree_dot_output_file() {
         let src = r#"
@@ -510,4 +1320,59 @@ public class hello {
         assert!(dot.contains("FieldAccess#0"));
         assert!(dot.contains("hello, jzero!"));
     }
-}
\ No newline at end of file
+    // ─── Production metadata ─────────────────────────────
+
+    /// Every `Tree::new(sym, rule, ..)` of the grammar.  The rule of
+    /// FieldDecl and MethodHeader is the `access` value, 0 to 2, or
+    /// `access + n`.
+    fn grammar_alternatives() -> Vec<(String, i32)> {
+        let grammar = include_str!("jzero.lalrpop");
+        let mut found = Vec::new();
+        for call in grammar.split("Tree::new(\"").skip(1) {
+            let (sym, rest) = call.split_once('"').unwrap();
+            let rule = rest.trim_start_matches([',', ' ']).split(',').next().unwrap().trim();
+            let rules = match rule.parse::<i32>() {
+                Ok(n) => vec![n],
+                Err(_) => {
+                    let offset = rule.strip_prefix("access").unwrap_or_else(|| panic!("{}: {}", sym, rule));
+                    let offset: i32 = offset.trim_start_matches([' ', '+']).parse().unwrap_or(0);
+                    (0..3).map(|a| a + offset).collect()
+                }
+            };
+            for r in rules {
+                if !found.contains(&(sym.to_string(), r)) { found.push((sym.to_string(), r)); }
+            }
+        }
+        found
+    }
+
+    #[test]
+    fn production_table_matches_the_grammar() {
+        let built = grammar_alternatives();
+        for (sym, rule) in &built {
+            assert!(jzero_ast::production::production_info(sym, *rule).is_some(), "{}#{} has no entry", sym, rule);
+        }
+        for p in jzero_ast::production::PRODUCTIONS {
+            assert!(built.contains(&(p.sym.to_string(), p.rule)), "{}#{} is never built", p.sym, p.rule);
+        }
+    }
+
+    #[test]
+    fn every_node_of_the_examples_has_roles_for_its_kids() {
+        fn check(tree: &Tree) {
+            if tree.is_leaf() { return; }
+            let info = tree.production_info().unwrap_or_else(|| panic!("{}#{}", tree.sym, tree.rule));
+            assert!(info.is_variadic() || info.roles.len() == tree.kids.len(), "{} has {} kids", info, tree.kids.len());
+            assert!(tree.kids.len() >= info.roles.len() - usize::from(info.is_variadic()), "{}", info);
+            tree.kids.iter().for_each(check);
+        }
+        let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/../../tests/examples");
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.extension().is_some_and(|e| e == "java")
+                && let Ok(tree) = parse_tree(&std::fs::read_to_string(&path).unwrap()) {
+                check(&tree);
+            }
+        }
+    }
+}
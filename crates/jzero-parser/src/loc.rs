@@ -1,18 +1,33 @@
 /// Convert a byte offset into a 1-based line number.
 ///
-/// Used by grammar action code to create leaf nodes with line info.
-/// The source text is passed through from the parser.
+/// Scans the input up to `offset`; to look up many offsets in the same
+/// input, build a [`LineIndex`] once instead.
 pub fn line_from_offset(input: &str, offset: usize) -> usize {
-    let mut line = 1;
-    for (i, ch) in input.char_indices() {
-        if i >= offset {
-            break;
-        }
-        if ch == '\n' {
-            line += 1;
-        }
+    let offset = offset.min(input.len());
+    1 + input.as_bytes()[..offset].iter().filter(|&&b| b == b'\n').count()
+}
+
+/// The start of every line of an input, for line lookups in O(log n).
+///
+/// Used by grammar action code to create leaf nodes with line info: the
+/// parser builds one per input and passes it to the grammar, so each leaf
+/// costs a binary search rather than a scan from the start of the file.
+#[derive(Debug, Clone)]
+pub struct LineIndex {
+    /// Byte offset of the first character of each line after the first.
+    starts: Vec<usize>,
+}
+
+impl LineIndex {
+    pub fn new(input: &str) -> Self {
+        let starts = input.bytes().enumerate().filter(|&(_, b)| b == b'\n').map(|(i, _)| i + 1).collect();
+        LineIndex { starts }
+    }
+
+    /// The 1-based line containing byte `offset`.
+    pub fn line(&self, offset: usize) -> usize {
+        1 + self.starts.partition_point(|&start| start <= offset)
     }
-    line
 }
 
 #[cfg(test)]
@@ -38,4 +53,13 @@ mod tests {
     fn test_offset_zero() {
         assert_eq!(line_from_offset("hello", 0), 1);
     }
+
+    #[test]
+    fn test_line_index_agrees() {
+        let input = "a\nbc\n\nd→e\n";
+        let index = LineIndex::new(input);
+        for offset in 0..=input.len() + 1 {
+            assert_eq!(index.line(offset), line_from_offset(input, offset), "offset {}", offset);
+        }
+    }
 }
\ No newline at end of file
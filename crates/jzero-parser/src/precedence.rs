@@ -0,0 +1,229 @@
+//! Operator precedence and associativity, as the grammar implements them.
+//!
+//! `jzero.lalrpop` spells precedence out as one nonterminal per level:
+//! `MulExpr` is built from `UnaryExpr`s, `AddExpr` from `MulExpr`s, and so
+//! on out to `Expr`.  [`TABLE`] lists the same levels, tightest first, with
+//! the operators of each and the tree node they build.  The tests check
+//! the table against the grammar's nonterminals and parse every pair of
+//! operators, so an operator added at the wrong level, or a level whose
+//! recursion is turned around, fails them.  `j0 grammar --precedence`
+//! prints [`chart`]:
+//!
+//! ```
+//! use jzero_parser::precedence::{Assoc, TABLE, operator};
+//!
+//! let (add, plus) = operator("+").unwrap();
+//! let (mul, _) = operator("*").unwrap();
+//! assert!(mul.precedence < add.precedence, "tighter levels come first");
+//! assert_eq!((plus.node, add.assoc), ("AddExpr", Assoc::Left));
+//! assert_eq!(TABLE.last().unwrap().assoc, Assoc::Right);
+//! ```
+
+use std::fmt::Write;
+
+/// How operators of one level group.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Assoc {
+    /// `a - b - c` is `(a - b) - c`.
+    Left,
+    /// `a = b = c` is `a = (b = c)`.
+    Right,
+    /// A prefix operator, applied to what follows it: `- - a`.
+    Prefix,
+}
+
+impl Assoc {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Assoc::Left   => "left",
+            Assoc::Right  => "right",
+            Assoc::Prefix => "prefix",
+        }
+    }
+}
+
+/// An operator and the node a use of it builds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Operator {
+    pub text: &'static str,
+    /// The node's sym, e.g. `MulExpr`; the operator is its middle kid,
+    /// but for a prefix operator, which has only its operand and, for a
+    /// cast, the type before it.
+    pub node: &'static str,
+}
+
+/// One level of precedence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PrecedenceLevel {
+    /// 1 for the tightest level.
+    pub precedence: u8,
+    /// The grammar nonterminal of the level.
+    pub nonterminal: &'static str,
+    pub assoc: Assoc,
+    pub operators: &'static [Operator],
+}
+
+const fn op(text: &'static str, node: &'static str) -> Operator {
+    Operator { text, node }
+}
+
+/// The levels of precedence, tightest first.
+pub const TABLE: &[PrecedenceLevel] = &[
+    PrecedenceLevel { precedence: 1, nonterminal: "UnaryExpr", assoc: Assoc::Prefix, operators: &[
+        op("-", "UnaryMinus"), op("!", "UnaryNot"), op("(int)", "CastExpr"), op("(double)", "CastExpr"),
+    ] },
+    PrecedenceLevel { precedence: 2, nonterminal: "MulExpr", assoc: Assoc::Left, operators: &[
+        op("*", "MulExpr"), op("/", "MulExpr"), op("%", "MulExpr"),
+    ] },
+    PrecedenceLevel { precedence: 3, nonterminal: "AddExpr", assoc: Assoc::Left, operators: &[
+        op("+", "AddExpr"), op("-", "AddExpr"),
+    ] },
+    PrecedenceLevel { precedence: 4, nonterminal: "RelExpr", assoc: Assoc::Left, operators: &[
+        op("<", "RelExpr"), op(">", "RelExpr"), op("<=", "RelExpr"), op(">=", "RelExpr"),
+        op("instanceof", "InstanceOfExpr"),
+    ] },
+    PrecedenceLevel { precedence: 5, nonterminal: "EqExpr", assoc: Assoc::Left, operators: &[
+        op("==", "EqExpr"), op("!=", "EqExpr"),
+    ] },
+    PrecedenceLevel { precedence: 6, nonterminal: "CondAndExpr", assoc: Assoc::Left, operators: &[
+        op("&&", "CondAndExpr"),
+    ] },
+    PrecedenceLevel { precedence: 7, nonterminal: "CondOrExpr", assoc: Assoc::Left, operators: &[
+        op("||", "CondOrExpr"),
+    ] },
+    // Only a variable, field or array element may be assigned: `a + b = c`
+    // does not parse.
+    PrecedenceLevel { precedence: 8, nonterminal: "Expr", assoc: Assoc::Right, operators: &[
+        op("=", "Assignment"), op("+=", "Assignment"), op("-=", "Assignment"),
+        op("*=", "Assignment"), op("/=", "Assignment"), op("%=", "Assignment"),
+    ] },
+];
+
+/// The binary operator `text` and its level; `-` is subtraction.
+pub fn operator(text: &str) -> Option<(&'static PrecedenceLevel, &'static Operator)> {
+    TABLE.iter()
+        .filter(|level| level.assoc != Assoc::Prefix)
+        .find_map(|level| level.operators.iter().find(|o| o.text == text).map(|o| (level, o)))
+}
+
+/// The table as a chart, one line per level, tightest first.
+pub fn chart() -> String {
+    let mut out = String::from("level  associativity  operators\n");
+    for level in TABLE {
+        let ops: Vec<&str> = level.operators.iter().map(|o| o.text).collect();
+        let _ = writeln!(out, "{:<6} {:<14} {}", level.precedence, level.assoc.name(), ops.join(" "));
+    }
+    out
+}
+
+// ─── Tests ───────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jzero_ast::tree::Tree;
+    use jzero_lexer::{Level, LexOptions};
+
+    use crate::treesitter::{Symbol, productions};
+
+    /// The right-hand side of `r = <expr>;` in a method body.
+    fn parse_expr(expr: &str) -> Result<Tree, String> {
+        let src = format!("public class T {{ public static void f() {{ r = {}; }} }}", expr);
+        let mut tree = crate::parse_tree_with(&src, LexOptions::at_level(Level::Extended))?;
+        let mut block = tree.kids.remove(1).kids.remove(1);
+        Ok(block.kids.remove(0).kids.remove(2))
+    }
+
+    /// Whether `tree` is a use of `op`.
+    fn is_use(tree: &Tree, op: &Operator) -> bool {
+        let text = match tree.kids.as_slice() {
+            // A prefix operator's node holds only its operand, or a cast's
+            // the type and the operand.
+            [_] => Some(op.text),
+            [ty, _] => ty.tok.as_ref().map(|t| t.text.as_str())
+                .and_then(|ty| (op.text.trim_matches(['(', ')']) == ty).then_some(op.text)),
+            [_, mid, _] => mid.tok.as_ref().map(|t| t.text.as_str()),
+            _ => None,
+        };
+        tree.sym == op.node && text == Some(op.text)
+    }
+
+    /// The binary operators but `instanceof`, whose right side is a class
+    /// name rather than an operand.
+    fn binary() -> impl Iterator<Item = (&'static PrecedenceLevel, &'static Operator)> {
+        TABLE.iter()
+            .filter(|level| level.assoc != Assoc::Prefix)
+            .flat_map(|level| level.operators.iter().map(move |o| (level, o)))
+            .filter(|(_, o)| o.node != "InstanceOfExpr")
+    }
+
+    #[test]
+    fn each_level_is_built_from_the_one_before() {
+        let grammar = productions();
+        for pair in TABLE.windows(2) {
+            let outer = grammar.iter().find(|p| p.name == pair[1].nonterminal)
+                .unwrap_or_else(|| panic!("no nonterminal {}", pair[1].nonterminal));
+            let inner = Symbol::Nonterminal(pair[0].nonterminal.to_string());
+            assert!(outer.alternatives.iter().any(|alt| alt == std::slice::from_ref(&inner)),
+                "{} does not fall through to {}", pair[1].nonterminal, pair[0].nonterminal);
+        }
+    }
+
+    #[test]
+    fn every_pair_of_binary_operators_groups_by_the_table() {
+        let mut checked = 0;
+        for (a_level, a) in binary() {
+            for (b_level, b) in binary() {
+                let src = format!("x {} y {} z", a.text, b.text);
+                let parsed = parse_expr(&src);
+                let a_first = a_level.precedence < b_level.precedence
+                    || (a_level.precedence == b_level.precedence && a_level.assoc == Assoc::Left);
+                if a_first && b_level.assoc == Assoc::Right {
+                    assert!(parsed.is_err(), "{} should not parse", src);
+                    continue;
+                }
+                let tree = parsed.unwrap_or_else(|e| panic!("{}: {}", src, e));
+                if a_first {
+                    assert!(is_use(&tree, b) && is_use(&tree.kids[0], a), "{} is not (x {} y) {} z: {:?}", src, a.text, b.text, tree);
+                } else {
+                    assert!(is_use(&tree, a) && is_use(&tree.kids[2], b), "{} is not x {} (y {} z): {:?}", src, a.text, b.text, tree);
+                }
+                checked += 1;
+            }
+        }
+        assert!(checked > 250, "{}", checked);
+    }
+
+    #[test]
+    fn prefix_operators_bind_tightest() {
+        for prefix in TABLE[0].operators {
+            for (_, b) in binary().filter(|(level, _)| level.assoc == Assoc::Left) {
+                let tree = parse_expr(&format!("{}x {} y", prefix.text, b.text)).unwrap();
+                assert!(is_use(&tree, b) && is_use(&tree.kids[0], prefix), "{}x {} y", prefix.text, b.text);
+                let tree = parse_expr(&format!("x {} {}y", b.text, prefix.text)).unwrap();
+                assert!(is_use(&tree, b) && is_use(&tree.kids[2], prefix), "x {} {}y", b.text, prefix.text);
+            }
+        }
+    }
+
+    #[test]
+    fn instanceof_groups_as_a_comparison() {
+        let tree = parse_expr("x + y instanceof T").unwrap();
+        assert_eq!((tree.sym.as_str(), tree.kids[0].sym.as_str()), ("InstanceOfExpr", "AddExpr"));
+        let tree = parse_expr("x < y instanceof T").unwrap();
+        assert_eq!((tree.sym.as_str(), tree.kids[0].sym.as_str()), ("InstanceOfExpr", "RelExpr"));
+        let tree = parse_expr("x instanceof T == b").unwrap();
+        assert_eq!((tree.sym.as_str(), tree.kids[0].sym.as_str()), ("EqExpr", "InstanceOfExpr"));
+        assert!(parse_expr("x instanceof T + y").is_err());
+    }
+
+    #[test]
+    fn the_chart_lists_every_level() {
+        let chart = chart();
+        assert_eq!(chart.lines().count(), TABLE.len() + 1);
+        assert!(chart.contains("\n2      left           * / %\n"), "{}", chart);
+        assert!(chart.ends_with("8      right          = += -= *= /= %=\n"), "{}", chart);
+        assert_eq!(operator("-").map(|(_, o)| o.node), Some("AddExpr"));
+        assert!(operator("!").is_none());
+    }
+}
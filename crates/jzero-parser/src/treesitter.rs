@@ -0,0 +1,431 @@
+//! The grammar as a tree-sitter grammar, for editor syntax support.
+//!
+//! [`productions`] reads the productions of `jzero.lalrpop` — the source the
+//! parser itself is generated from — as a plain context-free grammar, and
+//! [`tree_sitter_grammar`] writes them out as tree-sitter's `grammar.js`.
+//! The export is kept in `editors/tree-sitter-jzero/grammar.js`; a test
+//! fails when it is out of date, and `JZERO_BLESS=1 cargo test` rewrites it.
+//!
+//! The translation is one rule per nonterminal and needs no `conflicts` or
+//! precedence declarations: the grammar is already LR(1), with precedence
+//! spelled out as one nonterminal per level.  Tree-sitter cannot have rules
+//! that match nothing, so an empty alternative is left out and the places
+//! that use its nonterminal wrap it in `optional(..)` instead.  Nonterminals
+//! that build no node of their own, such as `Stmt`, become hidden rules.
+//!
+//! [`recognizes`] runs the extracted productions over a token stream, so
+//! the tests can check on the example corpus that they accept exactly what
+//! the generated parser accepts.
+//!
+//! Keywords are those of the highest language level: tree-sitter has no
+//! notion of levels, so at a lower level a word such as `try` is highlighted
+//! as a keyword although the compiler takes it for an identifier.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::bridge::{terminals, tok_variant};
+use crate::lexer::{Lexer, Tok};
+
+const GRAMMAR: &str = include_str!("jzero.lalrpop");
+
+/// A symbol on the right-hand side of a production.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Symbol {
+    /// A terminal, by its name in the grammar: `";"` is `;`.
+    Terminal(String),
+    Nonterminal(String),
+}
+
+/// One nonterminal and its alternatives.  An alternative written with an
+/// optional symbol `X?` appears twice, with and without `X`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Production {
+    pub name: String,
+    pub alternatives: Vec<Vec<Symbol>>,
+    /// Whether every one of its actions builds a tree node of its own,
+    /// rather than passing one on or collecting several.
+    pub builds_node: bool,
+}
+
+/// The productions of `jzero.lalrpop`, start symbol first.
+pub fn productions() -> Vec<Production> {
+    let mut found = Vec::new();
+    let mut lines = GRAMMAR.lines().skip_while(|l| !l.starts_with("extern {"));
+    while let Some(line) = lines.next() {
+        let Some(name) = rule_name(line) else { continue };
+        let body: Vec<&str> = lines.by_ref()
+            .take_while(|l| *l != "};")
+            .map(|l| l.split(" //").next().unwrap_or_default())
+            .filter(|l| !l.trim_start().starts_with("//"))
+            .collect();
+        let body = body.join("\n");
+        let mut alternatives = Vec::new();
+        let mut builds_node = true;
+        for (lhs, action) in split_alternatives(&body) {
+            builds_node &= action.contains("Tree::new(") || action.contains("Tree::leaf(");
+            alternatives.extend(expand_optionals(&parse_symbols(lhs)));
+        }
+        found.push(Production { name: name.to_string(), alternatives, builds_node });
+    }
+    found
+}
+
+/// The nonterminal a line such as `pub ClassDecl: Tree = {` opens.
+fn rule_name(line: &str) -> Option<&str> {
+    let line = line.strip_prefix("pub ").unwrap_or(line);
+    let (name, rest) = line.split_once(':')?;
+    let starts_upper = name.starts_with(|c: char| c.is_ascii_uppercase());
+    (starts_upper && name.chars().all(|c| c.is_ascii_alphanumeric()) && rest.trim_end().ends_with("= {"))
+        .then_some(name)
+}
+
+/// Split a rule body into its `symbols => action` alternatives.
+fn split_alternatives(body: &str) -> Vec<(&str, &str)> {
+    let mut found = Vec::new();
+    let mut rest = body;
+    while let Some(arrow) = rest.find("=>") {
+        let lhs = &rest[..arrow];
+        let action = &rest[arrow + 2..];
+        let end = action_end(action);
+        found.push((lhs, &action[..end]));
+        rest = action.get(end + 1..).unwrap_or_default();
+    }
+    found
+}
+
+/// Where the Rust action starting `text` ends: at its first `,` outside
+/// brackets, strings and character literals, or at the end of the body.
+fn action_end(text: &str) -> usize {
+    let bytes = text.as_bytes();
+    let mut depth = 0i32;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'(' | b'[' | b'{' => depth += 1,
+            b')' | b']' | b'}' => depth -= 1,
+            b',' if depth == 0 => return i,
+            b'"' => {
+                i += 1;
+                while i < bytes.len() && bytes[i] != b'"' {
+                    i += if bytes[i] == b'\\' { 2 } else { 1 };
+                }
+            }
+            // A character literal, not a lifetime such as `'input`.
+            b'\'' if bytes.get(i + 2) == Some(&b'\'') => i += 2,
+            b'\'' if bytes.get(i + 1) == Some(&b'\\') && bytes.get(i + 3) == Some(&b'\'') => i += 3,
+            _ => {}
+        }
+        i += 1;
+    }
+    bytes.len()
+}
+
+/// The symbols of the left of an alternative, such as
+/// `"if" "(" <cond:Expr> ")" <l:@L> <body:Block?>`; a symbol ending in
+/// `?` keeps its `?`.
+fn parse_symbols(lhs: &str) -> Vec<String> {
+    let mut symbols = Vec::new();
+    let mut rest = lhs.trim();
+    while !rest.is_empty() {
+        let (item, after) = if let Some(quoted) = rest.strip_prefix('"') {
+            let end = quoted.find('"').map_or(quoted.len(), |e| e + 1);
+            (&rest[..end + 1], &quoted[end..])
+        } else if let Some(inner) = rest.strip_prefix('<') {
+            let end = inner.find('>').unwrap_or(inner.len());
+            let binding = &inner[..end];
+            // `<name:Sym>` or `<mut name:Sym>` binds Sym; `<Sym>` passes it on.
+            let sym = binding.split_once(':').map_or(binding, |(_, s)| s);
+            (sym, inner.get(end + 1..).unwrap_or_default())
+        } else {
+            let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            (&rest[..end], &rest[end..])
+        };
+        if !item.starts_with('@') {
+            symbols.push(item.to_string());
+        }
+        rest = after.trim_start();
+    }
+    symbols
+}
+
+/// Every alternative `symbols` stands for, with each `X?` present or not.
+fn expand_optionals(symbols: &[String]) -> Vec<Vec<Symbol>> {
+    let mut alternatives = vec![Vec::new()];
+    for text in symbols {
+        let (text, optional) = match text.strip_suffix('?') {
+            Some(t) => (t, true),
+            None => (text.as_str(), false),
+        };
+        let symbol = match text.strip_prefix('"') {
+            Some(t) => Symbol::Terminal(t.trim_end_matches('"').to_string()),
+            None => Symbol::Nonterminal(text.to_string()),
+        };
+        let without = if optional { alternatives.clone() } else { Vec::new() };
+        for alt in &mut alternatives {
+            alt.push(symbol.clone());
+        }
+        alternatives.extend(without);
+    }
+    alternatives
+}
+
+/// The nonterminals that can match nothing.
+fn nullable(productions: &[Production]) -> HashSet<&str> {
+    let mut found = HashSet::new();
+    loop {
+        let before = found.len();
+        for p in productions {
+            let empty = p.alternatives.iter().any(|alt| alt.iter().all(|s| match s {
+                Symbol::Nonterminal(n) => found.contains(n.as_str()),
+                Symbol::Terminal(_) => false,
+            }));
+            if empty {
+                found.insert(p.name.as_str());
+            }
+        }
+        if found.len() == before {
+            return found;
+        }
+    }
+}
+
+// ─── grammar.js ──────────────────────────────────────────────────────────────
+
+/// The terminals that stand for a class of words, and the tree-sitter rule
+/// for each; they follow the lexer's patterns.
+const TOKEN_RULES: &[(&str, &str)] = &[
+    ("identifier", r"/[\p{XID_Start}_]\p{XID_Continue}*/"),
+    ("intlit",     r"/[0-9]+/"),
+    ("longlit",    r"/[0-9]+[lL]/"),
+    ("doublelit",  r"/[0-9]+\.[0-9]*([eE][+-]?[0-9]+)?[dD]?|[0-9]*\.[0-9]+([eE][+-]?[0-9]+)?[dD]?|[0-9]+[eE][+-]?[0-9]+[dD]?|[0-9]+[dD]/"),
+    ("floatlit",   r"/([0-9]+\.[0-9]*([eE][+-]?[0-9]+)?|[0-9]*\.[0-9]+([eE][+-]?[0-9]+)?|[0-9]+([eE][+-]?[0-9]+)?)[fF]/"),
+    ("stringlit",  r#"token(choice(/"([^"\\\n]|\\[^\n])*"/, /"""[ \t\f]*\r?\n([^"\\]|\\(.|\n)|"([^"\\]|\\(.|\n))|""([^"\\]|\\(.|\n)))*"""/))"#),
+    ("charlit",    r"/'([^'\\\n]|\\[^\n])'/"),
+    ("boollit",    r"choice('true', 'false')"),
+];
+
+const COMMENT_RULE: &str = r"token(choice(seq('//', /[^\n]*/), seq('/*', /[^*]*\*+([^/*][^*]*\*+)*/, '/')))";
+
+/// `grammar.js` for tree-sitter, generated from [`productions`].
+///
+/// # Panics
+/// Panics if a nonterminal of the grammar has no production, or if an
+/// alternative could match nothing once its nullable symbols are
+/// optional — tree-sitter cannot generate either.
+pub fn tree_sitter_grammar() -> String {
+    let productions = productions();
+    let nullable = nullable(&productions);
+    let hidden: HashMap<&str, bool> = productions.iter()
+        .map(|p| (p.name.as_str(), !p.builds_node))
+        .collect();
+    let reference = |name: &str| {
+        let hide = *hidden.get(name).unwrap_or_else(|| panic!("{} has no production", name));
+        let rule = format!("$.{}{}", if hide { "_" } else { "" }, snake_case(name));
+        if nullable.contains(name) { format!("optional({})", rule) } else { rule }
+    };
+
+    let mut used_tokens = Vec::new();
+    let mut rules = Vec::new();
+    for (i, p) in productions.iter().enumerate() {
+        let mut choices = Vec::new();
+        for alt in &p.alternatives {
+            let symbols: Vec<&Symbol> = alt.iter().filter(|s| **s != Symbol::Terminal("EOF".into())).collect();
+            if symbols.is_empty() {
+                continue;
+            }
+            assert!(
+                symbols.iter().any(|s| !matches!(s, Symbol::Nonterminal(n) if nullable.contains(n.as_str()))),
+                "an alternative of {} matches nothing", p.name,
+            );
+            let items: Vec<String> = symbols.iter().map(|s| match s {
+                Symbol::Nonterminal(n) => reference(n),
+                Symbol::Terminal(t) if TOKEN_RULES.iter().any(|(name, _)| name == t) => {
+                    if !used_tokens.contains(t) { used_tokens.push(t.clone()); }
+                    format!("$.{}", t)
+                }
+                Symbol::Terminal(t) => format!("'{}'", t.replace('\\', "\\\\").replace('\'', "\\'")),
+            }).collect();
+            choices.push(if items.len() == 1 { items[0].clone() } else { format!("seq({})", items.join(", ")) });
+        }
+        let body = if choices.len() == 1 {
+            choices.remove(0)
+        } else {
+            format!("choice(\n      {},\n    )", choices.join(",\n      "))
+        };
+        // The first rule is the root, which tree-sitter does not let hide.
+        let name = match (i, p.builds_node) {
+            (0, _) => "source_file".to_string(),
+            (_, true) => snake_case(&p.name),
+            (_, false) => format!("_{}", snake_case(&p.name)),
+        };
+        rules.push(format!("    {}: $ => {},", name, body));
+    }
+    for (name, rule) in TOKEN_RULES {
+        if used_tokens.iter().any(|t| t == name) {
+            rules.push(format!("    {}: $ => {},", name, rule));
+        }
+    }
+    rules.push(format!("    comment: $ => {},", COMMENT_RULE));
+
+    format!(
+        "// Generated from crates/jzero-parser/src/jzero.lalrpop by\n\
+         // jzero_parser::treesitter::tree_sitter_grammar; do not edit.\n\
+         module.exports = grammar({{\n  \
+         name: 'jzero',\n\n  \
+         extras: $ => [/\\s/, $.comment],\n\n  \
+         word: $ => $.identifier,\n\n  \
+         rules: {{\n{}\n  }},\n}});\n",
+        rules.join("\n"),
+    )
+}
+
+/// `ClassBodyDecl` → `class_body_decl`.
+fn snake_case(name: &str) -> String {
+    let mut out = String::new();
+    for (i, c) in name.chars().enumerate() {
+        if c.is_ascii_uppercase() && i > 0 {
+            out.push('_');
+        }
+        out.push(c.to_ascii_lowercase());
+    }
+    out
+}
+
+// ─── Recognizer ──────────────────────────────────────────────────────────────
+
+/// The grammar terminal each token of `input` is, ending with `EOF`;
+/// `None` if the input has a lexical error.
+pub fn terminal_names(input: &str) -> Option<Vec<String>> {
+    let names: HashMap<String, String> = terminals().into_iter().map(|t| (t.variant, t.name)).collect();
+    Lexer::new(input)
+        .map(|r| r.ok().map(|(_, tok, _): (usize, Tok<'_>, usize)| names[tok_variant(&tok)].clone()))
+        .collect()
+}
+
+/// Whether `productions` derive `tokens` (terminal names, as
+/// [`terminal_names`] gives them) from their start symbol.
+///
+/// An Earley recognizer: it needs nothing of the grammar but that it be
+/// context-free, so it checks the productions independently of the
+/// LR(1) parser generated from the same source.
+pub fn recognizes(productions: &[Production], tokens: &[String]) -> bool {
+    type Item = (usize, usize, usize, usize); // production, alternative, dot, origin
+
+    let index: HashMap<&str, usize> = productions.iter().enumerate().map(|(i, p)| (p.name.as_str(), i)).collect();
+    let nullable = nullable(productions);
+    let symbol_at = |&(p, a, dot, _): &Item| productions[p].alternatives[a].get(dot);
+
+    let mut sets: Vec<Vec<Item>> = vec![Vec::new(); tokens.len() + 1];
+    let mut seen: Vec<HashSet<Item>> = vec![HashSet::new(); tokens.len() + 1];
+    let start = (0..productions[0].alternatives.len()).map(|a| (0, a, 0, 0));
+    for item in start {
+        if seen[0].insert(item) { sets[0].push(item); }
+    }
+
+    for k in 0..=tokens.len() {
+        let mut i = 0;
+        while i < sets[k].len() {
+            let item = sets[k][i];
+            i += 1;
+            let mut add = |set: usize, item: Item, sets: &mut Vec<Vec<Item>>| {
+                if seen[set].insert(item) { sets[set].push(item); }
+            };
+            match symbol_at(&item) {
+                Some(Symbol::Nonterminal(n)) => {
+                    let Some(&p) = index.get(n.as_str()) else { continue };
+                    for a in 0..productions[p].alternatives.len() {
+                        add(k, (p, a, 0, k), &mut sets);
+                    }
+                    // A nullable nonterminal may also be passed over.
+                    if nullable.contains(n.as_str()) {
+                        add(k, (item.0, item.1, item.2 + 1, item.3), &mut sets);
+                    }
+                }
+                Some(Symbol::Terminal(t)) => {
+                    if tokens.get(k) == Some(t) {
+                        add(k + 1, (item.0, item.1, item.2 + 1, item.3), &mut sets);
+                    }
+                }
+                None => {
+                    let name = productions[item.0].name.as_str();
+                    let waiting: Vec<Item> = sets[item.3].iter().copied()
+                        .filter(|w| symbol_at(w) == Some(&Symbol::Nonterminal(name.to_string())))
+                        .collect();
+                    for (p, a, dot, origin) in waiting {
+                        add(k, (p, a, dot + 1, origin), &mut sets);
+                    }
+                }
+            }
+        }
+    }
+    sets[tokens.len()].iter().any(|&(p, a, dot, origin)| {
+        p == 0 && origin == 0 && dot == productions[0].alternatives[a].len()
+    })
+}
+
+// ─── Tests ───────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jzero::ClassDeclParser;
+    use crate::lexer::LexicalError;
+    use crate::loc::LineIndex;
+
+    fn examples() -> Vec<String> {
+        let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/../../tests/examples");
+        let mut paths: Vec<_> = std::fs::read_dir(dir).unwrap().map(|e| e.unwrap().path()).collect();
+        paths.sort();
+        paths.iter().map(|p| std::fs::read_to_string(p).unwrap()).collect()
+    }
+
+    #[test]
+    fn productions_are_read_from_the_grammar() {
+        let productions = productions();
+        assert_eq!(productions[0].name, "ClassDecl");
+        let try_stmt = productions.iter().find(|p| p.name == "TryStmt").unwrap();
+        // `CatchClauses?` gives the finally form with and without catches.
+        assert_eq!(try_stmt.alternatives.len(), 3);
+        let access = productions.iter().find(|p| p.name == "AccessOpt").unwrap();
+        assert!(access.alternatives.contains(&vec![]));
+        assert!(!productions.iter().find(|p| p.name == "Stmt").unwrap().builds_node);
+    }
+
+    /// Each example, and each example with one token left out, is accepted
+    /// by the extracted productions exactly when the parser accepts it.
+    #[test]
+    fn productions_accept_what_the_parser_accepts() {
+        let productions = productions();
+        let mut rejected = 0;
+        for src in examples() {
+            let tokens: Vec<(usize, Tok<'_>, usize)> = Lexer::new(&src).map(Result::unwrap).collect();
+            let names = terminal_names(&src).unwrap();
+            assert!(recognizes(&productions, &names), "{}", src);
+            let lines = LineIndex::new(&src);
+            for skip in 0..tokens.len() - 1 {
+                let kept = || tokens.iter().enumerate().filter(|(i, _)| *i != skip).map(|(_, t)| *t);
+                let parsed = ClassDeclParser::new()
+                    .parse(&lines, kept().map(Ok::<_, LexicalError>))
+                    .is_ok();
+                let mut names = names.clone();
+                names.remove(skip);
+                assert_eq!(recognizes(&productions, &names), parsed, "without token {} of\n{}", skip, src);
+                rejected += usize::from(!parsed);
+            }
+        }
+        assert!(rejected > 0);
+    }
+
+    #[test]
+    fn grammar_js_is_up_to_date() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/../../editors/tree-sitter-jzero/grammar.js");
+        let generated = tree_sitter_grammar();
+        if std::env::var_os("JZERO_BLESS").is_some() {
+            std::fs::write(path, &generated).unwrap();
+        }
+        let kept = std::fs::read_to_string(path).unwrap_or_default();
+        assert!(kept == generated, "{} is out of date; rerun with JZERO_BLESS=1", path);
+        assert!(generated.contains("    source_file: $ => choice(\n      $.class_def,\n      $.interface_def,\n      seq($._import_decls, $._type_def),\n    ),"), "{}", generated);
+        assert!(generated.contains("    while_stmt: $ => seq('while', '(', $._expr, ')', $._loop_body),"), "{}", generated);
+    }
+}
@@ -0,0 +1,140 @@
+//! Access control — `private` members are usable only inside their class.
+//!
+//! Runs after `check_type`, when the base of every `base.member` has a
+//! type.  For each field access and qualified method call whose base is a
+//! class, the member is looked up in that class's scope; if it is private
+//! and the access is not inside the same class, an
+//! [`AccessDenied`](SemanticError::AccessDenied) error is reported with both
+//! the access line and the declaration line.
+
+use jzero_ast::tree::Tree;
+use jzero_symtab::{SymTabEntry, TypeInfo, entry::Access};
+
+use crate::checktype::find_token;
+use crate::error::SemanticError;
+
+/// Check every member access in `tree`.
+pub fn check_access(tree: &Tree, errors: &mut Vec<SemanticError>) {
+    walk(tree, "", errors);
+}
+
+fn walk(tree: &Tree, current_class: &str, errors: &mut Vec<SemanticError>) {
+    let current_class = if tree.sym == "ClassDecl" {
+        tree.kids.first()
+            .and_then(|k| k.tok.as_ref())
+            .map(|t| t.text.as_str())
+            .unwrap_or_default()
+    } else {
+        current_class
+    };
+
+    let qualified = match tree.sym.as_str() {
+        "FieldAccess" => true,
+        "MethodCall"  => tree.rule >= 2,
+        _ => false,
+    };
+    if qualified
+        && let Some((class, member)) = resolve_member(tree)
+        && member.access == Access::Private
+        && class != current_class
+    {
+        let lineno = tree.kids.get(1)
+            .and_then(|k| k.tok.as_ref())
+            .map(|t| t.lineno)
+            .or_else(|| find_token(tree).and_then(|t| t.tok.as_ref()).map(|t| t.lineno))
+            .unwrap_or(0);
+        errors.push(SemanticError::AccessDenied {
+            member: member.sym.clone(),
+            class,
+            lineno,
+            decl_lineno: member.lineno,
+        });
+    }
+
+    for kid in &tree.kids {
+        walk(kid, current_class, errors);
+    }
+}
+
+/// For `base.member`, the class of `base` and the member's entry.
+pub(crate) fn resolve_member(tree: &Tree) -> Option<(String, SymTabEntry)> {
+    let TypeInfo::Class(ct) = tree.kids.first()?.typ.as_ref()? else { return None };
+    let name = &tree.kids.get(1)?.tok.as_ref()?.text;
+
+    // Variables of class type carry only the class name; find its scope.
+    let st = match &ct.st {
+        Some(st) => st.clone(),
+        None => tree.stab.as_ref()?.borrow().lookup(&ct.name)?.st?,
+    };
+    let member = st.borrow().lookup_local(name).cloned()?;
+    Some((ct.name.clone(), member))
+}
+
+// ─── Tests ───────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use jzero_parser::parse_tree;
+    use jzero_symtab::{SymTab, build_predefined};
+
+    use crate::{analyze, analyze_in};
+
+    const POINT: &str = r#"
+public class Point {
+    private int x;
+    public int y;
+    private static int secret() { return 42; }
+    public static int peek() { return Point.x + secret(); }
+}
+"#;
+
+    /// Analyze `POINT` and then `src` in one global scope, as one project.
+    fn errors_with_point(src: &str) -> Vec<String> {
+        let global = SymTab::new("global", None).into_rc();
+        build_predefined(&global);
+        let mut point = parse_tree(POINT).expect("parse failed");
+        let first = analyze_in(&mut point, Rc::clone(&global));
+        assert!(first.errors.is_empty(), "{:?}", first.errors);
+
+        let mut tree = parse_tree(src).expect("parse failed");
+        analyze_in(&mut tree, global).errors.iter().map(|e| e.to_string()).collect()
+    }
+
+    #[test]
+    fn private_field_from_other_class_is_denied() {
+        let errs = errors_with_point(r#"
+public class T {
+    public static void main(String argv[]) {
+        int a;
+        a = Point.x;
+    }
+}
+"#);
+        assert_eq!(errs, vec!["line 5: 'x' has private access in 'Point' (declared on line 3)"]);
+    }
+
+    #[test]
+    fn private_method_from_other_class_is_denied() {
+        let errs = errors_with_point(r#"
+public class T {
+    public static void main(String argv[]) {
+        Point p;
+        int a;
+        a = Point.secret();
+        a = p.y;
+    }
+}
+"#);
+        assert_eq!(errs.len(), 1, "{:?}", errs);
+        assert!(errs[0].contains("'secret' has private access"), "{:?}", errs);
+        assert!(errs[0].contains("line 6:") && errs[0].contains("declared on line 5"));
+    }
+
+    #[test]
+    fn private_members_are_usable_inside_their_class() {
+        let mut tree = parse_tree(POINT).expect("parse failed");
+        assert!(analyze(&mut tree).errors.is_empty());
+    }
+}
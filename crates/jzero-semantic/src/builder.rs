@@ -5,7 +5,7 @@ use std::cell::RefCell;
 use std::rc::Rc;
 
 use jzero_ast::tree::Tree;
-use jzero_symtab::{SymTab, SymTabEntry, TypeInfo, entry::SymbolKind};
+use jzero_symtab::{SymTab, SymTabEntry, TypeInfo, entry::{Access, SymbolKind}};
 
 use crate::calctype::{calc_type, assign_type};
 use crate::error::SemanticError;
@@ -17,19 +17,36 @@ pub fn build_symtabs(
     current_scope: Rc<RefCell<SymTab>>,
     errors: &mut Vec<SemanticError>,
 ) {
+    // A placeholder for a left-out part declares and uses nothing.
+    if tree.is_empty_placeholder() { return; }
     tree.set_stab(Rc::clone(&current_scope));
 
     match tree.sym.as_str() {
+        "ImportDecl"   => walk_import(tree, current_scope, errors),
         "ClassDecl"    => walk_class(tree, current_scope, errors),
+        "InterfaceDecl" => walk_interface(tree, current_scope, errors),
         "MethodDecl"   => walk_method(tree, current_scope, errors),
         "FieldDecl"    => walk_field_decl(tree, current_scope, errors),
         "LocalVarDecl" => walk_local_var_decl(tree, current_scope, errors),
         "FormalParm"   => walk_formal_parm(tree, current_scope, errors),
         "Block"        => walk_block(tree, current_scope, errors),
+        "CatchClause"  => walk_catch(tree, current_scope, errors),
         _              => walk_children(tree, current_scope, errors),
     }
 }
 
+/// Whether `tree` has the kids the grammar gives it; if not, it is
+/// reported, rather than skipped without a word.
+fn well_formed(tree: &Tree, errors: &mut Vec<SemanticError>) -> bool {
+    match tree.validate_node() {
+        Ok(()) => true,
+        Err(e) => {
+            errors.push(SemanticError::MalformedTree { problem: e.problem, lineno: e.lineno });
+            false
+        }
+    }
+}
+
 // ─── Generic child walker ─────────────────────────────────────────────────────
 
 fn walk_children(
@@ -42,6 +59,15 @@ fn walk_children(
     }
 }
 
+/// The error for declaring `name` where `existing` already is.
+fn redeclared(existing: &SymTabEntry, name: &str, lineno: usize) -> SemanticError {
+    if existing.origin.is_predefined() {
+        SemanticError::PredefinedConflict { name: name.to_string(), kind: existing.kind.to_string(), lineno }
+    } else {
+        SemanticError::RedeclaredVariable { name: name.to_string(), lineno }
+    }
+}
+
 // ─── ClassDecl ────────────────────────────────────────────────────────────────
 
 fn walk_class(
@@ -58,23 +84,39 @@ fn walk_class(
         .map(|t| t.lineno)
         .unwrap_or(0);
 
-    let class_scope = SymTab::new("class", Some(Rc::clone(&global))).into_rc();
+    // A subclass scope's parent is its superclass's, so lookups that miss
+    // in the class go on to inherited members and then to the globals.
+    // The superclass is resolved before the class is declared: a class
+    // cannot extend itself.
+    let parent = match tree.rule {
+        1 | 3 => superclass_scope(&tree.kids[1], &global, errors),
+        _ => None,
+    };
+    let class_scope = SymTab::new("class", Some(parent.unwrap_or_else(|| Rc::clone(&global)))).into_rc();
 
-    let class_entry = SymTabEntry::with_scope(
+    let mut class_entry = SymTabEntry::with_scope(
         &class_name,
         SymbolKind::Class,
         Rc::clone(&global),
         false,
         Rc::clone(&class_scope),
     );
-    if let Err(_) = global.borrow_mut().insert(class_entry) {
-        errors.push(SemanticError::RedeclaredVariable { name: class_name.clone(), lineno });
+    class_entry.lineno = lineno;
+    if let Err(existing) = global.borrow_mut().insert(class_entry) {
+        errors.push(redeclared(&existing, &class_name, lineno));
     }
 
     tree.set_stab(Rc::clone(&class_scope));
 
+    // The members follow the name, the superclass of a subclass and the
+    // interfaces it implements, which are looked up globally.
+    let members = class_members_start(tree);
+    for kid in &mut tree.kids[1..members] {
+        set_stab_all(kid, &global);
+    }
+
     // First pass: register fields + method signatures
-    for kid in &tree.kids[1..] {
+    for kid in &tree.kids[members..] {
         match kid.sym.as_str() {
             "FieldDecl"  => register_field(kid, Rc::clone(&class_scope), errors),
             "MethodDecl" => register_method_signature(kid, Rc::clone(&class_scope), errors),
@@ -83,13 +125,112 @@ fn walk_class(
     }
 
     // Second pass: walk method bodies
-    for kid in &mut tree.kids[1..] {
+    for kid in &mut tree.kids[members..] {
         if kid.sym == "MethodDecl" {
             walk_method(kid, Rc::clone(&class_scope), errors);
         } else {
             build_symtabs(kid, Rc::clone(&class_scope), errors);
         }
     }
+
+    // `this` is an object of the class.
+    let this_typ = TypeInfo::class_with_symtab(&class_name, Rc::clone(&class_scope));
+    for kid in &mut tree.kids[members..] {
+        set_this_type(kid, &this_typ);
+    }
+}
+
+fn set_this_type(tree: &mut Tree, typ: &TypeInfo) {
+    if tree.tok.as_ref().is_some_and(|t| t.category == "THIS") {
+        tree.set_typ(typ.clone());
+    }
+    for kid in &mut tree.kids {
+        set_this_type(kid, typ);
+    }
+}
+
+/// The index of the first member of a ClassDecl.
+pub(crate) fn class_members_start(tree: &Tree) -> usize {
+    match tree.rule {
+        0 => 1,
+        1 | 2 => 2,
+        _ => 3,
+    }
+}
+
+fn set_stab_all(tree: &mut Tree, scope: &Rc<RefCell<SymTab>>) {
+    tree.set_stab(Rc::clone(scope));
+    for kid in &mut tree.kids {
+        set_stab_all(kid, scope);
+    }
+}
+
+// ─── ImportDecl ───────────────────────────────────────────────────────────────
+
+/// Declare the known classes `import java.util.Scanner;` or
+/// `import java.util.*;` names (see [`jzero_symtab::KNOWN_CLASSES`]).  The
+/// imports come before the class, so its members see them.
+fn walk_import(
+    tree: &mut Tree,
+    global: Rc<RefCell<SymTab>>,
+    errors: &mut Vec<SemanticError>,
+) {
+    set_stab_all(tree, &global);
+    let names: Vec<&str> = tree.kids.iter().filter_map(|k| k.tok.as_ref()).map(|t| t.text.as_str()).collect();
+    let path = names.join(".");
+    let on_demand = tree.rule == 1;
+    if jzero_symtab::import(&global, &path, on_demand).is_empty() {
+        let lineno = tree.kids.first().and_then(|k| k.tok.as_ref()).map_or(0, |t| t.lineno);
+        let name = if on_demand { format!("{}.*", path) } else { path };
+        errors.push(SemanticError::UnknownImport { name, lineno });
+    }
+}
+
+// ─── InterfaceDecl ────────────────────────────────────────────────────────────
+
+/// Declare an interface and the signatures of its methods.  Each method
+/// gets a scope of its own, holding its parameters.
+fn walk_interface(
+    tree: &mut Tree,
+    global: Rc<RefCell<SymTab>>,
+    errors: &mut Vec<SemanticError>,
+) {
+    let Some(tok) = tree.kids.first().and_then(|n| n.tok.clone()) else { return };
+    let scope = SymTab::new("interface", Some(Rc::clone(&global))).into_rc();
+    let mut entry = SymTabEntry::with_scope(&tok.text, SymbolKind::Interface, Rc::clone(&global), false, Rc::clone(&scope));
+    entry.lineno = tok.lineno;
+    if let Err(existing) = global.borrow_mut().insert(entry) {
+        errors.push(redeclared(&existing, &tok.text, tok.lineno));
+    }
+    tree.set_stab(Rc::clone(&scope));
+    tree.kids[0].set_stab(Rc::clone(&scope));
+
+    for method in &mut tree.kids[1..] {
+        register_method_signature(method, Rc::clone(&scope), errors);
+        let name = method_name(method);
+        let method_scope = scope.borrow().lookup_local(&name).and_then(|e| e.st.clone())
+            .unwrap_or_else(|| SymTab::new("method", Some(Rc::clone(&scope))).into_rc());
+        method.set_stab(Rc::clone(&method_scope));
+        for kid in &mut method.kids {
+            build_symtabs(kid, Rc::clone(&method_scope), errors);
+        }
+    }
+}
+
+/// The scope of the class `base` names, which must already be declared.
+fn superclass_scope(
+    base: &Tree,
+    global: &Rc<RefCell<SymTab>>,
+    errors: &mut Vec<SemanticError>,
+) -> Option<Rc<RefCell<SymTab>>> {
+    let tok = base.tok.as_ref()?;
+    let scope = global.borrow().lookup(&tok.text)
+        .filter(|e| e.kind == SymbolKind::Class)
+        .and_then(|e| e.st.clone());
+    if scope.is_none() {
+        errors.push(SemanticError::UndeclaredClass { name: tok.text.clone(), lineno: tok.lineno });
+    }
+    scope
 }
 
 // ─── FieldDecl registration (first pass) ─────────────────────────────────────
@@ -99,7 +240,7 @@ fn register_field(
     class_scope: Rc<RefCell<SymTab>>,
     errors: &mut Vec<SemanticError>,
 ) {
-    if tree.kids.len() < 2 { return; }
+    if !well_formed(tree, errors) { return; }
 
     // Collect all VarDeclarator kids (kids[1..]) — there may be multiple
     // e.g. `int x, y;`
@@ -111,15 +252,23 @@ fn register_field(
     for decl in &tree.kids[1..] {
         if decl.sym != "VarDeclarator" { continue; }
         let (name, lineno) = declarator_name_and_line(decl);
+        // An initialized declarator holds the declarator of its name.
+        let decl = if decl.rule == 2 { &decl.kids[0] } else { decl };
         let typ = if decl.rule == 1 {
             base_typ.as_ref().map(|t| TypeInfo::array(t.clone()))
         } else {
             base_typ.clone()
         };
-        let mut entry = SymTabEntry::new(&name, SymbolKind::Field, Rc::clone(&class_scope), false);
+        // `static final` fields are named constants; `final` ones are
+        // constant too, in each object.
+        let is_const = (3..6).contains(&tree.rule) || tree.rule >= 9;
+        let mut entry = SymTabEntry::new(&name, SymbolKind::Field, Rc::clone(&class_scope), is_const);
         if let Some(t) = typ { entry.set_typ(t); }
-        if let Err(_) = class_scope.borrow_mut().insert(entry) {
-            errors.push(SemanticError::RedeclaredVariable { name, lineno });
+        entry.access = Access::from_rule(tree.rule);
+        entry.is_static = (3..9).contains(&tree.rule);
+        entry.lineno = lineno;
+        if let Err(existing) = class_scope.borrow_mut().insert(entry) {
+            errors.push(redeclared(&existing, &name, lineno));
         }
     }
 }
@@ -132,6 +281,8 @@ fn type_node_to_typeinfo(node: &Tree) -> Option<TypeInfo> {
             "DOUBLE"     => Some(TypeInfo::double()),
             "BOOL"       => Some(TypeInfo::boolean()),
             "STRING"     => Some(TypeInfo::string()),
+            "CHAR"       => Some(TypeInfo::char()),
+            "LONG"       => Some(TypeInfo::long()),
             "VOID"       => Some(TypeInfo::void()),
             "IDENTIFIER" => Some(TypeInfo::class(&tok.text)),
             _ => None,
@@ -169,9 +320,16 @@ fn register_method_signature(
         Rc::clone(&method_scope),
     );
     if let Some(t) = method_typ { entry.set_typ(t); }
+    // A class's MethodHeader adds 3 to its rule for an instance method.
+    // An interface's methods are abstract instead.
+    if let Some(header) = tree.kids.first() {
+        entry.access = Access::from_rule(header.rule);
+        entry.is_static = tree.sym == "MethodDecl" && header.rule < 3;
+    }
+    entry.lineno = lineno;
 
-    if let Err(_) = class_scope.borrow_mut().insert(entry) {
-        errors.push(SemanticError::RedeclaredVariable { name, lineno });
+    if let Err(existing) = class_scope.borrow_mut().insert(entry) {
+        errors.push(redeclared(&existing, &name, lineno));
     }
 }
 
@@ -259,7 +417,7 @@ fn walk_formal_parm(
     scope: Rc<RefCell<SymTab>>,
     errors: &mut Vec<SemanticError>,
 ) {
-    if tree.kids.len() < 2 { return; }
+    if !well_formed(tree, errors) { return; }
 
     let typ = calc_type(&mut tree.kids[0], errors);
     let final_typ = typ.and_then(|t| assign_type(&mut tree.kids[1], t, errors));
@@ -269,8 +427,9 @@ fn walk_formal_parm(
 
     let mut entry = SymTabEntry::new(&name, SymbolKind::Param, Rc::clone(&scope), false);
     if let Some(t) = final_typ { entry.set_typ(t); }
-    if let Err(_) = scope.borrow_mut().insert(entry) {
-        errors.push(SemanticError::RedeclaredVariable { name, lineno });
+    entry.lineno = lineno;
+    if let Err(existing) = scope.borrow_mut().insert(entry) {
+        errors.push(redeclared(&existing, &name, lineno));
     }
 
     walk_children(tree, scope, errors);
@@ -283,7 +442,14 @@ fn walk_field_decl(
     scope: Rc<RefCell<SymTab>>,
     errors: &mut Vec<SemanticError>,
 ) {
-    // Types already registered in first pass; walk children for initialiser exprs
+    // Types already registered in first pass.  Stamp them on the
+    // declarators too, which checks that only arrays have an initializer
+    // list, then walk children for initialiser exprs.
+    if let Some(typ) = tree.kids.first_mut().and_then(|t| calc_type(t, errors)) {
+        for decl in tree.kids[1..].iter_mut().filter(|k| k.sym == "VarDeclarator") {
+            assign_type(decl, typ.clone(), errors);
+        }
+    }
     walk_children(tree, scope, errors);
 }
 
@@ -294,18 +460,20 @@ fn walk_local_var_decl(
     scope: Rc<RefCell<SymTab>>,
     errors: &mut Vec<SemanticError>,
 ) {
-    if tree.kids.len() < 2 { return; }
+    if !well_formed(tree, errors) { return; }
 
+    // `int x, y[];` declares each name with its own type.
     let typ = calc_type(&mut tree.kids[0], errors);
-    let final_typ = typ.and_then(|t| assign_type(&mut tree.kids[1], t, errors));
-
-    let var_decl = &tree.kids[1];
-    let (name, lineno) = declarator_name_and_line(var_decl);
-
-    let mut entry = SymTabEntry::new(&name, SymbolKind::Local, Rc::clone(&scope), false);
-    if let Some(t) = final_typ { entry.set_typ(t); }
-    if let Err(_) = scope.borrow_mut().insert(entry) {
-        errors.push(SemanticError::RedeclaredVariable { name, lineno });
+    for var_decl in tree.kids[1..].iter_mut().filter(|k| k.sym == "VarDeclarator") {
+        let final_typ = typ.clone().and_then(|t| assign_type(var_decl, t, errors));
+        let (name, lineno) = declarator_name_and_line(var_decl);
+
+        let mut entry = SymTabEntry::new(&name, SymbolKind::Local, Rc::clone(&scope), tree.rule == 3);
+        if let Some(t) = final_typ { entry.set_typ(t); }
+        entry.lineno = lineno;
+        if let Err(existing) = scope.borrow_mut().insert(entry) {
+            errors.push(redeclared(&existing, &name, lineno));
+        }
     }
 
     walk_children(tree, scope, errors);
@@ -321,6 +489,29 @@ fn walk_block(
     walk_children(tree, scope, errors);
 }
 
+// ─── CatchClause ──────────────────────────────────────────────────────────────
+
+/// A catch clause gets a scope of its own for its parameter, so that two
+/// clauses of a method may both call theirs `e`.  As in Java, the
+/// parameter may not shadow a local variable or parameter of the method.
+fn walk_catch(
+    tree: &mut Tree,
+    scope: Rc<RefCell<SymTab>>,
+    errors: &mut Vec<SemanticError>,
+) {
+    if let Some(declarator) = tree.kids.first().and_then(|parm| parm.kids.get(1)) {
+        let (name, lineno) = ident_name_and_line(declarator);
+        if let Some(existing) = scope.borrow().lookup(&name)
+            && matches!(existing.kind, SymbolKind::Local | SymbolKind::Param)
+        {
+            errors.push(redeclared(&existing, &name, lineno));
+        }
+    }
+    let catch_scope = SymTab::new("catch", Some(scope)).into_rc();
+    tree.set_stab(Rc::clone(&catch_scope));
+    walk_children(tree, catch_scope, errors);
+}
+
 // ─── Identifier helpers ───────────────────────────────────────────────────────
 
 fn ident_name_and_line(node: &Tree) -> (String, usize) {
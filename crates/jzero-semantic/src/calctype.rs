@@ -44,17 +44,17 @@ pub fn mksig(parms: &[Tree]) -> Vec<Parameter> {
 
 /// Walk a VarDeclarator subtree to find the IDENTIFIER leaf text.
 fn extract_identifier_name(tree: &Tree) -> Option<String> {
+    find_identifier(tree).and_then(|leaf| leaf.tok.as_ref()).map(|tok| tok.text.clone())
+}
+
+/// The first IDENTIFIER leaf of `tree`: the name of a VarDeclarator.
+fn find_identifier(tree: &Tree) -> Option<&Tree> {
     if let Some(tok) = &tree.tok {
         if tok.category == "IDENTIFIER" {
-            return Some(tok.text.clone());
-        }
-    }
-    for kid in &tree.kids {
-        if let Some(name) = extract_identifier_name(kid) {
-            return Some(name);
+            return Some(tree);
         }
     }
-    None
+    tree.kids.iter().find_map(find_identifier)
 }
 
 // ─── calc_type ───────────────────────────────────────────────────────────────
@@ -103,6 +103,8 @@ pub fn calc_type(tree: &mut Tree, errors: &mut Vec<SemanticError>) -> Option<Typ
                 "DOUBLE"     => Some(TypeInfo::double()),
                 "BOOL"       => Some(TypeInfo::boolean()),
                 "STRING"     => Some(TypeInfo::string()),
+                "CHAR"       => Some(TypeInfo::char()),
+                "LONG"       => Some(TypeInfo::long()),
                 "VOID"       => Some(TypeInfo::void()),
                 "IDENTIFIER" => Some(TypeInfo::class(&tok.text)),
                 _            => tree.typ.clone(),
@@ -139,6 +141,26 @@ pub fn assign_type(
             None
         }
 
+        // ── Initialized VarDeclarator (rule 2): int x = 5, y[] = {1, 2} ──
+        //
+        // The type is the inner declarator's; only an array may have an
+        // initializer list.
+        "VarDeclarator" if tree.rule == 2 => {
+            let typ = assign_type(tree.kids.first_mut()?, t, errors)?;
+            let is_list = tree.kids.get(1).is_some_and(|init| init.sym == "ArrayInit");
+            if is_list && typ.basetype() != "array" {
+                let leaf = find_identifier(tree).and_then(|l| l.tok.as_ref());
+                errors.push(SemanticError::TypeAssignmentError {
+                    msg: format!(
+                        "'{}' is declared {}, not as an array, so it cannot have an array initializer",
+                        leaf.map_or("", |t| t.text.as_str()), typ.display(),
+                    ),
+                    lineno: leaf.map_or(0, |t| t.lineno),
+                });
+            }
+            Some(typ)
+        }
+
         // ── Plain VarDeclarator (rule 0) ─────────────────────────────────
         "VarDeclarator" => {
             if let Some(kid) = tree.kids.first_mut() {
@@ -256,6 +278,23 @@ mod tests {
         assert_eq!(result.unwrap().to_string(), "String[]");
     }
 
+    #[test]
+    fn test_assign_type_initialized_declarator() {
+        let ident = Tree::leaf("IDENTIFIER", "a", 3);
+        let array = Tree::new("VarDeclarator", 1, vec![Tree::new("VarDeclarator", 0, vec![ident.clone()])]);
+        let init = Tree::new("ArrayInit", 0, vec![Tree::leaf("INTLIT", "1", 3)]);
+        let mut decl = Tree::new("VarDeclarator", 2, vec![array, init.clone()]);
+        let mut errors = no_errors();
+        assert_eq!(assign_type(&mut decl, TypeInfo::int(), &mut errors).unwrap().to_string(), "int[]");
+        assert!(errors.is_empty());
+
+        let mut scalar = Tree::new("VarDeclarator", 2, vec![Tree::new("VarDeclarator", 0, vec![ident]), init]);
+        assert_eq!(assign_type(&mut scalar, TypeInfo::int(), &mut errors).unwrap().to_string(), "int");
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].to_string(),
+            "line 3: type assignment error: 'a' is declared int, not as an array, so it cannot have an array initializer");
+    }
+
     #[test]
     fn test_method_header_builds_method_type() {
         let src = r#"
@@ -0,0 +1,238 @@
+//! Call-site validation — static methods through the class, instance
+//! methods through an object.
+//!
+//! For a qualified call `base.m(args)` the base is classified by what it
+//! resolves to, not by its type: a name chain ending in a class entry
+//! (`Point`, `mypkg.Point`) is a *class*; anything else — a variable, a
+//! field such as `System.out`, a call result — is an *object*.  The method
+//! is then found in the class of the base and its `is_static` flag must
+//! agree with the base kind.
+//!
+//! A call to a native method must also pass arguments of the types its
+//! signature declares: the embedder's Rust code relies on them.
+//!
+//! The class named by `new C(args)`, by `x instanceof C` or by
+//! `catch (C e)` must be one in scope.
+
+use jzero_ast::tree::Tree;
+use jzero_symtab::entry::SymbolKind;
+use jzero_symtab::{SymbolOrigin, TypeInfo};
+
+use crate::access::resolve_member;
+use crate::checktype::qualified_path;
+use crate::error::SemanticError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BaseKind {
+    Class,
+    Object,
+}
+
+/// Check every qualified method call, every `new C(args)`, every
+/// `instanceof` and every catch parameter's type in `tree`.
+pub fn check_call_sites(tree: &Tree, errors: &mut Vec<SemanticError>) {
+    match tree.sym.as_str() {
+        "InstanceCreation" => check_class_name(tree, 0, errors),
+        "InstanceOfExpr"   => check_class_name(tree, 1, errors),
+        "CatchClause"      => if let Some(parm) = tree.kids.first() { check_class_name(parm, 0, errors) },
+        _ => {}
+    }
+    if let Some(target) = call_target(tree) {
+        check_call(target, errors);
+        let args = &tree.kids[if tree.rule >= 2 { 2 } else { 1 }..];
+        check_native_args(target, args, errors);
+    }
+    for kid in &tree.kids {
+        check_call_sites(kid, errors);
+    }
+}
+
+/// The node whose kids are `[base, method]` for a qualified call.
+///
+/// Expression calls are `MethodCall#2/3 [base, method, args..]`; statement
+/// calls are `MethodCall#0/1 [FieldAccess [base, method], args..]`.
+fn call_target(tree: &Tree) -> Option<&Tree> {
+    if tree.sym != "MethodCall" { return None; }
+    if tree.rule >= 2 { return Some(tree); }
+    tree.kids.first().filter(|k| k.sym == "FieldAccess")
+}
+
+fn check_call(tree: &Tree, errors: &mut Vec<SemanticError>) {
+    let Some(base) = tree.kids.first() else { return };
+    let Some(kind) = base_kind(base) else { return };
+    let Some((class, method)) = resolve_member(tree) else { return };
+    if method.kind != SymbolKind::Method { return; }
+
+    let lineno = tree.kids.get(1)
+        .and_then(|k| k.tok.as_ref())
+        .map(|t| t.lineno)
+        .unwrap_or(0);
+    match (kind, method.is_static) {
+        (BaseKind::Object, true) => errors.push(SemanticError::StaticCallViaInstance {
+            method: method.sym.clone(), class, lineno,
+        }),
+        (BaseKind::Class, false) => errors.push(SemanticError::InstanceCallViaClass {
+            method: method.sym.clone(), class, lineno,
+        }),
+        _ => {}
+    }
+}
+
+/// Check that the class named by kid `i` of `tree` is in scope.  An
+/// interface, or `String`, which is a type of its own, will do for
+/// `instanceof`.
+fn check_class_name(tree: &Tree, i: usize, errors: &mut Vec<SemanticError>) {
+    let Some(name) = tree.kids.get(i).and_then(|k| k.tok.as_ref()) else { return };
+    let Some(stab) = &tree.stab else { return };
+    let instanceof = tree.sym == "InstanceOfExpr";
+    let is_class = (instanceof && name.text == "String") || stab.borrow().lookup(&name.text).is_some_and(|e| {
+        e.kind == SymbolKind::Class || (instanceof && e.kind == SymbolKind::Interface)
+    });
+    if !is_class {
+        errors.push(SemanticError::UndeclaredClass { name: name.text.clone(), lineno: name.lineno });
+    }
+}
+
+/// Check the arguments `args` of a call to `target` if it is a native method.
+fn check_native_args(target: &Tree, args: &[Tree], errors: &mut Vec<SemanticError>) {
+    let Some((class, method)) = resolve_member(target) else { return };
+    if method.origin != SymbolOrigin::Native { return; }
+    let Some(TypeInfo::Method(sig)) = &method.typ else { return };
+
+    let expected: Vec<String> = sig.parameters.iter().map(|p| p.param_type.to_string()).collect();
+    let found: Vec<String> = args.iter()
+        .map(|a| a.typ.as_ref().map_or_else(|| "?".to_string(), |t| t.to_string()))
+        .collect();
+    // An argument of unknown type has been reported already, if wrong.
+    let matches = expected.len() == found.len()
+        && expected.iter().zip(&found).all(|(e, f)| f == "?" || e == f);
+    if !matches {
+        errors.push(SemanticError::NativeCallMismatch {
+            method: format!("{}.{}", class, method.sym),
+            expected: format!("({})", expected.join(", ")),
+            found: format!("({})", found.join(", ")),
+            lineno: target.kids.get(1).and_then(|k| k.tok.as_ref()).map_or(0, |t| t.lineno),
+        });
+    }
+}
+
+/// Whether `base` names a class or denotes an object.  `None` if it does
+/// not resolve (already reported elsewhere) or names a package.
+fn base_kind(base: &Tree) -> Option<BaseKind> {
+    let Some(path) = qualified_path(base) else {
+        return Some(BaseKind::Object);
+    };
+    let path: Vec<&str> = path.iter().map(|s| s.as_str()).collect();
+    let stab = base.stab.clone()?;
+    let entry = jzero_symtab::resolve_qualified(&stab.borrow(), &path)?;
+    match entry.kind {
+        SymbolKind::Class | SymbolKind::Interface => Some(BaseKind::Class),
+        SymbolKind::Package => None,
+        _                   => Some(BaseKind::Object),
+    }
+}
+
+// ─── Tests ───────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use jzero_parser::parse_tree;
+    use jzero_symtab::{SymTab, SymTabEntry, TypeInfo, build_predefined};
+
+    use super::*;
+    use crate::{analyze, analyze_in};
+
+    fn errors(src: &str) -> Vec<String> {
+        let mut tree = parse_tree(src).expect("parse failed");
+        analyze(&mut tree).errors.iter().map(|e| e.to_string()).collect()
+    }
+
+    #[test]
+    fn println_is_called_on_an_object() {
+        let errs = errors(r#"
+public class T {
+    public static void main(String argv[]) {
+        System.out.println("hi");
+    }
+}
+"#);
+        assert!(errs.is_empty(), "{:?}", errs);
+    }
+
+    #[test]
+    fn static_method_through_variable_is_rejected() {
+        let errs = errors(r#"
+public class T {
+    public static int f() { return 1; }
+    public static void main(String argv[]) {
+        T t;
+        int a;
+        a = T.f();
+        a = t.f();
+    }
+}
+"#);
+        assert_eq!(errs.len(), 1, "{:?}", errs);
+        assert!(errs[0].starts_with("line 8: static method 'f'"), "{:?}", errs);
+    }
+
+    #[test]
+    fn instance_method_through_class_is_rejected() {
+        // `PrintStream` as a visible class whose `println` is an instance method.
+        let global = SymTab::new("global", None).into_rc();
+        build_predefined(&global);
+        let out_st = global.borrow().lookup("System").unwrap().st.unwrap()
+            .borrow().lookup_local("out").unwrap().st.clone().unwrap();
+        let mut ps = SymTabEntry::with_scope(
+            "PrintStream", SymbolKind::Class, Rc::clone(&global), false, Rc::clone(&out_st),
+        );
+        ps.set_typ(TypeInfo::class_with_symtab("PrintStream", out_st));
+        global.borrow_mut().insert(ps).unwrap();
+
+        let mut tree = parse_tree(r#"
+public class T {
+    public static void main(String argv[]) {
+        PrintStream.println("hi");
+    }
+}
+"#).expect("parse failed");
+        let errs: Vec<String> = analyze_in(&mut tree, global).errors.iter()
+            .map(|e| e.to_string()).collect();
+        assert_eq!(errs.len(), 1, "{:?}", errs);
+        assert!(errs[0].contains("instance method 'println' of 'PrintStream'"), "{:?}", errs);
+    }
+
+    #[test]
+    fn new_needs_a_class_in_scope() {
+        let errs = errors(r#"
+public class T {
+    int x;
+    public static void main(String argv[]) {
+        T t;
+        int n;
+        t = new T();
+        t = new Missing(1, 2);
+        t = new n();
+    }
+}
+"#);
+        assert_eq!(errs, ["line 8: undeclared class 'Missing'", "line 9: undeclared class 'n'"]);
+    }
+
+    #[test]
+    fn instanceof_needs_a_class_in_scope() {
+        let src = r#"
+public class T {
+    public static void main(String argv[]) {
+        T t;
+        bool b;
+        b = t instanceof T;
+        b = t instanceof Missing || t instanceof b;
+    }
+}
+"#;
+        assert_eq!(crate::tests::extended_errors(src), ["line 7: undeclared class 'Missing'", "line 7: undeclared class 'b'"]);
+    }
+}
@@ -4,7 +4,7 @@ use std::cell::RefCell;
 use std::rc::Rc;
 
 use jzero_ast::tree::Tree;
-use jzero_symtab::{SymTab, TypeInfo};
+use jzero_symtab::{SymTab, TypeInfo, entry::SymbolKind};
 
 // ─── TypeCheckResult ─────────────────────────────────────────────────────────
 
@@ -22,8 +22,8 @@ impl TypeCheckResult {
         TypeCheckResult {
             lineno,
             operator: operator.to_string(),
-            op1: op1.str(),
-            op2: op2.str(),
+            op1: op1.display().to_string(),
+            op2: op2.display().to_string(),
             ok,
         }
     }
@@ -76,7 +76,7 @@ pub fn check_type(
                 tree.kids.get(2).and_then(|k| k.typ.clone()),
             ) {
                 let result = check_types(tree, &lhs, &rhs);
-                let typ = if result.ok { Some(lhs.clone()) } else { None };
+                let typ = if result.ok { Some(promoted(&lhs, &rhs).unwrap_or(lhs)) } else { None };
                 results.push(result);
                 if let Some(t) = typ { tree.set_typ(t); }
             }
@@ -118,6 +118,41 @@ pub fn check_type(
             }
         }
 
+        // ── InstanceOfExpr: x instanceof C → boolean.  Only an object can
+        //    be tested; `callsite` checks that C is a class.
+        "InstanceOfExpr" => {
+            if let Some(operand) = tree.kids.first().and_then(|k| k.typ.clone()) {
+                let class = tree.kids.get(1).and_then(|k| k.tok.as_ref()).map(|t| match t.text.as_str() {
+                    "String" => TypeInfo::string(),
+                    name => TypeInfo::class(name),
+                });
+                let ok = matches!(operand, TypeInfo::Class(_) | TypeInfo::Array(_)) || operand.basetype() == "String";
+                let lineno = find_token(tree).and_then(|t| t.tok.as_ref()).map_or(0, |t| t.lineno);
+                results.push(TypeCheckResult::new(lineno, "instanceof", &class.unwrap_or_else(TypeInfo::unknown), &operand, ok));
+                if ok { tree.set_typ(TypeInfo::boolean()); }
+            }
+        }
+
+        // ── CastExpr: (double) n → double.  int and double widen and
+        //    narrow into each other; any other type casts only to itself.
+        "CastExpr" => {
+            let target = tree.kids.first().and_then(|k| k.tok.as_ref()).and_then(|t| match t.category.as_str() {
+                "INT"    => Some(TypeInfo::int()),
+                "DOUBLE" => Some(TypeInfo::double()),
+                "BOOL"   => Some(TypeInfo::boolean()),
+                "STRING" => Some(TypeInfo::string()),
+                "CHAR"   => Some(TypeInfo::char()),
+                "LONG"   => Some(TypeInfo::long()),
+                _ => None,
+            });
+            if let (Some(target), Some(operand)) = (target, tree.kids.get(1).and_then(|k| k.typ.clone())) {
+                let ok = (target.is_numeric() && operand.is_numeric()) || target.same_base(&operand);
+                let lineno = find_token(tree).and_then(|t| t.tok.as_ref()).map_or(0, |t| t.lineno);
+                results.push(TypeCheckResult::new(lineno, "cast", &target, &operand, ok));
+                if ok { tree.set_typ(target); }
+            }
+        }
+
         // ── ArrayCreation: new int[n] → Array(int) ────────────────────────
         "ArrayCreation" => {
             let elem_typ = tree.kids.first().and_then(|k| {
@@ -127,6 +162,8 @@ pub fn check_type(
                         "DOUBLE"     => Some(TypeInfo::double()),
                         "BOOL"       => Some(TypeInfo::boolean()),
                         "STRING"     => Some(TypeInfo::string()),
+                        "CHAR"       => Some(TypeInfo::char()),
+                        "LONG"       => Some(TypeInfo::long()),
                         "IDENTIFIER" => Some(TypeInfo::class(&t.text)),
                         _ => None,
                     })
@@ -153,7 +190,7 @@ pub fn check_type(
                         results.push(TypeCheckResult {
                             lineno,
                             operator: "subscript".to_string(),
-                            op1: idx.str(),
+                            op1: idx.display().to_string(),
                             op2: "int".to_string(),
                             ok: false,
                         });
@@ -167,7 +204,7 @@ pub fn check_type(
                     results.push(TypeCheckResult {
                         lineno,
                         operator: "subscript".to_string(),
-                        op1: base.str(),
+                        op1: base.display().to_string(),
                         op2: "array".to_string(),
                         ok: false,
                     });
@@ -179,6 +216,19 @@ pub fn check_type(
         // ── MethodCall ────────────────────────────────────────────────────
         "MethodCall" => {
             match tree.rule {
+                // `a.b(args)` with arguments parses as a call of the field
+                // access `a.b`.
+                0 if tree.kids.first().is_some_and(|k| k.sym == "FieldAccess") => {
+                    if let Some(TypeInfo::Method(mt)) = dequalify(&tree.kids[0]) {
+                        let args: Vec<TypeInfo> = tree.kids[1..]
+                            .iter()
+                            .filter_map(|k| k.typ.clone())
+                            .collect();
+                        let return_typ = *mt.return_type.clone();
+                        cksig(tree, &mt.parameters, &args, results);
+                        tree.set_typ(return_typ);
+                    }
+                }
                 0 => {
                     let name = tree.kids.first()
                         .and_then(|k| k.tok.as_ref())
@@ -203,7 +253,7 @@ pub fn check_type(
                                 results.push(TypeCheckResult {
                                     lineno,
                                     operator: "param".to_string(),
-                                    op1: other.str(),
+                                    op1: other.display().to_string(),
                                     op2: "method".to_string(),
                                     ok: false,
                                 });
@@ -243,8 +293,8 @@ pub fn check_type(
                         results.push(TypeCheckResult {
                             lineno,
                             operator: "return".to_string(),
-                            op1: rt.str(),
-                            op2: expr_typ.str(),
+                            op1: rt.display().to_string(),
+                            op2: expr_typ.display().to_string(),
                             ok,
                         });
                         if ok { tree.set_typ(rt); }
@@ -284,10 +334,14 @@ pub fn check_type(
                         }
                     }
                 }
+            } else if let Some(typ) = resolve_qualified_type(tree) {
+                // Untyped base: a package prefix such as `mypkg.util.Helper`.
+                tree.set_typ(typ);
             }
         }
 
-        "Block" | "BlockStmts" | "EmptyStmt" | "BreakStmt" => {
+        "Block" | "BlockStmts" | "EmptyStmt" | "BreakStmt"
+        | "TryStmt" | "CatchClause" | "ThrowStmt" | "SwitchStmt" | "CaseClause" => {
             tree.set_typ(TypeInfo::void());
         }
 
@@ -325,13 +379,63 @@ fn check_kids(
             true
         }
 
+        // ── Inferred local: var x = expr ─────────────────────────────────
+        //
+        // The builder declared `x` without a type; it takes the type of
+        // its initializer, checked first.  There is nothing to infer from
+        // `null`, `void` or an initializer that has no type.
+        "LocalVarDecl" if tree.rule == 2 => {
+            let Some(decl) = tree.kids.get_mut(1) else { return true };
+            let Some(init) = decl.kids.get_mut(1) else { return true };
+            check_type(init, true, results);
+            let Some(typ) = init.typ.clone() else { return true };
+            let lineno = find_token(init).and_then(|t| t.tok.as_ref()).map_or(0, |t| t.lineno);
+            if matches!(typ.basetype(), "null" | "void" | "n/a" | "unknown") {
+                results.push(TypeCheckResult::new(lineno, "var", &TypeInfo::unknown(), &typ, false));
+                return true;
+            }
+            decl.set_typ(typ.clone());
+            let name = &mut decl.kids[0];
+            name.set_typ(typ.clone());
+            let Some(leaf) = name.kids.first_mut() else { return true };
+            leaf.set_typ(typ.clone());
+            if let (Some(stab), Some(tok)) = (&leaf.stab, &leaf.tok)
+                && let Some(entry) = stab.borrow_mut().lookup_local_mut(&tok.text)
+                && entry.typ.is_none()
+            {
+                entry.set_typ(typ);
+            }
+            true
+        }
+
         "LocalVarDecl" => {
-            if let Some(declarator) = tree.kids.get_mut(1) {
+            for declarator in tree.kids.iter_mut().skip(1) {
                 check_type(declarator, false, results);
             }
             true
         }
 
+        // ── Initialized declarator: int x = 5, a[] = {1, 2} ──────────────
+        //
+        // Reached from fields too, outside any code block: the
+        // initializers are expressions all the same.  One that is not a
+        // list is checked as if assigned to the name.
+        "VarDeclarator" if tree.rule == 2 => {
+            let declared = tree.kids.first().and_then(declared_type);
+            if let Some(init) = tree.kids.get_mut(1) {
+                check_type(init, true, results);
+                match declared {
+                    Some(TypeInfo::Array(elem)) if init.sym == "ArrayInit" => check_array_init(init, &elem, results),
+                    Some(declared) if init.sym != "ArrayInit" => if let Some(typ) = &init.typ {
+                        let lineno = find_token(init).and_then(|t| t.tok.as_ref()).map_or(0, |t| t.lineno);
+                        results.push(TypeCheckResult::new(lineno, "=", &declared, typ, assignable(&declared, typ)));
+                    },
+                    _ => {}
+                }
+            }
+            true
+        }
+
         "FieldAccess" => {
             if let Some(obj) = tree.kids.get_mut(0) {
                 check_type(obj, in_codeblock, results);
@@ -388,6 +492,36 @@ fn check_kids(
     }
 }
 
+/// The type the declarator `tree` gives its name.
+fn declared_type(tree: &Tree) -> Option<TypeInfo> {
+    match &tree.tok {
+        Some(_) => tree.typ.clone(),
+        None => declared_type(tree.kids.first()?),
+    }
+}
+
+/// Check each element of the initializer list `init` of an array of
+/// `elem`s as if it were assigned to an element.
+fn check_array_init(init: &mut Tree, elem: &TypeInfo, results: &mut Vec<TypeCheckResult>) {
+    init.set_typ(TypeInfo::array(elem.clone()));
+    for kid in &mut init.kids {
+        let lineno = find_token(kid).and_then(|t| t.tok.as_ref()).map_or(0, |t| t.lineno);
+        match (kid.sym.as_str(), elem) {
+            ("ArrayInit", TypeInfo::Array(inner)) => check_array_init(kid, inner, results),
+            ("ArrayInit", _) => results.push(TypeCheckResult {
+                lineno,
+                operator: "=".to_string(),
+                op1: elem.display().to_string(),
+                op2: "array initializer".to_string(),
+                ok: false,
+            }),
+            _ => if let Some(typ) = &kid.typ {
+                results.push(TypeCheckResult::new(lineno, "=", elem, typ, assignable(elem, typ)));
+            },
+        }
+    }
+}
+
 // ─── cksig ───────────────────────────────────────────────────────────────────
 
 fn cksig(
@@ -417,8 +551,8 @@ fn cksig(
         results.push(TypeCheckResult {
             lineno,
             operator: "param".to_string(),
-            op1: param.param_type.str(),
-            op2: arg.str(),
+            op1: param.param_type.display().to_string(),
+            op2: arg.display().to_string(),
             ok,
         });
     }
@@ -447,15 +581,7 @@ fn check_types(tree: &Tree, op1: &TypeInfo, op2: &TypeInfo) -> TypeCheckResult {
     let lineno = find_token(tree).and_then(|t| t.tok.as_ref()).map(|t| t.lineno).unwrap_or(0);
 
     let ok = match operator.as_str() {
-        "=" | "+=" | "-=" => {
-            if op1.basetype() == "array" && op2.basetype() == "array" {
-                if let (TypeInfo::Array(e1), TypeInfo::Array(e2)) = (op1, op2) {
-                    e1.same_base(e2)
-                } else { false }
-            } else {
-                op1.same_base(op2)
-            }
-        }
+        "=" | "+=" | "-=" => assignable(op1, op2),
         "+" | "-" | "*" | "/" | "%" => {
             if op1.same_base(op2) {
                 // String supports + (concatenation) but not -, *, /, %
@@ -465,13 +591,13 @@ fn check_types(tree: &Tree, op1: &TypeInfo, op2: &TypeInfo) -> TypeCheckResult {
                     op1.is_numeric()
                 }
             } else {
-                false
+                promoted(op1, op2).is_some()
             }
         }
         "<" | ">" | "<=" | ">=" =>
-            op1.same_base(op2) && op1.is_numeric(),
+            (op1.same_base(op2) && op1.is_numeric()) || promoted(op1, op2).is_some(),
         "==" | "!=" =>
-            op1.same_base(op2),
+            op1.same_base(op2) || promoted(op1, op2).is_some(),
         "&&" | "||" =>
             op1.is_boolean() && op2.is_boolean(),
         "param" | "return" =>
@@ -482,6 +608,33 @@ fn check_types(tree: &Tree, op1: &TypeInfo, op2: &TypeInfo) -> TypeCheckResult {
     TypeCheckResult::new(lineno, &operator, op1, op2, ok)
 }
 
+/// Whether a value of type `op2` may be assigned to a `op1`.  A `char`
+/// widens to an `int`, its code point, as character literals were ints
+/// before Jzero had `char`; both widen to a `long`, and all three to a
+/// `double`.
+fn assignable(op1: &TypeInfo, op2: &TypeInfo) -> bool {
+    if op1.basetype() == "array" && op2.basetype() == "array" {
+        if let (TypeInfo::Array(e1), TypeInfo::Array(e2)) = (op1, op2) {
+            e1.same_base(e2)
+        } else { false }
+    } else {
+        op1.same_base(op2) || widens(op2, op1)
+    }
+}
+
+/// Whether a `from` widens to a `to` without a cast.
+fn widens(from: &TypeInfo, to: &TypeInfo) -> bool {
+    matches!((from.basetype(), to.basetype()), ("char", "int") | ("char" | "int", "long") | ("char" | "int" | "long", "double"))
+}
+
+/// The type of an operation on an `op1` and an `op2` of different types,
+/// one of which widens to the other: `l + 1` is a `long`.
+fn promoted(op1: &TypeInfo, op2: &TypeInfo) -> Option<TypeInfo> {
+    if widens(op2, op1) { Some(op1.clone()) }
+    else if widens(op1, op2) { Some(op2.clone()) }
+    else { None }
+}
+
 // ─── Helpers ─────────────────────────────────────────────────────────────────
 
 fn get_op(tree: &Tree) -> Option<&str> {
@@ -496,6 +649,33 @@ pub fn find_token(tree: &Tree) -> Option<&Tree> {
     None
 }
 
+/// `a.b.c` as `["a", "b", "c"]`, if the `FieldAccess` chain is made only
+/// of names.
+pub(crate) fn qualified_path(tree: &Tree) -> Option<Vec<String>> {
+    if let Some(tok) = &tree.tok {
+        return (tok.category == "IDENTIFIER").then(|| vec![tok.text.clone()]);
+    }
+    if tree.sym != "FieldAccess" { return None; }
+    let mut path = qualified_path(tree.kids.first()?)?;
+    path.push(tree.kids.get(1)?.tok.as_ref()?.text.clone());
+    Some(path)
+}
+
+/// Resolve a dotted name through the package and class scopes.  Classes
+/// are typed even before `mkcls` has stamped them; packages have no type.
+fn resolve_qualified_type(tree: &Tree) -> Option<TypeInfo> {
+    let stab = tree.stab.clone()?;
+    let path = qualified_path(tree)?;
+    let path: Vec<&str> = path.iter().map(|s| s.as_str()).collect();
+    let entry = jzero_symtab::resolve_qualified(&stab.borrow(), &path)?;
+    match (&entry.typ, &entry.kind, &entry.st) {
+        (Some(t), _, _) => Some(t.clone()),
+        (None, SymbolKind::Class, Some(st)) =>
+            Some(TypeInfo::class_with_symtab(&entry.sym, Rc::clone(st))),
+        _ => None,
+    }
+}
+
 fn lookup_in_stab(tree: &Tree) -> Option<TypeInfo> {
     let stab: Rc<RefCell<SymTab>> = tree.stab.clone()?;
     let name = tree.tok.as_ref().map(|t| t.text.clone())?;
@@ -600,7 +780,7 @@ public class T {
         println!("\n=== array type checks ===");
         for r in &type_results { println!("{}", r); }
 
-        let arr_assign = type_results.iter().find(|r| r.operator == "=" && r.op1 == "array");
+        let arr_assign = type_results.iter().find(|r| r.operator == "=" && r.op1 == "int[]");
         assert!(arr_assign.is_some(), "expected array assignment typecheck");
         assert!(arr_assign.unwrap().ok);
 
@@ -0,0 +1,419 @@
+//! Compile-time constants — the value of an expression made only of
+//! literals and operators.
+//!
+//! [`eval_const`] folds `2 * 8 - 1` to `15` and `!(1 < 2)` to `false`;
+//! anything naming a variable or calling a method is not constant, bar
+//! the right side of `false && ...` and `true || ...`, which never runs.  Integer
+//! arithmetic that would overflow, and division by zero, are left to run
+//! time rather than folded.
+//!
+//! [`check_array_sizes`] uses it on the size of every `new T[n]`: a
+//! negative constant size is an error, and each known size is recorded by
+//! the node id of its `ArrayCreation`, for passes that want to know how
+//! long an array is — bounds-check elimination first among them.  The
+//! length of each `{1, 2, 3}` initializer list is recorded too, by the id
+//! of its `ArrayInit`.
+//!
+//! [`check_case_labels`] uses it on the labels of every `switch`: each
+//! must be a constant `int`, and no two labels of a switch, `default`
+//! included, may be the same.
+//!
+//! A `static final` field of type `int`, `double` or `bool` is a named
+//! constant: its initializer must be a constant expression, which may name
+//! the constants declared before it.  [`constant_names`] finds the value of
+//! every use of one, and [`eval_const_with`] folds expressions that use
+//! them — `if (DEBUG)` included, so codegen can drop the branch that never
+//! runs.  [`define`] overrides a constant's value before analysis.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use jzero_ast::tree::Tree;
+use jzero_symtab::entry::SymbolKind;
+
+use crate::checktype::find_token;
+use crate::error::SemanticError;
+
+/// The value of a constant expression.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConstValue {
+    Int(i64),
+    Double(f64),
+    Bool(bool),
+}
+
+impl fmt::Display for ConstValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConstValue::Int(n)    => write!(f, "{}", n),
+            ConstValue::Double(d) => write!(f, "{:?}", d),
+            ConstValue::Bool(b)   => write!(f, "{}", b),
+        }
+    }
+}
+
+/// The value of `tree`, if it is a constant expression.
+pub fn eval_const(tree: &Tree) -> Option<ConstValue> {
+    eval_in(tree, &|_| None)
+}
+
+/// Like [`eval_const`], with the names in `names`, by the id of their
+/// IDENTIFIER leaf, as constants — the map [`constant_names`] returns.
+pub fn eval_const_with(tree: &Tree, names: &HashMap<u32, ConstValue>) -> Option<ConstValue> {
+    eval_in(tree, &|leaf| names.get(&leaf.id).cloned())
+}
+
+/// The value of `tree`, `name` giving that of an IDENTIFIER leaf.
+fn eval_in(tree: &Tree, name: &dyn Fn(&Tree) -> Option<ConstValue>) -> Option<ConstValue> {
+    use ConstValue::*;
+
+    if let Some(tok) = &tree.tok {
+        return match tree.sym.as_str() {
+            "INTLIT"     => tok.text.parse().ok().map(Int),
+            "DOUBLELIT"  => tok.text.trim_end_matches(['d', 'D']).parse().ok().map(Double),
+            "BOOLLIT"    => Some(Bool(tok.text == "true")),
+            "IDENTIFIER" => name(tree),
+            _ => None,
+        };
+    }
+
+    let eval_const = |tree: &Tree| eval_in(tree, name);
+    let operator = || tree.kids.get(1).map(|k| k.sym.as_str());
+    let operands = || Some((eval_const(tree.kids.first()?)?, eval_const(tree.kids.get(2)?)?));
+    match tree.sym.as_str() {
+        "UnaryMinus" => match eval_const(tree.kids.first()?)? {
+            Int(n)    => n.checked_neg().map(Int),
+            Double(d) => Some(Double(-d)),
+            Bool(_)   => None,
+        },
+        "UnaryNot" => match eval_const(tree.kids.first()?)? {
+            Bool(b) => Some(Bool(!b)),
+            _ => None,
+        },
+        "CastExpr" => match (tree.kids.first()?.tok.as_ref()?.category.as_str(), eval_const(tree.kids.get(1)?)?) {
+            ("INT", Int(n))       => Some(Int(n)),
+            ("INT", Double(d))    => Some(Int(d as i64)),
+            ("DOUBLE", Int(n))    => Some(Double(n as f64)),
+            ("DOUBLE", Double(d)) => Some(Double(d)),
+            ("BOOL", Bool(b))     => Some(Bool(b)),
+            _ => None,
+        },
+        "AddExpr" | "MulExpr" => match operands()? {
+            (Int(a), Int(b)) => match operator()? {
+                "PLUS"    => a.checked_add(b),
+                "MINUS"   => a.checked_sub(b),
+                "STAR"    => a.checked_mul(b),
+                "SLASH"   => a.checked_div(b),
+                "PERCENT" => a.checked_rem(b),
+                _ => None,
+            }.map(Int),
+            (a, b) => {
+                let (a, b) = (as_double(&a)?, as_double(&b)?);
+                match operator()? {
+                    "PLUS"    => Some(Double(a + b)),
+                    "MINUS"   => Some(Double(a - b)),
+                    "STAR"    => Some(Double(a * b)),
+                    "SLASH"   => Some(Double(a / b)),
+                    "PERCENT" => Some(Double(a % b)),
+                    _ => None,
+                }
+            }
+        },
+        "RelExpr" => {
+            let (a, b) = operands()?;
+            let (a, b) = (as_double(&a)?, as_double(&b)?);
+            Some(Bool(match operator()? {
+                "LESS"         => a < b,
+                "GREATER"      => a > b,
+                "LESSEQUAL"    => a <= b,
+                "GREATEREQUAL" => a >= b,
+                _ => return None,
+            }))
+        }
+        "EqExpr" => {
+            let equal = match operands()? {
+                (Bool(a), Bool(b)) => a == b,
+                (a, b) => as_double(&a)? == as_double(&b)?,
+            };
+            Some(Bool(equal == (operator()? == "EQUALEQUAL")))
+        }
+        // `false && x` and `true || x` are constant whatever `x` is.
+        "CondAndExpr" | "CondOrExpr" => {
+            let or = tree.sym == "CondOrExpr";
+            match eval_const(tree.kids.first()?)? {
+                Bool(a) if a == or => Some(Bool(a)),
+                Bool(_) => match eval_const(tree.kids.get(2)?)? {
+                    Bool(b) => Some(Bool(b)),
+                    _ => None,
+                },
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+fn as_double(value: &ConstValue) -> Option<f64> {
+    match value {
+        ConstValue::Int(n)    => Some(*n as f64),
+        ConstValue::Double(d) => Some(*d),
+        ConstValue::Bool(_)   => None,
+    }
+}
+
+/// Check the size of every `new T[n]` in `tree` that is a constant, and
+/// return the non-negative ones by the id of their `ArrayCreation` node,
+/// with the length of every initializer list by the id of its `ArrayInit`.
+pub fn check_array_sizes(tree: &Tree, errors: &mut Vec<SemanticError>) -> HashMap<u32, i64> {
+    let mut sizes = HashMap::new();
+    walk(tree, errors, &mut sizes);
+    sizes
+}
+
+fn walk(tree: &Tree, errors: &mut Vec<SemanticError>, sizes: &mut HashMap<u32, i64>) {
+    if tree.sym == "ArrayCreation"
+        && let Some(size) = tree.kids.get(1)
+        && let Some(ConstValue::Int(n)) = eval_const(size)
+    {
+        if n < 0 {
+            let lineno = find_token(size).and_then(|t| t.tok.as_ref()).map_or(0, |t| t.lineno);
+            errors.push(SemanticError::NegativeArraySize { size: n, lineno });
+        } else {
+            sizes.insert(tree.id, n);
+        }
+    }
+    if tree.sym == "ArrayInit" {
+        sizes.insert(tree.id, tree.kids.len() as i64);
+    }
+    for kid in &tree.kids {
+        walk(kid, errors, sizes);
+    }
+}
+
+/// The value of a `case` label, if it is a constant `int`.
+pub fn case_value(label: &Tree) -> Option<i64> {
+    match eval_const(label)? {
+        ConstValue::Int(n) => Some(n),
+        _ => None,
+    }
+}
+
+/// Check the labels of every `switch` in `tree`.
+pub fn check_case_labels(tree: &Tree, errors: &mut Vec<SemanticError>) {
+    if tree.sym == "SwitchStmt" {
+        // Each value seen so far and its line; `None` is `default`.
+        let mut seen: HashMap<Option<i64>, usize> = HashMap::new();
+        let labels = tree.kids.iter().skip(1).flat_map(|clause| clause.kids.iter().skip(1));
+        for label in labels {
+            let lineno = find_token(label).and_then(|t| t.tok.as_ref()).map_or(0, |t| t.lineno);
+            let value = if label.sym == "DefaultLabel" {
+                None
+            } else if let Some(n) = case_value(label) {
+                Some(n)
+            } else {
+                errors.push(SemanticError::NonConstantCaseLabel { lineno });
+                continue;
+            };
+            if let Some(&first_lineno) = seen.get(&value) {
+                let label = value.map_or("default".to_string(), |n| format!("case {}", n));
+                errors.push(SemanticError::DuplicateCaseLabel { label, lineno, first_lineno });
+            } else {
+                seen.insert(value, lineno);
+            }
+        }
+    }
+    for kid in &tree.kids {
+        check_case_labels(kid, errors);
+    }
+}
+
+/// The values of named constants, by name and line of declaration.
+type Constants = HashMap<(String, usize), ConstValue>;
+
+/// The value of every use of a named constant in `tree`, by the id of its
+/// IDENTIFIER leaf.  A constant with no initializer, or one that is not a
+/// constant expression, is an error.  Run on the numbered tree, after the
+/// symbol tables are built.
+pub fn constant_names(tree: &Tree, errors: &mut Vec<SemanticError>) -> HashMap<u32, ConstValue> {
+    let mut constants = Constants::new();
+    collect_constants(tree, &mut constants, errors);
+    let mut names = HashMap::new();
+    find_uses(tree, &constants, &mut names);
+    names
+}
+
+/// The scalar type of the constants a `static final` FieldDecl declares,
+/// if it declares named constants.
+fn constant_type(tree: &Tree) -> Option<&str> {
+    if tree.sym != "FieldDecl" || !(3..6).contains(&tree.rule) { return None; }
+    let ty = tree.kids.first()?.sym.as_str();
+    matches!(ty, "INT" | "DOUBLE" | "BOOL").then_some(ty)
+}
+
+/// Whether a VarDeclarator declares an array, `int a[]`.
+fn declares_array(decl: &Tree) -> bool {
+    let name = if decl.rule == 2 { decl.kids.first() } else { Some(decl) };
+    name.is_some_and(|n| n.rule == 1)
+}
+
+/// Evaluate the constants in source order, so each may use those before.
+fn collect_constants(tree: &Tree, constants: &mut Constants, errors: &mut Vec<SemanticError>) {
+    if let Some(ty) = constant_type(tree) {
+        for decl in tree.kids.iter().skip(1).filter(|d| d.sym == "VarDeclarator" && !declares_array(d)) {
+            let Some(tok) = find_token(decl).and_then(|t| t.tok.as_ref()) else { continue };
+            let value = (decl.rule == 2)
+                .then(|| eval_in(decl.kids.get(1)?, &|leaf| constant_value(leaf, constants)))
+                .flatten();
+            match value {
+                Some(ConstValue::Int(n)) if ty == "DOUBLE" => {
+                    constants.insert((tok.text.clone(), tok.lineno), ConstValue::Double(n as f64));
+                }
+                Some(value) => { constants.insert((tok.text.clone(), tok.lineno), value); }
+                None => errors.push(SemanticError::NonConstantField { name: tok.text.clone(), lineno: tok.lineno }),
+            }
+        }
+        return;
+    }
+    for kid in &tree.kids {
+        collect_constants(kid, constants, errors);
+    }
+}
+
+/// The value of the named constant the IDENTIFIER `leaf` names in its
+/// scope, if it names one and its value is known.
+fn constant_value(leaf: &Tree, constants: &Constants) -> Option<ConstValue> {
+    let name = &leaf.tok.as_ref()?.text;
+    let entry = leaf.stab.as_ref()?.borrow().lookup(name)?;
+    if entry.kind != SymbolKind::Field || !entry.is_const { return None; }
+    constants.get(&(entry.sym, entry.lineno)).cloned()
+}
+
+fn find_uses(tree: &Tree, constants: &Constants, names: &mut HashMap<u32, ConstValue>) {
+    match tree.sym.as_str() {
+        // The name a declarator declares, and the member of `a.b`, are
+        // not uses of a name in scope.
+        "VarDeclarator" if tree.rule == 0 => return,
+        "FieldAccess" => {
+            if let Some(base) = tree.kids.first() { find_uses(base, constants, names); }
+            return;
+        }
+        "IDENTIFIER" => {
+            if let Some(value) = constant_value(tree, constants) { names.insert(tree.id, value); }
+            return;
+        }
+        _ => {}
+    }
+    for kid in &tree.kids {
+        find_uses(kid, constants, names);
+    }
+}
+
+/// Give the named constant `name` the value `value`, written as a literal
+/// of its type, in place of its initializer — `j0 --define NAME=VALUE`.
+/// Run on the parsed tree, before analysis.
+///
+/// # Errors
+/// Returns a message if no constant `name` is declared, or `value` is not
+/// a literal of its type.
+pub fn define(tree: &mut Tree, name: &str, value: &str) -> Result<(), String> {
+    let Some((field, i)) = find_constant(tree, name) else {
+        return Err(format!("no constant '{}' is declared", name));
+    };
+    let ty = constant_type(field).ok_or_else(|| format!("constant '{}' is not an int, double or bool", name))?.to_string();
+    let decl = &mut field.kids[i];
+    if declares_array(decl) {
+        return Err(format!("constant '{}' is not an int, double or bool", name));
+    }
+    let lineno = find_token(decl).and_then(|t| t.tok.as_ref()).map_or(0, |t| t.lineno);
+    let category = match ty.as_str() {
+        "INT" if value.parse::<i64>().is_ok() => "INTLIT",
+        "DOUBLE" if value.parse::<f64>().is_ok() => "DOUBLELIT",
+        "BOOL" if value == "true" || value == "false" => "BOOLLIT",
+        _ => return Err(format!("'{}' is not a {} value for constant '{}'", value, ty.to_lowercase(), name)),
+    };
+    let literal = Tree::leaf(category, value, lineno);
+    if decl.rule == 2 {
+        decl.replace_kid(1, literal);
+    } else {
+        let name = std::mem::replace(decl, Tree::new("VarDeclarator", 2, Vec::new()));
+        *decl = Tree::new("VarDeclarator", 2, vec![name, literal]);
+    }
+    Ok(())
+}
+
+/// The `static final` FieldDecl declaring `name`, and the index of its
+/// declarator.
+fn find_constant<'a>(tree: &'a mut Tree, name: &str) -> Option<(&'a mut Tree, usize)> {
+    if tree.sym == "FieldDecl" && (3..6).contains(&tree.rule) {
+        let declares = |d: &Tree| find_token(d).and_then(|t| t.tok.as_ref()).is_some_and(|t| t.text == name);
+        return tree.kids.iter().skip(1).position(declares).map(|i| (tree, i + 1));
+    }
+    tree.kids.iter_mut().find_map(|kid| find_constant(kid, name))
+}
+
+// ─── Tests ───────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The value of the expression `e`, as the right side of an assignment.
+    fn eval(e: &str) -> Option<ConstValue> {
+        let src = format!("public class t {{ public static void main(String argv[]) {{ int x; x = {}; }} }}", e);
+        let tree = jzero_parser::parse_tree(&src).unwrap();
+        // ClassDecl → MethodDecl → Block → [LocalVarDecl, Assignment]
+        let assign = &tree.kids[1].kids[1].kids[1];
+        eval_const(&assign.kids[2])
+    }
+
+    #[test]
+    fn folds_literals_and_operators() {
+        assert_eq!(eval("2 * 8 - 1"), Some(ConstValue::Int(15)));
+        assert_eq!(eval("-(7 % 4)"), Some(ConstValue::Int(-3)));
+        assert_eq!(eval("1 + 0.5"), Some(ConstValue::Double(1.5)));
+        assert_eq!(eval("!(1 < 2) || 3 == 3"), Some(ConstValue::Bool(true)));
+        assert_eq!(eval("true && false"), Some(ConstValue::Bool(false)));
+        assert_eq!(eval("false && x > 1"), Some(ConstValue::Bool(false)));
+        assert_eq!(eval("true || x"), Some(ConstValue::Bool(true)));
+        assert_eq!(eval("(int) 7.9 * 2"), Some(ConstValue::Int(14)));
+        assert_eq!(eval("(double) 3"), Some(ConstValue::Double(3.0)));
+        assert_eq!(eval("(int) true"), None);
+    }
+
+    #[test]
+    fn duplicate_and_non_constant_case_labels() {
+        let src = "public class t {\n    public static void main(String argv[]) {\n        switch (x) {\n            \
+                   case 1: case 2 - 1: f();\n            default: case x: g();\n            default:\n        }\n    }\n}\n";
+        let tree = jzero_parser::parse_tree_with(src, jzero_lexer::LexOptions::at_level(jzero_lexer::Level::Extended)).unwrap();
+        let mut errors = Vec::new();
+        check_case_labels(&tree, &mut errors);
+        let shown: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+        assert_eq!(shown, [
+            "line 4: duplicate case 1 in switch (first on line 4)",
+            "line 5: case label must be a constant int",
+            "line 6: duplicate default in switch (first on line 5)",
+        ]);
+    }
+
+    #[test]
+    fn define_replaces_a_constants_initializer() {
+        let src = "public class t {\n    static final bool DEBUG = false;\n    static final double RATE;\n    int n;\n}\n";
+        let mut tree = jzero_parser::parse_tree_with(src, jzero_lexer::LexOptions::at_level(jzero_lexer::Level::Extended)).unwrap();
+        define(&mut tree, "DEBUG", "true").unwrap();
+        define(&mut tree, "RATE", "0.5").unwrap();
+        assert_eq!(eval_const(&tree.kids[1].kids[1].kids[1]), Some(ConstValue::Bool(true)));
+        assert_eq!(eval_const(&tree.kids[2].kids[1].kids[1]), Some(ConstValue::Double(0.5)));
+
+        assert_eq!(define(&mut tree, "DEBUG", "1").unwrap_err(), "'1' is not a bool value for constant 'DEBUG'");
+        assert_eq!(define(&mut tree, "n", "1").unwrap_err(), "no constant 'n' is declared");
+    }
+
+    #[test]
+    fn names_overflow_and_division_by_zero_are_not_folded() {
+        assert_eq!(eval("x + 1"), None);
+        assert_eq!(eval("1 / 0"), None);
+        assert_eq!(eval("9223372036854775807 + 1"), None);
+        assert_eq!(eval("true + 1"), None);
+        assert_eq!(eval("true && x"), None);
+    }
+}
@@ -0,0 +1,158 @@
+//! Desugaring — lowering the surface tree to core Jzero.
+//!
+//! The parser produces the full surface syntax.  Before any symbol table or
+//! type work, this pass lowers it to a smaller core node set so the later
+//! passes (type checking, IR lowering) see fewer shapes:
+//!
+//! ```text
+//! x += e;                     →  x = x + e;
+//! x -= e;                     →  x = x - e;
+//! for (init; cond; upd) body  →  { init; while (cond) { body; upd; } }
+//! for (init; ; upd) body      →  { init; while (true) { body; upd; } }
+//! while (c) stmt              →  while (c) { stmt }
+//! if (c) {..} else if ..      →  if (c) {..} else { if .. }
+//! ```
+//!
+//! Leaves are moved, not re-created, so every identifier and literal keeps
+//! its original line number for diagnostics.  Synthesized leaves (`=`, `+`,
+//! `true`) take the line of the construct they replace.
+
+use jzero_ast::tree::Tree;
+
+use crate::rewrite::{Rewriter, Rule, compound_assign};
+
+/// Lower `tree` to core Jzero in place.  Returns the number of rewrites.
+pub fn desugar(tree: &mut Tree) -> usize {
+    Rewriter::new()
+        .rule(compound_assign())
+        .rule(for_to_while())
+        .rule(explicit_blocks())
+        .rewrite(tree)
+}
+
+/// `for (init; cond; upd) body` → `{ init; while (cond) { body; upd; } }`.
+///
+/// `break` inside `body` still leaves the loop, skipping `upd`, exactly as
+/// it did in the `for`.  Jzero has no `continue`, so moving `upd` to the end
+/// of the body is safe.
+fn for_to_while() -> Rule {
+    Rule::new(
+        "for-to-while",
+        |t| t.sym == "ForStmt" && t.kids.len() == 4,
+        |t| {
+            let [init, cond, upd, body] = <[Tree; 4]>::try_from(t.kids.clone())
+                .expect("pattern checked four kids");
+
+            let cond = if cond.sym == "EmptyExpr" {
+                Tree::leaf("BOOLLIT", "true", cond.first_lineno())
+            } else {
+                cond
+            };
+
+            let mut loop_body = vec![body];
+            if upd.sym != "EmptyForUpdate" { loop_body.push(upd); }
+            let while_stmt = Tree::new("WhileStmt", 0, vec![
+                cond,
+                Tree::new("Block", 0, loop_body),
+            ]);
+
+            let mut stmts = vec![];
+            if init.sym != "EmptyForInit" { stmts.push(init); }
+            stmts.push(while_stmt);
+            Tree::new("Block", 0, stmts)
+        },
+    )
+}
+
+/// Wrap single-statement loop bodies and `else if` chains in a `Block`.
+fn explicit_blocks() -> Rule {
+    Rule::new(
+        "explicit-blocks",
+        |t| match t.sym.as_str() {
+            "WhileStmt"      => t.kids.len() == 2 && t.kids[1].sym != "Block",
+            "IfThenElseStmt" => t.kids.len() == 3 && t.kids[2].sym != "Block",
+            _ => false,
+        },
+        |t| {
+            let mut kids = t.kids.clone();
+            let last = kids.pop().unwrap();
+            kids.push(Tree::new("Block", 0, vec![last]));
+            Tree::new(&t.sym, t.rule, kids)
+        },
+    )
+}
+
+// ─── Tests ───────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(src: &str) -> Tree {
+        jzero_parser::parse_tree(src).expect("parse failed")
+    }
+
+    fn count(t: &Tree, sym: &str) -> usize {
+        (t.sym == sym) as usize + t.kids.iter().map(|k| count(k, sym)).sum::<usize>()
+    }
+
+    fn find<'a>(t: &'a Tree, sym: &str) -> Option<&'a Tree> {
+        if t.sym == sym { return Some(t); }
+        t.kids.iter().find_map(|k| find(k, sym))
+    }
+
+    fn wrap(body: &str) -> String {
+        format!(
+            "public class T {{\n public static void main(String argv[]) {{\n{}\n }}\n}}\n",
+            body
+        )
+    }
+
+    #[test]
+    fn for_becomes_while() {
+        let mut tree = parse(&wrap("int i;\nfor (i = 0; i < 3; i = i + 1) { i = i; }"));
+        desugar(&mut tree);
+        assert_eq!(count(&tree, "ForStmt"), 0);
+        let w = find(&tree, "WhileStmt").expect("no WhileStmt");
+        assert_eq!(w.kids[0].sym, "RelExpr");
+        let body = &w.kids[1];
+        assert_eq!(body.sym, "Block");
+        // body block, then the update
+        assert_eq!(body.kids.len(), 2);
+        assert_eq!(body.kids[1].sym, "Assignment");
+    }
+
+    #[test]
+    fn empty_for_condition_becomes_true() {
+        let mut tree = parse(&wrap("int i;\nfor (i = 0; ; i = i + 1) { break; }"));
+        desugar(&mut tree);
+        let w = find(&tree, "WhileStmt").unwrap();
+        let cond = w.kids[0].tok.as_ref().unwrap();
+        assert_eq!((cond.category.as_str(), cond.text.as_str()), ("BOOLLIT", "true"));
+        assert_eq!(cond.lineno, 4, "synthesized leaf takes the for's line");
+    }
+
+    #[test]
+    fn compound_assign_is_lowered() {
+        let mut tree = parse(&wrap("int x;\nx = 1;\nx += 2;"));
+        desugar(&mut tree);
+        assert!(!tree.to_text(0).contains("+="));
+    }
+
+    #[test]
+    fn while_body_gets_block() {
+        let mut tree = parse(&wrap("int x;\nx = 1;\nwhile (x < 3) break;"));
+        desugar(&mut tree);
+        let w = find(&tree, "WhileStmt").unwrap();
+        assert_eq!(w.kids[1].sym, "Block");
+    }
+
+    #[test]
+    fn leaves_keep_their_lines() {
+        let mut tree = parse(&wrap("int i;\nfor (i = 0; i < 3; i = i + 1) { }"));
+        desugar(&mut tree);
+        let w = find(&tree, "WhileStmt").unwrap();
+        let i = w.kids[0].kids[0].tok.as_ref().unwrap();
+        assert_eq!((i.text.as_str(), i.lineno), ("i", 4));
+    }
+}
@@ -6,16 +6,133 @@ pub enum SemanticError {
         name: String,
         lineno: usize,
     },
+    /// `new C(args)` names no class in scope.
+    UndeclaredClass {
+        name: String,
+        lineno: usize,
+    },
     /// A variable was declared more than once in the same scope.
     RedeclaredVariable {
         name: String,
         lineno: usize,
     },
+    /// A declaration reuses the name of a predefined symbol such as
+    /// `System`.
+    PredefinedConflict {
+        name: String,
+        /// What the predefined symbol is, e.g. `class`.
+        kind: String,
+        lineno: usize,
+    },
+    /// A private member was used from outside its class.
+    AccessDenied {
+        /// The field or method name.
+        member: String,
+        /// The class that declares it.
+        class: String,
+        /// Line of the offending access.
+        lineno: usize,
+        /// Line of the member's declaration.
+        decl_lineno: usize,
+    },
+    /// A static method was called through an object (`p.f()`).
+    StaticCallViaInstance {
+        method: String,
+        class: String,
+        lineno: usize,
+    },
+    /// An instance method was called through a class name (`Point.f()`).
+    InstanceCallViaClass {
+        method: String,
+        class: String,
+        lineno: usize,
+    },
+    /// A native method was called with arguments its signature does not
+    /// take.
+    NativeCallMismatch {
+        /// `Class.method`.
+        method: String,
+        /// The parameter types, e.g. `(int, String)`.
+        expected: String,
+        /// The argument types.
+        found: String,
+        lineno: usize,
+    },
+    /// `new T[n]` with a constant `n` below zero.
+    NegativeArraySize {
+        size: i64,
+        lineno: usize,
+    },
+    /// Two labels of a switch have the same value, or it has two
+    /// `default`s.
+    DuplicateCaseLabel {
+        /// `case 3` or `default`.
+        label: String,
+        lineno: usize,
+        /// Line of the label it repeats.
+        first_lineno: usize,
+    },
+    /// A `case` label is not a constant `int`.
+    NonConstantCaseLabel {
+        lineno: usize,
+    },
+    /// A `static final` scalar field has no initializer, or one that is
+    /// not a constant expression.
+    NonConstantField {
+        name: String,
+        lineno: usize,
+    },
+    /// An `implements` clause names a class.
+    NotAnInterface {
+        name: String,
+        lineno: usize,
+    },
+    /// A class lacks a method of an interface it implements, or has it
+    /// with another signature.
+    MissingInterfaceMethod {
+        class: String,
+        interface: String,
+        /// The interface's method, e.g. `double area(double)`.
+        method: String,
+        lineno: usize,
+    },
+    /// A static method names an instance field or method of its class
+    /// without an object.
+    NonStaticReference {
+        /// `field` or `method`.
+        kind: String,
+        name: String,
+        method: String,
+        lineno: usize,
+    },
+    /// A static method uses `this`, which it does not have.
+    ThisInStaticMethod {
+        method: String,
+        lineno: usize,
+    },
+    /// A `final` variable or field is assigned after its declaration.
+    FinalAssignment {
+        name: String,
+        lineno: usize,
+    },
+    /// An `import` names no known class or package, e.g.
+    /// `java.util.Vector`.
+    UnknownImport {
+        name: String,
+        lineno: usize,
+    },
     /// A type could not be assigned to a declarator node.
     TypeAssignmentError {
         msg: String,
         lineno: usize,
     },
+    /// A node the analysis needs lacks the kids the grammar gives it — a
+    /// bug in the parser or in a pass that rewrote the tree, not in the
+    /// program.
+    MalformedTree {
+        problem: String,
+        lineno: usize,
+    },
 }
 
 impl std::fmt::Display for SemanticError {
@@ -23,10 +140,144 @@ impl std::fmt::Display for SemanticError {
         match self {
             SemanticError::UndeclaredVariable { name, lineno } =>
                 write!(f, "line {}: undeclared variable '{}'", lineno, name),
+            SemanticError::UndeclaredClass { name, lineno } =>
+                write!(f, "line {}: undeclared class '{}'", lineno, name),
             SemanticError::RedeclaredVariable { name, lineno } =>
                 write!(f, "line {}: redeclared variable '{}'", lineno, name),
+            SemanticError::PredefinedConflict { name, kind, lineno } =>
+                write!(f, "line {}: '{}' conflicts with the predefined {} {}", lineno, name, kind, name),
+            SemanticError::AccessDenied { member, class, lineno, decl_lineno } =>
+                write!(f, "line {}: '{}' has private access in '{}' (declared on line {})",
+                    lineno, member, class, decl_lineno),
+            SemanticError::StaticCallViaInstance { method, class, lineno } =>
+                write!(f, "line {}: static method '{}' should be called as '{}.{}(...)', not through an object",
+                    lineno, method, class, method),
+            SemanticError::InstanceCallViaClass { method, class, lineno } =>
+                write!(f, "line {}: instance method '{}' of '{}' needs an object, not the class name",
+                    lineno, method, class),
+            SemanticError::NativeCallMismatch { method, expected, found, lineno } =>
+                write!(f, "line {}: native method '{}' takes {}, not {}", lineno, method, expected, found),
+            SemanticError::NegativeArraySize { size, lineno } =>
+                write!(f, "line {}: array size {} is negative", lineno, size),
+            SemanticError::DuplicateCaseLabel { label, lineno, first_lineno } =>
+                write!(f, "line {}: duplicate {} in switch (first on line {})", lineno, label, first_lineno),
+            SemanticError::NonConstantCaseLabel { lineno } =>
+                write!(f, "line {}: case label must be a constant int", lineno),
+            SemanticError::NonConstantField { name, lineno } =>
+                write!(f, "line {}: constant '{}' must be initialized with a constant expression", lineno, name),
+            SemanticError::NotAnInterface { name, lineno } =>
+                write!(f, "line {}: '{}' is not an interface", lineno, name),
+            SemanticError::MissingInterfaceMethod { class, interface, method, lineno } =>
+                write!(f, "line {}: class '{}' does not implement '{}' of interface '{}'",
+                    lineno, class, method, interface),
+            SemanticError::NonStaticReference { kind, name, method, lineno } =>
+                write!(f, "line {}: non-static {} '{}' cannot be referenced from static method '{}'",
+                    lineno, kind, name, method),
+            SemanticError::ThisInStaticMethod { method, lineno } =>
+                write!(f, "line {}: 'this' cannot be used in static method '{}'", lineno, method),
+            SemanticError::FinalAssignment { name, lineno } =>
+                write!(f, "line {}: cannot assign a value to final variable '{}'", lineno, name),
+            SemanticError::UnknownImport { name, lineno } =>
+                write!(f, "line {}: cannot find '{}' to import", lineno, name),
             SemanticError::TypeAssignmentError { msg, lineno } =>
                 write!(f, "line {}: type assignment error: {}", lineno, msg),
+            SemanticError::MalformedTree { problem, lineno } =>
+                write!(f, "line {}: internal error: malformed tree: {}", lineno, problem),
         }
     }
-}
\ No newline at end of file
+}
+impl SemanticError {
+    /// The line the error is reported on.
+    pub fn lineno(&self) -> usize {
+        match self {
+            SemanticError::UndeclaredVariable { lineno, .. }
+            | SemanticError::UndeclaredClass { lineno, .. }
+            | SemanticError::RedeclaredVariable { lineno, .. }
+            | SemanticError::PredefinedConflict { lineno, .. }
+            | SemanticError::AccessDenied { lineno, .. }
+            | SemanticError::StaticCallViaInstance { lineno, .. }
+            | SemanticError::InstanceCallViaClass { lineno, .. }
+            | SemanticError::NativeCallMismatch { lineno, .. }
+            | SemanticError::NegativeArraySize { lineno, .. }
+            | SemanticError::DuplicateCaseLabel { lineno, .. }
+            | SemanticError::NonConstantCaseLabel { lineno }
+            | SemanticError::NonConstantField { lineno, .. }
+            | SemanticError::NotAnInterface { lineno, .. }
+            | SemanticError::MissingInterfaceMethod { lineno, .. }
+            | SemanticError::NonStaticReference { lineno, .. }
+            | SemanticError::ThisInStaticMethod { lineno, .. }
+            | SemanticError::FinalAssignment { lineno, .. }
+            | SemanticError::UnknownImport { lineno, .. }
+            | SemanticError::TypeAssignmentError { lineno, .. }
+            | SemanticError::MalformedTree { lineno, .. } => *lineno,
+        }
+    }
+
+    /// The diagnostic code, e.g. `undeclared-variable`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            SemanticError::UndeclaredVariable { .. }    => "undeclared-variable",
+            SemanticError::UndeclaredClass { .. }       => "undeclared-class",
+            SemanticError::RedeclaredVariable { .. }    => "redeclared-variable",
+            SemanticError::PredefinedConflict { .. }    => "predefined-conflict",
+            SemanticError::AccessDenied { .. }          => "private-access",
+            SemanticError::StaticCallViaInstance { .. } => "static-call-via-instance",
+            SemanticError::InstanceCallViaClass { .. }  => "instance-call-via-class",
+            SemanticError::NativeCallMismatch { .. }    => "native-call-mismatch",
+            SemanticError::NegativeArraySize { .. }     => "negative-array-size",
+            SemanticError::DuplicateCaseLabel { .. }    => "duplicate-case-label",
+            SemanticError::NonConstantCaseLabel { .. }  => "non-constant-case-label",
+            SemanticError::NonConstantField { .. }      => "non-constant-field",
+            SemanticError::NotAnInterface { .. }        => "not-an-interface",
+            SemanticError::MissingInterfaceMethod { .. } => "missing-interface-method",
+            SemanticError::NonStaticReference { .. }    => "non-static-reference",
+            SemanticError::ThisInStaticMethod { .. }    => "this-in-static-method",
+            SemanticError::FinalAssignment { .. }       => "final-assignment",
+            SemanticError::UnknownImport { .. }         => "unknown-import",
+            SemanticError::TypeAssignmentError { .. }   => "type-assignment",
+            SemanticError::MalformedTree { .. }         => "malformed-tree",
+        }
+    }
+
+    /// The values the message shows, by name, for a translation of it.
+    pub fn args(&self) -> Vec<(&'static str, String)> {
+        match self {
+            SemanticError::UndeclaredVariable { name, .. }
+            | SemanticError::UndeclaredClass { name, .. }
+            | SemanticError::RedeclaredVariable { name, .. }
+            | SemanticError::NonConstantField { name, .. }
+            | SemanticError::NotAnInterface { name, .. }
+            | SemanticError::FinalAssignment { name, .. }
+            | SemanticError::UnknownImport { name, .. } => vec![("name", name.clone())],
+            SemanticError::PredefinedConflict { name, kind, .. } =>
+                vec![("name", name.clone()), ("kind", kind.clone())],
+            SemanticError::AccessDenied { member, class, decl_lineno, .. } => vec![
+                ("member", member.clone()),
+                ("class", class.clone()),
+                ("decl_line", decl_lineno.to_string()),
+            ],
+            SemanticError::StaticCallViaInstance { method, class, .. }
+            | SemanticError::InstanceCallViaClass { method, class, .. } =>
+                vec![("method", method.clone()), ("class", class.clone())],
+            SemanticError::NativeCallMismatch { method, expected, found, .. } => vec![
+                ("method", method.clone()),
+                ("expected", expected.clone()),
+                ("found", found.clone()),
+            ],
+            SemanticError::NegativeArraySize { size, .. } => vec![("size", size.to_string())],
+            SemanticError::DuplicateCaseLabel { label, first_lineno, .. } =>
+                vec![("label", label.clone()), ("first_line", first_lineno.to_string())],
+            SemanticError::NonConstantCaseLabel { .. } => vec![],
+            SemanticError::MissingInterfaceMethod { class, interface, method, .. } => vec![
+                ("class", class.clone()),
+                ("interface", interface.clone()),
+                ("method", method.clone()),
+            ],
+            SemanticError::NonStaticReference { kind, name, method, .. } =>
+                vec![("kind", kind.clone()), ("name", name.clone()), ("method", method.clone())],
+            SemanticError::ThisInStaticMethod { method, .. } => vec![("method", method.clone())],
+            SemanticError::TypeAssignmentError { msg, .. } => vec![("msg", msg.clone())],
+            SemanticError::MalformedTree { problem, .. } => vec![("problem", problem.clone())],
+        }
+    }
+}
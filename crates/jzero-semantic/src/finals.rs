@@ -0,0 +1,75 @@
+//! Final variables — assigned once, where they are declared.
+//!
+//! Runs after `check_type`, when the base of every `base.field` has a
+//! type.  An assignment, `=` or compound, whose target is a `final` local
+//! or field, its own class's or through an object, is a
+//! [`FinalAssignment`](SemanticError::FinalAssignment) error.  A `static
+//! final` named constant is final too.  The initializer of a declaration
+//! is not an assignment, and the elements of a final array may change.
+
+use jzero_ast::tree::Tree;
+use jzero_symtab::entry::SymbolKind;
+
+use crate::access::resolve_member;
+use crate::error::SemanticError;
+
+/// Check every assignment in `tree`.
+pub fn check_final_assignments(tree: &Tree, errors: &mut Vec<SemanticError>) {
+    if tree.sym == "Assignment"
+        && let Some(target) = tree.kids.first()
+        && let Some((name, lineno)) = final_target(target)
+    {
+        errors.push(SemanticError::FinalAssignment { name, lineno });
+    }
+    for kid in &tree.kids {
+        check_final_assignments(kid, errors);
+    }
+}
+
+/// The name and line of `target` if it names a final local or field.
+fn final_target(target: &Tree) -> Option<(String, usize)> {
+    let (leaf, entry) = match target.sym.as_str() {
+        "FieldAccess" => (target.kids.get(1)?, resolve_member(target)?.1),
+        _ => {
+            let name = &target.tok.as_ref()?.text;
+            (target, target.stab.as_ref()?.borrow().lookup(name)?)
+        }
+    };
+    let tok = leaf.tok.as_ref()?;
+    let is_variable = matches!(entry.kind, SymbolKind::Local | SymbolKind::Field);
+    (is_variable && entry.is_const).then(|| (tok.text.clone(), tok.lineno))
+}
+
+// ─── Tests ───────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::extended_errors as errors;
+
+    #[test]
+    fn finals_are_assigned_only_where_declared() {
+        let errs = errors(r#"
+public class Limits {
+    public static final int MAX = 10;
+    private final int size = 4;
+    int used;
+    public void fill() {
+        final int half = size / 2;
+        final int xs[] = {1, 2};
+        used = half;
+        xs[0] = half;
+        half = 1;
+        size += 1;
+        this.size = 2;
+        MAX = 11;
+    }
+}
+"#);
+        assert_eq!(errs, [
+            "line 11: cannot assign a value to final variable 'half'",
+            "line 12: cannot assign a value to final variable 'size'",
+            "line 13: cannot assign a value to final variable 'size'",
+            "line 14: cannot assign a value to final variable 'MAX'",
+        ]);
+    }
+}
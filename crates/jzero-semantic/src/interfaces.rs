@@ -0,0 +1,130 @@
+//! Interfaces — a class provides every method of the interfaces it
+//! implements.
+//!
+//! Runs after the symbol tables are built.  Each name in a class's
+//! `implements` list must be an interface in the global scope, declared
+//! earlier as another file is.  For every method of the interface the
+//! class must have an instance method of that name, its own or inherited,
+//! with the same parameter and return types; otherwise a
+//! [`MissingInterfaceMethod`](SemanticError::MissingInterfaceMethod) error
+//! names the method the interface wants.  Interface methods are not
+//! `static`, so a `static` method does not implement one.
+
+use jzero_ast::tree::Tree;
+use jzero_symtab::TypeInfo;
+use jzero_symtab::entry::SymbolKind;
+
+use crate::builder::class_members_start;
+use crate::error::SemanticError;
+
+/// Check the `implements` list of the class `tree` declares, if any.
+pub fn check_interfaces(tree: &Tree, errors: &mut Vec<SemanticError>) {
+    if tree.sym != "ClassDecl" { return; }
+    let Some(list) = tree.kids[1..class_members_start(tree)].iter().find(|k| k.sym == "InterfaceList") else {
+        return;
+    };
+    let (Some(class), Some(class_scope)) = (tree.kids[0].tok.as_ref(), tree.stab.as_ref()) else { return };
+
+    for name in list.kids.iter().filter_map(|k| k.tok.as_ref()) {
+        let entry = class_scope.borrow().lookup(&name.text);
+        let interface = match entry {
+            Some(e) if e.kind == SymbolKind::Interface => e,
+            Some(_) => {
+                errors.push(SemanticError::NotAnInterface { name: name.text.clone(), lineno: name.lineno });
+                continue;
+            }
+            None => {
+                errors.push(SemanticError::UndeclaredClass { name: name.text.clone(), lineno: name.lineno });
+                continue;
+            }
+        };
+        let Some(wanted) = interface.st else { continue };
+        for method in wanted.borrow().entries_of_kind(SymbolKind::Method) {
+            let Some(typ) = &method.typ else { continue };
+            let provided = class_scope.borrow().lookup(&method.sym)
+                .filter(|e| e.kind == SymbolKind::Method && !e.is_static)
+                .and_then(|e| e.typ)
+                .is_some_and(|t| same_signature(&t, typ));
+            if !provided {
+                errors.push(SemanticError::MissingInterfaceMethod {
+                    class: class.text.clone(),
+                    interface: name.text.clone(),
+                    method: format!("{}{}", method.sym, typ.display()),
+                    lineno: class.lineno,
+                });
+            }
+        }
+    }
+}
+
+/// Whether two method types take and return the same types; parameter
+/// names do not matter.
+fn same_signature(a: &TypeInfo, b: &TypeInfo) -> bool {
+    a.display().to_string() == b.display().to_string()
+}
+
+// ─── Tests ───────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use jzero_lexer::{Level, LexOptions};
+    use jzero_parser::parse_tree_with;
+    use jzero_symtab::{SymTab, build_predefined};
+
+    use crate::analyze_in;
+
+    const SHAPE: &str = r#"
+public interface Shape {
+    double area(double w, double h);
+    String name();
+}
+"#;
+
+    /// Analyze `SHAPE` and then `src` in one global scope, as one project.
+    fn errors_with_shape(src: &str) -> Vec<String> {
+        let extended = || LexOptions::at_level(Level::Extended);
+        let global = SymTab::new("global", None).into_rc();
+        build_predefined(&global);
+        let mut shape = parse_tree_with(SHAPE, extended()).expect("parse failed");
+        let first = analyze_in(&mut shape, Rc::clone(&global));
+        assert!(first.errors.is_empty(), "{:?}", first.errors);
+
+        let mut tree = parse_tree_with(src, extended()).expect("parse failed");
+        analyze_in(&mut tree, global).errors.iter().map(|e| e.to_string()).collect()
+    }
+
+    #[test]
+    fn a_class_providing_every_method_passes() {
+        let errs = errors_with_shape(r#"
+public class Rect implements Shape {
+    public double area(double width, double height) { return width * height; }
+    public String name() { return "rect"; }
+}
+"#);
+        assert!(errs.is_empty(), "{:?}", errs);
+    }
+
+    #[test]
+    fn missing_and_mismatched_methods_are_named() {
+        let errs = errors_with_shape(r#"
+public class Rect implements Shape {
+    public int area(double w, double h) { return 0; }
+    public static String name() { return "rect"; }
+}
+"#);
+        assert_eq!(errs, [
+            "line 2: class 'Rect' does not implement 'area(double,double)->double' of interface 'Shape'",
+            "line 2: class 'Rect' does not implement 'name()->String' of interface 'Shape'",
+        ]);
+    }
+
+    #[test]
+    fn only_declared_interfaces_can_be_implemented() {
+        let errs = errors_with_shape("public class Rect implements Shape, Missing, Rect { }");
+        assert_eq!(errs.len(), 4, "{:?}", errs);
+        assert_eq!(errs[2], "line 1: undeclared class 'Missing'");
+        assert_eq!(errs[3], "line 1: 'Rect' is not an interface");
+    }
+}
@@ -1,20 +1,38 @@
+pub mod access;
 pub mod builder;
+pub mod callsite;
 pub mod calctype;
 pub mod checktype;
+pub mod constant;
+pub mod desugar;
 pub mod error;
+pub mod finals;
+pub mod interfaces;
 pub mod mkcls;
+pub mod rewrite;
+pub mod statics;
 pub mod typeinit;
 mod tests;
 
+pub use access::check_access;
 pub use builder::build_symtabs;
+pub use callsite::check_call_sites;
 pub use calctype::{calc_type, assign_type};
 pub use checktype::{check_type, TypeCheckResult};
+pub use constant::{case_value, check_array_sizes, check_case_labels, constant_names, define, eval_const, eval_const_with, ConstValue};
+pub use desugar::desugar;
 pub use error::SemanticError;
+pub use finals::check_final_assignments;
+pub use interfaces::check_interfaces;
 pub use mkcls::mkcls;
+pub use rewrite::{Rewriter, Rule, RewriteError};
+pub use statics::check_static_contexts;
 pub use typeinit::assign_leaf_types;
 
 use jzero_ast::tree::Tree;
-use jzero_symtab::{SymTab, build_predefined};
+use jzero_ast::{CancellationToken, Cancelled};
+use jzero_symtab::{FileId, MethodType, SymTab, SymbolOrigin, build_predefined};
+use std::collections::HashMap;
 use std::rc::Rc;
 use std::cell::RefCell;
 
@@ -23,30 +41,148 @@ pub struct SemanticResult {
     pub global: Rc<RefCell<SymTab>>,
     pub errors: Vec<SemanticError>,
     pub type_checks: Vec<TypeCheckResult>,
+    /// The length of each `new T[n]` whose size is a constant, by the id
+    /// of its `ArrayCreation` node, and of each `{..}` initializer list, by
+    /// the id of its `ArrayInit`.
+    pub array_sizes: HashMap<u32, i64>,
+    /// The value of each use of a named constant, a `static final` field,
+    /// by the id of its IDENTIFIER leaf.
+    pub constants: HashMap<u32, ConstValue>,
 }
 
 /// Run full semantic analysis on a parsed syntax tree.
 ///
 /// Passes in order:
 /// 1. Build global scope + predefined symbols
-/// 2. Assign types to literal/operator leaves          (Phase 3)
-/// 3. Build symbol tables + declaration types          (Phase 4)
-/// 4. Build full ClassType for every ClassDecl         (mkcls)
-/// 5. Check expression types in method bodies          (Phase 5)
+/// 2. Desugar the surface tree to core Jzero          (desugar)
+/// 3. Assign types to literal/operator leaves          (Phase 3)
+/// 4. Build symbol tables + declaration types          (Phase 4)
+/// 5. Build full ClassType for every ClassDecl         (mkcls)
+/// 6. Check expression types in method bodies          (Phase 5)
+/// 7. Reject outside uses of private members           (access)
+/// 8. Match static/instance calls to their receiver,
+///    and `new C(..)` to a class in scope              (callsite)
+/// 9. Check a class provides the methods of the
+///    interfaces it implements                         (interfaces)
+/// 10. Reject instance members and `this` in static
+///     methods                                         (statics)
+/// 11. Reject assignments to final variables           (finals)
+/// 12. Renumber the nodes of the rewritten tree
+/// 13. Check constant array sizes and case labels, and
+///     evaluate named constants                        (constant)
 pub fn analyze(tree: &mut Tree) -> SemanticResult {
     let global = SymTab::new("global", None).into_rc();
     build_predefined(&global);
+    analyze_in(tree, global)
+}
+
+/// Like [`analyze`], but in a caller-supplied global scope.
+///
+/// Used when the program can see symbols from outside its own file —
+/// classes in packages (see [`jzero_symtab::declare_package`]) or other
+/// compilation units.  The caller is responsible for the predefined
+/// symbols.
+pub fn analyze_in(tree: &mut Tree, global: Rc<RefCell<SymTab>>) -> SemanticResult {
+    match run_passes(tree, global, FileId::default(), None) {
+        Ok(result) => result,
+        Err(Cancelled) => unreachable!("analysis without a token cannot be cancelled"),
+    }
+}
 
+/// Like [`analyze`], but give up between passes once `cancel` is
+/// cancelled.  The tree may then be partly rewritten; discard it.
+///
+/// # Errors
+/// Returns [`Cancelled`] if the token was cancelled before the last pass.
+pub fn analyze_cancellable(tree: &mut Tree, cancel: &CancellationToken) -> Result<SemanticResult, Cancelled> {
+    let global = SymTab::new("global", None).into_rc();
+    build_predefined(&global);
+    run_passes(tree, global, FileId::default(), Some(cancel))
+}
+
+/// Like [`analyze`], for file `file` of a compilation: the symbols the
+/// tree declares get [`SymbolOrigin::UserFile`]`(file)` as origin, not
+/// file 0.  With a `cancel` token, gives up as [`analyze_cancellable`].
+///
+/// # Errors
+/// Returns [`Cancelled`] if the token was cancelled before the last pass.
+pub fn analyze_file(tree: &mut Tree, file: FileId, cancel: Option<&CancellationToken>) -> Result<SemanticResult, Cancelled> {
+    let global = SymTab::new("global", None).into_rc();
+    build_predefined(&global);
+    run_passes(tree, global, file, cancel)
+}
+
+/// Like [`analyze_file`], with the native methods `natives`, by qualified
+/// name, declared next to the predefined symbols (see
+/// [`jzero_symtab::declare_native`]).  A native that cannot be declared is
+/// left out.
+///
+/// # Errors
+/// Returns [`Cancelled`] if the token was cancelled before the last pass.
+pub fn analyze_file_with_natives(
+    tree: &mut Tree,
+    file: FileId,
+    natives: &[(String, MethodType)],
+    cancel: Option<&CancellationToken>,
+) -> Result<SemanticResult, Cancelled> {
+    let global = SymTab::new("global", None).into_rc();
+    build_predefined(&global);
+    for (name, signature) in natives {
+        let _ = jzero_symtab::declare_native(&global, name, signature.clone());
+    }
+    run_passes(tree, global, file, cancel)
+}
+
+/// Stamp the class `tree` declares, and all in it, as from `file`.
+fn set_file(tree: &Tree, global: &Rc<RefCell<SymTab>>, file: FileId) {
+    let Some(name) = tree.kids.first().and_then(|k| k.tok.as_ref()) else { return };
+    if let Some(class) = global.borrow_mut().lookup_local_mut(&name.text)
+        && class.lineno == name.lineno
+        && !class.origin.is_predefined()
+    {
+        class.set_origin(SymbolOrigin::UserFile(file));
+    }
+}
+
+fn run_passes(
+    tree: &mut Tree,
+    global: Rc<RefCell<SymTab>>,
+    file: FileId,
+    cancel: Option<&CancellationToken>,
+) -> Result<SemanticResult, Cancelled> {
+    let check = || cancel.map_or(Ok(()), CancellationToken::check);
+
+    check()?;
+    desugar(tree);
+    debug_assert!(tree.validate().is_ok(), "malformed tree: {:?}", tree.validate());
     assign_leaf_types(tree);
 
     let mut errors = Vec::new();
+    check()?;
     build_symtabs(tree, Rc::clone(&global), &mut errors);
+    if file != FileId::default() {
+        set_file(tree, &global, file);
+    }
 
     // Build ClassType entries so InstanceCreation can look them up
     mkcls(tree);
 
     let mut type_checks = Vec::new();
+    check()?;
     check_type(tree, false, &mut type_checks);
 
-    SemanticResult { global, errors, type_checks }
-}
\ No newline at end of file
+    check()?;
+    check_access(tree, &mut errors);
+    check_call_sites(tree, &mut errors);
+    check_interfaces(tree, &mut errors);
+    check_static_contexts(tree, &mut errors);
+    check_final_assignments(tree, &mut errors);
+
+    // Desugaring adds nodes; give them IDs codegen can tell apart.
+    tree.number_nodes();
+    let array_sizes = check_array_sizes(tree, &mut errors);
+    check_case_labels(tree, &mut errors);
+    let constants = constant_names(tree, &mut errors);
+
+    Ok(SemanticResult { global, errors, type_checks, array_sizes, constants })
+}
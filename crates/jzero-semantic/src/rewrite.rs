@@ -0,0 +1,295 @@
+//! Tree rewriting — pattern → replacement transformations over `Tree`.
+//!
+//! A [`Rewriter`] holds an ordered list of [`Rule`]s.  Each rule is a
+//! pattern (a predicate on a node) and a replacement (a function building
+//! the new subtree from the matched one).  Rewriting is a single post-order
+//! pass: children are rewritten before their parent is matched, and a
+//! replacement is never matched again in the same pass, so rules that
+//! produce a node of the kind they match cannot loop.
+//!
+//! [`Rewriter::rewrite_checked`] validates the rewritten tree and re-runs
+//! name resolution on it, and rejects the whole rewrite if a rule built a
+//! malformed node (see [`Tree::validate`]) or it introduces new resolution
+//! errors — the safety net for quick fixes and refactorings.  In debug
+//! builds [`Rewriter::rewrite`] panics on a malformed replacement.
+//!
+//! ```text
+//! x += e;   ──compound_assign──▶   x = x + e;
+//! ```
+
+use jzero_ast::tree::{InvalidTree, Tree};
+use jzero_symtab::{SymTab, build_predefined};
+
+use crate::builder::build_symtabs;
+use crate::error::SemanticError;
+
+// ─── Rule ─────────────────────────────────────────────────────────────────────
+
+type Pattern     = Box<dyn Fn(&Tree) -> bool>;
+type Replacement = Box<dyn Fn(&Tree) -> Tree>;
+
+/// One named pattern → replacement transformation.
+pub struct Rule {
+    pub name: String,
+    pattern: Pattern,
+    replace: Replacement,
+}
+
+impl Rule {
+    pub fn new(
+        name: &str,
+        pattern: impl Fn(&Tree) -> bool + 'static,
+        replace: impl Fn(&Tree) -> Tree + 'static,
+    ) -> Self {
+        Rule {
+            name: name.to_string(),
+            pattern: Box::new(pattern),
+            replace: Box::new(replace),
+        }
+    }
+
+    pub fn matches(&self, tree: &Tree) -> bool {
+        (self.pattern)(tree)
+    }
+}
+
+// ─── Error ────────────────────────────────────────────────────────────────────
+
+/// A checked rewrite that was rolled back because it broke the tree or
+/// name resolution.
+#[derive(Debug, Clone)]
+pub struct RewriteError {
+    /// The malformed node a rule built, if any.
+    pub invalid: Option<InvalidTree>,
+    /// Resolution errors present after the rewrite but not before.
+    pub errors: Vec<SemanticError>,
+}
+
+impl std::fmt::Display for RewriteError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "rewrite rejected:")?;
+        if let Some(invalid) = &self.invalid {
+            write!(f, " {};", invalid)?;
+        }
+        for e in &self.errors {
+            write!(f, " {};", e)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for RewriteError {}
+
+// ─── Rewriter ─────────────────────────────────────────────────────────────────
+
+#[derive(Default)]
+pub struct Rewriter {
+    rules: Vec<Rule>,
+}
+
+impl Rewriter {
+    pub fn new() -> Self {
+        Rewriter::default()
+    }
+
+    /// Append a rule.  When several rules match a node, the first wins.
+    pub fn rule(mut self, rule: Rule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    /// Rewrite `tree` in place and return the number of replacements made.
+    pub fn rewrite(&self, tree: &mut Tree) -> usize {
+        self.apply(tree, cfg!(debug_assertions))
+    }
+
+    /// [`Rewriter::rewrite`], panicking on a malformed replacement if
+    /// `validate` is set.
+    fn apply(&self, tree: &mut Tree, validate: bool) -> usize {
+        let mut count = 0;
+        for kid in &mut tree.kids {
+            count += self.apply(kid, validate);
+        }
+        if let Some(rule) = self.rules.iter().find(|r| r.matches(tree)) {
+            *tree = (rule.replace)(tree);
+            if validate && let Err(e) = tree.validate() {
+                panic!("rule {} built a malformed tree: {}", rule.name, e);
+            }
+            count += 1;
+        }
+        count
+    }
+
+    /// Rewrite `tree`, then re-run name resolution on the result.
+    ///
+    /// If the rewritten tree is malformed, or has resolution errors the
+    /// original did not, `tree` is left untouched and the problem is
+    /// returned.
+    pub fn rewrite_checked(&self, tree: &mut Tree) -> Result<usize, RewriteError> {
+        let before = resolution_errors(tree);
+        let mut candidate = tree.clone();
+        let count = self.apply(&mut candidate, false);
+        if let Err(invalid) = candidate.validate() {
+            return Err(RewriteError { invalid: Some(invalid), errors: vec![] });
+        }
+        let after = resolution_errors(&candidate);
+
+        let before: Vec<String> = before.iter().map(|e| e.to_string()).collect();
+        let new_errors: Vec<SemanticError> = after.into_iter()
+            .filter(|e| !before.contains(&e.to_string()))
+            .collect();
+        if !new_errors.is_empty() {
+            return Err(RewriteError { invalid: None, errors: new_errors });
+        }
+        *tree = candidate;
+        Ok(count)
+    }
+}
+
+/// Build fresh symbol tables for a copy of `tree` and return the errors.
+fn resolution_errors(tree: &Tree) -> Vec<SemanticError> {
+    let mut copy = tree.clone();
+    let global = SymTab::new("global", None).into_rc();
+    build_predefined(&global);
+    let mut errors = Vec::new();
+    build_symtabs(&mut copy, global, &mut errors);
+    errors
+}
+
+// ─── Stock rules ──────────────────────────────────────────────────────────────
+
+/// The binary operator of a compound assignment category, as the sym and
+/// rule of the expression it builds and the operator leaf's category and
+/// text.
+fn compound_operator(category: &str) -> Option<(&'static str, i32, &'static str, &'static str)> {
+    Some(match category {
+        "PLUSASSIGN"    => ("AddExpr", 0, "PLUS", "+"),
+        "MINUSASSIGN"   => ("AddExpr", 1, "MINUS", "-"),
+        "STARASSIGN"    => ("MulExpr", 0, "STAR", "*"),
+        "SLASHASSIGN"   => ("MulExpr", 1, "SLASH", "/"),
+        "PERCENTASSIGN" => ("MulExpr", 2, "PERCENT", "%"),
+        _ => return None,
+    })
+}
+
+/// `lhs op= rhs` → `lhs = lhs op rhs`, for each of `+= -= *= /= %=`.
+pub fn compound_assign() -> Rule {
+    Rule::new(
+        "compound-assign",
+        |t| t.sym == "Assignment" && t.kids.len() == 3
+            && t.kids[1].tok.as_ref().and_then(|k| compound_operator(&k.category)).is_some(),
+        |t| {
+            let op = t.kids[1].tok.as_ref().unwrap();
+            let (sym, rule, cat, text) = compound_operator(&op.category).unwrap();
+            let lhs = t.kids[0].clone();
+            let bin = Tree::new(sym, rule, vec![
+                lhs.clone(),
+                Tree::leaf(cat, text, op.lineno),
+                t.kids[2].clone(),
+            ]);
+            Tree::new("Assignment", 0, vec![lhs, Tree::leaf("ASSIGN", "=", op.lineno), bin])
+        },
+    )
+}
+
+// ─── Tests ───────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(src: &str) -> Tree {
+        jzero_parser::parse_tree(src).expect("parse failed")
+    }
+
+    fn find<'a>(t: &'a Tree, sym: &str) -> Option<&'a Tree> {
+        if t.sym == sym { return Some(t); }
+        t.kids.iter().find_map(|k| find(k, sym))
+    }
+
+    const COMPOUND: &str = r#"
+public class T {
+    public static void main(String argv[]) {
+        int x;
+        x = 1;
+        x += 2;
+        x -= 3;
+        x *= 4;
+        x /= 5;
+        x %= 6;
+    }
+}
+"#;
+
+    #[test]
+    fn compound_assign_is_desugared() {
+        let mut tree = parse(COMPOUND);
+        let n = Rewriter::new().rule(compound_assign()).rewrite(&mut tree);
+        assert_eq!(n, 5);
+        let text = tree.to_text(0);
+        for op in ["+=", "-=", "*=", "/=", "%="] {
+            assert!(!text.contains(op), "{} left in", op);
+        }
+        let rem = find(&tree, "MulExpr").expect("MulExpr not produced");
+        assert_eq!(rem.kids[1].tok.as_ref().unwrap().text, "*");
+        let add = find(&tree, "AddExpr").expect("AddExpr not produced");
+        assert_eq!(add.kids[0].tok.as_ref().unwrap().text, "x");
+    }
+
+    #[test]
+    fn unmatched_tree_is_unchanged() {
+        let mut tree = parse(COMPOUND);
+        let before = tree.to_text(0);
+        let never = Rule::new("never", |_| false, |t| t.clone());
+        assert_eq!(Rewriter::new().rule(never).rewrite(&mut tree), 0);
+        assert_eq!(tree.to_text(0), before);
+    }
+
+    #[test]
+    fn checked_rewrite_accepts_safe_change() {
+        let mut tree = parse(COMPOUND);
+        let n = Rewriter::new().rule(compound_assign()).rewrite_checked(&mut tree).unwrap();
+        assert_eq!(n, 5);
+    }
+
+    #[test]
+    fn checked_rewrite_rejects_redeclaration() {
+        let mut tree = parse(COMPOUND);
+        let before = tree.to_text(0);
+        // Declare every local twice — blocks share the method scope, so the
+        // second `int x;` collides with the first.
+        let dup = Rule::new(
+            "dup-local",
+            |t| t.sym == "LocalVarDecl",
+            |t| Tree::new("Block", 0, vec![t.clone(), t.clone()]),
+        );
+        let err = Rewriter::new().rule(dup).rewrite_checked(&mut tree).unwrap_err();
+        assert!(err.to_string().contains("redeclared variable 'x'"));
+        assert_eq!(tree.to_text(0), before, "tree must be left untouched");
+    }
+
+    fn drop_operand() -> Rule {
+        Rule::new(
+            "drop-operand",
+            |t| t.sym == "AddExpr",
+            |t| { let mut t = t.clone(); t.remove_kid(2); t },
+        )
+    }
+
+    #[test]
+    fn checked_rewrite_rejects_malformed_node() {
+        let mut tree = parse("public class T { public static void main(String argv[]) { int x; x = x + 1; } }");
+        let err = Rewriter::new().rule(drop_operand()).rewrite_checked(&mut tree).unwrap_err();
+        assert!(err.errors.is_empty());
+        assert_eq!(err.invalid.unwrap().problem, "addition needs 3 kids, has 2");
+        assert!(find(&tree, "AddExpr").is_some_and(|t| t.kids.len() == 3));
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "rule drop-operand built a malformed tree")]
+    fn malformed_replacement_panics_in_debug_builds() {
+        let mut tree = parse("public class T { public static void main(String argv[]) { int x; x = x + 1; } }");
+        Rewriter::new().rule(drop_operand()).rewrite(&mut tree);
+    }
+}
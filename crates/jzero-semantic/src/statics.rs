@@ -0,0 +1,122 @@
+//! Static contexts — a `static` method has no object of its class.
+//!
+//! Runs after the symbol tables are built.  In the body of a static method
+//! a name that resolves to an instance field or instance method of the
+//! class, its own or inherited, is a
+//! [`NonStaticReference`](SemanticError::NonStaticReference), and `this` is
+//! a [`ThisInStaticMethod`](SemanticError::ThisInStaticMethod) error.  The
+//! member of `base.member` is looked up in the class of `base`, so only
+//! names standing alone are checked; calling an instance method through
+//! the class name is left to `callsite`.
+
+use jzero_ast::tree::Tree;
+use jzero_symtab::entry::SymbolKind;
+
+use crate::error::SemanticError;
+
+/// Check the static methods of every class in `tree`.
+pub fn check_static_contexts(tree: &Tree, errors: &mut Vec<SemanticError>) {
+    if tree.sym == "MethodDecl" {
+        let Some(header) = tree.kids.first().filter(|h| h.sym == "MethodHeader") else { return };
+        // An instance method's header adds 3 to its rule.
+        if header.rule >= 3 { return; }
+        let Some(name) = header.kids.get(1).and_then(|d| d.kids.first()).and_then(|n| n.tok.as_ref()) else { return };
+        if let Some(body) = tree.kids.get(1) {
+            walk(body, &name.text, errors);
+        }
+        return;
+    }
+    for kid in &tree.kids {
+        check_static_contexts(kid, errors);
+    }
+}
+
+fn walk(tree: &Tree, method: &str, errors: &mut Vec<SemanticError>) {
+    if let Some(tok) = &tree.tok {
+        match tok.category.as_str() {
+            "THIS" => errors.push(SemanticError::ThisInStaticMethod {
+                method: method.to_string(),
+                lineno: tok.lineno,
+            }),
+            "IDENTIFIER" => if let Some(kind) = instance_member(tree) {
+                errors.push(SemanticError::NonStaticReference {
+                    kind: kind.to_string(),
+                    name: tok.text.clone(),
+                    method: method.to_string(),
+                    lineno: tok.lineno,
+                });
+            },
+            _ => {}
+        }
+        return;
+    }
+    let member = match tree.sym.as_str() {
+        "FieldAccess" => Some(1),
+        "MethodCall" if tree.rule >= 2 => Some(1),
+        _ => None,
+    };
+    for (i, kid) in tree.kids.iter().enumerate() {
+        if Some(i) != member {
+            walk(kid, method, errors);
+        }
+    }
+}
+
+/// `field` or `method` if the name `leaf` resolves to an instance member
+/// of a class.
+fn instance_member(leaf: &Tree) -> Option<&'static str> {
+    let name = &leaf.tok.as_ref()?.text;
+    let entry = leaf.stab.as_ref()?.borrow().lookup(name)?;
+    if entry.is_static || entry.parent_st.borrow().scope != "class" {
+        return None;
+    }
+    match entry.kind {
+        SymbolKind::Field  => Some("field"),
+        SymbolKind::Method => Some("method"),
+        _ => None,
+    }
+}
+
+// ─── Tests ───────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::extended_errors as errors;
+
+    const COUNTER: &str = r#"
+public class Counter {
+    private int count;
+    public static int made;
+    public void add(int n) { count = count + n; this.count = this.count + 1; made = made + 1; }
+    public int get() { return this.count; }
+    public static int twice(int n) { return n + n; }
+"#;
+
+    #[test]
+    fn instance_methods_see_fields_and_this() {
+        let errs = errors(&format!("{}{}", COUNTER, "}\n"));
+        assert!(errs.is_empty(), "{:?}", errs);
+    }
+
+    #[test]
+    fn static_methods_cannot_use_instance_members() {
+        let errs = errors(&format!("{}{}", COUNTER, r#"
+    public static void main(String argv[]) {
+        int k;
+        k = twice(made);
+        count = 1;
+        add(2);
+        k = this.get();
+        Counter c;
+        c = new Counter();
+        c.add(k);
+    }
+}
+"#));
+        assert_eq!(errs, [
+            "line 12: non-static field 'count' cannot be referenced from static method 'main'",
+            "line 13: non-static method 'add' cannot be referenced from static method 'main'",
+            "line 14: 'this' cannot be used in static method 'main'",
+        ]);
+    }
+}
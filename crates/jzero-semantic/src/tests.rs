@@ -1,3 +1,12 @@
+/// The errors of `src`, parsed at the extended level, as strings — for
+/// the tests of the checks of extended-level constructs.
+#[cfg(test)]
+pub(crate) fn extended_errors(src: &str) -> Vec<String> {
+    let options = jzero_lexer::LexOptions::at_level(jzero_lexer::Level::Extended);
+    let mut tree = jzero_parser::parse_tree_with(src, options).expect("parse failed");
+    crate::analyze(&mut tree).errors.iter().map(|e| e.to_string()).collect()
+}
+
 #[cfg(test)]
 mod tests {
     use jzero_parser::parse_tree;
@@ -32,9 +41,10 @@ public class hello {
         let result = run(src);
         assert!(result.errors.is_empty(), "{:?}", result.errors);
         let g = result.global.borrow();
-        assert_eq!(g.len(), 2);
+        assert_eq!(g.len(), 4);
         assert!(g.lookup_local("hello").is_some());
         assert!(g.lookup_local("System").is_some());
+        assert!(g.lookup_local("Math").is_some());
     }
 
     #[test]
@@ -76,6 +86,29 @@ public class T {
         assert!(ms.lookup_local("y").is_some());
     }
 
+    #[test]
+    fn test_each_declarator_is_a_local() {
+        let src = r#"
+public class T {
+    int a, b[];
+    public static void main(String argv[]) {
+        int x, y[], z;
+        int w, x;
+    }
+}
+"#;
+        let result = run(src);
+        assert_eq!(result.errors.len(), 1, "{:?}", result.errors);
+        assert!(result.errors[0].to_string().contains("redeclared variable 'x'"), "{}", result.errors[0]);
+        let g = result.global.borrow();
+        let class_st = g.lookup_local("T").unwrap().st.clone().unwrap();
+        assert_eq!(class_st.borrow().lookup_local("b").unwrap().typ.as_ref().unwrap().to_string(), "int[]");
+        let method_st = class_st.borrow().lookup_local("main").cloned().unwrap().st.unwrap();
+        let ms = method_st.borrow();
+        let typ = |name: &str| ms.lookup_local(name).unwrap().typ.as_ref().unwrap().to_string();
+        assert_eq!((typ("x"), typ("y"), typ("z"), typ("w")), ("int".into(), "int[]".into(), "int".into(), "int".into()));
+    }
+
     #[test]
     fn test_redeclared_local_variable() {
         let src = r#"
@@ -312,7 +345,7 @@ public class T {
         let tc = checks(src);
         println!("\n=== array creation ===");
         for r in &tc { println!("{}", r); }
-        let assign = tc.iter().find(|r| r.operator == "=" && r.op1 == "array")
+        let assign = tc.iter().find(|r| r.operator == "=" && r.op1 == "int[]")
             .expect("array assignment check missing");
         assert!(assign.ok, "int[] = new int[3] should be OK");
     }
@@ -495,6 +528,70 @@ public class hello {
         assert_eq!(fail_add.op2, "String");
     }
 
+    // ═════════════════════════════════════════════════════════════════════════
+    // Package-qualified names
+    // ═════════════════════════════════════════════════════════════════════════
+
+    /// A global scope with `mypkg.util.Helper.compute(int) -> int`.
+    fn global_with_helper() -> std::rc::Rc<std::cell::RefCell<jzero_symtab::SymTab>> {
+        use std::rc::Rc;
+        use jzero_symtab::{Parameter, SymTab, SymTabEntry, TypeInfo, entry::SymbolKind};
+
+        let global = SymTab::new("global", None).into_rc();
+        jzero_symtab::build_predefined(&global);
+        let util = jzero_symtab::declare_package(&global, "mypkg.util");
+
+        let helper_st = SymTab::new("class", Some(Rc::clone(&global))).into_rc();
+        let compute_st = SymTab::new("method", Some(Rc::clone(&helper_st))).into_rc();
+        let mut compute = SymTabEntry::with_scope(
+            "compute", SymbolKind::Method, Rc::clone(&helper_st), false, compute_st,
+        );
+        compute.set_typ(TypeInfo::method(TypeInfo::int(), vec![Parameter::new("n", TypeInfo::int())]));
+        compute.is_static = true;
+        helper_st.borrow_mut().insert(compute).unwrap();
+        util.borrow_mut().insert(SymTabEntry::with_scope(
+            "Helper", SymbolKind::Class, Rc::clone(&util), false, helper_st,
+        )).unwrap();
+        global
+    }
+
+    #[test]
+    fn test_qualified_call_through_packages() {
+        let src = r#"
+public class T {
+    public static void main(String argv[]) {
+        int x;
+        x = mypkg.util.Helper.compute(2);
+    }
+}
+"#;
+        let mut tree = parse_tree(src).expect("parse failed");
+        let result = crate::analyze_in(&mut tree, global_with_helper());
+        assert!(result.errors.is_empty(), "{:?}", result.errors);
+        let tc = &result.type_checks;
+        let param = tc.iter().find(|r| r.operator == "param").expect("param check missing");
+        assert!(param.ok, "{}", param);
+        let assign = tc.iter().find(|r| r.operator == "=").expect("= missing");
+        assert!(assign.ok, "{}", assign);
+    }
+
+    #[test]
+    fn test_qualified_call_checks_arguments() {
+        let src = r#"
+public class T {
+    public static void main(String argv[]) {
+        int x;
+        x = mypkg.util.Helper.compute("two");
+    }
+}
+"#;
+        let mut tree = parse_tree(src).expect("parse failed");
+        let result = crate::analyze_in(&mut tree, global_with_helper());
+        let param = result.type_checks.iter().find(|r| r.operator == "param")
+            .expect("param check missing");
+        assert!(!param.ok, "String argument for int parameter should FAIL");
+    }
+
     // ═════════════════════════════════════════════════════════════════════════
     // Output format verification
     // ═════════════════════════════════════════════════════════════════════════
@@ -542,4 +639,379 @@ public class hello {
         );
         assert_eq!(r.to_string(), "line 7: typecheck param on a String and a String -> OK");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_cancelled_analysis_stops() {
+        use jzero_ast::{CancellationToken, Cancelled};
+        let src = "public class T { public static void main(String argv[]) { int x; } }";
+        let token = CancellationToken::new();
+        let mut tree = parse_tree(src).expect("parse failed");
+        assert!(crate::analyze_cancellable(&mut tree, &token).is_ok());
+
+        token.cancel();
+        let mut tree = parse_tree(src).expect("parse failed");
+        assert!(matches!(crate::analyze_cancellable(&mut tree, &token), Err(Cancelled)));
+    }
+
+    #[test]
+    fn malformed_declaration_is_reported_not_skipped() {
+        use jzero_symtab::{SymTab, build_predefined};
+        let src = "public class t {\n public static void main(String argv[]) {\n int x;\n }\n}\n";
+        let mut tree = parse_tree(src).expect("parse failed");
+        // ClassDecl → MethodDecl → Block → LocalVarDecl: drop its declarator.
+        let block = &mut tree.kids[1].kids[1];
+        block.kids[0].remove_kid(1);
+
+        let global = SymTab::new("global", None).into_rc();
+        build_predefined(&global);
+        let mut errors = Vec::new();
+        crate::builder::build_symtabs(&mut tree, global, &mut errors);
+        let found: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+        assert_eq!(found, ["line 3: internal error: malformed tree: local variable needs at least 2 kids, has 1"]);
+    }
+
+    #[test]
+    fn symbols_know_where_they_come_from() {
+        use jzero_symtab::{FileId, SymbolOrigin};
+        let src = "public class T {\n public static void main(String argv[]) { int x; }\n}\n";
+        let mut tree = parse_tree(src).expect("parse failed");
+        let sem = crate::analyze_file(&mut tree, FileId::new(3), None).unwrap();
+        let g = sem.global.borrow();
+        assert_eq!(g.lookup_local("System").unwrap().origin, SymbolOrigin::Predefined);
+        let main = g.lookup_local("T").unwrap().st.clone().unwrap().borrow().lookup_local("main").cloned().unwrap();
+        assert_eq!(main.origin, SymbolOrigin::UserFile(FileId::new(3)));
+        assert_eq!(main.st.unwrap().borrow().lookup_local("x").unwrap().origin, SymbolOrigin::UserFile(FileId::new(3)));
+        assert_eq!(g.user_text(0), "global - 1 symbols\n T\n  class - 1 symbols\n   main\n    method - 3 symbols\n     return\n     argv\n     x\n");
+        assert!(g.prelude_text(0).starts_with("global - 3 symbols\n System\n"));
+    }
+
+    #[test]
+    fn constant_array_sizes_are_checked_and_recorded() {
+        let src = r#"
+public class T {
+    public static void main(String argv[]) {
+        int a[];
+        int n;
+        a = new int[2 * 5];
+        a = new int[n];
+        a = new int[1 - 3];
+    }
+}
+"#;
+        let result = run(src);
+        let errs: Vec<String> = result.errors.iter().map(|e| e.to_string()).collect();
+        assert_eq!(errs, ["line 8: array size -2 is negative"]);
+        let sizes: Vec<i64> = result.array_sizes.values().copied().collect();
+        assert_eq!(sizes, [10]);
+    }
+
+    #[test]
+    fn array_initializers_are_checked_against_the_element_type() {
+        let src = r#"
+public class T {
+    int table[] = {1, 2 * 3, 4};
+    public static void main(String argv[]) {
+        int grid[][] = {{1, 2}, {3}}, n;
+        String names[] = {"a", 1};
+        int x = {1};
+        String words[] = {};
+    }
+}
+"#;
+        let result = run(src);
+        let errs: Vec<String> = result.errors.iter().map(|e| e.to_string()).collect();
+        assert_eq!(errs, ["line 7: type assignment error: 'x' is declared int, not as an array, so it cannot have an array initializer"]);
+        let failed: Vec<String> = result.type_checks.iter().filter(|r| !r.ok).map(|r| r.to_string()).collect();
+        assert_eq!(failed, ["line 6: typecheck = on a int and a String -> FAIL"]);
+        assert_eq!(result.type_checks.iter().filter(|r| r.ok).count(), 8);
+        let mut sizes: Vec<i64> = result.array_sizes.values().copied().collect();
+        sizes.sort();
+        assert_eq!(sizes, [0, 1, 1, 2, 2, 2, 3]);
+    }
+
+    #[test]
+    fn initializers_are_checked_as_assignments() {
+        let src = r#"
+public class T {
+    public static void main(String argv[]) {
+        int n = 3, m = n + 1;
+        String s = "hi", t = n;
+        int a[] = new int[n];
+    }
+}
+"#;
+        let result = run(src);
+        assert!(result.errors.is_empty(), "{:?}", result.errors);
+        let failed: Vec<String> = result.type_checks.iter().filter(|r| !r.ok).map(|r| r.to_string()).collect();
+        assert_eq!(failed, ["line 5: typecheck = on a int and a String -> FAIL"]);
+        let assigned = result.type_checks.iter().filter(|r| r.operator == "=" && r.ok).count();
+        assert_eq!(assigned, 4);
+        let g = result.global.borrow();
+        let main = g.lookup_local("T").unwrap().st.clone().unwrap().borrow().lookup_local("main").cloned().unwrap();
+        assert!(main.st.unwrap().borrow().lookup_local("m").is_some());
+    }
+
+    #[test]
+    fn catch_parameters_have_a_scope_of_their_own() {
+        let src = r#"
+import java.io.IOException;
+public class T {
+    public static void main(String argv[]) {
+        int n;
+        try { n = 1; } catch (IOException e) { n = 2; } catch (T e) { n = 3; }
+        try { n = 4; } catch (IOException n) { }
+        try { n = 5; } catch (Oops e) { }
+    }
+}
+"#;
+        let mut tree = jzero_parser::parse_tree_with(src, jzero_lexer::LexOptions::at_level(jzero_lexer::Level::Extended)).unwrap();
+        let result = analyze(&mut tree);
+        // The second `e` is no redeclaration; `n` shadowing the local is.
+        let errors: Vec<String> = result.errors.iter().map(|e| e.to_string()).collect();
+        assert_eq!(errors, ["line 7: redeclared variable 'n'", "line 8: undeclared class 'Oops'"]);
+
+        let try_stmt = &tree.kids[1].kids[1].kids[1].kids[1];
+        let catch = &try_stmt.kids[2];
+        let scope = catch.stab.clone().unwrap();
+        assert_eq!(scope.borrow().scope, "catch");
+        let e = scope.borrow().lookup_local("e").cloned().unwrap();
+        assert_eq!((e.kind.to_string(), e.typ.unwrap().display().to_string(), e.lineno), ("param".to_string(), "T".to_string(), 6));
+        // Names of the method are found through the catch scope.
+        assert!(scope.borrow().lookup("n").is_some());
+        assert!(result.global.borrow().lookup("T").unwrap().st.unwrap().borrow().lookup("e").is_none());
+    }
+
+    #[test]
+    fn named_constants_are_folded_where_used() {
+        let src = r#"
+public class T {
+    static final int N = 2 * 3;
+    static final double HALF = N / 2;
+    static final bool BIG = N > 5, ON;
+    static final String NAME = "t";
+    public static void main(String argv[]) {
+        int x;
+        x = N + 1;
+        if (BIG) { x = 0; }
+    }
+}
+"#;
+        let mut tree = jzero_parser::parse_tree_with(src, jzero_lexer::LexOptions::at_level(jzero_lexer::Level::Extended)).unwrap();
+        let result = analyze(&mut tree);
+        let errs: Vec<String> = result.errors.iter().map(|e| e.to_string()).collect();
+        assert_eq!(errs, ["line 5: constant 'ON' must be initialized with a constant expression"]);
+        assert_eq!(result.errors[0].code(), "non-constant-field");
+
+        let block = &tree.kids[5].kids[1];
+        let (assign, cond) = (&block.kids[1].kids[2], &block.kids[2].kids[0]);
+        assert_eq!(crate::eval_const_with(assign, &result.constants), Some(crate::ConstValue::Int(7)));
+        assert_eq!(crate::eval_const_with(cond, &result.constants), Some(crate::ConstValue::Bool(true)));
+        assert_eq!(crate::eval_const(assign), None);
+        let half = result.global.borrow().lookup("T").unwrap().st.unwrap().borrow().lookup_local("HALF").cloned().unwrap();
+        assert!(half.is_const);
+    }
+
+    #[test]
+    fn a_subclass_looks_up_inherited_members() {
+        let extended = || jzero_lexer::LexOptions::at_level(jzero_lexer::Level::Extended);
+        let global = jzero_symtab::SymTab::new("global", None).into_rc();
+        jzero_symtab::build_predefined(&global);
+        let mut base = jzero_parser::parse_tree_with(r#"
+public class Base {
+    static int count;
+    public static int twice(int n) { return n + n; }
+}
+"#, extended()).unwrap();
+        assert!(crate::analyze_in(&mut base, std::rc::Rc::clone(&global)).errors.is_empty());
+
+        let mut sub = jzero_parser::parse_tree_with(r#"
+public class Sub extends Base {
+    public static void main(String argv[]) {
+        int x;
+        x = twice(count);
+    }
+}
+"#, extended()).unwrap();
+        let result = crate::analyze_in(&mut sub, std::rc::Rc::clone(&global));
+        assert!(result.errors.is_empty(), "{:?}", result.errors);
+        assert!(result.type_checks.iter().all(|c| c.ok), "{:?}", result.type_checks);
+        let scope = global.borrow().lookup("Sub").unwrap().st.unwrap();
+        assert!(scope.borrow().lookup_local("count").is_none());
+        assert_eq!(scope.borrow().lookup("count").unwrap().kind, jzero_symtab::entry::SymbolKind::Field);
+
+        // The superclass must be declared first, and cannot be the class itself.
+        for src in ["public class A extends Missing { }", "public class Loop extends Loop { }"] {
+            let mut tree = jzero_parser::parse_tree_with(src, extended()).unwrap();
+            let errs: Vec<String> = crate::analyze_in(&mut tree, std::rc::Rc::clone(&global)).errors.iter()
+                .map(|e| e.to_string()).collect();
+            assert_eq!(errs.len(), 1, "{:?}", errs);
+            assert!(errs[0].starts_with("line 1: undeclared class"), "{:?}", errs);
+        }
+    }
+
+    #[test]
+    fn var_takes_the_type_of_its_initializer() {
+        let src = r#"
+public class T {
+    public static double half(double d) { return d / 2.0; }
+    public static void main(String argv[]) {
+        var n = 4;
+        var h = half(3.0) + 1.0;
+        var xs = new int[n];
+        var s = "n" + "=";
+        var none = null;
+        int k;
+        k = h;
+        for (var i = 0; i < n; i += 1) { xs[i] = i; }
+    }
+}
+"#;
+        let mut tree = jzero_parser::parse_tree_with(src, jzero_lexer::LexOptions::at_level(jzero_lexer::Level::Extended)).unwrap();
+        let result = analyze(&mut tree);
+        assert!(result.errors.is_empty(), "{:?}", result.errors);
+        let main = result.global.borrow().lookup("T").unwrap().st.unwrap().borrow().lookup_local("main").cloned().unwrap();
+        let scope = main.st.unwrap();
+        let typ = |name: &str| scope.borrow().lookup_local(name).and_then(|e| e.typ.clone()).map(|t| t.display().to_string());
+        assert_eq!([typ("n"), typ("h"), typ("xs"), typ("s"), typ("i")].map(Option::unwrap), ["int", "double", "int[]", "String", "int"]);
+        assert_eq!(typ("none"), None);
+
+        let failed: Vec<String> = result.type_checks.iter().filter(|c| !c.ok).map(|c| c.to_string()).collect();
+        assert_eq!(failed, [
+            "line 9: typecheck var on a null and a unknown -> FAIL",
+            "line 11: typecheck = on a double and a int -> FAIL",
+        ]);
+    }
+
+    #[test]
+    fn casts_convert_between_numbers() {
+        let src = r#"
+public class T {
+    public static void main(String argv[]) {
+        int n;
+        double d;
+        d = (double) n / 2.0;
+        n = (int) d + 1;
+        n = (int) (d * 2.0);
+        n = (int) true;
+        d = (double) "one";
+    }
+}
+"#;
+        let result = run(src);
+        let failed: Vec<String> = result.type_checks.iter().filter(|c| !c.ok).map(|c| c.to_string()).collect();
+        assert_eq!(failed, [
+            "line 9: typecheck cast on a boolean and a int -> FAIL",
+            "line 10: typecheck cast on a String and a double -> FAIL",
+        ]);
+    }
+
+    #[test]
+    fn char_and_int_widen_to_long() {
+        let src = r#"
+public class T {
+    public static long total(long a, char c) { return a + (long) c; }
+    public static void main(String argv[]) {
+        char c;
+        int code;
+        long n;
+        c = 'a';
+        code = c;
+        n = total(12L, c);
+        n = 12;
+        n = c;
+        n = n + 1;
+        n = 1 + n * c;
+        c = code;
+        code = n;
+        code = code + n;
+    }
+}
+"#;
+        let mut tree = jzero_parser::parse_tree_with(src, jzero_lexer::LexOptions::at_level(jzero_lexer::Level::Extended)).unwrap();
+        let result = analyze(&mut tree);
+        assert!(result.errors.is_empty(), "{:?}", result.errors);
+        let failed: Vec<String> = result.type_checks.iter().filter(|c| !c.ok).map(|c| c.to_string()).collect();
+        assert_eq!(failed, [
+            "line 15: typecheck = on a int and a char -> FAIL",
+            "line 16: typecheck = on a long and a int -> FAIL",
+            "line 17: typecheck = on a long and a int -> FAIL",
+        ]);
+    }
+
+    #[test]
+    fn numbers_widen_to_double() {
+        let src = r#"
+public class T {
+    public static void main(String argv[]) {
+        int n;
+        double d;
+        d = Math.random() * 6;
+        d = n;
+        d = d / n + 'a';
+        if (n < d) { n = d; }
+    }
+}
+"#;
+        let result = run(src);
+        assert!(result.errors.is_empty(), "{:?}", result.errors);
+        let failed: Vec<String> = result.type_checks.iter().filter(|c| !c.ok).map(|c| c.to_string()).collect();
+        assert_eq!(failed, ["line 9: typecheck = on a double and a int -> FAIL"]);
+    }
+
+    #[test]
+    fn instanceof_tests_objects() {
+        let src = r#"
+public class T {
+    public static void main(String argv[]) {
+        T t;
+        int n;
+        bool b;
+        b = t instanceof T && "s" instanceof String;
+        b = n instanceof T;
+    }
+}
+"#;
+        let mut tree = jzero_parser::parse_tree_with(src, jzero_lexer::LexOptions::at_level(jzero_lexer::Level::Extended)).unwrap();
+        let result = analyze(&mut tree);
+        assert!(result.errors.is_empty(), "{:?}", result.errors);
+        let failed: Vec<String> = result.type_checks.iter().filter(|c| !c.ok).map(|c| c.to_string()).collect();
+        assert_eq!(failed, ["line 8: typecheck instanceof on a int and a T -> FAIL"]);
+    }
+
+    #[test]
+    fn imports_declare_known_classes() {
+        let src = r#"
+import java.util.Scanner;
+import java.io.*;
+import java.util.Vector;
+public class T {
+    public static void main(String argv[]) {
+        Scanner in;
+        in = new Scanner();
+        File f;
+        f = new File();
+        Random r;
+        r = new Random();
+    }
+}
+"#;
+        let mut tree = jzero_parser::parse_tree_with(src, jzero_lexer::LexOptions::at_level(jzero_lexer::Level::Extended)).unwrap();
+        let result = analyze(&mut tree);
+        let errs: Vec<String> = result.errors.iter().map(|e| e.to_string()).collect();
+        assert_eq!(errs, [
+            "line 4: cannot find 'java.util.Vector' to import",
+            "line 12: undeclared class 'Random'",
+        ]);
+        assert_eq!(result.errors[0].code(), "unknown-import");
+        let scanner = result.global.borrow().lookup("Scanner").unwrap();
+        assert_eq!(scanner.origin, jzero_symtab::SymbolOrigin::Library);
+        assert!(result.global.borrow().lookup("T").is_some());
+    }
+
+    #[test]
+    fn redeclaring_a_predefined_class_names_it() {
+        let errs: Vec<String> = run("public class System {\n}\n").errors.iter().map(|e| e.to_string()).collect();
+        assert_eq!(errs, ["line 1: 'System' conflicts with the predefined class System"]);
+        assert_eq!(run("public class System {\n}\n").errors[0].code(), "predefined-conflict");
+    }
+}
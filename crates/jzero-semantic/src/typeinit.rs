@@ -15,9 +15,12 @@ use jzero_symtab::TypeInfo;
 /// - `INTLIT`    → `TypeInfo::int()`
 /// - `DOUBLELIT` → `TypeInfo::double()`
 /// - `STRINGLIT` → `TypeInfo::string()`
+/// - `LONGLIT`   → `TypeInfo::long()`
+/// - `CHARLIT`   → `TypeInfo::char()`
 /// - `BOOLLIT`   → `TypeInfo::boolean()`
 /// - `NULL`      → `TypeInfo::null()`
 /// - operators   → `TypeInfo::na()`
+/// - `FLOATLIT` → left as `None` until Jzero has `float`
 /// - other leaves (IDENTIFIER, keywords) → left as `None` for later passes
 pub fn assign_leaf_types(tree: &mut Tree) {
     if let Some(ref tok) = tree.tok.clone() {
@@ -25,11 +28,14 @@ pub fn assign_leaf_types(tree: &mut Tree) {
             "INTLIT"    => Some(TypeInfo::int()),
             "DOUBLELIT" => Some(TypeInfo::double()),
             "STRINGLIT" => Some(TypeInfo::string()),
+            "LONGLIT"   => Some(TypeInfo::long()),
+            "CHARLIT"   => Some(TypeInfo::char()),
             "BOOLLIT"   => Some(TypeInfo::boolean()),
             "NULL"      => Some(TypeInfo::null()),
             // Operators carry no value type — n/a matches the book
             "PLUS" | "MINUS" | "STAR" | "SLASH" | "PERCENT" |
             "ASSIGN" | "PLUSASSIGN" | "MINUSASSIGN" |
+            "STARASSIGN" | "SLASHASSIGN" | "PERCENTASSIGN" |
             "LESS" | "GREATER" | "LESSEQUAL" | "GREATEREQUAL" |
             "EQUALEQUAL" | "NOTEQUAL" |
             "LOGICALAND" | "LOGICALOR" => Some(TypeInfo::na()),
@@ -83,6 +89,20 @@ mod tests {
         assert_eq!(t.typ.as_ref().unwrap().basetype(), "String");
     }
 
+    #[test]
+    fn test_charlit_gets_char_type() {
+        let mut t = leaf("CHARLIT", "'a'");
+        assign_leaf_types(&mut t);
+        assert_eq!(t.typ.as_ref().unwrap().basetype(), "char");
+    }
+
+    #[test]
+    fn test_longlit_gets_long_type() {
+        let mut t = leaf("LONGLIT", "12L");
+        assign_leaf_types(&mut t);
+        assert_eq!(t.typ.as_ref().unwrap().basetype(), "long");
+    }
+
     #[test]
     fn test_boollit_gets_boolean_type() {
         let mut t = leaf("BOOLLIT", "true");
@@ -104,6 +124,7 @@ mod tests {
         for (cat, text) in &[
             ("PLUS", "+"), ("MINUS", "-"), ("STAR", "*"), ("SLASH", "/"),
             ("ASSIGN", "="), ("PLUSASSIGN", "+="), ("MINUSASSIGN", "-="),
+            ("STARASSIGN", "*="), ("SLASHASSIGN", "/="), ("PERCENTASSIGN", "%="),
             ("LESS", "<"), ("GREATER", ">"), ("EQUALEQUAL", "=="),
             ("LOGICALAND", "&&"), ("LOGICALOR", "||"),
         ] {
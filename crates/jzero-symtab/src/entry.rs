@@ -1,6 +1,8 @@
 use std::cell::RefCell;
 use std::rc::Rc;
 
+use jzero_text::FileId;
+
 use crate::symtab::SymTab;
 use crate::typeinfo::TypeInfo;
 
@@ -8,24 +10,89 @@ use crate::typeinfo::TypeInfo;
 #[derive(Debug, Clone, PartialEq)]
 pub enum SymbolKind {
     Class,
+    /// An interface; its scope holds its abstract methods.
+    Interface,
     Method,
     Field,
     Param,
     Local,
+    /// One segment of a package name; its child scope holds the next.
+    Package,
 }
 
 impl std::fmt::Display for SymbolKind {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             SymbolKind::Class  => write!(f, "class"),
+            SymbolKind::Interface => write!(f, "interface"),
             SymbolKind::Method => write!(f, "method"),
             SymbolKind::Field  => write!(f, "field"),
             SymbolKind::Param  => write!(f, "param"),
             SymbolKind::Local  => write!(f, "local"),
+            SymbolKind::Package => write!(f, "package"),
         }
     }
 }
 
+/// The access modifier of a field or method.
+///
+/// Jzero has no `package` declarations, so every class is in the same
+/// (default) package and a missing modifier behaves like `public`.  For the
+/// same reason `protected`, which Java opens to the whole package, cannot
+/// be violated yet; only `private` members are ever out of reach.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Access {
+    #[default]
+    Public,
+    Protected,
+    Private,
+}
+
+impl Access {
+    /// Decode the rule number the parser gives `FieldDecl`/`MethodHeader`;
+    /// the modifiers after the access add a multiple of 3.
+    pub fn from_rule(rule: i32) -> Self {
+        match rule % 3 {
+            1 => Access::Protected,
+            2 => Access::Private,
+            _ => Access::Public,
+        }
+    }
+}
+
+impl std::fmt::Display for Access {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Access::Public    => write!(f, "public"),
+            Access::Protected => write!(f, "protected"),
+            Access::Private   => write!(f, "private"),
+        }
+    }
+}
+
+/// Where a symbol comes from: the prelude of predefined classes
+/// (`System`, `String`), the runtime library beyond the book's prelude,
+/// the native methods of the program embedding the compiler, or a source
+/// file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SymbolOrigin {
+    Predefined,
+    /// A built-in the book's prelude does not have, e.g. `Math.randomInt`;
+    /// left out of the book-format symbol table dump.
+    Library,
+    /// Declared with [`declare_native`](crate::predef::declare_native) and
+    /// implemented by the embedder, not in Jzero.
+    Native,
+    UserFile(FileId),
+}
+
+impl SymbolOrigin {
+    /// Whether the symbol is not declared in a source file.
+    pub fn is_predefined(&self) -> bool {
+        matches!(self, SymbolOrigin::Predefined | SymbolOrigin::Library | SymbolOrigin::Native)
+    }
+}
+
 /// One entry in a symbol table.
 #[derive(Debug, Clone)]
 pub struct SymTabEntry {
@@ -33,7 +100,7 @@ pub struct SymTabEntry {
     pub sym: String,
     /// The scope this entry was declared in.
     pub parent_st: Rc<RefCell<SymTab>>,
-    /// Child scope — present only for classes and methods.
+    /// Child scope — present only for classes, methods and packages.
     pub st: Option<Rc<RefCell<SymTab>>>,
     /// Whether this symbol is a compile-time constant.
     pub is_const: bool,
@@ -42,6 +109,15 @@ pub struct SymTabEntry {
     /// The declared type of this symbol.
     /// `None` until populated by semantic analysis (Chapter 7).
     pub typ: Option<TypeInfo>,
+    /// Access modifier — meaningful for fields and methods.
+    pub access: Access,
+    /// Whether a method is `static` (called on the class, not an object).
+    pub is_static: bool,
+    /// Line of the declaration, or 0 for predefined symbols.
+    pub lineno: usize,
+    /// Where the symbol is declared.  Entries are made as declared in
+    /// file 0; analysis of another file stamps its own.
+    pub origin: SymbolOrigin,
 }
 
 impl SymTabEntry {
@@ -59,6 +135,10 @@ impl SymTabEntry {
             is_const,
             kind,
             typ: None,
+            access: Access::Public,
+            is_static: false,
+            lineno: 0,
+            origin: SymbolOrigin::UserFile(FileId::default()),
         }
     }
 
@@ -77,6 +157,21 @@ impl SymTabEntry {
             is_const,
             kind,
             typ: None,
+            access: Access::Public,
+            is_static: false,
+            lineno: 0,
+            origin: SymbolOrigin::UserFile(FileId::default()),
+        }
+    }
+
+    /// Record that this symbol and everything declared in its scope come
+    /// from `origin`.
+    pub fn set_origin(&mut self, origin: SymbolOrigin) {
+        self.origin = origin;
+        if let Some(st) = &self.st {
+            for (_, entry) in st.borrow_mut().iter_mut() {
+                entry.set_origin(origin);
+            }
         }
     }
 
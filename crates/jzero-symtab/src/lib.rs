@@ -1,9 +1,12 @@
 pub mod symtab;
 pub mod entry;
 pub mod predef;
+pub mod package;
 pub mod typeinfo;
 
 pub use symtab::SymTab;
-pub use entry::SymTabEntry;
-pub use predef::build_predefined;
-pub use typeinfo::{TypeInfo, MethodType, ClassType, Parameter};
\ No newline at end of file
+pub use entry::{SymTabEntry, SymbolOrigin};
+pub use jzero_text::FileId;
+pub use predef::{build_predefined, declare_native};
+pub use package::{KNOWN_CLASSES, declare_package, import, resolve_qualified};
+pub use typeinfo::{TypeInfo, TypeDisplay, MethodType, ClassType, Parameter};
\ No newline at end of file
@@ -0,0 +1,164 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::entry::{SymTabEntry, SymbolKind, SymbolOrigin};
+use crate::symtab::SymTab;
+use crate::typeinfo::TypeInfo;
+
+/// The library classes an `import` can name, by qualified name.  Jzero
+/// knows none of their members: importing one makes its name a class
+/// type, to declare variables of and create with `new`.
+pub const KNOWN_CLASSES: &[&str] = &[
+    "java.io.File",
+    "java.io.IOException",
+    "java.util.ArrayList",
+    "java.util.HashMap",
+    "java.util.Random",
+    "java.util.Scanner",
+];
+
+/// Declare the package `path` (e.g. `"mypkg.util"`) under `global` and
+/// return the scope of its innermost segment.
+///
+/// Each segment is a `Package` entry whose child scope holds the next
+/// segment, so `mypkg.util.Helper` is laid out like `System.out`:
+/// ```text
+/// mypkg
+///   package - 1 symbols
+///    util
+///      package - 1 symbols
+///       Helper
+/// ```
+/// Segments that already exist are reused, so two packages sharing a
+/// prefix share its scope.
+pub fn declare_package(global: &Rc<RefCell<SymTab>>, path: &str) -> Rc<RefCell<SymTab>> {
+    let mut scope = Rc::clone(global);
+    for segment in path.split('.').filter(|s| !s.is_empty()) {
+        let existing = scope.borrow().lookup_local(segment)
+            .filter(|e| e.kind == SymbolKind::Package)
+            .and_then(|e| e.st.clone());
+        let next = match existing {
+            Some(st) => st,
+            None => {
+                let st = SymTab::new("package", Some(Rc::clone(global))).into_rc();
+                let entry = SymTabEntry::with_scope(
+                    segment,
+                    SymbolKind::Package,
+                    Rc::clone(&scope),
+                    false,
+                    Rc::clone(&st),
+                );
+                // A non-package symbol of the same name keeps its slot; the
+                // package is then reachable only through the returned scope.
+                let _ = scope.borrow_mut().insert(entry);
+                st
+            }
+        };
+        scope = next;
+    }
+    scope
+}
+
+/// Import `path` into `global`: the class of [`KNOWN_CLASSES`] it names,
+/// or with `on_demand` (`import java.util.*;`) each one in the package it
+/// names.  A class is declared in its package, and by its simple name in
+/// `global` unless that name is taken.
+///
+/// Returns the simple names imported; none if `path` names no known class
+/// or package.
+pub fn import(global: &Rc<RefCell<SymTab>>, path: &str, on_demand: bool) -> Vec<String> {
+    let mut imported = Vec::new();
+    for qualified in KNOWN_CLASSES {
+        let Some((package, class)) = qualified.rsplit_once('.') else { continue };
+        let wanted = if on_demand { package == path } else { *qualified == path };
+        if !wanted { continue; }
+
+        let package_st = declare_package(global, package);
+        let existing = package_st.borrow().lookup_local(class).and_then(|e| e.st.clone());
+        let class_st = existing.unwrap_or_else(|| {
+            let st = SymTab::new("class", Some(Rc::clone(global))).into_rc();
+            let entry = library_class(class, &package_st, &st);
+            package_st.borrow_mut().insert(entry).expect("class was looked up");
+            st
+        });
+        if global.borrow().lookup_local(class).is_none() {
+            let entry = library_class(class, global, &class_st);
+            global.borrow_mut().insert(entry).expect("class was looked up");
+        }
+        imported.push(class.to_string());
+    }
+    imported
+}
+
+fn library_class(name: &str, parent: &Rc<RefCell<SymTab>>, scope: &Rc<RefCell<SymTab>>) -> SymTabEntry {
+    let mut entry = SymTabEntry::with_scope(name, SymbolKind::Class, Rc::clone(parent), false, Rc::clone(scope));
+    entry.set_typ(TypeInfo::class_with_symtab(name, Rc::clone(scope)));
+    entry.set_origin(SymbolOrigin::Library);
+    entry
+}
+
+/// Resolve a dotted name such as `["mypkg", "util", "Helper", "compute"]`.
+///
+/// The first segment is looked up from `scope` outwards, like any name, so a
+/// local variable hides a package of the same name.  Each later segment is
+/// looked up only in the child scope of the previous one.
+pub fn resolve_qualified(scope: &SymTab, path: &[&str]) -> Option<SymTabEntry> {
+    let (first, rest) = path.split_first()?;
+    let mut entry = scope.lookup(first)?;
+    for segment in rest {
+        let st = entry.st.clone()?;
+        let next = st.borrow().lookup_local(segment).cloned()?;
+        entry = next;
+    }
+    Some(entry)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::predef::build_predefined;
+
+    #[test]
+    fn test_nested_package_scopes() {
+        let global = SymTab::new("global", None).into_rc();
+        let util = declare_package(&global, "mypkg.util");
+        let helper_st = SymTab::new("class", Some(Rc::clone(&global))).into_rc();
+        util.borrow_mut().insert(SymTabEntry::with_scope(
+            "Helper", SymbolKind::Class, Rc::clone(&util), false, helper_st,
+        )).unwrap();
+
+        // A second package under the same root reuses `mypkg`.
+        declare_package(&global, "mypkg.io");
+        assert_eq!(global.borrow().len(), 1);
+
+        let g = global.borrow();
+        let mypkg = resolve_qualified(&g, &["mypkg"]).unwrap();
+        assert_eq!(mypkg.kind, SymbolKind::Package);
+        let helper = resolve_qualified(&g, &["mypkg", "util", "Helper"]).unwrap();
+        assert_eq!(helper.kind, SymbolKind::Class);
+        assert!(resolve_qualified(&g, &["mypkg", "Helper"]).is_none());
+    }
+
+    #[test]
+    fn test_import_declares_known_classes() {
+        let global = SymTab::new("global", None).into_rc();
+        assert_eq!(import(&global, "java.util.Scanner", false), ["Scanner"]);
+        assert_eq!(import(&global, "java.util", true), ["ArrayList", "HashMap", "Random", "Scanner"]);
+        assert!(import(&global, "java.util.Vector", false).is_empty());
+        assert!(import(&global, "java.util.Scanner", true).is_empty());
+
+        let g = global.borrow();
+        let simple = g.lookup_local("Scanner").unwrap();
+        let qualified = resolve_qualified(&g, &["java", "util", "Scanner"]).unwrap();
+        assert_eq!((&simple.kind, simple.origin), (&SymbolKind::Class, SymbolOrigin::Library));
+        assert!(Rc::ptr_eq(simple.st.as_ref().unwrap(), qualified.st.as_ref().unwrap()));
+    }
+
+    #[test]
+    fn test_resolves_predefined_chain() {
+        let global = SymTab::new("global", None).into_rc();
+        build_predefined(&global);
+        let println = resolve_qualified(&global.borrow(), &["System", "out", "println"]).unwrap();
+        assert_eq!(println.kind, SymbolKind::Method);
+    }
+}
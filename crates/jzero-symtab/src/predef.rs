@@ -1,8 +1,24 @@
 use std::cell::RefCell;
 use std::rc::Rc;
 
-use crate::entry::{SymTabEntry, SymbolKind};
+use crate::entry::{SymTabEntry, SymbolKind, SymbolOrigin};
 use crate::symtab::SymTab;
+use crate::typeinfo::{MethodType, Parameter, TypeInfo};
+
+/// The static methods of the runtime library beyond the book's prelude,
+/// as `(class, method, return type)`: each takes no arguments.
+///
+/// `Math.random()` returns a `double` in `[0, 1)`, as in Java, and
+/// `Math.randomInt()` the same draw as an `int` in `0..2^31`; take it
+/// `% n` for a number below `n`.
+pub const LIBRARY_METHODS: &[(&str, &str, &str)] = &[
+    ("System", "currentTimeMillis", "int"),
+    ("Math", "random", "double"),
+    ("Math", "randomInt", "int"),
+];
+
+/// The library's one method outside a class, for unit tests.
+pub const ASSERT_EQUALS: &str = "assertEquals";
 
 /// Build the predefined `System.out.println` scope hierarchy and insert it
 /// into the given global scope.
@@ -18,6 +34,10 @@ use crate::symtab::SymTab;
 ///      class - 1 symbols
 ///       println
 /// ```
+///
+/// `out` is a static *field* holding a `PrintStream` object, and `println`
+/// is an instance method of `PrintStream`; the nested scope under `out` is
+/// that class's scope, reachable through the field's type.
 pub fn build_predefined(global: &Rc<RefCell<SymTab>>) {
     // println scope (empty — no local vars)
     let println_st = SymTab::new("method", Some(Rc::clone(global))).into_rc();
@@ -35,24 +55,115 @@ pub fn build_predefined(global: &Rc<RefCell<SymTab>>) {
 
     // System scope — contains out
     let system_st = SymTab::new("class", Some(Rc::clone(global))).into_rc();
-    let out_entry = SymTabEntry::with_scope(
+    let mut out_entry = SymTabEntry::with_scope(
         "out",
-        SymbolKind::Class,
+        SymbolKind::Field,
         Rc::clone(&system_st),
         false,
         Rc::clone(&out_st),
     );
+    out_entry.set_typ(TypeInfo::class_with_symtab("PrintStream", Rc::clone(&out_st)));
+    out_entry.is_static = true;
     system_st.borrow_mut().insert(out_entry).expect("predefined insert failed");
 
     // Insert System into global
-    let system_entry = SymTabEntry::with_scope(
+    let mut system_entry = SymTabEntry::with_scope(
         "System",
         SymbolKind::Class,
         Rc::clone(global),
         false,
         Rc::clone(&system_st),
     );
+    system_entry.set_typ(TypeInfo::class_with_symtab("System", Rc::clone(&system_st)));
+    system_entry.set_origin(SymbolOrigin::Predefined);
     global.borrow_mut().insert(system_entry).expect("predefined insert failed");
+
+    for (class, method, returns) in LIBRARY_METHODS {
+        declare_library(global, class, method, returns);
+    }
+    declare_assert_equals(global);
+}
+
+/// Declare `assertEquals(int expected, int actual)`, callable without a
+/// class from any method, for unit tests written in Jzero: it stops the
+/// program with an error if the two differ.
+fn declare_assert_equals(global: &Rc<RefCell<SymTab>>) {
+    let method_st = SymTab::new("method", Some(Rc::clone(global))).into_rc();
+    let mut entry = SymTabEntry::with_scope(ASSERT_EQUALS, SymbolKind::Method, Rc::clone(global), false, method_st);
+    entry.set_typ(TypeInfo::Method(MethodType::new(TypeInfo::void(), vec![
+        Parameter::new("expected", TypeInfo::int()),
+        Parameter::new("actual", TypeInfo::int()),
+    ])));
+    entry.is_static = true;
+    entry.set_origin(SymbolOrigin::Library);
+    global.borrow_mut().insert(entry).expect("predefined insert failed");
+}
+
+/// Declare `class.method()` of [`LIBRARY_METHODS`], and `class` too if it
+/// is not declared yet.
+fn declare_library(global: &Rc<RefCell<SymTab>>, class: &str, method: &str, returns: &str) {
+    let existing = global.borrow().lookup_local(class).and_then(|e| e.st.clone());
+    let class_st = existing.unwrap_or_else(|| {
+        let st = SymTab::new("class", Some(Rc::clone(global))).into_rc();
+        let mut entry = SymTabEntry::with_scope(class, SymbolKind::Class, Rc::clone(global), false, Rc::clone(&st));
+        entry.set_typ(TypeInfo::class_with_symtab(class, Rc::clone(&st)));
+        entry.set_origin(SymbolOrigin::Library);
+        global.borrow_mut().insert(entry).expect("predefined insert failed");
+        st
+    });
+
+    let method_st = SymTab::new("method", Some(Rc::clone(&class_st))).into_rc();
+    let mut entry = SymTabEntry::with_scope(method, SymbolKind::Method, Rc::clone(&class_st), false, method_st);
+    entry.set_typ(TypeInfo::Method(MethodType::new(TypeInfo::Base(returns.to_string()), vec![])));
+    entry.is_static = true;
+    entry.set_origin(SymbolOrigin::Library);
+    class_st.borrow_mut().insert(entry).expect("predefined insert failed");
+}
+
+/// Declare the native method `qualified`, e.g. `Robot.move`, as a static
+/// method of that class with the given signature.  The class is declared
+/// too the first time one of its methods is.
+///
+/// # Errors
+/// Returns a message if `qualified` is not `Class.method`, if the class is
+/// declared but not native (`System`, say), or if the method already is.
+pub fn declare_native(global: &Rc<RefCell<SymTab>>, qualified: &str, signature: MethodType) -> Result<(), String> {
+    let Some((class, method)) = qualified.split_once('.')
+        .filter(|(c, m)| is_identifier(c) && is_identifier(m))
+    else {
+        return Err(format!("native method '{}' is not named Class.method", qualified));
+    };
+
+    let class_st = match global.borrow().lookup_local(class) {
+        Some(e) if e.origin == SymbolOrigin::Native => e.st.clone(),
+        Some(_) => return Err(format!("'{}' is not a native class", class)),
+        None => None,
+    };
+    let class_st = match class_st {
+        Some(st) => st,
+        None => {
+            let st = SymTab::new("class", Some(Rc::clone(global))).into_rc();
+            let mut entry = SymTabEntry::with_scope(class, SymbolKind::Class, Rc::clone(global), false, Rc::clone(&st));
+            entry.set_typ(TypeInfo::class_with_symtab(class, Rc::clone(&st)));
+            entry.set_origin(SymbolOrigin::Native);
+            global.borrow_mut().insert(entry).expect("class was looked up");
+            st
+        }
+    };
+
+    let method_st = SymTab::new("method", Some(Rc::clone(&class_st))).into_rc();
+    let mut entry = SymTabEntry::with_scope(method, SymbolKind::Method, Rc::clone(&class_st), false, method_st);
+    entry.set_typ(TypeInfo::Method(signature));
+    entry.is_static = true;
+    entry.set_origin(SymbolOrigin::Native);
+    class_st.borrow_mut().insert(entry)
+        .map_err(|_| format!("native method '{}' is already declared", qualified))
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_alphabetic() || c == '_')
+        && chars.all(|c| c.is_alphanumeric() || c == '_')
 }
 
 #[cfg(test)]
@@ -71,12 +182,13 @@ mod tests {
         let system_entry = g.lookup_local("System").expect("System not found");
         assert_eq!(system_entry.kind, SymbolKind::Class);
 
-        // System scope has out
+        // System scope has out — a PrintStream object, not a class
         let system_st = system_entry.st.as_ref().expect("System has no child scope");
         let out_entry = system_st.borrow().lookup_local("out")
             .cloned()
             .expect("out not found");
-        assert_eq!(out_entry.kind, SymbolKind::Class);
+        assert_eq!(out_entry.kind, SymbolKind::Field);
+        assert_eq!(out_entry.typ.as_ref().map(|t| t.str()).as_deref(), Some("PrintStream"));
 
         // out scope has println
         let out_st = out_entry.st.as_ref().expect("out has no child scope").clone();
@@ -84,6 +196,43 @@ mod tests {
             .cloned()
             .expect("println not found");
         assert_eq!(println_entry.kind, SymbolKind::Method);
+        assert!(!println_entry.is_static);
+        assert_eq!(println_entry.origin, SymbolOrigin::Predefined);
+    }
+
+    #[test]
+    fn natives_are_static_methods_of_native_classes() {
+        let global = SymTab::new("global", None).into_rc();
+        build_predefined(&global);
+        let sig = || MethodType::new(TypeInfo::void(), vec![crate::Parameter::new("steps", TypeInfo::int())]);
+        declare_native(&global, "Robot.move", sig()).unwrap();
+        declare_native(&global, "Robot.turn", sig()).unwrap();
+
+        let robot = global.borrow().lookup_local("Robot").cloned().unwrap();
+        assert_eq!((robot.kind, robot.origin), (SymbolKind::Class, SymbolOrigin::Native));
+        let moves = robot.st.unwrap().borrow().lookup_local("move").cloned().unwrap();
+        assert!(moves.is_static && moves.origin.is_predefined());
+        assert_eq!(moves.typ.unwrap().to_string(), sig().to_string());
+
+        assert!(declare_native(&global, "Robot.move", sig()).unwrap_err().contains("already declared"));
+        assert!(declare_native(&global, "System.exit", sig()).unwrap_err().contains("not a native class"));
+        assert!(declare_native(&global, "move", sig()).is_err());
+    }
+
+    #[test]
+    fn library_methods_are_left_out_of_the_book_dump() {
+        let global = SymTab::new("global", None).into_rc();
+        build_predefined(&global);
+        let g = global.borrow();
+        let system = g.lookup_local("System").and_then(|e| e.st.clone()).unwrap();
+        let millis = system.borrow().lookup_local("currentTimeMillis").cloned().unwrap();
+        assert!(millis.is_static && millis.origin == SymbolOrigin::Library);
+        assert_eq!(g.lookup_local("Math").map(|e| e.origin), Some(SymbolOrigin::Library));
+
+        assert_eq!(
+            g.text_without(0, SymbolOrigin::Library),
+            "global - 1 symbols\n System\n  class - 1 symbols\n   out\n    class - 1 symbols\n     println\n      method - 0 symbols\n"
+        );
     }
 
     #[test]
@@ -1,7 +1,7 @@
 use std::cell::RefCell;
 use std::rc::Rc;
 
-use crate::entry::SymTabEntry;
+use crate::entry::{SymTabEntry, SymbolKind, SymbolOrigin};
 
 #[derive(Debug)]
 pub struct SymTab {
@@ -22,9 +22,17 @@ impl SymTab {
     pub fn len(&self) -> usize { self.entries.len() }
     pub fn is_empty(&self) -> bool { self.entries.is_empty() }
 
-    pub fn insert(&mut self, entry: SymTabEntry) -> Result<(), SymTabEntry> {
+    /// Number of entries in this scope and every scope nested in it.
+    pub fn total_len(&self) -> usize {
+        self.entries.iter()
+            .map(|(_, e)| 1 + e.st.as_ref().map_or(0, |st| st.borrow().total_len()))
+            .sum()
+    }
+
+    /// Add `entry`, or return the existing entry of the same name.
+    pub fn insert(&mut self, entry: SymTabEntry) -> Result<(), Box<SymTabEntry>> {
         if let Some((_, existing)) = self.entries.iter().find(|(k, _)| k == &entry.sym) {
-            return Err(existing.clone());
+            return Err(Box::new(existing.clone()));
         }
         self.entries.push((entry.sym.clone(), entry));
         Ok(())
@@ -54,15 +62,131 @@ impl SymTab {
         self.entries.iter_mut().map(|(k, v)| (k as &String, v))
     }
 
+    /// The entries of this scope that are of `kind`, in declaration order.
+    pub fn entries_of_kind(&self, kind: SymbolKind) -> impl Iterator<Item = &SymTabEntry> {
+        self.entries.iter().map(|(_, e)| e).filter(move |e| e.kind == kind)
+    }
+
+    /// The scope enclosing this one; `None` for the global scope.
+    pub fn parent(&self) -> Option<Rc<RefCell<SymTab>>> {
+        self.parent.clone()
+    }
+
+    /// How many scopes enclose this one: 0 for the global scope, 1 for a
+    /// class, 2 for a method of it.
+    pub fn depth(&self) -> usize {
+        self.parent.as_ref().map_or(0, |p| 1 + p.borrow().depth())
+    }
+
+    /// The scopes declared directly in this one, in declaration order.
+    pub fn children(&self) -> Vec<Rc<RefCell<SymTab>>> {
+        self.entries.iter().filter_map(|(_, e)| e.st.clone()).collect()
+    }
+
+    /// Call `f` with every entry of this scope and of every scope nested
+    /// in it, and the scope it is in — in declaration order, each entry
+    /// before the entries of its own scope.
+    pub fn walk(&self, f: &mut impl FnMut(&SymTab, &SymTabEntry)) {
+        for (_, entry) in &self.entries {
+            f(self, entry);
+            if let Some(st) = &entry.st {
+                st.borrow().walk(f);
+            }
+        }
+    }
+
     pub fn print(&self, indent: usize) {
+        print!("{}", self.to_text(indent));
+    }
+
+    /// The scope and everything nested in it, in the format of [`print`](Self::print).
+    pub fn to_text(&self, indent: usize) -> String {
+        self.text_of(indent, |_| true)
+    }
+
+    /// [`to_text`](Self::to_text) of the predefined symbols of this scope.
+    pub fn prelude_text(&self, indent: usize) -> String {
+        self.text_of(indent, |e| e.origin.is_predefined())
+    }
+
+    /// [`to_text`](Self::to_text) without the predefined symbols: what
+    /// the source files declare.
+    pub fn user_text(&self, indent: usize) -> String {
+        self.text_of(indent, |e| !e.origin.is_predefined())
+    }
+
+    /// [`to_text`](Self::to_text) without the symbols of `origin`, in this
+    /// scope or any nested in it.
+    pub fn text_without(&self, indent: usize, origin: SymbolOrigin) -> String {
+        let entries: Vec<_> = self.entries.iter().filter(|(_, e)| e.origin != origin).collect();
+        let pad = " ".repeat(indent);
+        let mut out = format!("{}{} - {} symbols\n", pad, self.scope, entries.len());
+        for (name, entry) in entries {
+            out.push_str(&format!("{} {}\n", pad, name));
+            if let Some(ref child_st) = entry.st {
+                out.push_str(&child_st.borrow().text_without(indent + 2, origin));
+            }
+        }
+        out
+    }
+
+    fn text_of(&self, indent: usize, keep: impl Fn(&SymTabEntry) -> bool) -> String {
+        let entries: Vec<_> = self.entries.iter().filter(|(_, e)| keep(e)).collect();
         let pad = " ".repeat(indent);
-        println!("{}{} - {} symbols", pad, self.scope, self.len());
-        for (name, entry) in &self.entries {
+        let mut out = format!("{}{} - {} symbols\n", pad, self.scope, entries.len());
+        for (name, entry) in entries {
             let child_pad = " ".repeat(indent + 1);
-            println!("{}{}", child_pad, name);
+            out.push_str(&format!("{}{}\n", child_pad, name));
             if let Some(ref child_st) = entry.st {
-                child_st.borrow().print(indent + 2);
+                out.push_str(&child_st.borrow().to_text(indent + 2));
             }
         }
+        out
+    }
+}
+
+// ─── Tests ───────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// global { C { f, m { p, x } } }
+    fn scopes() -> Rc<RefCell<SymTab>> {
+        let global = SymTab::new("global", None).into_rc();
+        let class = SymTab::new("class", Some(Rc::clone(&global))).into_rc();
+        let method = SymTab::new("method", Some(Rc::clone(&class))).into_rc();
+        for (name, kind) in [("p", SymbolKind::Param), ("x", SymbolKind::Local)] {
+            method.borrow_mut().insert(SymTabEntry::new(name, kind, Rc::clone(&method), false)).unwrap();
+        }
+        class.borrow_mut().insert(SymTabEntry::new("f", SymbolKind::Field, Rc::clone(&class), false)).unwrap();
+        class.borrow_mut().insert(SymTabEntry::with_scope(
+            "m", SymbolKind::Method, Rc::clone(&class), false, Rc::clone(&method),
+        )).unwrap();
+        global.borrow_mut().insert(SymTabEntry::with_scope(
+            "C", SymbolKind::Class, Rc::clone(&global), false, class,
+        )).unwrap();
+        global
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn walk_visits_every_scope_in_order() {
+        let global = scopes();
+        let mut seen = Vec::new();
+        global.borrow().walk(&mut |scope, e| seen.push(format!("{}:{}@{}", scope.scope, e.sym, scope.depth())));
+        assert_eq!(seen, ["global:C@0", "class:f@1", "class:m@1", "method:p@2", "method:x@2"]);
+        assert_eq!(seen.len(), global.borrow().total_len());
+    }
+
+    #[test]
+    fn kinds_children_and_parents() {
+        let global = scopes();
+        let class = global.borrow().children().remove(0);
+        let names: Vec<_> = class.borrow().entries_of_kind(SymbolKind::Field).map(|e| e.sym.clone()).collect();
+        assert_eq!(names, ["f"]);
+        assert_eq!(class.borrow().entries_of_kind(SymbolKind::Local).count(), 0);
+        let method = class.borrow().children().remove(0);
+        assert!(Rc::ptr_eq(&method.borrow().parent().unwrap(), &class));
+        assert!(global.borrow().parent().is_none());
+    }
+}
@@ -121,7 +121,8 @@ impl fmt::Display for ClassType {
 #[derive(Debug, Clone)]
 pub enum TypeInfo {
     /// A primitive or built-in base type.
-    /// Covers: "int", "double", "boolean", "String", "void", "null", "n/a", "unknown"
+    /// Covers: "int", "long", "char", "double", "boolean", "String", "void",
+    /// "null", "n/a", "unknown"
     Base(String),
 
     /// An array type — wraps the element type.
@@ -139,6 +140,8 @@ impl TypeInfo {
     // ─── Constructors ─────────────────────────────────────────────────────
 
     pub fn int()     -> Self { TypeInfo::Base("int".to_string()) }
+    pub fn long()    -> Self { TypeInfo::Base("long".to_string()) }
+    pub fn char()    -> Self { TypeInfo::Base("char".to_string()) }
     pub fn double()  -> Self { TypeInfo::Base("double".to_string()) }
     pub fn boolean() -> Self { TypeInfo::Base("boolean".to_string()) }
     pub fn string()  -> Self { TypeInfo::Base("String".to_string()) }
@@ -181,9 +184,15 @@ impl TypeInfo {
         self.basetype().to_string()
     }
 
-    /// Returns true if this is a numeric type (int or double).
+    /// The type as it is spelled in Java, for messages: `int[]`,
+    /// `String`, `(int,double)->void` for a method.
+    pub fn display(&self) -> TypeDisplay<'_> {
+        TypeDisplay(self)
+    }
+
+    /// Returns true if this is a numeric type (int, long, char or double).
     pub fn is_numeric(&self) -> bool {
-        matches!(self, TypeInfo::Base(s) if s == "int" || s == "double")
+        matches!(self, TypeInfo::Base(s) if matches!(s.as_str(), "int" | "long" | "char" | "double"))
     }
 
     /// Returns true if this is the boolean type.
@@ -208,6 +217,23 @@ impl fmt::Display for TypeInfo {
     }
 }
 
+/// A [`TypeInfo`] spelled as in Java; see [`TypeInfo::display`].
+pub struct TypeDisplay<'a>(&'a TypeInfo);
+
+impl fmt::Display for TypeDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            TypeInfo::Base(s)   => write!(f, "{}", s),
+            TypeInfo::Array(t)  => write!(f, "{}[]", t.display()),
+            TypeInfo::Method(m) => {
+                let params: Vec<String> = m.parameters.iter().map(|p| p.param_type.display().to_string()).collect();
+                write!(f, "({})->{}", params.join(","), m.return_type.display())
+            }
+            TypeInfo::Class(c)  => write!(f, "{}", c.name),
+        }
+    }
+}
+
 // ─── Tests ───────────────────────────────────────────────────────────────────
 
 #[cfg(test)]
@@ -256,6 +282,16 @@ mod tests {
         assert_eq!(t.to_string(), "hello");
     }
 
+    #[test]
+    fn test_display_spells_types_as_java() {
+        let params = vec![Parameter::new("n", TypeInfo::int()), Parameter::new("x", TypeInfo::double())];
+        assert_eq!(TypeInfo::method(TypeInfo::void(), params).display().to_string(), "(int,double)->void");
+        let main = TypeInfo::method(TypeInfo::void(), vec![Parameter::new("argv", TypeInfo::array(TypeInfo::string()))]);
+        assert_eq!(main.display().to_string(), "(String[])->void");
+        assert_eq!(TypeInfo::array(TypeInfo::array(TypeInfo::class("hello"))).display().to_string(), "hello[][]");
+        assert_eq!(TypeInfo::method(TypeInfo::int(), vec![]).display().to_string(), "()->int");
+    }
+
     #[test]
     fn test_same_base() {
         assert!(TypeInfo::int().same_base(&TypeInfo::int()));
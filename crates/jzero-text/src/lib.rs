@@ -0,0 +1,379 @@
+//! # jzero-text
+//!
+//! Editor-agnostic text edits for Jzero tooling.
+//!
+//! The formatter, autofixes and rename all produce changes to a source file.
+//! Rather than each splicing strings itself, they describe their changes as
+//! [`TextEdit`]s — a byte [`Span`] of the original text plus its replacement
+//! — and hand them to [`apply_edits`].
+//!
+//! All spans refer to the *original* text, so edits can be collected in any
+//! order.  They must not overlap; insertions (empty spans) at the same
+//! offset are applied in the order given.
+//!
+//! ```
+//! use jzero_text::{Span, TextEdit, apply_edits};
+//!
+//! let src = "int x;";
+//! let edits = [
+//!     TextEdit::replace(Span::new(4, 5), "count"),
+//!     TextEdit::insert(0, "// counter\n"),
+//! ];
+//! assert_eq!(apply_edits(src, &edits), "// counter\nint count;");
+//! ```
+//!
+//! Errors and warnings use the same spans: every phase converts its own
+//! error type into a [`Diagnostic`], which renders the same way whichever
+//! phase produced it.
+
+use std::fmt;
+
+// ─── Span ────────────────────────────────────────────────────────────────────
+
+/// A half-open byte range `start..end` into a source string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Self {
+        Span { start, end }
+    }
+
+    /// An empty span at `offset` (an insertion point).
+    pub fn at(offset: usize) -> Self {
+        Span { start: offset, end: offset }
+    }
+
+    pub fn len(&self) -> usize { self.end - self.start }
+    pub fn is_empty(&self) -> bool { self.start == self.end }
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}..{}", self.start, self.end)
+    }
+}
+
+// ─── FileId ──────────────────────────────────────────────────────────────────
+
+/// A source file of a compilation, by its position in the list of files.
+/// A single file compiled on its own is file 0, the default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct FileId(u32);
+
+impl FileId {
+    pub const fn new(index: u32) -> Self {
+        FileId(index)
+    }
+
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+impl fmt::Display for FileId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
+
+// ─── TextEdit ────────────────────────────────────────────────────────────────
+
+/// Replace the text at `span` with `replacement`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextEdit {
+    pub span: Span,
+    pub replacement: String,
+}
+
+impl TextEdit {
+    pub fn replace(span: Span, replacement: &str) -> Self {
+        TextEdit { span, replacement: replacement.to_string() }
+    }
+
+    pub fn insert(offset: usize, text: &str) -> Self {
+        TextEdit::replace(Span::at(offset), text)
+    }
+
+    pub fn delete(span: Span) -> Self {
+        TextEdit::replace(span, "")
+    }
+}
+
+// ─── Errors ──────────────────────────────────────────────────────────────────
+
+/// Why a set of edits cannot be applied.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EditError {
+    /// A span ends past the end of the text or has `start > end`.
+    OutOfBounds { span: Span, len: usize },
+    /// A span boundary falls inside a multi-byte character.
+    NotCharBoundary { span: Span },
+    /// Two edits touch the same text.
+    Overlap { first: Span, second: Span },
+}
+
+impl fmt::Display for EditError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EditError::OutOfBounds { span, len } =>
+                write!(f, "edit span {} is out of bounds for text of length {}", span, len),
+            EditError::NotCharBoundary { span } =>
+                write!(f, "edit span {} splits a character", span),
+            EditError::Overlap { first, second } =>
+                write!(f, "edit spans {} and {} overlap", first, second),
+        }
+    }
+}
+
+impl std::error::Error for EditError {}
+
+// ─── Validation and application ──────────────────────────────────────────────
+
+/// Check that every edit fits in `source` and that no two overlap.
+pub fn validate_edits(source: &str, edits: &[TextEdit]) -> Result<(), EditError> {
+    for e in edits {
+        let span = e.span;
+        if span.start > span.end || span.end > source.len() {
+            return Err(EditError::OutOfBounds { span, len: source.len() });
+        }
+        if !source.is_char_boundary(span.start) || !source.is_char_boundary(span.end) {
+            return Err(EditError::NotCharBoundary { span });
+        }
+    }
+    let sorted = sorted(edits);
+    for pair in sorted.windows(2) {
+        let (a, b) = (pair[0].span, pair[1].span);
+        if b.start < a.end {
+            return Err(EditError::Overlap { first: a, second: b });
+        }
+    }
+    Ok(())
+}
+
+/// Apply `edits` to `source`, or report why they cannot be applied.
+pub fn try_apply_edits(source: &str, edits: &[TextEdit]) -> Result<String, EditError> {
+    validate_edits(source, edits)?;
+    let mut out = String::with_capacity(source.len());
+    let mut pos = 0;
+    for e in sorted(edits) {
+        out.push_str(&source[pos..e.span.start]);
+        out.push_str(&e.replacement);
+        pos = e.span.end;
+    }
+    out.push_str(&source[pos..]);
+    Ok(out)
+}
+
+/// Apply `edits` to `source`.
+///
+/// # Panics
+/// Panics if the edits are invalid; see [`validate_edits`].  Use
+/// [`try_apply_edits`] for edits from an untrusted source.
+pub fn apply_edits(source: &str, edits: &[TextEdit]) -> String {
+    match try_apply_edits(source, edits) {
+        Ok(s) => s,
+        Err(e) => panic!("apply_edits: {}", e),
+    }
+}
+
+/// The smallest single edit that turns `old` into `new`.
+///
+/// Returns `None` if the texts are equal.  Used by tools that compute a
+/// whole new text (the formatter) but want to report a minimal change.
+pub fn diff_edit(old: &str, new: &str) -> Option<TextEdit> {
+    if old == new { return None; }
+    let mut prefix = old.bytes().zip(new.bytes()).take_while(|(a, b)| a == b).count();
+    while !old.is_char_boundary(prefix) || !new.is_char_boundary(prefix) { prefix -= 1; }
+    let max_suffix = old.len().min(new.len()) - prefix;
+    let mut suffix = old.bytes().rev().zip(new.bytes().rev())
+        .take(max_suffix)
+        .take_while(|(a, b)| a == b)
+        .count();
+    while !old.is_char_boundary(old.len() - suffix) || !new.is_char_boundary(new.len() - suffix) {
+        suffix -= 1;
+    }
+    Some(TextEdit::replace(
+        Span::new(prefix, old.len() - suffix),
+        &new[prefix..new.len() - suffix],
+    ))
+}
+
+/// Stable sort by span, so same-offset insertions keep their given order.
+fn sorted(edits: &[TextEdit]) -> Vec<&TextEdit> {
+    let mut v: Vec<&TextEdit> = edits.iter().collect();
+    v.sort_by_key(|e| (e.span.start, e.span.end));
+    v
+}
+
+// ─── Diagnostics ─────────────────────────────────────────────────────────────
+
+/// How serious a [`Diagnostic`] is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    Error,
+    Warning,
+    Note,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Error   => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+            Severity::Note    => write!(f, "note"),
+        }
+    }
+}
+
+/// A message about a span of a source file, from any phase.
+///
+/// Phases keep their own error types; each converts into a `Diagnostic`
+/// so that tools can render, sort and filter them uniformly.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub span: Span,
+    pub message: String,
+    /// A short stable name for the kind of problem, e.g. `unknown-char`.
+    pub code: Option<&'static str>,
+    /// The values a translation of `message` needs, by name, e.g.
+    /// `("name", "x")` for `undeclared variable 'x'`.
+    pub args: Vec<(&'static str, String)>,
+}
+
+impl Diagnostic {
+    pub fn error(span: Span, message: impl Into<String>) -> Self {
+        Diagnostic { severity: Severity::Error, span, message: message.into(), code: None, args: Vec::new() }
+    }
+
+    pub fn warning(span: Span, message: impl Into<String>) -> Self {
+        Diagnostic { severity: Severity::Warning, ..Diagnostic::error(span, message) }
+    }
+
+    pub fn with_code(mut self, code: &'static str) -> Self {
+        self.code = Some(code);
+        self
+    }
+
+    pub fn with_arg(mut self, name: &'static str, value: impl fmt::Display) -> Self {
+        self.args.push((name, value.to_string()));
+        self
+    }
+
+    /// Render as `error: line 3: message [code]`, the format the compiler
+    /// uses for every phase.
+    pub fn render(&self, source: &str) -> String {
+        let (line, _) = line_col(source, self.span.start);
+        let mut out = format!("{}: line {}: {}", self.severity, line, self.message);
+        if let Some(code) = self.code {
+            out.push_str(&format!(" [{}]", code));
+        }
+        out
+    }
+}
+
+/// The 1-based line and column (in characters) of byte `offset`.
+pub fn line_col(source: &str, offset: usize) -> (usize, usize) {
+    let before = &source[..source.floor_char_boundary(offset.min(source.len()))];
+    let line = before.matches('\n').count() + 1;
+    let col = before.rsplit('\n').next().unwrap_or_default().chars().count() + 1;
+    (line, col)
+}
+
+/// Byte offset of the start of 1-based line `line`; the end of `source`
+/// if it has fewer lines.
+pub fn line_start(source: &str, line: usize) -> usize {
+    source.split_inclusive('\n').take(line.saturating_sub(1)).map(str::len).sum()
+}
+
+// ─── Tests ───────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edits_apply_in_any_order() {
+        let src = "a = b + c;";
+        let edits = [
+            TextEdit::replace(Span::new(8, 9), "d"),
+            TextEdit::replace(Span::new(0, 1), "x"),
+        ];
+        assert_eq!(apply_edits(src, &edits), "x = b + d;");
+    }
+
+    #[test]
+    fn same_offset_insertions_keep_order() {
+        let edits = [TextEdit::insert(0, "a"), TextEdit::insert(0, "b")];
+        assert_eq!(apply_edits("!", &edits), "ab!");
+    }
+
+    #[test]
+    fn insertion_before_replacement_is_allowed() {
+        let edits = [
+            TextEdit::replace(Span::new(0, 3), "int"),
+            TextEdit::insert(0, "final "),
+        ];
+        assert_eq!(apply_edits("var x;", &edits), "final int x;");
+    }
+
+    #[test]
+    fn overlap_is_rejected() {
+        let edits = [
+            TextEdit::replace(Span::new(0, 4), "x"),
+            TextEdit::replace(Span::new(3, 5), "y"),
+        ];
+        assert_eq!(
+            try_apply_edits("abcdef", &edits),
+            Err(EditError::Overlap { first: Span::new(0, 4), second: Span::new(3, 5) })
+        );
+    }
+
+    #[test]
+    fn insertion_inside_replacement_is_rejected() {
+        let edits = [TextEdit::delete(Span::new(0, 4)), TextEdit::insert(2, "!")];
+        assert!(matches!(try_apply_edits("abcdef", &edits), Err(EditError::Overlap { .. })));
+    }
+
+    #[test]
+    fn out_of_bounds_and_char_boundary() {
+        assert!(matches!(
+            try_apply_edits("abc", &[TextEdit::delete(Span::new(1, 9))]),
+            Err(EditError::OutOfBounds { .. })
+        ));
+        assert!(matches!(
+            try_apply_edits("→", &[TextEdit::delete(Span::new(0, 1))]),
+            Err(EditError::NotCharBoundary { .. })
+        ));
+    }
+
+    #[test]
+    fn diff_edit_is_minimal() {
+        let e = diff_edit("int  x;", "int x;").unwrap();
+        assert_eq!(e.span.len(), 1);
+        assert_eq!(apply_edits("int  x;", &[e]), "int x;");
+        assert!(diff_edit("same", "same").is_none());
+    }
+
+    #[test]
+    fn diff_edit_respects_char_boundaries() {
+        let (old, new) = ("a→b", "a←b");
+        let e = diff_edit(old, new).unwrap();
+        assert_eq!(apply_edits(old, &[e]), new);
+    }
+
+    #[test]
+    fn diagnostic_renders_line_and_code() {
+        let src = "int x;\nint @;";
+        let d = Diagnostic::error(Span::new(11, 12), "unrecognized character '@'")
+            .with_code("unknown-char");
+        assert_eq!(d.render(src), "error: line 2: unrecognized character '@' [unknown-char]");
+        assert_eq!(line_col(src, 11), (2, 5));
+        assert_eq!(line_col("a→b", 4), (1, 3));
+        assert_eq!([1, 2, 3].map(|line| line_start(src, line)), [0, 7, 13]);
+    }
+}
@@ -1,16 +1,30 @@
 //! Jzero bytecode interpreter (Chapter 12).
 //!
 //! Public entry point: `run(bytes, args)` takes a `.j0` binary image and
-//! the command-line arguments to pass to main().
+//! the command-line arguments to pass to main().  `run_with_host` does the
+//! same with a caller-supplied I/O policy (see [`host`]).
 
+pub mod host;
 pub mod machine;
+pub mod native;
 pub mod runtime;
+pub mod trace;
 
+pub use host::{Host, Rng, SandboxHost, StdHost};
 pub use machine::J0Machine;
+pub use native::{NativeType, Natives, Value};
+pub use trace::ExecTrace;
 
 /// Execute a `.j0` binary image, passing `args` as argv to main().
 /// Returns the collected stdout output.
 pub fn run(bytes: &[u8], args: &[String]) -> Result<String, String> {
     let mut m = J0Machine::load(bytes, args.len() as i64)?;
     m.interp()
-}
\ No newline at end of file
+}
+
+/// Execute a `.j0` binary image with built-in I/O routed through `host`.
+/// Returns the collected stdout output, as `run` does.
+pub fn run_with_host(bytes: &[u8], args: &[String], host: Box<dyn Host>) -> Result<String, String> {
+    let mut m = J0Machine::load_with_host(bytes, args.len() as i64, host)?;
+    m.interp()
+}
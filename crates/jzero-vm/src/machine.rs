@@ -6,11 +6,27 @@
 //! code        – the full .j0 image as bytes
 //! data        – static data section (string literals, NUL-terminated)
 //! stack       – fn_addr, args, locals, temporaries (integers and string keys)
+//! heap        – arrays: each one's length, then its elements, one word each
 //! string_pool – runtime string storage: maps i64 key ↔ String value
 //!               String keys are negative integers (-1, -2, …) so they never
 //!               collide with data-section byte offsets (which are ≥ 0).
 //! ```
 //!
+//! # Arrays
+//!
+//! `NEWARRAY` puts an array on the heap and pushes the index of its first
+//! element, which is never 0, so 0 is the null array.  The word before
+//! the first element holds the length, which `ASIZE` reads.  `ALOAD` and
+//! `ASTORE` reach an element by adding the index to the array; `BCHK`,
+//! which the code generator puts before them, traps on an index outside
+//! the length.
+//!
+//! # Doubles
+//!
+//! A `double` is a fixed-point number in one word, [`FIXED_ONE`] to 1, so
+//! `ADD`, `SUB`, `NEG` and the comparisons serve it as they do an int.
+//! `FMUL` and `FDIV` rescale their result, and `FTOS` prints one.
+//!
 //! # String representation
 //!
 //! String literals live in the data section as NUL-terminated UTF-8.
@@ -25,6 +41,13 @@
 //! `do_println` checks whether the value on the stack is a pool key (< 0) or
 //! a data-section offset (≥ 0) and reads the string accordingly.
 //!
+//! # Host I/O
+//!
+//! Built-ins never touch the process directly; they go through the machine's
+//! [`Host`](crate::host::Host).  `load` installs a `SandboxHost`, so a plain
+//! `run()` has no side effects beyond the returned output.  Everything printed
+//! is also appended to `output`, which `interp` returns.
+//!
 //! # Calling convention
 //!
 //! Before CALL n the stack looks like:
@@ -36,9 +59,21 @@
 //!
 //! RETURN pops (saved_ip, saved_bp, fn_slot), restores ip and bp, and sets
 //! sp = fn_slot - 1 to clean up the entire frame.
-
-use std::collections::HashMap;
-use jzero_codegen::byc::{Byc, BycRegion, Op};
+//!
+//! # Introspection
+//!
+//! The machine counts the instructions it executes, in all and per
+//! instruction; given the image's [`LineTable`] it totals them per source
+//! line ([`J0Machine::counts_by_line`]), for coverage and profiling to share.
+//! A step budget stops a program that runs too long, and an [`ExecTrace`]
+//! shows each instruction as it runs.
+
+use std::collections::{BTreeMap, HashMap};
+use jzero_codegen::byc::{Byc, BycRegion, Op, FIXED_ONE};
+use jzero_codegen::pipeline::LineTable;
+use crate::host::{Host, SandboxHost};
+use crate::native::{Native, Natives};
+use crate::trace::{self, ExecTrace};
 
 const STACK_WORDS: usize = 100_000;
 const MAGIC:   &[u8; 8] = b"Jzero!!\0";
@@ -98,11 +133,22 @@ pub struct J0Machine {
     sp:          i64,
     bp:          i64,
     call_stack:  Vec<(usize, i64, i64)>,
-    #[allow(dead_code)]
-    hp:          i64,
+    /// The arrays; see the module docs.
+    heap:        Vec<i64>,
     /// Runtime string pool (Chapter 15).
     pub spool:   StringPool,
     pub output:  String,
+    /// I/O policy for runtime built-ins.
+    pub host:    Box<dyn Host>,
+    /// Instructions executed so far.
+    steps:       u64,
+    /// Times each instruction word of the image has been executed.
+    counts:      Vec<u64>,
+    step_budget: Option<u64>,
+    lines:       LineTable,
+    trace:       Option<ExecTrace>,
+    /// The image's native imports, linked by [`J0Machine::link`].
+    natives:     Vec<Native>,
 }
 
 impl J0Machine {
@@ -111,6 +157,11 @@ impl J0Machine {
     // -----------------------------------------------------------------------
 
     pub fn load(bytes: &[u8], argc: i64) -> Result<Self, String> {
+        Self::load_with_host(bytes, argc, Box::new(SandboxHost::new()))
+    }
+
+    /// Load an image whose built-ins will perform I/O through `host`.
+    pub fn load_with_host(bytes: &[u8], argc: i64, host: Box<dyn Host>) -> Result<Self, String> {
         if bytes.len() < 24 {
             return Err("image too small".into());
         }
@@ -142,21 +193,108 @@ impl J0Machine {
             sp:         -1,
             bp:         -1,
             call_stack: Vec::new(),
-            hp:         0,
+            heap:       Vec::new(),
             spool:      StringPool::new(),
             output:     String::new(),
+            host,
+            steps:       0,
+            counts:      vec![0; bytes.len() / 8],
+            step_budget: None,
+            lines:       LineTable::default(),
+            trace:       None,
+            natives:     Vec::new(),
         })
     }
 
+    /// Link the image's native imports `imports` (qualified names, in
+    /// import order) to their implementations in `natives`.
+    ///
+    /// # Errors
+    /// Returns a message naming the first import `natives` lacks.
+    pub fn link(&mut self, imports: &[String], natives: &Natives) -> Result<(), String> {
+        self.natives = imports.iter()
+            .map(|name| natives.get(name).cloned()
+                .ok_or_else(|| format!("native method {} is not registered", name)))
+            .collect::<Result<_, _>>()?;
+        Ok(())
+    }
+
+    /// Native import `index`, if linked.
+    pub fn native(&self, index: usize) -> Option<Native> {
+        self.natives.get(index).cloned()
+    }
+
+    /// Give source lines to the image's instructions, for
+    /// [`counts_by_line`](J0Machine::counts_by_line) and the trace.
+    pub fn set_line_table(&mut self, lines: LineTable) {
+        self.lines = lines;
+    }
+
+    /// Stop with an error once `max` instructions have been executed.
+    pub fn set_step_budget(&mut self, max: u64) {
+        self.step_budget = Some(max);
+    }
+
+    /// Show each instruction executed on `trace`.
+    pub fn set_trace(&mut self, trace: ExecTrace) {
+        self.trace = Some(trace);
+    }
+
+    /// How many instructions have been executed.
+    pub fn steps(&self) -> u64 { self.steps }
+
+    /// How many more instructions the step budget allows; `None` without one.
+    pub fn steps_left(&self) -> Option<u64> {
+        self.step_budget.map(|max| max.saturating_sub(self.steps))
+    }
+
+    /// How many times the instruction at byte offset `offset` has been executed.
+    pub fn exec_count(&self, offset: usize) -> u64 {
+        self.counts.get(offset / 8).copied().unwrap_or(0)
+    }
+
+    /// Instructions executed per source line, for the lines that ran at
+    /// least one.  Empty without a line table.
+    pub fn counts_by_line(&self) -> BTreeMap<usize, u64> {
+        let mut by_line = BTreeMap::new();
+        for (word, &n) in self.counts.iter().enumerate() {
+            if n == 0 { continue; }
+            if let Some(line) = self.lines.line_of(word * 8) {
+                *by_line.entry(line).or_insert(0) += n;
+            }
+        }
+        by_line
+    }
+
     // -----------------------------------------------------------------------
     // Fetch-decode-execute loop
     // -----------------------------------------------------------------------
 
     pub fn interp(&mut self) -> Result<String, String> {
+        let result = self.execute();
+        if let Some(trace) = &mut self.trace {
+            trace.finish();
+        }
+        result
+    }
+
+    fn execute(&mut self) -> Result<String, String> {
         loop {
             let word = self.fetch()?;
+            let at   = self.ip - 8;
             let byc  = Byc::from_binary(&word)
-                .ok_or_else(|| format!("illegal opcode 0x{:02x} at ip={}", word[0], self.ip - 8))?;
+                .ok_or_else(|| format!("illegal opcode 0x{:02x} at ip={}", word[0], at))?;
+
+            if self.step_budget.is_some_and(|max| self.steps >= max) {
+                return Err(format!("step budget of {} instructions exhausted at ip={}", self.steps, at));
+            }
+            self.steps += 1;
+            self.counts[at / 8] += 1;
+            if self.trace.is_some() {
+                let value = trace::operand_value(&byc, |r, o| self.deref(r, o));
+                let line  = self.lines.line_of(at);
+                if let Some(t) = &mut self.trace { t.record(at, &byc, value, line); }
+            }
 
             match byc.op {
                 Op::Halt => break,
@@ -177,6 +315,15 @@ impl J0Machine {
                     self.push(a % b);
                 }
                 Op::Neg => { let a = self.pop(); self.push(-a); }
+                Op::Fmul => {
+                    let (b,a) = self.pop2();
+                    self.push((a as i128 * b as i128 / FIXED_ONE as i128) as i64);
+                }
+                Op::Fdiv => {
+                    let (b,a) = self.pop2();
+                    if b == 0 { return Err("division by zero".into()); }
+                    self.push((a as i128 * FIXED_ONE as i128 / b as i128) as i64);
+                }
 
                 // ── String operations (Chapter 15) ───────────────────────
                 //
@@ -274,7 +421,7 @@ impl J0Machine {
                         self.bp = fn_slot;
                         self.ip = f as usize;
                     } else {
-                        crate::runtime::dispatch(self, f)?;
+                        crate::runtime::dispatch(self, f, n as usize)?;
                     }
                 }
                 Op::Return => {
@@ -291,6 +438,43 @@ impl J0Machine {
                     let key = self.spool.put(s);
                     self.push(key);
                 }
+                Op::Ftos => {
+                    let d   = self.pop();
+                    let key = self.spool.put(double_text(d));
+                    self.push(key);
+                }
+
+                // ── Arrays ──────────────────────────────────────────────
+                Op::Newarray => {
+                    let n = self.pop();
+                    if n < 0 { return Err(format!("negative array size: {}", n)); }
+                    self.heap.push(n);
+                    let array = self.heap.len() as i64;
+                    self.heap.resize(self.heap.len() + n as usize, 0);
+                    self.push(array);
+                }
+                Op::Aload => {
+                    let (index, array) = self.pop2();
+                    let v = self.read_heap(array, index)?;
+                    self.push(v);
+                }
+                Op::Astore => {
+                    let v = self.pop();
+                    let (index, array) = self.pop2();
+                    self.write_heap(array, index, v)?;
+                }
+                Op::Asize => {
+                    let array = self.pop();
+                    let n = self.read_heap(array, -1)?;
+                    self.push(n);
+                }
+                Op::Bchk => {
+                    let (index, array) = self.pop2();
+                    let n = self.read_heap(array, -1)?;
+                    if !(0..n).contains(&index) {
+                        return Err(format!("array index {} out of bounds for length {}", index, n));
+                    }
+                }
             }
         }
 
@@ -395,6 +579,32 @@ impl J0Machine {
         Ok(())
     }
 
+    // -----------------------------------------------------------------------
+    // Heap helpers
+    // -----------------------------------------------------------------------
+
+    /// The heap word `index` words past the start of `array`.
+    fn heap_word(&self, array: i64, index: i64) -> Result<usize, String> {
+        if array == 0 {
+            return Err("null array".into());
+        }
+        let off = array + index;
+        if off < 0 || off as usize >= self.heap.len() {
+            return Err(format!("heap access out of range: array={} index={}", array, index));
+        }
+        Ok(off as usize)
+    }
+
+    fn read_heap(&self, array: i64, index: i64) -> Result<i64, String> {
+        Ok(self.heap[self.heap_word(array, index)?])
+    }
+
+    fn write_heap(&mut self, array: i64, index: i64, val: i64) -> Result<(), String> {
+        let off = self.heap_word(array, index)?;
+        self.heap[off] = val;
+        Ok(())
+    }
+
     // -----------------------------------------------------------------------
     // Fetch
     // -----------------------------------------------------------------------
@@ -446,6 +656,14 @@ impl J0Machine {
     pub fn sp(&self)   -> i64 { self.sp }
 }
 
+/// A fixed-point double as Java prints it, `1.0` or `0.25`, to the 7
+/// decimal places it keeps.
+fn double_text(d: i64) -> String {
+    let text = format!("{:.7}", d as f64 / FIXED_ONE as f64);
+    let text = text.trim_end_matches('0');
+    if text.ends_with('.') { format!("{}0", text) } else { text.to_string() }
+}
+
 fn read_i64(bytes: &[u8], off: usize) -> i64 {
     i64::from_le_bytes(bytes[off..off + 8].try_into().unwrap())
 }
\ No newline at end of file
@@ -0,0 +1,165 @@
+//! Native methods — Jzero static methods implemented in Rust.
+//!
+//! A program embedding the VM registers each native with its signature
+//! in a [`Natives`] registry.  An image names the natives it calls in an
+//! import table ([`BytecodeOutput::natives`]); [`J0Machine::link`] looks
+//! each one up before the image runs, and `CALL` reaches import `i` at
+//! function address [`NATIVE_BASE`]` - i`.
+//!
+//! Arguments arrive converted to [`Value`]s by the declared parameter
+//! types, and the result is checked against the declared return type.
+//!
+//! [`BytecodeOutput::natives`]: jzero_codegen::pipeline::BytecodeOutput::natives
+//! [`J0Machine::link`]: crate::J0Machine::link
+
+use std::fmt;
+use std::rc::Rc;
+
+pub use jzero_codegen::bytecode::NATIVE_BASE;
+
+use crate::machine::J0Machine;
+
+/// A type a native method can take or return.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NativeType {
+    Int,
+    Boolean,
+    String,
+    /// Return type only: no result.
+    Void,
+}
+
+impl fmt::Display for NativeType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            NativeType::Int     => "int",
+            NativeType::Boolean => "boolean",
+            NativeType::String  => "String",
+            NativeType::Void    => "void",
+        })
+    }
+}
+
+/// A value passed to or returned from a native method.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Value {
+    Int(i64),
+    Boolean(bool),
+    String(String),
+    Void,
+}
+
+impl Value {
+    pub fn native_type(&self) -> NativeType {
+        match self {
+            Value::Int(_)     => NativeType::Int,
+            Value::Boolean(_) => NativeType::Boolean,
+            Value::String(_)  => NativeType::String,
+            Value::Void       => NativeType::Void,
+        }
+    }
+}
+
+/// The Rust side of a native method.  An `Err` stops the program with
+/// that message, as a VM error.
+pub type NativeFn = dyn Fn(&[Value]) -> Result<Value, String>;
+
+/// A native method: its name, signature and implementation.
+#[derive(Clone)]
+pub struct Native {
+    /// `Class.method`.
+    pub name: String,
+    pub params: Vec<NativeType>,
+    pub returns: NativeType,
+    pub function: Rc<NativeFn>,
+}
+
+impl fmt::Debug for Native {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Native")
+            .field("name", &self.name)
+            .field("params", &self.params)
+            .field("returns", &self.returns)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The native methods a program embedding the VM provides.
+#[derive(Debug, Clone, Default)]
+pub struct Natives {
+    natives: Vec<Native>,
+}
+
+impl Natives {
+    pub fn new() -> Self {
+        Natives::default()
+    }
+
+    /// Register `function` as the native method `name` (`Class.method`),
+    /// replacing any registered under that name.
+    pub fn register(
+        &mut self,
+        name: &str,
+        params: &[NativeType],
+        returns: NativeType,
+        function: impl Fn(&[Value]) -> Result<Value, String> + 'static,
+    ) {
+        let native = Native { name: name.to_string(), params: params.to_vec(), returns, function: Rc::new(function) };
+        match self.natives.iter_mut().find(|n| n.name == name) {
+            Some(old) => *old = native,
+            None => self.natives.push(native),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Native> {
+        self.natives.iter().find(|n| n.name == name)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Native> {
+        self.natives.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.natives.is_empty()
+    }
+}
+
+/// Call import `index` with the `n` arguments on top of the stack.
+///
+/// Stack layout when called:
+///   sp   → first argument (arguments are pushed last to first)
+///   …
+///   sp-n → fn_addr (`NATIVE_BASE - index`)
+///
+/// The arguments and fn_addr are replaced by the result, if any.
+pub(crate) fn call(m: &mut J0Machine, index: usize, n: usize) -> Result<(), String> {
+    let native = m.native(index)
+        .ok_or_else(|| format!("native method #{} is not linked", index))?;
+    if n != native.params.len() {
+        return Err(format!("{} takes {} arguments, called with {}", native.name, native.params.len(), n));
+    }
+
+    let mut args = Vec::with_capacity(n);
+    for ty in &native.params {
+        let raw = m.pop();
+        args.push(match ty {
+            NativeType::Int     => Value::Int(raw),
+            NativeType::Boolean => Value::Boolean(raw != 0),
+            NativeType::String  => Value::String(m.resolve_string(raw)),
+            NativeType::Void    => Value::Void,
+        });
+    }
+    let _fn = m.pop();  // fn_addr
+
+    let result = (native.function)(&args).map_err(|e| format!("{}: {}", native.name, e))?;
+    if result.native_type() != native.returns {
+        return Err(format!("{} returned {}, declared {}", native.name, result.native_type(), native.returns));
+    }
+    match result {
+        Value::Int(v)     => m.push(v),
+        Value::Boolean(b) => m.push(i64::from(b)),
+        Value::String(s)  => { let key = m.spool.put(s); m.push(key); }
+        Value::Void       => {}
+    }
+    Ok(())
+}
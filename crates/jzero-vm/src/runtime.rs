@@ -3,13 +3,31 @@
 //! When `CALL` encounters a negative function address, it dispatches here.
 //! The convention:
 //!   -1  →  PrintStream__println(arg)
+//!   -2  →  InputStream__readLine()   — pushes a string-pool key
+//!   -3  →  System__currentTimeMillis() — pushes an int
+//!   -4  →  Math__randomInt()  — pushes an int in `0..2^31`
+//!   -5  →  assertEquals(expected, actual) — fails the run if they differ
+//!   -6  →  Math__random()  — pushes a fixed-point double in `[0, 1)`
+//!   NATIVE_BASE - i  →  native import i (see [`native`](crate::native))
+//!
+//! All I/O goes through the machine's [`Host`](crate::host::Host).
+//! Built-ins that produce a value replace the fn_addr sentinel with it.
+
+use jzero_codegen::byc::FIXED_BITS;
 
 use crate::machine::J0Machine;
+use crate::native::{self, NATIVE_BASE};
 
-/// Dispatch a runtime call by function index.
-pub fn dispatch(m: &mut J0Machine, f: i64) -> Result<(), String> {
+/// Dispatch a runtime call by function index; `n` is its argument count.
+pub fn dispatch(m: &mut J0Machine, f: i64, n: usize) -> Result<(), String> {
     match f {
         -1 => do_println(m),
+        -2 => do_read_line(m),
+        -3 => do_clock(m),
+        -4 => do_random(m),
+        -5 => do_assert_equals(m),
+        -6 => do_random_double(m),
+        _ if f <= NATIVE_BASE => native::call(m, (NATIVE_BASE - f) as usize, n),
         _  => Err(format!("unknown runtime function: {}", f)),
     }
 }
@@ -26,18 +44,75 @@ fn do_println(m: &mut J0Machine) -> Result<(), String> {
     let _fn = m.pop();  // fn_addr sentinel
 
     let line = m.resolve_string(arg);
+    m.host.println(&line);
     m.output.push_str(&line);
     m.output.push('\n');
 
     Ok(())
 }
 
+/// Read one line from the host and push its string-pool key.
+/// End of input yields the empty string.
+fn do_read_line(m: &mut J0Machine) -> Result<(), String> {
+    let _fn  = m.pop();  // fn_addr sentinel
+    let line = m.host.read_line().unwrap_or_default();
+    let key  = m.spool.put(line);
+    m.push(key);
+    Ok(())
+}
+
+/// Push the host clock in milliseconds.
+fn do_clock(m: &mut J0Machine) -> Result<(), String> {
+    let _fn = m.pop();  // fn_addr sentinel
+    let t   = m.host.clock();
+    m.push(t);
+    Ok(())
+}
+
+/// `assertEquals(expected, actual)`: an error naming both if they differ.
+///
+/// Stack layout when called:
+///   sp   → expected
+///   sp-1 → actual
+///   sp-2 → fn_addr sentinel (-5)
+fn do_assert_equals(m: &mut J0Machine) -> Result<(), String> {
+    let expected = m.pop();
+    let actual   = m.pop();
+    let _fn      = m.pop();  // fn_addr sentinel
+    if expected != actual {
+        return Err(format!("assertEquals failed: expected {}, but was {}", expected, actual));
+    }
+    Ok(())
+}
+
+/// Push the host's next pseudo-random number.
+fn do_random(m: &mut J0Machine) -> Result<(), String> {
+    let _fn = m.pop();  // fn_addr sentinel
+    let r   = m.host.random();
+    m.push(r);
+    Ok(())
+}
+
+/// Push the host's next pseudo-random number as a fraction of 2^31: a
+/// fixed-point double in `[0, 1)`.
+fn do_random_double(m: &mut J0Machine) -> Result<(), String> {
+    let _fn = m.pop();  // fn_addr sentinel
+    let r   = m.host.random();
+    m.push(r >> (31 - FIXED_BITS));
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::host::SandboxHost;
     use crate::machine::J0Machine;
 
     fn make_machine_with_data(data: &[u8]) -> J0Machine {
+        make_machine_with_host(data, Box::new(SandboxHost::new()))
+    }
+
+    fn make_machine_with_host(data: &[u8], host: Box<dyn crate::host::Host>) -> J0Machine {
         let mut image: Vec<u8> = Vec::new();
         image.extend_from_slice(b"Jzero!!\0");
         image.extend_from_slice(b"1.0\0\0\0\0\0");
@@ -55,7 +130,7 @@ mod tests {
         halt[0] = 1;
         image.extend_from_slice(&halt);
 
-        J0Machine::load(&image, 0).unwrap()
+        J0Machine::load_with_host(&image, 0, host).unwrap()
     }
 
     #[test]
@@ -97,4 +172,42 @@ mod tests {
         let key = m.spool.put("there".to_string());
         assert_eq!(m.resolve_string(key), "there");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn read_line_comes_from_host() {
+        let host = SandboxHost::new().with_input(["typed"]);
+        let mut m = make_machine_with_host(b"", Box::new(host));
+        m.push(-2);  // fn sentinel
+        dispatch(&mut m, -2, 0).unwrap();
+        let key = m.pop();
+        assert_eq!(m.resolve_string(key), "typed");
+    }
+
+    #[test]
+    fn read_line_at_eof_is_empty() {
+        let mut m = make_machine_with_data(b"");
+        m.push(-2);
+        dispatch(&mut m, -2, 0).unwrap();
+        let key = m.pop();
+        assert_eq!(m.resolve_string(key), "");
+    }
+
+    #[test]
+    fn clock_comes_from_host() {
+        let host = SandboxHost::new().with_clock(500, 5);
+        let mut m = make_machine_with_host(b"", Box::new(host));
+        m.push(-3);
+        dispatch(&mut m, -3, 0).unwrap();
+        assert_eq!(m.pop(), 500);
+        m.push(-3);
+        dispatch(&mut m, -3, 0).unwrap();
+        assert_eq!(m.pop(), 505);
+    }
+
+    #[test]
+    fn unknown_builtin_is_an_error() {
+        let mut m = make_machine_with_data(b"");
+        m.push(-99);
+        assert!(dispatch(&mut m, -99, 0).is_err());
+    }
+}
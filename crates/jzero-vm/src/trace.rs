@@ -0,0 +1,157 @@
+//! Execution tracing — one line per instruction the machine executes.
+//!
+//! An [`ExecTrace`] installed with [`J0Machine::set_trace`] is shown each
+//! instruction before it runs: its byte offset, its assembler text, the
+//! value its operand names, and the source line it came from when the
+//! machine has a [`LineTable`].  A loop can run millions of instructions, so
+//! only the first `limit` are written; the rest are counted and reported
+//! once execution stops.
+//!
+//! ```text
+//!    224  line 4    push stack:8 = 3
+//!    232  line 4    push 2
+//!    240  line 4    add
+//! ```
+//!
+//! [`J0Machine::set_trace`]: crate::J0Machine::set_trace
+//! [`LineTable`]: jzero_codegen::pipeline::LineTable
+
+use std::io::Write;
+
+use jzero_codegen::byc::{Byc, BycRegion, Op};
+
+/// How many instructions `j0 --trace-exec` shows by default.
+pub const DEFAULT_TRACE_LIMIT: u64 = 10_000;
+
+/// A sink for the instructions a machine executes.
+pub struct ExecTrace {
+    out:     Box<dyn Write>,
+    limit:   u64,
+    shown:   u64,
+    skipped: u64,
+}
+
+impl ExecTrace {
+    /// Trace to `out`, showing at most `limit` instructions.
+    pub fn new(out: Box<dyn Write>, limit: u64) -> Self {
+        ExecTrace { out, limit, shown: 0, skipped: 0 }
+    }
+
+    /// Show the instruction `byc` at byte offset `at`.  `value` is what its
+    /// operand holds, for an operand naming memory.
+    pub(crate) fn record(&mut self, at: usize, byc: &Byc, value: Option<i64>, line: Option<usize>) {
+        if self.shown >= self.limit {
+            self.skipped += 1;
+            return;
+        }
+        self.shown += 1;
+        let line = line.map_or_else(String::new, |l| format!("line {}", l));
+        let value = value.map_or_else(String::new, |v| format!(" = {}", v));
+        // Tracing must never change what the program does, so a sink that
+        // fails is ignored.
+        let _ = writeln!(self.out, "{:>7}  {:<9} {}{}", at, line, byc.text().trim(), value);
+    }
+
+    /// Report the instructions past the limit.
+    pub(crate) fn finish(&mut self) {
+        if self.skipped > 0 {
+            let _ = writeln!(self.out, "... {} more instructions not traced (limit {})", self.skipped, self.limit);
+        }
+        let _ = self.out.flush();
+    }
+}
+
+/// The value `byc`'s operand names in memory, as the trace shows it.  An
+/// immediate operand is already in the assembler text, and a branch's is a
+/// code address rather than a value.
+pub(crate) fn operand_value(byc: &Byc, deref: impl Fn(BycRegion, i64) -> Result<i64, String>) -> Option<i64> {
+    match (byc.op, byc.region) {
+        (Op::Goto | Op::Bif | Op::Call, _) => None,
+        (_, BycRegion::None | BycRegion::Imm) => None,
+        (_, region) => deref(region, byc.opnd).ok(),
+    }
+}
+
+// ─── Tests ───────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::collections::HashMap;
+    use std::rc::Rc;
+
+    use jzero_codegen::j0file::assemble;
+    use jzero_codegen::pipeline::LineTable;
+
+    use super::*;
+    use crate::J0Machine;
+
+    /// The first code instruction of an image with no data.
+    const CODE_BASE: usize = 8 * 8;
+
+    /// A countdown from 3: lines 1-2 set it up, 3-5 loop, 6 halts.
+    fn countdown() -> (Vec<u8>, LineTable) {
+        let bycs = vec![
+            Byc::imm(Op::Local, 3),
+            Byc::imm(Op::Push, 3),
+            Byc { op: Op::Pop, region: BycRegion::Stack, opnd: 16, needs_patch: false },
+            Byc { op: Op::Push, region: BycRegion::Stack, opnd: 16, needs_patch: false },
+            Byc::imm(Op::Push, 1),
+            Byc::no_operand(Op::Sub),
+            Byc { op: Op::Pop, region: BycRegion::Stack, opnd: 16, needs_patch: false },
+            Byc { op: Op::Push, region: BycRegion::Stack, opnd: 16, needs_patch: false },
+            Byc { op: Op::Bif, region: BycRegion::Abs, opnd: 3 * 8, needs_patch: false },
+            Byc::no_operand(Op::Halt),
+        ];
+        let image = assemble(&bycs, &[], &HashMap::new(), Some(CODE_BASE as i64), 0);
+        let lines = LineTable::new(CODE_BASE, vec![1, 2, 2, 3, 3, 3, 4, 5, 5, 6]);
+        (image, lines)
+    }
+
+    #[derive(Clone, Default)]
+    struct Shared(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> { self.0.borrow_mut().write(buf) }
+        fn flush(&mut self) -> std::io::Result<()> { Ok(()) }
+    }
+
+    #[test]
+    fn counts_instructions_per_line() {
+        let (image, lines) = countdown();
+        let mut m = J0Machine::load(&image, 0).unwrap();
+        m.set_line_table(lines);
+        m.interp().unwrap();
+        let by_line: Vec<(usize, u64)> = m.counts_by_line().into_iter().collect();
+        assert_eq!(by_line, [(1, 1), (2, 2), (3, 9), (4, 3), (5, 6), (6, 1)]);
+        // Four startup instructions before main, none of them on a line.
+        assert_eq!(m.steps(), 4 + 22);
+        assert_eq!(m.exec_count(CODE_BASE + 3 * 8), 3);
+    }
+
+    #[test]
+    fn step_budget_stops_a_long_run() {
+        let (image, _) = countdown();
+        let mut m = J0Machine::load(&image, 0).unwrap();
+        m.set_step_budget(10);
+        let err = m.interp().unwrap_err();
+        assert!(err.contains("step budget of 10"), "{}", err);
+        assert_eq!((m.steps(), m.steps_left()), (10, Some(0)));
+    }
+
+    #[test]
+    fn trace_shows_operands_and_stops_at_its_limit() {
+        let (image, lines) = countdown();
+        let sink = Shared::default();
+        let mut m = J0Machine::load(&image, 0).unwrap();
+        m.set_line_table(lines);
+        m.set_trace(ExecTrace::new(Box::new(sink.clone()), 8));
+        m.interp().unwrap();
+        let text = String::from_utf8(sink.0.borrow().clone()).unwrap();
+        let shown: Vec<&str> = text.lines().collect();
+        assert_eq!(shown.len(), 9, "{}", text);
+        assert_eq!(shown[4], format!("{:>7}  line 1    local 3", CODE_BASE));
+        assert!(shown[7].ends_with("line 3    push stack:16 = 3"), "{}", shown[7]);
+        assert_eq!(shown[8], "... 18 more instructions not traced (limit 8)");
+    }
+}
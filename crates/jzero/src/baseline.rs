@@ -0,0 +1,256 @@
+//! Diagnostics baselines — adopt stricter checks on existing code gradually.
+//!
+//! A baseline records the findings a set of files has today.  Later runs
+//! report only findings that are not in it, so turning on a new check does
+//! not bury students under warnings for code that was already there.
+//!
+//! Findings are matched by file, check and message, not by line, so editing
+//! the code above an old finding does not make it new again.  Each key
+//! keeps a count: a second identical finding in the same file is new.
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "findings": [
+//!     {"file": "src/T.java", "check": "class-name", "message": "...", "count": 1}
+//!   ]
+//! }
+//! ```
+
+use std::collections::BTreeMap;
+
+use crate::JzeroError;
+use crate::checks::Finding;
+use crate::project::json_str;
+
+/// Recorded findings, keyed by `(file, check, message)`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Baseline {
+    counts: BTreeMap<(String, String, String), usize>,
+}
+
+impl Baseline {
+    /// Record `findings`, each paired with the file it is in.
+    pub fn from_findings<'a>(findings: impl IntoIterator<Item = (&'a str, &'a Finding)>) -> Self {
+        let mut counts = BTreeMap::new();
+        for (file, f) in findings {
+            *counts.entry(key(file, f)).or_insert(0) += 1;
+        }
+        Baseline { counts }
+    }
+
+    pub fn len(&self) -> usize {
+        self.counts.values().sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    /// The findings not covered by this baseline, in the order given.
+    pub fn new_findings<'a>(
+        &self,
+        findings: impl IntoIterator<Item = (&'a str, &'a Finding)>,
+    ) -> Vec<(&'a str, &'a Finding)> {
+        let mut left = self.counts.clone();
+        findings.into_iter()
+            .filter(|(file, f)| match left.get_mut(&key(file, f)) {
+                Some(n) if *n > 0 => { *n -= 1; false }
+                _ => true,
+            })
+            .collect()
+    }
+
+    pub fn to_json(&self) -> String {
+        let mut out = String::from("{\n  \"version\": 1,\n  \"findings\": [");
+        for (i, ((file, check, message), count)) in self.counts.iter().enumerate() {
+            out.push_str(if i == 0 { "\n" } else { ",\n" });
+            out.push_str(&format!(
+                "    {{\"file\": {}, \"check\": {}, \"message\": {}, \"count\": {}}}",
+                json_str(file), json_str(check), json_str(message), count
+            ));
+        }
+        out.push_str(if self.counts.is_empty() { "]\n}\n" } else { "\n  ]\n}\n" });
+        out
+    }
+
+    /// Read a baseline written by [`Baseline::to_json`].
+    ///
+    /// # Errors
+    /// Returns a [`JzeroError`] if the text is not a baseline.
+    pub fn parse(text: &str) -> Result<Baseline, JzeroError> {
+        let bad = |what: &str| JzeroError(format!("invalid baseline: {}", what));
+        let mut p = Json { s: text.as_bytes(), i: 0 };
+        let root = p.value().ok_or_else(|| bad("malformed JSON"))?;
+        let Some(JsonValue::Array(items)) = root.get("findings") else {
+            return Err(bad("missing \"findings\""));
+        };
+        let mut counts = BTreeMap::new();
+        for item in items {
+            let field = |k: &str| match item.get(k) {
+                Some(JsonValue::Str(s)) => Ok(s.clone()),
+                _ => Err(bad(&format!("finding without \"{}\"", k))),
+            };
+            let count = match item.get("count") {
+                Some(JsonValue::Num(n)) => *n as usize,
+                None => 1,
+                _ => return Err(bad("\"count\" is not a number")),
+            };
+            *counts.entry((field("file")?, field("check")?, field("message")?)).or_insert(0) += count;
+        }
+        Ok(Baseline { counts })
+    }
+}
+
+fn key(file: &str, f: &Finding) -> (String, String, String) {
+    (file.to_string(), f.check.to_string(), f.message.clone())
+}
+
+// ─── Minimal JSON reader ─────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, PartialEq)]
+enum JsonValue {
+    Null,
+    Bool(bool),
+    Num(f64),
+    Str(String),
+    Array(Vec<JsonValue>),
+    Object(Vec<(String, JsonValue)>),
+}
+
+impl JsonValue {
+    fn get(&self, key: &str) -> Option<&JsonValue> {
+        match self {
+            JsonValue::Object(fields) => fields.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+}
+
+struct Json<'a> {
+    s: &'a [u8],
+    i: usize,
+}
+
+impl Json<'_> {
+    fn ws(&mut self) {
+        while self.i < self.s.len() && self.s[self.i].is_ascii_whitespace() { self.i += 1; }
+    }
+
+    fn eat(&mut self, c: u8) -> bool {
+        self.ws();
+        if self.s.get(self.i) == Some(&c) { self.i += 1; true } else { false }
+    }
+
+    fn value(&mut self) -> Option<JsonValue> {
+        self.ws();
+        match *self.s.get(self.i)? {
+            b'{' => {
+                self.i += 1;
+                let mut fields = Vec::new();
+                if self.eat(b'}') { return Some(JsonValue::Object(fields)); }
+                loop {
+                    self.ws();
+                    let k = self.string()?;
+                    if !self.eat(b':') { return None; }
+                    fields.push((k, self.value()?));
+                    if self.eat(b'}') { return Some(JsonValue::Object(fields)); }
+                    if !self.eat(b',') { return None; }
+                }
+            }
+            b'[' => {
+                self.i += 1;
+                let mut items = Vec::new();
+                if self.eat(b']') { return Some(JsonValue::Array(items)); }
+                loop {
+                    items.push(self.value()?);
+                    if self.eat(b']') { return Some(JsonValue::Array(items)); }
+                    if !self.eat(b',') { return None; }
+                }
+            }
+            b'"' => self.string().map(JsonValue::Str),
+            _ => {
+                let start = self.i;
+                while self.i < self.s.len() && !b",]} \t\r\n".contains(&self.s[self.i]) { self.i += 1; }
+                match std::str::from_utf8(&self.s[start..self.i]).ok()? {
+                    "null"  => Some(JsonValue::Null),
+                    "true"  => Some(JsonValue::Bool(true)),
+                    "false" => Some(JsonValue::Bool(false)),
+                    n => n.parse().ok().map(JsonValue::Num),
+                }
+            }
+        }
+    }
+
+    fn string(&mut self) -> Option<String> {
+        if self.s.get(self.i) != Some(&b'"') { return None; }
+        self.i += 1;
+        let mut out = Vec::new();
+        loop {
+            let c = *self.s.get(self.i)?;
+            self.i += 1;
+            match c {
+                b'"' => return String::from_utf8(out).ok(),
+                b'\\' => {
+                    let e = *self.s.get(self.i)?;
+                    self.i += 1;
+                    match e {
+                        b'n' => out.push(b'\n'),
+                        b't' => out.push(b'\t'),
+                        b'r' => out.push(b'\r'),
+                        b'u' => {
+                            let hex = std::str::from_utf8(self.s.get(self.i..self.i + 4)?).ok()?;
+                            self.i += 4;
+                            let ch = char::from_u32(u32::from_str_radix(hex, 16).ok()?)?;
+                            out.extend_from_slice(ch.to_string().as_bytes());
+                        }
+                        other => out.push(other),
+                    }
+                }
+                c => out.push(c),
+            }
+        }
+    }
+}
+
+// ─── Tests ───────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Level;
+
+    fn finding(check: &'static str, lineno: usize, message: &str) -> Finding {
+        Finding { check, level: Level::Warn, lineno, message: message.to_string() }
+    }
+
+    #[test]
+    fn round_trips_through_json() {
+        let a = finding("class-name", 2, "class 'x' should be \"X\"");
+        let b = finding("file-name", 1, "tab\there");
+        let base = Baseline::from_findings([("A.java", &a), ("A.java", &a), ("B.java", &b)]);
+        let parsed = Baseline::parse(&base.to_json()).unwrap();
+        assert_eq!(parsed, base);
+        assert_eq!(parsed.len(), 3);
+        assert!(Baseline::parse(&Baseline::default().to_json()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn only_unrecorded_findings_are_new() {
+        let old = finding("variable-name", 3, "variable 'X' should be lowerCamelCase, e.g. 'x'");
+        let base = Baseline::from_findings([("T.java", &old)]);
+
+        // The old finding moved down a line and a second copy appeared.
+        let moved = Finding { lineno: 5, ..old.clone() };
+        let other = finding("method-name", 9, "method 'F' should be lowerCamelCase, e.g. 'f'");
+        let now = [("T.java", &moved), ("T.java", &old), ("T.java", &other)];
+        let new: Vec<usize> = base.new_findings(now).iter().map(|(_, f)| f.lineno).collect();
+        assert_eq!(new, vec![3, 9]);
+    }
+
+    #[test]
+    fn malformed_baseline_is_an_error() {
+        assert!(Baseline::parse("{").is_err());
+        assert!(Baseline::parse("{\"findings\": [{\"file\": \"a\"}]}").is_err());
+    }
+}
@@ -0,0 +1,342 @@
+//! Optional convention checks, configured under `[checks]` in `jzero.toml`.
+//!
+//! These are not language rules — the compiler accepts the program either
+//! way — but conventions the book's toolchain and graders rely on.  Each
+//! check has a default [`Level`] that the configuration can override.
+//!
+//! | key             | default | rule                                     |
+//! |-----------------|---------|------------------------------------------|
+//! | `file-name`     | warn    | the public class `Foo` is in `Foo.java`  |
+//! | `class-name`    | off     | class names are `UpperCamelCase`         |
+//! | `method-name`   | off     | method names are `lowerCamelCase`        |
+//! | `variable-name` | off     | fields, parameters and locals are `lowerCamelCase` |
+//! | `constant-name` | off     | `static final` fields are `UPPER_SNAKE_CASE` |
+//! | `method-length` | off     | at most `limits.method-length` (30) statements per method |
+//! | `nesting-depth` | off     | bodies nested at most `limits.nesting-depth` (3) deep |
+//!
+//! Checks can also be silenced in the source with a
+//! `// j0:allow(<check>, ...)` comment; see [`suppress`](crate::suppress).
+
+use std::fmt;
+use std::path::Path;
+
+use jzero_lexer::token::Token;
+use jzero_lexer::{Level as LanguageLevel, LexOptions};
+use jzero_symtab::entry::SymbolKind;
+
+use crate::config::{Config, Level};
+use crate::metrics::method_metrics;
+use crate::suppress;
+
+/// A convention violation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    /// The check that produced it, e.g. `file-name`.
+    pub check: &'static str,
+    pub level: Level,
+    pub lineno: usize,
+    pub message: String,
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: line {}: {} [{}]", self.level, self.lineno, self.message, self.check)
+    }
+}
+
+/// The names of all checks, as used in `jzero.toml` and `j0:allow`.
+pub const CHECKS: &[&str] = &[
+    "file-name",
+    "class-name",
+    "method-name",
+    "variable-name",
+    "constant-name",
+    "method-length",
+    "nesting-depth",
+];
+
+/// Run every enabled check on the file at `path`, minus those silenced by
+/// `j0:allow` comments.
+pub fn run_checks(path: &Path, source: &str, config: &Config) -> Vec<Finding> {
+    let mut findings: Vec<Finding> = check_file_name(path, source, config).into_iter().collect();
+    findings.extend(check_naming(source, config));
+    findings.extend(check_method_size(source, config));
+
+    let (sups, mut unknown) = suppress::suppressions(source, config);
+    let mut findings = suppress::apply(findings, &sups);
+    findings.append(&mut unknown);
+    findings
+}
+
+/// `file-name`: the public class must be declared in `<ClassName>.java`.
+pub fn check_file_name(path: &Path, source: &str, config: &Config) -> Option<Finding> {
+    let level = config.check_level("file-name", Level::Warn);
+    if level == Level::Off { return None; }
+
+    let (class, lineno) = public_class(source)?;
+    let file = path.file_name()?.to_string_lossy();
+    let expected = format!("{}.java", class);
+    if file == expected { return None; }
+    Some(Finding {
+        check: "file-name",
+        level,
+        lineno,
+        message: format!("public class '{}' should be declared in '{}', not '{}'", class, expected, file),
+    })
+}
+
+/// Name and line of the class in `public class Name`.
+fn public_class(source: &str) -> Option<(String, usize)> {
+    let tokens = jzero_lexer::lex(source).into_result().ok()?;
+    tokens.windows(3).find_map(|w| match (&w[0].token, &w[1].token, &w[2].token) {
+        (Token::Public, Token::Class, Token::Identifier) => Some((w[2].text.clone(), w[2].line)),
+        _ => None,
+    })
+}
+
+/// `class-name`, `method-name`, `variable-name`, `constant-name`:
+/// identifier case styles.
+///
+/// Works over the symbol table, so every declared name is seen exactly
+/// once, at its declaration.  The file is read at the extended level,
+/// where constants can be declared; files that do not parse are skipped.
+pub fn check_naming(source: &str, config: &Config) -> Vec<Finding> {
+    let levels = [
+        config.check_level("class-name", Level::Off),
+        config.check_level("method-name", Level::Off),
+        config.check_level("variable-name", Level::Off),
+        config.check_level("constant-name", Level::Off),
+    ];
+    if levels.iter().all(|l| *l == Level::Off) { return vec![]; }
+    let Ok(mut tree) = jzero_parser::parse_tree_with(source, LexOptions::at_level(LanguageLevel::MAX)) else {
+        return vec![];
+    };
+    let sem = jzero_semantic::analyze(&mut tree);
+
+    let mut findings = Vec::new();
+    sem.global.borrow().walk(&mut |_, entry| {
+        if entry.origin.is_predefined() { return; }
+        if entry.kind == SymbolKind::Field && entry.is_const && entry.is_static {
+            let expected = upper_snake_case(&entry.sym);
+            if levels[3] != Level::Off && entry.sym != expected {
+                findings.push(Finding {
+                    check: "constant-name",
+                    level: levels[3],
+                    lineno: entry.lineno,
+                    message: format!("constant '{}' should be UPPER_SNAKE_CASE, e.g. '{}'", entry.sym, expected),
+                });
+            }
+            return;
+        }
+        let (check, level, what, upper) = match entry.kind {
+            SymbolKind::Class  => ("class-name", levels[0], "class", true),
+            SymbolKind::Interface => ("class-name", levels[0], "interface", true),
+            SymbolKind::Method => ("method-name", levels[1], "method", false),
+            SymbolKind::Field | SymbolKind::Param | SymbolKind::Local =>
+                ("variable-name", levels[2], "variable", false),
+            SymbolKind::Package => return,
+        };
+        let expected = camel_case(&entry.sym, upper);
+        if level != Level::Off && entry.sym != expected {
+            let style = if upper { "UpperCamelCase" } else { "lowerCamelCase" };
+            findings.push(Finding {
+                check,
+                level,
+                lineno: entry.lineno,
+                message: format!("{} '{}' should be {}, e.g. '{}'", what, entry.sym, style, expected),
+            });
+        }
+    });
+    findings.sort_by_key(|f| f.lineno);
+    findings
+}
+
+/// `method-length`, `nesting-depth`: methods that are too long or too deep,
+/// by the [`metrics`](crate::metrics) of each method.
+pub fn check_method_size(source: &str, config: &Config) -> Vec<Finding> {
+    let length = config.check_level("method-length", Level::Off);
+    let depth = config.check_level("nesting-depth", Level::Off);
+    if length == Level::Off && depth == Level::Off { return vec![]; }
+    let Ok(tree) = jzero_parser::parse_tree(source) else { return vec![] };
+    let max_statements = limit(config, "method-length", 30);
+    let max_depth = limit(config, "nesting-depth", 3);
+
+    let mut findings = Vec::new();
+    for m in method_metrics(&tree) {
+        if length != Level::Off && m.statements > max_statements {
+            findings.push(Finding {
+                check: "method-length",
+                level: length,
+                lineno: m.lineno,
+                message: format!(
+                    "method '{}' has {} statements (limit {}); consider splitting it",
+                    m.name, m.statements, max_statements
+                ),
+            });
+        }
+        if depth != Level::Off && m.max_depth > max_depth {
+            findings.push(Finding {
+                check: "nesting-depth",
+                level: depth,
+                lineno: m.deepest_line,
+                message: format!(
+                    "code in '{}' is nested {} deep (limit {}); consider a helper method",
+                    m.name, m.max_depth, max_depth
+                ),
+            });
+        }
+    }
+    findings
+}
+
+/// The threshold `limits.<name>`, or `default` if unset or negative.
+fn limit(config: &Config, name: &str, default: usize) -> usize {
+    config.get_int(&format!("limits.{}", name))
+        .and_then(|n| usize::try_from(n).ok())
+        .unwrap_or(default)
+}
+
+/// `name` in camel case: `fizz_buzz` → `FizzBuzz` / `fizzBuzz`,
+/// `MAX_SIZE` → `maxSize`.
+fn camel_case(name: &str, upper_first: bool) -> String {
+    let mut out = String::new();
+    for word in name.split('_').filter(|w| !w.is_empty()) {
+        // An all-caps word is a word, not a run of initials.
+        let word = if word.chars().all(|c| !c.is_lowercase()) { word.to_lowercase() } else { word.to_string() };
+        let mut chars = word.chars();
+        if let Some(first) = chars.next() {
+            out.extend(first.to_uppercase());
+            out.push_str(chars.as_str());
+        }
+    }
+    if !upper_first && let Some(first) = out.chars().next() {
+        out.replace_range(..first.len_utf8(), &first.to_lowercase().to_string());
+    }
+    out
+}
+
+/// `name` in upper snake case: `maxSize` → `MAX_SIZE`, `max_size` →
+/// `MAX_SIZE`.
+fn upper_snake_case(name: &str) -> String {
+    let mut out = String::new();
+    let mut prev_lower = false;
+    for c in name.chars() {
+        if c.is_uppercase() && prev_lower {
+            out.push('_');
+        }
+        prev_lower = c.is_lowercase() || c.is_ascii_digit();
+        out.extend(c.to_uppercase());
+    }
+    out
+}
+
+// ─── Tests ───────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SRC: &str = "\npublic class Hello { }";
+
+    #[test]
+    fn matching_file_name_passes() {
+        let c = Config::default();
+        assert!(check_file_name(Path::new("src/Hello.java"), SRC, &c).is_none());
+    }
+
+    #[test]
+    fn mismatch_is_a_warning_by_default() {
+        let f = check_file_name(Path::new("src/hello.java"), SRC, &Config::default()).unwrap();
+        assert_eq!(f.level, Level::Warn);
+        assert_eq!(
+            f.to_string(),
+            "warning: line 2: public class 'Hello' should be declared in 'Hello.java', \
+             not 'hello.java' [file-name]"
+        );
+    }
+
+    #[test]
+    fn level_comes_from_config() {
+        let strict = Config::parse("[checks]\nfile-name = \"error\"").unwrap();
+        let off = Config::parse("[checks]\nfile-name = \"off\"").unwrap();
+        let p = Path::new("x.java");
+        assert_eq!(check_file_name(p, SRC, &strict).unwrap().level, Level::Error);
+        assert!(run_checks(p, SRC, &off).is_empty());
+    }
+
+    #[test]
+    fn naming_checks_are_off_by_default() {
+        let src = "public class hello { int Total; }";
+        assert!(check_naming(src, &Config::default()).is_empty());
+    }
+
+    #[test]
+    fn naming_checks_report_each_declaration() {
+        let src = r#"
+public class fizz_buzz {
+    int Total;
+    public static void Print_it(int max_count) {
+        int i;
+    }
+}
+"#;
+        let c = Config::parse(
+            "[checks]\nclass-name = \"warn\"\nmethod-name = \"error\"\nvariable-name = \"warn\"",
+        ).unwrap();
+        let found: Vec<String> = check_naming(src, &c).iter().map(|f| f.to_string()).collect();
+        assert_eq!(found, vec![
+            "warning: line 2: class 'fizz_buzz' should be UpperCamelCase, e.g. 'FizzBuzz' [class-name]",
+            "warning: line 3: variable 'Total' should be lowerCamelCase, e.g. 'total' [variable-name]",
+            "error: line 4: method 'Print_it' should be lowerCamelCase, e.g. 'printIt' [method-name]",
+            "warning: line 4: variable 'max_count' should be lowerCamelCase, e.g. 'maxCount' [variable-name]",
+        ]);
+    }
+
+    #[test]
+    fn constants_are_upper_snake_case() {
+        let src = r#"
+public class Limits {
+    public static final int MAX_SIZE = 10;
+    public static final int maxCount = 3;
+    private final int size = 4;
+}
+"#;
+        let c = Config::parse("[checks]\nconstant-name = \"warn\"\nvariable-name = \"warn\"").unwrap();
+        let found: Vec<String> = check_naming(src, &c).iter().map(|f| f.to_string()).collect();
+        assert_eq!(found, vec![
+            "warning: line 4: constant 'maxCount' should be UPPER_SNAKE_CASE, e.g. 'MAX_COUNT' [constant-name]",
+        ]);
+        assert_eq!(upper_snake_case("parseXML2"), "PARSE_XML2");
+    }
+
+    #[test]
+    fn camel_case_suggestions() {
+        assert_eq!(camel_case("MAX_SIZE", false), "maxSize");
+        assert_eq!(camel_case("hello", true), "Hello");
+        assert_eq!(camel_case("parseXML", false), "parseXML");
+    }
+
+    #[test]
+    fn method_size_limits_come_from_config() {
+        let src = r#"
+public class T {
+    public static void f(int a) {
+        if (a > 0) {
+            while (a > 1) {
+                a = a - 1;
+            }
+        }
+    }
+}
+"#;
+        assert!(check_method_size(src, &Config::default()).is_empty());
+        let c = Config::parse(
+            "[checks]\nmethod-length = \"warn\"\nnesting-depth = \"warn\"\n[limits]\nmethod-length = 2\nnesting-depth = 1\n",
+        ).unwrap();
+        let found: Vec<String> = check_method_size(src, &c).iter().map(|f| f.to_string()).collect();
+        assert_eq!(found, vec![
+            "warning: line 3: method 'f' has 3 statements (limit 2); consider splitting it [method-length]",
+            "warning: line 6: code in 'f' is nested 2 deep (limit 1); consider a helper method [nesting-depth]",
+        ]);
+    }
+}
@@ -0,0 +1,207 @@
+//! Differential comparison against stored reference outputs.
+//!
+//! A reference directory holds the expected output for each source file,
+//! named after the file's stem and the artifact's extension:
+//!
+//! ```text
+//! src/hello.java      ref/hello.ast     ref/hello.symtab
+//!                     ref/hello.tac     ref/hello.out
+//! ```
+//!
+//! [`compare`] runs the pipeline on every `.java` file below the source
+//! directory and checks each artifact that has a reference — every text
+//! [`EmitKind`], plus `.out` for what the program prints when run with no
+//! arguments.  A mismatch reports the first line where the two differ,
+//! which is usually where to start looking, whether the reference is a
+//! figure from the book or the output of an earlier build.
+
+use std::fmt;
+
+use crate::emit::EmitKind;
+use crate::vfs::FileProvider;
+use crate::{Compiler, JzeroError};
+
+/// Extension of the reference for the program's output.
+pub const OUTPUT_EXTENSION: &str = "out";
+
+/// The first line at which an artifact differs from its reference.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Divergence {
+    /// 1-based line number.
+    pub line: usize,
+    /// The reference line; `None` past its end.
+    pub expected: Option<String>,
+    /// The produced line; `None` past its end.
+    pub actual: Option<String>,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let show = |l: &Option<String>| l.as_deref().map_or("<end of output>".to_string(), |s| format!("{:?}", s));
+        writeln!(f, "first difference at line {}:", self.line)?;
+        writeln!(f, "  expected: {}", show(&self.expected))?;
+        write!(f, "  actual:   {}", show(&self.actual))
+    }
+}
+
+/// The first line where `actual` differs from `expected`, or `None` if
+/// they are the same apart from line endings.
+pub fn first_difference(expected: &str, actual: &str) -> Option<Divergence> {
+    let mut exp = expected.lines();
+    let mut act = actual.lines();
+    let mut line = 0;
+    loop {
+        line += 1;
+        match (exp.next(), act.next()) {
+            (None, None) => return None,
+            (e, a) if e == a => continue,
+            (e, a) => {
+                return Some(Divergence {
+                    line,
+                    expected: e.map(str::to_string),
+                    actual: a.map(str::to_string),
+                });
+            }
+        }
+    }
+}
+
+/// How one artifact compared with its reference.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Same,
+    Differs(Divergence),
+    /// The pipeline failed before producing the artifact.
+    Failed(String),
+}
+
+/// One reference checked.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Comparison {
+    pub source: String,
+    pub reference: String,
+    pub outcome: Outcome,
+}
+
+impl Comparison {
+    pub fn is_same(&self) -> bool {
+        self.outcome == Outcome::Same
+    }
+}
+
+impl fmt::Display for Comparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.outcome {
+            Outcome::Same => write!(f, "ok      {}", self.reference),
+            Outcome::Differs(d) => write!(f, "DIFFERS {} ({})\n{}", self.reference, self.source, d),
+            Outcome::Failed(e) => write!(f, "FAILED  {} ({}): {}", self.reference, self.source, e),
+        }
+    }
+}
+
+/// Compare the output for every `.java` file below `src_dir` with the
+/// references in `ref_dir`.  Trees are printed in the book's format if
+/// `book_compat` is set.  Sources without any reference are skipped.
+///
+/// # Errors
+/// Returns a [`JzeroError`] if a directory cannot be listed or a file
+/// cannot be read.
+pub fn compare(
+    files: &dyn FileProvider,
+    src_dir: &str,
+    ref_dir: &str,
+    book_compat: bool,
+) -> Result<Vec<Comparison>, JzeroError> {
+    let mut found = Vec::new();
+    for source in files.files(src_dir)?.into_iter().filter(|p| p.ends_with(".java")) {
+        let name = source.rsplit('/').next().unwrap_or(&source);
+        let stem = name.strip_suffix(".java").unwrap_or(name);
+        let reference = |ext: &str| format!("{}/{}.{}", ref_dir.trim_end_matches('/'), stem, ext);
+
+        let kinds: Vec<EmitKind> = EmitKind::ALL.iter().copied()
+            .filter(|k| *k != EmitKind::Bytecode && files.exists(&reference(k.extension())))
+            .collect();
+        let run = files.exists(&reference(OUTPUT_EXTENSION));
+        if kinds.is_empty() && !run { continue; }
+
+        let compiler = Compiler::new().source(&files.read(&source)?).book_compat(book_compat);
+        let mut produced: Vec<(String, Result<String, String>)> = Vec::new();
+        match compiler.emit(&kinds) {
+            Ok(artifacts) => {
+                for a in artifacts {
+                    let text = String::from_utf8_lossy(&a.contents).into_owned();
+                    produced.push((reference(a.kind.extension()), Ok(text)));
+                }
+            }
+            Err(e) => {
+                for k in &kinds {
+                    produced.push((reference(k.extension()), Err(e.to_string())));
+                }
+            }
+        }
+        if run {
+            let out = compiler.run(&[]).map(|o| o.stdout).map_err(|e| e.to_string());
+            produced.push((reference(OUTPUT_EXTENSION), out));
+        }
+
+        for (reference, actual) in produced {
+            let outcome = match actual {
+                Ok(actual) => match first_difference(&files.read(&reference)?, &actual) {
+                    None => Outcome::Same,
+                    Some(d) => Outcome::Differs(d),
+                },
+                Err(e) => Outcome::Failed(e),
+            };
+            found.push(Comparison { source: source.clone(), reference, outcome });
+        }
+    }
+    Ok(found)
+}
+
+// ─── Tests ───────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MemoryFs;
+
+    const HELLO: &str = r#"public class hello {
+    public static void main(String argv[]) {
+        System.out.println("hello, jzero!");
+    }
+}"#;
+
+    #[test]
+    fn first_difference_names_the_line() {
+        assert_eq!(first_difference("a\nb\n", "a\r\nb"), None);
+        let d = first_difference("a\nb\nc\n", "a\nx\nc\n").unwrap();
+        assert_eq!((d.line, d.expected.as_deref(), d.actual.as_deref()), (2, Some("b"), Some("x")));
+        let d = first_difference("a\n", "a\nmore\n").unwrap();
+        assert_eq!(d.to_string(), "first difference at line 2:\n  expected: <end of output>\n  actual:   \"more\"");
+    }
+
+    #[test]
+    fn only_referenced_artifacts_are_compared() {
+        let mut files = MemoryFs::new();
+        files.insert("src/hello.java", HELLO);
+        files.insert("src/other.java", HELLO);
+        files.insert("ref/hello.out", "hello, jzero!\n");
+        files.insert("ref/hello.symtab", "global - 4 symbols\n");
+        let found = compare(&files, "src", "ref", false).unwrap();
+
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].reference, "ref/hello.symtab");
+        assert!(matches!(&found[0].outcome, Outcome::Differs(d) if d.line == 2), "{}", found[0]);
+        assert_eq!(found[1].reference, "ref/hello.out");
+        assert!(found[1].is_same());
+    }
+
+    #[test]
+    fn pipeline_errors_are_reported_per_reference() {
+        let mut files = MemoryFs::new();
+        files.insert("bad.java", "public class {");
+        files.insert("ref/bad.ast", "");
+        let found = compare(&files, "", "ref", false).unwrap();
+        assert!(matches!(found[0].outcome, Outcome::Failed(_)));
+    }
+}
@@ -0,0 +1,200 @@
+//! Output in the format of the book's reference implementation.
+//!
+//! The workspace's own printers favour readability; these reproduce the
+//! book's Java `print()` methods so that output can be diffed against the
+//! figures in the text.  The differences they undo:
+//!
+//! | output        | workspace                     | book                              |
+//! |---------------|-------------------------------|-----------------------------------|
+//! | tree, leaf    | `[INTLIT] 42 (line 3)`        | `7   42 (272): 3`                 |
+//! | tree, node    | `Block#0 (2 kids)`            | `9   Block (1200): 2`             |
+//!
+//! A leaf shows its token category as the integer the book's byacc/j
+//! parser uses: the character code for a one-character token, 257 and up
+//! in `%token` order otherwise.  Tokens the book's grammar does not have
+//! show their category name instead.  Node ids are the ones
+//! [`Tree::number_nodes`] assigns.  A node shows the production code the
+//! book's `j0.node()` call passes for its rule, 1000 and up; a rule the
+//! book's grammar does not have shows its alternative number instead.
+//! The placeholders this workspace
+//! puts where a `for` part or a `return` value is left out are not shown.
+//!
+//! The symbol table dump (`SymTab::to_text`) already follows the book, but
+//! for the runtime library the book's prelude does not have, which
+//! [`symtab_text`] leaves out; the TAC listing does too, but for the array
+//! bounds checks (`BCHK`) the book does not generate, which [`tac_text`]
+//! drops.
+
+use jzero_ast::tree::Tree;
+use jzero_symtab::{SymTab, SymbolOrigin};
+
+/// The book's `%token` declarations, in order; the first is 257.
+const BOOK_TOKENS: &[&str] = &[
+    "BREAK", "PUBLIC", "DOUBLE", "ELSE", "FOR", "IF", "INT", "RETURN", "VOID", "WHILE",
+    "IDENTIFIER", "CLASSNAME", "CLASS", "STRING", "BOOL",
+    "INTLIT", "DOUBLELIT", "STRINGLIT", "BOOLLIT", "NULLVAL",
+    "LESSTHANOREQUAL", "GREATERTHANOREQUAL", "ISEQUALTO", "NOTEQUALTO",
+    "LOGICALAND", "LOGICALOR", "INCREMENT", "DECREMENT", "STATIC",
+];
+
+/// The book's integer category for the token category `category`.
+pub fn book_category(category: &str) -> Option<u32> {
+    let single = match category {
+        "LPAREN" => Some('('), "RPAREN" => Some(')'),
+        "LBRACKET" => Some('['), "RBRACKET" => Some(']'),
+        "LBRACE" => Some('{'), "RBRACE" => Some('}'),
+        "SEMICOLON" => Some(';'), "COLON" => Some(':'), "COMMA" => Some(','),
+        "DOT" => Some('.'), "ASSIGN" => Some('='), "BANG" => Some('!'),
+        "PLUS" => Some('+'), "MINUS" => Some('-'), "STAR" => Some('*'),
+        "SLASH" => Some('/'), "PERCENT" => Some('%'),
+        "LESS" => Some('<'), "GREATER" => Some('>'),
+        _ => None,
+    };
+    if let Some(c) = single { return Some(c as u32); }
+    let name = match category {
+        "NULL"         => "NULLVAL",
+        "LESSEQUAL"    => "LESSTHANOREQUAL",
+        "GREATEREQUAL" => "GREATERTHANOREQUAL",
+        "EQUALEQUAL"   => "ISEQUALTO",
+        "NOTEQUAL"     => "NOTEQUALTO",
+        other => other,
+    };
+    BOOK_TOKENS.iter().position(|&t| t == name).map(|i| 257 + i as u32)
+}
+
+/// The book's production code for alternative `rule` of `sym`.
+pub fn book_rule(sym: &str, rule: i32) -> Option<i32> {
+    let code = match (sym, rule) {
+        ("ClassDecl", _)        => 1000,
+        ("FieldDecl", _)        => 1030,
+        ("QualifiedName", _)    => 1040,
+        ("VarDecls", _)         => 1050,
+        ("VarDeclarator", 1)    => 1060,
+        ("MethodHeader", _)     => 1070,
+        ("MethodDeclarator", _) => 1080,
+        ("FormalParmList", _)   => 1090,
+        ("FormalParm", _)       => 1100,
+        ("ConstructorDecl", _)  => 1110,
+        ("BlockStmts", _)       => 1130,
+        ("LocalVarDecl", 0 | 1) => 1140,
+        ("IfThenStmt", _)       => 1150,
+        ("IfThenElseStmt", _)   => 1160,
+        ("Block", _)            => 1200,
+        ("WhileStmt", _)        => 1210,
+        ("ForStmt", _)          => 1220,
+        ("StmtExprList", _)     => 1230,
+        ("BreakStmt", 1)        => 1240,
+        ("ReturnStmt", _)       => 1250,
+        ("ArrayCreation", _)    => 1260,
+        ("ArgList", _)          => 1270,
+        ("FieldAccess", _)      => 1280,
+        ("MethodCall", 0..=3)   => 1290 + rule,
+        ("UnaryMinus", _)       => 1300,
+        ("UnaryNot", _)         => 1301,
+        ("MulExpr", 0..=2)      => 1310 + rule,
+        ("AddExpr", 0..=1)      => 1320 + rule,
+        ("RelExpr", _)          => 1330,
+        ("EqExpr", 0..=1)       => 1340 + rule,
+        ("CondAndExpr", _)      => 1350,
+        ("CondOrExpr", _)       => 1360,
+        ("Assignment", _)       => 1370,
+        ("MethodDecl", _)       => 1380,
+        ("ArrayAccess", _)      => 1390,
+        _ => return None,
+    };
+    Some(code)
+}
+
+/// `tree` as printed by the book's `tree.print()`.
+pub fn tree_text(tree: &Tree) -> String {
+    let mut out = String::new();
+    tree_into(tree, 0, &mut out);
+    out
+}
+
+fn tree_into(tree: &Tree, level: usize, out: &mut String) {
+    out.push_str(&"  ".repeat(level));
+    match &tree.tok {
+        Some(tok) => {
+            let cat = book_category(&tok.category).map_or(tok.category.clone(), |c| c.to_string());
+            out.push_str(&format!("{}   {} ({}): {}\n", tree.id, tok.text, cat, tok.lineno));
+        }
+        None => {
+            let nkids = tree.kids.iter().filter(|k| !k.is_empty_placeholder()).count();
+            let rule = book_rule(&tree.sym, tree.rule).unwrap_or(tree.rule);
+            out.push_str(&format!("{}   {} ({}): {}\n", tree.id, tree.sym, rule, nkids));
+        }
+    }
+    for kid in tree.kids.iter().filter(|k| !k.is_empty_placeholder()) {
+        tree_into(kid, level + 1, out);
+    }
+}
+
+/// The symbol table dump of `global` without the library methods, such as
+/// `Math.randomInt`, that the book's prelude does not declare.
+pub fn symtab_text(global: &SymTab) -> String {
+    global.text_without(0, SymbolOrigin::Library)
+}
+
+/// The TAC listing `ir` without the bounds checks the book's code
+/// generator does not emit.
+pub fn tac_text(ir: &str) -> String {
+    ir.lines().filter(|l| !l.starts_with("BCHK ")).map(|l| format!("{}\n", l)).collect()
+}
+
+// ─── Tests ───────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn categories_follow_byacc_numbering() {
+        assert_eq!(book_category("BREAK"), Some(257));
+        assert_eq!(book_category("IDENTIFIER"), Some(267));
+        assert_eq!(book_category("INTLIT"), Some(272));
+        assert_eq!(book_category("EQUALEQUAL"), Some(279));
+        assert_eq!(book_category("SEMICOLON"), Some(59));
+        assert_eq!(book_category("PLUSASSIGN"), None);
+    }
+
+    #[test]
+    fn tree_prints_like_the_book() {
+        let mut tree = jzero_parser::parse_tree(
+            "public class t { public static void main(String argv[]) { x = 42; } }",
+        ).unwrap();
+        tree.number_nodes();
+        let text = tree_text(&tree);
+        let lines: Vec<&str> = text.lines().collect();
+        assert!(lines[0].ends_with("   ClassDecl (1000): 2"), "{}", text);
+        assert!(lines[1].starts_with("  ") && lines[1].ends_with("   t (267): 1"), "{}", text);
+        assert!(text.contains("   42 (272): 1\n"), "{}", text);
+        assert!(text.contains("   Assignment (1370): 3\n"), "{}", text);
+    }
+
+    #[test]
+    fn rules_are_the_books_production_codes() {
+        assert_eq!(book_rule("MethodDecl", 0), Some(1380));
+        assert_eq!(book_rule("MethodCall", 2), Some(1292));
+        assert_eq!(book_rule("EqExpr", 1), Some(1341));
+        assert_eq!(book_rule("BreakStmt", 0), None);
+        assert_eq!(book_rule("SwitchStmt", 0), None);
+    }
+
+    #[test]
+    fn tac_drops_bounds_checks() {
+        let ir = ".code\nproc main,0,0\nBCHK loc:8,imm:12\nLOAD loc:16,loc:8,imm:12\nRET\nend\n";
+        assert_eq!(tac_text(ir), ".code\nproc main,0,0\nLOAD loc:16,loc:8,imm:12\nRET\nend\n");
+    }
+
+    #[test]
+    fn placeholders_are_not_printed() {
+        let mut tree = jzero_parser::parse_tree(
+            "public class t { public static void main(String argv[]) { return; } }",
+        ).unwrap();
+        tree.number_nodes();
+        let text = tree_text(&tree);
+        assert!(text.contains("   ReturnStmt (1250): 0\n"), "{}", text);
+        assert!(!text.contains("EmptyExpr"), "{}", text);
+    }
+}
@@ -0,0 +1,229 @@
+//! Project configuration (`jzero.toml`).
+//!
+//! A project may put a `jzero.toml` next to its sources (or in any parent
+//! directory).  Only the small subset of TOML the settings need is
+//! understood: `[section]` headers, `key = value` pairs whose value is a
+//! string, boolean, integer or a one-line array of those, and `#` comments.
+//!
+//! ```toml
+//! [checks]
+//! # the public class must live in <ClassName>.java
+//! file-name = "error"   # "off", "warn" (default) or "error"
+//!
+//! [limits]
+//! method-length = 40     # thresholds used by the size checks
+//! ```
+//!
+//! Keys are stored with their section, e.g. `checks.file-name`.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::JzeroError;
+
+/// The file name looked for by [`Config::find`].
+pub const CONFIG_FILE: &str = "jzero.toml";
+
+/// A configuration value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Str(String),
+    Bool(bool),
+    Int(i64),
+    List(Vec<Value>),
+}
+
+/// How an optional check reports a violation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Level {
+    Off,
+    Warn,
+    Error,
+}
+
+impl Level {
+    fn parse(s: &str) -> Option<Level> {
+        match s {
+            "off" | "allow"  => Some(Level::Off),
+            "warn" | "warning" => Some(Level::Warn),
+            "error" | "deny" => Some(Level::Error),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Level::Off   => write!(f, "off"),
+            Level::Warn  => write!(f, "warning"),
+            Level::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// Parsed `jzero.toml` settings.
+#[derive(Debug, Clone, Default)]
+pub struct Config {
+    values: BTreeMap<String, Value>,
+    /// The file the settings were read from, if any.
+    pub path: Option<PathBuf>,
+}
+
+impl Config {
+    /// Parse configuration text.
+    ///
+    /// # Errors
+    /// Returns a [`JzeroError`] naming the first malformed line.
+    pub fn parse(text: &str) -> Result<Config, JzeroError> {
+        let mut values = BTreeMap::new();
+        let mut section = String::new();
+        for (i, raw) in text.lines().enumerate() {
+            let line = strip_comment(raw).trim();
+            if line.is_empty() { continue; }
+            let bad = || JzeroError(format!("{} line {}: cannot parse '{}'", CONFIG_FILE, i + 1, raw.trim()));
+
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                section = name.trim().to_string();
+                continue;
+            }
+            let (key, value) = line.split_once('=').ok_or_else(bad)?;
+            let key = key.trim().trim_matches('"');
+            if key.is_empty() { return Err(bad()); }
+            let value = parse_value(value.trim()).ok_or_else(bad)?;
+            let full = if section.is_empty() { key.to_string() } else { format!("{}.{}", section, key) };
+            values.insert(full, value);
+        }
+        Ok(Config { values, path: None })
+    }
+
+    /// Load the nearest `jzero.toml` at or above `dir`.  Returns the default
+    /// configuration if there is none.
+    ///
+    /// # Errors
+    /// Returns a [`JzeroError`] if a file is found but cannot be read or
+    /// parsed.
+    pub fn find(dir: &Path) -> Result<Config, JzeroError> {
+        for d in dir.ancestors() {
+            let path = d.join(CONFIG_FILE);
+            if path.is_file() {
+                let text = std::fs::read_to_string(&path)
+                    .map_err(|e| JzeroError(format!("error reading '{}': {}", path.display(), e)))?;
+                let mut config = Config::parse(&text)
+                    .map_err(|e| JzeroError(format!("{}: {}", path.display(), e)))?;
+                config.path = Some(path);
+                return Ok(config);
+            }
+        }
+        Ok(Config::default())
+    }
+
+    /// The raw value at `key` (`section.key`).
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.values.get(key)
+    }
+
+    pub fn get_str(&self, key: &str) -> Option<&str> {
+        match self.get(key)? {
+            Value::Str(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn get_int(&self, key: &str) -> Option<i64> {
+        match self.get(key)? {
+            Value::Int(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    pub fn get_bool(&self, key: &str) -> Option<bool> {
+        match self.get(key)? {
+            Value::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    /// The level of the optional check `checks.<name>`, or `default`.
+    ///
+    /// `true`/`false` are accepted as `error`/`off`.
+    pub fn check_level(&self, name: &str, default: Level) -> Level {
+        match self.get(&format!("checks.{}", name)) {
+            Some(Value::Str(s))  => Level::parse(s).unwrap_or(default),
+            Some(Value::Bool(b)) => if *b { Level::Error } else { Level::Off },
+            _ => default,
+        }
+    }
+}
+
+/// Drop a `#` comment, ignoring `#` inside strings.
+fn strip_comment(line: &str) -> &str {
+    let mut in_str = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => in_str = !in_str,
+            '#' if !in_str => return &line[..i],
+            _ => {}
+        }
+    }
+    line
+}
+
+fn parse_value(s: &str) -> Option<Value> {
+    if let Some(inner) = s.strip_prefix('[').and_then(|s| s.strip_suffix(']')) {
+        return inner.split(',')
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+            .map(parse_value)
+            .collect::<Option<Vec<_>>>()
+            .map(Value::List);
+    }
+    if let Some(inner) = s.strip_prefix('"').and_then(|s| s.strip_suffix('"')) {
+        return Some(Value::Str(inner.replace("\\\"", "\"").replace("\\\\", "\\")));
+    }
+    match s {
+        "true"  => Some(Value::Bool(true)),
+        "false" => Some(Value::Bool(false)),
+        _ => s.parse().ok().map(Value::Int),
+    }
+}
+
+// ─── Tests ───────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sections_and_values() {
+        let c = Config::parse(r#"
+top = 1
+[checks]
+file-name = "error"  # strict
+"quoted" = true
+[lints]
+allow = ["a", "b#c"]
+"#).unwrap();
+        assert_eq!(c.get("top"), Some(&Value::Int(1)));
+        assert_eq!(c.get_str("checks.file-name"), Some("error"));
+        assert_eq!(c.get_bool("checks.quoted"), Some(true));
+        assert_eq!(c.get("lints.allow"), Some(&Value::List(vec![
+            Value::Str("a".into()), Value::Str("b#c".into()),
+        ])));
+    }
+
+    #[test]
+    fn check_levels() {
+        let c = Config::parse("[checks]\nfile-name = \"off\"\nother = true\n").unwrap();
+        assert_eq!(c.check_level("file-name", Level::Warn), Level::Off);
+        assert_eq!(c.check_level("other", Level::Warn), Level::Error);
+        assert_eq!(c.check_level("missing", Level::Warn), Level::Warn);
+    }
+
+    #[test]
+    fn malformed_line_is_reported() {
+        let err = Config::parse("[checks]\nfile-name\n").unwrap_err();
+        assert!(err.0.contains("line 2"), "{}", err);
+    }
+}
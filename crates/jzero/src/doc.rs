@@ -0,0 +1,200 @@
+//! Doc comments — `/** ... */` attached to what they document.
+//!
+//! A doc comment documents the class, method, constructor or field
+//! declaration that starts on the next line of code after it, as in
+//! Javadoc; other comments may come in between.  A doc comment followed by
+//! anything else — a statement, or another doc comment — documents nothing
+//! and is left out.
+//!
+//! ```
+//! let src = "/** Says hello. */\npublic class hello {\n    /** How many. */\n    int count;\n}\n";
+//! let tree = jzero_parser::parse_tree(src).unwrap();
+//! let docs = jzero::doc::doc_comments(src, &tree);
+//! assert_eq!((docs[0].name.as_str(), docs[0].text().as_str()), ("hello", "Says hello."));
+//! assert_eq!((docs[1].name.as_str(), docs[1].text().as_str()), ("count", "How many."));
+//! ```
+
+use std::fmt;
+
+use jzero_ast::tree::Tree;
+use jzero_lexer::TriviaKind;
+
+/// What a doc comment documents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeclKind {
+    Class,
+    Method,
+    Constructor,
+    Field,
+}
+
+impl fmt::Display for DeclKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeclKind::Class       => write!(f, "class"),
+            DeclKind::Method      => write!(f, "method"),
+            DeclKind::Constructor => write!(f, "constructor"),
+            DeclKind::Field       => write!(f, "field"),
+        }
+    }
+}
+
+/// A doc comment and the declaration it documents.  A field declaration
+/// of several variables gives one `DocItem` per variable.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocItem {
+    pub kind: DeclKind,
+    pub name: String,
+    /// Line of the declared name.
+    pub lineno: usize,
+    /// Id of the declaration's node in the tree.
+    pub node: u32,
+    /// The comment as written, `/**` and `*/` included.
+    pub comment: String,
+}
+
+impl DocItem {
+    /// The comment's text without `/**`, `*/` and the `*` that starts
+    /// each line, trimmed, with lines joined by `\n`.
+    pub fn text(&self) -> String {
+        let body = self.comment.strip_prefix("/**").unwrap_or(&self.comment);
+        let body = body.strip_suffix("*/").unwrap_or(body);
+        let lines: Vec<&str> = body.lines()
+            .map(|l| { let l = l.trim(); l.strip_prefix('*').map_or(l, str::trim_start).trim_end() })
+            .collect();
+        lines.join("\n").trim().to_string()
+    }
+}
+
+/// The doc comments of `source`, each with the declaration of `tree` it
+/// documents, in source order.  `tree` is the parse of `source`.
+pub fn doc_comments(source: &str, tree: &Tree) -> Vec<DocItem> {
+    let Ok(file) = jzero_lexer::lex_lossless(source) else { return vec![] };
+    let mut decls = Vec::new();
+    declarations(tree, &mut decls);
+
+    let mut found = Vec::new();
+    let mut line = 1;
+    for tok in &file.tokens {
+        // Only the last doc comment before a token can document it.
+        let mut doc = None;
+        for t in &tok.leading {
+            if t.kind == TriviaKind::DocComment { doc = Some(t.text.as_str()); }
+            line += t.newlines();
+        }
+        if let Some(comment) = doc {
+            for d in decls.iter().filter(|d| d.start == line) {
+                for (name, lineno) in &d.names {
+                    found.push(DocItem {
+                        kind: d.kind,
+                        name: name.clone(),
+                        lineno: *lineno,
+                        node: d.node,
+                        comment: comment.to_string(),
+                    });
+                }
+            }
+        }
+        line += tok.text.matches('\n').count();
+    }
+    found
+}
+
+/// A declaration of the tree: where it starts and the names it declares.
+struct Decl {
+    kind: DeclKind,
+    start: usize,
+    node: u32,
+    names: Vec<(String, usize)>,
+}
+
+fn declarations(tree: &Tree, out: &mut Vec<Decl>) {
+    let kind = match tree.sym.as_str() {
+        "ClassDecl"       => Some(DeclKind::Class),
+        "MethodDecl"      => Some(DeclKind::Method),
+        "ConstructorDecl" => Some(DeclKind::Constructor),
+        "FieldDecl"       => Some(DeclKind::Field),
+        _ => None,
+    };
+    if let Some(kind) = kind {
+        let names = match kind {
+            DeclKind::Class => tree.kids.first().into_iter().filter_map(leaf).collect(),
+            // MethodDecl → MethodHeader → MethodDeclarator → name.
+            DeclKind::Method => tree.kids.first().and_then(|h| h.kids.get(1))
+                .and_then(|d| d.kids.first()).and_then(leaf).into_iter().collect(),
+            DeclKind::Constructor => tree.kids.first().and_then(|d| d.kids.first())
+                .and_then(leaf).into_iter().collect(),
+            DeclKind::Field => tree.kids.iter().skip(1).filter_map(declarator_name).collect(),
+        };
+        let start = tree.first_lineno();
+        out.push(Decl { kind, start, node: tree.id, names });
+    }
+    for kid in &tree.kids {
+        declarations(kid, out);
+    }
+}
+
+fn leaf(tree: &Tree) -> Option<(String, usize)> {
+    tree.tok.as_ref().map(|t| (t.text.clone(), t.lineno))
+}
+
+/// The name in a `VarDeclarator`, through any `[]`s.
+fn declarator_name(tree: &Tree) -> Option<(String, usize)> {
+    match &tree.tok {
+        Some(_) => leaf(tree),
+        None => declarator_name(tree.kids.first()?),
+    }
+}
+
+// ─── Tests ───────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SRC: &str = r#"/**
+ * A counter.
+ *
+ * Counts things.
+ */
+public class counter {
+    /** Current values. */
+    int a, b[];
+    /* not a doc comment */
+    int plain;
+
+    /** Starts at zero. */
+    counter() { }
+
+    /** Entry point. */
+    // j0:allow(method-name)
+    public static void main(String argv[]) {
+        /** Inside a body: documents nothing. */
+        int x;
+    }
+}
+"#;
+
+    #[test]
+    fn comments_attach_to_the_next_declaration() {
+        let tree = jzero_parser::parse_tree(SRC).unwrap();
+        let docs = doc_comments(SRC, &tree);
+        let summary: Vec<(DeclKind, &str, usize)> =
+            docs.iter().map(|d| (d.kind, d.name.as_str(), d.lineno)).collect();
+        assert_eq!(summary, [
+            (DeclKind::Class, "counter", 6),
+            (DeclKind::Field, "a", 8),
+            (DeclKind::Field, "b", 8),
+            (DeclKind::Constructor, "counter", 13),
+            (DeclKind::Method, "main", 17),
+        ]);
+        assert_eq!(docs[0].text(), "A counter.\n\nCounts things.");
+        assert_eq!(docs[1].node, docs[2].node);
+    }
+
+    #[test]
+    fn doc_comments_are_hidden_tokens() {
+        let tokens = jzero_lexer::lex("/** doc */ int /**/ x;").tokens;
+        assert_eq!(tokens.len(), 3);
+    }
+}
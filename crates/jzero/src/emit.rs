@@ -0,0 +1,391 @@
+//! Selected intermediate artifacts, as requested with `j0 --emit=...`.
+//!
+//! Each [`EmitKind`] names one artifact and its file extension.  Only the
+//! phases an artifact needs are run: tokens need only the lexer, the tree
+//! forms need the parser, and the rest need full semantic analysis.
+//!
+//! | kind       | file            | contents                          |
+//! |------------|-----------------|-----------------------------------|
+//! | `tokens`   | `<stem>.tokens` | one token per line                |
+//! | `ast`      | `<stem>.ast`    | the syntax tree as indented text  |
+//! | `ast-json` | `<stem>.ast.json` | the syntax tree as JSON         |
+//! | `dot`      | `<stem>.dot`    | the syntax tree for Graphviz      |
+//! | `symtab`   | `<stem>.symtab` | the scopes after analysis         |
+//! | `ir`       | `<stem>.tac`    | three-address code                |
+//! | `asm`      | `<stem>.asm`    | the bytecode assembler listing    |
+//! | `bytecode` | `<stem>.j0`     | the binary image for the VM       |
+//!
+//! [`Compiler::compile_to`] compiles for one backend [`Target`] and
+//! returns its artifact in memory, with [`Metadata`] about the program —
+//! its entry point, classes and methods — so that embedders and tests can
+//! inspect the output without writing files:
+//!
+//! ```
+//! use jzero::Compiler;
+//! use jzero::emit::Target;
+//!
+//! let src = "public class hello {
+//!     public static void main(String argv[]) { System.out.println(\"hi\"); }
+//! }";
+//! let artifact = Compiler::new().source(src).compile_to(Target::Bytecode).unwrap();
+//! assert_eq!(&artifact.contents[0..8], b"Jzero!!\0");
+//! assert_eq!(artifact.metadata.entry_class.as_deref(), Some("hello"));
+//! assert_eq!(artifact.metadata.symbols, ["hello", "hello.main"]);
+//! ```
+
+use std::fmt;
+use std::path::Path;
+
+use jzero_lexer::LexOptions;
+use jzero_symtab::entry::{SymbolKind, SymbolOrigin};
+
+use crate::{compat, Compiler, JzeroError, SemanticResult};
+
+/// One kind of artifact.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum EmitKind {
+    Tokens,
+    Ast,
+    AstJson,
+    Dot,
+    Symtab,
+    Ir,
+    Asm,
+    Bytecode,
+}
+
+impl EmitKind {
+    pub const ALL: &'static [EmitKind] = &[
+        EmitKind::Tokens,
+        EmitKind::Ast,
+        EmitKind::AstJson,
+        EmitKind::Dot,
+        EmitKind::Symtab,
+        EmitKind::Ir,
+        EmitKind::Asm,
+        EmitKind::Bytecode,
+    ];
+
+    /// The name used on the command line.
+    pub fn name(&self) -> &'static str {
+        match self {
+            EmitKind::Tokens   => "tokens",
+            EmitKind::Ast      => "ast",
+            EmitKind::AstJson  => "ast-json",
+            EmitKind::Dot      => "dot",
+            EmitKind::Symtab   => "symtab",
+            EmitKind::Ir       => "ir",
+            EmitKind::Asm      => "asm",
+            EmitKind::Bytecode => "bytecode",
+        }
+    }
+
+    /// The file extension, without the leading dot.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            EmitKind::Tokens   => "tokens",
+            EmitKind::Ast      => "ast",
+            EmitKind::AstJson  => "ast.json",
+            EmitKind::Dot      => "dot",
+            EmitKind::Symtab   => "symtab",
+            EmitKind::Ir       => "tac",
+            EmitKind::Asm      => "asm",
+            EmitKind::Bytecode => "j0",
+        }
+    }
+
+    /// Whether producing this artifact needs semantic analysis.
+    fn needs_analysis(&self) -> bool {
+        matches!(self, EmitKind::Symtab | EmitKind::Ir | EmitKind::Asm | EmitKind::Bytecode)
+    }
+
+    /// Parse a comma-separated list such as `tokens,ast-json,ir`.
+    /// Duplicates are dropped; the result is in [`EmitKind::ALL`] order.
+    ///
+    /// # Errors
+    /// Returns a [`JzeroError`] naming the first unknown kind.
+    pub fn parse_list(list: &str) -> Result<Vec<EmitKind>, JzeroError> {
+        let mut kinds = Vec::new();
+        for name in list.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+            let kind = EmitKind::ALL.iter().find(|k| k.name() == name).ok_or_else(|| {
+                let known: Vec<&str> = EmitKind::ALL.iter().map(|k| k.name()).collect();
+                JzeroError(format!("unknown --emit kind '{}' (expected one of: {})", name, known.join(", ")))
+            })?;
+            kinds.push(*kind);
+        }
+        kinds.sort();
+        kinds.dedup();
+        Ok(kinds)
+    }
+}
+
+impl fmt::Display for EmitKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// A backend [`Compiler::compile_to`] compiles for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Target {
+    /// The binary image for the VM.
+    Bytecode,
+    /// The bytecode assembler listing.
+    Asm,
+    /// Three-address code.
+    Ir,
+}
+
+impl Target {
+    /// The artifact kind the target produces.
+    pub fn kind(&self) -> EmitKind {
+        match self {
+            Target::Bytecode => EmitKind::Bytecode,
+            Target::Asm      => EmitKind::Asm,
+            Target::Ir       => EmitKind::Ir,
+        }
+    }
+}
+
+/// What a compiled artifact holds, beyond its bytes.  Empty for the
+/// token and tree artifacts, which are produced before analysis.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Metadata {
+    /// The class that declares `main`.
+    pub entry_class: Option<String>,
+    /// The byte offset of `main` in a bytecode image.
+    pub entry_offset: Option<usize>,
+    /// The classes and methods of the source, as `Class` and
+    /// `Class.method`, in declaration order.
+    pub symbols: Vec<String>,
+    /// The native methods the code calls, which must be registered to run
+    /// it (see [`CompileSession::register_native`](crate::CompileSession::register_native)).
+    pub natives: Vec<String>,
+}
+
+impl Metadata {
+    /// The entry class and symbols of the analysed source.
+    fn of(sem: &SemanticResult) -> Self {
+        let mut metadata = Metadata::default();
+        for (name, entry) in sem.global.borrow().iter() {
+            if entry.kind != SymbolKind::Class || !matches!(entry.origin, SymbolOrigin::UserFile(_)) { continue; }
+            metadata.symbols.push(name.clone());
+            let Some(scope) = &entry.st else { continue };
+            for method in scope.borrow().entries_of_kind(SymbolKind::Method) {
+                if method.sym == "main" && metadata.entry_class.is_none() {
+                    metadata.entry_class = Some(name.clone());
+                }
+                metadata.symbols.push(format!("{}.{}", name, method.sym));
+            }
+        }
+        metadata
+    }
+}
+
+/// One produced artifact.
+#[derive(Debug, Clone)]
+pub struct Artifact {
+    pub kind: EmitKind,
+    pub contents: Vec<u8>,
+    pub metadata: Metadata,
+}
+
+impl Artifact {
+    fn text(kind: EmitKind, s: String) -> Self {
+        Artifact { kind, contents: s.into_bytes(), metadata: Metadata::default() }
+    }
+
+    fn with_metadata(mut self, metadata: &Metadata) -> Self {
+        self.metadata = metadata.clone();
+        self
+    }
+
+    /// The file name for a source whose stem is `stem`, e.g. `hello.tac`.
+    pub fn file_name(&self, stem: &str) -> String {
+        format!("{}.{}", stem, self.kind.extension())
+    }
+
+    /// Write the contents to `path`.
+    ///
+    /// # Errors
+    /// Returns a [`JzeroError`] naming `path` if it cannot be written.
+    pub fn write_to(&self, path: impl AsRef<Path>) -> Result<(), JzeroError> {
+        let path = path.as_ref();
+        std::fs::write(path, &self.contents)
+            .map_err(|e| JzeroError(format!("error writing '{}': {}", path.display(), e)))
+    }
+}
+
+impl Compiler {
+    /// Compile for `target` and return its artifact, with the metadata of
+    /// the program.
+    ///
+    /// # Errors
+    /// Returns a [`JzeroError`] if parsing, semantic analysis or code generation fails.
+    pub fn compile_to(&self, target: Target) -> Result<Artifact, JzeroError> {
+        let mut artifacts = self.emit(&[target.kind()])?;
+        Ok(artifacts.remove(0))
+    }
+
+    /// Produce the artifacts in `kinds`, in the order given.
+    ///
+    /// # Errors
+    /// Returns a [`JzeroError`] if a phase that a requested artifact needs
+    /// fails.
+    pub fn emit(&self, kinds: &[EmitKind]) -> Result<Vec<Artifact>, JzeroError> {
+        let mut out = Vec::new();
+        let options = LexOptions::at_level(self.level);
+        if kinds.contains(&EmitKind::Tokens) {
+            out.push(Artifact::text(EmitKind::Tokens, token_listing(&self.source, &options)?));
+        }
+        let tree_kinds = [EmitKind::Ast, EmitKind::AstJson, EmitKind::Dot];
+        if !kinds.iter().any(|k| tree_kinds.contains(k) || k.needs_analysis()) {
+            return Ok(order(out, kinds));
+        }
+
+        let (tree, sem) = if kinds.iter().any(EmitKind::needs_analysis) {
+            let (tree, sem) = self.analyse()?;
+            (tree, Some(sem))
+        } else {
+            (jzero_parser::parse_tree_with(&self.source, options).map_err(JzeroError)?, None)
+        };
+        for &kind in kinds {
+            match kind {
+                EmitKind::Ast     => {
+                    let text = if self.book_compat { compat::tree_text(&tree) } else { tree.to_string() };
+                    out.push(Artifact::text(kind, text));
+                }
+                EmitKind::AstJson => out.push(Artifact::text(kind, tree.to_json())),
+                EmitKind::Dot     => out.push(Artifact::text(kind, tree.to_dot())),
+                _ => {}
+            }
+        }
+
+        if let Some(sem) = sem {
+            if kinds.contains(&EmitKind::Symtab) {
+                let global = sem.global.borrow();
+                let text = if self.book_compat { compat::symtab_text(&global) } else { global.to_text(0) };
+                out.push(Artifact::text(EmitKind::Symtab, text));
+            }
+            let ctx = self.generate(&tree, &sem)?;
+            let mut metadata = Metadata { natives: ctx.natives.clone(), ..Metadata::of(&sem) };
+            if kinds.contains(&EmitKind::Ir) {
+                let ir = jzero_codegen::emit::emit(&tree, &ctx);
+                let ir = if self.book_compat { compat::tac_text(&ir) } else { ir };
+                out.push(Artifact::text(EmitKind::Ir, ir).with_metadata(&metadata));
+            }
+            if kinds.contains(&EmitKind::Asm) || kinds.contains(&EmitKind::Bytecode) {
+                let output = jzero_codegen::pipeline::compile_bytecode(&tree, &ctx, 0);
+                if kinds.contains(&EmitKind::Asm) {
+                    out.push(Artifact::text(EmitKind::Asm, output.text).with_metadata(&metadata));
+                }
+                if kinds.contains(&EmitKind::Bytecode) {
+                    metadata.entry_offset = Some(output.main_offset);
+                    out.push(Artifact { kind: EmitKind::Bytecode, contents: output.binary, metadata });
+                }
+            }
+        }
+        Ok(order(out, kinds))
+    }
+}
+
+/// Sort `artifacts` into the order of `kinds`.
+fn order(mut artifacts: Vec<Artifact>, kinds: &[EmitKind]) -> Vec<Artifact> {
+    artifacts.sort_by_key(|a| kinds.iter().position(|k| *k == a.kind));
+    artifacts
+}
+
+/// `line  CATEGORY  text`, one token per line.
+fn token_listing(source: &str, options: &LexOptions) -> Result<String, JzeroError> {
+    let tokens = jzero_lexer::lex_with(source, options).into_result().map_err(|errs| {
+        JzeroError(errs.iter().map(|e| e.to_string()).collect::<Vec<_>>().join("\n"))
+    })?;
+    Ok(tokens.iter()
+        .map(|t| format!("{:>4}  {:<14} {}\n", t.line, t.token.category_name(), t.text))
+        .collect())
+}
+
+// ─── Tests ───────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jzero_lexer::Level;
+
+    const HELLO: &str = r#"public class hello {
+    public static void main(String argv[]) {
+        System.out.println("hi");
+    }
+}"#;
+
+    #[test]
+    fn emit_list_parsing() {
+        assert_eq!(
+            EmitKind::parse_list("ir,tokens,ir").unwrap(),
+            vec![EmitKind::Tokens, EmitKind::Ir]
+        );
+        let err = EmitKind::parse_list("tokens,llvm").unwrap_err();
+        assert!(err.0.starts_with("unknown --emit kind 'llvm'"), "{}", err);
+    }
+
+    #[test]
+    fn emits_only_what_is_asked() {
+        let kinds = [EmitKind::Tokens, EmitKind::AstJson, EmitKind::Ir, EmitKind::Bytecode];
+        let out = Compiler::new().source(HELLO).emit(&kinds).unwrap();
+        let got: Vec<EmitKind> = out.iter().map(|a| a.kind).collect();
+        assert_eq!(got, kinds);
+
+        let tokens = String::from_utf8(out[0].contents.clone()).unwrap();
+        assert!(tokens.starts_with("   1  PUBLIC         public\n"), "{}", tokens);
+        assert!(String::from_utf8_lossy(&out[1].contents).starts_with("{\"id\":"));
+        assert!(String::from_utf8_lossy(&out[2].contents).contains("proc main"));
+        assert_eq!(&out[3].contents[0..8], b"Jzero!!\0");
+        assert_eq!(out[2].file_name("hello"), "hello.tac");
+    }
+
+    #[test]
+    fn compiles_to_a_target_in_memory() {
+        let compiler = Compiler::new().source(HELLO);
+        let ir = compiler.compile_to(Target::Ir).unwrap();
+        assert_eq!(ir.kind, EmitKind::Ir);
+        assert!(String::from_utf8_lossy(&ir.contents).contains("proc main"));
+        assert_eq!(ir.metadata, Metadata {
+            entry_class: Some("hello".into()),
+            entry_offset: None,
+            symbols: vec!["hello".into(), "hello.main".into()],
+            natives: vec![],
+        });
+
+        let image = compiler.compile_to(Target::Bytecode).unwrap();
+        let offset = image.metadata.entry_offset.unwrap();
+        assert!(offset > 8 && offset < image.contents.len(), "{}", offset);
+        assert!(compiler.compile_to(Target::Asm).unwrap().metadata.entry_offset.is_none());
+        let src = "public class T {
+    public static int twice(int n) { return n + n; }
+    public static void main(String argv[]) { twice(2); }
+}";
+        let symbols = Compiler::new().source(src).compile_to(Target::Asm).unwrap().metadata.symbols;
+        assert_eq!(symbols, ["T", "T.twice", "T.main"]);
+        assert!(Compiler::new().source("public class T { int x; int x; }").compile_to(Target::Ir).is_err());
+
+        let err = image.write_to("/no/such/dir/hello.j0").unwrap_err();
+        assert!(err.0.starts_with("error writing '/no/such/dir/hello.j0': "), "{}", err);
+    }
+
+    #[test]
+    fn tree_artifacts_do_not_need_analysis() {
+        // `x` is declared twice: analysis fails, but the tree is still emitted.
+        let src = "public class T { public static void main(String argv[]) { int x; int x; } }";
+        let out = Compiler::new().source(src).emit(&[EmitKind::Ast]).unwrap();
+        assert!(String::from_utf8_lossy(&out[0].contents).starts_with("ClassDecl#0"));
+        assert!(Compiler::new().source(src).emit(&[EmitKind::Symtab]).is_err());
+    }
+
+    #[test]
+    fn tokens_and_trees_follow_the_language_level() {
+        let src = "public class T { final int x = 1; }";
+        let out = Compiler::new().level(Level::Extended).source(src).emit(&[EmitKind::Tokens, EmitKind::Ast]).unwrap();
+        assert!(String::from_utf8_lossy(&out[0].contents).contains("FINAL          final"));
+        assert!(String::from_utf8_lossy(&out[1].contents).contains("FieldDecl#9"));
+    }
+}
@@ -0,0 +1,62 @@
+//! Compilation event hooks.
+//!
+//! A [`CompilerEvents`] observer attached with [`Compiler::events`] is told
+//! about each artifact as the pipeline produces it, in phase order:
+//!
+//! ```text
+//! on_token          – every token, in source order          (lexing)
+//! on_node_built     – every tree node, children first       (parsing)
+//! on_scope_created  – every symbol table, parents first     (semantic)
+//! on_diagnostic     – every error that stops the pipeline
+//! on_ir_emitted     – every TAC instruction, in program order (codegen)
+//! ```
+//!
+//! Nodes are reported in post-order because that is the order an LR parser
+//! reduces them.  All methods have empty defaults, so an observer only
+//! implements the events it cares about.  Methods take `&self`; observers
+//! that record events use interior mutability.
+//!
+//! [`Compiler::events`]: crate::Compiler::events
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use jzero_ast::tree::Tree;
+use jzero_codegen::Tac;
+use jzero_lexer::{LexOptions, SpannedToken};
+use jzero_symtab::SymTab;
+
+/// Observer for compilation phases.
+pub trait CompilerEvents {
+    fn on_token(&self, _tok: &SpannedToken) {}
+    fn on_node_built(&self, _node: &Tree) {}
+    fn on_scope_created(&self, _scope: &SymTab) {}
+    fn on_diagnostic(&self, _msg: &str) {}
+    fn on_ir_emitted(&self, _instr: &Tac) {}
+}
+
+/// Report every token in `source` that lexes with `options`, around any
+/// lexical errors; the errors are left to the parser, which reports them
+/// as a diagnostic.
+pub(crate) fn notify_tokens(ev: &dyn CompilerEvents, source: &str, options: &LexOptions) {
+    for tok in &jzero_lexer::lex_with(source, options).tokens {
+        ev.on_token(tok);
+    }
+}
+
+/// Report every node of `tree` in reduction (post-) order.
+pub(crate) fn notify_nodes(ev: &dyn CompilerEvents, tree: &Tree) {
+    for kid in &tree.kids {
+        notify_nodes(ev, kid);
+    }
+    ev.on_node_built(tree);
+}
+
+/// Report `scope` and every scope nested under it, parents first.
+pub(crate) fn notify_scopes(ev: &dyn CompilerEvents, scope: &Rc<RefCell<SymTab>>) {
+    ev.on_scope_created(&scope.borrow());
+    let children = scope.borrow().children();
+    for child in &children {
+        notify_scopes(ev, child);
+    }
+}
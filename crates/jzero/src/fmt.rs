@@ -0,0 +1,608 @@
+//! Source formatter (`j0 fmt`).
+//!
+//! Works on the lossless token stream from [`jzero_lexer::lex_lossless`], so
+//! comments are never lost and the parser is not needed — a file with syntax
+//! errors still formats.
+//!
+//! The canonical layout is the one used by the programs in `tests/examples`:
+//!
+//! - four-space indent per brace level, opening brace on the same line
+//! - one statement per line; `} else {` on one line
+//! - one space around binary operators and after commas
+//! - no space inside parentheses/brackets, or between a name and its `(`
+//! - at most one blank line in a row, kept where the author put one
+//! - comments stay where they were: on their own line, or at the end of
+//!   the line they trailed
+//! - the file ends with a newline only if it did before
+//!
+//! Lines the formatter cannot make sense of are left exactly as written:
+//! those holding text that does not lex, and those holding a bracket that
+//! matches none — a stray `}`, or a `(` a `}` closes over.  A stray bracket
+//! does not count towards the nesting of the lines after it.
+//! [`format_recovering`] formats such a file and says which lines it kept;
+//! [`format_source`] refuses one that does not lex.
+//!
+//! [`indentation`] and [`reindent_line`] apply the same layout to one line
+//! as it is typed, for an editor's on-type formatting: the new line after
+//! Enter, or a line whose `}` was just typed, gets the indent the formatter
+//! would give it.
+
+use std::collections::BTreeMap;
+
+use jzero_lexer::token::Token;
+use jzero_lexer::{LexError, LosslessToken, Trivia, TriviaKind, lex, lex_lossless, lex_lossless_recovering};
+use jzero_text::{Span, TextEdit, diff_edit, line_col};
+
+use crate::JzeroError;
+
+const INDENT: &str = "    ";
+
+/// Format `source` into the canonical layout.
+///
+/// # Errors
+/// Returns a [`JzeroError`] if the source does not lex.
+pub fn format_source(source: &str) -> Result<String, JzeroError> {
+    let formatted = format_recovering(source);
+    if !formatted.errors.is_empty() {
+        let errs: Vec<String> = formatted.errors.iter().map(|e| e.to_string()).collect();
+        return Err(JzeroError(errs.join("\n")));
+    }
+    Ok(formatted.text)
+}
+
+/// What [`format_recovering`] made of a file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Formatted {
+    pub text: String,
+    /// The lines left as written, 1-based, each with the reason: a
+    /// lexical error or an unmatched bracket on it.
+    pub kept: Vec<(usize, String)>,
+    /// The lexical errors in the file.
+    pub errors: Vec<LexError>,
+}
+
+/// Format `source` into the canonical layout, leaving the lines with text
+/// that does not lex or an unmatched bracket as written.
+pub fn format_recovering(source: &str) -> Formatted {
+    let (file, errors) = lex_lossless_recovering(source);
+    let stray = unmatched_brackets(&file.tokens);
+
+    // Every piece of the file, token or trivia, in order, on its line.
+    let mut pieces = Vec::new();
+    for (i, tok) in file.tokens.iter().enumerate() {
+        pieces.extend(tok.leading.iter().map(Piece::Trivia));
+        pieces.push(Piece::Token(i));
+    }
+    pieces.extend(file.trailing.iter().map(Piece::Trivia));
+
+    let mut kept = BTreeMap::new();
+    let mut unlexed = errors.iter();
+    let mut line = 1;
+    for piece in &pieces {
+        let text = piece.text(&file.tokens);
+        let why = match piece {
+            Piece::Trivia(t) if t.kind == TriviaKind::Unlexed => unlexed.next().map(|e| e.to_string()),
+            Piece::Token(i) if stray[*i] => Some(format!("unmatched '{}'", text)),
+            _ => None,
+        };
+        if let Some(why) = why {
+            for l in line..=line + text.trim_end_matches('\n').matches('\n').count() {
+                kept.entry(l).or_insert_with(|| why.clone());
+            }
+        }
+        line += text.matches('\n').count();
+    }
+
+    let mut f = Formatter::default();
+    let mut pending: Vec<Trivia> = Vec::new();
+    let (mut at, mut line, mut i) = (0, 1, 0);
+    while i < pieces.len() {
+        if kept.contains_key(&line) {
+            // Write the rest of the line, and of any line a piece on it
+            // runs into, as it stands.
+            f.trivia(&std::mem::take(&mut pending));
+            let start = at;
+            let mut end = line_end(source, at);
+            while i < pieces.len() && at < end {
+                let text = pieces[i].text(&file.tokens);
+                if let Piece::Token(k) = pieces[i] { f.track(&file.tokens[k].token, stray[k]); }
+                at += text.len();
+                line += text.matches('\n').count();
+                if at > end { end = line_end(source, at - 1); }
+                i += 1;
+            }
+            f.verbatim(&source[start..end], start == line_start(source, start));
+            continue;
+        }
+        let text = pieces[i].text(&file.tokens);
+        match pieces[i] {
+            Piece::Trivia(t) => pending.push(t.clone()),
+            Piece::Token(k) => {
+                f.trivia(&std::mem::take(&mut pending));
+                f.token(&file.tokens[k], file.tokens.get(k + 1));
+            }
+        }
+        at += text.len();
+        line += text.matches('\n').count();
+        i += 1;
+    }
+    f.trivia(&pending);
+
+    let mut text = f.out.trim_end_matches([' ', '\n']).to_string();
+    if source.ends_with('\n') {
+        text.push('\n');
+    }
+    Formatted { text, kept: kept.into_iter().collect(), errors }
+}
+
+/// A token of a [`LosslessFile`](jzero_lexer::LosslessFile), by index, or
+/// a piece of its trivia.
+#[derive(Clone, Copy)]
+enum Piece<'a> {
+    Trivia(&'a Trivia),
+    Token(usize),
+}
+
+impl<'a> Piece<'a> {
+    fn text(self, tokens: &'a [LosslessToken]) -> &'a str {
+        match self {
+            Piece::Trivia(t) => &t.text,
+            Piece::Token(i) => &tokens[i].text,
+        }
+    }
+}
+
+/// Which of `tokens` are brackets that match none: a closing one with no
+/// opening one of its kind before it, or an opening one that a closing one
+/// of another kind skips, as the `}` of `f(x; }` skips the `(`.
+fn unmatched_brackets(tokens: &[LosslessToken]) -> Vec<bool> {
+    let mut stray = vec![false; tokens.len()];
+    let mut open: Vec<usize> = Vec::new();
+    for (i, tok) in tokens.iter().enumerate() {
+        let opening = match tok.token {
+            Token::LBrace | Token::LParen | Token::LBracket => { open.push(i); continue; }
+            Token::RBrace => Token::LBrace,
+            Token::RParen => Token::LParen,
+            Token::RBracket => Token::LBracket,
+            _ => continue,
+        };
+        match open.iter().rposition(|&o| tokens[o].token == opening) {
+            Some(at) => for skipped in open.drain(at..).skip(1) { stray[skipped] = true; },
+            None => stray[i] = true,
+        }
+    }
+    stray
+}
+
+/// The edits that bring `source` into canonical layout — empty if it is
+/// already formatted.  Text outside the edit is left exactly as written.
+///
+/// # Errors
+/// Returns a [`JzeroError`] if the source does not lex.
+pub fn format_edits(source: &str) -> Result<Vec<TextEdit>, JzeroError> {
+    let formatted = format_source(source)?;
+    Ok(diff_edit(source, &formatted).into_iter().collect())
+}
+
+/// Returns true if `source` is already in canonical layout.
+///
+/// # Errors
+/// Returns a [`JzeroError`] if the source does not lex.
+pub fn is_formatted(source: &str) -> Result<bool, JzeroError> {
+    Ok(format_source(source)? == source)
+}
+
+/// The indent the formatter gives the line holding byte `offset`: one
+/// level per brace open before it, one less if the line starts with `}`,
+/// and one more inside an unclosed `(` or `[`.  `None` if the line starts
+/// inside a comment or a text block, whose lines are left as written.
+///
+/// The source need not lex or parse — it is usually being typed.
+///
+/// # Panics
+/// Panics if `offset` is not on a character boundary of `source`.
+pub fn indentation(source: &str, offset: usize) -> Option<String> {
+    let start = line_start(source, offset);
+    let (mut braces, mut parens) = nesting(source, start)?;
+    let rest = source[start..].trim_start_matches([' ', '\t']);
+    if rest.starts_with('}') {
+        braces = braces.saturating_sub(1);
+    } else if rest.starts_with([')', ']']) {
+        parens = parens.saturating_sub(1);
+    }
+    let levels = braces + usize::from(parens > 0);
+    Some(INDENT.repeat(levels))
+}
+
+/// The edit giving the line holding byte `offset` its [`indentation`];
+/// `None` if it already has it or is left as written.
+///
+/// # Panics
+/// Panics if `offset` is not on a character boundary of `source`.
+pub fn reindent_line(source: &str, offset: usize) -> Option<TextEdit> {
+    let start = line_start(source, offset);
+    let indent = source[start..].len() - source[start..].trim_start_matches([' ', '\t']).len();
+    let want = indentation(source, offset)?;
+    (source[start..start + indent] != want).then(|| TextEdit::replace(Span::new(start, start + indent), &want))
+}
+
+/// The offset of the start of the line holding byte `offset`.
+fn line_start(source: &str, offset: usize) -> usize {
+    jzero_text::line_start(source, line_col(source, offset).0)
+}
+
+/// The offset just past the end of the line holding byte `offset`, its
+/// newline included.
+fn line_end(source: &str, offset: usize) -> usize {
+    source[offset..].find('\n').map_or(source.len(), |i| offset + i + 1)
+}
+
+/// The braces, and parentheses or brackets, open before the line starting
+/// at `start`; `None` if the line starts inside a token or comment.
+fn nesting(source: &str, start: usize) -> Option<(usize, usize)> {
+    let tokens = lex(source).tokens;
+    let (mut braces, mut parens) = (0usize, 0usize);
+    let mut gap = (0, source.len());
+    for tok in &tokens {
+        if tok.start >= start {
+            gap.1 = tok.start;
+            break;
+        }
+        if tok.end > start {
+            return None;
+        }
+        match tok.token {
+            Token::LBrace => braces += 1,
+            Token::RBrace => braces = braces.saturating_sub(1),
+            Token::LParen | Token::LBracket => parens += 1,
+            Token::RParen | Token::RBracket => parens = parens.saturating_sub(1),
+            _ => {}
+        }
+        gap.0 = tok.end;
+    }
+    // Between two tokens there is only whitespace and comments — and text
+    // that does not lex, which is taken to be neither.
+    let Ok(file) = lex_lossless(&source[gap.0..gap.1]) else { return Some((braces, parens)) };
+    let mut at = gap.0;
+    for t in file.trailing {
+        if t.is_comment() && at < start && start < at + t.text.len() {
+            return None;
+        }
+        at += t.text.len();
+    }
+    Some((braces, parens))
+}
+
+#[derive(Default)]
+struct Formatter {
+    out:          String,
+    depth:        usize,
+    parens:       usize,
+    /// Paren depth of an open `for (...)` header, if any.
+    for_header:   Option<usize>,
+    pending_for:  bool,
+    /// How many braces of an array initializer list are open; those stay
+    /// on the line, `{1, 2}`.
+    init_braces:  usize,
+    prev:         Option<Token>,
+    prev_unary:   bool,
+}
+
+impl Formatter {
+    fn at_line_start(&self) -> bool {
+        self.out.is_empty() || self.out.ends_with('\n')
+    }
+
+    fn newline(&mut self) {
+        if !self.at_line_start() {
+            self.out.push('\n');
+        }
+    }
+
+    fn blank_line(&mut self) {
+        self.newline();
+        if !self.out.is_empty() && !self.out.ends_with("\n\n") && !self.out.ends_with("{\n") {
+            self.out.push('\n');
+        }
+    }
+
+    fn indent(&mut self) {
+        if self.at_line_start() {
+            for _ in 0..self.depth { self.out.push_str(INDENT); }
+        }
+    }
+
+    /// Emit `text` as it stands: on a line of its own if it starts a line
+    /// of the source, else after a space.
+    fn verbatim(&mut self, text: &str, starts_line: bool) {
+        if starts_line {
+            self.newline();
+        } else if !self.at_line_start() {
+            self.out.push(' ');
+        }
+        self.out.push_str(text);
+    }
+
+    /// Keep the nesting [`Formatter::token`] keeps, for a token written out
+    /// by [`Formatter::verbatim`].  A `stray` bracket changes nothing.
+    fn track(&mut self, cur: &Token, stray: bool) {
+        if stray { return; }
+        let in_init = self.init_braces > 0 || self.prev == Some(Token::Assign);
+        match cur {
+            Token::LBrace if !in_init => self.depth += 1,
+            Token::RBrace if !in_init => self.depth = self.depth.saturating_sub(1),
+            _ => {}
+        }
+        self.advance(cur, in_init);
+    }
+
+    /// Emit the comments in `trivia`, keeping blank lines and placement.
+    fn trivia(&mut self, trivia: &[Trivia]) {
+        // Newlines seen since the previous token or comment.
+        let mut newlines = 0;
+        for t in trivia {
+            if !t.is_comment() {
+                newlines += t.newlines();
+                continue;
+            }
+            if newlines == 0 && !self.out.is_empty() {
+                // Trailing comment: keep it on the line it followed.
+                if self.out.ends_with('\n') { self.out.pop(); }
+                self.out.push(' ');
+            } else {
+                if newlines >= 2 { self.blank_line(); }
+                self.newline();
+                self.indent();
+            }
+            self.out.push_str(t.text.trim_end());
+            if t.kind == TriviaKind::LineComment {
+                self.out.push('\n');
+            }
+            newlines = t.newlines();
+        }
+        if newlines >= 2 && self.at_line_start() {
+            self.blank_line();
+        }
+    }
+
+    fn token(&mut self, tok: &LosslessToken, next: Option<&LosslessToken>) {
+        let cur = &tok.token;
+        let in_init = self.init_braces > 0 || self.prev == Some(Token::Assign);
+        match cur {
+            Token::LBrace | Token::RBrace if in_init => {
+                if self.at_line_start() {
+                    self.indent();
+                } else if self.space_before(cur) {
+                    self.out.push(' ');
+                }
+                self.out.push_str(&tok.text);
+            }
+            Token::LBrace => {
+                if self.at_line_start() { self.indent(); } else { self.out.push(' '); }
+                self.out.push('{');
+                self.out.push('\n');
+                self.depth += 1;
+            }
+            Token::RBrace => {
+                self.depth = self.depth.saturating_sub(1);
+                self.newline();
+                self.indent();
+                self.out.push('}');
+                // `} else` stays on one line; `space_before` adds the gap.
+                if next.map(|n| &n.token) != Some(&Token::Else) {
+                    self.out.push('\n');
+                }
+            }
+            Token::Semicolon if self.for_header.is_none() => {
+                self.out.push(';');
+                self.out.push('\n');
+            }
+            _ => {
+                if self.at_line_start() {
+                    self.indent();
+                } else if self.space_before(cur) {
+                    self.out.push(' ');
+                }
+                self.out.push_str(&tok.text);
+            }
+        }
+        self.advance(cur, in_init);
+    }
+
+    /// Note what `cur`, just written, opens or closes.
+    fn advance(&mut self, cur: &Token, in_init: bool) {
+        match cur {
+            Token::For => self.pending_for = true,
+            Token::LBrace if in_init => self.init_braces += 1,
+            Token::RBrace if in_init => self.init_braces = self.init_braces.saturating_sub(1),
+            Token::LParen => {
+                self.parens += 1;
+                if self.pending_for {
+                    self.for_header = Some(self.parens);
+                    self.pending_for = false;
+                }
+            }
+            Token::RParen => {
+                if self.for_header == Some(self.parens) { self.for_header = None; }
+                self.parens = self.parens.saturating_sub(1);
+            }
+            _ => {}
+        }
+
+        self.prev_unary = matches!(cur, Token::Minus | Token::Bang) && self.is_unary_position();
+        self.prev = Some(cur.clone());
+    }
+
+    /// True if an operator in the current position is a prefix operator.
+    fn is_unary_position(&self) -> bool {
+        match &self.prev {
+            None => true,
+            Some(p) => is_operator(p) || matches!(p,
+                Token::LParen | Token::LBracket | Token::Comma | Token::Return
+                | Token::LBrace | Token::RBrace | Token::Semicolon),
+        }
+    }
+
+    fn space_before(&self, cur: &Token) -> bool {
+        let Some(prev) = &self.prev else { return false };
+        if matches!(cur, Token::RParen | Token::RBracket | Token::Comma | Token::Semicolon | Token::Dot) {
+            return false;
+        }
+        if matches!(prev, Token::LParen | Token::LBracket | Token::Dot) || self.prev_unary {
+            return false;
+        }
+        if self.init_braces > 0 && (*cur == Token::RBrace || *prev == Token::LBrace) {
+            return false;
+        }
+        match cur {
+            Token::LParen   => !matches!(prev, Token::Identifier | Token::RParen | Token::RBracket),
+            Token::LBracket => false,
+            _ => true,
+        }
+    }
+}
+
+fn is_operator(tok: &Token) -> bool {
+    matches!(tok,
+        Token::Assign | Token::PlusAssign | Token::MinusAssign
+        | Token::StarAssign | Token::SlashAssign | Token::PercentAssign
+        | Token::Plus | Token::Minus | Token::Star | Token::Slash | Token::Percent
+        | Token::Less | Token::Greater | Token::LessEqual | Token::GreaterEqual
+        | Token::EqualEqual | Token::NotEqual | Token::LogicalAnd | Token::LogicalOr
+        | Token::Bang)
+}
+
+// ─── Tests ───────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn examples_are_already_formatted() {
+        // hello_loop.java keeps the book's three-space indent, so it is left out.
+        for name in ["hello", "concat", "countdown", "fibonacci", "fizzbuzz", "greet"] {
+            let path = format!("{}/../../tests/examples/{}.java", env!("CARGO_MANIFEST_DIR"), name);
+            let src = std::fs::read_to_string(&path).unwrap();
+            assert_eq!(format_source(&src).unwrap(), src, "{} is not canonical", name);
+        }
+    }
+
+    #[test]
+    fn messy_source_is_normalised() {
+        let src = "public class a{public static void main(String argv[]){int x;x=-1+2*(3-x);\
+                   if(x<0){x=x;}else{x= !true;}}}\n";
+        let want = "\
+public class a {
+    public static void main(String argv[]) {
+        int x;
+        x = -1 + 2 * (3 - x);
+        if (x < 0) {
+            x = x;
+        } else {
+            x = !true;
+        }
+    }
+}
+";
+        assert_eq!(format_source(src).unwrap(), want);
+    }
+
+    #[test]
+    fn comments_and_blank_lines_survive() {
+        let src = "class a {\n  int x;   // trailing\n\n\n  // own line\n  int y;\n}";
+        let want = "class a {\n    int x; // trailing\n\n    // own line\n    int y;\n}";
+        assert_eq!(format_source(src).unwrap(), want);
+    }
+
+    #[test]
+    fn for_header_stays_on_one_line() {
+        let src = "class a { void f() { for (i=0;i<3;i=i+1) { } } }";
+        let out = format_source(src).unwrap();
+        assert!(out.contains("for (i = 0; i < 3; i = i + 1) {\n"), "{}", out);
+    }
+
+    #[test]
+    fn array_initializers_stay_on_one_line() {
+        let src = "class a { int t[]={ 1,-2 ,3 }; void f() { int g[][] = {{1},{ }}; } }";
+        let out = format_source(src).unwrap();
+        assert!(out.contains("    int t[] = {1, -2, 3};\n"), "{}", out);
+        assert!(out.contains("        int g[][] = {{1}, {}};\n    }\n"), "{}", out);
+    }
+
+    #[test]
+    fn format_edits_touch_only_the_change() {
+        let src = "class a {\n    int x;\n    int  y;\n}\n";
+        let edits = format_edits(src).unwrap();
+        assert_eq!(edits.len(), 1);
+        assert!(edits[0].span.start > src.find("int x").unwrap());
+        assert_eq!(jzero_text::apply_edits(src, &edits), format_source(src).unwrap());
+    }
+
+    #[test]
+    fn indentation_agrees_with_the_formatter() {
+        let src = "class a{void f(){if(x){y();}else{z();}}\n/* a\n   b */ int g;}\n";
+        let formatted = format_source(src).unwrap();
+        let mut offset = 0;
+        for line in formatted.split_inclusive('\n') {
+            if line.trim() != "b */ int g;" {
+                let indent = &line[..line.len() - line.trim_start().len()];
+                assert_eq!(indentation(&formatted, offset).as_deref(), Some(indent), "{:?}", line);
+                assert_eq!(reindent_line(&formatted, offset), None);
+            }
+            offset += line.len();
+        }
+        // A line inside a comment keeps its own indent.
+        let comment = formatted.find("   b */").unwrap();
+        assert_eq!(indentation(&formatted, comment), None);
+    }
+
+    #[test]
+    fn lines_being_typed_are_reindented() {
+        // Enter after `{`: the new, empty line goes one level in.
+        let src = "class a {\n    void f() {\n\n}";
+        let blank = src.find("\n\n").unwrap() + 1;
+        assert_eq!(indentation(src, blank).as_deref(), Some("        "));
+        assert_eq!(reindent_line(src, blank), Some(TextEdit::insert(blank, "        ")));
+
+        // A `}` typed at the wrong depth moves back out.
+        let src = "class a {\n    void f() {\n        x = 1;\n        }";
+        let edit = reindent_line(src, src.len()).unwrap();
+        assert_eq!(jzero_text::apply_edits(src, &[edit]), "class a {\n    void f() {\n        x = 1;\n    }");
+
+        // Inside an unclosed call, one level more; the source need not lex.
+        let src = "class a {\n  void f() {\n    g(1,\n2); @\n}";
+        let arg = src.find("2);").unwrap();
+        assert_eq!(indentation(src, arg).as_deref(), Some("            "));
+        assert_eq!(indentation(src, src.len()).as_deref(), Some("    "));
+    }
+
+    #[test]
+    fn lines_that_do_not_lex_are_kept_as_written() {
+        let src = "class a{int x;\n  void f( ) {  x = \"abc;   # y\n  g( 1 );}}\n";
+        let formatted = format_recovering(src);
+        assert_eq!(formatted.text, "class a {\n    int x;\n  void f( ) {  x = \"abc;   # y\n        g(1);\n    }\n}\n");
+        assert_eq!(formatted.kept.iter().map(|(line, _)| *line).collect::<Vec<_>>(), [2]);
+        assert_eq!(formatted.errors.len(), 1);
+        assert!(format_source(src).is_err());
+    }
+
+    #[test]
+    fn unmatched_brackets_are_kept_and_do_not_nest() {
+        // The `(` is never closed, and the class has one `}` too many.
+        let src = "class a {\nvoid f() {\nif(x{  y( );\n}\n}\n}\n  }\nint  z;\n";
+        let formatted = format_recovering(src);
+        assert_eq!(formatted.text, "class a {\n    void f() {\nif(x{  y( );\n        }\n    }\n}\n  }\nint z;\n");
+        let kept: Vec<(usize, &str)> = formatted.kept.iter().map(|(l, why)| (*l, why.as_str())).collect();
+        assert_eq!(kept, [(3, "unmatched '('"), (7, "unmatched '}'")]);
+        assert_eq!(format_source(src).unwrap(), formatted.text);
+    }
+
+    #[test]
+    fn formatting_is_idempotent() {
+        let src = "class a{ /* c */ int f(int a,int b){return a-b;}}";
+        let once = format_source(src).unwrap();
+        assert_eq!(format_source(&once).unwrap(), once);
+        assert!(is_formatted(&once).unwrap());
+    }
+}
@@ -0,0 +1,263 @@
+//! Inlay hints — what the compiler works out about a file, for an editor
+//! to show between the characters of the source.
+//!
+//! [`inlay_hints`] finds three kinds:
+//!
+//! - the name of the parameter each argument of a call is passed as, left
+//!   out where the argument is a variable of that name;
+//! - the value of an expression folded at compile time, such as `2 * 8 - 1`;
+//! - the type of a declaration the type before the name does not spell out
+//!   in full: `int a[]` declares an `int[]`.
+//!
+//! [`annotate`] writes the hints into the source as comments, for
+//! `j0 fmt --annotate`.
+//!
+//! ```
+//! let src = "public class a {\n    public static int f(int n, int m[]) { return n * 2 + 1; }\n}\n";
+//! let hints = jzero::hints::inlay_hints(src).unwrap();
+//! assert_eq!(hints.iter().map(|h| h.label.as_str()).collect::<Vec<_>>(), [": int[]"]);
+//! ```
+
+use std::collections::HashMap;
+
+use jzero_ast::tree::{LeafToken, Tree};
+use jzero_semantic::eval_const;
+use jzero_symtab::TypeInfo;
+use jzero_text::Span;
+
+use crate::JzeroError;
+use crate::ranges::node_spans;
+
+/// What an [`InlayHint`] tells.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InlayHintKind {
+    /// The name of a parameter, shown before its argument.
+    Parameter,
+    /// The value of a constant expression, shown after it.
+    Constant,
+    /// The type of a declaration, shown after the declared name.
+    Type,
+}
+
+/// A label to show at a place in the source.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InlayHint {
+    /// Byte offset the label goes at.
+    pub offset: usize,
+    /// `n:`, `= 15` or `: int[]`.
+    pub label: String,
+    pub kind: InlayHintKind,
+}
+
+/// The inlay hints of `source`, ordered by offset.
+///
+/// Types come from semantic analysis, so a declaration it cannot type has
+/// no type hint; the other hints need only a syntax tree.
+///
+/// # Errors
+/// Returns a [`JzeroError`] if the source does not parse.
+pub fn inlay_hints(source: &str) -> Result<Vec<InlayHint>, JzeroError> {
+    let tree = jzero_parser::parse_tree(source).map_err(JzeroError)?;
+    let spans = node_spans(source, &tree);
+    // Analysis rewrites the tree, but declared names keep their leaves.
+    let mut analysed = tree.clone();
+    jzero_semantic::analyze(&mut analysed);
+    let mut types = Vec::new();
+    declared_types(&analysed, &mut types);
+
+    let mut hints = Hints { spans: &spans, class: declared_name(&tree), methods: Vec::new(), types, out: Vec::new() };
+    collect_methods(&tree, &mut hints.methods);
+    hints.visit(&tree, false);
+    let mut out = hints.out;
+    out.sort_by_key(|h| h.offset);
+    Ok(out)
+}
+
+/// `source` with its inlay hints written in as comments: `f(/* n: */ 3)`,
+/// `2 * 8 - 1 /* = 15 */`, `int a[] /* : int[] */`.
+///
+/// # Errors
+/// Returns a [`JzeroError`] if the source does not parse.
+pub fn annotate(source: &str) -> Result<String, JzeroError> {
+    let mut out = String::with_capacity(source.len());
+    let mut at = 0;
+    for hint in inlay_hints(source)? {
+        out.push_str(&source[at..hint.offset]);
+        at = hint.offset;
+        match hint.kind {
+            InlayHintKind::Parameter => out.push_str(&format!("/* {} */ ", hint.label)),
+            InlayHintKind::Constant | InlayHintKind::Type => out.push_str(&format!(" /* {} */", hint.label)),
+        }
+    }
+    out.push_str(&source[at..]);
+    Ok(out)
+}
+
+struct Hints<'a> {
+    spans: &'a HashMap<u32, Span>,
+    class: Option<&'a LeafToken>,
+    /// Each method's name and parameter names.
+    methods: Vec<(String, Vec<String>)>,
+    /// The type of each declared name, by its text and line.
+    types: Vec<(String, usize, TypeInfo)>,
+    out: Vec<InlayHint>,
+}
+
+impl Hints<'_> {
+    fn push(&mut self, offset: usize, label: String, kind: InlayHintKind) {
+        self.out.push(InlayHint { offset, label, kind });
+    }
+
+    /// `in_constant` is true below an expression already given a value.
+    fn visit(&mut self, tree: &Tree, in_constant: bool) {
+        let constant = !in_constant && !tree.is_leaf() && eval_const(tree).is_some();
+        if constant && !(tree.sym == "UnaryMinus" && tree.kids.first().is_some_and(Tree::is_leaf))
+            && let (Some(value), Some(span)) = (eval_const(tree), self.spans.get(&tree.id))
+        {
+            self.push(span.end, format!("= {}", value), InlayHintKind::Constant);
+        }
+        match tree.sym.as_str() {
+            "MethodCall" => self.call(tree),
+            "VarDeclarator" if tree.rule == 1 => self.declaration(tree),
+            _ => {}
+        }
+        // An array declarator holds the declarator of its name.
+        let array = tree.sym == "VarDeclarator" && tree.rule == 1;
+        for kid in tree.kids.iter().filter(|k| !(array && k.sym == "VarDeclarator")) {
+            self.visit(kid, in_constant || constant);
+        }
+    }
+
+    fn call(&mut self, call: &Tree) {
+        // `f(args)` and `f{args}`, or `a.f(args)` and `a.f{args}` on the class.
+        let (name, args) = match call.rule {
+            0 | 1 => (call.kids.first(), call.kids.get(1..)),
+            _ => {
+                let base = call.kids.first().and_then(|b| b.tok.as_ref());
+                let on_class = base.is_some_and(|b| self.class.is_some_and(|c| c.text == b.text));
+                (call.kids.get(1).filter(|_| on_class), call.kids.get(2..))
+            }
+        };
+        let (Some(name), Some(args)) = (name.and_then(|n| n.tok.as_ref()), args) else { return };
+        let Some(params) = self.methods.iter().find(|(m, _)| *m == name.text).map(|(_, p)| p.clone()) else { return };
+        if params.len() != args.len() {
+            return;
+        }
+        for (param, arg) in params.iter().zip(args) {
+            let same_name = arg.tok.as_ref().is_some_and(|t| t.text == *param);
+            if let (false, Some(span)) = (same_name, self.spans.get(&arg.id)) {
+                self.push(span.start, format!("{}:", param), InlayHintKind::Parameter);
+            }
+        }
+    }
+
+    fn declaration(&mut self, declarator: &Tree) {
+        let Some(name) = declared_name(declarator) else { return };
+        let typ = self.types.iter().find(|(n, l, _)| *n == name.text && *l == name.lineno).map(|(_, _, t)| t);
+        if let (Some(typ), Some(span)) = (typ, self.spans.get(&declarator.id)) {
+            self.push(span.end, format!(": {}", typ.display()), InlayHintKind::Type);
+        }
+    }
+}
+
+/// The name leaf of a class, method declarator or variable declarator:
+/// its first leaf.
+fn declared_leaf(tree: &Tree) -> Option<&Tree> {
+    match tree.tok {
+        Some(_) => Some(tree),
+        None => declared_leaf(tree.kids.first()?),
+    }
+}
+
+fn declared_name(tree: &Tree) -> Option<&LeafToken> {
+    declared_leaf(tree)?.tok.as_ref()
+}
+
+fn collect_methods(tree: &Tree, out: &mut Vec<(String, Vec<String>)>) {
+    if tree.sym == "MethodDeclarator" {
+        let params = tree.kids[1..].iter()
+            .filter(|p| p.sym == "FormalParm")
+            .filter_map(|p| declared_name(p.kids.get(1)?))
+            .map(|leaf| leaf.text.clone());
+        if let Some(name) = declared_name(tree) {
+            out.push((name.text.clone(), params.collect()));
+        }
+        return;
+    }
+    for kid in &tree.kids {
+        collect_methods(kid, out);
+    }
+}
+
+/// The types semantic analysis gave the names declared in `tree`.
+fn declared_types(tree: &Tree, out: &mut Vec<(String, usize, TypeInfo)>) {
+    if tree.sym == "VarDeclarator" {
+        if let Some(leaf) = declared_leaf(tree)
+            && let (Some(name), Some(typ)) = (&leaf.tok, &leaf.typ)
+        {
+            out.push((name.text.clone(), name.lineno, typ.clone()));
+        }
+        return;
+    }
+    for kid in &tree.kids {
+        declared_types(kid, out);
+    }
+}
+
+// ─── Tests ───────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SRC: &str = "\
+public class a {
+    public static int area(int width, int height) {
+        return width * height;
+    }
+    public static void main(String argv[]) {
+        int height;
+        int cells[];
+        height = 3;
+        cells = new int[2 * 8 - 1];
+        System.out.println(area(4 + 1, height));
+        System.out.println(a.area(height, -1));
+        System.out.println(area(-(1 + 2), 0));
+    }
+}
+";
+
+    #[test]
+    fn hints_parameters_constants_and_array_types() {
+        let hints = inlay_hints(SRC).unwrap();
+        let shown: Vec<_> = hints.iter()
+            .map(|h| (SRC[..h.offset].lines().count(), h.label.as_str(), h.kind))
+            .collect();
+        assert_eq!(shown, [
+            (5, ": String[]", InlayHintKind::Type),
+            (7, ": int[]", InlayHintKind::Type),
+            (9, "= 15", InlayHintKind::Constant),
+            (10, "width:", InlayHintKind::Parameter),
+            (10, "= 5", InlayHintKind::Constant),
+            (11, "width:", InlayHintKind::Parameter),
+            (11, "height:", InlayHintKind::Parameter),
+            (12, "width:", InlayHintKind::Parameter),
+            (12, "= -3", InlayHintKind::Constant),
+            (12, "height:", InlayHintKind::Parameter),
+        ]);
+    }
+
+    #[test]
+    fn annotate_writes_the_hints_as_comments() {
+        let annotated = annotate(SRC).unwrap();
+        assert!(annotated.contains("String argv[] /* : String[] */)"), "{}", annotated);
+        assert!(annotated.contains("new int[2 * 8 - 1 /* = 15 */]"), "{}", annotated);
+        assert!(annotated.contains("area(/* width: */ 4 + 1 /* = 5 */, height)"), "{}", annotated);
+        assert!(annotated.contains("a.area(/* width: */ height, /* height: */ -1)"), "{}", annotated);
+        // Still a program, with the same tree.
+        assert_eq!(
+            format!("{}", jzero_parser::parse_tree(&annotated).unwrap()),
+            format!("{}", jzero_parser::parse_tree(SRC).unwrap()),
+        );
+    }
+}
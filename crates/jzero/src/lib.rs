@@ -33,13 +33,55 @@
 //!     → run()              [jzero-vm]       → stdout
 //! ```
 
-use jzero_ast::tree::reset_ids;
+use std::rc::Rc;
+
+use jzero_lexer::LexOptions;
+
+pub mod baseline;
+pub mod checks;
+pub mod compare;
+pub mod compat;
+pub mod config;
+pub mod doc;
+pub mod emit;
+pub mod events;
+pub mod fmt;
+pub mod hints;
+pub mod live;
+pub mod locale;
+pub mod memory;
+pub mod metrics;
+pub mod project;
+pub mod query;
+pub mod ranges;
+pub mod refactor;
+pub mod search;
+pub mod semdiff;
+pub mod session;
+pub mod severity;
+pub mod stub;
+pub mod suppress;
+pub mod symbols;
+pub mod unit;
+pub mod vfs;
+pub mod xref;
 
 // ─── Re-exports ───────────────────────────────────────────────────────────────
 
+pub use jzero_ast::{CancellationToken, Cancelled};
 pub use jzero_semantic::SemanticResult;
 pub use jzero_codegen::pipeline::BytecodeOutput;
 pub use jzero_codegen::CodegenContext;
+pub use jzero_vm::{Host, NativeType, Natives, SandboxHost, StdHost, Value};
+pub use config::Config;
+pub use events::CompilerEvents;
+pub use locale::Locale;
+pub use memory::MemoryReport;
+pub use project::{DuplicateClass, Project};
+pub use session::{CompileSession, DiagnosticGroup, DiagnosticOrder, FileId, Limits, SessionOptions, Usage};
+pub use severity::SeverityOverrides;
+pub use unit::UnitTest;
+pub use vfs::{FileProvider, MemoryFs, Overlay, ReadOptions, RealFs, ZipFs};
 
 // ─── CompileOutput ────────────────────────────────────────────────────────────
 
@@ -51,7 +93,7 @@ pub struct RunOutput {
 }
 
 /// The result of compiling to bytecode without executing.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct CompileOutput {
     /// The raw `.j0` binary image.
     pub binary: Vec<u8>,
@@ -59,6 +101,9 @@ pub struct CompileOutput {
     pub text: String,
     /// The TAC assembler listing (intermediate code).
     pub tac: String,
+    /// The native methods the image calls, which must be registered to
+    /// run it (see [`CompileSession::register_native`]).
+    pub natives: Vec<String>,
 }
 
 // ─── Error ────────────────────────────────────────────────────────────────────
@@ -84,6 +129,11 @@ impl std::error::Error for JzeroError {}
 #[derive(Default)]
 pub struct Compiler {
     source: String,
+    events: Option<Rc<dyn CompilerEvents>>,
+    cancel: Option<CancellationToken>,
+    book_compat: bool,
+    defines: Vec<(String, String)>,
+    level: jzero_lexer::Level,
 }
 
 impl Compiler {
@@ -98,15 +148,82 @@ impl Compiler {
         self
     }
 
+    /// Attach an observer that is notified as each phase runs.
+    /// See [`events`] for the order in which events arrive.
+    pub fn events(mut self, observer: Rc<dyn CompilerEvents>) -> Self {
+        self.events = Some(observer);
+        self
+    }
+
+    /// Stop at the next safe point once `token` is cancelled; the phase
+    /// running then fails with a [`JzeroError`] saying so.
+    pub fn cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancel = Some(token);
+        self
+    }
+
+    /// Print trees in [`emit`](Compiler::emit) the way the book's reference
+    /// implementation does; see [`compat`].
+    pub fn book_compat(mut self, on: bool) -> Self {
+        self.book_compat = on;
+        self
+    }
+
+    /// Parse at language level `level`; see [`jzero_lexer::Level`].
+    pub fn level(mut self, level: jzero_lexer::Level) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// Give the `static final` constant `name` the value `value`, a
+    /// literal of its type, in place of its initializer.
+    pub fn define(mut self, name: &str, value: &str) -> Self {
+        self.defines.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// `Err` once the cancellation token, if any, has been cancelled.
+    fn check_cancelled(&self) -> Result<(), JzeroError> {
+        match &self.cancel {
+            Some(token) => token.check().map_err(|c| JzeroError(c.to_string())),
+            None => Ok(()),
+        }
+    }
+
     /// Parse and semantically analyse the source, returning any errors.
     ///
     /// This is the first step in the pipeline and is called internally
     /// by all other methods.
     fn analyse(&self) -> Result<(jzero_ast::tree::Tree, SemanticResult), JzeroError> {
-        reset_ids();
-        let mut tree = jzero_parser::parse_tree(&self.source)
-            .map_err(|e| JzeroError(e.to_string()))?;
-        let sem = jzero_semantic::analyze(&mut tree);
+        let ev = self.events.as_deref();
+        if let Some(ev) = ev { events::notify_tokens(ev, &self.source, &LexOptions::at_level(self.level)); }
+
+        let parsed = match &self.cancel {
+            Some(token) => jzero_parser::parse_tree_cancellable(&self.source, LexOptions::at_level(self.level), token),
+            None => jzero_parser::parse_tree_with(&self.source, LexOptions::at_level(self.level)),
+        };
+        let mut tree = match parsed {
+            Ok(tree) => tree,
+            Err(e) => {
+                self.check_cancelled()?;
+                if let Some(ev) = ev { ev.on_diagnostic(&e); }
+                return Err(JzeroError(e));
+            }
+        };
+        for (name, value) in &self.defines {
+            jzero_semantic::define(&mut tree, name, value).map_err(JzeroError)?;
+        }
+        if let Some(ev) = ev { events::notify_nodes(ev, &tree); }
+
+        let sem = match &self.cancel {
+            Some(token) => jzero_semantic::analyze_cancellable(&mut tree, token)
+                .map_err(|c| JzeroError(c.to_string()))?,
+            None => jzero_semantic::analyze(&mut tree),
+        };
+        if let Some(ev) = ev {
+            events::notify_scopes(ev, &sem.global);
+            for err in &sem.errors { ev.on_diagnostic(&err.to_string()); }
+        }
         if !sem.errors.is_empty() {
             let msg = sem.errors.iter()
                 .map(|e| e.to_string())
@@ -117,13 +234,31 @@ impl Compiler {
         Ok((tree, sem))
     }
 
+    /// Run the codegen passes, reporting each emitted TAC instruction.
+    fn generate(&self, tree: &jzero_ast::tree::Tree, sem: &SemanticResult) -> Result<CodegenContext, JzeroError> {
+        let ctx = match &self.cancel {
+            Some(token) => jzero_codegen::generate_cancellable(tree, sem, token)
+                .map_err(|c| JzeroError(c.to_string()))?,
+            None => jzero_codegen::generate(tree, sem),
+        };
+        if !ctx.errors.is_empty() {
+            return Err(JzeroError(ctx.errors.join("\n")));
+        }
+        if let Some(ev) = self.events.as_deref() {
+            for instr in &jzero_codegen::pipeline::collect_icode(tree, &ctx) {
+                ev.on_ir_emitted(instr);
+            }
+        }
+        Ok(ctx)
+    }
+
     /// Compile to TAC intermediate code and return the assembler listing.
     ///
     /// # Errors
-    /// Returns a [`JzeroError`] if parsing or semantic analysis fails.
+    /// Returns a [`JzeroError`] if parsing, semantic analysis or code generation fails.
     pub fn tac(&self) -> Result<String, JzeroError> {
         let (tree, sem) = self.analyse()?;
-        let ctx = jzero_codegen::generate(&tree, &sem);
+        let ctx = self.generate(&tree, &sem)?;
         Ok(jzero_codegen::emit::emit(&tree, &ctx))
     }
 
@@ -132,16 +267,17 @@ impl Compiler {
     /// `argc` is the number of command-line arguments to pass to `main()`.
     ///
     /// # Errors
-    /// Returns a [`JzeroError`] if parsing or semantic analysis fails.
+    /// Returns a [`JzeroError`] if parsing, semantic analysis or code generation fails.
     pub fn compile(&self, argc: i64) -> Result<CompileOutput, JzeroError> {
         let (tree, sem) = self.analyse()?;
-        let ctx    = jzero_codegen::generate(&tree, &sem);
+        let ctx    = self.generate(&tree, &sem)?;
         let tac    = jzero_codegen::emit::emit(&tree, &ctx);
         let output = jzero_codegen::pipeline::compile_bytecode(&tree, &ctx, argc);
         Ok(CompileOutput {
-            binary: output.binary,
-            text:   output.text,
+            binary:  output.binary,
+            text:    output.text,
             tac,
+            natives: output.natives,
         })
     }
 
@@ -151,15 +287,26 @@ impl Compiler {
     /// so `args.len()` determines `argv.length`.
     ///
     /// # Errors
-    /// Returns a [`JzeroError`] if parsing, semantic analysis, or VM execution fails.
+    /// Returns a [`JzeroError`] if parsing, semantic analysis, code generation, or VM execution fails.
     pub fn run(&self, args: &[&str]) -> Result<RunOutput, JzeroError> {
+        self.run_with_host(args, Box::new(SandboxHost::new()))
+    }
+
+    /// Compile and execute in the VM, routing built-in I/O through `host`.
+    ///
+    /// Use [`StdHost`] to talk to the real console, or a [`SandboxHost`]
+    /// with scripted input, a fixed clock and a seed for deterministic runs.
+    ///
+    /// # Errors
+    /// Returns a [`JzeroError`] if parsing, semantic analysis, code generation, or VM execution fails.
+    pub fn run_with_host(&self, args: &[&str], host: Box<dyn Host>) -> Result<RunOutput, JzeroError> {
         let owned: Vec<String> = args.iter().map(|s| s.to_string()).collect();
         let argc = owned.len() as i64;
         let (tree, sem) = self.analyse()?;
-        let ctx    = jzero_codegen::generate(&tree, &sem);
+        let ctx    = self.generate(&tree, &sem)?;
         let output = jzero_codegen::pipeline::compile_bytecode(&tree, &ctx, argc);
-        let stdout = jzero_vm::run(&output.binary, &owned)
-            .map_err(|e| JzeroError(e))?;
+        let stdout = jzero_vm::run_with_host(&output.binary, &owned, host)
+            .map_err(JzeroError)?;
         Ok(RunOutput { stdout })
     }
 }
@@ -223,6 +370,24 @@ mod tests {
         assert_eq!(out.stdout, "hello, jzero!\n");
     }
 
+    #[test]
+    fn string_escapes_are_decoded() {
+        let out = Compiler::new()
+            .source(&HELLO.replace("hello, jzero!", "tab\\there \\\"q\\\""))
+            .run(&[])
+            .unwrap();
+        assert_eq!(out.stdout, "tab\there \"q\"\n");
+    }
+
+    #[test]
+    fn run_with_sandbox_host() {
+        let out = Compiler::new()
+            .source(HELLO)
+            .run_with_host(&[], Box::new(SandboxHost::new()))
+            .unwrap();
+        assert_eq!(out.stdout, "hello, jzero!\n");
+    }
+
     #[test]
     fn tac_contains_proc_main() {
         let tac = Compiler::new().source(HELLO).tac().unwrap();
@@ -235,6 +400,13 @@ mod tests {
         assert_eq!(&out.binary[0..8], b"Jzero!!\0");
     }
 
+    #[test]
+    fn instanceof_is_a_codegen_error() {
+        let src = "public class t {\n    public static void main(String argv[]) {\n        t x;\n        bool b;\n        b = x instanceof t;\n    }\n}\n";
+        let err = Compiler::new().level(jzero_lexer::Level::Extended).source(src).compile(0).unwrap_err();
+        assert_eq!(err.0, "line 5: instanceof cannot be compiled: an object does not know its class at run time");
+    }
+
     #[test]
     fn semantic_error_is_reported() {
         let src = r#"
@@ -251,6 +423,199 @@ mod tests {
         let _ = Compiler::new().source(src).tac();
     }
 
+    #[derive(Default)]
+    struct Recorder {
+        log: std::cell::RefCell<Vec<String>>,
+    }
+
+    impl CompilerEvents for Recorder {
+        fn on_token(&self, t: &jzero_lexer::SpannedToken) { self.log.borrow_mut().push(format!("token {:?}", t.token)); }
+        fn on_node_built(&self, n: &jzero_ast::tree::Tree) { self.log.borrow_mut().push(format!("node {}", n.sym)); }
+        fn on_scope_created(&self, s: &jzero_symtab::SymTab) { self.log.borrow_mut().push(format!("scope {}", s.scope)); }
+        fn on_diagnostic(&self, m: &str) { self.log.borrow_mut().push(format!("diag {}", m)); }
+        fn on_ir_emitted(&self, _: &jzero_codegen::Tac) { self.log.borrow_mut().push("ir".into()); }
+    }
+
+    #[test]
+    fn events_arrive_in_phase_order() {
+        let rec = Rc::new(Recorder::default());
+        Compiler::new().source(HELLO).events(rec.clone()).tac().unwrap();
+        let log = rec.log.borrow();
+        let first = |p: &str| log.iter().position(|e| e.starts_with(p)).unwrap();
+        let last  = |p: &str| log.iter().rposition(|e| e.starts_with(p)).unwrap();
+        assert!(last("token") < first("node"));
+        assert!(last("node")  < first("scope"));
+        assert!(last("scope") < first("ir"));
+        assert_eq!(log.iter().rev().find(|e| e.starts_with("node")).unwrap(), "node ClassDecl");
+        assert_eq!(log[first("scope")], "scope global");
+    }
+
+    #[test]
+    fn events_lex_tokens_at_the_compile_level() {
+        let src = HELLO.replace("System.out", "var = 1; System.out");
+        let has_var = |level| {
+            let rec = Rc::new(Recorder::default());
+            let _ = Compiler::new().level(level).source(&src).events(rec.clone()).tac();
+            rec.log.borrow().iter().any(|e| e == "token Var")
+        };
+        assert!(!has_var(jzero_lexer::Level::Jzero));
+        assert!(has_var(jzero_lexer::Level::Extended));
+    }
+
+    #[test]
+    fn events_report_parse_diagnostic() {
+        let rec = Rc::new(Recorder::default());
+        let result = Compiler::new().source("class {").events(rec.clone()).tac();
+        assert!(result.is_err());
+        let log = rec.log.borrow();
+        assert!(log.iter().any(|e| e.starts_with("diag")));
+        assert!(!log.iter().any(|e| e.starts_with("node")));
+    }
+
+    #[test]
+    fn cancelled_compile_fails() {
+        let token = CancellationToken::new();
+        let compiler = Compiler::new().source(HELLO).cancellation(token.clone());
+        assert!(compiler.tac().is_ok());
+        token.cancel();
+        assert_eq!(compiler.tac().unwrap_err().0, "analysis cancelled");
+    }
+
+    #[test]
+    fn while_loops_leave_by_their_condition_or_a_break() {
+        let src = r#"
+            public class loops {
+                public static void main(String argv[]) {
+                    int i;
+                    i = 0;
+                    while (true) {
+                        i = i + 1;
+                        if (i > 5) { break; }
+                    }
+                    System.out.println(String.valueOf(i));
+                    while (i < 20) i = i + 2;
+                    System.out.println(String.valueOf(i));
+                    while (i < 100) {
+                        while (true) { break; }
+                        i = i * 2;
+                    }
+                    System.out.println(String.valueOf(i));
+                }
+            }
+        "#;
+        let out = Compiler::new().source(src).run_with_host(&[], Box::new(SandboxHost::new())).unwrap().stdout;
+        assert_eq!(out, "6\n20\n160\n");
+    }
+
+    #[test]
+    fn array_initializers_and_element_stores_run() {
+        let src = r#"
+            public class arrays {
+                static int t[] = {1, 2};
+                public static void main(String argv[]) {
+                    int a[] = {1, 2, 3};
+                    int b[];
+                    b = new int[4];
+                    b[3] = a[2] * 10;
+                    a[0] += t[1];
+                    System.out.println(String.valueOf(a[0] + a[1] + b[3]));
+                    System.out.println(String.valueOf(a.length + b.length + t.length + argv.length));
+                    System.out.println(String.valueOf(b[0]));
+                }
+            }
+        "#;
+        let out = Compiler::new().source(src).run(&["x"]).unwrap().stdout;
+        assert_eq!(out, "35\n10\n0\n");
+    }
+
+    #[test]
+    fn out_of_bounds_element_access_traps() {
+        let src = |access: &str| format!(r#"
+            public class bounds {{
+                public static void main(String argv[]) {{
+                    int a[];
+                    int i;
+                    a = new int[3];
+                    i = 3;
+                    {}
+                    System.out.println("unreachable");
+                }}
+            }}
+        "#, access);
+        for access in ["a[i] = 1;", "i = a[i];", "a[i - 4] += 1;"] {
+            let err = Compiler::new().source(&src(access)).run(&[]).unwrap_err().to_string();
+            assert!(err.contains("out of bounds for length 3"), "{}: {}", access, err);
+        }
+        let out = Compiler::new().source(&src("a[i - 1] = 1;")).run(&[]).unwrap().stdout;
+        assert_eq!(out, "unreachable\n");
+    }
+
+    #[test]
+    fn random_and_time_repeat_with_the_same_seed_and_clock() {
+        let src = r#"
+            public class dice {
+                public static void main(String argv[]) {
+                    int t;
+                    int i;
+                    t = System.currentTimeMillis();
+                    System.out.println(String.valueOf(t));
+                    i = 0;
+                    while (i < 3) {
+                        System.out.println(String.valueOf(Math.randomInt() % 6 + 1));
+                        i = i + 1;
+                    }
+                }
+            }
+        "#;
+        let run = |seed| {
+            let host = SandboxHost::new().with_seed(seed).with_clock(1_000, 0);
+            Compiler::new().source(src).run_with_host(&[], Box::new(host)).unwrap().stdout
+        };
+        let out = run(7);
+        assert_eq!(out, run(7));
+        assert_ne!(out, run(8));
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines[0], "1000");
+        assert!(lines[1..].iter().all(|l| ["1", "2", "3", "4", "5", "6"].contains(l)), "{}", out);
+
+        let symtab = Compiler::new().source(src).book_compat(true).emit(&[emit::EmitKind::Symtab]).unwrap();
+        assert!(!String::from_utf8_lossy(&symtab[0].contents).contains("Math"), "the book has no Math");
+    }
+
+    #[test]
+    fn math_random_is_a_double_below_one() {
+        let src = r#"
+            public class doubles {
+                public static void main(String argv[]) {
+                    double r;
+                    int i;
+                    int die;
+                    i = 0;
+                    while (i < 100) {
+                        r = Math.random();
+                        die = (int) (r * 6) + 1;
+                        if (r < 0) { System.out.println("negative"); }
+                        if (r >= 1) { System.out.println("one or more"); }
+                        i = i + 1;
+                    }
+                    System.out.println(String.valueOf(die));
+                    System.out.println(7.0 / 2);
+                    System.out.println(String.valueOf(1 + 0.25 * 3));
+                    System.out.println((double) 3);
+                }
+            }
+        "#;
+        let run = |seed| {
+            let host = SandboxHost::new().with_seed(seed);
+            Compiler::new().source(src).run_with_host(&[], Box::new(host)).unwrap().stdout
+        };
+        let out = run(7);
+        assert_eq!(out, run(7));
+        let lines: Vec<&str> = out.lines().collect();
+        assert!(["1", "2", "3", "4", "5", "6"].contains(&lines[0]), "{}", out);
+        assert_eq!(lines[1..], ["3.5", "1.75", "3.0"]);
+    }
+
     #[test]
     fn parse_error_returns_err() {
         let result = Compiler::new().source("this is not valid jzero").run(&[]);
@@ -0,0 +1,294 @@
+//! Diagnostics for the files open in an editor, computed in the background.
+//!
+//! An editor reports every edit as it happens and wants the errors of each
+//! open file shortly after typing pauses.  [`LiveDiagnostics`] keeps the
+//! latest text of each open file and checks files on a pool of worker
+//! threads:
+//!
+//! - each file is checked once no edit to it has arrived for the
+//!   *debounce* interval, so a burst of keystrokes costs one check;
+//! - an edit to a file whose check is running cancels that check through
+//!   its [`CancellationToken`]; the file is checked again once the new
+//!   text has settled;
+//! - files are independent: an edit to one neither delays nor cancels
+//!   the check of another, and with more than one worker a slow file does
+//!   not hold up the rest.
+//!
+//! Each check runs on a [`Database`] of its own, so nothing is shared
+//! between threads but the text.  Results arrive as [`Published`]
+//! diagnostics, only for the latest version of a file still open.
+//!
+//! ```
+//! use std::time::Duration;
+//! use jzero::live::LiveDiagnostics;
+//!
+//! let live = LiveDiagnostics::new(2, Duration::from_millis(10), Default::default());
+//! live.update("bad.java", "public class bad { int x }");
+//! let published = live.recv_timeout(Duration::from_secs(10)).unwrap();
+//! assert_eq!((published.path.as_str(), published.version), ("bad.java", 1));
+//! assert_eq!(published.diagnostics.len(), 1);
+//! ```
+
+use std::collections::HashMap;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use jzero_text::Diagnostic;
+
+use crate::query::Database;
+use crate::{CancellationToken, Cancelled, SessionOptions};
+
+/// The diagnostics of one version of a file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Published {
+    pub path: String,
+    /// Counts the updates of the file since it was opened, from 1.
+    pub version: u64,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// An open file.
+struct Document {
+    text: Arc<str>,
+    version: u64,
+    /// When the latest edit will have settled.
+    due: Instant,
+    /// Whether the latest version still has to be checked.
+    pending: bool,
+    /// The number and token of the check running on this file, if one is.
+    running: Option<(u64, CancellationToken)>,
+}
+
+#[derive(Default)]
+struct State {
+    documents: HashMap<String, Document>,
+    /// Checks started so far; numbers each check, so that a check of a
+    /// file closed and opened again is not taken for the current one.
+    checks: u64,
+    shutdown: bool,
+}
+
+struct Shared {
+    state: Mutex<State>,
+    /// Signalled when a file is edited or closed, a check ends, or the
+    /// pool shuts down.
+    changed: Condvar,
+    debounce: Duration,
+    options: SessionOptions,
+}
+
+impl Shared {
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Open files, checked in the background as they change.
+pub struct LiveDiagnostics {
+    shared: Arc<Shared>,
+    published: Receiver<Published>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl LiveDiagnostics {
+    /// A pool of `workers` threads (at least one) checking each file
+    /// `debounce` after its last edit, with `options`.
+    pub fn new(workers: usize, debounce: Duration, options: SessionOptions) -> Self {
+        let shared = Arc::new(Shared {
+            state: Mutex::new(State::default()),
+            changed: Condvar::new(),
+            debounce,
+            options,
+        });
+        let (sender, published) = mpsc::channel();
+        let workers = (0..workers.max(1))
+            .map(|_| {
+                let shared = Arc::clone(&shared);
+                let sender = sender.clone();
+                std::thread::spawn(move || work(&shared, &sender))
+            })
+            .collect();
+        LiveDiagnostics { shared, published, workers }
+    }
+
+    /// Open `path` with `text`, or replace its text.  Returns the new
+    /// version, which the diagnostics of this text will carry.
+    pub fn update(&self, path: &str, text: &str) -> u64 {
+        let mut state = self.shared.lock();
+        let due = Instant::now() + self.shared.debounce;
+        let document = state.documents.entry(path.to_string()).or_insert_with(|| Document {
+            text: Arc::from(""),
+            version: 0,
+            due,
+            pending: false,
+            running: None,
+        });
+        document.text = Arc::from(text);
+        document.version += 1;
+        document.due = due;
+        document.pending = true;
+        if let Some((_, token)) = &document.running {
+            token.cancel();
+        }
+        let version = document.version;
+        drop(state);
+        self.shared.changed.notify_all();
+        version
+    }
+
+    /// Close `path`: its running check is cancelled and nothing more is
+    /// published for it.
+    pub fn close(&self, path: &str) {
+        let mut state = self.shared.lock();
+        if let Some((_, token)) = state.documents.remove(path).and_then(|d| d.running) {
+            token.cancel();
+        }
+        drop(state);
+        self.shared.changed.notify_all();
+    }
+
+    /// The next diagnostics published, waiting at most `timeout`.
+    pub fn recv_timeout(&self, timeout: Duration) -> Option<Published> {
+        self.published.recv_timeout(timeout).ok()
+    }
+
+    /// The diagnostics published so far and not yet received.
+    pub fn try_iter(&self) -> impl Iterator<Item = Published> + '_ {
+        self.published.try_iter()
+    }
+}
+
+impl Drop for LiveDiagnostics {
+    /// Cancel the running checks and wait for the workers to stop.
+    fn drop(&mut self) {
+        let mut state = self.shared.lock();
+        state.shutdown = true;
+        for token in state.documents.values().filter_map(|d| d.running.as_ref().map(|(_, t)| t)) {
+            token.cancel();
+        }
+        drop(state);
+        self.shared.changed.notify_all();
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+/// A worker: check whichever settled file has waited longest, until the
+/// pool shuts down.
+fn work(shared: &Shared, published: &Sender<Published>) {
+    let mut state = shared.lock();
+    loop {
+        if state.shutdown {
+            return;
+        }
+        let now = Instant::now();
+        let next = state.documents.iter()
+            .filter(|(_, d)| d.pending && d.running.is_none())
+            .min_by_key(|(_, d)| d.due)
+            .map(|(path, d)| (path.clone(), d.due));
+        let path = match next {
+            Some((path, due)) if due <= now => path,
+            Some((_, due)) => {
+                state = shared.changed.wait_timeout(state, due - now).unwrap_or_else(|p| p.into_inner()).0;
+                continue;
+            }
+            None => {
+                state = shared.changed.wait(state).unwrap_or_else(|p| p.into_inner());
+                continue;
+            }
+        };
+
+        let token = CancellationToken::new();
+        state.checks += 1;
+        let number = state.checks;
+        let document = state.documents.get_mut(&path).expect("a file found above");
+        document.pending = false;
+        document.running = Some((number, token.clone()));
+        let (text, version) = (Arc::clone(&document.text), document.version);
+        drop(state);
+
+        let checked = check(shared.options, &path, &text, &token);
+
+        state = shared.lock();
+        if let Some(document) = state.documents.get_mut(&path)
+            && document.running.as_ref().is_some_and(|(n, _)| *n == number)
+        {
+            document.running = None;
+            if let Ok(diagnostics) = checked
+                && document.version == version
+            {
+                let _ = published.send(Published { path, version, diagnostics });
+            }
+        }
+        // The file may have been edited meanwhile and be waiting for this
+        // check to end.
+        shared.changed.notify_all();
+    }
+}
+
+/// The diagnostics of `text`, checked as a file of its own.
+fn check(options: SessionOptions, path: &str, text: &str, cancel: &CancellationToken) -> Result<Vec<Diagnostic>, Cancelled> {
+    let mut db = Database::new(options.lex_options());
+    let id = db.add_file(path, text);
+    Ok(db.diagnostics(id, Some(cancel))?.to_vec())
+}
+
+// ─── Tests ───────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GOOD: &str = "public class good { public static void main(String argv[]) { } }";
+    const BAD: &str = "public class bad { public static void main(String argv[]) { int x } }";
+
+    fn wait(live: &LiveDiagnostics) -> Published {
+        live.recv_timeout(Duration::from_secs(20)).expect("diagnostics within the timeout")
+    }
+
+    #[test]
+    fn a_burst_of_edits_is_checked_once() {
+        let live = LiveDiagnostics::new(2, Duration::from_millis(100), SessionOptions::default());
+        live.update("a.java", "public class");
+        live.update("a.java", BAD);
+        assert_eq!(live.update("a.java", GOOD), 3);
+        let published = wait(&live);
+        assert_eq!(published, Published { path: "a.java".into(), version: 3, diagnostics: vec![] });
+        assert!(live.recv_timeout(Duration::from_millis(300)).is_none());
+
+        live.update("a.java", BAD);
+        let published = wait(&live);
+        assert_eq!(published.version, 4);
+        assert_eq!(published.diagnostics.len(), 1, "{:?}", published);
+    }
+
+    #[test]
+    fn typing_in_one_file_does_not_hold_up_another() {
+        let live = LiveDiagnostics::new(2, Duration::from_millis(100), SessionOptions::default());
+        live.update("quiet.java", BAD);
+        // Keep editing busy.java for well past the debounce interval.
+        let start = Instant::now();
+        while start.elapsed() < Duration::from_millis(500) {
+            live.update("busy.java", GOOD);
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        let first = wait(&live);
+        assert_eq!((first.path.as_str(), first.diagnostics.len()), ("quiet.java", 1));
+        let second = wait(&live);
+        assert_eq!(second.path, "busy.java");
+        assert!(second.version > 1);
+    }
+
+    #[test]
+    fn closed_files_are_not_published() {
+        let live = LiveDiagnostics::new(1, Duration::from_millis(50), SessionOptions::default());
+        live.update("gone.java", BAD);
+        live.close("gone.java");
+        live.update("kept.java", GOOD);
+        assert_eq!(wait(&live).path, "kept.java");
+        assert!(live.recv_timeout(Duration::from_millis(200)).is_none());
+    }
+}
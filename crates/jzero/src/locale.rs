@@ -0,0 +1,309 @@
+//! Localized diagnostics — message catalogs keyed by diagnostic code.
+//!
+//! Every phase reports in English.  A [`Diagnostic`] with a code also
+//! carries the values its message shows as named arguments, so that a
+//! [`Locale`] can rebuild the message from its own template for that code:
+//!
+//! ```text
+//! undeclared-variable   en: undeclared variable '{name}'
+//!                       es: variable no declarada '{name}'
+//! ```
+//!
+//! A code the locale has no template for, or a diagnostic without a code,
+//! is shown as reported.  The English catalog reproduces the messages the
+//! phases write, so rendering in [`Locale::English`] changes nothing.
+//!
+//! A project picks its locale in `jzero.toml`:
+//!
+//! ```toml
+//! [diagnostics]
+//! locale = "es"
+//! ```
+//!
+//! Adding a language is adding a catalog: a `&[(key, template)]` with a
+//! template for each code of [`ENGLISH`], plus the words `error`,
+//! `warning`, `note` and the `diagnostic` layout that
+//! [`Locale::render`] uses.
+
+use std::fmt;
+
+use jzero_text::{Diagnostic, Severity};
+
+use crate::config::{Config, Value};
+
+/// A language diagnostics can be shown in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Locale {
+    #[default]
+    English,
+    Spanish,
+}
+
+impl Locale {
+    pub const ALL: [Locale; 2] = [Locale::English, Locale::Spanish];
+
+    /// The language tag, e.g. `es`.
+    pub fn tag(self) -> &'static str {
+        match self {
+            Locale::English => "en",
+            Locale::Spanish => "es",
+        }
+    }
+
+    /// The locale for a language tag such as `es`, `es-MX` or the
+    /// `es_ES.UTF-8` of `LANG`; only the language is looked at.
+    pub fn from_tag(tag: &str) -> Option<Locale> {
+        let language = tag.split(['-', '_', '.']).next().unwrap_or_default();
+        Locale::ALL.into_iter().find(|l| l.tag().eq_ignore_ascii_case(language))
+    }
+
+    /// The locale set by `diagnostics.locale` in `config`, English if it
+    /// is missing or not a known tag.
+    pub fn from_config(config: &Config) -> Locale {
+        match config.get("diagnostics.locale") {
+            Some(Value::Str(tag)) => Locale::from_tag(tag).unwrap_or_default(),
+            _ => Locale::English,
+        }
+    }
+
+    fn catalog(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Locale::English => ENGLISH,
+            Locale::Spanish => SPANISH,
+        }
+    }
+
+    /// The template for `key`, a diagnostic code or one of the words
+    /// [`Locale::render`] uses.
+    pub fn template(self, key: &str) -> Option<&'static str> {
+        self.catalog().iter().find(|(k, _)| *k == key).map(|(_, t)| *t)
+    }
+
+    /// The message of `diagnostic` in this locale.
+    pub fn message(self, diagnostic: &Diagnostic) -> String {
+        match diagnostic.code.and_then(|c| self.template(c)) {
+            Some(template) => interpolate(template, &diagnostic.args),
+            None => diagnostic.message.clone(),
+        }
+    }
+
+    /// [`Diagnostic::render`] in this locale: `error: line 3: message
+    /// [code]` in English.  The code is never translated, so it can still
+    /// be looked up or passed to `--allow`.
+    pub fn render(self, diagnostic: &Diagnostic, source: &str) -> String {
+        let severity = match diagnostic.severity {
+            Severity::Error   => "error",
+            Severity::Warning => "warning",
+            Severity::Note    => "note",
+        };
+        let (line, _) = jzero_text::line_col(source, diagnostic.span.start);
+        let args = [
+            ("severity", self.template(severity).unwrap_or(severity).to_string()),
+            ("line", line.to_string()),
+            ("message", self.message(diagnostic)),
+        ];
+        let mut out = interpolate(self.template("diagnostic").unwrap_or("{severity}: line {line}: {message}"), &args);
+        if let Some(code) = diagnostic.code {
+            out.push_str(&format!(" [{}]", code));
+        }
+        out
+    }
+}
+
+impl fmt::Display for Locale {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.tag())
+    }
+}
+
+/// `template` with each `{name}` replaced by the value of argument
+/// `name`.  A placeholder without an argument is left as it is.
+pub fn interpolate(template: &str, args: &[(&str, String)]) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        out.push_str(&rest[..open]);
+        let after = &rest[open + 1..];
+        let value = after.find('}').and_then(|close| {
+            let name = &after[..close];
+            args.iter().find(|(n, _)| *n == name).map(|(_, v)| (v, close))
+        });
+        match value {
+            Some((v, close)) => {
+                out.push_str(v);
+                rest = &after[close + 1..];
+            }
+            None => {
+                out.push('{');
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+// ─── Catalogs ────────────────────────────────────────────────────────────────
+
+/// The messages as the phases write them.
+pub const ENGLISH: &[(&str, &str)] = &[
+    ("error", "error"),
+    ("warning", "warning"),
+    ("note", "note"),
+    ("diagnostic", "{severity}: line {line}: {message}"),
+    // lexical
+    ("unknown-char", "unrecognized character {text}"),
+    ("unterminated-string", "unterminated string literal"),
+    ("unterminated-comment", "unterminated block comment"),
+    ("bad-escape", "invalid escape sequence {text}"),
+    ("number-format", "malformed number {text}"),
+    ("cancelled", "analysis cancelled"),
+    ("too-many-tokens", "too many tokens"),
+    // syntax
+    ("invalid-token", "Invalid token at line {line} column {column}"),
+    ("unexpected-eof", "Unexpected end of file at line {line} column {column}. Expected one of: {expected}"),
+    ("reserved-word", "Reserved word '{word}' at line {line} column {column} is not supported in Jzero"),
+    ("level-keyword", "'{word}' at line {line} column {column} is a keyword of language level '{level}' and is not supported in Jzero"),
+    ("unexpected-token", "Unexpected token '{token}' at line {line} column {column}. Expected one of: {expected}"),
+    ("colon-for-semicolon", "Unexpected token '{token}' at line {line} column {column}. Expected one of: {expected}. ':' only ends a case label; did you mean ';'?"),
+    ("extra-token", "Extra token '{token}' at line {line} column {column}"),
+    // semantic
+    ("undeclared-variable", "undeclared variable '{name}'"),
+    ("undeclared-class", "undeclared class '{name}'"),
+    ("redeclared-variable", "redeclared variable '{name}'"),
+    ("predefined-conflict", "'{name}' conflicts with the predefined {kind} {name}"),
+    ("private-access", "'{member}' has private access in '{class}' (declared on line {decl_line})"),
+    ("static-call-via-instance", "static method '{method}' should be called as '{class}.{method}(...)', not through an object"),
+    ("instance-call-via-class", "instance method '{method}' of '{class}' needs an object, not the class name"),
+    ("native-call-mismatch", "native method '{method}' takes {expected}, not {found}"),
+    ("negative-array-size", "array size {size} is negative"),
+    ("duplicate-case-label", "duplicate {label} in switch (first on line {first_line})"),
+    ("non-constant-case-label", "case label must be a constant int"),
+    ("non-constant-field", "constant '{name}' must be initialized with a constant expression"),
+    ("not-an-interface", "'{name}' is not an interface"),
+    ("missing-interface-method", "class '{class}' does not implement '{method}' of interface '{interface}'"),
+    ("non-static-reference", "non-static {kind} '{name}' cannot be referenced from static method '{method}'"),
+    ("this-in-static-method", "'this' cannot be used in static method '{method}'"),
+    ("final-assignment", "cannot assign a value to final variable '{name}'"),
+    ("unknown-import", "cannot find '{name}' to import"),
+    ("type-assignment", "type assignment error: {msg}"),
+    ("malformed-tree", "internal error: malformed tree: {problem}"),
+];
+
+pub const SPANISH: &[(&str, &str)] = &[
+    ("error", "error"),
+    ("warning", "advertencia"),
+    ("note", "nota"),
+    ("diagnostic", "{severity}: línea {line}: {message}"),
+    // lexical
+    ("unknown-char", "carácter no reconocido {text}"),
+    ("unterminated-string", "literal de cadena sin cerrar"),
+    ("unterminated-comment", "comentario de bloque sin cerrar"),
+    ("bad-escape", "secuencia de escape no válida {text}"),
+    ("number-format", "número mal formado {text}"),
+    ("cancelled", "análisis cancelado"),
+    ("too-many-tokens", "demasiados tokens"),
+    // syntax
+    ("invalid-token", "Token no válido en la línea {line}, columna {column}"),
+    ("unexpected-eof", "Fin de archivo inesperado en la línea {line}, columna {column}. Se esperaba uno de: {expected}"),
+    ("reserved-word", "La palabra reservada '{word}' de la línea {line}, columna {column} no existe en Jzero"),
+    ("level-keyword", "'{word}' (línea {line}, columna {column}) es una palabra clave del nivel de lenguaje '{level}' y no existe en Jzero"),
+    ("unexpected-token", "Token inesperado '{token}' en la línea {line}, columna {column}. Se esperaba uno de: {expected}"),
+    ("colon-for-semicolon", "Token inesperado '{token}' en la línea {line}, columna {column}. Se esperaba uno de: {expected}. ':' solo cierra una etiqueta case; ¿quería escribir ';'?"),
+    ("extra-token", "Token de más '{token}' en la línea {line}, columna {column}"),
+    // semantic
+    ("undeclared-variable", "variable no declarada '{name}'"),
+    ("undeclared-class", "clase no declarada '{name}'"),
+    ("redeclared-variable", "variable '{name}' declarada dos veces"),
+    ("predefined-conflict", "'{name}' choca con el símbolo predefinido {name} ({kind})"),
+    ("private-access", "'{member}' es privado en '{class}' (declarado en la línea {decl_line})"),
+    ("static-call-via-instance", "el método estático '{method}' se llama como '{class}.{method}(...)', no a través de un objeto"),
+    ("instance-call-via-class", "el método de instancia '{method}' de '{class}' necesita un objeto, no el nombre de la clase"),
+    ("native-call-mismatch", "el método nativo '{method}' recibe {expected}, no {found}"),
+    ("negative-array-size", "el tamaño de array {size} es negativo"),
+    ("duplicate-case-label", "{label} repetido en el switch (el primero está en la línea {first_line})"),
+    ("non-constant-case-label", "la etiqueta case debe ser una constante int"),
+    ("non-constant-field", "la constante '{name}' debe inicializarse con una expresión constante"),
+    ("not-an-interface", "'{name}' no es una interfaz"),
+    ("missing-interface-method", "la clase '{class}' no implementa '{method}' de la interfaz '{interface}'"),
+    ("non-static-reference", "el método estático '{method}' no puede usar '{name}' ({kind} no estático)"),
+    ("this-in-static-method", "el método estático '{method}' no puede usar 'this'"),
+    ("final-assignment", "no se puede asignar un valor a la variable final '{name}'"),
+    ("unknown-import", "no se encuentra '{name}' para importar"),
+    ("type-assignment", "error de asignación de tipo: {msg}"),
+    ("malformed-tree", "error interno: árbol mal formado: {problem}"),
+];
+
+// ─── Tests ───────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::query::Database;
+    use jzero_lexer::LexOptions;
+
+    const MAIN: &str = "public class t {\n    public static void main(String argv[]) {\n        BODY\n    }\n}\n";
+
+    fn diagnostics(body: &str) -> (String, Vec<Diagnostic>) {
+        let src = MAIN.replace("BODY", body);
+        let found = match jzero_parser::parse_tree_diagnostic(&src, LexOptions::default(), None) {
+            Err(d) => vec![d],
+            Ok(_) => {
+                let mut db = Database::default();
+                let id = db.add_file("t.java", &src);
+                db.diagnostics(id, None).unwrap().to_vec()
+            }
+        };
+        (src, found)
+    }
+
+    #[test]
+    fn english_templates_reproduce_the_reported_messages() {
+        let bodies = ["int @;", "String s = \"abc;", "String s = \"\\q\";", "int x = 12ab;",
+            "int x; int x;", "int x; x = 1 : 2;", "int x = 1", "do { }", "/* open"];
+        for body in bodies {
+            let (src, found) = diagnostics(body);
+            assert!(!found.is_empty(), "{}", body);
+            for d in &found {
+                assert!(d.code.is_some(), "{:?}", d);
+                assert_eq!(Locale::English.message(d), d.message, "{}", body);
+                assert_eq!(Locale::English.render(d, &src), d.render(&src));
+            }
+        }
+    }
+
+    #[test]
+    fn spanish_fills_in_the_arguments() {
+        let (src, found) = diagnostics("int x; int x;");
+        assert_eq!(Locale::Spanish.render(&found[0], &src),
+            "error: línea 3: variable 'x' declarada dos veces [redeclared-variable]");
+        let (src, found) = diagnostics("int x; x = 1 : 2;");
+        assert!(Locale::Spanish.render(&found[0], &src)
+            .starts_with("error: línea 3: Token inesperado ':' en la línea 3, columna 22."), "{:?}", found[0]);
+    }
+
+    #[test]
+    fn every_language_translates_every_key() {
+        for locale in Locale::ALL {
+            for (key, english) in ENGLISH {
+                let template = locale.template(key).unwrap_or_else(|| panic!("{} lacks {}", locale, key));
+                let names = |t: &str| -> Vec<String> {
+                    t.split('{').skip(1).filter_map(|p| p.split_once('}')).map(|(n, _)| n.to_string()).collect()
+                };
+                for name in names(template) {
+                    assert!(names(english).contains(&name), "{} {} uses {{{}}}", locale, key, name);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn tags_and_interpolation() {
+        assert_eq!(Locale::from_tag("es_ES.UTF-8"), Some(Locale::Spanish));
+        assert_eq!(Locale::from_tag("EN-us"), Some(Locale::English));
+        assert_eq!(Locale::from_tag("fr"), None);
+        let config = Config::parse("[diagnostics]\nlocale = \"es\"\n").unwrap();
+        assert_eq!(Locale::from_config(&config), Locale::Spanish);
+        assert_eq!(interpolate("{a} and {b} {", &[("a", "1".into())]), "1 and {b} {");
+    }
+}
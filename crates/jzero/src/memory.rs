@@ -0,0 +1,172 @@
+//! Memory statistics for analysed programs.
+//!
+//! [`CompileSession::memory_report`](crate::CompileSession::memory_report)
+//! measures what a session keeps after analysis: its syntax trees by node
+//! kind, the bytes of the strings the trees and symbol tables own, the
+//! scopes and their entries, and how many `Rc` handles point at each scope.
+//! These are counts, not allocator figures — numbers to compare an
+//! interning or arena change against.
+//!
+//! ```
+//! use jzero::CompileSession;
+//!
+//! let mut session = CompileSession::default();
+//! let id = session.add_file("a.java", "public class a {
+//!     public static void main(String argv[]) { int x; x = 1; }
+//! }");
+//! session.check(id);
+//! let report = session.memory_report();
+//! assert_eq!(report.files, 1);
+//! assert_eq!(report.nodes["LocalVarDecl"], 1);
+//! ```
+
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+use std::rc::Rc;
+
+use jzero_ast::tree::Tree;
+use jzero_symtab::SymTab;
+
+/// What analysed trees and symbol tables hold.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MemoryReport {
+    /// Analysed files measured.
+    pub files: usize,
+    /// Tree nodes by kind; a leaf counts under its token category.
+    pub nodes: BTreeMap<String, usize>,
+    /// Bytes of the strings tree nodes own: kinds, token categories, token
+    /// texts and string values.
+    pub tree_string_bytes: usize,
+    /// Scopes, the global scope of each file included.
+    pub scopes: usize,
+    /// Symbol table entries, predefined ones included.
+    pub symbols: usize,
+    /// Bytes of scope and symbol names.
+    pub symbol_string_bytes: usize,
+    /// `Rc` handles to scopes, held by tree nodes, symbols, types and the
+    /// scopes nested in them.
+    pub scope_refs: usize,
+    /// The scope with the most handles, e.g. `method main`, and how many
+    /// it has.
+    pub busiest_scope: Option<(String, usize)>,
+    /// Strings in the session's interner.
+    pub interned: usize,
+    pub interned_bytes: usize,
+}
+
+impl MemoryReport {
+    /// Tree nodes of every kind.
+    pub fn node_count(&self) -> usize {
+        self.nodes.values().sum()
+    }
+
+    /// Add a file's analysed `tree` and the scopes under its `global` scope.
+    pub fn measure(&mut self, tree: &Tree, global: &Rc<RefCell<SymTab>>) {
+        self.files += 1;
+        self.measure_tree(tree);
+        self.measure_scope("global", global, &mut HashSet::new());
+    }
+
+    fn measure_tree(&mut self, tree: &Tree) {
+        *self.nodes.entry(tree.sym.clone()).or_default() += 1;
+        self.tree_string_bytes += tree.sym.len();
+        if let Some(tok) = &tree.tok {
+            self.tree_string_bytes += tok.category.len() + tok.text.len() + tok.sval.as_ref().map_or(0, String::len);
+        }
+        for kid in &tree.kids {
+            self.measure_tree(kid);
+        }
+    }
+
+    /// Count `scope`, the scope of the symbol `name`, and the scopes nested
+    /// in it, each once.  The scope is borrowed, never cloned, so its strong
+    /// count holds no handle of ours.
+    fn measure_scope(&mut self, name: &str, scope: &Rc<RefCell<SymTab>>, seen: &mut HashSet<*const RefCell<SymTab>>) {
+        if !seen.insert(Rc::as_ptr(scope)) { return; }
+        let refs = Rc::strong_count(scope);
+        let st = scope.borrow();
+        self.scopes += 1;
+        self.scope_refs += refs;
+        if self.busiest_scope.as_ref().is_none_or(|(_, most)| refs > *most) {
+            self.busiest_scope = Some((format!("{} {}", st.scope, name), refs));
+        }
+        self.symbol_string_bytes += st.scope.len();
+        for (key, entry) in st.iter() {
+            self.symbols += 1;
+            self.symbol_string_bytes += key.len() + entry.sym.len();
+            if let Some(inner) = &entry.st {
+                self.measure_scope(&entry.sym, inner, seen);
+            }
+        }
+    }
+}
+
+/// A few summary lines, then the node kinds, most numerous first.
+impl fmt::Display for MemoryReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "files:         {}", self.files)?;
+        writeln!(f, "tree nodes:    {} ({} string bytes)", self.node_count(), self.tree_string_bytes)?;
+        writeln!(f, "scopes:        {}", self.scopes)?;
+        writeln!(f, "symbols:       {} ({} string bytes)", self.symbols, self.symbol_string_bytes)?;
+        write!(f, "scope handles: {}", self.scope_refs)?;
+        if let Some((scope, refs)) = &self.busiest_scope {
+            write!(f, " (most: {} with {})", scope, refs)?;
+        }
+        writeln!(f)?;
+        writeln!(f, "interned:      {} ({} bytes)", self.interned, self.interned_bytes)?;
+        let mut kinds: Vec<(&String, &usize)> = self.nodes.iter().collect();
+        kinds.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+        for (kind, count) in kinds {
+            writeln!(f, "  {:<20} {}", kind, count)?;
+        }
+        Ok(())
+    }
+}
+
+// ─── Tests ───────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use crate::CompileSession;
+
+    const SRC: &str = "public class a {
+    int n;
+    public static int twice(int k) { return k + k; }
+    public static void main(String argv[]) {
+        int x;
+        x = twice(2);
+        System.out.println(\"x\");
+    }
+}
+";
+
+    #[test]
+    fn counts_what_the_analysis_holds() {
+        let mut session = CompileSession::default();
+        let id = session.add_file("a.java", SRC);
+        assert!(session.check(id));
+        let report = session.memory_report();
+        let tree = jzero_parser::parse_tree(SRC).unwrap();
+        assert_eq!(report.files, 1);
+        assert_eq!(report.node_count(), tree.node_count());
+        assert_eq!((report.nodes["MethodDecl"], report.nodes["IDENTIFIER"] > 10), (2, true));
+        assert!(report.tree_string_bytes > SRC.split_whitespace().count());
+
+        // The global, class and method scopes, and the predefined classes'.
+        assert!(report.scopes >= 4, "{}", report);
+        assert!(report.symbols >= 6, "{}", report);
+        assert!(report.scope_refs > report.scopes, "{}", report);
+        assert_eq!((report.interned, report.interned_bytes), (session.interner().len(), "antwicekmainStringargvxSystemoutprintln".len()));
+
+        let text = report.to_string();
+        assert!(text.starts_with("files:         1\n"), "{}", text);
+        assert!(text.contains("  IDENTIFIER"), "{}", text);
+    }
+
+    #[test]
+    fn an_empty_session_reports_nothing() {
+        let report = CompileSession::default().memory_report();
+        assert_eq!((report.files, report.node_count(), report.scopes, report.busiest_scope), (0, 0, 0, None));
+    }
+}
@@ -0,0 +1,147 @@
+//! Size statistics for methods, gathered from the syntax tree.
+//!
+//! A statement is anything that appears in a block: a declaration, an
+//! assignment, a call, a `return`, or a compound statement (`if`, `while`,
+//! `for`), whose bodies are counted as well.  Bare `{ }` blocks and empty
+//! statements do not count.
+//!
+//! Nesting depth is the number of bodies a statement sits in: statements
+//! directly in the method body are at depth 0, the body of an `if` there is
+//! at depth 1, and so on.  An `else if` continues its chain at the same
+//! depth rather than nesting inside the `else`.
+
+use jzero_ast::tree::{LeafToken, Tree};
+
+/// Statistics for one method or constructor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MethodMetrics {
+    pub name: String,
+    pub lineno: usize,
+    /// Number of statements in the body, at any depth.
+    pub statements: usize,
+    /// Deepest nesting of bodies.
+    pub max_depth: usize,
+    /// Line of the first statement at `max_depth`.
+    pub deepest_line: usize,
+}
+
+/// Metrics for every method and constructor in `tree`, in source order.
+pub fn method_metrics(tree: &Tree) -> Vec<MethodMetrics> {
+    let mut out = Vec::new();
+    collect(tree, &mut out);
+    out
+}
+
+fn collect(tree: &Tree, out: &mut Vec<MethodMetrics>) {
+    if matches!(tree.sym.as_str(), "MethodDecl" | "ConstructorDecl") {
+        let header = tree.kids.first();
+        let name_leaf = header.and_then(find_declarator_name);
+        let mut m = MethodMetrics {
+            name: name_leaf.map(|t| t.text.clone()).unwrap_or_default(),
+            lineno: name_leaf.map(|t| t.lineno).unwrap_or(0),
+            statements: 0,
+            max_depth: 0,
+            deepest_line: 0,
+        };
+        if let Some(body) = tree.kids.last() {
+            block(body, 0, &mut m);
+        }
+        out.push(m);
+        return;
+    }
+    for kid in &tree.kids {
+        collect(kid, out);
+    }
+}
+
+/// The name leaf under a method header or constructor declarator.
+fn find_declarator_name(tree: &Tree) -> Option<&LeafToken> {
+    if matches!(tree.sym.as_str(), "MethodDeclarator" | "ConstructorDeclarator") {
+        return tree.kids.first()?.tok.as_ref();
+    }
+    tree.kids.iter().find_map(find_declarator_name)
+}
+
+/// Count the statements of a block whose statements are at `depth`.
+fn block(tree: &Tree, depth: usize, m: &mut MethodMetrics) {
+    for stmt in &tree.kids {
+        statement(stmt, depth, m);
+    }
+}
+
+fn statement(tree: &Tree, depth: usize, m: &mut MethodMetrics) {
+    match tree.sym.as_str() {
+        "Block"     => return block(tree, depth, m),
+        "EmptyStmt" => return,
+        _ => {}
+    }
+
+    m.statements += 1;
+    if depth > m.max_depth || m.deepest_line == 0 {
+        m.max_depth = depth;
+        m.deepest_line = tree.first_lineno();
+    }
+    bodies(tree, depth, m);
+}
+
+/// The bodies of a compound statement at `depth`; each is a block or a
+/// single statement.
+fn bodies(tree: &Tree, depth: usize, m: &mut MethodMetrics) {
+    match tree.sym.as_str() {
+        "IfThenStmt" | "WhileStmt" => statement(&tree.kids[1], depth + 1, m),
+        "ForStmt" => statement(&tree.kids[3], depth + 1, m),
+        "IfThenElseStmt" => {
+            statement(&tree.kids[1], depth + 1, m);
+            let else_body = &tree.kids[2];
+            if matches!(else_body.sym.as_str(), "IfThenStmt" | "IfThenElseStmt") {
+                // `else if`: the same chain, not a new statement.
+                bodies(else_body, depth, m);
+            } else {
+                statement(else_body, depth + 1, m);
+            }
+        }
+        _ => {}
+    }
+}
+
+// ─── Tests ───────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metrics(src: &str) -> Vec<MethodMetrics> {
+        method_metrics(&jzero_parser::parse_tree(src).expect("parse failed"))
+    }
+
+    #[test]
+    fn counts_statements_at_every_depth() {
+        let m = metrics(r#"
+public class T {
+    public static void main(String argv[]) {
+        int i;
+        for (i = 0; i < 3; i += 1) {
+            if (i == 1) {
+                System.out.println("one");
+            } else if (i == 2) {
+                System.out.println("two");
+            } else {
+                { ; }
+            }
+        }
+    }
+}
+"#);
+        assert_eq!(m.len(), 1);
+        assert_eq!((m[0].name.as_str(), m[0].lineno), ("main", 3));
+        // int, for, if, println, println — the else-if is part of the chain.
+        assert_eq!(m[0].statements, 5);
+        assert_eq!((m[0].max_depth, m[0].deepest_line), (2, 7));
+    }
+
+    #[test]
+    fn empty_method() {
+        let m = metrics("public class T { public static void f() { } }");
+        assert_eq!((m[0].statements, m[0].max_depth), (0, 0));
+    }
+}
@@ -0,0 +1,398 @@
+//! Multi-file projects — compilation database and dependency graph.
+//!
+//! Each Jzero source file declares exactly one class.  A [`Project`] parses
+//! a set of files, records which class each declares and which class names
+//! it mentions, and links the two: file `A` depends on file `B` if `A`
+//! names the class declared in `B`.
+//!
+//! A class name counts as *mentioned* when it appears as a declared type
+//! (`Point p;`, parameters, return types, array element types), after
+//! `new`, or as the base of a dot (`Util.max(a, b)`), and a superclass or
+//! interface a class extends or implements.  Names there that are not
+//! classes of the project — variables, `System` — are ignored.
+//!
+//! Files are parsed at the highest language [`Level`], so that `extends`,
+//! `implements` and interfaces are understood whatever level the files
+//! are compiled at.
+//!
+//! Two artifacts are produced for tools outside the compiler:
+//!
+//! - [`Project::compile_commands`] — a `j0-compile-commands.json` database
+//!   in the style of `compile_commands.json`, one entry per file with the
+//!   command that compiles it and the files it depends on.
+//! - [`Project::to_dot`] — the file/class dependency graph in Graphviz DOT.
+//!
+//! [`Project::dependents`] answers "what needs recompiling if this file
+//! changes", and [`Project::duplicate_classes`] finds classes declared by
+//! more than one file.
+
+use std::collections::BTreeSet;
+use std::fmt;
+
+use jzero_ast::tree::Tree;
+use jzero_lexer::{Level, LexOptions};
+
+use crate::JzeroError;
+use crate::vfs::{FileProvider, RealFs};
+
+/// One source file of a project.
+#[derive(Debug, Clone)]
+pub struct Unit {
+    pub path: String,
+    /// The class or interface the file declares.
+    pub class: String,
+    /// Line of the class name in its declaration.
+    pub line: usize,
+    /// Names used where a class may appear, excluding its own.  Only the
+    /// ones declared by another unit become dependencies.
+    pub references: BTreeSet<String>,
+}
+
+/// A set of source files and the dependencies between them.
+#[derive(Debug, Clone, Default)]
+pub struct Project {
+    pub units: Vec<Unit>,
+}
+
+impl Project {
+    /// Build a project from `(path, source)` pairs.
+    ///
+    /// # Errors
+    /// Returns a [`JzeroError`] naming the first file that does not parse.
+    pub fn from_sources<'a>(
+        sources: impl IntoIterator<Item = (&'a str, &'a str)>,
+    ) -> Result<Self, JzeroError> {
+        let mut units = Vec::new();
+        for (path, source) in sources {
+            let tree = jzero_parser::parse_tree_with(source, LexOptions::at_level(Level::MAX))
+                .map_err(|e| JzeroError(format!("{}: {}", path, e)))?;
+            let (class, line) = declared_type(&tree)
+                .and_then(|k| k.tok.as_ref())
+                .map(|t| (t.text.clone(), t.lineno))
+                .unwrap_or_default();
+            let mut references = BTreeSet::new();
+            collect_references(&tree, &mut references);
+            references.remove(&class);
+            units.push(Unit { path: path.to_string(), class, line, references });
+        }
+        Ok(Project { units })
+    }
+
+    /// Read and parse every file in `paths`.
+    ///
+    /// # Errors
+    /// Returns a [`JzeroError`] if a file cannot be read or does not parse.
+    pub fn load(paths: &[String]) -> Result<Self, JzeroError> {
+        Project::load_from(&RealFs, paths)
+    }
+
+    /// Read every file in `paths` from `files` and parse it.
+    ///
+    /// # Errors
+    /// Returns a [`JzeroError`] if a file cannot be read or does not parse.
+    pub fn load_from(files: &dyn FileProvider, paths: &[String]) -> Result<Self, JzeroError> {
+        let mut sources = Vec::new();
+        for path in paths {
+            sources.push((path.as_str(), files.read(path)?));
+        }
+        Project::from_sources(sources.iter().map(|(p, s)| (*p, s.as_str())))
+    }
+
+    /// Read and parse every `.java` file below `dir` of `files`, e.g. all
+    /// the sources of a submitted [`ZipFs`](crate::ZipFs) with `dir` `""`.
+    ///
+    /// # Errors
+    /// Returns a [`JzeroError`] if `dir` cannot be listed or a file cannot
+    /// be read or does not parse.
+    pub fn load_dir(files: &dyn FileProvider, dir: &str) -> Result<Self, JzeroError> {
+        let paths: Vec<String> = files.files(dir)?.into_iter().filter(|p| p.ends_with(".java")).collect();
+        Project::load_from(files, &paths)
+    }
+
+    /// Index of the unit declaring `class` — the first one, if
+    /// [`duplicate_classes`](Project::duplicate_classes) is not empty.
+    pub fn owner(&self, class: &str) -> Option<usize> {
+        self.units.iter().position(|u| u.class == class)
+    }
+
+    /// Indices of the units that unit `i` depends on directly.
+    pub fn dependencies(&self, i: usize) -> Vec<usize> {
+        let deps: BTreeSet<usize> = self.units[i].references.iter()
+            .filter_map(|c| self.owner(c))
+            .filter(|&j| j != i)
+            .collect();
+        deps.into_iter().collect()
+    }
+
+    /// Paths of every file that must be recompiled when `path` changes:
+    /// the file itself and everything that depends on it, directly or not.
+    pub fn dependents(&self, path: &str) -> Vec<&str> {
+        let Some(start) = self.units.iter().position(|u| u.path == path) else {
+            return vec![];
+        };
+        let mut dirty = vec![false; self.units.len()];
+        dirty[start] = true;
+        let mut work = vec![start];
+        while let Some(changed) = work.pop() {
+            for (i, d) in dirty.iter_mut().enumerate() {
+                if !*d && self.dependencies(i).contains(&changed) {
+                    *d = true;
+                    work.push(i);
+                }
+            }
+        }
+        self.units.iter().zip(dirty)
+            .filter(|(_, d)| *d)
+            .map(|(u, _)| u.path.as_str())
+            .collect()
+    }
+
+    /// Every class declared by more than one file, in order of first
+    /// declaration.
+    pub fn duplicate_classes(&self) -> Vec<DuplicateClass> {
+        let mut dups: Vec<DuplicateClass> = Vec::new();
+        for (i, u) in self.units.iter().enumerate() {
+            if self.owner(&u.class) != Some(i) {
+                continue;
+            }
+            let sites: Vec<(String, usize)> = self.units.iter()
+                .filter(|v| v.class == u.class)
+                .map(|v| (v.path.clone(), v.line))
+                .collect();
+            if sites.len() > 1 {
+                dups.push(DuplicateClass { class: u.class.clone(), sites });
+            }
+        }
+        dups
+    }
+
+    /// The `j0-compile-commands.json` database, with paths relative to
+    /// `directory`.
+    pub fn compile_commands(&self, directory: &str) -> String {
+        let mut out = String::from("[\n");
+        for (i, u) in self.units.iter().enumerate() {
+            let deps: Vec<String> = self.dependencies(i).iter()
+                .map(|&j| json_str(&self.units[j].path))
+                .collect();
+            out.push_str("  {\n");
+            out.push_str(&format!("    \"directory\": {},\n", json_str(directory)));
+            out.push_str(&format!("    \"file\": {},\n", json_str(&u.path)));
+            out.push_str(&format!("    \"class\": {},\n", json_str(&u.class)));
+            out.push_str(&format!(
+                "    \"arguments\": [\"j0\", {}, \"--bytecode\"],\n", json_str(&u.path)
+            ));
+            out.push_str(&format!("    \"output\": {},\n", json_str(&output_path(&u.path))));
+            out.push_str(&format!("    \"depends\": [{}]\n", deps.join(", ")));
+            out.push_str(if i + 1 < self.units.len() { "  },\n" } else { "  }\n" });
+        }
+        out.push_str("]\n");
+        out
+    }
+
+    /// The dependency graph in Graphviz DOT.  Nodes are files labelled with
+    /// their class; an edge `A -> B` labelled `C` means `A` uses class `C`
+    /// from `B`.
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph depgraph {\n    node [shape=box];\n");
+        for (i, u) in self.units.iter().enumerate() {
+            out.push_str(&format!(
+                "    f{} [label=\"{}\\n{}\"];\n", i, dot_escape(&u.path), dot_escape(&u.class)
+            ));
+        }
+        for i in 0..self.units.len() {
+            for j in self.dependencies(i) {
+                out.push_str(&format!(
+                    "    f{} -> f{} [label=\"{}\"];\n", i, j, dot_escape(&self.units[j].class)
+                ));
+            }
+        }
+        out.push_str("}\n");
+        out
+    }
+}
+
+/// A class declared by more than one source file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateClass {
+    pub class: String,
+    /// `(path, line)` of every declaration, in project order.
+    pub sites: Vec<(String, usize)>,
+}
+
+impl fmt::Display for DuplicateClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "class '{}' is declared in {} files:", self.class, self.sites.len())?;
+        for (path, line) in &self.sites {
+            write!(f, "\n  {}:{}", path, line)?;
+        }
+        Ok(())
+    }
+}
+
+/// The name of the class or interface `tree` declares, after any imports.
+pub(crate) fn declared_type(tree: &Tree) -> Option<&Tree> {
+    match tree.sym.as_str() {
+        "ClassDecl" | "InterfaceDecl" => tree.kids.first(),
+        _ => tree.kids.iter().find_map(declared_type),
+    }
+}
+
+/// Collect identifiers in positions where a class name can appear.
+pub(crate) fn collect_references(tree: &Tree, out: &mut BTreeSet<String>) {
+    let class_kid = match tree.sym.as_str() {
+        "FieldDecl" | "LocalVarDecl" | "FormalParm" | "MethodHeader" | "ArrayType"
+        | "ArrayCreation" | "InstanceCreation" | "FieldAccess" => tree.kids.first(),
+        // `base.m(args)`; rules 0 and 1 start with the method name instead.
+        "MethodCall" if tree.rule >= 2 => tree.kids.first(),
+        // `class Sub extends Base`.
+        "ClassDecl" if matches!(tree.rule, 1 | 3) => tree.kids.get(1),
+        _ => None,
+    };
+    if let Some(tok) = class_kid.and_then(|k| k.tok.as_ref())
+        && tok.category == "IDENTIFIER"
+    {
+        out.insert(tok.text.clone());
+    }
+    // `implements Shape, Named`: each is a leaf of its own.
+    if tree.sym == "InterfaceList" {
+        out.extend(tree.kids.iter().filter_map(|k| k.tok.as_ref()).map(|t| t.text.clone()));
+    }
+    for kid in &tree.kids {
+        collect_references(kid, out);
+    }
+}
+
+/// `src/Point.java` → `src/Point.j0`, as `j0 --bytecode` writes it.
+fn output_path(path: &str) -> String {
+    format!("{}.j0", path.strip_suffix(".java").unwrap_or(path))
+}
+
+pub(crate) fn json_str(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"'  => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn dot_escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+// ─── Tests ───────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAIN: &str = r#"public class Main {
+    public static void main(String argv[]) {
+        Point p;
+        p = new Point();
+        System.out.println(Util.twice(2));
+    }
+}"#;
+    const POINT: &str = "public class Point { int x; }";
+    const UTIL: &str = r#"public class Util {
+    public static int twice(int n) { return n + n; }
+}"#;
+
+    fn project() -> Project {
+        Project::from_sources([("Main.java", MAIN), ("Point.java", POINT), ("Util.java", UTIL)])
+            .unwrap()
+    }
+
+    #[test]
+    fn references_are_resolved_to_files() {
+        let p = project();
+        assert_eq!(p.units[0].class, "Main");
+        assert!(p.units[0].references.contains("Point"));
+        assert!(p.units[0].references.contains("Util"));
+        assert!(!p.units[0].references.contains("p"), "variables are not classes");
+        assert_eq!(p.dependencies(0), vec![1, 2]);
+        assert!(p.dependencies(1).is_empty());
+    }
+
+    #[test]
+    fn dependents_are_transitive() {
+        let p = project();
+        assert_eq!(p.dependents("Point.java"), vec!["Main.java", "Point.java"]);
+        assert_eq!(p.dependents("Main.java"), vec!["Main.java"]);
+        assert!(p.dependents("Missing.java").is_empty());
+    }
+
+    #[test]
+    fn compile_commands_list_every_file() {
+        let json = project().compile_commands("/work");
+        assert_eq!(json.matches("\"file\"").count(), 3);
+        assert!(json.contains("\"output\": \"Point.j0\""));
+        assert!(json.contains("\"depends\": [\"Point.java\", \"Util.java\"]"));
+        assert!(json.contains("\"directory\": \"/work\""));
+    }
+
+    #[test]
+    fn dot_has_an_edge_per_dependency() {
+        let dot = project().to_dot();
+        assert!(dot.starts_with("digraph depgraph {"));
+        assert!(dot.contains("f0 -> f1 [label=\"Point\"]"));
+        assert!(dot.contains("f0 -> f2 [label=\"Util\"]"));
+        assert_eq!(dot.matches("->").count(), 2);
+    }
+
+    #[test]
+    fn duplicate_class_lists_every_file() {
+        let other = "\n\npublic class Point { int y; }";
+        let p = Project::from_sources([
+            ("Main.java", MAIN), ("a/Point.java", POINT), ("b/Point.java", other),
+        ]).unwrap();
+        let dups = p.duplicate_classes();
+        assert_eq!(dups.len(), 1);
+        assert_eq!(
+            dups[0].to_string(),
+            "class 'Point' is declared in 2 files:\n  a/Point.java:1\n  b/Point.java:3"
+        );
+        assert!(project().duplicate_classes().is_empty());
+    }
+
+    #[test]
+    fn subclasses_depend_on_their_superclass() {
+        let base = "public class Base { int n; }";
+        let sub = "import java.util.Scanner;\npublic class Sub extends Base implements Named { }";
+        let named = "public interface Named { String name(); }";
+        let p = Project::from_sources([("Base.java", base), ("Sub.java", sub), ("Named.java", named)]).unwrap();
+        assert_eq!((p.units[1].class.as_str(), p.units[1].line), ("Sub", 2));
+        assert_eq!(p.units[2].class, "Named");
+        assert_eq!(p.dependencies(1), [0, 2]);
+        assert_eq!(p.dependents("Base.java"), ["Base.java", "Sub.java"]);
+    }
+
+    #[test]
+    fn parse_error_names_the_file() {
+        let err = Project::from_sources([("Bad.java", "public class {")]).unwrap_err();
+        assert!(err.0.starts_with("Bad.java:"));
+    }
+
+    #[test]
+    fn load_reads_through_a_file_provider() {
+        let mut files = crate::MemoryFs::new();
+        files.insert("Main.java", MAIN);
+        files.insert("Point.java", POINT);
+        let paths = ["Main.java".to_string(), "Point.java".to_string()];
+        let p = Project::load_from(&files, &paths).unwrap();
+        assert_eq!(p.dependencies(0), [1]);
+
+        let err = Project::load_from(&files, &["Util.java".to_string()]).unwrap_err();
+        assert!(err.0.contains("'Util.java'"));
+
+        files.insert("notes.txt", "not java");
+        let p = Project::load_dir(&files, "").unwrap();
+        assert_eq!(p.units.iter().map(|u| u.path.as_str()).collect::<Vec<_>>(), ["Main.java", "Point.java"]);
+    }
+}
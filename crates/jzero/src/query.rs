@@ -0,0 +1,357 @@
+//! Memoized front-end queries.
+//!
+//! The front end is a chain of queries over each file of a [`SourceMap`]:
+//!
+//! ```text
+//! source text → tokens → tree → analysis → diagnostics
+//! ```
+//!
+//! A [`Database`] remembers each result together with the file revision
+//! it was checked against.  Asking again at the same revision returns the
+//! stored result; after an edit a query first brings its input up to date
+//! and reruns only if that input actually changed.  An edit that leaves
+//! the tokens as they were — a comment, or spaces inside a line — therefore
+//! relexes the file but does not reparse or reanalyse it.
+//!
+//! Results are shared as [`Rc`]s, so every tool built on a database — the
+//! [`CompileSession`](crate::CompileSession), an editor, a watcher — sees
+//! the same values and none keeps a cache of its own.
+
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use jzero_ast::tree::Tree;
+use jzero_ast::{CancellationToken, Cancelled};
+use jzero_lexer::{LexOptions, Lexed};
+use jzero_symtab::MethodType;
+use jzero_text::{Diagnostic, Span, line_start};
+
+use crate::SemanticResult;
+use crate::session::{FileId, SourceMap};
+
+/// One query of the chain, for [`Database::executions`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Query {
+    Tokens,
+    Tree,
+    Analysis,
+    Diagnostics,
+}
+
+/// The result of the tokens query.
+pub type Tokens = Lexed;
+
+/// An analysed tree and what semantic analysis found in it.
+pub struct Analysis {
+    pub tree: Tree,
+    pub sem: SemanticResult,
+}
+
+/// A stored result.
+struct Memo<T> {
+    /// The file revision the value is known to be current for.
+    verified_at: u32,
+    /// The revision at which the value last changed.
+    changed_at: u32,
+    value: Rc<T>,
+}
+
+impl<T> Memo<T> {
+    fn new(revision: u32, value: T) -> Self {
+        Memo { verified_at: revision, changed_at: revision, value: Rc::new(value) }
+    }
+}
+
+/// Source files and the memoized results of the queries over them.
+#[derive(Default)]
+pub struct Database {
+    options: LexOptions,
+    sources: SourceMap,
+    tokens: HashMap<FileId, Memo<Tokens>>,
+    trees: HashMap<FileId, Memo<Result<Tree, Diagnostic>>>,
+    analyses: HashMap<FileId, Memo<Option<Analysis>>>,
+    diagnostics: HashMap<FileId, Memo<Vec<Diagnostic>>>,
+    executions: HashMap<Query, usize>,
+    /// Native methods every file can call, by qualified name.
+    natives: Vec<(String, MethodType)>,
+}
+
+impl Database {
+    pub fn new(options: LexOptions) -> Self {
+        Database { options, ..Database::default() }
+    }
+
+    pub fn sources(&self) -> &SourceMap {
+        &self.sources
+    }
+
+    /// Add a file, or update the text of the file already at `path`.
+    pub fn add_file(&mut self, path: &str, text: &str) -> FileId {
+        self.sources.add(path, text)
+    }
+
+    /// Replace the text of `id`.  Returns whether it changed.
+    pub fn set_text(&mut self, id: FileId, text: &str) -> bool {
+        self.sources.set_text(id, text)
+    }
+
+    /// Declare the native methods every file can call.  Each file's
+    /// analysis and diagnostics are recomputed on next request.
+    pub fn set_natives(&mut self, natives: Vec<(String, MethodType)>) {
+        self.natives = natives;
+        self.analyses.clear();
+        self.diagnostics.clear();
+    }
+
+    /// How many times `query` has actually run, as opposed to returning a
+    /// stored result.
+    pub fn executions(&self, query: Query) -> usize {
+        self.executions.get(&query).copied().unwrap_or(0)
+    }
+
+    fn executed(&mut self, query: Query) {
+        *self.executions.entry(query).or_insert(0) += 1;
+    }
+
+    fn revision(&self, id: FileId) -> u32 {
+        self.sources.get(id).revision
+    }
+
+    /// The tokens of `id`.
+    pub fn tokens(&mut self, id: FileId) -> Rc<Tokens> {
+        let revision = self.revision(id);
+        if let Some(m) = self.tokens.get(&id)
+            && m.verified_at == revision
+        {
+            return Rc::clone(&m.value);
+        }
+        self.executed(Query::Tokens);
+        let tokens = jzero_lexer::lex_with(&self.sources.get(id).text, &self.options);
+        match self.tokens.get_mut(&id) {
+            // Same tokens as before, if perhaps at other byte offsets: the
+            // tree records only lines, so later queries need not rerun.
+            Some(m) if same_tokens(&m.value, &tokens) => {
+                m.verified_at = revision;
+                m.value = Rc::new(tokens);
+            }
+            _ => { self.tokens.insert(id, Memo::new(revision, tokens)); }
+        }
+        Rc::clone(&self.tokens[&id].value)
+    }
+
+    /// The syntax tree of `id`, or the syntax error that prevents one.
+    ///
+    /// # Errors
+    /// Returns [`Cancelled`] if `cancel` is cancelled while parsing; nothing
+    /// is stored then.
+    pub fn tree(
+        &mut self,
+        id: FileId,
+        cancel: Option<&CancellationToken>,
+    ) -> Result<Rc<Result<Tree, Diagnostic>>, Cancelled> {
+        let revision = self.revision(id);
+        if let Some(m) = self.trees.get(&id)
+            && m.verified_at == revision
+        {
+            return Ok(Rc::clone(&m.value));
+        }
+        self.tokens(id);
+        let tokens_changed = self.tokens[&id].changed_at;
+        // A syntax error's span is a byte offset, which edits that keep the
+        // tokens can still move, so only a tree is reused.
+        if let Some(m) = self.trees.get_mut(&id)
+            && m.value.is_ok()
+            && tokens_changed <= m.verified_at
+        {
+            m.verified_at = revision;
+            return Ok(Rc::clone(&m.value));
+        }
+
+        self.executed(Query::Tree);
+        let parsed = jzero_parser::parse_tree_diagnostic(&self.sources.get(id).text, self.options, cancel);
+        if let Some(token) = cancel { token.check()?; }
+        let memo = Memo::new(revision, parsed);
+        let value = Rc::clone(&memo.value);
+        self.trees.insert(id, memo);
+        Ok(value)
+    }
+
+    /// The analysed tree of `id`; `None` if it does not parse.
+    ///
+    /// # Errors
+    /// Returns [`Cancelled`] if `cancel` is cancelled before analysis
+    /// finishes; nothing is stored then.
+    pub fn analysis(
+        &mut self,
+        id: FileId,
+        cancel: Option<&CancellationToken>,
+    ) -> Result<Rc<Option<Analysis>>, Cancelled> {
+        let revision = self.revision(id);
+        if let Some(m) = self.analyses.get(&id)
+            && m.verified_at == revision
+        {
+            return Ok(Rc::clone(&m.value));
+        }
+        let tree = self.tree(id, cancel)?;
+        let tree_changed = self.trees[&id].changed_at;
+        if let Some(m) = self.analyses.get_mut(&id)
+            && tree_changed <= m.verified_at
+        {
+            m.verified_at = revision;
+            return Ok(Rc::clone(&m.value));
+        }
+
+        self.executed(Query::Analysis);
+        let analysis = match &*tree {
+            Ok(tree) => {
+                let mut tree = tree.clone();
+                let sem = jzero_semantic::analyze_file_with_natives(&mut tree, id, &self.natives, cancel)?;
+                Some(Analysis { tree, sem })
+            }
+            Err(_) => None,
+        };
+        let memo = Memo::new(revision, analysis);
+        let value = Rc::clone(&memo.value);
+        self.analyses.insert(id, memo);
+        Ok(value)
+    }
+
+    /// The analyses stored now, by file.  A file's analysis may predate
+    /// its latest edit.
+    pub fn stored_analyses(&self) -> Vec<(FileId, &Analysis)> {
+        let mut stored: Vec<(FileId, &Analysis)> = self.analyses.iter()
+            .filter_map(|(&id, m)| Some((id, m.value.as_ref().as_ref()?)))
+            .collect();
+        stored.sort_by_key(|&(id, _)| id);
+        stored
+    }
+
+    /// The syntax or semantic errors of `id`.  Their spans are byte
+    /// offsets, so they are recomputed after every edit, even one that
+    /// keeps the analysis.
+    ///
+    /// # Errors
+    /// Returns [`Cancelled`] if `cancel` is cancelled before analysis
+    /// finishes; nothing is stored then.
+    pub fn diagnostics(
+        &mut self,
+        id: FileId,
+        cancel: Option<&CancellationToken>,
+    ) -> Result<Rc<Vec<Diagnostic>>, Cancelled> {
+        let revision = self.revision(id);
+        if let Some(m) = self.diagnostics.get(&id)
+            && m.verified_at == revision
+        {
+            return Ok(Rc::clone(&m.value));
+        }
+        let analysis = self.analysis(id, cancel)?;
+
+        self.executed(Query::Diagnostics);
+        let text = &self.sources.get(id).text;
+        let found = match &*analysis {
+            Some(a) => a.sem.errors.iter()
+                .map(|err| {
+                    let shown = err.to_string();
+                    // Drop the `line N: ` prefix; the span carries the line.
+                    let message = shown.split_once(": ").map_or(shown.as_str(), |(_, m)| m);
+                    let mut d = Diagnostic::error(Span::at(line_start(text, err.lineno())), message)
+                        .with_code(err.code());
+                    d.args = err.args();
+                    d
+                })
+                .collect(),
+            None => match &*self.tree(id, cancel)? {
+                Err(diagnostic) => vec![diagnostic.clone()],
+                Ok(_) => vec![],
+            },
+        };
+        let memo = Memo::new(revision, found);
+        let value = Rc::clone(&memo.value);
+        self.diagnostics.insert(id, memo);
+        Ok(value)
+    }
+}
+
+/// Whether `a` and `b` are the same tokens on the same lines, wherever in
+/// their lines they are.
+fn same_tokens(a: &Tokens, b: &Tokens) -> bool {
+    a.errors.len() == b.errors.len()
+        && a.errors.iter().zip(&b.errors).all(|(x, y)| (x.kind, &x.text, x.line) == (y.kind, &y.text, y.line))
+        && a.tokens.len() == b.tokens.len()
+        && a.tokens.iter().zip(&b.tokens).all(|(x, y)| (&x.token, &x.text, x.line) == (&y.token, &y.text, y.line))
+}
+
+// ─── Tests ───────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SRC: &str = "public class t {\n    public static void main(String argv[]) {\n        int x;\n    }\n}\n";
+
+    fn runs(db: &Database) -> [usize; 4] {
+        [Query::Tokens, Query::Tree, Query::Analysis, Query::Diagnostics].map(|q| db.executions(q))
+    }
+
+    #[test]
+    fn results_are_reused_until_the_text_changes() {
+        let mut db = Database::default();
+        let id = db.add_file("t.java", SRC);
+        assert!(db.diagnostics(id, None).unwrap().is_empty());
+        assert_eq!(runs(&db), [1, 1, 1, 1]);
+
+        let first = db.analysis(id, None).unwrap();
+        assert!(Rc::ptr_eq(&first, &db.analysis(id, None).unwrap()));
+        assert_eq!(runs(&db), [1, 1, 1, 1]);
+
+        db.set_text(id, &SRC.replace("int x;", "int x; int x;"));
+        assert_eq!(db.diagnostics(id, None).unwrap().len(), 1);
+        assert_eq!(runs(&db), [2, 2, 2, 2]);
+    }
+
+    #[test]
+    fn edits_that_keep_the_tokens_stop_at_the_lexer() {
+        let mut db = Database::default();
+        let id = db.add_file("t.java", SRC);
+        let before = db.analysis(id, None).unwrap();
+
+        db.set_text(id, &SRC.replace("int x;", "int   x; // x"));
+        let after = db.analysis(id, None).unwrap();
+        assert!(Rc::ptr_eq(&before, &after));
+        assert_eq!(runs(&db)[..3], [2, 1, 1]);
+    }
+
+    #[test]
+    fn diagnostics_move_with_the_lines_they_are_on() {
+        let mut db = Database::default();
+        let src = SRC.replace("int x;", "int x; int x;");
+        let id = db.add_file("t.java", &src);
+        let before = db.diagnostics(id, None).unwrap()[0].span;
+
+        db.set_text(id, &src.replace("public class t {", "public   class t { // t"));
+        let after = db.diagnostics(id, None).unwrap()[0].span;
+        assert_eq!(runs(&db)[..3], [2, 1, 1]);
+        assert_eq!(after.start, before.start + 7);
+    }
+
+    #[test]
+    fn syntax_errors_are_diagnostics() {
+        let mut db = Database::default();
+        let id = db.add_file("t.java", "public class {");
+        assert!(db.analysis(id, None).unwrap().is_none());
+        let found = db.diagnostics(id, None).unwrap();
+        assert_eq!(found.len(), 1);
+        assert!(found[0].message.starts_with("Unexpected token '{'"), "{:?}", found);
+    }
+
+    #[test]
+    fn cancelled_queries_store_nothing() {
+        let mut db = Database::default();
+        let id = db.add_file("t.java", SRC);
+        let token = CancellationToken::new();
+        token.cancel();
+        assert!(db.analysis(id, Some(&token)).is_err());
+        assert!(db.analysis(id, None).unwrap().is_some());
+        assert_eq!(runs(&db)[1..3], [2, 1]);
+    }
+}
@@ -0,0 +1,470 @@
+//! Folding and selection ranges for an editor, from the syntax tree.
+//!
+//! - [`folding_ranges`] lists the lines an editor can fold: class bodies,
+//!   method bodies, blocks, and comments spanning several lines;
+//! - [`selection_ranges`] lists the nodes around a position, innermost
+//!   first, for "expand selection": expression, statement, block, method,
+//!   class.
+//!
+//! The tree keeps the line of each leaf but no offsets, so the span of a
+//! node is worked out from the tokens of the source it was parsed from:
+//! each leaf is lined up with its token, a block, class or switch takes its
+//! braces, and a statement or member in one takes the keywords before it
+//! and the `;` after it.
+//!
+//! ```
+//! let src = "public class a {\n    public static void main(String argv[]) {\n        f(1 + 2);\n    }\n}\n";
+//! let tree = jzero_parser::parse_tree(src).unwrap();
+//! let folds = jzero::ranges::folding_ranges(src, &tree);
+//! assert_eq!(folds.iter().map(|f| (f.start_line, f.end_line)).collect::<Vec<_>>(), [(1, 4), (2, 3)]);
+//! let spans = jzero::ranges::selection_ranges(src, &tree, src.find('+').unwrap());
+//! assert_eq!(&src[spans[1].start..spans[1].end], "1 + 2");
+//! assert_eq!(&src[spans[2].start..spans[2].end], "f(1 + 2);");
+//! ```
+
+use std::collections::HashMap;
+
+use jzero_ast::tree::{LeafToken, Tree};
+use jzero_lexer::token::Token;
+use jzero_lexer::{SpannedToken, Trivia, lex, lex_lossless};
+use jzero_text::{Span, line_col};
+
+/// What a [`FoldingRange`] folds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FoldingKind {
+    Class,
+    Method,
+    Block,
+    Comment,
+}
+
+/// Lines an editor can fold: the first stays visible, the ones after it
+/// up to `end_line` are hidden.  A closing brace on a line of its own is
+/// left out, so it stays visible too.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FoldingRange {
+    /// 1-based.
+    pub start_line: usize,
+    pub end_line: usize,
+    pub kind: FoldingKind,
+}
+
+/// The folding ranges of `source`, whose syntax tree is `tree`, ordered by
+/// their first line.  Comments are only folded if the source lexes.
+pub fn folding_ranges(source: &str, tree: &Tree) -> Vec<FoldingRange> {
+    let tokens = lex(source).tokens;
+    let root = Aligner::new(&tokens).root(tree);
+    let mut out = Vec::new();
+    fold_nodes(&root, &tokens, &mut out);
+    fold_comments(source, &mut out);
+    out.sort_by_key(|f| (f.start_line, f.end_line));
+    // A method body folds the same lines as its method.
+    out.dedup_by_key(|f| (f.start_line, f.end_line));
+    out
+}
+
+/// The spans of the nodes of `tree`, parsed from `source`, that contain
+/// byte `offset`, innermost first and each larger than the one before.
+/// Empty if `offset` is outside the class.
+pub fn selection_ranges(source: &str, tree: &Tree, offset: usize) -> Vec<Span> {
+    let tokens = lex(source).tokens;
+    let root = Aligner::new(&tokens).root(tree);
+    let mut out: Vec<Span> = Vec::new();
+    let mut node = Some(&root);
+    while let Some(n) = node {
+        let Some(span) = n.span(&tokens).filter(|s| s.start <= offset && offset <= s.end) else { break };
+        if out.last() != Some(&span) {
+            out.push(span);
+        }
+        // Between two nodes that touch, the one starting at `offset`.
+        let contains = |k: &&Node<'_>| k.span(&tokens).is_some_and(|s| s.start <= offset && offset < s.end);
+        let ends = |k: &&Node<'_>| k.span(&tokens).is_some_and(|s| s.end == offset);
+        node = n.kids.iter().find(contains).or_else(|| n.kids.iter().find(ends));
+    }
+    out.reverse();
+    out
+}
+
+/// The span of each node of `tree`, parsed from `source`, by node id.
+/// Nodes with no tokens, such as placeholders, have none.
+pub(crate) fn node_spans(source: &str, tree: &Tree) -> HashMap<u32, Span> {
+    fn collect(node: &Node<'_>, tokens: &[SpannedToken], out: &mut HashMap<u32, Span>) {
+        if let Some(span) = node.span(tokens) {
+            out.insert(node.tree.id, span);
+        }
+        for kid in &node.kids {
+            collect(kid, tokens, out);
+        }
+    }
+    let tokens = lex(source).tokens;
+    let mut out = HashMap::new();
+    collect(&Aligner::new(&tokens).root(tree), &tokens, &mut out);
+    out
+}
+
+fn fold_nodes(node: &Node<'_>, tokens: &[SpannedToken], out: &mut Vec<FoldingRange>) {
+    let kind = match node.tree.sym.as_str() {
+        "ClassDecl" | "InterfaceDecl" => Some(FoldingKind::Class),
+        "MethodDecl" | "ConstructorDecl" => Some(FoldingKind::Method),
+        "Block" | "SwitchStmt" if node.braces.is_some() => Some(FoldingKind::Block),
+        _ => None,
+    };
+    if let (Some(kind), Some((first, _)), Some((open, close))) = (kind, node.tokens, node.last_braces()) {
+        let start_line = tokens[first].line;
+        let end_line = if close > open + 1 && tokens[close - 1].line == tokens[close].line {
+            tokens[close].line
+        } else {
+            tokens[close].line - 1
+        };
+        if end_line > start_line {
+            out.push(FoldingRange { start_line, end_line, kind });
+        }
+    }
+    for kid in &node.kids {
+        fold_nodes(kid, tokens, out);
+    }
+}
+
+fn fold_comments(source: &str, out: &mut Vec<FoldingRange>) {
+    let Ok(file) = lex_lossless(source) else { return };
+    let mut at = 0;
+    for tok in &file.tokens {
+        for t in &tok.leading {
+            fold_comment(source, at, t, out);
+            at += t.text.len();
+        }
+        at += tok.text.len();
+    }
+    for t in &file.trailing {
+        fold_comment(source, at, t, out);
+        at += t.text.len();
+    }
+}
+
+/// Fold the comment `t`, starting at byte `at`, if it spans lines.
+fn fold_comment(source: &str, at: usize, t: &Trivia, out: &mut Vec<FoldingRange>) {
+    // A line comment includes its newline but covers one line.
+    let lines = t.text.trim_end_matches('\n').matches('\n').count();
+    if t.is_comment() && lines > 0 {
+        let start_line = line_col(source, at).0;
+        out.push(FoldingRange { start_line, end_line: start_line + lines, kind: FoldingKind::Comment });
+    }
+}
+
+// ─── Node spans ──────────────────────────────────────────
+
+/// A node of the tree with the tokens it covers.
+struct Node<'t> {
+    tree: &'t Tree,
+    /// Indices of its first and last token; `None` if it has none, like a
+    /// placeholder for something left out.
+    tokens: Option<(usize, usize)>,
+    /// Indices of its `{` and `}`, for a node written between braces.
+    braces: Option<(usize, usize)>,
+    /// In source order, which is not always the tree's: a case group
+    /// keeps its body before its labels, a `try` its `finally` block
+    /// before its catch clauses.
+    kids: Vec<Node<'t>>,
+}
+
+impl Node<'_> {
+    fn span(&self, tokens: &[SpannedToken]) -> Option<Span> {
+        self.tokens.map(|(first, last)| Span::new(tokens[first].start, tokens[last].end))
+    }
+
+    /// The braces closing this node: its own, or those of the body of a
+    /// method or constructor.
+    fn last_braces(&self) -> Option<(usize, usize)> {
+        self.braces.or_else(|| self.kids.last().filter(|k| k.tree.sym == "Block").and_then(|k| k.braces))
+    }
+}
+
+/// Lines up the nodes of a tree with the tokens of its source, walking
+/// both in source order.
+struct Aligner<'a> {
+    tokens: &'a [SpannedToken],
+    /// The index of the bracket closing each `(`, `[` or `{`.
+    closing: Vec<Option<usize>>,
+    /// The index of the bracket opening each `)`, `]` or `}`.
+    opening: Vec<Option<usize>>,
+    /// The first token not yet taken.
+    cursor: usize,
+}
+
+impl<'a> Aligner<'a> {
+    fn new(tokens: &'a [SpannedToken]) -> Self {
+        let (mut closing, mut opening) = (vec![None; tokens.len()], vec![None; tokens.len()]);
+        let mut open: Vec<(usize, Token)> = Vec::new();
+        for (i, tok) in tokens.iter().enumerate() {
+            let opener = match tok.token {
+                Token::LParen | Token::LBracket | Token::LBrace => {
+                    open.push((i, tok.token.clone()));
+                    continue;
+                }
+                Token::RParen => Token::LParen,
+                Token::RBracket => Token::LBracket,
+                Token::RBrace => Token::LBrace,
+                _ => continue,
+            };
+            if open.last().is_some_and(|(_, t)| *t == opener) {
+                let (o, _) = open.pop().expect("checked above");
+                closing[o] = Some(i);
+                opening[i] = Some(o);
+            }
+        }
+        Aligner { tokens, closing, opening, cursor: 0 }
+    }
+
+    fn root<'t>(mut self, tree: &'t Tree) -> Node<'t> {
+        let mut root = self.node(tree, true);
+        // `public class` comes before the class's first leaf, its name.
+        if let Some((_, last)) = root.tokens {
+            root.tokens = Some((0, last));
+        }
+        root
+    }
+
+    /// `braced` is false for the body of a case group, a block with no
+    /// braces of its own.
+    fn node<'t>(&mut self, tree: &'t Tree, braced: bool) -> Node<'t> {
+        if let Some(tok) = &tree.tok {
+            let tokens = self.leaf(tok).map(|i| (i, i));
+            return Node { tree, tokens, braces: None, kids: Vec::new() };
+        }
+        let n = tree.kids.len();
+        let order: Vec<usize> = match (tree.sym.as_str(), tree.rule) {
+            ("CaseClause", _) => (1..n).chain([0]).collect(),
+            ("TryStmt", 1) => [0].into_iter().chain(2..n).chain([1]).collect(),
+            _ => (0..n).collect(),
+        };
+        let brace_at = if braced { brace_position(tree) } else { None };
+        let mut braces = None;
+        // A case group's body starts after the `:` of its last label.
+        let mut members_start = if braced { None } else { self.next(Token::Colon).map(|i| i + 1) };
+        let mut kids = Vec::with_capacity(order.len());
+        for at in 0..=order.len() {
+            if brace_at == Some(at) {
+                braces = self.open_brace();
+                members_start = braces.map(|(open, _)| open + 1);
+            }
+            if let Some(&i) = order.get(at) {
+                let braced = !(tree.sym == "CaseClause" && i == 0);
+                kids.push(self.node(&tree.kids[i], braced));
+            }
+        }
+        if let Some((_, close)) = braces {
+            self.cursor = self.cursor.max(close + 1);
+        }
+        if matches!(tree.sym.as_str(), "Block" | "ClassDecl" | "SwitchStmt") {
+            let members = brace_at.map_or(0, |at| at.min(kids.len()));
+            self.widen_members(&mut kids[members..], members_start);
+        }
+        let tokens = kids.iter().filter_map(|k| k.tokens)
+            .chain(braces)
+            .reduce(|a, b| (a.0.min(b.0), a.1.max(b.1)))
+            .map(|(first, last)| match (prefix(tree), first.checked_sub(1)) {
+                (Some(token), Some(before)) if self.tokens[before].token == token => (before, last),
+                _ => (first, last),
+            })
+            .map(|t| self.take_brackets(t));
+        Node { tree, tokens, braces, kids }
+    }
+
+    /// The index of the next token with the text of leaf `tok`, taken.
+    fn leaf(&mut self, tok: &LeafToken) -> Option<usize> {
+        if tok.text.is_empty() {
+            return None;
+        }
+        let found = self.tokens[self.cursor..].iter()
+            .take_while(|t| t.line <= tok.lineno)
+            .position(|t| t.line == tok.lineno && t.text == tok.text)?;
+        let i = self.cursor + found;
+        self.cursor = i + 1;
+        Some(i)
+    }
+
+    /// The index of the next `token` from `from` on, not taken.
+    fn next_from(&self, from: usize, token: Token) -> Option<usize> {
+        Some(from + self.tokens.get(from..)?.iter().position(|t| t.token == token)?)
+    }
+
+    fn next(&self, token: Token) -> Option<usize> {
+        self.next_from(self.cursor, token)
+    }
+
+    /// The next `{` and its `}`, the `{` taken.
+    fn open_brace(&mut self) -> Option<(usize, usize)> {
+        let open = self.next(Token::LBrace)?;
+        self.cursor = open + 1;
+        Some((open, self.closing[open]?))
+    }
+
+    /// Widen the tokens of an expression or declaration by the brackets
+    /// it opens or closes but are no leaves: `f(x` becomes `f(x)`, `x) * y`
+    /// becomes `(x) * y`, and `f` followed by `()` becomes `f()`.
+    fn take_brackets(&mut self, (mut first, mut last): (usize, usize)) -> (usize, usize) {
+        while first > 0 && self.closing[first - 1].is_some_and(|c| c <= last) {
+            first -= 1;
+        }
+        loop {
+            if self.opening.get(last + 1).copied().flatten().is_some_and(|o| o >= first) {
+                last += 1;
+            } else if self.closing.get(last + 1).copied().flatten() == Some(last + 2) {
+                last += 2;
+            } else {
+                break;
+            }
+        }
+        self.cursor = self.cursor.max(last + 1);
+        (first, last)
+    }
+
+    /// Let each of the statements or members of a block, class or switch
+    /// start right after the one before — taking in a keyword such as
+    /// `return` or `public` that is no leaf — and end with its `;`.
+    fn widen_members(&self, members: &mut [Node<'_>], start: Option<usize>) {
+        let mut next = start;
+        for member in members {
+            let tokens = match member.tokens {
+                Some((first, last)) => {
+                    let semicolon = self.tokens.get(last + 1).is_some_and(|t| t.token == Token::Semicolon);
+                    (next.map_or(first, |n| n.min(first)), last + usize::from(semicolon))
+                }
+                // `break;`, `return;` and `;` have no leaf to line up.
+                None => match next.and_then(|n| Some((n, self.next_from(n, Token::Semicolon)?))) {
+                    Some(tokens) => tokens,
+                    None => continue,
+                },
+            };
+            member.tokens = Some(tokens);
+            next = Some(tokens.1 + 1);
+        }
+    }
+}
+
+/// For a node whose members are written between braces, how many of its
+/// kids, in source order, come before the `{`.
+fn brace_position(tree: &Tree) -> Option<usize> {
+    match tree.sym.as_str() {
+        "Block" | "ArrayInit" => Some(0),
+        // A subclass names its superclass too, and a class may name the
+        // interfaces it implements.
+        "ClassDecl" => Some(match tree.rule { 0 => 1, 1 | 2 => 2, _ => 3 }),
+        "InterfaceDecl" => Some(1),
+        "SwitchStmt" => Some(1),
+        _ => None,
+    }
+}
+
+/// The token a node starts with that is no leaf: the operator of a
+/// unary expression, the `new` of a creation.
+fn prefix(tree: &Tree) -> Option<Token> {
+    match tree.sym.as_str() {
+        "UnaryMinus" => Some(Token::Minus),
+        "UnaryNot" => Some(Token::Bang),
+        "CastExpr" => Some(Token::LParen),
+        "ArrayCreation" | "InstanceCreation" => Some(Token::New),
+        _ => None,
+    }
+}
+
+// ─── Tests ───────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jzero_lexer::{Level, LexOptions};
+
+    const SRC: &str = "\
+public class a {
+    /* Two lines
+       of comment. */
+    public static int twice(int x) {
+        return x * 2;
+    }
+    public static void main(String argv[]) {
+        int i;
+        i = 0;
+        while (i < 3) {
+            System.out.println(twice(i + 1));
+            i = i + 1;
+        }
+        if (i > 2) { return; }
+    }
+}
+";
+
+    fn selected(src: &str, needle: &str) -> Vec<String> {
+        let tree = jzero_parser::parse_tree_with(src, LexOptions::at_level(Level::Extended)).unwrap();
+        selection_ranges(src, &tree, src.find(needle).unwrap())
+            .iter()
+            .map(|s| src[s.start..s.end].split_whitespace().collect::<Vec<_>>().join(" "))
+            .collect()
+    }
+
+    #[test]
+    fn folds_classes_methods_blocks_and_comments() {
+        let tree = jzero_parser::parse_tree(SRC).unwrap();
+        let folds: Vec<_> = folding_ranges(SRC, &tree).iter().map(|f| (f.start_line, f.end_line, f.kind)).collect();
+        assert_eq!(folds, [
+            (1, 15, FoldingKind::Class),
+            (2, 3, FoldingKind::Comment),
+            (4, 5, FoldingKind::Method),
+            (7, 14, FoldingKind::Method),
+            (10, 12, FoldingKind::Block),
+        ]);
+    }
+
+    #[test]
+    fn selection_expands_from_expression_to_class() {
+        let chain = selected(SRC, "i + 1");
+        assert_eq!(chain[..4], ["i", "i + 1", "twice(i + 1)", "System.out.println(twice(i + 1));"]);
+        assert_eq!(chain[4], "{ System.out.println(twice(i + 1)); i = i + 1; }");
+        assert_eq!(chain[5], "while (i < 3) { System.out.println(twice(i + 1)); i = i + 1; }");
+        assert!(chain[chain.len() - 3].starts_with("{ int i;"), "{:?}", chain);
+        assert!(chain[chain.len() - 2].starts_with("public static void main"), "{:?}", chain);
+        assert!(chain[chain.len() - 1].starts_with("public class a {") && chain[chain.len() - 1].ends_with('}'));
+
+        assert_eq!(selected(SRC, "return x")[..2], ["return x * 2;", "{ return x * 2; }"]);
+    }
+
+    #[test]
+    fn every_leaf_of_the_examples_is_lined_up() {
+        fn check(node: &Node<'_>, tokens: &[SpannedToken], name: &str) {
+            if let Some(tok) = node.tree.tok.as_ref().filter(|t| !t.text.is_empty()) {
+                let found = node.tokens.map(|(i, _)| tokens[i].text.as_str());
+                assert_eq!(found, Some(tok.text.as_str()), "{}: line {}", name, tok.lineno);
+            }
+            for kid in &node.kids {
+                check(kid, tokens, name);
+            }
+        }
+        let dir = format!("{}/../../tests/examples", env!("CARGO_MANIFEST_DIR"));
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            let src = std::fs::read_to_string(&path).unwrap();
+            let Ok(tree) = jzero_parser::parse_tree_with(&src, LexOptions::at_level(Level::MAX)) else { continue };
+            let tokens = lex(&src).tokens;
+            check(&Aligner::new(&tokens).root(&tree), &tokens, &path.display().to_string());
+        }
+    }
+
+    #[test]
+    fn case_groups_and_brace_calls_are_lined_up() {
+        let src = "public class a { public static void main(String argv[]) {\n\
+                   switch (f{1}.g{2}) {\n\
+                   case 1: case 2: h(3); break;\n\
+                   default: h(4);\n\
+                   }\n} }";
+        assert_eq!(selected(src, "3")[..4], ["3", "h(3);", "h(3); break;", "case 1: case 2: h(3); break;"]);
+        assert_eq!(selected(src, "2}")[..3], ["2", "f{1}.g{2}", "switch (f{1}.g{2}) { case 1: case 2: h(3); break; default: h(4); }"]);
+        assert!(selected(&format!("\n{}", src), "\n").is_empty());
+    }
+
+    #[test]
+    fn array_initializers_take_their_braces() {
+        let src = "public class a { int t[][] = {{1, 2}, {}}, u[]; }";
+        assert_eq!(selected(src, "2")[..4], ["2", "{1, 2}", "{{1, 2}, {}}", "t[][] = {{1, 2}, {}}"]);
+        assert_eq!(selected(src, "}}")[..2], ["{}", "{{1, 2}, {}}"]);
+    }
+}
@@ -0,0 +1,272 @@
+//! Refactorings across the files of a workspace, for an editor's rename
+//! and code actions.
+//!
+//! Both work on an [`XrefIndex`] and return the edits to make to each file,
+//! leaving the files themselves alone:
+//!
+//! - [`rename`] renames a declaration and every name referring to it;
+//! - [`safe_delete`] removes a field, method or local variable, and
+//!   refuses while anything still refers to it.
+//!
+//! [`code_actions`] offers both for the name under the cursor.
+//!
+//! ```
+//! use jzero::xref::XrefIndex;
+//! use jzero_lexer::LexOptions;
+//!
+//! let src = "public class a {\n    int n;\n    int unused;\n    public static void main(String argv[]) { n = 1; }\n}\n";
+//! let index = XrefIndex::from_sources([("a.java", src)], LexOptions::default()).unwrap();
+//! let renamed = jzero::refactor::rename(&index, "a.java", src.find("n;").unwrap(), "count").unwrap();
+//! assert_eq!(jzero_text::apply_edits(src, &renamed[0].edits), src.replace("int n;", "int count;").replace("n = 1", "count = 1"));
+//! assert!(jzero::refactor::safe_delete(&index, "a.java", src.find("n;").unwrap()).is_err());
+//! let deleted = jzero::refactor::safe_delete(&index, "a.java", src.find("unused").unwrap()).unwrap();
+//! assert!(!jzero_text::apply_edits(src, &deleted[0].edits).contains("unused"));
+//! ```
+
+use jzero_lexer::lex;
+use jzero_lexer::token::Token;
+use jzero_symtab::entry::SymbolKind;
+use jzero_text::{Span, TextEdit, line_col};
+
+use crate::JzeroError;
+use crate::xref::{Location, XrefIndex};
+
+/// The edits to make to one file, in source order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileEdit {
+    pub path: String,
+    pub edits: Vec<TextEdit>,
+}
+
+/// A refactoring offered for the name under the cursor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CodeAction {
+    /// Rename the declaration: the editor asks for the new name and calls
+    /// [`rename`].
+    Rename { title: String },
+    /// Make `edits`.
+    Edit { title: String, edits: Vec<FileEdit> },
+}
+
+/// Rename the declaration of the name at byte `offset` of `path`, and every
+/// name referring to it, to `new_name`.
+///
+/// # Errors
+/// Returns a [`JzeroError`] if there is no declared name at `offset`, if
+/// `new_name` is not a name, or if the scope of the declaration already
+/// declares `new_name`.
+pub fn rename(index: &XrefIndex, path: &str, offset: usize, new_name: &str) -> Result<Vec<FileEdit>, JzeroError> {
+    let decl = declaration_at(index, path, offset)?;
+    let lexed = lex(new_name);
+    let is_name = lexed.is_ok()
+        && matches!(lexed.tokens.as_slice(), [t] if t.token == Token::Identifier && t.text == new_name);
+    if !is_name {
+        return Err(JzeroError(format!("'{}' is not a name", new_name)));
+    }
+    let kind = &index.decls[decl].info.kind;
+    let clash = index.siblings(decl)
+        .find(|d| d.info.name == new_name && (d.info.kind == SymbolKind::Method) == (*kind == SymbolKind::Method));
+    if let Some(other) = clash {
+        return Err(JzeroError(format!("'{}' is already declared at {}", new_name, at(index, &other.info.location))));
+    }
+    let edits = index.occurrences(decl).into_iter().map(|l| (l.path, TextEdit::replace(l.span, new_name)));
+    Ok(by_file(edits))
+}
+
+/// Delete the field, method or local variable named at byte `offset` of
+/// `path`.  A declaration alone on its lines goes with its lines; one of
+/// several declarators, such as `y` in `int x, y;`, goes with its comma.
+///
+/// # Errors
+/// Returns a [`JzeroError`] if there is no declared name at `offset`, if
+/// it is a class or parameter, or if anything outside the declaration
+/// still refers to it, naming where.
+pub fn safe_delete(index: &XrefIndex, path: &str, offset: usize) -> Result<Vec<FileEdit>, JzeroError> {
+    let decl = &index.decls[declaration_at(index, path, offset)?];
+    let info = &decl.info;
+    if !matches!(info.kind, SymbolKind::Field | SymbolKind::Method | SymbolKind::Local) {
+        return Err(JzeroError(format!("'{}' is a {}; only fields, methods and local variables can be deleted", info.name, info.kind)));
+    }
+    let file = &index.files[decl.file];
+    let whole = file.spans.get(&decl.node).copied().unwrap_or(info.location.span);
+    // A recursive method may call itself.
+    let uses: Vec<Location> = index.references(info).into_iter()
+        .filter(|l| !(l.path == file.path && whole.start <= l.span.start && l.span.end <= whole.end))
+        .collect();
+    if !uses.is_empty() {
+        let places: Vec<String> = uses.iter().map(|l| at(index, l)).collect();
+        return Err(JzeroError(format!("'{}' is still used at {}", info.name, places.join(", "))));
+    }
+
+    let span = match decl.declarators.iter().position(|d| *d == decl.declarator) {
+        Some(i) if decl.declarators.len() > 1 => {
+            let span = |i: usize| file.spans.get(&decl.declarators[i]).copied().unwrap_or_default();
+            match decl.declarators.get(i + 1) {
+                Some(_) => Span::new(span(i).start, span(i + 1).start),
+                None => Span::new(span(i - 1).end, span(i).end),
+            }
+        }
+        _ => whole_lines(&file.source, whole),
+    };
+    Ok(vec![FileEdit { path: file.path.clone(), edits: vec![TextEdit::delete(span)] }])
+}
+
+/// The refactorings offered for the name at byte `offset` of `path`:
+/// renaming it, and deleting it where [`safe_delete`] would.
+pub fn code_actions(index: &XrefIndex, path: &str, offset: usize) -> Vec<CodeAction> {
+    let Some(info) = index.declaration_at(path, offset) else { return Vec::new() };
+    let mut actions = vec![CodeAction::Rename { title: format!("Rename '{}'", info.name) }];
+    if let Ok(edits) = safe_delete(index, path, offset) {
+        actions.push(CodeAction::Edit { title: format!("Delete unused {} '{}'", info.kind, info.name), edits });
+    }
+    actions
+}
+
+fn declaration_at(index: &XrefIndex, path: &str, offset: usize) -> Result<usize, JzeroError> {
+    index.decl_at(path, offset)
+        .ok_or_else(|| JzeroError(format!("{}: no declared name at offset {}", path, offset)))
+}
+
+/// `Main.java:4`
+fn at(index: &XrefIndex, location: &Location) -> String {
+    let file = index.files.iter().find(|f| f.path == location.path).expect("a location of the index");
+    format!("{}:{}", location.path, line_col(&file.source, location.span.start).0)
+}
+
+/// The edits grouped by file, in the order the files were first edited.
+fn by_file(edits: impl IntoIterator<Item = (String, TextEdit)>) -> Vec<FileEdit> {
+    let mut out: Vec<FileEdit> = Vec::new();
+    for (path, edit) in edits {
+        match out.iter_mut().find(|f| f.path == path) {
+            Some(file) => file.edits.push(edit),
+            None => out.push(FileEdit { path, edits: vec![edit] }),
+        }
+    }
+    out
+}
+
+/// `span` widened to whole lines, newline included, when nothing else is
+/// on them.
+fn whole_lines(source: &str, span: Span) -> Span {
+    let start = source[..span.start].rfind('\n').map_or(0, |i| i + 1);
+    let end = source[span.end..].find('\n').map_or(source.len(), |i| span.end + i + 1);
+    let blank = |s: &str| s.trim().is_empty();
+    if blank(&source[start..span.start]) && blank(&source[span.end..end]) {
+        Span::new(start, end)
+    } else {
+        span
+    }
+}
+
+// ─── Tests ───────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use jzero_lexer::LexOptions;
+    use jzero_text::apply_edits;
+
+    use super::*;
+
+    const SHAPE: &str = "\
+public class Shape {
+    int width, height;
+    int depth;
+    public static int area(int w, int h) {
+        return w * h;
+    }
+    public static int unused(int n) {
+        if (n > 0) { return unused(n - 1); }
+        return 0;
+    }
+}
+";
+
+    const MAIN: &str = "\
+public class Main {
+    public static void main(String argv[]) {
+        Shape s;
+        int spare, kept;
+        s = new Shape();
+        s.width = Shape.area(2, 3);
+        kept = s.width;
+        System.out.println(kept);
+    }
+}
+";
+
+    fn index() -> XrefIndex {
+        XrefIndex::from_sources([("Shape.java", SHAPE), ("Main.java", MAIN)], LexOptions::default()).unwrap()
+    }
+
+    fn apply(edits: &[FileEdit], path: &str, source: &str) -> String {
+        edits.iter().find(|f| f.path == path).map_or(source.to_string(), |f| apply_edits(source, &f.edits))
+    }
+
+    #[test]
+    fn rename_edits_every_file() {
+        let index = index();
+        let edits = rename(&index, "Main.java", MAIN.find("width").unwrap(), "w").unwrap();
+        assert_eq!(apply(&edits, "Shape.java", SHAPE), SHAPE.replace("width,", "w,"));
+        assert_eq!(apply(&edits, "Main.java", MAIN), MAIN.replace("s.width", "s.w"));
+
+        let edits = rename(&index, "Shape.java", SHAPE.find("area").unwrap(), "size").unwrap();
+        assert_eq!(edits.iter().map(|f| f.edits.len()).sum::<usize>(), 2);
+        assert!(apply(&edits, "Main.java", MAIN).contains("Shape.size(2, 3)"));
+
+        let edits = rename(&index, "Shape.java", SHAPE.find("Shape").unwrap(), "Box").unwrap();
+        assert_eq!(apply(&edits, "Main.java", MAIN).matches("Box").count(), 3);
+    }
+
+    #[test]
+    fn rename_refuses_clashes_and_non_names() {
+        let index = index();
+        let w = SHAPE.find("width").unwrap();
+        let err = rename(&index, "Shape.java", SHAPE.find("(int w").unwrap() + 5, "h").unwrap_err();
+        assert_eq!(err.0, "'h' is already declared at Shape.java:4");
+        assert_eq!(rename(&index, "Shape.java", w, "depth").unwrap_err().0, "'depth' is already declared at Shape.java:3");
+        assert!(rename(&index, "Shape.java", w, "area").is_ok());
+        assert_eq!(rename(&index, "Shape.java", w, "while").unwrap_err().0, "'while' is not a name");
+        assert_eq!(rename(&index, "Shape.java", w, "a b").unwrap_err().0, "'a b' is not a name");
+        assert!(rename(&index, "Shape.java", SHAPE.find("return").unwrap(), "x").is_err());
+    }
+
+    #[test]
+    fn safe_delete_refuses_what_is_still_used() {
+        let index = index();
+        let err = safe_delete(&index, "Shape.java", SHAPE.find("width").unwrap()).unwrap_err();
+        assert_eq!(err.0, "'width' is still used at Main.java:6, Main.java:7");
+        let err = safe_delete(&index, "Shape.java", SHAPE.find("(int w").unwrap() + 5).unwrap_err();
+        assert_eq!(err.0, "'w' is a param; only fields, methods and local variables can be deleted");
+        assert!(safe_delete(&index, "Main.java", MAIN.find("Shape").unwrap()).is_err());
+    }
+
+    #[test]
+    fn safe_delete_removes_unused_declarations() {
+        let index = index();
+        let edits = safe_delete(&index, "Shape.java", SHAPE.find("height").unwrap()).unwrap();
+        assert_eq!(apply(&edits, "Shape.java", SHAPE), SHAPE.replace("width, height;", "width;"));
+        let edits = safe_delete(&index, "Shape.java", SHAPE.find("depth").unwrap()).unwrap();
+        assert_eq!(apply(&edits, "Shape.java", SHAPE), SHAPE.replace("    int depth;\n", ""));
+        let edits = safe_delete(&index, "Main.java", MAIN.find("spare").unwrap()).unwrap();
+        assert_eq!(apply(&edits, "Main.java", MAIN), MAIN.replace("spare, ", ""));
+
+        // Only calls itself.
+        let edits = safe_delete(&index, "Shape.java", SHAPE.find("unused").unwrap()).unwrap();
+        let deleted = apply(&edits, "Shape.java", SHAPE);
+        assert!(!deleted.contains("unused") && deleted.ends_with("    }\n}\n"), "{}", deleted);
+        jzero_parser::parse_tree(&deleted).unwrap();
+    }
+
+    #[test]
+    fn code_actions_offer_rename_and_delete() {
+        let index = index();
+        let titles = |offset| -> Vec<String> {
+            code_actions(&index, "Shape.java", offset).into_iter().map(|a| match a {
+                CodeAction::Rename { title } | CodeAction::Edit { title, .. } => title,
+            }).collect()
+        };
+        assert_eq!(titles(SHAPE.find("depth").unwrap()), ["Rename 'depth'", "Delete unused field 'depth'"]);
+        assert_eq!(titles(SHAPE.find("width").unwrap()), ["Rename 'width'"]);
+        assert!(titles(SHAPE.find("return").unwrap()).is_empty());
+    }
+}
@@ -0,0 +1,394 @@
+//! Code search — find constructs by the shape of the syntax tree, for
+//! `j0 search`.
+//!
+//! A [`Query`] is a list of steps, each naming a node kind (`*` for any)
+//! and optionally attributes it must have, in brackets.  Steps separated
+//! by a space match a node inside the one before; `>` asks for a direct
+//! child:
+//!
+//! ```text
+//! MethodCall[callee="println"]          every call of println
+//! WhileStmt MethodCall[args=0]          calls without arguments in a loop
+//! MethodDecl[name="main"] > Block > *   the statements of main
+//! LocalVarDecl[type="double"]           double local variables
+//! ```
+//!
+//! The attributes are `name` (of a class, method, constructor, variable,
+//! field access, call, `new` or identifier), `callee` (of a call), `type`
+//! (of a declaration, or a method's return type), `args` (of a call or
+//! `new`), `text` (of a leaf), `rule` and `line`.  A value is quoted or a
+//! bare word; it may hold `*` and `?` wildcards, and `!=` negates the test.
+//!
+//! ```
+//! use jzero::search::Query;
+//!
+//! let src = "public class a {\n    public static void main(String argv[]) {\n        while (true) { System.out.println(1); }\n    }\n}\n";
+//! let query = Query::parse(r#"WhileStmt MethodCall[callee="print*"]"#).unwrap();
+//! let found = query.search("a.java", src).unwrap();
+//! assert_eq!(found[0].to_string(), "a.java:3:24: System.out.println(1);");
+//! ```
+
+use std::fmt;
+
+use jzero_ast::tree::Tree;
+use jzero_text::{Span, line_col, line_start};
+
+use crate::JzeroError;
+use crate::ranges::node_spans;
+use crate::symbols::glob;
+
+/// The attributes a query can test.
+const ATTRIBUTES: &[&str] = &["name", "callee", "type", "args", "text", "rule", "line"];
+
+/// A parsed search query.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Query {
+    steps: Vec<Step>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Step {
+    /// Whether the node matching the step before must be the parent of
+    /// this one, not just an ancestor.
+    child: bool,
+    /// `None` for `*`.
+    kind: Option<String>,
+    tests: Vec<Test>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Test {
+    attribute: String,
+    negated: bool,
+    value: String,
+}
+
+/// A node a query matched.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchMatch {
+    pub path: String,
+    pub span: Span,
+    /// 1-based line and column of the start of the span.
+    pub line: usize,
+    pub column: usize,
+    /// The node kind, e.g. `MethodCall`.
+    pub kind: String,
+    /// The first line of the matched source.
+    pub snippet: String,
+}
+
+/// `a.java:3:24: System.out.println(1);`
+impl fmt::Display for SearchMatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}: {}", self.path, self.line, self.column, self.snippet)
+    }
+}
+
+impl Query {
+    /// Parse `query`.
+    ///
+    /// # Errors
+    /// Returns a [`JzeroError`] naming the column of the first mistake, or
+    /// an attribute not in the list above.
+    pub fn parse(query: &str) -> Result<Self, JzeroError> {
+        let mut parser = Parser { chars: query.char_indices().peekable(), len: query.len() };
+        let mut steps = Vec::new();
+        loop {
+            let spaced = parser.skip_spaces();
+            let at = parser.offset();
+            let child = parser.eat('>');
+            if steps.is_empty() && child {
+                return Err(JzeroError(format!("query: column {}: '>' needs a step before it", at + 1)));
+            }
+            if child {
+                parser.skip_spaces();
+            }
+            if parser.at_end() {
+                if child || steps.is_empty() {
+                    return Err(parser.error("a node kind"));
+                }
+                break;
+            }
+            if !steps.is_empty() && !spaced && !child {
+                return Err(parser.error("a space or '>'"));
+            }
+            let kind = if parser.eat('*') { None } else { Some(parser.word().ok_or_else(|| parser.error("a node kind"))?) };
+            let mut tests = Vec::new();
+            if parser.eat('[') {
+                loop {
+                    parser.skip_spaces();
+                    let at = parser.offset();
+                    let attribute = parser.word().ok_or_else(|| parser.error("an attribute"))?;
+                    if !ATTRIBUTES.contains(&attribute.as_str()) {
+                        return Err(JzeroError(format!(
+                            "query: column {}: unknown attribute '{}'; expected one of {}",
+                            at + 1, attribute, ATTRIBUTES.join(", "),
+                        )));
+                    }
+                    parser.skip_spaces();
+                    let negated = parser.eat('!');
+                    if !parser.eat('=') {
+                        return Err(parser.error("'=' or '!='"));
+                    }
+                    parser.skip_spaces();
+                    let value = parser.value().ok_or_else(|| parser.error("a value"))?;
+                    tests.push(Test { attribute, negated, value });
+                    parser.skip_spaces();
+                    if parser.eat(']') {
+                        break;
+                    }
+                    if !parser.eat(',') {
+                        return Err(parser.error("',' or ']'"));
+                    }
+                }
+            }
+            steps.push(Step { child, kind, tests });
+        }
+        Ok(Query { steps })
+    }
+
+    /// The nodes of `tree` the query matches, in source order.
+    pub fn matches<'t>(&self, tree: &'t Tree) -> Vec<&'t Tree> {
+        let mut found = Vec::new();
+        self.collect(tree, &mut Vec::new(), &mut found);
+        found
+    }
+
+    /// The matches of the query in `source`, the file at `path`.
+    ///
+    /// # Errors
+    /// Returns a [`JzeroError`] if the source does not parse.
+    pub fn search(&self, path: &str, source: &str) -> Result<Vec<SearchMatch>, JzeroError> {
+        let tree = jzero_parser::parse_tree(source).map_err(|e| JzeroError(format!("{}: {}", path, e)))?;
+        let spans = node_spans(source, &tree);
+        Ok(self.matches(&tree).into_iter().map(|node| {
+            let span = spans.get(&node.id).copied().unwrap_or_else(|| Span::at(line_start(source, node.first_lineno().max(1))));
+            let (line, column) = line_col(source, span.start);
+            let text = &source[span.start..span.end];
+            SearchMatch {
+                path: path.to_string(),
+                span,
+                line,
+                column,
+                kind: node.sym.clone(),
+                snippet: text.lines().next().unwrap_or("").trim_end().to_string(),
+            }
+        }).collect())
+    }
+
+    fn collect<'t>(&self, tree: &'t Tree, ancestors: &mut Vec<&'t Tree>, found: &mut Vec<&'t Tree>) {
+        if self.matches_at(self.steps.len() - 1, tree, ancestors) {
+            found.push(tree);
+        }
+        ancestors.push(tree);
+        for kid in &tree.kids {
+            self.collect(kid, ancestors, found);
+        }
+        ancestors.pop();
+    }
+
+    /// Whether `node`, below `ancestors`, matches step `i` and the steps
+    /// before it.
+    fn matches_at(&self, i: usize, node: &Tree, ancestors: &[&Tree]) -> bool {
+        let step = &self.steps[i];
+        if !step.matches(node) {
+            return false;
+        }
+        if i == 0 {
+            return true;
+        }
+        if step.child {
+            return ancestors.split_last().is_some_and(|(parent, above)| self.matches_at(i - 1, parent, above));
+        }
+        (0..ancestors.len()).rev().any(|j| self.matches_at(i - 1, ancestors[j], &ancestors[..j]))
+    }
+}
+
+impl Step {
+    fn matches(&self, node: &Tree) -> bool {
+        self.kind.as_ref().is_none_or(|k| *k == node.sym)
+            && self.tests.iter().all(|test| {
+                let value = attribute_of(node, &test.attribute);
+                let found = value.is_some_and(|v| glob(&test.value.chars().collect::<Vec<_>>(), &v.chars().collect::<Vec<_>>()));
+                found != test.negated
+            })
+    }
+}
+
+/// The value of `attribute` for `node`, if it has one.
+fn attribute_of(node: &Tree, attribute: &str) -> Option<String> {
+    let text = |tree: Option<&Tree>| tree.and_then(|t| t.tok.as_ref()).map(|t| t.text.clone());
+    let kid = |i: usize| node.kids.get(i);
+    match attribute {
+        "name" => match node.sym.as_str() {
+            _ if node.tok.is_some() => text(Some(node)),
+            "ClassDecl" | "MethodDeclarator" | "ConstructorDeclarator" | "InstanceCreation" => text(kid(0)),
+            "MethodDecl" => text(kid(0)?.kids.get(1)?.kids.first()),
+            "ConstructorDecl" => text(kid(0)?.kids.first()),
+            "VarDeclarator" => attribute_of(kid(0)?, "name"),
+            "FieldAccess" => text(kid(1)),
+            "MethodCall" => attribute_of(node, "callee"),
+            _ => None,
+        },
+        "callee" if node.sym == "MethodCall" => match node.rule {
+            0 | 1 => {
+                let name = kid(0)?;
+                if name.sym == "FieldAccess" { text(name.kids.get(1)) } else { text(Some(name)) }
+            }
+            _ => text(kid(1)),
+        },
+        "type" => match node.sym.as_str() {
+            // `int a[]` declares an `int[]`, unless other names share the type.
+            "FieldDecl" | "LocalVarDecl" | "FormalParm" if node.kids.len() == 2 => {
+                let mut typ = type_text(kid(0)?)?;
+                let mut declarator = kid(1)?;
+                while declarator.rule == 1 {
+                    typ.push_str("[]");
+                    declarator = declarator.kids.first()?;
+                }
+                Some(typ)
+            }
+            "FieldDecl" | "LocalVarDecl" | "ArrayCreation" => type_text(kid(0)?),
+            "MethodDecl" => type_text(kid(0)?.kids.first()?),
+            "MethodHeader" => type_text(kid(0)?),
+            _ => None,
+        },
+        "args" => match (node.sym.as_str(), node.rule) {
+            ("MethodCall", 0 | 1) | ("InstanceCreation", _) => Some(node.kids.len().saturating_sub(1).to_string()),
+            ("MethodCall", _) => Some(node.kids.len().saturating_sub(2).to_string()),
+            _ => None,
+        },
+        "text" => text(Some(node)),
+        "rule" => (node.tok.is_none()).then(|| node.rule.to_string()),
+        "line" => Some(node.first_lineno().max(1).to_string()),
+        _ => None,
+    }
+}
+
+/// `int`, `String[]`.
+fn type_text(typ: &Tree) -> Option<String> {
+    match &typ.tok {
+        Some(tok) => Some(tok.text.clone()),
+        None => Some(format!("{}[]", type_text(typ.kids.first()?)?)),
+    }
+}
+
+struct Parser<'q> {
+    chars: std::iter::Peekable<std::str::CharIndices<'q>>,
+    len: usize,
+}
+
+impl Parser<'_> {
+    fn offset(&mut self) -> usize {
+        self.chars.peek().map_or(self.len, |(i, _)| *i)
+    }
+
+    fn at_end(&mut self) -> bool {
+        self.chars.peek().is_none()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        self.chars.next_if(|(_, n)| *n == c).is_some()
+    }
+
+    /// Skip white space; whether there was any.
+    fn skip_spaces(&mut self) -> bool {
+        let mut skipped = false;
+        while self.chars.next_if(|(_, c)| c.is_whitespace()).is_some() {
+            skipped = true;
+        }
+        skipped
+    }
+
+    fn word(&mut self) -> Option<String> {
+        let mut word = String::new();
+        while let Some((_, c)) = self.chars.next_if(|(_, c)| c.is_alphanumeric() || *c == '_') {
+            word.push(c);
+        }
+        (!word.is_empty()).then_some(word)
+    }
+
+    /// A quoted string, with `\"` and `\\` escapes, or a bare word that
+    /// may hold wildcards.
+    fn value(&mut self) -> Option<String> {
+        if !self.eat('"') {
+            let mut word = String::new();
+            while let Some((_, c)) = self.chars.next_if(|(_, c)| c.is_alphanumeric() || matches!(c, '_' | '*' | '?' | '.' | '-')) {
+                word.push(c);
+            }
+            return (!word.is_empty()).then_some(word);
+        }
+        let mut value = String::new();
+        loop {
+            match self.chars.next()?.1 {
+                '"' => return Some(value),
+                '\\' => value.push(self.chars.next()?.1),
+                c => value.push(c),
+            }
+        }
+    }
+
+    fn error(&mut self, expected: &str) -> JzeroError {
+        let at = self.offset();
+        match self.chars.peek() {
+            Some((_, c)) => JzeroError(format!("query: column {}: expected {}, found '{}'", at + 1, expected, c)),
+            None => JzeroError(format!("query: column {}: expected {}, found the end", at + 1, expected)),
+        }
+    }
+}
+
+// ─── Tests ───────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SRC: &str = "\
+public class a {
+    public static int twice(int n) {
+        return n * 2;
+    }
+    public static void main(String argv[]) {
+        double d;
+        int i;
+        i = 0;
+        while (i < 3) {
+            System.out.println(twice(i));
+            i = i + 1;
+        }
+        System.out.println(d);
+    }
+}
+";
+
+    fn found(query: &str) -> Vec<String> {
+        let query = Query::parse(query).unwrap();
+        query.search("a.java", SRC).unwrap().iter().map(|m| m.to_string()).collect()
+    }
+
+    #[test]
+    fn queries_match_kinds_attributes_and_nesting() {
+        assert_eq!(found(r#"MethodCall[callee="println"]"#), [
+            "a.java:10:13: System.out.println(twice(i));",
+            "a.java:13:9: System.out.println(d);",
+        ]);
+        assert_eq!(found("WhileStmt MethodCall[callee=twice, args=1]"), ["a.java:10:32: twice(i)"]);
+        assert_eq!(found("MethodCall[callee!=println]"), ["a.java:10:32: twice(i)"]);
+        assert_eq!(found(r#"MethodDecl[name="main"] > Block > LocalVarDecl[type="d*"]"#), ["a.java:6:9: double d;"]);
+        assert_eq!(found("MethodDecl[type=int]").len(), 1);
+        assert_eq!(found("ClassDecl > LocalVarDecl"), Vec::<String>::new());
+        assert_eq!(found("* > IDENTIFIER[text=n]").len(), 2);
+        assert_eq!(found("FormalParm[type=\"String[]\"]"), ["a.java:5:29: String argv[]"]);
+    }
+
+    #[test]
+    fn malformed_queries_are_reported() {
+        let err = |q: &str| Query::parse(q).unwrap_err().0;
+        assert_eq!(err(""), "query: column 1: expected a node kind, found the end");
+        assert_eq!(err("> MethodCall"), "query: column 1: '>' needs a step before it");
+        assert_eq!(err("MethodCall[callee]"), "query: column 18: expected '=' or '!=', found ']'");
+        assert_eq!(err("MethodCall[callee=\"f\""), "query: column 22: expected ',' or ']', found the end");
+        assert!(err("MethodCall[calee=f]").starts_with("query: column 12: unknown attribute 'calee'; expected one of name, callee"));
+        assert_eq!(err("Block >"), "query: column 8: expected a node kind, found the end");
+    }
+}
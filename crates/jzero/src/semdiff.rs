@@ -0,0 +1,276 @@
+//! Semantic diff (`j0 semdiff`) — what changed between two versions of a
+//! class, read from their symbol tables rather than their text.
+//!
+//! Both sources are analysed and the members of their class scopes
+//! compared by name.  A member in both whose declaration differs is
+//! *changed*; one only in the old version is *removed* and one only in the
+//! new *added* — unless a removed and an added member of the same kind
+//! have the same declaration but for the name, which makes them one
+//! *renamed* member.  Reformatting, comments, renamed parameters and edits
+//! inside method bodies are not changes:
+//!
+//! ```
+//! let old = "public class shape {\n    int n;\n    public static int sum(int a) { return a; }\n}\n";
+//! let new = "public class shape {\n    public static int total(int a) {\n        return a + 1;\n    }\n    public static int twice(double d) { return 2; }\n}\n";
+//! let changes: Vec<String> = jzero::semdiff::diff(old, new).unwrap().iter().map(|c| c.to_string()).collect();
+//! assert_eq!(changes, [
+//!     "removed field public int n",
+//!     "renamed method sum to total",
+//!     "added method public static int twice(double d)",
+//! ]);
+//! ```
+//!
+//! Fields are listed before methods, each in declaration order.
+//! Constructors are not compared.  Both versions are parsed at the highest
+//! language level, so members using `long`, `char` or `final` compare too.
+
+use std::fmt;
+
+use jzero_lexer::{Level, LexOptions};
+use jzero_symtab::entry::SymbolKind;
+use jzero_symtab::{Parameter, TypeInfo};
+
+use crate::project::declared_type;
+use crate::stub::{jzero_type, MethodSig};
+use crate::JzeroError;
+
+/// What a [`Member`] is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MemberKind {
+    Class,
+    Field,
+    Method,
+}
+
+impl fmt::Display for MemberKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MemberKind::Class  => write!(f, "class"),
+            MemberKind::Field  => write!(f, "field"),
+            MemberKind::Method => write!(f, "method"),
+        }
+    }
+}
+
+/// A class or one of its members, as its symbol table entry describes it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Member {
+    pub kind: MemberKind,
+    pub name: String,
+    /// The declaration as Jzero writes it: `private int count`,
+    /// `public static int max(int a, int b)`.
+    pub declaration: String,
+    /// The declaration with the names left out.  A rename keeps it;
+    /// renaming a parameter does not change it either.
+    shape: String,
+}
+
+/// One difference between two versions of a class.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change {
+    Added(Member),
+    Removed(Member),
+    Renamed { old: Member, new: Member },
+    /// Declared with the same name but another type, access or signature.
+    Changed { old: Member, new: Member },
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Change::Added(m)   => write!(f, "added {} {}", m.kind, m.declaration),
+            Change::Removed(m) => write!(f, "removed {} {}", m.kind, m.declaration),
+            Change::Renamed { old, new } => write!(f, "renamed {} {} to {}", old.kind, old.name, new.name),
+            Change::Changed { old, new } =>
+                write!(f, "changed {} {}: {} -> {}", old.kind, old.name, old.declaration, new.declaration),
+        }
+    }
+}
+
+/// The changes from the class in `old` to the class in `new`: a renamed
+/// class first, then its fields and then its methods.
+///
+/// # Errors
+/// Returns a [`JzeroError`] if either source does not parse.
+pub fn diff(old: &str, new: &str) -> Result<Vec<Change>, JzeroError> {
+    let (old_class, old_members) = class_members(old)?;
+    let (new_class, new_members) = class_members(new)?;
+    let mut changes = Vec::new();
+    if old_class.name != new_class.name {
+        changes.push(Change::Renamed { old: old_class, new: new_class });
+    }
+    for kind in [MemberKind::Field, MemberKind::Method] {
+        let of_kind = |members: &[Member]| -> Vec<Member> {
+            members.iter().filter(|m| m.kind == kind).cloned().collect()
+        };
+        changes.extend(diff_members(of_kind(&old_members), of_kind(&new_members)));
+    }
+    Ok(changes)
+}
+
+/// The changes between two lists of members of one kind.
+fn diff_members(old: Vec<Member>, new: Vec<Member>) -> Vec<Change> {
+    let mut changed = Vec::new();
+    let mut removed = Vec::new();
+    for o in &old {
+        match new.iter().find(|n| n.name == o.name) {
+            Some(n) if n.shape != o.shape => changed.push(Change::Changed { old: o.clone(), new: n.clone() }),
+            Some(_) => {}
+            None => removed.push(o.clone()),
+        }
+    }
+    let mut added: Vec<Member> = new.into_iter().filter(|n| !old.iter().any(|o| o.name == n.name)).collect();
+
+    // Pair each removed member with the first added one of its shape.
+    let mut renamed = Vec::new();
+    removed.retain(|r| {
+        let Some(i) = added.iter().position(|a| a.shape == r.shape) else { return true };
+        renamed.push(Change::Renamed { old: r.clone(), new: added.remove(i) });
+        false
+    });
+
+    renamed.into_iter()
+        .chain(changed)
+        .chain(removed.into_iter().map(Change::Removed))
+        .chain(added.into_iter().map(Change::Added))
+        .collect()
+}
+
+/// The class declared in `source` and its fields and methods, in
+/// declaration order.
+fn class_members(source: &str) -> Result<(Member, Vec<Member>), JzeroError> {
+    let mut tree = jzero_parser::parse_tree_with(source, LexOptions::at_level(Level::MAX)).map_err(JzeroError)?;
+    let name = declared_type(&tree).and_then(|k| k.tok.as_ref()).map(|t| t.text.clone())
+        .ok_or_else(|| JzeroError("no class declaration".to_string()))?;
+    let class = Member {
+        kind: MemberKind::Class,
+        declaration: format!("class {}", name),
+        name: name.clone(),
+        shape: "class".to_string(),
+    };
+    let result = jzero_semantic::analyze(&mut tree);
+    let global = result.global.borrow();
+    let Some(scope) = global.lookup_local(&name).and_then(|e| e.st.clone()) else {
+        return Ok((class, vec![]));
+    };
+    let members = scope.borrow().iter()
+        .filter_map(|(_, e)| match (&e.kind, &e.typ) {
+            (SymbolKind::Field, Some(typ)) => {
+                let modifier = match (e.is_const, e.is_static) {
+                    (true, true)  => " static final",
+                    (true, false) => " final",
+                    (false, true) => " static",
+                    _             => "",
+                };
+                let modifiers = format!("{}{}", e.access, modifier);
+                let (typ, dims) = element_type(typ);
+                Some(Member {
+                    kind: MemberKind::Field,
+                    name: e.sym.clone(),
+                    declaration: format!("{} {} {}{}", modifiers, jzero_type(typ), e.sym, "[]".repeat(dims)),
+                    shape: format!("{} {}{}", modifiers, jzero_type(typ), "[]".repeat(dims)),
+                })
+            }
+            (SymbolKind::Method, Some(TypeInfo::Method(typ))) => {
+                let sig = MethodSig { name: e.sym.clone(), access: e.access, is_static: e.is_static, typ: typ.clone() };
+                let mut unnamed = MethodSig { name: String::new(), ..sig.clone() };
+                unnamed.typ.parameters = typ.parameters.iter()
+                    .map(|p| Parameter { name: String::new(), param_type: p.param_type.clone() })
+                    .collect();
+                Some(Member {
+                    kind: MemberKind::Method,
+                    name: e.sym.clone(),
+                    declaration: sig.to_string(),
+                    shape: unnamed.to_string(),
+                })
+            }
+            _ => None,
+        })
+        .collect();
+    Ok((class, members))
+}
+
+/// The element type of an array type and its dimensions; a type that is
+/// no array and 0 otherwise.
+fn element_type(mut typ: &TypeInfo) -> (&TypeInfo, usize) {
+    let mut dims = 0;
+    while let TypeInfo::Array(elem) = typ {
+        typ = elem;
+        dims += 1;
+    }
+    (typ, dims)
+}
+
+// ─── Tests ───────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OLD: &str = r#"public class grades {
+    private int count;
+    public double total;
+    int scores[];
+    public static int max(int a, int b) { if (a > b) { return a; } return b; }
+    public static double average(int xs[], int n) { return 0.0; }
+    public static void report(String title) { }
+    public static void main(String argv[]) { }
+}
+"#;
+
+    fn changes(old: &str, new: &str) -> Vec<String> {
+        diff(old, new).unwrap().iter().map(|c| c.to_string()).collect()
+    }
+
+    #[test]
+    fn reports_each_kind_of_change() {
+        let new = r#"public class grades {
+    private int count;
+    public int total;
+    int marks[];
+    public static int max(int first, int second) { return first; }
+    public static double mean(int xs[], int n) { return 1.0; }
+    public static void report(String title, int width) { }
+    public static bool passed(int score) { return score > 50; }
+    public static void main(String argv[]) { }
+}
+"#;
+        assert_eq!(changes(OLD, new), [
+            "renamed field scores to marks",
+            "changed field total: public double total -> public int total",
+            "renamed method average to mean",
+            "changed method report: public static void report(String title) -> public static void report(String title, int width)",
+            "added method public static bool passed(int score)",
+        ]);
+    }
+
+    #[test]
+    fn layout_and_bodies_are_not_changes() {
+        let reformatted = crate::fmt::format_source(OLD).unwrap().replace("return b;", "return a + b;");
+        assert!(diff(OLD, &reformatted).unwrap().is_empty());
+    }
+
+    #[test]
+    fn extended_level_members_are_compared() {
+        let old = "public class counter {\n    long total;\n    public static void put(char c) { }\n}\n";
+        let new = "import java.util.Scanner;\npublic class counter {\n    int total;\n    public static void put(char c, long times) { }\n}\n";
+        assert_eq!(changes(old, new), [
+            "changed field total: public long total -> public int total",
+            "changed method put: public static void put(char c) -> public static void put(char c, long times)",
+        ]);
+    }
+
+    #[test]
+    fn a_field_made_final_is_changed() {
+        let old = "public class counter {\n    private int size;\n    public static final int MAX = 10;\n}\n";
+        let new = "public class counter {\n    private final int size = 4;\n    public static final int MAX = 10;\n}\n";
+        assert_eq!(changes(old, new), ["changed field size: private int size -> private final int size"]);
+    }
+
+    #[test]
+    fn a_renamed_class_comes_first() {
+        let new = OLD.replace("class grades", "class marks").replace("    private int count;\n", "");
+        assert_eq!(changes(OLD, &new), ["renamed class grades to marks", "removed field private int count"]);
+        assert!(diff(OLD, "public class {").is_err());
+    }
+}
@@ -0,0 +1,873 @@
+//! Compile sessions — one pipeline reused across files and edits.
+//!
+//! A [`Compiler`](crate::Compiler) compiles one source and is then thrown
+//! away.  A [`CompileSession`] outlives the files it compiles.  It owns
+//!
+//! - a [`SourceMap`] of the files added to it, each with a revision that
+//!   goes up whenever its text changes;
+//! - an [`Interner`] shared by every file, holding each name once; a file
+//!   keeps the names it uses as symbols, which
+//!   [`CompileSession::files_naming`] searches;
+//! - a diagnostics sink collecting what each phase reports, per file,
+//!   without duplicates, in a [`DiagnosticOrder`] and at the severities
+//!   [`SeverityOverrides`] set; and
+//! - the [`SessionOptions`] every file is compiled with.
+//!
+//! A [`CancellationToken`] set with [`CompileSession::set_cancellation`]
+//! stops a compile that a newer edit has made pointless.
+//!
+//! Compiling a file whose text has not changed since its last compile
+//! returns the earlier output without running the pipeline again.
+//!
+//! The front end runs on a query [`Database`], which keeps each file's
+//! tokens, tree and analysis until an edit changes them.
+//!
+//! The session counts what its compiles allocate — tree nodes, interned
+//! strings and symbol table entries — in a [`Usage`].  [`Limits`] cap
+//! them, so that a pathological generated input fails with a
+//! `resource-limit` diagnostic instead of exhausting memory.  The node and
+//! symbol caps apply to each compile, the interner cap to the session,
+//! since interned strings live as long as it does.
+//! [`CompileSession::memory_report`] measures what the analyses it holds
+//! keep alive.
+//!
+//! Native methods registered with [`CompileSession::register_native`] are
+//! static methods every file can call, implemented in Rust: a game or a
+//! robot simulation can give its programs `Robot.move(3)` to call.
+//!
+//! ```
+//! use jzero::CompileSession;
+//!
+//! let mut session = CompileSession::default();
+//! let hello = session.add_file("hello.java", "public class hello {
+//!     public static void main(String argv[]) { System.out.println(\"hi\"); }
+//! }");
+//! assert_eq!(session.run(hello, &[]).unwrap().stdout, "hi\n");
+//! ```
+//!
+//! ```
+//! use jzero::{CompileSession, NativeType, Value};
+//!
+//! let mut session = CompileSession::default();
+//! session.register_native("Dice.roll", &[NativeType::Int], NativeType::Int, |args| {
+//!     let [Value::Int(sides)] = args else { unreachable!() };
+//!     Ok(Value::Int(*sides))
+//! }).unwrap();
+//! let game = session.add_file("game.java", "public class game {
+//!     public static void main(String argv[]) { int n; n = Dice.roll(6); System.out.println(String.valueOf(n)); }
+//! }");
+//! assert_eq!(session.run(game, &[]).unwrap().stdout, "6\n");
+//! ```
+
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+use jzero_ast::tree::Tree;
+use jzero_lexer::{LexOptions, Level};
+use jzero_symtab::{MethodType, Parameter, SymTab, TypeInfo};
+use jzero_text::{Diagnostic, Severity, Span};
+
+use crate::locale::Locale;
+use crate::memory::MemoryReport;
+use crate::query::{Analysis, Database, Query};
+use crate::severity::SeverityOverrides;
+use crate::vfs::FileProvider;
+use crate::{CancellationToken, Cancelled, CompileOutput, JzeroError, NativeType, Natives, RunOutput, SandboxHost, Value};
+
+// ─── Source map ──────────────────────────────────────────────────────────────
+
+pub use jzero_text::FileId;
+
+/// One source file and the revision of its text.
+#[derive(Debug, Clone)]
+pub struct SourceFile {
+    pub path: String,
+    pub text: String,
+    /// 1 when added; increased by every change of `text`.
+    pub revision: u32,
+}
+
+/// The files of a session, by [`FileId`].
+#[derive(Debug, Clone, Default)]
+pub struct SourceMap {
+    files: Vec<SourceFile>,
+}
+
+impl SourceMap {
+    /// Add a file, or update the text of the file already at `path`.
+    pub fn add(&mut self, path: &str, text: &str) -> FileId {
+        if let Some(id) = self.find(path) {
+            self.set_text(id, text);
+            return id;
+        }
+        self.files.push(SourceFile { path: path.to_string(), text: text.to_string(), revision: 1 });
+        FileId::new(self.files.len() as u32 - 1)
+    }
+
+    /// Replace the text of `id`.  Returns whether it changed.
+    pub fn set_text(&mut self, id: FileId, text: &str) -> bool {
+        let file = &mut self.files[id.index()];
+        if file.text == text { return false; }
+        file.text = text.to_string();
+        file.revision += 1;
+        true
+    }
+
+    /// # Panics
+    /// Panics if `id` is not from this map.
+    pub fn get(&self, id: FileId) -> &SourceFile {
+        &self.files[id.index()]
+    }
+
+    pub fn find(&self, path: &str) -> Option<FileId> {
+        self.files.iter().position(|f| f.path == path).map(|i| FileId::new(i as u32))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (FileId, &SourceFile)> {
+        self.files.iter().enumerate().map(|(i, f)| (FileId::new(i as u32), f))
+    }
+
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+}
+
+// ─── Interner ────────────────────────────────────────────────────────────────
+
+/// An interned string; compare symbols instead of strings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Symbol(u32);
+
+/// Each distinct string once, shared by every file of a session.
+#[derive(Debug, Clone, Default)]
+pub struct Interner {
+    ids: HashMap<Rc<str>, Symbol>,
+    names: Vec<Rc<str>>,
+}
+
+impl Interner {
+    pub fn intern(&mut self, name: &str) -> Symbol {
+        if let Some(&sym) = self.ids.get(name) { return sym; }
+        let sym = Symbol(self.names.len() as u32);
+        let name: Rc<str> = Rc::from(name);
+        self.names.push(Rc::clone(&name));
+        self.ids.insert(name, sym);
+        sym
+    }
+
+    /// The symbol for `name`, if it has been interned.
+    pub fn get(&self, name: &str) -> Option<Symbol> {
+        self.ids.get(name).copied()
+    }
+
+    /// # Panics
+    /// Panics if `sym` is not from this interner.
+    pub fn resolve(&self, sym: Symbol) -> &str {
+        &self.names[sym.0 as usize]
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    /// Bytes of the interned strings, each counted once.
+    pub fn bytes(&self) -> usize {
+        self.names.iter().map(|name| name.len()).sum()
+    }
+}
+
+// ─── Session ─────────────────────────────────────────────────────────────────
+
+/// Settings shared by every file of a session.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SessionOptions {
+    /// Which keywords the lexer recognises.
+    pub language_level: Level,
+    pub limits: Limits,
+    /// The language [`CompileSession::render`] shows diagnostics in.
+    pub locale: Locale,
+}
+
+impl SessionOptions {
+    /// What the lexer is told: the language level, and a token cap that
+    /// bounds the tree, since a tree has fewer than twice as many nodes as
+    /// leaves and not every token becomes a leaf.  The node count itself
+    /// is checked afterwards.
+    pub(crate) fn lex_options(&self) -> LexOptions {
+        LexOptions {
+            language_level: self.language_level,
+            max_tokens: self.limits.max_nodes.map(max_tokens),
+        }
+    }
+}
+
+/// Caps on what a session may allocate; `None` is unlimited.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Limits {
+    /// Syntax tree nodes of one compile.
+    pub max_nodes: Option<usize>,
+    /// Strings in the session's interner.
+    pub max_interned: Option<usize>,
+    /// Symbol table entries of one compile, predefined ones included.
+    pub max_symbols: Option<usize>,
+}
+
+/// What a session's compiles have allocated.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Usage {
+    /// Tree nodes built, over every compile.
+    pub nodes: usize,
+    /// Strings held by the interner now.
+    pub interned: usize,
+    /// Symbol table entries created, over every compile.
+    pub symbols: usize,
+}
+
+/// A diagnostic and the file it is about.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionDiagnostic {
+    pub file: FileId,
+    pub diagnostic: Diagnostic,
+}
+
+/// How the diagnostics sink orders what it collects.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DiagnosticOrder {
+    /// In the order files were checked and their phases reported.
+    #[default]
+    Reported,
+    /// By file path, then position in the file, so that the output is the
+    /// same whatever order the files were checked in.
+    Location,
+}
+
+/// A diagnostic with the notes that explain it.  A note belongs to the
+/// diagnostic its file reported last before it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiagnosticGroup {
+    pub file: FileId,
+    pub primary: Diagnostic,
+    pub notes: Vec<Diagnostic>,
+}
+
+/// The signature of a native method taking `params` and returning `returns`.
+fn signature(params: &[NativeType], returns: NativeType) -> MethodType {
+    let type_info = |t: NativeType| match t {
+        NativeType::Int     => TypeInfo::int(),
+        NativeType::Boolean => TypeInfo::boolean(),
+        NativeType::String  => TypeInfo::string(),
+        NativeType::Void    => TypeInfo::void(),
+    };
+    let params = params.iter().enumerate()
+        .map(|(i, &t)| Parameter::new(&format!("arg{}", i), type_info(t)))
+        .collect();
+    MethodType::new(type_info(returns), params)
+}
+
+/// Split `found` into groups, in report order.  A note with nothing
+/// before it in its file stands alone.
+fn group(found: Vec<SessionDiagnostic>) -> Vec<DiagnosticGroup> {
+    let mut groups: Vec<DiagnosticGroup> = Vec::new();
+    let mut last: HashMap<FileId, usize> = HashMap::new();
+    for SessionDiagnostic { file, diagnostic } in found {
+        if diagnostic.severity == Severity::Note
+            && let Some(&i) = last.get(&file)
+        {
+            groups[i].notes.push(diagnostic);
+            continue;
+        }
+        last.insert(file, groups.len());
+        groups.push(DiagnosticGroup { file, primary: diagnostic, notes: Vec::new() });
+    }
+    groups
+}
+
+/// The last successful compile of a file.
+struct Compiled {
+    revision: u32,
+    argc: i64,
+    output: CompileOutput,
+}
+
+/// Source map, interner, diagnostics and options for a series of compiles.
+#[derive(Default)]
+pub struct CompileSession {
+    options: SessionOptions,
+    db: Database,
+    interner: Interner,
+    /// The names each file used when it was last analysed.
+    names: HashMap<FileId, HashSet<Symbol>>,
+    diagnostics: Vec<SessionDiagnostic>,
+    order: DiagnosticOrder,
+    overrides: SeverityOverrides,
+    compiled: HashMap<FileId, Compiled>,
+    cancel: Option<CancellationToken>,
+    usage: Usage,
+    natives: Natives,
+}
+
+impl CompileSession {
+    pub fn new(options: SessionOptions) -> Self {
+        CompileSession { options, db: Database::new(options.lex_options()), ..CompileSession::default() }
+    }
+
+    pub fn options(&self) -> SessionOptions {
+        self.options
+    }
+
+    pub fn sources(&self) -> &SourceMap {
+        self.db.sources()
+    }
+
+    /// The query database the session's front end runs on.
+    pub fn database(&self) -> &Database {
+        &self.db
+    }
+
+    pub fn interner(&self) -> &Interner {
+        &self.interner
+    }
+
+    /// The files whose last analysed text uses the identifier `name`, in
+    /// the order they were added.
+    pub fn files_naming(&self, name: &str) -> Vec<FileId> {
+        let Some(sym) = self.interner.get(name) else { return Vec::new() };
+        let mut files: Vec<FileId> = self.names.iter()
+            .filter(|(_, names)| names.contains(&sym))
+            .map(|(&id, _)| id)
+            .collect();
+        files.sort();
+        files
+    }
+
+    /// What the session's compiles have allocated so far.
+    pub fn usage(&self) -> Usage {
+        Usage { interned: self.interner.len(), ..self.usage }
+    }
+
+    /// Node, string, scope and `Rc` handle counts of the analyses the
+    /// session holds, and the size of its interner.
+    pub fn memory_report(&self) -> MemoryReport {
+        let mut report = MemoryReport::default();
+        for (_, analysis) in self.db.stored_analyses() {
+            report.measure(&analysis.tree, &analysis.sem.global);
+        }
+        report.interned = self.interner.len();
+        report.interned_bytes = self.interner.bytes();
+        report
+    }
+
+    /// Make later compiles stop at the next safe point once `token` is
+    /// cancelled.  A cancelled compile fails, keeps the file's earlier
+    /// diagnostics and caches nothing.
+    pub fn set_cancellation(&mut self, token: Option<CancellationToken>) {
+        self.cancel = token;
+    }
+
+    /// Register `function` as the static method `name` (`Class.method`)
+    /// that every file can call, taking `params` and returning `returns`.
+    /// Calls are type-checked against that signature like any other.
+    /// Registering a name again replaces its signature and function.
+    ///
+    /// # Errors
+    /// Returns a [`JzeroError`] if `name` is not `Class.method`, names a
+    /// class that is not native (`System`), or a parameter is `Void`.
+    pub fn register_native(
+        &mut self,
+        name: &str,
+        params: &[NativeType],
+        returns: NativeType,
+        function: impl Fn(&[Value]) -> Result<Value, String> + 'static,
+    ) -> Result<(), JzeroError> {
+        if params.contains(&NativeType::Void) {
+            return Err(JzeroError(format!("native method '{}' has a void parameter", name)));
+        }
+        let mut natives = self.natives.clone();
+        natives.register(name, params, returns, function);
+
+        // Declaring them all in a scratch scope finds a bad name before
+        // any compile does.
+        let signatures: Vec<(String, MethodType)> = natives.iter()
+            .map(|n| (n.name.clone(), signature(&n.params, n.returns)))
+            .collect();
+        let global = SymTab::new("global", None).into_rc();
+        jzero_symtab::build_predefined(&global);
+        for (name, sig) in &signatures {
+            jzero_symtab::declare_native(&global, name, sig.clone()).map_err(JzeroError)?;
+        }
+
+        self.natives = natives;
+        self.db.set_natives(signatures);
+        self.compiled.clear();
+        Ok(())
+    }
+
+    /// The native methods registered so far.
+    pub fn natives(&self) -> &Natives {
+        &self.natives
+    }
+
+    /// Add a file, or update the text of the file already at `path`.
+    pub fn add_file(&mut self, path: &str, text: &str) -> FileId {
+        self.db.add_file(path, text)
+    }
+
+    /// Add the file at `path` of `files`, or update it if already added.
+    ///
+    /// # Errors
+    /// Returns a [`JzeroError`] if `files` cannot read it.
+    pub fn load_file(&mut self, files: &dyn FileProvider, path: &str) -> Result<FileId, JzeroError> {
+        Ok(self.add_file(path, &files.read(path)?))
+    }
+
+    /// Replace the text of `id`, e.g. after an edit.  Returns whether it
+    /// changed; an unchanged file keeps its compiled output.
+    pub fn update_file(&mut self, id: FileId, text: &str) -> bool {
+        self.db.set_text(id, text)
+    }
+
+    /// Everything reported so far, each note after the diagnostic it
+    /// explains.  Analysing a file again replaces its earlier diagnostics.
+    pub fn diagnostics(&self) -> &[SessionDiagnostic] {
+        &self.diagnostics
+    }
+
+    /// [`CompileSession::diagnostics`] with each diagnostic's notes
+    /// gathered under it.
+    pub fn diagnostic_groups(&self) -> Vec<DiagnosticGroup> {
+        group(self.diagnostics.clone())
+    }
+
+    /// Order the diagnostics collected so far, and later ones, by `order`.
+    pub fn set_diagnostic_order(&mut self, order: DiagnosticOrder) {
+        self.order = order;
+        self.arrange();
+    }
+
+    /// Remap the severity of diagnostics reported from now on, e.g. to
+    /// make warnings errors.  A warning made an error fails the compile.
+    pub fn set_severity_overrides(&mut self, overrides: SeverityOverrides) {
+        self.overrides = overrides;
+    }
+
+    /// Put the sink in the session's order and drop repeats: a group with
+    /// the same file, diagnostic and notes as an earlier one.  Sorting is
+    /// stable, so diagnostics at the same place keep their report order.
+    fn arrange(&mut self) {
+        let mut groups = group(std::mem::take(&mut self.diagnostics));
+        let mut seen = HashSet::new();
+        groups.retain(|g| seen.insert((g.file, g.primary.clone(), g.notes.clone())));
+        if self.order == DiagnosticOrder::Location {
+            let sources = self.db.sources();
+            groups.sort_by(|a, b| {
+                let key = |g: &DiagnosticGroup| (sources.get(g.file).path.clone(), g.primary.span.start);
+                key(a).cmp(&key(b))
+            });
+        }
+        self.diagnostics = groups.into_iter()
+            .flat_map(|g| {
+                let file = g.file;
+                std::iter::once(g.primary).chain(g.notes)
+                    .map(move |diagnostic| SessionDiagnostic { file, diagnostic })
+            })
+            .collect();
+    }
+
+    /// Drain the diagnostics sink.
+    pub fn take_diagnostics(&mut self) -> Vec<SessionDiagnostic> {
+        std::mem::take(&mut self.diagnostics)
+    }
+
+    /// `path: error: line 3: message`, in the session's [`Locale`].
+    pub fn render(&self, d: &SessionDiagnostic) -> String {
+        let file = self.db.sources().get(d.file);
+        format!("{}: {}", file.path, self.options.locale.render(&d.diagnostic, &file.text))
+    }
+
+    /// Parse and analyse `id`, reporting to the diagnostics sink.  Returns
+    /// whether the file is free of errors.
+    pub fn check(&mut self, id: FileId) -> bool {
+        self.analyse(id).is_ok()
+    }
+
+    /// Compile `id` to bytecode, reusing the last output if neither its
+    /// text nor `argc` has changed since.
+    ///
+    /// # Errors
+    /// Returns a [`JzeroError`] if parsing, semantic analysis or code generation fails.
+    pub fn compile(&mut self, id: FileId, argc: i64) -> Result<CompileOutput, JzeroError> {
+        let revision = self.db.sources().get(id).revision;
+        if let Some(c) = self.compiled.get(&id)
+            && c.revision == revision
+            && c.argc == argc
+        {
+            return Ok(c.output.clone());
+        }
+        self.compiled.remove(&id);
+
+        let analysis = self.analyse(id)?;
+        let Some(Analysis { tree, sem }) = analysis.as_ref() else {
+            unreachable!("a file without errors has been analysed")
+        };
+        let ctx = match &self.cancel {
+            Some(token) => jzero_codegen::generate_cancellable(tree, sem, token).map_err(cancelled)?,
+            None => jzero_codegen::generate(tree, sem),
+        };
+        if !ctx.errors.is_empty() {
+            return Err(JzeroError(ctx.errors.join("\n")));
+        }
+        let tac    = jzero_codegen::emit::emit(tree, &ctx);
+        let output = jzero_codegen::pipeline::compile_bytecode(tree, &ctx, argc);
+        let output = CompileOutput { binary: output.binary, text: output.text, tac, natives: output.natives };
+        self.compiled.insert(id, Compiled { revision, argc, output: output.clone() });
+        Ok(output)
+    }
+
+    /// Compile `id` and execute it in a [`SandboxHost`].
+    ///
+    /// # Errors
+    /// Returns a [`JzeroError`] if compilation or VM execution fails.
+    pub fn run(&mut self, id: FileId, args: &[&str]) -> Result<RunOutput, JzeroError> {
+        let owned: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+        let argc = owned.len() as i64;
+        let output = self.compile(id, argc)?;
+        let mut machine = jzero_vm::J0Machine::load_with_host(&output.binary, argc, Box::new(SandboxHost::new()))
+            .map_err(JzeroError)?;
+        machine.link(&output.natives, &self.natives).map_err(JzeroError)?;
+        let stdout = machine.interp().map_err(JzeroError)?;
+        Ok(RunOutput { stdout })
+    }
+
+    /// Run the front-end queries for `id` and check the session's limits
+    /// along the way.  On success the analysis is `Some`.
+    fn analyse(&mut self, id: FileId) -> Result<Rc<Option<Analysis>>, JzeroError> {
+        let cancel = self.cancel.clone();
+        let cancel = cancel.as_ref();
+        let limits = self.options.limits;
+
+        let parses = self.db.executions(Query::Tree);
+        let tree = self.db.tree(id, cancel).map_err(cancelled)?;
+        let mut analysis = None;
+        let found: Vec<Diagnostic> = 'found: {
+            match &*tree {
+                Ok(tree) => {
+                    let nodes = tree.node_count();
+                    if self.db.executions(Query::Tree) > parses { self.usage.nodes += nodes; }
+                    if let Some(max) = limits.max_nodes.filter(|&max| nodes > max) {
+                        break 'found vec![over_limit(format!("{} tree nodes", nodes), max.to_string())];
+                    }
+                    let names = self.names.entry(id).or_default();
+                    names.clear();
+                    if !intern_names(tree, &mut self.interner, names, limits.max_interned) {
+                        let max = limits.max_interned.unwrap_or_default();
+                        break 'found vec![over_limit(format!("more than {} interned strings", max), max.to_string())];
+                    }
+                }
+                Err(diagnostic) if diagnostic.code == Some("too-many-tokens") => {
+                    let max = limits.max_nodes.unwrap_or_default();
+                    break 'found vec![over_limit(format!("more than {} tokens", max_tokens(max)), format!("{} tree nodes", max))];
+                }
+                Err(_) => {}
+            }
+
+            let analyses = self.db.executions(Query::Analysis);
+            let analysed = self.db.analysis(id, cancel).map_err(cancelled)?;
+            if let Some(a) = &*analysed {
+                let symbols = a.sem.global.borrow().total_len();
+                if self.db.executions(Query::Analysis) > analyses { self.usage.symbols += symbols; }
+                if let Some(max) = limits.max_symbols.filter(|&max| symbols > max) {
+                    break 'found vec![over_limit(format!("{} symbol table entries", symbols), max.to_string())];
+                }
+            }
+            analysis = Some(analysed);
+            self.db.diagnostics(id, cancel).map_err(cancelled)?.to_vec()
+        };
+
+        // Only a finished run replaces the file's diagnostics.
+        let found: Vec<Diagnostic> = found.into_iter().filter_map(|d| self.overrides.diagnostic(d)).collect();
+        let text = &self.db.sources().get(id).text;
+        let errors: Vec<String> = found.iter()
+            .filter(|d| d.severity == Severity::Error)
+            .map(|d| d.render(text))
+            .collect();
+        self.diagnostics.retain(|d| d.file != id);
+        self.diagnostics.extend(found.into_iter().map(|diagnostic| SessionDiagnostic { file: id, diagnostic }));
+        self.arrange();
+        match analysis {
+            Some(analysis) if errors.is_empty() => Ok(analysis),
+            _ => Err(JzeroError(errors.join("\n"))),
+        }
+    }
+}
+
+fn cancelled(c: Cancelled) -> JzeroError {
+    JzeroError(c.to_string())
+}
+
+/// The diagnostic for an input that needs `what`, beyond the cap `max`.
+fn over_limit(what: String, max: String) -> Diagnostic {
+    Diagnostic::error(Span::default(), format!("too large to analyse: {} (limit {})", what, max))
+        .with_code("resource-limit")
+}
+
+/// The token cap for a tree of at most `max_nodes` nodes.  Punctuation
+/// makes up most of the tokens that do not become leaves.
+fn max_tokens(max_nodes: usize) -> usize {
+    max_nodes.saturating_mul(4)
+}
+
+/// Intern every identifier in `tree` and add its symbol to `names`,
+/// stopping with `false` before the interner grows past `max`.
+fn intern_names(tree: &Tree, interner: &mut Interner, names: &mut HashSet<Symbol>, max: Option<usize>) -> bool {
+    if let Some(tok) = &tree.tok
+        && tok.category == "IDENTIFIER"
+    {
+        let sym = match interner.get(&tok.text) {
+            Some(sym) => sym,
+            None if max.is_some_and(|max| interner.len() >= max) => return false,
+            None => interner.intern(&tok.text),
+        };
+        names.insert(sym);
+    }
+    tree.kids.iter().all(|kid| intern_names(kid, interner, names, max))
+}
+
+// ─── Tests ───────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn program(class: &str, text: &str) -> String {
+        format!(
+            "public class {} {{\n    public static void main(String argv[]) {{\n        System.out.println(\"{}\");\n    }}\n}}\n",
+            class, text
+        )
+    }
+
+    #[test]
+    fn files_share_one_session() {
+        let mut session = CompileSession::default();
+        let a = session.add_file("a.java", &program("a", "one"));
+        let b = session.add_file("b.java", &program("b", "two"));
+        assert_ne!(a, b);
+        assert_eq!(session.run(a, &[]).unwrap().stdout, "one\n");
+        assert_eq!(session.run(b, &[]).unwrap().stdout, "two\n");
+
+        // `main`, `String`, `argv`, `System`, ... are interned once for both.
+        let names = session.interner().len();
+        assert_eq!(session.interner().resolve(session.interner().get("main").unwrap()), "main");
+        session.check(a);
+        assert_eq!(session.interner().len(), names);
+
+        assert_eq!(session.files_naming("println"), [a, b]);
+        session.update_file(b, &program("b", "two").replace("println", "print"));
+        session.check(b);
+        assert_eq!(session.files_naming("println"), [a]);
+        assert_eq!(session.files_naming("print"), [b]);
+        assert!(session.files_naming("nowhere").is_empty());
+    }
+
+    #[test]
+    fn edits_bump_the_revision() {
+        let mut session = CompileSession::default();
+        let id = session.add_file("t.java", &program("t", "old"));
+        assert_eq!(session.run(id, &[]).unwrap().stdout, "old\n");
+
+        assert!(!session.update_file(id, &program("t", "old")));
+        assert_eq!(session.sources().get(id).revision, 1);
+
+        assert_eq!(session.add_file("t.java", &program("t", "new")), id);
+        assert_eq!(session.sources().get(id).revision, 2);
+        assert_eq!(session.run(id, &[]).unwrap().stdout, "new\n");
+    }
+
+    #[test]
+    fn diagnostics_are_replaced_when_a_file_is_fixed() {
+        let mut session = CompileSession::default();
+        let bad = "public class t {\n    public static void main(String argv[]) {\n        int x;\n        int x;\n    }\n}\n";
+        let id = session.add_file("t.java", bad);
+        assert!(!session.check(id));
+        let found: Vec<String> = session.diagnostics().iter().map(|d| session.render(d)).collect();
+        assert_eq!(found, vec!["t.java: error: line 4: redeclared variable 'x' [redeclared-variable]"]);
+
+        session.add_file("broken.java", "public class {");
+        let broken = session.sources().find("broken.java").unwrap();
+        assert!(session.compile(broken, 0).is_err());
+        assert_eq!(session.diagnostics().len(), 2);
+
+        session.update_file(id, &program("t", "ok"));
+        assert!(session.check(id));
+        assert_eq!(session.take_diagnostics().iter().map(|d| d.file).collect::<Vec<_>>(), vec![broken]);
+        assert!(session.diagnostics().is_empty());
+    }
+
+    #[test]
+    fn diagnostics_sort_by_location_with_notes_grouped() {
+        let mut session = CompileSession::default();
+        let redeclared = "public class t {\n    public static void main(String argv[]) {\n        int x;\n        int x;\n    }\n}\n";
+        let b = session.add_file("b.java", redeclared);
+        let a = session.add_file("a.java", "public class {");
+        session.check(b);
+        session.check(a);
+        let files = |s: &CompileSession| s.diagnostics().iter().map(|d| d.file).collect::<Vec<_>>();
+        assert_eq!(files(&session), [b, a]);
+
+        session.set_diagnostic_order(DiagnosticOrder::Location);
+        assert_eq!(files(&session), [a, b]);
+
+        // A note stays under its diagnostic; a repeated group is dropped.
+        let at = |line: usize| Span::at(redeclared.lines().take(line - 1).map(|l| l.len() + 1).sum());
+        let note = Diagnostic { severity: Severity::Note, ..Diagnostic::error(at(3), "first declared here") };
+        let reported = [Diagnostic::error(at(4), "redeclared variable 'x'"), note, Diagnostic::warning(at(2), "w")];
+        session.diagnostics.clear();
+        for d in reported.iter().chain(&reported) {
+            session.diagnostics.push(SessionDiagnostic { file: b, diagnostic: d.clone() });
+        }
+        session.arrange();
+        let rendered: Vec<String> = session.diagnostics().iter().map(|d| session.render(d)).collect();
+        assert_eq!(rendered, [
+            "b.java: warning: line 2: w",
+            "b.java: error: line 4: redeclared variable 'x'",
+            "b.java: note: line 3: first declared here",
+        ]);
+        assert_eq!(session.diagnostic_groups()[1].notes.len(), 1);
+    }
+
+    #[test]
+    fn cancelled_compile_keeps_earlier_diagnostics() {
+        let mut session = CompileSession::default();
+        let id = session.add_file("t.java", "public class t {\n    public static void main(String argv[]) {\n        int x;\n        int x;\n    }\n}\n");
+        assert!(!session.check(id));
+
+        let token = CancellationToken::new();
+        session.set_cancellation(Some(token.clone()));
+        session.update_file(id, &program("t", "fixed"));
+        token.cancel();
+        let err = session.compile(id, 0).unwrap_err();
+        assert_eq!(err.0, "analysis cancelled");
+        assert_eq!(session.diagnostics().len(), 1);
+
+        session.set_cancellation(Some(CancellationToken::new()));
+        assert_eq!(session.run(id, &[]).unwrap().stdout, "fixed\n");
+        assert!(session.diagnostics().is_empty());
+    }
+
+    #[test]
+    fn limits_stop_oversized_inputs() {
+        let src = program("t", "hi");
+        let mut session = CompileSession::default();
+        let id = session.add_file("t.java", &src);
+        session.check(id);
+        let used = session.usage();
+        assert!(used.nodes > 10 && used.interned > 3 && used.symbols > 3, "{:?}", used);
+
+        let limited = |limits: Limits| {
+            let mut session = CompileSession::new(SessionOptions { limits, ..SessionOptions::default() });
+            let id = session.add_file("t.java", &src);
+            assert!(session.compile(id, 0).is_err());
+            session.diagnostics().iter().map(|d| session.render(d)).collect::<Vec<_>>()
+        };
+        assert_eq!(
+            limited(Limits { max_nodes: Some(5), ..Limits::default() }),
+            vec!["t.java: error: line 1: too large to analyse: more than 20 tokens (limit 5 tree nodes) [resource-limit]"]
+        );
+        let nodes = used.nodes;
+        assert_eq!(
+            limited(Limits { max_nodes: Some(nodes - 1), ..Limits::default() }),
+            vec![format!("t.java: error: line 1: too large to analyse: {} tree nodes (limit {}) [resource-limit]", nodes, nodes - 1)]
+        );
+        assert_eq!(
+            limited(Limits { max_interned: Some(2), ..Limits::default() }),
+            vec!["t.java: error: line 1: too large to analyse: more than 2 interned strings (limit 2) [resource-limit]"]
+        );
+        let symbols = used.symbols;
+        assert_eq!(
+            limited(Limits { max_symbols: Some(symbols - 1), ..Limits::default() }),
+            vec![format!("t.java: error: line 1: too large to analyse: {} symbol table entries (limit {}) [resource-limit]", symbols, symbols - 1)]
+        );
+
+        let mut session = CompileSession::new(SessionOptions {
+            limits: Limits { max_nodes: Some(nodes), max_interned: Some(used.interned), max_symbols: Some(symbols) },
+            ..SessionOptions::default()
+        });
+        let id = session.add_file("t.java", &src);
+        assert_eq!(session.run(id, &[]).unwrap().stdout, "hi\n");
+    }
+    #[test]
+    fn native_methods_are_type_checked_and_called() {
+        use std::cell::RefCell;
+
+        let moves = Rc::new(RefCell::new(Vec::new()));
+        let log = Rc::clone(&moves);
+        let mut session = CompileSession::default();
+        session.register_native("Robot.move", &[NativeType::Int, NativeType::String], NativeType::Void, move |args| {
+            log.borrow_mut().push(args.to_vec());
+            Ok(Value::Void)
+        }).unwrap();
+        session.register_native("Robot.blocked", &[], NativeType::Int, |_| Ok(Value::Int(1))).unwrap();
+
+        let id = session.add_file("r.java", "public class r {
+    public static void main(String argv[]) {
+        int b;
+        b = Robot.blocked();
+        if (b == 1) { Robot.move(2 + 1, \"left\"); }
+    }
+}");
+        session.run(id, &[]).unwrap();
+        assert_eq!(*moves.borrow(), [vec![Value::Int(3), Value::String("left".into())]]);
+        assert_eq!(session.compile(id, 0).unwrap().natives, ["Robot.blocked", "Robot.move"]);
+
+        session.update_file(id, "public class r {
+    public static void main(String argv[]) { Robot.move(true, \"up\"); }
+}");
+        assert!(session.compile(id, 0).is_err(), "a boolean is not an int");
+
+        assert!(session.register_native("System.exit", &[], NativeType::Void, |_| Ok(Value::Void)).is_err());
+        assert!(session.register_native("Robot.say", &[NativeType::Void], NativeType::Void, |_| Ok(Value::Void)).is_err());
+        session.register_native("Robot.blocked", &[], NativeType::Int, |_| Ok(Value::Boolean(true))).unwrap();
+        session.update_file(id, "public class r {
+    public static void main(String argv[]) { int b; b = Robot.blocked(); }
+}");
+        let err = session.run(id, &[]).unwrap_err();
+        assert_eq!(err.0, "Robot.blocked returned boolean, declared int");
+    }
+
+    #[test]
+    fn switch_jumps_to_its_case_and_falls_through() {
+        let options = SessionOptions { language_level: Level::Extended, ..SessionOptions::default() };
+        let mut session = CompileSession::new(options);
+        let id = session.add_file("s.java", "public class s {
+    public static void main(String argv[]) {
+        int i;
+        i = 0;
+        while (i < 5) {
+            switch (i) {
+                case 0: System.out.println(\"zero\"); break;
+                case 1: case 2: System.out.println(\"small\");
+                case 3: System.out.println(\"three or less\"); break;
+                default: System.out.println(\"big\");
+            }
+            i = i + 1;
+        }
+        switch (i) { case 1: System.out.println(\"never\"); }
+    }
+}");
+        assert_eq!(session.run(id, &[]).unwrap().stdout,
+            "zero\nsmall\nthree or less\nsmall\nthree or less\nthree or less\nbig\n");
+
+        session.update_file(id, "public class s {
+    public static void main(String argv[]) { switch (1) { case 1: case 1: } }
+}");
+        assert!(!session.check(id));
+        assert_eq!(session.render(&session.diagnostics()[0]), "s.java: error: line 2: duplicate case 1 in switch (first on line 2) [duplicate-case-label]");
+    }
+}
@@ -0,0 +1,147 @@
+//! Severity overrides — `--deny`, `--warn` and `--allow`.
+//!
+//! A course can hold submissions to a stricter standard than the defaults
+//! without touching the checks themselves: overrides remap the severity of
+//! what is reported, by code, before anything is rendered.
+//!
+//! ```toml
+//! [diagnostics]
+//! deny  = ["warnings"]        # every warning is an error
+//! warn  = ["method-length"]   # ...except this one
+//! allow = ["file-name"]       # and this one is not reported at all
+//! ```
+//!
+//! The same settings come from the command line as `--deny warnings`,
+//! `--warn CODE` and `--allow CODE`, applied after the configuration so
+//! that a flag wins.  A code set explicitly wins over `warnings`.
+//!
+//! Check findings can be remapped either way.  Compiler diagnostics can
+//! only be made stricter or silenced while they are warnings: an error
+//! means there is no program to run, and no setting changes that.
+
+use std::collections::BTreeMap;
+
+use jzero_text::{Diagnostic, Severity};
+
+use crate::checks::Finding;
+use crate::config::{Config, Level, Value};
+
+/// The pseudo-code that stands for every warning.
+pub const WARNINGS: &str = "warnings";
+
+/// Levels set for diagnostic codes.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SeverityOverrides {
+    codes: BTreeMap<String, Level>,
+}
+
+impl SeverityOverrides {
+    pub fn new() -> Self {
+        SeverityOverrides::default()
+    }
+
+    /// The overrides in the `[diagnostics]` section of `config`.
+    pub fn from_config(config: &Config) -> Self {
+        let mut overrides = SeverityOverrides::new();
+        for (key, level) in [("allow", Level::Off), ("warn", Level::Warn), ("deny", Level::Error)] {
+            if let Some(Value::List(codes)) = config.get(&format!("diagnostics.{}", key)) {
+                for code in codes {
+                    if let Value::Str(code) = code { overrides.set(code, level); }
+                }
+            }
+        }
+        overrides
+    }
+
+    /// Report `code` at `level`, replacing any earlier setting for it.
+    /// `code` may be [`WARNINGS`].
+    pub fn set(&mut self, code: &str, level: Level) {
+        self.codes.insert(code.to_string(), level);
+    }
+
+    /// Apply a `--deny`, `--warn` or `--allow` flag.  Returns false if
+    /// `flag` is none of them.
+    pub fn apply_flag(&mut self, flag: &str, code: &str) -> bool {
+        let level = match flag {
+            "--deny"  => Level::Error,
+            "--warn"  => Level::Warn,
+            "--allow" => Level::Off,
+            _ => return false,
+        };
+        self.set(code, level);
+        true
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.codes.is_empty()
+    }
+
+    /// The level for something with `code` that would be reported at
+    /// `level`.
+    pub fn level(&self, code: Option<&str>, level: Level) -> Level {
+        if let Some(&set) = code.and_then(|c| self.codes.get(c)) {
+            return set;
+        }
+        match self.codes.get(WARNINGS) {
+            Some(&set) if level == Level::Warn => set,
+            _ => level,
+        }
+    }
+
+    /// `finding` at its remapped level, or `None` if it is allowed.
+    pub fn finding(&self, mut finding: Finding) -> Option<Finding> {
+        finding.level = self.level(Some(finding.check), finding.level);
+        (finding.level != Level::Off).then_some(finding)
+    }
+
+    /// `diagnostic` at its remapped severity, or `None` if it is allowed.
+    /// Errors and notes are kept as they are.
+    pub fn diagnostic(&self, mut diagnostic: Diagnostic) -> Option<Diagnostic> {
+        if diagnostic.severity != Severity::Warning { return Some(diagnostic); }
+        match self.level(diagnostic.code, Level::Warn) {
+            Level::Off   => return None,
+            Level::Warn  => {}
+            Level::Error => diagnostic.severity = Severity::Error,
+        }
+        Some(diagnostic)
+    }
+}
+
+// ─── Tests ───────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jzero_text::Span;
+
+    fn finding(check: &'static str, level: Level) -> Finding {
+        Finding { check, level, lineno: 1, message: String::new() }
+    }
+
+    #[test]
+    fn codes_win_over_warnings() {
+        let config = Config::parse("[diagnostics]\ndeny = [\"warnings\"]\nwarn = [\"method-length\"]\n").unwrap();
+        let mut o = SeverityOverrides::from_config(&config);
+        assert!(o.apply_flag("--allow", "file-name"));
+        assert!(!o.apply_flag("--forbid", "file-name"));
+
+        assert_eq!(o.finding(finding("class-name", Level::Warn)).unwrap().level, Level::Error);
+        assert_eq!(o.finding(finding("method-length", Level::Warn)).unwrap().level, Level::Warn);
+        assert_eq!(o.finding(finding("file-name", Level::Error)), None);
+        assert_eq!(o.finding(finding("unknown-lint", Level::Error)).unwrap().level, Level::Error);
+    }
+
+    #[test]
+    fn only_warnings_of_the_compiler_change() {
+        let mut o = SeverityOverrides::new();
+        o.set(WARNINGS, Level::Error);
+        o.set("unknown-char", Level::Off);
+        let warning = Diagnostic::warning(Span::at(0), "w").with_code("unused");
+        assert_eq!(o.diagnostic(warning).unwrap().severity, Severity::Error);
+        let error = Diagnostic::error(Span::at(0), "e").with_code("unknown-char");
+        assert_eq!(o.diagnostic(error.clone()), Some(error));
+
+        o.set("unused", Level::Off);
+        assert_eq!(o.diagnostic(Diagnostic::warning(Span::at(0), "w").with_code("unused")), None);
+    }
+}
@@ -0,0 +1,293 @@
+//! Method stubs (`j0 stub`) — fill in the methods a class is missing.
+//!
+//! The methods a class must have are given by a *spec*: an interface it
+//! implements, or another Jzero class whose methods have the signatures
+//! wanted.  Both files are analysed and their scopes compared; every method
+//! of the spec with no member of that name in the target gets a stub that
+//! returns the zero value of its type:
+//!
+//! ```
+//! let spec = "public class shape {\n    public static double area(double w, double h) { return w * h; }\n}\n";
+//! let src = "public class square {\n}\n";
+//! let edits = jzero::stub::stub_edits(spec, src).unwrap();
+//! assert_eq!(jzero_text::apply_edits(src, &edits), "public class square {\n\
+//! \n    public static double area(double w, double h) {\n        return 0.0;\n    }\n}\n");
+//! ```
+//!
+//! The stubs go before the class's closing brace, in spec order, laid out
+//! as [`crate::fmt`] would.  A method of the target with the right name
+//! but another signature is left alone: Jzero has no overloading, so no
+//! stub could sit beside it — [`mismatched_methods`] reports those.
+//!
+//! [`implemented_interfaces`] names the specs a class declares itself, in
+//! its `implements` list.  Files are parsed at the highest language level.
+
+use std::fmt;
+
+use jzero_ast::tree::Tree;
+use jzero_lexer::token::Token;
+use jzero_lexer::{Level, LexOptions};
+use jzero_symtab::entry::{Access, SymbolKind};
+use jzero_symtab::{MethodType, TypeInfo};
+use jzero_text::TextEdit;
+
+use crate::JzeroError;
+use crate::project::declared_type;
+
+const INDENT: &str = "    ";
+
+/// A method of a class, as its symbol table entry describes it.
+#[derive(Debug, Clone)]
+pub struct MethodSig {
+    pub name: String,
+    pub access: Access,
+    pub is_static: bool,
+    pub typ: MethodType,
+}
+
+impl MethodSig {
+    /// The stub for this method: its header and a body returning the
+    /// zero value of its type, indented one level.
+    pub fn stub(&self) -> String {
+        let mut out = format!("{}{} {{\n", INDENT, self);
+        if let Some(value) = zero_value(&self.typ.return_type) {
+            out.push_str(&format!("{}{}return {};\n", INDENT, INDENT, value));
+        }
+        out.push_str(&format!("{}}}\n", INDENT));
+        out
+    }
+}
+
+/// The method header as written in Jzero:
+/// `public static int max(int a[], int n)`.
+impl fmt::Display for MethodSig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ", self.access)?;
+        if self.is_static { write!(f, "static ")?; }
+        write!(f, "{} {}(", jzero_type(&self.typ.return_type), self.name)?;
+        for (i, p) in self.typ.parameters.iter().enumerate() {
+            if i > 0 { write!(f, ", ")?; }
+            // Arrays are declared on the name: `int a[]`.
+            let mut typ = &*p.param_type;
+            let mut dims = 0;
+            while let TypeInfo::Array(elem) = typ {
+                typ = elem;
+                dims += 1;
+            }
+            write!(f, "{} {}{}", jzero_type(typ), p.name, "[]".repeat(dims))?;
+        }
+        write!(f, ")")
+    }
+}
+
+/// A type as Jzero spells it: the type info says `boolean` where the
+/// source keyword is `bool`.
+pub(crate) fn jzero_type(typ: &TypeInfo) -> String {
+    match typ {
+        TypeInfo::Base(b) if b == "boolean" => "bool".to_string(),
+        TypeInfo::Array(elem) => format!("{}[]", jzero_type(elem)),
+        _ => typ.display().to_string(),
+    }
+}
+
+/// What a stub returns; `None` for `void`.
+fn zero_value(typ: &TypeInfo) -> Option<&'static str> {
+    match typ {
+        TypeInfo::Base(b) => match b.as_str() {
+            "void"    => None,
+            "int"     => Some("0"),
+            "long"    => Some("0L"),
+            "char"    => Some("'\\0'"),
+            "double"  => Some("0.0"),
+            "boolean" => Some("false"),
+            _         => Some("null"),
+        },
+        _ => Some("null"),
+    }
+}
+
+fn parse(source: &str) -> Result<Tree, JzeroError> {
+    jzero_parser::parse_tree_with(source, LexOptions::at_level(Level::MAX)).map_err(JzeroError)
+}
+
+/// The name of the class or interface declared in `source` and its
+/// methods, in declaration order.  Constructors are not methods.
+///
+/// # Errors
+/// Returns a [`JzeroError`] if `source` does not parse.
+pub fn class_methods(source: &str) -> Result<(String, Vec<MethodSig>), JzeroError> {
+    let mut tree = parse(source)?;
+    let class = declared_type(&tree).and_then(|k| k.tok.as_ref()).map(|t| t.text.clone())
+        .ok_or_else(|| JzeroError("no class declaration".to_string()))?;
+    let result = jzero_semantic::analyze(&mut tree);
+    let global = result.global.borrow();
+    let Some(scope) = global.lookup_local(&class).and_then(|e| e.st.clone()) else {
+        return Ok((class, vec![]));
+    };
+    let methods = scope.borrow().entries_of_kind(SymbolKind::Method)
+        .filter_map(|e| match &e.typ {
+            Some(TypeInfo::Method(typ)) => Some(MethodSig {
+                name: e.sym.clone(),
+                access: e.access,
+                is_static: e.is_static,
+                typ: typ.clone(),
+            }),
+            _ => None,
+        })
+        .collect();
+    Ok((class, methods))
+}
+
+/// The interfaces the class declared in `source` implements, in the order
+/// of its `implements` list.
+///
+/// # Errors
+/// Returns a [`JzeroError`] if `source` does not parse.
+pub fn implemented_interfaces(source: &str) -> Result<Vec<String>, JzeroError> {
+    fn list(tree: &Tree) -> Option<&Tree> {
+        match tree.sym.as_str() {
+            "InterfaceList" => Some(tree),
+            "CompilationUnit" | "ClassDecl" => tree.kids.iter().find_map(list),
+            _ => None,
+        }
+    }
+    let tree = parse(source)?;
+    let names = list(&tree).map(|l| l.kids.iter().filter_map(|k| k.tok.as_ref()).map(|t| t.text.clone()).collect());
+    Ok(names.unwrap_or_default())
+}
+
+/// The methods of `spec` that `source` has no method of that name for.
+///
+/// # Errors
+/// Returns a [`JzeroError`] if either file does not parse.
+pub fn missing_methods(spec: &str, source: &str) -> Result<Vec<MethodSig>, JzeroError> {
+    let (_, wanted) = class_methods(spec)?;
+    let (_, have) = class_methods(source)?;
+    Ok(wanted.into_iter().filter(|w| !have.iter().any(|h| h.name == w.name)).collect())
+}
+
+/// The methods of `source` named as in `spec` but with another signature,
+/// each paired with the spec's.
+///
+/// # Errors
+/// Returns a [`JzeroError`] if either file does not parse.
+pub fn mismatched_methods(spec: &str, source: &str) -> Result<Vec<(MethodSig, MethodSig)>, JzeroError> {
+    let (_, wanted) = class_methods(spec)?;
+    let (_, have) = class_methods(source)?;
+    Ok(have.into_iter()
+        .filter_map(|h| {
+            let w = wanted.iter().find(|w| w.name == h.name)?;
+            (w.to_string() != h.to_string()).then(|| (h, w.clone()))
+        })
+        .collect())
+}
+
+/// The edit that adds a stub for each of [`missing_methods`] before the
+/// closing brace of the class in `source` — empty if none are missing.
+///
+/// # Errors
+/// Returns a [`JzeroError`] if either file does not parse.
+pub fn stub_edits(spec: &str, source: &str) -> Result<Vec<TextEdit>, JzeroError> {
+    let missing = missing_methods(spec, source)?;
+    if missing.is_empty() { return Ok(vec![]); }
+
+    // The class is the whole file, so its brace is the last one.
+    let tokens = jzero_lexer::lex(source).tokens;
+    let Some(close) = tokens.iter().rev().find(|t| t.token == Token::RBrace) else {
+        return Err(JzeroError("class has no closing brace".to_string()));
+    };
+    let at = close.start;
+    let line_start = source[..at].rfind('\n').map_or(0, |i| i + 1);
+    let (at, mut text) = if source[line_start..at].trim().is_empty() {
+        (line_start, String::new())
+    } else {
+        (at, "\n".to_string())
+    };
+    for sig in &missing {
+        text.push('\n');
+        text.push_str(&sig.stub());
+    }
+    Ok(vec![TextEdit::insert(at, &text)])
+}
+
+/// `source` with [`stub_edits`] applied.
+///
+/// # Errors
+/// Returns a [`JzeroError`] if either file does not parse.
+pub fn add_stubs(spec: &str, source: &str) -> Result<String, JzeroError> {
+    Ok(jzero_text::apply_edits(source, &stub_edits(spec, source)?))
+}
+
+// ─── Tests ───────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jzero_text::apply_edits;
+
+    const SPEC: &str = r#"public class shape {
+    public static double area(double w, double h) { return w * h; }
+    private static bool empty(int xs[]) { return true; }
+    public static void draw(String label) { }
+    public static String name() { return "shape"; }
+}
+"#;
+
+    #[test]
+    fn stubs_the_missing_methods_in_spec_order() {
+        let src = "public class square {\n    public static String name() {\n        return \"square\";\n    }\n}\n";
+        let out = apply_edits(src, &stub_edits(SPEC, src).unwrap());
+        assert_eq!(out, r#"public class square {
+    public static String name() {
+        return "square";
+    }
+
+    public static double area(double w, double h) {
+        return 0.0;
+    }
+
+    private static bool empty(int xs[]) {
+        return false;
+    }
+
+    public static void draw(String label) {
+    }
+}
+"#);
+        assert!(crate::fmt::is_formatted(&out).unwrap());
+        assert!(stub_edits(SPEC, &out).unwrap().is_empty());
+        let (_, methods) = class_methods(&out).unwrap();
+        assert_eq!(methods.len(), 4);
+    }
+
+    #[test]
+    fn brace_on_the_class_line() {
+        let src = "public class square { }";
+        let out = add_stubs(SPEC, src).unwrap();
+        assert!(out.starts_with("public class square { \n\n    public static double area("), "{}", out);
+        assert!(out.ends_with("    public static String name() {\n        return null;\n    }\n}"), "{}", out);
+        assert!(jzero_parser::parse_tree(&out).is_ok());
+    }
+
+    #[test]
+    fn a_different_signature_is_a_mismatch_not_a_gap() {
+        let src = "public class square {\n    public static int area(int side) { return side * side; }\n}\n";
+        let missing: Vec<String> = missing_methods(SPEC, src).unwrap().iter().map(|m| m.name.clone()).collect();
+        assert_eq!(missing, ["empty", "draw", "name"]);
+        let mismatched = mismatched_methods(SPEC, src).unwrap();
+        assert_eq!(mismatched.len(), 1);
+        assert_eq!(mismatched[0].0.to_string(), "public static int area(int side)");
+        assert_eq!(mismatched[0].1.to_string(), "public static double area(double w, double h)");
+    }
+
+    #[test]
+    fn an_implemented_interface_is_a_spec() {
+        let shape = "public interface Shape {\n    double area(double w, double h);\n    String name();\n}\n";
+        let src = "public class Rect implements Shape, Named {\n    public String name() { return \"rect\"; }\n}\n";
+        assert_eq!(implemented_interfaces(src).unwrap(), ["Shape", "Named"]);
+        assert!(implemented_interfaces(shape).unwrap().is_empty());
+        let out = add_stubs(shape, src).unwrap();
+        assert!(out.ends_with("    public double area(double w, double h) {\n        return 0.0;\n    }\n}\n"), "{}", out);
+        assert!(stub_edits(shape, &out).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,201 @@
+//! Inline suppression of convention checks.
+//!
+//! A line comment of the form
+//!
+//! ```text
+//! // j0:allow(method-length, nesting-depth)
+//! ```
+//!
+//! turns the named checks off locally.  After code on the same line it
+//! covers that line.  On a line of its own it covers the next line of code
+//! and, if a class, method, constructor or field declaration starts there,
+//! the whole declaration.  Names that are not checks are reported as
+//! `unknown-lint`.
+
+use std::ops::RangeInclusive;
+
+use jzero_ast::tree::Tree;
+use jzero_lexer::TriviaKind;
+
+use crate::checks::{CHECKS, Finding};
+use crate::config::{Config, Level};
+
+const MARKER: &str = "j0:allow(";
+
+/// One check turned off for a range of lines.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Suppression {
+    pub check: String,
+    pub lines: RangeInclusive<usize>,
+}
+
+/// A `j0:allow` comment before it is resolved to lines.
+struct Allow {
+    names: Vec<String>,
+    lineno: usize,
+    /// Whether code precedes the comment on its line.
+    trailing: bool,
+    /// The first line of code after the comment.
+    next_code: Option<usize>,
+}
+
+/// The suppressions in `source`, plus an `unknown-lint` finding for each
+/// name that is not a check.
+pub fn suppressions(source: &str, config: &Config) -> (Vec<Suppression>, Vec<Finding>) {
+    let allows = allow_comments(source);
+    if allows.is_empty() { return (vec![], vec![]); }
+    let decls = jzero_parser::parse_tree(source)
+        .map(|tree| { let mut d = Vec::new(); declarations(&tree, &mut d); d })
+        .unwrap_or_default();
+    let unknown_level = config.check_level("unknown-lint", Level::Warn);
+
+    let mut sups = Vec::new();
+    let mut findings = Vec::new();
+    for allow in allows {
+        let lines = if allow.trailing {
+            allow.lineno..=allow.lineno
+        } else {
+            let Some(next) = allow.next_code else { continue };
+            let end = decls.iter()
+                .filter(|d| *d.start() == next)
+                .map(|d| *d.end())
+                .max()
+                .unwrap_or(next);
+            next..=end
+        };
+        for name in allow.names {
+            if !CHECKS.contains(&name.as_str()) {
+                if unknown_level != Level::Off {
+                    findings.push(Finding {
+                        check: "unknown-lint",
+                        level: unknown_level,
+                        lineno: allow.lineno,
+                        message: format!("unknown check '{}' in j0:allow", name),
+                    });
+                }
+                continue;
+            }
+            sups.push(Suppression { check: name, lines: lines.clone() });
+        }
+    }
+    (sups, findings)
+}
+
+/// Drop the findings covered by a suppression.
+pub fn apply(findings: Vec<Finding>, sups: &[Suppression]) -> Vec<Finding> {
+    findings.into_iter()
+        .filter(|f| !sups.iter().any(|s| s.check == f.check && s.lines.contains(&f.lineno)))
+        .collect()
+}
+
+/// Every `j0:allow` line comment, with where it sits.
+fn allow_comments(source: &str) -> Vec<Allow> {
+    let Ok(file) = jzero_lexer::lex_lossless(source) else { return vec![] };
+    let mut allows: Vec<Allow> = Vec::new();
+    let mut line = 1;
+    let mut line_has_code = false;
+
+    for tok in &file.tokens {
+        for t in &tok.leading {
+            if t.kind == TriviaKind::LineComment
+                && let Some(names) = parse_allow(&t.text)
+            {
+                allows.push(Allow { names, lineno: line, trailing: line_has_code, next_code: None });
+            }
+            if t.newlines() > 0 {
+                line += t.newlines();
+                line_has_code = false;
+            }
+        }
+        for a in allows.iter_mut().filter(|a| a.next_code.is_none()) {
+            a.next_code = Some(line);
+        }
+        line += tok.text.matches('\n').count();
+        line_has_code = true;
+    }
+    allows
+}
+
+/// The names in `// j0:allow(a, b)`, if the comment is one.
+fn parse_allow(comment: &str) -> Option<Vec<String>> {
+    let body = comment.trim_start_matches('/').trim();
+    let inner = body.strip_prefix(MARKER)?.strip_suffix(')')?;
+    Some(inner.split(',').map(|n| n.trim().to_string()).filter(|n| !n.is_empty()).collect())
+}
+
+/// Line ranges of class, method, constructor and field declarations.
+fn declarations(tree: &Tree, out: &mut Vec<RangeInclusive<usize>>) {
+    if matches!(tree.sym.as_str(), "ClassDecl" | "MethodDecl" | "ConstructorDecl" | "FieldDecl") {
+        let lines = leaf_lines(tree);
+        if let (Some(start), Some(end)) = (lines.iter().min(), lines.iter().max()) {
+            out.push(*start..=*end);
+        }
+    }
+    for kid in &tree.kids {
+        declarations(kid, out);
+    }
+}
+
+fn leaf_lines(tree: &Tree) -> Vec<usize> {
+    let mut lines: Vec<usize> = tree.tok.iter().map(|t| t.lineno).collect();
+    for kid in &tree.kids {
+        lines.extend(leaf_lines(kid));
+    }
+    lines
+}
+
+// ─── Tests ───────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+    use crate::checks::run_checks;
+
+    const CONFIG: &str = "[checks]\nvariable-name = \"warn\"\nmethod-name = \"warn\"\n";
+
+    fn findings(src: &str) -> Vec<String> {
+        let config = Config::parse(CONFIG).unwrap();
+        run_checks(Path::new("T.java"), src, &config).iter().map(|f| f.to_string()).collect()
+    }
+
+    #[test]
+    fn comment_covers_the_next_declaration() {
+        let found = findings(r#"
+public class T {
+    // j0:allow(method-name, variable-name)
+    public static void Do_it(int Max) {
+        int Count;
+    }
+    int Total;
+}
+"#);
+        assert_eq!(found, vec![
+            "warning: line 7: variable 'Total' should be lowerCamelCase, e.g. 'total' [variable-name]",
+        ]);
+    }
+
+    #[test]
+    fn trailing_comment_covers_its_line() {
+        let found = findings(r#"
+public class T {
+    int Total; // j0:allow(variable-name)
+    int Other;
+}
+"#);
+        assert_eq!(found.len(), 1, "{:?}", found);
+        assert!(found[0].contains("'Other'"), "{:?}", found);
+    }
+
+    #[test]
+    fn unknown_check_name_is_reported() {
+        let found = findings(r#"
+public class T {
+    // j0:allow(unused_variable)
+    int total;
+}
+"#);
+        assert_eq!(found, vec!["warning: line 3: unknown check 'unused_variable' in j0:allow [unknown-lint]"]);
+    }
+}
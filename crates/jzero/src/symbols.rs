@@ -0,0 +1,224 @@
+//! Workspace symbol search — every declared name of a set of files.
+//!
+//! A [`ScopeGraph`] analyses each file of a workspace and keeps its global
+//! scope.  Symbols are named by the path of scopes they are declared in,
+//! so `argv` in `main` of class `hello` is `hello.main.argv`:
+//!
+//! - [`ScopeGraph::resolve_qualified`] finds the symbol with a given
+//!   qualified name, predefined ones such as `System.out.println` included;
+//! - [`ScopeGraph::find_symbols`] lists the declared symbols whose name
+//!   matches a pattern, for an editor's workspace symbol search and
+//!   `j0 find-symbol`.
+//!
+//! ```
+//! let hello = "public class hello {\n    public static void main(String argv[]) { }\n}\n";
+//! let graph = jzero::symbols::ScopeGraph::from_sources([("hello.java", hello)]).unwrap();
+//! let argv = graph.resolve_qualified("hello.main.argv").unwrap();
+//! assert_eq!((argv.path.as_deref(), argv.lineno), (Some("hello.java"), 2));
+//! assert_eq!(graph.find_symbols("ma*").len(), 1);
+//! ```
+
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
+
+use jzero_symtab::entry::SymbolKind;
+use jzero_symtab::{FileId, SymTab, SymTabEntry, SymbolOrigin};
+
+use crate::JzeroError;
+use crate::vfs::{FileProvider, RealFs};
+
+/// A symbol found by a [`ScopeGraph`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct SymbolInfo {
+    pub name: String,
+    /// The names of the enclosing scopes and the symbol's, joined by `.`.
+    pub qualified: String,
+    pub kind: SymbolKind,
+    /// The file declaring the symbol; `None` for a predefined one.
+    pub path: Option<String>,
+    /// Line of the declaration, or 0 for a predefined symbol.
+    pub lineno: usize,
+}
+
+impl SymbolInfo {
+    fn new(entry: &SymTabEntry, qualified: String, graph: &ScopeGraph) -> Self {
+        let path = match entry.origin {
+            SymbolOrigin::Predefined | SymbolOrigin::Library | SymbolOrigin::Native => None,
+            SymbolOrigin::UserFile(file) => graph.files.get(file.index()).map(|(path, _)| path.clone()),
+        };
+        SymbolInfo { name: entry.sym.clone(), qualified, kind: entry.kind.clone(), path, lineno: entry.lineno }
+    }
+}
+
+/// `hello.java:2: param hello.main.argv`
+impl fmt::Display for SymbolInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.path {
+            Some(path) => write!(f, "{}:{}: {} {}", path, self.lineno, self.kind, self.qualified),
+            None => write!(f, "<predefined>: {} {}", self.kind, self.qualified),
+        }
+    }
+}
+
+/// The global scopes of the files of a workspace.
+#[derive(Debug, Default)]
+pub struct ScopeGraph {
+    files: Vec<(String, Rc<RefCell<SymTab>>)>,
+}
+
+impl ScopeGraph {
+    /// Analyse each `(path, source)` pair.  Semantic errors do not stop a
+    /// file's symbols from being recorded.
+    ///
+    /// # Errors
+    /// Returns a [`JzeroError`] naming the first file that does not parse.
+    pub fn from_sources<'a>(
+        sources: impl IntoIterator<Item = (&'a str, &'a str)>,
+    ) -> Result<Self, JzeroError> {
+        let mut graph = ScopeGraph::default();
+        for (i, (path, source)) in sources.into_iter().enumerate() {
+            let mut tree = jzero_parser::parse_tree(source)
+                .map_err(|e| JzeroError(format!("{}: {}", path, e)))?;
+            let result = jzero_semantic::analyze_file(&mut tree, FileId::new(i as u32), None)
+                .expect("analysis without a token cannot be cancelled");
+            graph.files.push((path.to_string(), result.global));
+        }
+        Ok(graph)
+    }
+
+    /// Read and analyse every file in `paths`.
+    ///
+    /// # Errors
+    /// Returns a [`JzeroError`] if a file cannot be read or does not parse.
+    pub fn load(paths: &[String]) -> Result<Self, JzeroError> {
+        ScopeGraph::load_from(&RealFs, paths)
+    }
+
+    /// Read every file in `paths` from `files` and analyse it.
+    ///
+    /// # Errors
+    /// Returns a [`JzeroError`] if a file cannot be read or does not parse.
+    pub fn load_from(files: &dyn FileProvider, paths: &[String]) -> Result<Self, JzeroError> {
+        let mut sources = Vec::new();
+        for path in paths {
+            sources.push((path.as_str(), files.read(path)?));
+        }
+        ScopeGraph::from_sources(sources.iter().map(|(p, s)| (*p, s.as_str())))
+    }
+
+    /// The symbol named `qualified`, e.g. `hello.main.argv`.  A name
+    /// declared in a file wins over a predefined one; between files, the
+    /// first file wins.
+    pub fn resolve_qualified(&self, qualified: &str) -> Option<SymbolInfo> {
+        let path: Vec<&str> = qualified.split('.').collect();
+        let mut predefined = None;
+        for (_, global) in &self.files {
+            let Some(entry) = jzero_symtab::resolve_qualified(&global.borrow(), &path) else { continue };
+            let info = SymbolInfo::new(&entry, qualified.to_string(), self);
+            if info.path.is_some() { return Some(info); }
+            predefined.get_or_insert(info);
+        }
+        predefined
+    }
+
+    /// The symbols declared in the files whose name matches `pattern`, by
+    /// file and then in declaration order.
+    ///
+    /// A pattern with `*` (any run of characters) or `?` (any one) is a
+    /// glob that must match the whole name; any other pattern matches the
+    /// names it is a prefix of.  A pattern with a `.` is matched against
+    /// qualified names: `hello.main.*` lists the symbols of `main`.
+    pub fn find_symbols(&self, pattern: &str) -> Vec<SymbolInfo> {
+        let mut found = Vec::new();
+        for (_, global) in &self.files {
+            collect(&global.borrow(), "", self, &mut |info| {
+                let name = if pattern.contains('.') { &info.qualified } else { &info.name };
+                if matches(pattern, name) {
+                    found.push(info);
+                }
+            });
+        }
+        found
+    }
+}
+
+/// Report every declared symbol of `scope` and the scopes in it, each
+/// qualified by `prefix`.
+fn collect(scope: &SymTab, prefix: &str, graph: &ScopeGraph, f: &mut impl FnMut(SymbolInfo)) {
+    for (name, entry) in scope.iter() {
+        // A method's `return` entry is made up and has no declaration line.
+        if entry.origin.is_predefined() || entry.lineno == 0 { continue; }
+        let qualified = if prefix.is_empty() { name.clone() } else { format!("{}.{}", prefix, name) };
+        f(SymbolInfo::new(entry, qualified.clone(), graph));
+        if let Some(st) = &entry.st {
+            collect(&st.borrow(), &qualified, graph, f);
+        }
+    }
+}
+
+fn matches(pattern: &str, name: &str) -> bool {
+    if !pattern.contains(['*', '?']) {
+        return name.starts_with(pattern);
+    }
+    glob(&pattern.chars().collect::<Vec<_>>(), &name.chars().collect::<Vec<_>>())
+}
+
+pub(crate) fn glob(pattern: &[char], name: &[char]) -> bool {
+    match pattern.split_first() {
+        None => name.is_empty(),
+        Some(('*', rest)) => (0..=name.len()).any(|i| glob(rest, &name[i..])),
+        Some((c, rest)) => match name.split_first() {
+            Some((n, name)) => (*c == '?' || c == n) && glob(rest, name),
+            None => false,
+        },
+    }
+}
+
+// ─── Tests ───────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HELLO: &str = r#"public class hello {
+    public static void main(String argv[]) {
+        int count;
+        count = max(1, 2);
+    }
+    public static int max(int a, int b) { return a; }
+}
+"#;
+    const POINT: &str = "public class Point {\n    int x;\n    int count;\n}\n";
+
+    fn graph() -> ScopeGraph {
+        ScopeGraph::from_sources([("hello.java", HELLO), ("Point.java", POINT)]).unwrap()
+    }
+
+    fn names(found: &[SymbolInfo]) -> Vec<&str> {
+        found.iter().map(|s| s.qualified.as_str()).collect()
+    }
+
+    #[test]
+    fn resolves_qualified_names() {
+        let g = graph();
+        let count = g.resolve_qualified("hello.main.count").unwrap();
+        assert_eq!((count.kind, count.lineno), (SymbolKind::Local, 3));
+        assert_eq!(g.resolve_qualified("Point.x").unwrap().path.as_deref(), Some("Point.java"));
+        let println = g.resolve_qualified("System.out.println").unwrap();
+        assert_eq!((println.kind, println.path), (SymbolKind::Method, None));
+        assert!(g.resolve_qualified("hello.max.count").is_none());
+    }
+
+    #[test]
+    fn prefixes_and_globs() {
+        let g = graph();
+        assert_eq!(names(&g.find_symbols("ma")), ["hello.main", "hello.max"]);
+        assert_eq!(names(&g.find_symbols("count")), ["hello.main.count", "Point.count"]);
+        assert_eq!(names(&g.find_symbols("?")), ["hello.max.a", "hello.max.b", "Point.x"]);
+        assert_eq!(names(&g.find_symbols("hello.main.*")), ["hello.main.argv", "hello.main.count"]);
+        assert_eq!(names(&g.find_symbols("*.count")), ["hello.main.count", "Point.count"]);
+        assert!(g.find_symbols("System").is_empty(), "predefined symbols are not listed");
+        assert_eq!(g.find_symbols("Point")[0].to_string(), "Point.java:1: class Point");
+    }
+}
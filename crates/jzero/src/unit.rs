@@ -0,0 +1,148 @@
+//! Unit tests written in Jzero.
+//!
+//! Every `public static void testXxx()` method of a program is a test.
+//! [`Compiler::unit_tests`] compiles the program once per test with that
+//! method as its entry point, in place of `main`, and runs it in a
+//! [`SandboxHost`].  A test passes if its method returns, and fails if the
+//! VM stops it with an error — which is what the prelude's
+//! `assertEquals(expected, actual)` does when its arguments differ:
+//!
+//! ```text
+//! public static void testSum() {
+//!     assertEquals(5, 2 + 3);
+//! }
+//! ```
+
+use jzero_symtab::entry::{Access, SymbolKind};
+use jzero_symtab::{SymTab, TypeInfo};
+
+use crate::{Compiler, JzeroError, SandboxHost};
+
+/// How many instructions one test may execute before it is failed, so that
+/// a test stuck in a loop does not hang the run.
+pub const TEST_STEP_BUDGET: u64 = 1_000_000;
+
+/// The outcome of one unit test.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnitTest {
+    /// The test method's name, e.g. `testSum`.
+    pub name: String,
+    /// The line the method is declared on.
+    pub lineno: usize,
+    /// `Err` with the VM's error if the test failed.
+    pub outcome: Result<(), String>,
+    /// What the test printed.
+    pub stdout: String,
+}
+
+impl UnitTest {
+    pub fn passed(&self) -> bool {
+        self.outcome.is_ok()
+    }
+}
+
+impl Compiler {
+    /// Run each `public static void testXxx()` method of the source as a
+    /// unit test, in declaration order.
+    ///
+    /// # Errors
+    /// Returns a [`JzeroError`] if parsing, semantic analysis or code generation fails; a
+    /// failing test is reported in its [`UnitTest`] instead.
+    pub fn unit_tests(&self) -> Result<Vec<UnitTest>, JzeroError> {
+        let (tree, sem) = self.analyse()?;
+        let ctx = self.generate(&tree, &sem)?;
+        let mut tests = Vec::new();
+        for (name, lineno) in test_methods(&sem.global.borrow()) {
+            let Some(output) = jzero_codegen::pipeline::compile_bytecode_entry(&tree, &ctx, 0, &name) else {
+                continue;
+            };
+            let mut machine = jzero_vm::J0Machine::load_with_host(&output.binary, 0, Box::new(SandboxHost::new()))
+                .map_err(JzeroError)?;
+            machine.set_step_budget(TEST_STEP_BUDGET);
+            let outcome = machine.interp().map(|_| ());
+            tests.push(UnitTest { name, lineno, outcome, stdout: machine.output });
+        }
+        Ok(tests)
+    }
+}
+
+/// The name and line of each test method declared in `global`, in
+/// declaration order.
+fn test_methods(global: &SymTab) -> Vec<(String, usize)> {
+    let mut found = Vec::new();
+    for class in global.entries_of_kind(SymbolKind::Class).filter(|c| !c.origin.is_predefined()) {
+        let Some(st) = &class.st else { continue };
+        for method in st.borrow().entries_of_kind(SymbolKind::Method) {
+            let Some(TypeInfo::Method(mt)) = &method.typ else { continue };
+            if method.sym.starts_with("test")
+                && method.is_static
+                && method.access == Access::Public
+                && mt.parameters.is_empty()
+                && mt.return_type.basetype() == "void"
+            {
+                found.push((method.sym.clone(), method.lineno));
+            }
+        }
+    }
+    found.sort_by_key(|(_, lineno)| *lineno);
+    found
+}
+
+// ─── Tests ───────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TESTS: &str = r#"
+public class arith {
+    public static void main(String argv[]) {
+        System.out.println("not a test");
+    }
+    public static void testSum() {
+        int x;
+        x = 2 + 3;
+        assertEquals(5, x);
+    }
+    public static void testProduct() {
+        System.out.println("multiplying");
+        assertEquals(7, 2 * 3);
+        System.out.println("unreachable");
+    }
+    public static void helper() {
+        assertEquals(1, 2);
+    }
+    public static int testValue() {
+        assertEquals(1, 2);
+        return 1;
+    }
+}
+"#;
+
+    #[test]
+    fn each_test_method_runs_on_its_own() {
+        let tests = Compiler::new().source(TESTS).unit_tests().unwrap();
+        let names: Vec<&str> = tests.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, ["testSum", "testProduct"]);
+
+        assert!(tests[0].passed(), "{:?}", tests[0]);
+        assert_eq!(tests[0].stdout, "");
+        assert_eq!(tests[1].lineno, 11);
+        assert_eq!(tests[1].outcome, Err("assertEquals failed: expected 7, but was 6".to_string()));
+        assert_eq!(tests[1].stdout, "multiplying\n");
+    }
+
+    #[test]
+    fn static_fields_are_initialized_before_each_test() {
+        let src = "public class t {\n    static int n = 7;\n    public static void testField() {\n        assertEquals(7, n);\n    }\n}\n";
+        let tests = Compiler::new().source(src).unit_tests().unwrap();
+        assert!(tests[0].passed(), "{:?}", tests[0]);
+    }
+
+    #[test]
+    fn a_test_stuck_in_a_loop_fails() {
+        let src = "public class t {\n    public static void testLoop() {\n        int i;\n        i = 0;\n        while (i < 1) { i = i * 1; }\n    }\n}\n";
+        let tests = Compiler::new().source(src).unit_tests().unwrap();
+        assert!(tests[0].outcome.as_ref().unwrap_err().contains("step budget"), "{:?}", tests[0]);
+    }
+}
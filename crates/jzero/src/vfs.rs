@@ -0,0 +1,430 @@
+//! Virtual file system — where source files come from.
+//!
+//! Code that loads sources by path takes a [`FileProvider`] instead of
+//! reading the disk, so the same project can be compiled from:
+//!
+//! - [`RealFs`] — the real file system;
+//! - [`MemoryFs`] — files held in memory, for tests;
+//! - [`Overlay`] — in-memory buffers over another provider, as an editor
+//!   keeps unsaved changes over the files on disk;
+//! - [`ZipFs`] — the files of a zip archive, e.g. a submitted assignment,
+//!   extracted in memory.
+//!
+//! Paths use `/` as separator.  A leading `./` is ignored, so `./A.java`
+//! and `A.java` name the same file.
+//!
+//! Bytes become text through [`decode_source`]: a file over
+//! [`ReadOptions::max_size`] is refused, and one that is not UTF-8 is
+//! refused naming the first bad byte, or read with U+FFFD in its place and
+//! a warning if [`ReadOptions::lossy`] is set.  [`RealFs::read_with`]
+//! reads a file a chunk at a time and stops as soon as it passes the
+//! limit, so a huge file given by mistake is never held in memory.
+
+use std::collections::BTreeMap;
+use std::io::{BufReader, Cursor, Read};
+use std::path::Path;
+
+use crate::{Config, JzeroError};
+
+/// A source of files, by path.
+pub trait FileProvider {
+    /// The text of the file at `path`.
+    ///
+    /// # Errors
+    /// Returns a [`JzeroError`] if there is no such file or it is not
+    /// UTF-8 text.
+    fn read(&self, path: &str) -> Result<String, JzeroError>;
+
+    /// Whether `path` names a file.
+    fn exists(&self, path: &str) -> bool;
+
+    /// Every file below directory `dir`, in sorted order; `""` lists all.
+    ///
+    /// # Errors
+    /// Returns a [`JzeroError`] if `dir` cannot be listed.
+    fn files(&self, dir: &str) -> Result<Vec<String>, JzeroError>;
+}
+
+fn normalize(path: &str) -> &str {
+    let mut path = path;
+    while let Some(rest) = path.strip_prefix("./") { path = rest; }
+    path
+}
+
+fn not_found(path: &str) -> JzeroError {
+    JzeroError(format!("error reading '{}': no such file", path))
+}
+
+/// Whether `path` is below directory `dir`.
+fn is_below(path: &str, dir: &str) -> bool {
+    let dir = normalize(dir).trim_end_matches('/');
+    dir.is_empty() || dir == "." || path.strip_prefix(dir).is_some_and(|rest| rest.starts_with('/'))
+}
+
+// ─── Source text ──────────────────────────────────────────────────────────────
+
+/// The default [`ReadOptions::max_size`], 8 MiB: far more than any program
+/// written by hand, far less than a log or binary file passed by mistake.
+pub const DEFAULT_MAX_FILE_SIZE: u64 = 8 << 20;
+
+/// How the bytes of a source file become its text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReadOptions {
+    /// The most bytes a file may have.
+    pub max_size: u64,
+    /// Read invalid UTF-8 as U+FFFD, with a warning, rather than refuse
+    /// the file.
+    pub lossy: bool,
+}
+
+impl Default for ReadOptions {
+    fn default() -> Self {
+        ReadOptions { max_size: DEFAULT_MAX_FILE_SIZE, lossy: false }
+    }
+}
+
+impl ReadOptions {
+    /// The options `[input]` of `config` sets, the defaults for the rest:
+    ///
+    /// ```toml
+    /// [input]
+    /// max-file-size = 1048576
+    /// lossy-utf8 = true
+    /// ```
+    pub fn from_config(config: &Config) -> Self {
+        let default = ReadOptions::default();
+        ReadOptions {
+            max_size: config.get_int("input.max-file-size")
+                .and_then(|n| u64::try_from(n).ok())
+                .unwrap_or(default.max_size),
+            lossy: config.get_bool("input.lossy-utf8").unwrap_or(default.lossy),
+        }
+    }
+}
+
+/// The text of a source file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceText {
+    pub text: String,
+    /// What a lossy read replaced, if anything.
+    pub warning: Option<String>,
+}
+
+fn too_large(path: &str, max_size: u64) -> JzeroError {
+    JzeroError(format!("error reading '{}': file is larger than the limit of {} bytes", path, max_size))
+}
+
+/// The text of the file at `path` with contents `bytes`.
+///
+/// # Errors
+/// Returns a [`JzeroError`] if `bytes` is over the size limit, or is not
+/// UTF-8 and `options` are not lossy.
+pub fn decode_source(path: &str, bytes: Vec<u8>, options: ReadOptions) -> Result<SourceText, JzeroError> {
+    if bytes.len() as u64 > options.max_size {
+        return Err(too_large(path, options.max_size));
+    }
+    let bad = match String::from_utf8(bytes) {
+        Ok(text) => return Ok(SourceText { text, warning: None }),
+        Err(bad) => bad,
+    };
+    let at = bad.utf8_error().valid_up_to();
+    if !options.lossy {
+        return Err(JzeroError(format!("error reading '{}': not valid UTF-8 at byte {}", path, at)));
+    }
+    Ok(SourceText {
+        text: String::from_utf8_lossy(bad.as_bytes()).into_owned(),
+        warning: Some(format!("{}: not valid UTF-8 at byte {}; invalid bytes read as U+FFFD", path, at)),
+    })
+}
+
+/// The text read from `reader`, the contents of the file at `path`.
+/// Reading stops one byte past the size limit.
+///
+/// # Errors
+/// As [`decode_source`], and if `reader` fails.
+pub fn read_source(path: &str, reader: impl Read, options: ReadOptions) -> Result<SourceText, JzeroError> {
+    let mut bytes = Vec::new();
+    reader.take(options.max_size.saturating_add(1)).read_to_end(&mut bytes)
+        .map_err(|e| JzeroError(format!("error reading '{}': {}", path, e)))?;
+    decode_source(path, bytes, options)
+}
+
+// ─── RealFs ───────────────────────────────────────────────────────────────────
+
+/// The real file system.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RealFs;
+
+impl RealFs {
+    /// The text of the file at `path`, read as `options` say.
+    ///
+    /// # Errors
+    /// Returns a [`JzeroError`] if the file cannot be read, or as
+    /// [`decode_source`].
+    pub fn read_with(&self, path: &str, options: ReadOptions) -> Result<SourceText, JzeroError> {
+        let file = std::fs::File::open(path)
+            .map_err(|e| JzeroError(format!("error reading '{}': {}", path, e)))?;
+        // A regular file's length says up front if it is too large.
+        if file.metadata().is_ok_and(|m| m.is_file() && m.len() > options.max_size) {
+            return Err(too_large(path, options.max_size));
+        }
+        read_source(path, BufReader::new(file), options)
+    }
+}
+
+impl FileProvider for RealFs {
+    fn read(&self, path: &str) -> Result<String, JzeroError> {
+        self.read_with(path, ReadOptions::default()).map(|source| source.text)
+    }
+
+    fn exists(&self, path: &str) -> bool {
+        Path::new(path).is_file()
+    }
+
+    fn files(&self, dir: &str) -> Result<Vec<String>, JzeroError> {
+        let mut found = Vec::new();
+        let mut work = vec![if dir.is_empty() { ".".to_string() } else { dir.to_string() }];
+        while let Some(d) = work.pop() {
+            let entries = std::fs::read_dir(&d)
+                .map_err(|e| JzeroError(format!("error reading '{}': {}", d, e)))?;
+            for entry in entries.flatten() {
+                let path = entry.path().to_string_lossy().replace('\\', "/");
+                if entry.path().is_dir() { work.push(path); } else { found.push(normalize(&path).to_string()); }
+            }
+        }
+        found.sort();
+        Ok(found)
+    }
+}
+
+// ─── MemoryFs ─────────────────────────────────────────────────────────────────
+
+/// Files held in memory.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryFs {
+    files: BTreeMap<String, String>,
+}
+
+impl MemoryFs {
+    pub fn new() -> Self {
+        MemoryFs::default()
+    }
+
+    /// Add or replace the file at `path`.
+    pub fn insert(&mut self, path: &str, text: &str) {
+        self.files.insert(normalize(path).to_string(), text.to_string());
+    }
+
+    /// Remove the file at `path`, returning its text.
+    pub fn remove(&mut self, path: &str) -> Option<String> {
+        self.files.remove(normalize(path))
+    }
+}
+
+impl FileProvider for MemoryFs {
+    fn read(&self, path: &str) -> Result<String, JzeroError> {
+        self.files.get(normalize(path)).cloned().ok_or_else(|| not_found(path))
+    }
+
+    fn exists(&self, path: &str) -> bool {
+        self.files.contains_key(normalize(path))
+    }
+
+    fn files(&self, dir: &str) -> Result<Vec<String>, JzeroError> {
+        Ok(self.files.keys().filter(|p| is_below(p, dir)).cloned().collect())
+    }
+}
+
+// ─── Overlay ──────────────────────────────────────────────────────────────────
+
+/// In-memory buffers over another provider.  A path with a buffer reads
+/// as the buffer; any other path reads from the provider below.
+#[derive(Debug, Clone, Default)]
+pub struct Overlay<P> {
+    base: P,
+    buffers: MemoryFs,
+}
+
+impl<P: FileProvider> Overlay<P> {
+    pub fn new(base: P) -> Self {
+        Overlay { base, buffers: MemoryFs::new() }
+    }
+
+    /// Shadow the file at `path` with `text`, e.g. an unsaved buffer.
+    pub fn set_buffer(&mut self, path: &str, text: &str) {
+        self.buffers.insert(path, text);
+    }
+
+    /// Drop the buffer for `path`, e.g. once it is saved or closed.
+    pub fn clear_buffer(&mut self, path: &str) -> Option<String> {
+        self.buffers.remove(path)
+    }
+}
+
+impl<P: FileProvider> FileProvider for Overlay<P> {
+    fn read(&self, path: &str) -> Result<String, JzeroError> {
+        if self.buffers.exists(path) { self.buffers.read(path) } else { self.base.read(path) }
+    }
+
+    fn exists(&self, path: &str) -> bool {
+        self.buffers.exists(path) || self.base.exists(path)
+    }
+
+    fn files(&self, dir: &str) -> Result<Vec<String>, JzeroError> {
+        let mut found = self.base.files(dir)?;
+        found.extend(self.buffers.files(dir)?);
+        found.sort();
+        found.dedup();
+        Ok(found)
+    }
+}
+
+// ─── ZipFs ────────────────────────────────────────────────────────────────────
+
+/// The files of a zip archive, extracted into memory when it is opened.
+/// Directories are not files; entries whose names would leave the archive
+/// (`../x`, absolute paths) are skipped.
+#[derive(Debug, Clone, Default)]
+pub struct ZipFs {
+    files: BTreeMap<String, Vec<u8>>,
+}
+
+impl ZipFs {
+    /// Extract the archive in `bytes`.
+    ///
+    /// # Errors
+    /// Returns a [`JzeroError`] if `bytes` is not a readable zip archive.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, JzeroError> {
+        let bad = |e: zip::result::ZipError| JzeroError(format!("bad zip archive: {}", e));
+        let mut archive = zip::ZipArchive::new(Cursor::new(bytes)).map_err(bad)?;
+        let mut files = BTreeMap::new();
+        for i in 0..archive.len() {
+            let mut entry = archive.by_index(i).map_err(bad)?;
+            if entry.is_dir() { continue; }
+            let Some(name) = entry.enclosed_name() else { continue };
+            let name = name.to_string_lossy().replace('\\', "/");
+            let mut data = Vec::new();
+            entry.read_to_end(&mut data)
+                .map_err(|e| JzeroError(format!("bad zip archive: {}: {}", name, e)))?;
+            files.insert(normalize(&name).to_string(), data);
+        }
+        Ok(ZipFs { files })
+    }
+
+    /// Read and extract the archive at `path`.
+    ///
+    /// # Errors
+    /// Returns a [`JzeroError`] if the file cannot be read or is not a zip
+    /// archive.
+    pub fn open(path: &str) -> Result<Self, JzeroError> {
+        let bytes = std::fs::read(path)
+            .map_err(|e| JzeroError(format!("error reading '{}': {}", path, e)))?;
+        ZipFs::from_bytes(&bytes).map_err(|e| JzeroError(format!("{}: {}", path, e)))
+    }
+}
+
+impl FileProvider for ZipFs {
+    fn read(&self, path: &str) -> Result<String, JzeroError> {
+        let data = self.files.get(normalize(path)).ok_or_else(|| not_found(path))?;
+        decode_source(path, data.clone(), ReadOptions::default()).map(|source| source.text)
+    }
+
+    fn exists(&self, path: &str) -> bool {
+        self.files.contains_key(normalize(path))
+    }
+
+    fn files(&self, dir: &str) -> Result<Vec<String>, JzeroError> {
+        Ok(self.files.keys().filter(|p| is_below(p, dir)).cloned().collect())
+    }
+}
+
+// ─── Tests ───────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn zip_of(entries: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        let options = zip::write::SimpleFileOptions::default()
+            .compression_method(zip::CompressionMethod::Deflated);
+        for (name, data) in entries {
+            if name.ends_with('/') {
+                writer.add_directory(*name, options).unwrap();
+            } else {
+                writer.start_file(*name, options).unwrap();
+                writer.write_all(data).unwrap();
+            }
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn memory_files_are_listed_by_directory() {
+        let mut fs = MemoryFs::new();
+        fs.insert("./src/A.java", "a");
+        fs.insert("src/util/B.java", "b");
+        fs.insert("README", "r");
+        assert_eq!(fs.read("src/A.java").unwrap(), "a");
+        assert_eq!(fs.files("src").unwrap(), ["src/A.java", "src/util/B.java"]);
+        assert_eq!(fs.files("").unwrap().len(), 3);
+        assert!(fs.files("sr").unwrap().is_empty());
+        assert!(fs.read("C.java").unwrap_err().0.contains("no such file"));
+    }
+
+    #[test]
+    fn overlay_buffers_shadow_the_base() {
+        let mut disk = MemoryFs::new();
+        disk.insert("A.java", "saved");
+        let mut fs = Overlay::new(disk);
+        fs.set_buffer("A.java", "unsaved");
+        fs.set_buffer("B.java", "new");
+        assert_eq!(fs.read("A.java").unwrap(), "unsaved");
+        assert_eq!(fs.files("").unwrap(), ["A.java", "B.java"]);
+
+        fs.clear_buffer("A.java");
+        assert_eq!(fs.read("A.java").unwrap(), "saved");
+    }
+
+    #[test]
+    fn zip_entries_are_extracted_in_memory() {
+        let bytes = zip_of(&[
+            ("hw1/", b""),
+            ("hw1/Main.java", b"public class Main { }"),
+            ("../escape.java", b"x"),
+            ("hw1/logo.bin", &[0xff, 0xfe]),
+        ]);
+        let fs = ZipFs::from_bytes(&bytes).unwrap();
+        assert_eq!(fs.files("").unwrap(), ["hw1/Main.java", "hw1/logo.bin"]);
+        assert_eq!(fs.read("hw1/Main.java").unwrap(), "public class Main { }");
+        assert_eq!(fs.read("hw1/logo.bin").unwrap_err().0, "error reading 'hw1/logo.bin': not valid UTF-8 at byte 0");
+        assert!(ZipFs::from_bytes(b"not a zip").is_err());
+    }
+
+    #[test]
+    fn invalid_utf8_is_refused_or_replaced() {
+        let bytes = b"int x; // caf\xe9\n".to_vec();
+        let err = decode_source("A.java", bytes.clone(), ReadOptions::default()).unwrap_err();
+        assert_eq!(err.0, "error reading 'A.java': not valid UTF-8 at byte 13");
+
+        let lossy = ReadOptions { lossy: true, ..ReadOptions::default() };
+        let source = decode_source("A.java", bytes, lossy).unwrap();
+        assert_eq!(source.text, "int x; // caf\u{fffd}\n");
+        assert_eq!(source.warning.unwrap(), "A.java: not valid UTF-8 at byte 13; invalid bytes read as U+FFFD");
+        assert_eq!(decode_source("B.java", b"ok".to_vec(), lossy).unwrap().warning, None);
+    }
+
+    #[test]
+    fn reading_stops_past_the_size_limit() {
+        let small = ReadOptions { max_size: 4, ..ReadOptions::default() };
+        assert_eq!(read_source("A.java", &b"abcd"[..], small).unwrap().text, "abcd");
+        // An endless input is read only up to the limit.
+        let err = read_source("/dev/zero", std::io::repeat(b'x'), small).unwrap_err();
+        assert_eq!(err.0, "error reading '/dev/zero': file is larger than the limit of 4 bytes");
+
+        let config = Config::parse("[input]\nmax-file-size = 100\nlossy-utf8 = true\n").unwrap();
+        assert_eq!(ReadOptions::from_config(&config), ReadOptions { max_size: 100, lossy: true });
+        assert_eq!(ReadOptions::from_config(&Config::default()), ReadOptions::default());
+    }
+}
@@ -0,0 +1,693 @@
+//! Cross references — where each name of a workspace is declared and used.
+//!
+//! An [`XrefIndex`] parses each file of a workspace, analyses it the way
+//! the compiler would, and resolves every identifier to the class, field,
+//! method, parameter or local variable it names through the symbol tables
+//! of that analysis:
+//!
+//! - a bare name is looked up from the innermost scope around it, so an
+//!   inherited member is found in the superclass;
+//! - the name after a `.` is a member of the class of what is before it:
+//!   a class name, `this`, a variable or field of a class type, a call of
+//!   a method returning one, or a `new` expression;
+//! - the name of a type, a constructor or a `new` expression is a class.
+//!
+//! The files share one global scope and are analysed after the files
+//! declaring the classes they name, so a superclass is declared before its
+//! subclasses.  Between files naming each other's classes the order is
+//! arbitrary, and a subclass analysed first sees nothing inherited.
+//!
+//! Names resolving to nothing, such as `System`, are left out.  The index
+//! backs the refactorings of [`crate::refactor`].
+//!
+//! ```
+//! use jzero_lexer::LexOptions;
+//!
+//! let point = "public class Point {\n    int x;\n}\n";
+//! let main = "public class Main {\n    public static void main(String argv[]) {\n        Point p;\n        p = new Point();\n        p.x = 1;\n    }\n}\n";
+//! let index = jzero::xref::XrefIndex::from_sources([("Point.java", point), ("Main.java", main)], LexOptions::default()).unwrap();
+//! let x = index.declaration_at("Point.java", point.find("x;").unwrap()).unwrap();
+//! assert_eq!(x.qualified, "Point.x");
+//! let uses = index.references(x);
+//! assert_eq!(uses.len(), 1);
+//! assert_eq!((uses[0].path.as_str(), &main[uses[0].span.start..uses[0].span.end]), ("Main.java", "x"));
+//! ```
+
+use std::cell::RefCell;
+use std::collections::{BTreeSet, HashMap};
+use std::rc::Rc;
+
+use jzero_ast::tree::Tree;
+use jzero_lexer::LexOptions;
+use jzero_symtab::entry::SymbolKind;
+use jzero_symtab::{SymTab, SymTabEntry, TypeInfo, build_predefined};
+use jzero_text::Span;
+
+use crate::JzeroError;
+use crate::project::{collect_references, declared_type};
+use crate::ranges::node_spans;
+use crate::vfs::{FileProvider, RealFs};
+
+/// A place in a file of the workspace.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Location {
+    pub path: String,
+    pub span: Span,
+}
+
+/// A name declared in the workspace.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Declaration {
+    pub name: String,
+    /// The names of the enclosing class and method and the declaration's,
+    /// joined by `.`: `Main.main.p`.
+    pub qualified: String,
+    pub kind: SymbolKind,
+    /// The declared name.
+    pub location: Location,
+}
+
+/// A parsed file.
+pub(crate) struct File {
+    pub(crate) path: String,
+    pub(crate) source: String,
+    /// The span of each node, by id.
+    pub(crate) spans: HashMap<u32, Span>,
+}
+
+/// A declaration and where it sits in the workspace.
+pub(crate) struct Decl {
+    pub(crate) info: Declaration,
+    pub(crate) file: usize,
+    /// The class or method declaring it; `None` for a class.
+    pub(crate) parent: Option<usize>,
+    /// A constructor, which declares no name of its own.
+    constructor: bool,
+    /// The member or statement declaring it: a `FieldDecl`, `MethodDecl`,
+    /// `LocalVarDecl` or `FormalParm`.
+    pub(crate) node: u32,
+    /// The declarators of that node, this one's among them, for a variable
+    /// or field.
+    pub(crate) declarators: Vec<u32>,
+    /// This one's declarator.
+    pub(crate) declarator: u32,
+}
+
+/// A scope of the analysis.
+type Scope = Rc<RefCell<SymTab>>;
+
+/// The declarations of a workspace and the names referring to them.
+#[derive(Default)]
+pub struct XrefIndex {
+    pub(crate) files: Vec<File>,
+    pub(crate) decls: Vec<Decl>,
+    /// Each resolved name, declared names included: the declaration, the
+    /// file and the span of the name.
+    refs: Vec<(usize, usize, Span)>,
+    /// Declarations by the id of the leaf naming them, per file.
+    by_leaf: HashMap<(usize, u32), usize>,
+    /// Declarations by the scope declaring them and their name, as a symbol
+    /// table entry records them.
+    by_entry: HashMap<(*const RefCell<SymTab>, String), usize>,
+    /// The scope of each class, interface, method and catch clause, by the
+    /// id of its node, per file.
+    scopes: HashMap<(usize, u32), Scope>,
+    /// The global scope the files were analysed in, which keeps every scope
+    /// `by_entry` points to alive.
+    global: Option<Scope>,
+}
+
+impl XrefIndex {
+    /// Parse each `(path, source)` pair with `options` and index the names.
+    /// Semantic errors do not stop a file's names from being indexed.
+    ///
+    /// # Errors
+    /// Returns a [`JzeroError`] naming the first file that does not parse.
+    pub fn from_sources<'a>(
+        sources: impl IntoIterator<Item = (&'a str, &'a str)>,
+        options: LexOptions,
+    ) -> Result<Self, JzeroError> {
+        let mut index = XrefIndex::default();
+        let mut trees = Vec::new();
+        for (path, source) in sources {
+            let tree = jzero_parser::parse_tree_with(source, options)
+                .map_err(|e| JzeroError(format!("{}: {}", path, e)))?;
+            let spans = node_spans(source, &tree);
+            index.files.push(File { path: path.to_string(), source: source.to_string(), spans });
+            trees.push(tree);
+        }
+
+        // Analysis rewrites the tree and renumbers its nodes, so a copy is
+        // analysed.  Only the catch scopes cannot be found again by name;
+        // analysis keeps the clauses in order.
+        let global = SymTab::new("global", None).into_rc();
+        build_predefined(&global);
+        let mut catches = vec![Vec::new(); trees.len()];
+        for file in analysis_order(&trees) {
+            let mut analysed = trees[file].clone();
+            jzero_semantic::analyze_in(&mut analysed, Rc::clone(&global));
+            catch_scopes(&analysed, &mut catches[file]);
+        }
+        index.global = Some(global);
+
+        // Every declaration must be known before a name can be resolved.
+        for (file, (tree, catches)) in trees.iter().zip(catches).enumerate() {
+            index.declare(file, tree, None, "", None, &mut catches.into_iter());
+        }
+        for (file, tree) in trees.iter().enumerate() {
+            index.visit(tree, &mut Context { file, scope: None, class: None });
+        }
+        Ok(index)
+    }
+
+    /// Read and index every file in `paths`.
+    ///
+    /// # Errors
+    /// Returns a [`JzeroError`] if a file cannot be read or does not parse.
+    pub fn load(paths: &[String], options: LexOptions) -> Result<Self, JzeroError> {
+        XrefIndex::load_from(&RealFs, paths, options)
+    }
+
+    /// Read every file in `paths` from `files` and index it.
+    ///
+    /// # Errors
+    /// Returns a [`JzeroError`] if a file cannot be read or does not parse.
+    pub fn load_from(files: &dyn FileProvider, paths: &[String], options: LexOptions) -> Result<Self, JzeroError> {
+        let mut sources = Vec::new();
+        for path in paths {
+            sources.push((path.as_str(), files.read(path)?));
+        }
+        XrefIndex::from_sources(sources.iter().map(|(p, s)| (*p, s.as_str())), options)
+    }
+
+    /// The declaration of the name at byte `offset` of `path`, whether the
+    /// name declares it or refers to it.
+    pub fn declaration_at(&self, path: &str, offset: usize) -> Option<&Declaration> {
+        self.decl_at(path, offset).map(|d| &self.decls[d].info)
+    }
+
+    /// Every name referring to `declaration`, the declared name left out,
+    /// by file and then by offset.
+    pub fn references(&self, declaration: &Declaration) -> Vec<Location> {
+        let Some(decl) = self.decls.iter().position(|d| d.info == *declaration) else { return Vec::new() };
+        self.occurrences(decl).into_iter().filter(|l| *l != declaration.location).collect()
+    }
+
+    pub(crate) fn decl_at(&self, path: &str, offset: usize) -> Option<usize> {
+        let file = self.files.iter().position(|f| f.path == path)?;
+        self.refs.iter()
+            .find(|(_, f, span)| *f == file && span.start <= offset && offset <= span.end)
+            .map(|(d, _, _)| *d)
+    }
+
+    /// Every name referring to or declaring `decl`, by file and offset.
+    pub(crate) fn occurrences(&self, decl: usize) -> Vec<Location> {
+        let mut found: Vec<_> = self.refs.iter().filter(|(d, _, _)| *d == decl).collect();
+        found.sort_by_key(|(_, file, span)| (*file, span.start));
+        found.dedup();
+        found.iter().map(|(_, file, span)| Location { path: self.files[*file].path.clone(), span: *span }).collect()
+    }
+
+    /// The declarations visible by name in the same scope as `decl`: its
+    /// siblings in a class or method, or the classes of the workspace.
+    pub(crate) fn siblings(&self, decl: usize) -> impl Iterator<Item = &Decl> + '_ {
+        let parent = self.decls[decl].parent;
+        self.decls.iter().enumerate()
+            .filter(move |(i, d)| *i != decl && d.parent == parent && !d.constructor)
+            .map(|(_, d)| d)
+    }
+
+    // ── Declarations ──────────────────────────────────────
+
+    /// Record the declaration `leaf` names, and key it by `scope`, the
+    /// scope the analysis declared it in.
+    fn push(&mut self, file: usize, leaf: &Tree, kind: SymbolKind, parent: Option<usize>, qualified: &str, scope: Option<&Scope>) -> usize {
+        let name = leaf.tok.as_ref().map(|t| t.text.clone()).unwrap_or_default();
+        let qualified = if qualified.is_empty() { name.clone() } else { format!("{}.{}", qualified, name) };
+        let span = self.files[file].spans.get(&leaf.id).copied().unwrap_or_default();
+        let location = Location { path: self.files[file].path.clone(), span };
+        self.decls.push(Decl {
+            info: Declaration { name: name.clone(), qualified, kind, location },
+            file,
+            parent,
+            constructor: false,
+            node: 0,
+            declarators: Vec::new(),
+            declarator: 0,
+        });
+        let decl = self.decls.len() - 1;
+        self.by_leaf.insert((file, leaf.id), decl);
+        if let Some(scope) = scope {
+            self.by_entry.entry((Rc::as_ptr(scope), name)).or_insert(decl);
+        }
+        decl
+    }
+
+    /// Record the declarations of `tree`, declared in `parent` and in
+    /// `scope`, whose qualified name is `qualified`.  `catches` yields the
+    /// scopes of the catch clauses still to come, in order.
+    fn declare(&mut self, file: usize, tree: &Tree, parent: Option<usize>, qualified: &str, scope: Option<&Scope>, catches: &mut dyn Iterator<Item = Scope>) {
+        match tree.sym.as_str() {
+            "ClassDecl" | "InterfaceDecl" => {
+                let Some(name) = tree.kids.first() else { return };
+                let kind = if tree.sym == "ClassDecl" { SymbolKind::Class } else { SymbolKind::Interface };
+                let global = self.global.clone();
+                let class = self.push(file, name, kind, None, "", global.as_ref());
+                let inner = name.tok.as_ref().and_then(|t| self.type_scope(&t.text));
+                if let Some(inner) = &inner {
+                    self.scopes.insert((file, tree.id), Rc::clone(inner));
+                }
+                let qualified = self.decls[class].info.qualified.clone();
+                for member in &tree.kids[1..] {
+                    self.declare(file, member, Some(class), &qualified, inner.as_ref(), catches);
+                }
+            }
+            "FieldDecl" | "LocalVarDecl" => {
+                let kind = if tree.sym == "FieldDecl" { SymbolKind::Field } else { SymbolKind::Local };
+                self.variables(file, tree, kind, parent, qualified, scope);
+            }
+            "MethodDecl" | "AbstractMethodDecl" | "ConstructorDecl" => {
+                let constructor = tree.sym == "ConstructorDecl";
+                let declarator = match constructor {
+                    true => tree.kids.first(),
+                    false => tree.kids[0].kids.get(1),
+                };
+                let Some(declarator) = declarator else { return };
+                let Some(name) = declarator.kids.first() else { return };
+                // A constructor's parameters and locals are the class's.
+                let (method, inner) = match constructor {
+                    true => {
+                        let method = self.push(file, name, SymbolKind::Method, parent, qualified, None);
+                        self.decls[method].constructor = true;
+                        self.by_leaf.remove(&(file, name.id));
+                        (method, scope.cloned())
+                    }
+                    false => {
+                        let method = self.push(file, name, SymbolKind::Method, parent, qualified, scope);
+                        let text = &self.decls[method].info.name;
+                        let inner = scope.and_then(|s| s.borrow().lookup_local(text).and_then(|e| e.st.clone()));
+                        if let Some(inner) = &inner {
+                            self.scopes.insert((file, tree.id), Rc::clone(inner));
+                        }
+                        (method, inner)
+                    }
+                };
+                self.decls[method].node = tree.id;
+                let qualified = self.decls[method].info.qualified.clone();
+                for param in &declarator.kids[1..] {
+                    self.variables(file, param, SymbolKind::Param, Some(method), &qualified, inner.as_ref());
+                }
+                for kid in &tree.kids[1..] {
+                    self.declare(file, kid, Some(method), &qualified, inner.as_ref(), catches);
+                }
+            }
+            "CatchClause" => {
+                let inner = catches.next();
+                if let Some(inner) = &inner {
+                    self.scopes.insert((file, tree.id), Rc::clone(inner));
+                }
+                let Some(param) = tree.kids.first() else { return };
+                self.variables(file, param, SymbolKind::Param, parent, qualified, inner.as_ref());
+                for kid in &tree.kids[1..] {
+                    self.declare(file, kid, parent, qualified, inner.as_ref(), catches);
+                }
+            }
+            _ => {
+                for kid in &tree.kids {
+                    self.declare(file, kid, parent, qualified, scope, catches);
+                }
+            }
+        }
+    }
+
+    /// The variables of a `FieldDecl`, `LocalVarDecl` or `FormalParm`: a
+    /// type and its declarators.
+    fn variables(&mut self, file: usize, tree: &Tree, kind: SymbolKind, parent: Option<usize>, qualified: &str, scope: Option<&Scope>) {
+        let declarators: Vec<&Tree> = tree.kids.iter().skip(1).filter(|k| k.sym == "VarDeclarator").collect();
+        for declarator in &declarators {
+            let Some(name) = name_leaf(declarator) else { continue };
+            let decl = self.push(file, name, kind.clone(), parent, qualified, scope);
+            let d = &mut self.decls[decl];
+            d.node = tree.id;
+            d.declarators = declarators.iter().map(|k| k.id).collect();
+            d.declarator = declarator.id;
+        }
+    }
+
+    // ── Uses ──────────────────────────────────────────────
+
+    fn refer(&mut self, file: usize, leaf: &Tree, decl: Option<usize>) {
+        if let (Some(decl), Some(span)) = (decl, self.files[file].spans.get(&leaf.id)) {
+            self.refs.push((decl, file, *span));
+        }
+    }
+
+    fn visit(&mut self, tree: &Tree, at: &mut Context) {
+        let file = at.file;
+        if let Some(tok) = &tree.tok {
+            let decl = match self.by_leaf.get(&(file, tree.id)) {
+                Some(&decl) => Some(decl),
+                None if tok.category == "IDENTIFIER" => {
+                    let entry = at.scope.as_ref().and_then(|s| s.borrow().lookup(&tok.text));
+                    entry.and_then(|e| self.decl_of(&e))
+                }
+                None => None,
+            };
+            self.refer(file, tree, decl);
+            return;
+        }
+        match tree.sym.as_str() {
+            "ClassDecl" | "InterfaceDecl" => {
+                at.class = self.scopes.get(&(file, tree.id)).cloned();
+                at.scope = at.class.clone();
+                self.visit_all(&tree.kids, at);
+            }
+            "MethodDecl" | "AbstractMethodDecl" | "CatchClause" => {
+                let outer = at.scope.clone();
+                if let Some(inner) = self.scopes.get(&(file, tree.id)) {
+                    at.scope = Some(Rc::clone(inner));
+                }
+                self.visit_all(&tree.kids, at);
+                at.scope = outer;
+            }
+            "ConstructorDeclarator" => {
+                if let Some(name) = tree.kids.first() {
+                    self.visit_type(name, file);
+                }
+                self.visit_all(&tree.kids[1..], at);
+            }
+            "FieldDecl" | "LocalVarDecl" | "FormalParm" | "MethodHeader" | "ArrayCreation" | "ArrayType"
+            | "InstanceCreation" => {
+                if let Some(typ) = tree.kids.first() {
+                    self.visit_type(typ, file);
+                }
+                self.visit_all(&tree.kids[1..], at);
+            }
+            "InstanceOfExpr" => {
+                self.visit_all(&tree.kids[..1], at);
+                if let Some(typ) = tree.kids.get(1) {
+                    self.visit_type(typ, file);
+                }
+            }
+            "FieldAccess" => self.member(&tree.kids, at),
+            "MethodCall" => {
+                let args = match (tree.rule, tree.kids.first()) {
+                    (0 | 1, Some(name)) if name.sym == "FieldAccess" => {
+                        self.member(&name.kids, at);
+                        1
+                    }
+                    (0 | 1, Some(name)) => {
+                        let method = name.tok.as_ref().and_then(|t| self.member_of(at.class.as_ref(), &t.text));
+                        let decl = method.and_then(|e| self.decl_of(&e));
+                        self.refer(file, name, decl);
+                        1
+                    }
+                    _ => {
+                        self.member(&tree.kids, at);
+                        2
+                    }
+                };
+                self.visit_all(tree.kids.get(args..).unwrap_or_default(), at);
+            }
+            // A label names no declaration.
+            "BreakStmt" => {}
+            _ => self.visit_all(&tree.kids, at),
+        }
+    }
+
+    fn visit_all(&mut self, kids: &[Tree], at: &mut Context) {
+        for kid in kids {
+            self.visit(kid, at);
+        }
+    }
+
+    fn visit_type(&mut self, typ: &Tree, file: usize) {
+        match &typ.tok {
+            Some(tok) if tok.category == "IDENTIFIER" => {
+                let global = self.global.as_ref().and_then(|g| g.borrow().lookup_local(&tok.text).cloned());
+                let class = global.filter(|e| is_type(&e.kind)).and_then(|e| self.decl_of(&e));
+                self.refer(file, typ, class);
+            }
+            Some(_) => {}
+            None => {
+                for kid in &typ.kids {
+                    self.visit_type(kid, file);
+                }
+            }
+        }
+    }
+
+    /// `base.name`, given as `[base, name]`.
+    fn member(&mut self, kids: &[Tree], at: &mut Context) {
+        let (Some(base), Some(name)) = (kids.first(), kids.get(1)) else { return };
+        self.visit(base, at);
+        let class = self.class_of(base, at);
+        let entry = name.tok.as_ref().and_then(|t| self.member_of(class.as_ref(), &t.text));
+        let decl = entry.and_then(|e| self.decl_of(&e));
+        self.refer(at.file, name, decl);
+    }
+
+    // ── Resolution ────────────────────────────────────────
+
+    /// The declaration of the workspace `entry` is, if it is one.
+    fn decl_of(&self, entry: &SymTabEntry) -> Option<usize> {
+        self.by_entry.get(&(Rc::as_ptr(&entry.parent_st), entry.sym.clone())).copied()
+    }
+
+    /// The scope of the class or interface `name`.
+    fn type_scope(&self, name: &str) -> Option<Scope> {
+        let entry = self.global.as_ref()?.borrow().lookup_local(name).cloned()?;
+        if is_type(&entry.kind) { entry.st } else { None }
+    }
+
+    /// The member `name` of the class whose scope is `class`, inherited
+    /// members included.
+    fn member_of(&self, class: Option<&Scope>, name: &str) -> Option<SymTabEntry> {
+        let mut scope = Rc::clone(class?);
+        loop {
+            if let Some(entry) = scope.borrow().lookup_local(name) {
+                return Some(entry.clone());
+            }
+            // A class scope's parent is its superclass's, or the globals.
+            let parent = scope.borrow().parent.clone()?;
+            if parent.borrow().scope != "class" {
+                return None;
+            }
+            scope = parent;
+        }
+    }
+
+    /// The scope of the class of the value of `expr`, when the analysis
+    /// can tell.
+    fn class_of(&self, expr: &Tree, at: &Context) -> Option<Scope> {
+        let entry = match expr.sym.as_str() {
+            _ if expr.tok.as_ref().is_some_and(|t| t.category == "THIS") => return at.class.clone(),
+            _ if expr.tok.is_some() => at.scope.as_ref()?.borrow().lookup(&expr.tok.as_ref()?.text)?,
+            "FieldAccess" => {
+                let name = &expr.kids.get(1)?.tok.as_ref()?.text;
+                self.member_of(self.class_of(&expr.kids[0], at).as_ref(), name)?
+            }
+            "MethodCall" => match (expr.rule, expr.kids.first()?) {
+                (0 | 1, name) if name.sym == "FieldAccess" => {
+                    let method = &name.kids.get(1)?.tok.as_ref()?.text;
+                    self.member_of(self.class_of(&name.kids[0], at).as_ref(), method)?
+                }
+                (0 | 1, name) => self.member_of(at.class.as_ref(), &name.tok.as_ref()?.text)?,
+                (_, base) => {
+                    let method = &expr.kids.get(1)?.tok.as_ref()?.text;
+                    self.member_of(self.class_of(base, at).as_ref(), method)?
+                }
+            },
+            "InstanceCreation" => return self.type_scope(&expr.kids.first()?.tok.as_ref()?.text),
+            _ => return None,
+        };
+        match entry.kind {
+            SymbolKind::Class | SymbolKind::Interface => entry.st,
+            _ => self.class_scope_of(entry.typ.as_ref()?),
+        }
+    }
+
+    /// The scope of the class `typ` is, or a method of it returns; not of
+    /// an array.
+    fn class_scope_of(&self, typ: &TypeInfo) -> Option<Scope> {
+        match typ {
+            TypeInfo::Class(class) => class.st.clone().or_else(|| self.type_scope(&class.name)),
+            TypeInfo::Method(method) => self.class_scope_of(&method.return_type),
+            _ => None,
+        }
+    }
+}
+
+/// Where a name is being resolved.
+struct Context {
+    file: usize,
+    /// The innermost scope around the name.
+    scope: Option<Scope>,
+    /// The scope of the enclosing class, the class of `this`.
+    class: Option<Scope>,
+}
+
+fn is_type(kind: &SymbolKind) -> bool {
+    matches!(kind, SymbolKind::Class | SymbolKind::Interface)
+}
+
+/// The order to analyse `trees` in: each after the trees declaring the
+/// classes it names, unless they name its classes too.
+fn analysis_order(trees: &[Tree]) -> Vec<usize> {
+    let mut owner = HashMap::new();
+    for (i, tree) in trees.iter().enumerate() {
+        if let Some(tok) = declared_type(tree).and_then(|n| n.tok.as_ref()) {
+            owner.entry(tok.text.as_str()).or_insert(i);
+        }
+    }
+    let named: Vec<Vec<usize>> = trees.iter().enumerate().map(|(i, tree)| {
+        let mut names = BTreeSet::new();
+        collect_references(tree, &mut names);
+        names.iter().filter_map(|n| owner.get(n.as_str()).copied()).filter(|&j| j != i).collect()
+    }).collect();
+
+    fn visit(i: usize, named: &[Vec<usize>], seen: &mut [bool], order: &mut Vec<usize>) {
+        if std::mem::replace(&mut seen[i], true) {
+            return;
+        }
+        for &j in &named[i] {
+            visit(j, named, seen, order);
+        }
+        order.push(i);
+    }
+    let mut seen = vec![false; trees.len()];
+    let mut order = Vec::new();
+    for i in 0..trees.len() {
+        visit(i, &named, &mut seen, &mut order);
+    }
+    order
+}
+
+/// The scopes of the catch clauses of an analysed `tree`, in order.
+fn catch_scopes(tree: &Tree, out: &mut Vec<Scope>) {
+    if tree.sym == "CatchClause"
+        && let Some(stab) = &tree.stab
+    {
+        out.push(Rc::clone(stab));
+    }
+    for kid in &tree.kids {
+        catch_scopes(kid, out);
+    }
+}
+
+/// The name leaf of a declarator, below any `[]`.
+fn name_leaf(declarator: &Tree) -> Option<&Tree> {
+    match declarator.tok {
+        Some(_) => Some(declarator),
+        None => name_leaf(declarator.kids.first()?),
+    }
+}
+
+// ─── Tests ───────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jzero_lexer::Level;
+
+    const POINT: &str = "\
+public class Point {
+    int x, y;
+    public static Point origin() {
+        Point p;
+        p = new Point();
+        p.x = 0;
+        return p;
+    }
+    public static int twice(int x) {
+        return x * 2;
+    }
+}
+";
+
+    const MAIN: &str = "\
+public class Main {
+    public static void main(String argv[]) {
+        int x;
+        x = Point.twice(3);
+        System.out.println(Point.origin().x + x);
+        x = 1;
+    }
+}
+";
+
+    fn index() -> XrefIndex {
+        XrefIndex::from_sources([("Point.java", POINT), ("Main.java", MAIN)], LexOptions::default()).unwrap()
+    }
+
+    /// `(path, line)` of each location.
+    fn lines(index: &XrefIndex, locations: &[Location]) -> Vec<(String, usize)> {
+        locations.iter().map(|l| {
+            let file = index.files.iter().find(|f| f.path == l.path).unwrap();
+            (l.path.clone(), file.source[..l.span.start].lines().count().max(1))
+        }).collect()
+    }
+
+    #[test]
+    fn members_resolve_through_the_class_of_the_receiver() {
+        let index = index();
+        let x = index.declaration_at("Point.java", POINT.find("x,").unwrap()).unwrap();
+        assert_eq!((x.qualified.as_str(), x.kind.clone()), ("Point.x", SymbolKind::Field));
+        assert_eq!(lines(&index, &index.references(x)), [("Point.java".into(), 6), ("Main.java".into(), 5)]);
+
+        let twice = index.declaration_at("Main.java", MAIN.find("twice").unwrap()).unwrap();
+        assert_eq!(twice.qualified, "Point.twice");
+        assert_eq!(twice.location.span, Span::new(POINT.find("twice").unwrap(), POINT.find("twice").unwrap() + 5));
+
+        let point = index.declaration_at("Point.java", 14).unwrap();
+        assert_eq!(point.kind, SymbolKind::Class);
+        let uses = lines(&index, &index.references(point));
+        assert_eq!(uses.iter().filter(|(p, _)| p == "Point.java").count(), 3);
+        assert_eq!(uses.iter().filter(|(p, _)| p == "Main.java").count(), 2);
+    }
+
+    #[test]
+    fn variables_resolve_to_the_innermost_scope() {
+        let index = index();
+        let param = index.declaration_at("Point.java", POINT.find("x * 2").unwrap()).unwrap();
+        assert_eq!(param.qualified, "Point.twice.x");
+
+        let local = index.declaration_at("Main.java", MAIN.find("int x").unwrap() + 4).unwrap();
+        assert_eq!(local.qualified, "Main.main.x");
+        assert_eq!(lines(&index, &index.references(local)), [
+            ("Main.java".into(), 4), ("Main.java".into(), 5), ("Main.java".into(), 6),
+        ]);
+        assert!(index.declaration_at("Main.java", MAIN.find("System").unwrap()).is_none());
+    }
+
+    const SHAPE: &str = "\
+public class Shape {
+    int sides;
+    public int count() { return sides; }
+}
+";
+
+    const SQUARE: &str = "\
+public class Square extends Shape {
+    Square() { sides = 4; }
+    public int corners() { return this.sides + count(); }
+}
+";
+
+    #[test]
+    fn inherited_members_resolve_in_subclasses() {
+        let options = LexOptions::at_level(Level::Extended);
+        let index = XrefIndex::from_sources([("Square.java", SQUARE), ("Shape.java", SHAPE)], options).unwrap();
+        let sides = index.declaration_at("Shape.java", SHAPE.find("sides").unwrap()).unwrap();
+        assert_eq!(sides.qualified, "Shape.sides");
+        assert_eq!(lines(&index, &index.references(sides)), [
+            ("Square.java".into(), 2), ("Square.java".into(), 3), ("Shape.java".into(), 3),
+        ]);
+        assert!(crate::refactor::safe_delete(&index, "Shape.java", SHAPE.find("sides").unwrap()).is_err());
+
+        let count = index.declaration_at("Square.java", SQUARE.find("count").unwrap()).unwrap();
+        assert_eq!(count.qualified, "Shape.count");
+        let shape = index.declaration_at("Square.java", SQUARE.find("Shape").unwrap()).unwrap();
+        assert_eq!((shape.kind.clone(), shape.location.path.as_str()), (SymbolKind::Class, "Shape.java"));
+    }
+}
@@ -0,0 +1,392 @@
+// Generated from crates/jzero-parser/src/jzero.lalrpop by
+// jzero_parser::treesitter::tree_sitter_grammar; do not edit.
+module.exports = grammar({
+  name: 'jzero',
+
+  extras: $ => [/\s/, $.comment],
+
+  word: $ => $.identifier,
+
+  rules: {
+    source_file: $ => choice(
+      $.class_def,
+      $.interface_def,
+      seq($._import_decls, $._type_def),
+    ),
+    _type_def: $ => choice(
+      $.class_def,
+      $.interface_def,
+    ),
+    _import_decls: $ => choice(
+      $.import_decl,
+      seq($._import_decls, $.import_decl),
+    ),
+    import_decl: $ => choice(
+      seq('import', $.import_name, ';'),
+      seq('import', $.import_name, '.', '*', ';'),
+    ),
+    import_name: $ => choice(
+      $.identifier,
+      seq($.import_name, '.', $.identifier),
+    ),
+    class_def: $ => choice(
+      seq('public', 'class', $.identifier, $._class_body),
+      seq('public', 'class', $.identifier, 'extends', $.identifier, $._class_body),
+      seq('public', 'class', $.identifier, $.implements, $._class_body),
+      seq('public', 'class', $.identifier, 'extends', $.identifier, $.implements, $._class_body),
+    ),
+    implements: $ => seq('implements', $.interface_names),
+    interface_names: $ => choice(
+      $.identifier,
+      seq($.interface_names, ',', $.identifier),
+    ),
+    interface_def: $ => choice(
+      seq('public', 'interface', $.identifier, '{', $._abstract_method_decls, '}'),
+      seq('public', 'interface', $.identifier, '{', '}'),
+    ),
+    _abstract_method_decls: $ => choice(
+      $.abstract_method_decl,
+      seq($._abstract_method_decls, $.abstract_method_decl),
+    ),
+    abstract_method_decl: $ => seq(optional($._access_opt), $._method_return_val, $.method_declarator, ';'),
+    _class_body: $ => choice(
+      seq('{', $._class_body_decls, '}'),
+      seq('{', '}'),
+    ),
+    _class_body_decls: $ => choice(
+      $._class_body_decl,
+      seq($._class_body_decls, $._class_body_decl),
+    ),
+    _class_body_decl: $ => choice(
+      $.field_decl,
+      $.method_decl,
+      $.constructor_decl,
+    ),
+    field_decl: $ => choice(
+      seq(optional($._access_opt), $.type, $._var_decls, ';'),
+      seq(optional($._access_opt), 'static', 'final', $.type, $._var_decls, ';'),
+      seq(optional($._access_opt), 'static', $.type, $._var_decls, ';'),
+      seq(optional($._access_opt), 'final', $.type, $._var_decls, ';'),
+    ),
+    _access_opt: $ => $._access,
+    _access: $ => choice(
+      'public',
+      'protected',
+      'private',
+    ),
+    type: $ => choice(
+      'int',
+      'double',
+      'bool',
+      'string',
+      'char',
+      'long',
+      $.identifier,
+    ),
+    _var_decls: $ => choice(
+      $._var_decl,
+      seq($._var_decls, ',', $._var_decl),
+    ),
+    _var_decl: $ => choice(
+      $.var_declarator,
+      seq($.var_declarator, '=', $._var_initializer),
+    ),
+    array_init: $ => choice(
+      seq('{', $._array_init_elems, '}'),
+      seq('{', $._array_init_elems, ',', '}'),
+      seq('{', '}'),
+    ),
+    _array_init_elems: $ => choice(
+      $._var_initializer,
+      seq($._array_init_elems, ',', $._var_initializer),
+    ),
+    _var_initializer: $ => choice(
+      $._expr,
+      $.array_init,
+    ),
+    var_declarator: $ => choice(
+      $.identifier,
+      seq($.var_declarator, '[', ']'),
+    ),
+    _method_return_val: $ => choice(
+      $.type,
+      'void',
+    ),
+    method_decl: $ => seq($.method_header, $.block),
+    method_header: $ => choice(
+      seq($._access, 'static', $._method_return_val, $.method_declarator),
+      seq($._access, $._method_return_val, $.method_declarator),
+    ),
+    method_declarator: $ => seq($.identifier, '(', optional($._formal_parm_list_opt), ')'),
+    _formal_parm_list_opt: $ => $._formal_parm_list,
+    _formal_parm_list: $ => choice(
+      $.formal_parm,
+      seq($._formal_parm_list, ',', $.formal_parm),
+    ),
+    formal_parm: $ => seq($.type, $.var_declarator),
+    constructor_decl: $ => seq($.constructor_declarator, $.block),
+    constructor_declarator: $ => seq($.identifier, '(', optional($._formal_parm_list_opt), ')'),
+    block: $ => seq('{', optional($._block_stmts_opt), '}'),
+    _block_stmts_opt: $ => $._block_stmts,
+    _block_stmts: $ => choice(
+      $._block_stmt,
+      seq($._block_stmts, $._block_stmt),
+    ),
+    _block_stmt: $ => choice(
+      seq($.primitive_type, $._var_decls, ';'),
+      seq('final', $.type, $._var_decls, ';'),
+      seq($.inferred_var_decl, ';'),
+      seq($.identifier, $._identifier_started_stmt),
+      seq($.new_expr, ';'),
+      $._stmt,
+    ),
+    inferred_var_decl: $ => seq('var', $.identifier, '=', $._expr),
+    _identifier_started_stmt: $ => choice(
+      seq($.identifier, optional($._var_decl_rest), ';'),
+      seq('[', ']', $._var_decls, ';'),
+      $.identifier_started_expr_stmt,
+    ),
+    identifier_started_expr_stmt: $ => choice(
+      seq('(', optional($._arg_list_opt), ')', ';'),
+      seq('{', optional($._arg_list_opt), '}', ';'),
+      seq('.', $.identifier, $.dot_tail),
+      seq('[', $._expr, ']', $.assign_op, $._expr, ';'),
+      seq($.assign_op, $._expr, ';'),
+    ),
+    _var_decl_rest: $ => choice(
+      seq($._dims, $._var_init, $._more_var_decls),
+      seq($._var_init, $._more_var_decls),
+      seq($._dims, $._more_var_decls),
+      $._more_var_decls,
+      seq($._dims, $._var_init),
+      $._var_init,
+      $._dims,
+    ),
+    _var_init: $ => seq('=', $._var_initializer),
+    _more_var_decls: $ => seq(',', $._var_decls),
+    _dims: $ => choice(
+      seq('[', ']'),
+      seq($._dims, '[', ']'),
+    ),
+    dot_tail: $ => choice(
+      seq('.', $.identifier, $.dot_tail),
+      seq('(', optional($._arg_list_opt), ')', $._call_tail),
+      seq('{', optional($._arg_list_opt), '}', $._call_tail),
+      seq($.assign_op, $._expr, ';'),
+    ),
+    _call_tail: $ => choice(
+      ';',
+      seq('.', $.identifier, $.dot_tail),
+    ),
+    primitive_type: $ => choice(
+      'int',
+      'double',
+      'bool',
+      'string',
+      'char',
+      'long',
+    ),
+    _stmt: $ => choice(
+      $.block,
+      ';',
+      $.break_stmt,
+      $.return_stmt,
+      $.if_then_else_stmt,
+      $.if_then_stmt,
+      $.while_stmt,
+      $.for_stmt,
+      $.try_stmt,
+      $.throw_stmt,
+      $.switch_stmt,
+      $.this_stmt,
+    ),
+    this_stmt: $ => seq('this', '.', $.identifier, $.dot_tail),
+    if_then_stmt: $ => seq('if', '(', $._expr, ')', $.block),
+    if_then_else_stmt: $ => seq('if', '(', $._expr, ')', $.block, 'else', $._else_body),
+    _else_body: $ => choice(
+      $.block,
+      $.if_then_else_stmt,
+      $.if_then_stmt,
+    ),
+    while_stmt: $ => seq('while', '(', $._expr, ')', $._loop_body),
+    _loop_body: $ => choice(
+      $._stmt,
+      seq($.identifier, $.identifier_started_expr_stmt),
+    ),
+    for_stmt: $ => seq('for', '(', optional($._for_init), ';', optional($._expr_opt), ';', optional($._for_update), ')', $.block),
+    _for_init: $ => choice(
+      seq($.primitive_type, $._var_decls),
+      $.inferred_var_decl,
+      seq($.identifier, $.for_init_after_ident),
+    ),
+    for_init_after_ident: $ => choice(
+      seq($.identifier, optional($._var_decl_rest)),
+      seq('[', ']', $._var_decls),
+      seq('(', optional($._arg_list_opt), ')'),
+      seq('{', optional($._arg_list_opt), '}'),
+      seq('.', $.identifier, $.for_init_dot_tail),
+      seq($.assign_op, $._expr),
+    ),
+    for_init_dot_tail: $ => choice(
+      seq('.', $.identifier, $.for_init_dot_tail),
+      seq('(', optional($._arg_list_opt), ')'),
+      seq('{', optional($._arg_list_opt), '}'),
+      seq($.assign_op, $._expr),
+    ),
+    _expr_opt: $ => $._expr,
+    _for_update: $ => $._stmt_expr_list,
+    _stmt_expr_list: $ => choice(
+      $._stmt_expr,
+      seq($._stmt_expr_list, ',', $._stmt_expr),
+    ),
+    _stmt_expr: $ => choice(
+      $._access_expr,
+      seq($._access_expr, $.assign_op, $._expr),
+    ),
+    break_stmt: $ => choice(
+      seq('break', ';'),
+      seq('break', $.identifier, ';'),
+    ),
+    return_stmt: $ => seq('return', optional($._expr_opt), ';'),
+    try_stmt: $ => choice(
+      seq('try', $.block, $._catch_clauses),
+      seq('try', $.block, $._catch_clauses, 'finally', $.block),
+      seq('try', $.block, 'finally', $.block),
+    ),
+    _catch_clauses: $ => choice(
+      $.catch_clause,
+      seq($._catch_clauses, $.catch_clause),
+    ),
+    catch_clause: $ => seq('catch', '(', $.formal_parm, ')', $.block),
+    throw_stmt: $ => seq('throw', $._expr, ';'),
+    switch_stmt: $ => choice(
+      seq('switch', '(', $._expr, ')', '{', $._case_clauses, $._switch_labels, '}'),
+      seq('switch', '(', $._expr, ')', '{', $._switch_labels, '}'),
+      seq('switch', '(', $._expr, ')', '{', $._case_clauses, '}'),
+      seq('switch', '(', $._expr, ')', '{', '}'),
+    ),
+    _case_clauses: $ => choice(
+      $.case_clause,
+      seq($._case_clauses, $.case_clause),
+    ),
+    case_clause: $ => seq($._switch_labels, $._block_stmts),
+    _switch_labels: $ => choice(
+      $._switch_label,
+      seq($._switch_labels, $._switch_label),
+    ),
+    _switch_label: $ => choice(
+      seq('case', $._expr, ':'),
+      seq('default', ':'),
+    ),
+    new_expr: $ => choice(
+      seq('new', $.new_type, '[', $._expr, ']'),
+      seq('new', $.identifier, '(', optional($._arg_list_opt), ')'),
+    ),
+    new_type: $ => choice(
+      'int',
+      'double',
+      'bool',
+      'string',
+      'char',
+      'long',
+      $.identifier,
+    ),
+    _atom_expr: $ => choice(
+      $.literal,
+      $.identifier,
+      'this',
+      seq('(', $._expr, ')'),
+      $.new_expr,
+    ),
+    literal: $ => choice(
+      $.intlit,
+      $.longlit,
+      $.doublelit,
+      $.floatlit,
+      $.boollit,
+      $.stringlit,
+      $.charlit,
+      'null',
+    ),
+    _access_expr: $ => choice(
+      $._atom_expr,
+      seq($.identifier, '(', optional($._arg_list_opt), ')'),
+      seq($.identifier, '{', optional($._arg_list_opt), '}'),
+      seq($._access_expr, '.', $.identifier),
+      seq($._access_expr, '.', $.identifier, '(', optional($._arg_list_opt), ')'),
+      seq($._access_expr, '.', $.identifier, '{', optional($._arg_list_opt), '}'),
+      seq($._access_expr, '[', $._expr, ']'),
+    ),
+    _arg_list_opt: $ => $._arg_list,
+    _arg_list: $ => choice(
+      $._expr,
+      seq($._arg_list, ',', $._expr),
+    ),
+    _unary_expr: $ => choice(
+      seq('-', $._unary_expr),
+      seq('!', $._unary_expr),
+      seq('(', $.primitive_type, ')', $._unary_expr),
+      $._access_expr,
+    ),
+    _mul_expr: $ => choice(
+      $._unary_expr,
+      seq($._mul_expr, '*', $._unary_expr),
+      seq($._mul_expr, '/', $._unary_expr),
+      seq($._mul_expr, '%', $._unary_expr),
+    ),
+    _add_expr: $ => choice(
+      $._mul_expr,
+      seq($._add_expr, '+', $._mul_expr),
+      seq($._add_expr, '-', $._mul_expr),
+    ),
+    rel_op: $ => choice(
+      '<=',
+      '>=',
+      '<',
+      '>',
+    ),
+    _rel_expr: $ => choice(
+      $._add_expr,
+      seq($._rel_expr, $.rel_op, $._add_expr),
+      seq($._rel_expr, 'instanceof', $.identifier),
+    ),
+    _eq_expr: $ => choice(
+      $._rel_expr,
+      seq($._eq_expr, '==', $._rel_expr),
+      seq($._eq_expr, '!=', $._rel_expr),
+    ),
+    _cond_and_expr: $ => choice(
+      $._eq_expr,
+      seq($._cond_and_expr, '&&', $._eq_expr),
+    ),
+    _cond_or_expr: $ => choice(
+      $._cond_and_expr,
+      seq($._cond_or_expr, '||', $._cond_and_expr),
+    ),
+    _expr: $ => choice(
+      $._cond_or_expr,
+      seq($._access_expr, $.assign_op, $._expr),
+    ),
+    left_hand_side: $ => choice(
+      $.identifier,
+      seq($._access_expr, '[', $._expr, ']'),
+    ),
+    assign_op: $ => choice(
+      '=',
+      '+=',
+      '-=',
+      '*=',
+      '/=',
+      '%=',
+    ),
+    identifier: $ => /[\p{XID_Start}_]\p{XID_Continue}*/,
+    intlit: $ => /[0-9]+/,
+    longlit: $ => /[0-9]+[lL]/,
+    doublelit: $ => /[0-9]+\.[0-9]*([eE][+-]?[0-9]+)?[dD]?|[0-9]*\.[0-9]+([eE][+-]?[0-9]+)?[dD]?|[0-9]+[eE][+-]?[0-9]+[dD]?|[0-9]+[dD]/,
+    floatlit: $ => /([0-9]+\.[0-9]*([eE][+-]?[0-9]+)?|[0-9]*\.[0-9]+([eE][+-]?[0-9]+)?|[0-9]+([eE][+-]?[0-9]+)?)[fF]/,
+    stringlit: $ => token(choice(/"([^"\\\n]|\\[^\n])*"/, /"""[ \t\f]*\r?\n([^"\\]|\\(.|\n)|"([^"\\]|\\(.|\n))|""([^"\\]|\\(.|\n)))*"""/)),
+    charlit: $ => /'([^'\\\n]|\\[^\n])'/,
+    boollit: $ => choice('true', 'false'),
+    comment: $ => token(choice(seq('//', /[^\n]*/), seq('/*', /[^*]*\*+([^/*][^*]*\*+)*/, '/'))),
+  },
+});